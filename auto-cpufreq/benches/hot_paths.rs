@@ -0,0 +1,86 @@
+// benches/hot_paths.rs
+//
+// Benchmarks of what the daemon does every cycle, to check that refactors
+// meant to make the cycle cheaper actually do: /proc/stat sampling, the
+// governor and turbo decisions, sysfs writes and the stats formatting.
+// `cargo bench` runs them; `auto-cpufreq --bench-cycle` times whole cycles on
+// real hardware instead.
+
+use std::fs;
+use std::hint::black_box;
+
+use auto_cpufreq::battery::write_attribute;
+use auto_cpufreq::core::{get_appropriate_governor, turbo_decision};
+use auto_cpufreq::modules::system_info::SystemInfo;
+use auto_cpufreq::modules::system_monitor::format_stats;
+use auto_cpufreq::procfs::{parse_stat, usage_between};
+use auto_cpufreq::profiles::Profile;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const CORES: usize = 16;
+
+/// /proc/stat of a `CORES`-core machine, `offset` jiffies further along
+fn proc_stat(offset: u64) -> String {
+    let mut stat = String::new();
+    let line = |name: &str, scale: u64| {
+        let (user, system, idle) = (81_234 * scale + offset, 20_345 * scale + offset / 4, 912_345 * scale + offset * 2);
+        format!("{} {} 312 {} {} 1203 0 611 0 0 0\n", name, user, system, idle)
+    };
+    stat.push_str(&line("cpu", CORES as u64));
+    for core in 0..CORES {
+        stat.push_str(&line(&format!("cpu{}", core), 1));
+    }
+    stat.push_str("intr 123456789 0 0\nctxt 987654321\nbtime 1717000000\nprocesses 42000\n");
+    stat
+}
+
+fn sampling(c: &mut Criterion) {
+    let (prev, cur) = (proc_stat(0), proc_stat(500));
+    c.bench_function("parse /proc/stat", |b| b.iter(|| parse_stat(black_box(&cur))));
+
+    let (prev, cur) = (parse_stat(&prev).unwrap(), parse_stat(&cur).unwrap());
+    c.bench_function("usage between readings", |b| b.iter(|| usage_between(black_box(&prev), black_box(&cur))));
+}
+
+fn decisions(c: &mut Criterion) {
+    let profile = Profile { name: "balanced".to_string(), ..Profile::default() };
+    c.bench_function("governor decision", |b| {
+        b.iter(|| get_appropriate_governor(black_box(false), 12.0, 0.4, Some(&profile), None))
+    });
+    c.bench_function("turbo decision", |b| {
+        b.iter(|| turbo_decision(black_box(80.0), Some(62.0), true, Some(&profile), None))
+    });
+}
+
+fn sysfs_writes(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!("acf-bench-{}", std::process::id()));
+    let paths: Vec<_> = (0..CORES)
+        .map(|core| {
+            let cpufreq = dir.join(format!("cpu{}/cpufreq", core));
+            fs::create_dir_all(&cpufreq).unwrap();
+            let path = cpufreq.join("scaling_governor");
+            fs::write(&path, "schedutil\n").unwrap();
+            path
+        })
+        .collect();
+
+    // What a governor or EPP change costs: one write per core
+    c.bench_function("sysfs write per core", |b| {
+        b.iter(|| {
+            for path in &paths {
+                write_attribute(path, black_box("powersave")).unwrap();
+            }
+        })
+    });
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn stats_formatting(c: &mut Criterion) {
+    // Whatever this machine reports; the formatting doesn't depend on it
+    let report = SystemInfo::new().generate_daemon_report();
+    c.bench_function("format stats", |b| b.iter(|| format_stats(black_box(&report), "2024-05-01 12:00:00", None)));
+    c.bench_function("stats as JSON", |b| b.iter(|| serde_json::to_string(black_box(&report)).unwrap()));
+}
+
+criterion_group!(benches, sampling, decisions, sysfs_writes, stats_formatting);
+criterion_main!(benches);
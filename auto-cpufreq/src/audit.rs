@@ -0,0 +1,195 @@
+// src/audit.rs
+//
+// Audit log of the daemon's decisions: one JSON line per governor, turbo or
+// EPP change with what led to it (power source, load, profile, app, override)
+// and the readings at the time (usage, load, temperature, charger), trimmed to
+// the last MAX_ENTRIES. `auto-cpufreq --why` explains the last few and the
+// GUI shows the tail as a timeline, so the daemon's behaviour can be followed
+// without reading the journal.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::event_code::EventCode;
+
+pub const AUDIT_LOG: &str = "/var/lib/auto-cpufreq/decisions.jsonl";
+const MAX_ENTRIES: usize = 500;
+
+/// What the daemon read when it made a decision
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Inputs {
+    pub cpu_usage: f32,
+    pub load: f32,
+    /// Average core temperature, `None` without sensors
+    pub temperature: Option<f32>,
+    pub charging: bool,
+}
+
+impl Inputs {
+    /// "usage 3.1%, load 0.42, 48 °C, on battery"
+    pub fn describe(&self) -> String {
+        let temperature = self.temperature.map(|t| format!("{:.0} °C", t)).unwrap_or_else(|| "-- °C".to_string());
+        let power = if self.charging { "on AC" } else { "on battery" };
+        format!("usage {:.1}%, load {:.2}, {}, {}", self.cpu_usage, self.load, temperature, power)
+    }
+}
+
+fn governor_setting() -> String {
+    "governor".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decision {
+    /// Unix time of the change
+    pub time: i64,
+    /// "governor", "turbo" or "epp"; entries from before turbo and EPP were
+    /// recorded have no setting and are governor changes
+    #[serde(default = "governor_setting")]
+    pub setting: String,
+    /// The new value, e.g. "powersave", "on" or "balance_power"
+    #[serde(alias = "governor")]
+    pub value: String,
+    /// Why, e.g. ["battery", "idle"] or ["make running"]
+    pub reasons: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Inputs>,
+    /// Event code of the change, missing in entries from older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<EventCode>,
+}
+
+fn local_time(time: i64, format: &str) -> String {
+    DateTime::from_timestamp(time, 0)
+        .map(|t| t.with_timezone(&Local).format(format).to_string())
+        .unwrap_or_else(|| "--:--".to_string())
+}
+
+impl Decision {
+    pub fn new(setting: &str, value: &str, reasons: Vec<String>, inputs: Inputs) -> Self {
+        Self {
+            time: Local::now().timestamp(),
+            setting: setting.to_string(),
+            value: value.to_string(),
+            reasons,
+            inputs: Some(inputs),
+            code: EventCode::for_setting(setting),
+        }
+    }
+
+    /// "14:02 → powersave (battery, idle)", "14:05 → turbo on (high load)"
+    pub fn summary(&self) -> String {
+        let time = local_time(self.time, "%H:%M");
+        let change = match self.setting.as_str() {
+            "governor" => self.value.clone(),
+            setting => format!("{} {}", setting, self.value),
+        };
+        if self.reasons.is_empty() {
+            format!("{} → {}", time, change)
+        } else {
+            format!("{} → {} ({})", time, change, self.reasons.join(", "))
+        }
+    }
+
+    /// `--why`: the change, its reasons and the readings behind it
+    pub fn explain(&self) -> String {
+        let mut text = format!("{}  {} → {}", local_time(self.time, "%Y-%m-%d %H:%M:%S"), self.setting, self.value);
+        if let Some(code) = self.code {
+            text.push_str(&format!("  [{}]", code));
+        }
+        if !self.reasons.is_empty() {
+            text.push_str(&format!("\n    because: {}", self.reasons.join(", ")));
+        }
+        if let Some(inputs) = self.inputs {
+            text.push_str(&format!("\n    readings: {}", inputs.describe()));
+        }
+        text
+    }
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(AUDIT_LOG)
+    }
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every readable entry, oldest first; lines that don't parse are skipped
+    fn entries(&self) -> Vec<Decision> {
+        fs::read_to_string(&self.path)
+            .map(|content| content.lines().filter_map(|l| serde_json::from_str(l).ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn record(&self, decision: &Decision) -> Result<()> {
+        let mut entries = self.entries();
+        entries.push(decision.clone());
+        let skip = entries.len().saturating_sub(MAX_ENTRIES);
+
+        let mut content = String::new();
+        for entry in &entries[skip..] {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, content).with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// The last `count` decisions, newest first
+    pub fn recent(&self, count: usize) -> Vec<Decision> {
+        self.entries().into_iter().rev().take(count).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log() {
+        let dir = std::env::temp_dir().join(format!("acf-audit-{}", std::process::id()));
+        let log = AuditLog::new(dir.join("decisions.jsonl"));
+        assert!(log.recent(20).is_empty());
+
+        for i in 0..MAX_ENTRIES + 3 {
+            let decision = Decision { time: i as i64, setting: "governor".into(), value: "powersave".into(), reasons: vec![], inputs: None, code: None };
+            log.record(&decision).unwrap();
+        }
+        let recent = log.recent(20);
+        assert_eq!(recent.len(), 20);
+        assert_eq!(recent[0].time, (MAX_ENTRIES + 2) as i64);
+        assert_eq!(log.recent(usize::MAX).len(), MAX_ENTRIES);
+
+        let inputs = Inputs { cpu_usage: 62.5, load: 3.2, temperature: Some(71.4), charging: true };
+        let decision = Decision { time: 0, reasons: vec!["make running".into()], ..Decision::new("governor", "performance", vec![], inputs) };
+        assert!(decision.summary().ends_with(" → performance (make running)"));
+        assert!(decision.explain().ends_with("governor → performance  [ACF-001]\n    because: make running\n    readings: usage 62.5%, load 3.20, 71 °C, on AC"));
+        let turbo = Decision { setting: "turbo".into(), value: "off".into(), reasons: vec!["hot".into()], ..decision };
+        assert!(turbo.summary().ends_with(" → turbo off (hot)"));
+
+        // Entries written before turbo/EPP decisions were recorded
+        let old: Decision = serde_json::from_str(r#"{"time":0,"governor":"powersave","reasons":["battery"]}"#).unwrap();
+        assert_eq!((old.setting.as_str(), old.value.as_str(), old.inputs), ("governor", "powersave", None));
+        assert_eq!(old.code, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
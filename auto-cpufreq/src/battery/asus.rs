@@ -1,34 +1,123 @@
+// src/battery/asus.rs
+//
+// ASUS laptops (asus_wmi / asus-nb-wmi): charge thresholds on the battery,
+// and the fan/thermal policy of the embedded controller, which follows the
+// active power profile (see `apply_thermal_policy`).
+
 use std::fs;
 use std::path::{Path, PathBuf};
-use anyhow::Result;
+use anyhow::{Result, bail, Context};
+use tracing::{error, info, warn};
 
 use super::{BatteryManager, get_batteries};
-use crate::config::Config;
+use crate::config::{CONFIG, Config, Snapshot};
+use crate::event_code::EventCode;
+use crate::profiles::Profile;
 
 const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply/";
+const THERMAL_POLICY: &str = "/sys/devices/platform/asus-nb-wmi/throttle_thermal_policy";
+
+/// throttle_thermal_policy values: default fan curve, overboost, silent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThermalPolicy {
+    Balanced,
+    Performance,
+    Silent,
+}
+
+impl ThermalPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "balanced" | "default" | "0" => Some(Self::Balanced),
+            "performance" | "overboost" | "turbo" | "1" => Some(Self::Performance),
+            "silent" | "quiet" | "2" => Some(Self::Silent),
+            _ => None,
+        }
+    }
+
+    /// Policy matching an ACPI platform profile name
+    pub fn for_platform_profile(profile: &str) -> Option<Self> {
+        match profile {
+            "low-power" | "quiet" | "cool" => Some(Self::Silent),
+            "balanced" => Some(Self::Balanced),
+            "performance" | "balanced-performance" => Some(Self::Performance),
+            _ => None,
+        }
+    }
+
+    fn value(&self) -> u8 {
+        match self {
+            Self::Balanced => 0,
+            Self::Performance => 1,
+            Self::Silent => 2,
+        }
+    }
+}
+
+pub fn thermal_policy_supported() -> bool {
+    Path::new(THERMAL_POLICY).exists()
+}
+
+/// `asus_thermal_policy` from `section` when set, otherwise the policy that
+/// matches the platform profile of the active power profile or `section`
+fn wanted_policy(config: &Snapshot, section: &str, profile: Option<&Profile>) -> Result<Option<ThermalPolicy>> {
+    if let Some(value) = config.get(section, "asus_thermal_policy") {
+        return match ThermalPolicy::parse(value) {
+            Some(policy) => Ok(Some(policy)),
+            None => bail!("Invalid asus_thermal_policy in [{}]: {} (expected balanced, performance or silent)", section, value),
+        };
+    }
+
+    let platform = profile
+        .and_then(|p| p.platform_profile.as_deref())
+        .or_else(|| config.get(section, "platform_profile"));
+    Ok(platform.and_then(|p| ThermalPolicy::for_platform_profile(p.trim())))
+}
+
+/// Called by `set_autofreq`: keeps the EC fan/thermal policy in step with the
+/// power profile. Nothing is changed without the sysfs node or when neither
+/// `asus_thermal_policy` nor a platform profile says what to use.
+pub fn apply_thermal_policy(is_charging: bool, profile: Option<&Profile>) -> Result<()> {
+    if !thermal_policy_supported() {
+        return Ok(());
+    }
+
+    let section = if is_charging { "charger" } else { "battery" };
+    let Some(policy) = wanted_policy(&CONFIG.snapshot(), section, profile)? else {
+        return Ok(());
+    };
+
+    let current = fs::read_to_string(THERMAL_POLICY).ok().and_then(|v| v.trim().parse::<u8>().ok());
+    if current != Some(policy.value()) {
+        info!("Setting ASUS thermal policy: {:?}", policy);
+        super::write_attribute(Path::new(THERMAL_POLICY), policy.value())
+            .with_context(|| format!("Failed to write {}", THERMAL_POLICY))?;
+    }
+
+    Ok(())
+}
 
 pub struct AsusManager;
 
 impl BatteryManager for AsusManager {
     fn setup(&self, config: &Config) -> Result<()> {
         // Check if thresholds are enabled
-        if !config.get_bool("battery", "enable_thresholds").unwrap_or(false) {
+        if !super::profiles::thresholds_enabled(config) {
             return Ok(());
         }
 
         if !Path::new(POWER_SUPPLY_DIR).exists() {
-            println!("WARNING {} does NOT exist", POWER_SUPPLY_DIR);
+            warn!(code = %EventCode::BatterySetupFailed, "{} does NOT exist", POWER_SUPPLY_DIR);
             return Ok(());
         }
 
         let batteries = get_batteries()?;
         
         for bat in batteries {
-            let start_threshold = get_threshold_value(config, "start");
-            let stop_threshold = get_threshold_value(config, "stop");
+            let start_threshold = get_threshold_value(config, &bat, "start");
+            let stop_threshold = get_threshold_value(config, &bat, "stop");
             
-            set_battery(start_threshold, ThresholdMode::Start.as_str(), &bat)?;
-            set_battery(stop_threshold, ThresholdMode::Stop.as_str(), &bat)?;
+            super::set_thresholds(&bat, start_threshold, stop_threshold)?;
         }
         
         Ok(())
@@ -85,40 +174,8 @@ impl ThresholdMode {
     }
 }
 
-fn get_threshold_value(config: &Config, mode: &str) -> u8 {
-    config.get_threshold(mode).unwrap_or_else(|_| {
-        if mode == "start" { 0 } else { 100 }
-    })
-}
-
-fn set_battery(value: u8, mode: &str, battery: &str) -> Result<()> {
-    let file_path = PathBuf::from(format!(
-        "{}{}/charge_{}_threshold",
-        POWER_SUPPLY_DIR, battery, mode
-    ));
-
-    if !file_path.exists() {
-        println!("WARNING: {} does NOT exist", file_path.display());
-        return Ok(());
-    }
-
-    match std::process::Command::new("sh")
-        .arg("-c")
-        .arg(format!("echo {} | tee {}", value, file_path.display()))
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                println!("WARNING: Failed to set {} threshold for {}", mode, battery);
-                println!("  stderr: {}", String::from_utf8_lossy(&output.stderr));
-            }
-        }
-        Err(e) => {
-            println!("WARNING: Command failed for {} threshold: {}", mode, e);
-        }
-    }
-
-    Ok(())
+fn get_threshold_value(config: &Config, battery: &str, mode: &str) -> u8 {
+    super::profiles::threshold(config, battery, mode)
 }
 
 fn print_battery_threshold(battery: &str, mode: ThresholdMode) {
@@ -128,12 +185,12 @@ fn print_battery_threshold(battery: &str, mode: ThresholdMode) {
     if primary.exists() {
         match fs::read_to_string(&primary) {
             Ok(val) => println!("{} {} threshold = {}", battery, mode.as_str(), val.trim()),
-            Err(e) => println!("ERROR: failed to read battery {} thresholds: {}", battery, e),
+            Err(e) => error!(code = %EventCode::ThresholdReadFailed, "failed to read battery {} thresholds: {}", battery, e),
         }
     } else if fallback.exists() {
         match fs::read_to_string(&fallback) {
             Ok(val) => println!("{} {} threshold = {}", battery, mode.as_str(), val.trim()),
-            Err(e) => println!("ERROR: failed to read battery {} thresholds: {}", battery, e),
+            Err(e) => error!(code = %EventCode::ThresholdReadFailed, "failed to read battery {} thresholds: {}", battery, e),
         }
     } else {
         println!("{} {} threshold: file not found", battery, mode.as_str());
@@ -144,6 +201,24 @@ fn print_battery_threshold(battery: &str, mode: ThresholdMode) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_thermal_policy_follows_profile() {
+        let config = Snapshot::from_str_ini(
+            "[charger]\nplatform_profile = performance\n[battery]\nasus_thermal_policy = silent\n",
+        )
+        .unwrap();
+        let quiet = Profile { platform_profile: Some("low-power".to_string()), ..Profile::default() };
+
+        assert_eq!(wanted_policy(&config, "charger", None).unwrap(), Some(ThermalPolicy::Performance));
+        assert_eq!(wanted_policy(&config, "charger", Some(&quiet)).unwrap(), Some(ThermalPolicy::Silent));
+        assert_eq!(wanted_policy(&config, "battery", None).unwrap(), Some(ThermalPolicy::Silent));
+
+        let config = Snapshot::from_str_ini("[battery]\nasus_thermal_policy = loud\n").unwrap();
+        assert!(wanted_policy(&config, "battery", None).is_err());
+        assert_eq!(wanted_policy(&config, "charger", None).unwrap(), None);
+        assert_eq!(ThermalPolicy::parse("overboost"), Some(ThermalPolicy::Performance));
+    }
+
     #[test]
     fn test_threshold_mode_strings() {
         assert_eq!(ThresholdMode::Start.as_str(), "start");
@@ -0,0 +1,261 @@
+// src/battery/calibration.rs
+//
+// Battery calibration (--calibrate-battery): open the charging thresholds up
+// to 0-100, discharge down to a floor (with `force-discharge` where the driver
+// supports it, otherwise on battery power), charge to full, then put the
+// configured thresholds back. Progress is kept in the state store, so an
+// interrupted run continues where it stopped when the command is run again.
+// The thresholds are only held open while the command runs: when it stops,
+// however it stops, charging goes back to the configured thresholds.
+
+use std::fs;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::{charge_behaviour, PowerSupplies};
+use crate::config::CONFIG;
+use crate::event_code::EventCode;
+use crate::events::ShutdownSignal;
+use crate::state::StateStore;
+
+pub const DEFAULT_FLOOR: u8 = 5;
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Discharge,
+    Charge,
+}
+
+/// A calibration in progress, as stored in the state file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub phase: Phase,
+    /// Level (%) to discharge to
+    pub floor: u8,
+    /// Unix time the calibration was started
+    pub started: i64,
+    /// Process running the calibration, 0 while nothing is
+    #[serde(default)]
+    pub pid: u32,
+}
+
+impl Calibration {
+    /// Whether `--calibrate-battery` is still running it. The executable is
+    /// compared too, in case the pid was reused after a crash or reboot.
+    pub fn running(&self) -> bool {
+        self.pid != 0
+            && fs::read_link(format!("/proc/{}/exe", self.pid))
+                .is_ok_and(|exe| std::env::current_exe().is_ok_and(|current| current == exe))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Wait,
+    StartCharging,
+    Finish,
+}
+
+/// What to do given the battery level and whether it reports full
+fn next_step(calibration: &Calibration, level: u8, full: bool) -> Step {
+    match calibration.phase {
+        Phase::Discharge if level <= calibration.floor => Step::StartCharging,
+        Phase::Charge if full || level >= 100 => Step::Finish,
+        _ => Step::Wait,
+    }
+}
+
+/// The calibration in progress, if any
+pub fn active() -> Option<Calibration> {
+    StateStore::default().load().calibration
+}
+
+fn save(calibration: Option<Calibration>) -> Result<()> {
+    StateStore::default().update(|s| s.calibration = calibration)?;
+    Ok(())
+}
+
+fn set_behaviour(mode: &str) -> bool {
+    !charge_behaviour::batteries().is_empty() && charge_behaviour::set(mode).is_ok()
+}
+
+fn set_pid(pid: u32) -> Result<()> {
+    StateStore::default().update(|s| {
+        if let Some(calibration) = &mut s.calibration {
+            calibration.pid = pid;
+        }
+    })?;
+    Ok(())
+}
+
+/// Back to normal charging and the configured thresholds, keeping the
+/// calibration stored so it can be resumed
+fn release() -> Result<()> {
+    set_pid(0)?;
+    set_behaviour("auto");
+    super::battery_setup(&CONFIG)
+}
+
+/// Releases the battery when `run` returns early, fails or is interrupted
+struct Release {
+    armed: bool,
+}
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        if self.armed {
+            if let Err(e) = release() {
+                warn!(code = %EventCode::ThresholdWriteFailed, "Failed to restore the charging thresholds: {}", e);
+            }
+        }
+    }
+}
+
+/// Called at daemon start: a calibration whose process died without cleaning
+/// up (killed, or the machine lost power) may have left the battery
+/// force-discharging. It stays stored for `--calibrate-battery` to resume.
+pub fn recover_stale() -> Result<()> {
+    let Some(calibration) = active() else {
+        return Ok(());
+    };
+    if calibration.pid == 0 || calibration.running() {
+        return Ok(());
+    }
+
+    info!("Battery calibration was interrupted, back to the configured charging thresholds");
+    set_pid(0)?;
+    set_behaviour("auto");
+    Ok(())
+}
+
+/// Start a calibration, or continue the one in progress (its floor wins)
+pub fn run(floor: u8) -> Result<()> {
+    if floor >= 100 {
+        bail!("Calibration floor must be below 100%");
+    }
+    if PowerSupplies::scan().batteries.is_empty() {
+        bail!("No battery found");
+    }
+
+    let shutdown = ShutdownSignal::install()?;
+    let pid = std::process::id();
+
+    let mut calibration = match active() {
+        Some(calibration) if calibration.running() => {
+            bail!("A battery calibration is already running (pid {})", calibration.pid);
+        }
+        Some(calibration) => {
+            println!("Resuming battery calibration ({:?} phase)", calibration.phase);
+            Calibration { pid, ..calibration }
+        }
+        None => {
+            println!("Starting battery calibration: discharge to {}%, then charge to full", floor);
+            Calibration {
+                phase: Phase::Discharge,
+                floor,
+                started: chrono::Local::now().timestamp(),
+                pid,
+            }
+        }
+    };
+    save(Some(calibration.clone()))?;
+    let mut guard = Release { armed: true };
+
+    // The running calibration makes every backend write 0/100
+    super::battery_setup(&CONFIG)?;
+
+    if calibration.phase == Phase::Discharge && !set_behaviour("force-discharge") {
+        println!("The battery driver can't force a discharge, unplug the charger to continue");
+    }
+
+    loop {
+        let supplies = PowerSupplies::scan();
+        let Some(level) = supplies.combined_level() else {
+            println!("[{}] Battery level unavailable, checking again", chrono::Local::now().format("%H:%M"));
+            if shutdown.wait(POLL_INTERVAL) {
+                break;
+            }
+            continue;
+        };
+        let full = supplies.batteries.iter().all(|b| b.status.as_deref() == Some("Full"));
+
+        match next_step(&calibration, level, full) {
+            Step::Wait => {
+                let target = match calibration.phase {
+                    Phase::Discharge => format!("discharging to {}%", calibration.floor),
+                    Phase::Charge => "charging to full".to_string(),
+                };
+                println!("[{}] Battery at {}%, {}", chrono::Local::now().format("%H:%M"), level, target);
+                if shutdown.wait(POLL_INTERVAL) {
+                    break;
+                }
+            }
+            Step::StartCharging => {
+                calibration.phase = Phase::Charge;
+                save(Some(calibration.clone()))?;
+                set_behaviour("auto");
+                if supplies.ac_online != Some(true) {
+                    println!("Reached {}%, plug in the charger to continue", level);
+                } else {
+                    println!("Reached {}%, charging to full", level);
+                }
+            }
+            Step::Finish => {
+                guard.armed = false;
+                return finish();
+            }
+        }
+    }
+
+    println!("\nBattery calibration interrupted, charging thresholds restored; run the command again to resume");
+    Ok(())
+}
+
+/// End the calibration (or abandon it) and restore the configured thresholds
+pub fn finish() -> Result<()> {
+    let was_active = active().is_some();
+    save(None)?;
+    set_behaviour("auto");
+    super::battery_setup(&CONFIG)?;
+
+    if was_active {
+        println!("Battery calibration finished, charging thresholds restored");
+    } else {
+        println!("No battery calibration in progress");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration_steps() {
+        let mut calibration = Calibration { phase: Phase::Discharge, floor: 5, started: 0, pid: 0 };
+        assert_eq!(next_step(&calibration, 80, false), Step::Wait);
+        assert_eq!(next_step(&calibration, 100, true), Step::Wait);
+        assert_eq!(next_step(&calibration, 5, false), Step::StartCharging);
+
+        calibration.phase = Phase::Charge;
+        assert_eq!(next_step(&calibration, 3, false), Step::Wait);
+        assert_eq!(next_step(&calibration, 97, true), Step::Finish);
+        assert_eq!(next_step(&calibration, 100, false), Step::Finish);
+
+        let json = serde_json::to_string(&calibration).unwrap();
+        assert_eq!(json, r#"{"phase":"charge","floor":5,"started":0,"pid":0}"#);
+
+        // Written before the pid was stored
+        let old: Calibration = serde_json::from_str(r#"{"phase":"discharge","floor":5,"started":0}"#).unwrap();
+        assert!(!old.running());
+        calibration.pid = std::process::id();
+        assert!(calibration.running());
+        calibration.pid = u32::MAX;
+        assert!(!calibration.running());
+    }
+}
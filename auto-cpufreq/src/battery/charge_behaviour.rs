@@ -0,0 +1,83 @@
+// src/battery/charge_behaviour.rs
+//
+// The power_supply `charge_behaviour` attribute (thinkpad_acpi, cros_charge_control,
+// ...): `inhibit-charge` keeps the battery level while on AC, `force-discharge`
+// runs from the battery with the charger plugged in, e.g. to calibrate it or
+// bring it down to a storage level. Set with `--charge-behaviour` or from the
+// GUI; not persisted, the firmware goes back to `auto` on its own when
+// unplugged or rebooted on most machines.
+
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, bail};
+
+use super::{batteries_with, parse_choices, select_choice, POWER_SUPPLY_DIR};
+
+const ATTRIBUTE: &str = "charge_behaviour";
+
+/// Modes as named by the kernel
+pub const MODES: &[&str] = &["auto", "inhibit-charge", "force-discharge"];
+
+/// A mode name, with "reset" standing for `auto` like for --profile
+pub fn parse(value: &str) -> Option<&'static str> {
+    match value.trim().to_lowercase().as_str() {
+        "reset" => Some("auto"),
+        value => MODES.iter().copied().find(|mode| *mode == value),
+    }
+}
+
+/// Batteries whose driver supports charge behaviours
+pub fn batteries() -> Vec<String> {
+    batteries_with(Path::new(POWER_SUPPLY_DIR), ATTRIBUTE)
+}
+
+/// Selected mode of the first supporting battery
+pub fn current() -> Option<String> {
+    let battery = batteries().into_iter().next()?;
+    let content = fs::read_to_string(Path::new(POWER_SUPPLY_DIR).join(battery).join(ATTRIBUTE)).ok()?;
+    parse_choices(&content).1
+}
+
+fn set_in(dir: &Path, mode: &str) -> Result<Vec<String>> {
+    let batteries = batteries_with(dir, ATTRIBUTE);
+    if batteries.is_empty() {
+        bail!("No battery supports charge_behaviour on this machine");
+    }
+
+    for battery in &batteries {
+        select_choice(&dir.join(battery).join(ATTRIBUTE), mode)?;
+    }
+    Ok(batteries)
+}
+
+/// Apply `mode` to every supporting battery, returning their names
+pub fn set(mode: &str) -> Result<Vec<String>> {
+    set_in(Path::new(POWER_SUPPLY_DIR), mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_charge_behaviour() {
+        assert_eq!(parse("reset"), Some("auto"));
+        assert_eq!(parse("Force-Discharge"), Some("force-discharge"));
+        assert_eq!(parse("discharge"), None);
+
+        let dir = std::env::temp_dir().join(format!("charge_behaviour_test_{}", std::process::id()));
+        assert!(set_in(&dir, "auto").is_err());
+
+        for battery in ["BAT0", "BAT1"] {
+            fs::create_dir_all(dir.join(battery)).unwrap();
+            fs::write(dir.join(battery).join(ATTRIBUTE), "[auto] inhibit-charge force-discharge\n").unwrap();
+        }
+        assert_eq!(set_in(&dir, "force-discharge").unwrap(), vec!["BAT0", "BAT1"]);
+        assert_eq!(fs::read_to_string(dir.join("BAT1").join(ATTRIBUTE)).unwrap(), "force-discharge");
+
+        fs::write(dir.join("BAT0").join(ATTRIBUTE), "[auto] inhibit-charge\n").unwrap();
+        assert!(set_in(&dir, "force-discharge").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
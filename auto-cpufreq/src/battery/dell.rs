@@ -0,0 +1,133 @@
+// src/battery/dell.rs
+//
+// Dell laptops (dell_laptop / dell-smbios). Besides the generic
+// charge_control_*_threshold attributes, which the firmware only honours in
+// its Custom mode, the driver exposes the SMBIOS charge mode as `charge_types`
+// on the battery, e.g. "Trickle [Standard] Adaptive Fast Custom".
+
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, bail};
+use tracing::warn;
+
+use super::{BatteryManager, batteries_with, generic, parse_choices, POWER_SUPPLY_DIR};
+use crate::config::Config;
+use crate::event_code::EventCode;
+
+const CHARGE_TYPES: &str = "charge_types";
+
+/// `[battery] dell_charge_mode` values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChargeMode {
+    Standard,
+    Adaptive,
+    Express,
+    PrimarilyAc,
+    Custom,
+}
+
+impl ChargeMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "standard" => Some(Self::Standard),
+            "adaptive" => Some(Self::Adaptive),
+            "express" | "fast" => Some(Self::Express),
+            "primarily_ac" | "trickle" => Some(Self::PrimarilyAc),
+            "custom" => Some(Self::Custom),
+            _ => None,
+        }
+    }
+
+    /// Name used by the kernel in `charge_types`
+    fn sysfs_name(&self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Adaptive => "Adaptive",
+            Self::Express => "Fast",
+            Self::PrimarilyAc => "Trickle",
+            Self::Custom => "Custom",
+        }
+    }
+}
+
+fn set_charge_mode(dir: &Path, battery: &str, mode: ChargeMode) -> Result<()> {
+    super::select_choice(&dir.join(battery).join(CHARGE_TYPES), mode.sysfs_name())
+}
+
+pub struct DellManager;
+
+impl BatteryManager for DellManager {
+    fn setup(&self, config: &Config) -> Result<()> {
+        let dir = Path::new(POWER_SUPPLY_DIR);
+        let mode = match config.get_string("battery", "dell_charge_mode") {
+            Ok(Some(value)) => match ChargeMode::parse(&value) {
+                Some(mode) => Some(mode),
+                None => bail!("Invalid dell_charge_mode in [battery]: {}", value),
+            },
+            _ => None,
+        };
+
+        // Writing the thresholds switches the firmware to Custom, so they're
+        // only applied when no other mode was asked for
+        let mut thresholds = Ok(());
+        if matches!(mode, None | Some(ChargeMode::Custom)) && super::profiles::thresholds_enabled(config) {
+            thresholds = generic::set_configured_thresholds(config, &generic::supported_batteries_in(dir));
+        } else if mode.is_some() && super::profiles::thresholds_enabled(config) {
+            warn!(code = %EventCode::ConfigInvalid, "charging thresholds are ignored unless dell_charge_mode is custom");
+        }
+
+        if let Some(mode) = mode {
+            for bat in batteries_with(dir, CHARGE_TYPES) {
+                if let Err(e) = set_charge_mode(dir, &bat, mode) {
+                    warn!(code = %EventCode::ThresholdWriteFailed, "{}", e);
+                }
+            }
+        }
+
+        thresholds
+    }
+
+    fn print_thresholds(&self) -> Result<()> {
+        generic::GenericManager.print_thresholds()?;
+
+        let dir = Path::new(POWER_SUPPLY_DIR);
+        for bat in batteries_with(dir, CHARGE_TYPES) {
+            if let Ok(content) = fs::read_to_string(dir.join(&bat).join(CHARGE_TYPES)) {
+                let (_, current) = parse_choices(&content);
+                println!("{} charge mode = {}", bat, current.as_deref().unwrap_or("unknown"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_charge_types() {
+        let (available, current) = parse_choices("Trickle [Standard] Adaptive Fast Custom\n");
+        assert_eq!(available, vec!["Trickle", "Standard", "Adaptive", "Fast", "Custom"]);
+        assert_eq!(current.as_deref(), Some("Standard"));
+
+        assert_eq!(ChargeMode::parse("primarily_ac"), Some(ChargeMode::PrimarilyAc));
+        assert_eq!(ChargeMode::parse("Express").map(|m| m.sysfs_name()), Some("Fast"));
+        assert_eq!(ChargeMode::parse("turbo"), None);
+    }
+
+    #[test]
+    fn test_set_charge_mode() {
+        let dir = std::env::temp_dir().join(format!("dell_charge_mode_test_{}", std::process::id()));
+        fs::create_dir_all(dir.join("BAT0")).unwrap();
+        fs::write(dir.join("BAT0").join(CHARGE_TYPES), "Trickle [Standard] Adaptive Fast\n").unwrap();
+
+        assert_eq!(batteries_with(&dir, CHARGE_TYPES), vec!["BAT0"]);
+        set_charge_mode(&dir, "BAT0", ChargeMode::Adaptive).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("BAT0").join(CHARGE_TYPES)).unwrap(), "Adaptive");
+        assert!(set_charge_mode(&dir, "BAT0", ChargeMode::Custom).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
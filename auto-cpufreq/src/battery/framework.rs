@@ -0,0 +1,59 @@
+// src/battery/framework.rs
+//
+// Framework laptops, through the ChromeOS EC charge control driver
+// (cros_charge_control, or the framework_laptop DKMS module on older kernels).
+// The EC supports an end threshold, on newer firmware a start threshold too,
+// and `charge_behaviour` to pause charging or drain the battery on AC.
+
+use std::path::Path;
+use anyhow::{Result, bail};
+use tracing::warn;
+
+use super::{BatteryManager, charge_behaviour, generic, POWER_SUPPLY_DIR};
+use crate::config::Config;
+use crate::event_code::EventCode;
+
+pub struct FrameworkManager;
+
+/// `[battery] charge_behaviour`, `None` when not set
+fn configured_behaviour(config: &Config) -> Result<Option<&'static str>> {
+    match config.get_string("battery", "charge_behaviour") {
+        Ok(Some(value)) => match charge_behaviour::parse(&value) {
+            Some(mode) => Ok(Some(mode)),
+            None => bail!(
+                "Invalid charge_behaviour in [battery]: {} (expected {})",
+                value,
+                charge_behaviour::MODES.join(", ")
+            ),
+        },
+        _ => Ok(None),
+    }
+}
+
+impl BatteryManager for FrameworkManager {
+    fn setup(&self, config: &Config) -> Result<()> {
+        let thresholds = if super::profiles::thresholds_enabled(config) {
+            generic::set_configured_thresholds(config, &generic::supported_batteries_in(Path::new(POWER_SUPPLY_DIR)))
+        } else {
+            Ok(())
+        };
+
+        if let Some(mode) = configured_behaviour(config)? {
+            if let Err(e) = charge_behaviour::set(mode) {
+                warn!(code = %EventCode::ThresholdWriteFailed, "{}", e);
+            }
+        }
+
+        thresholds
+    }
+
+    fn print_thresholds(&self) -> Result<()> {
+        generic::GenericManager.print_thresholds()?;
+
+        if let Some(mode) = charge_behaviour::current() {
+            println!("charge behaviour = {}", mode);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+// src/battery/generic.rs
+//
+// Fallback for laptops without one of the vendor modules above. Many drivers
+// (lg-laptop, samsung-laptop, msi-ec, ...) expose the power_supply class
+// `charge_control_start_threshold` and `charge_control_end_threshold`
+// attributes directly; some only the end one.
+
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use tracing::warn;
+
+use super::{BatteryManager, SysfsError, CONTROL_ATTRS, POWER_SUPPLY_DIR};
+use crate::config::Config;
+use crate::event_code::EventCode;
+
+pub struct GenericManager;
+
+/// Batteries under `dir` with at least an end threshold
+pub(super) fn supported_batteries_in(dir: &Path) -> Vec<String> {
+    super::batteries_with(dir, CONTROL_ATTRS.stop)
+}
+
+pub fn is_supported() -> bool {
+    !supported_batteries_in(Path::new(POWER_SUPPLY_DIR)).is_empty()
+}
+
+/// Write both thresholds, or only the end one when the driver has no start
+/// threshold
+pub(super) fn apply_thresholds(dir: &Path, battery: &str, start: u8, stop: u8) -> Vec<SysfsError> {
+    if dir.join(battery).join(CONTROL_ATTRS.start).exists() {
+        super::write_thresholds(dir, battery, CONTROL_ATTRS, start, stop)
+    } else if let Err(invalid) = crate::validate::thresholds(0, stop) {
+        vec![SysfsError::Rejected(invalid)]
+    } else {
+        super::write_attribute(&dir.join(battery).join(CONTROL_ATTRS.stop), stop)
+            .err()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Apply the configured thresholds to `batteries`, carrying on past failures.
+/// Every failure but the first is logged, the first one is returned.
+pub(super) fn set_configured_thresholds(config: &Config, batteries: &[String]) -> Result<()> {
+    let dir = Path::new(POWER_SUPPLY_DIR);
+    let mut errors = batteries.iter().flat_map(|bat| {
+        let start_threshold = super::profiles::threshold(config, bat, "start");
+        let stop_threshold = super::profiles::threshold(config, bat, "stop");
+        apply_thresholds(dir, bat, start_threshold, stop_threshold)
+    });
+
+    let Some(first) = errors.next() else {
+        return Ok(());
+    };
+    for error in errors {
+        warn!(code = %EventCode::ThresholdWriteFailed, "{}", error);
+    }
+    Err(first.into())
+}
+
+impl BatteryManager for GenericManager {
+    fn setup(&self, config: &Config) -> Result<()> {
+        if !super::profiles::thresholds_enabled(config) {
+            return Ok(());
+        }
+
+        set_configured_thresholds(config, &supported_batteries_in(Path::new(POWER_SUPPLY_DIR)))
+    }
+
+    fn print_thresholds(&self) -> Result<()> {
+        let dir = Path::new(POWER_SUPPLY_DIR);
+        let batteries = supported_batteries_in(dir);
+
+        println!("\n{}\n", "-".repeat(32) + " Battery Info " + &"-".repeat(33));
+        println!("battery count = {}", batteries.len());
+
+        for bat in &batteries {
+            for (mode, attr) in [("start", CONTROL_ATTRS.start), ("stop", CONTROL_ATTRS.stop)] {
+                match fs::read_to_string(dir.join(bat).join(attr)) {
+                    Ok(val) => println!("{} {} threshold = {}", bat, mode, val.trim()),
+                    Err(_) => println!("{} {} threshold: not supported", bat, mode),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_thresholds() {
+        let dir = std::env::temp_dir().join(format!("generic_thresholds_test_{}", std::process::id()));
+        for (battery, files) in [
+            ("BAT0", &["charge_control_start_threshold", "charge_control_end_threshold"][..]),
+            ("BAT1", &["charge_control_end_threshold"][..]),
+            ("AC", &["online"][..]),
+        ] {
+            fs::create_dir_all(dir.join(battery)).unwrap();
+            for file in files {
+                fs::write(dir.join(battery).join(file), "100\n").unwrap();
+            }
+        }
+
+        assert_eq!(supported_batteries_in(&dir), vec!["BAT0", "BAT1"]);
+
+        assert!(apply_thresholds(&dir, "BAT0", 40, 80).is_empty());
+        assert_eq!(fs::read_to_string(dir.join("BAT0/charge_control_start_threshold")).unwrap(), "40");
+        assert_eq!(fs::read_to_string(dir.join("BAT0/charge_control_end_threshold")).unwrap(), "80");
+
+        // End-only drivers (LG, Samsung) don't warn about the missing start
+        assert!(apply_thresholds(&dir, "BAT1", 40, 80).is_empty());
+        assert_eq!(fs::read_to_string(dir.join("BAT1/charge_control_end_threshold")).unwrap(), "80");
+        assert!(!dir.join("BAT1/charge_control_start_threshold").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(supported_batteries_in(&dir).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+// src/battery/history.rs
+//
+// Rolling history of battery energy readings. Instantaneous power_now jumps
+// around with load, so rates and time estimates are computed from the energy
+// change over the last few minutes instead, falling back to the average of the
+// power_now readings when the battery only reports charge in coarse steps.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::sensors;
+
+/// How far back readings are kept for the average rate
+const WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Minimum time span before the energy-based rate is trusted
+const MIN_SPAN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub at: Instant,
+    /// Wh remaining
+    pub energy: Option<f32>,
+    /// W, as reported by the battery
+    pub power: Option<f32>,
+    pub charging: bool,
+}
+
+/// Estimates derived from the history
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Estimates {
+    /// Average charge/discharge rate (W) over the window
+    pub average_rate: Option<f32>,
+    pub time_to_empty: Option<Duration>,
+    pub time_to_full: Option<Duration>,
+    /// Wh drawn from the battery since the history started
+    pub session_energy: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct BatteryHistory {
+    samples: VecDeque<Sample>,
+    session_energy: f32,
+}
+
+impl BatteryHistory {
+    pub fn record(&mut self, sample: Sample) {
+        if let Some(last) = self.samples.back() {
+            // A flip between charging and discharging starts a new run
+            if last.charging != sample.charging {
+                self.samples.clear();
+            } else if let (false, Some(before), Some(now)) = (sample.charging, last.energy, sample.energy) {
+                if now < before {
+                    self.session_energy += before - now;
+                }
+            }
+        }
+
+        self.samples.push_back(sample);
+        while self
+            .samples
+            .front()
+            .is_some_and(|s| sample.at.duration_since(s.at) > WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    fn average_rate(&self) -> Option<f32> {
+        let first = self.samples.front()?;
+        let last = self.samples.back()?;
+        let span = last.at.duration_since(first.at);
+
+        if span >= MIN_SPAN {
+            if let (Some(start), Some(end)) = (first.energy, last.energy) {
+                let rate = (end - start).abs() / (span.as_secs_f32() / 3600.0);
+                if rate > 0.0 {
+                    return Some(rate);
+                }
+            }
+        }
+
+        let readings: Vec<f32> = self.samples.iter().filter_map(|s| s.power).filter(|p| *p > 0.0).collect();
+        (!readings.is_empty()).then(|| readings.iter().sum::<f32>() / readings.len() as f32)
+    }
+
+    /// `full` is the energy (Wh) at which charging stops, i.e. energy_full
+    /// scaled by the stop threshold when one is set
+    pub fn estimates(&self, full: Option<f32>) -> Estimates {
+        let average_rate = self.average_rate();
+        let last = self.samples.back();
+        let hours = |wh: f32| {
+            average_rate
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| Duration::from_secs_f32((wh / rate * 3600.0).max(0.0)))
+        };
+
+        let (time_to_empty, time_to_full) = match last {
+            Some(s) if !s.charging => (s.energy.and_then(hours), None),
+            Some(s) => (None, s.energy.zip(full).and_then(|(now, full)| hours(full - now))),
+            None => (None, None),
+        };
+
+        Estimates {
+            average_rate,
+            time_to_empty,
+            time_to_full,
+            session_energy: self.session_energy,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref BATTERY_HISTORY: Mutex<BatteryHistory> = Mutex::new(BatteryHistory::default());
+}
+
+/// Energy (Wh) from `energy_<kind>`, or `charge_<kind>` × `voltage_now` on
+/// batteries that only report charge
+pub fn read_energy(battery: &Path, kind: &str) -> Option<f32> {
+    if let Some(uwh) = sensors::read_value(&battery.join(format!("energy_{}", kind))) {
+        return Some(uwh / 1_000_000.0);
+    }
+    let uah = sensors::read_value(&battery.join(format!("charge_{}", kind)))?;
+    let uv = sensors::read_value(&battery.join("voltage_now"))?;
+    Some(uah / 1_000_000.0 * uv / 1_000_000.0)
+}
+
+/// Format an estimate as "3h 05m"
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(start: Instant, secs: u64, energy: f32, charging: bool) -> Sample {
+        Sample { at: start + Duration::from_secs(secs), energy: Some(energy), power: None, charging }
+    }
+
+    #[test]
+    fn test_discharge_estimates() {
+        let start = Instant::now();
+        let mut history = BatteryHistory::default();
+        history.record(sample(start, 0, 40.0, false));
+        history.record(sample(start, 180, 39.5, false));
+        history.record(sample(start, 360, 39.0, false));
+
+        // 1 Wh in 6 minutes = 10 W, 39 Wh left = 3.9 h
+        let estimates = history.estimates(Some(50.0));
+        assert!((estimates.average_rate.unwrap() - 10.0).abs() < 0.01);
+        assert_eq!(estimates.time_to_empty.unwrap().as_secs() / 60, 234);
+        assert_eq!(estimates.time_to_full, None);
+        assert!((estimates.session_energy - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_charge_estimates_reset_on_flip() {
+        let start = Instant::now();
+        let mut history = BatteryHistory::default();
+        history.record(sample(start, 0, 30.0, false));
+        history.record(sample(start, 60, 29.0, false));
+        history.record(sample(start, 120, 29.0, true));
+        history.record(sample(start, 480, 31.0, true));
+
+        // 2 Wh in 6 minutes = 20 W, 9 Wh to the 40 Wh stop point = 27 min
+        let estimates = history.estimates(Some(40.0));
+        assert!((estimates.average_rate.unwrap() - 20.0).abs() < 0.01);
+        assert_eq!(estimates.time_to_full.unwrap().as_secs() / 60, 27);
+        assert_eq!(estimates.time_to_empty, None);
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 5 * 60)), "3h 05m");
+    }
+}
@@ -0,0 +1,81 @@
+// src/battery/huawei.rs
+//
+// Huawei and Honor laptops (huawei_wmi). The driver takes both thresholds at
+// once, as "<start> <end>", in a single platform attribute that applies to the
+// whole battery; "0 100" turns battery protection off.
+
+use std::fs;
+use std::path::Path;
+use anyhow::Result;
+use tracing::{error, warn};
+
+use super::{BatteryManager, get_batteries};
+use crate::config::Config;
+use crate::event_code::EventCode;
+
+const THRESHOLDS_PATH: &str = "/sys/devices/platform/huawei-wmi/charge_control_thresholds";
+
+pub struct HuaweiManager;
+
+/// Parse "<start> <end>" as read back from the driver
+fn parse_thresholds(content: &str) -> Option<(u8, u8)> {
+    let mut values = content.split_whitespace().map(|v| v.parse::<u8>().ok());
+    Some((values.next()??, values.next()??))
+}
+
+fn write_thresholds(path: &Path, start: u8, stop: u8) -> std::result::Result<(), super::SysfsError> {
+    crate::validate::thresholds(start, stop).map_err(super::SysfsError::Rejected)?;
+    super::write_attribute(path, format!("{} {}", start, stop))
+}
+
+impl BatteryManager for HuaweiManager {
+    fn setup(&self, config: &Config) -> Result<()> {
+        if !super::profiles::thresholds_enabled(config) {
+            return Ok(());
+        }
+
+        // One setting for the machine; a [battery.<name>] section for the
+        // (only) battery still applies
+        let battery = get_batteries()?.into_iter().next().unwrap_or_else(|| "BAT0".to_string());
+        let start_threshold = super::profiles::threshold(config, &battery, "start");
+        let stop_threshold = super::profiles::threshold(config, &battery, "stop");
+
+        if let Err(e) = write_thresholds(Path::new(THRESHOLDS_PATH), start_threshold, stop_threshold) {
+            warn!(code = %EventCode::ThresholdWriteFailed, "{}", e);
+        }
+
+        Ok(())
+    }
+
+    fn print_thresholds(&self) -> Result<()> {
+        println!("\n{}\n", "-".repeat(32) + " Battery Info " + &"-".repeat(33));
+
+        match fs::read_to_string(THRESHOLDS_PATH).ok().as_deref().and_then(parse_thresholds) {
+            Some((start, stop)) => {
+                println!("battery start threshold = {}", start);
+                println!("battery stop threshold = {}", stop);
+            }
+            None => error!(code = %EventCode::ThresholdReadFailed, "failed to read {}", THRESHOLDS_PATH),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_huawei_thresholds() {
+        assert_eq!(parse_thresholds("40 70\n"), Some((40, 70)));
+        assert_eq!(parse_thresholds("0 100"), Some((0, 100)));
+        assert_eq!(parse_thresholds("40\n"), None);
+
+        let path = std::env::temp_dir().join(format!("huawei_thresholds_test_{}", std::process::id()));
+        fs::write(&path, "0 100\n").unwrap();
+        write_thresholds(&path, 40, 70).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "40 70");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,18 +1,39 @@
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
+use tracing::{error, info, warn};
 
 use super::{BatteryManager, get_batteries};
 use crate::config::Config;
+use crate::event_code::EventCode;
 
 const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply/";
-const CONSERVATION_MODE_FILE: &str = 
-    "/sys/bus/platform/drivers/ideapad_acpi/VPC2004:00/conservation_mode";
+const PLATFORM_DRIVERS_DIR: &str = "/sys/bus/platform/drivers/";
+
+/// Where conservation mode is switched on this machine
+#[derive(Debug, Clone, PartialEq)]
+enum Conservation {
+    /// `ideapad_*/<device>/conservation_mode`, 1 or 0. The device node is
+    /// VPC2004:00 on most models but not all of them.
+    ModeFile(PathBuf),
+    /// `charge_types` (Long_Life / Standard) on kernels that expose
+    /// conservation through the power supply class
+    PowerSupply { path: PathBuf, on: &'static str, off: &'static str },
+    /// The battery's `charge_control_end_threshold`: conservation stops
+    /// charging at CONSERVATION_LIMIT, off is a full charge
+    EndThreshold(PathBuf),
+}
+
+const POWER_SUPPLY_MODES: &[(&str, &str, &str)] = &[("charge_types", "Long_Life", "Standard")];
+
+/// Where conservation mode stops charging on most IdeaPads
+const CONSERVATION_LIMIT: u8 = 80;
 
 pub struct IdeapadLaptopManager;
 
 impl BatteryManager for IdeapadLaptopManager {
     fn setup(&self, config: &Config) -> Result<()> {
-        if !config.get_bool("battery", "enable_thresholds").unwrap_or(false) {
+        if !super::profiles::thresholds_enabled(config) {
             return Ok(());
         }
 
@@ -35,14 +56,13 @@ impl BatteryManager for IdeapadLaptopManager {
         // Only set thresholds if conservation mode is off
         if !check_conservation_mode()? {
             for bat in batteries {
-                let start_threshold = get_threshold_value(config, "start");
-                let stop_threshold = get_threshold_value(config, "stop");
+                let start_threshold = get_threshold_value(config, &bat, "start");
+                let stop_threshold = get_threshold_value(config, &bat, "stop");
                 
-                set_battery(start_threshold, "start", &bat)?;
-                set_battery(stop_threshold, "stop", &bat)?;
+                super::set_thresholds(&bat, start_threshold, stop_threshold)?;
             }
         } else {
-            println!("conservation mode is enabled unable to set thresholds");
+            warn!(code = %EventCode::ThresholdWriteFailed, "conservation mode is enabled unable to set thresholds");
         }
         
         Ok(())
@@ -62,12 +82,12 @@ impl BatteryManager for IdeapadLaptopManager {
         for bat in &batteries {
             match read_threshold(bat, "start") {
                 Ok(val) => println!("{} start threshold = {}", bat, val),
-                Err(e) => println!("ERROR: failed to read battery {} thresholds: {}", bat, e),
+                Err(e) => error!(code = %EventCode::ThresholdReadFailed, "failed to read battery {} thresholds: {}", bat, e),
             }
             
             match read_threshold(bat, "stop") {
                 Ok(val) => println!("{} stop threshold = {}", bat, val),
-                Err(e) => println!("ERROR: failed to read battery {} thresholds: {}", bat, e),
+                Err(e) => error!(code = %EventCode::ThresholdReadFailed, "failed to read battery {} thresholds: {}", bat, e),
             }
         }
         
@@ -75,91 +95,161 @@ impl BatteryManager for IdeapadLaptopManager {
     }
 }
 
-fn get_threshold_value(config: &Config, mode: &str) -> u8 {
-    config.get_threshold(mode).unwrap_or_else(|_| {
-        if mode == "start" { 0 } else { 100 }
-    })
+fn get_threshold_value(config: &Config, battery: &str, mode: &str) -> u8 {
+    super::profiles::threshold(config, battery, mode)
 }
 
-fn set_battery(value: u8, mode: &str, battery: &str) -> Result<()> {
+fn read_threshold(battery: &str, mode: &str) -> Result<String> {
     let file_path = PathBuf::from(format!(
         "{}{}/charge_{}_threshold",
         POWER_SUPPLY_DIR, battery, mode
     ));
     
-    if !file_path.exists() {
-        println!("WARNING: {} does NOT exist", file_path.display());
-        return Ok(());
+    fs::read_to_string(&file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))
+        .map(|value| value.trim().to_string())
+}
+
+/// `ideapad_*/*/conservation_mode` under the platform drivers directory
+fn mode_files_in(drivers_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(drivers_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|driver| driver.file_name().to_string_lossy().starts_with("ideapad_"))
+        .flat_map(|driver| fs::read_dir(driver.path()).into_iter().flatten().flatten())
+        .map(|device| device.path().join("conservation_mode"))
+        .filter(|file| file.exists())
+        .collect();
+    files.sort();
+    files
+}
+
+fn find_conservation(drivers_dir: &Path, power_supply_dir: &Path, batteries: &[String]) -> Option<Conservation> {
+    if let Some(file) = mode_files_in(drivers_dir).into_iter().next() {
+        return Some(Conservation::ModeFile(file));
     }
-    
-    match std::process::Command::new("sh")
-        .arg("-c")
-        .arg(format!("echo {} | tee {}", value, file_path.display()))
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                println!("WARNING: Failed to set {} threshold for {}", mode, battery);
-                println!("  stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    for bat in batteries {
+        for (attribute, on, off) in POWER_SUPPLY_MODES {
+            let path = power_supply_dir.join(bat).join(attribute);
+            let Ok(content) = fs::read_to_string(&path) else { continue };
+            let (choices, _) = super::parse_choices(&content);
+            if choices.iter().any(|c| c == on) && choices.iter().any(|c| c == off) {
+                return Some(Conservation::PowerSupply { path, on, off });
             }
         }
-        Err(e) => {
-            println!("WARNING: Command failed for {} threshold: {}", mode, e);
-        }
     }
-    
-    Ok(())
+
+    batteries
+        .iter()
+        .map(|bat| power_supply_dir.join(bat).join(super::CONTROL_ATTRS.stop))
+        .find(|path| path.exists())
+        .map(Conservation::EndThreshold)
 }
 
-fn read_threshold(battery: &str, mode: &str) -> Result<String> {
-    let file_path = PathBuf::from(format!(
-        "{}{}/charge_{}_threshold",
-        POWER_SUPPLY_DIR, battery, mode
-    ));
-    
-    std::process::Command::new("cat")
-        .arg(&file_path)
-        .output()
-        .with_context(|| format!("Failed to read threshold"))
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+fn conservation() -> Option<Conservation> {
+    let batteries = get_batteries().unwrap_or_default();
+    find_conservation(Path::new(PLATFORM_DRIVERS_DIR), Path::new(POWER_SUPPLY_DIR), &batteries)
+}
+
+impl Conservation {
+    fn set(&self, enabled: bool) -> Result<()> {
+        match self {
+            Self::ModeFile(path) => Ok(super::write_attribute(path, u8::from(enabled))?),
+            Self::PowerSupply { path, on, off } => super::select_choice(path, if enabled { on } else { off }),
+            Self::EndThreshold(path) => Ok(super::write_attribute(path, if enabled { CONSERVATION_LIMIT } else { 100 })?),
+        }
+    }
+
+    fn is_enabled(&self) -> Option<bool> {
+        match self {
+            Self::ModeFile(path) => match fs::read_to_string(path).ok()?.trim() {
+                "1" => Some(true),
+                "0" => Some(false),
+                _ => None,
+            },
+            Self::PowerSupply { path, on, .. } => {
+                let (_, selected) = super::parse_choices(&fs::read_to_string(path).ok()?);
+                Some(selected.as_deref() == Some(*on))
+            }
+            Self::EndThreshold(path) => fs::read_to_string(path).ok()?.trim().parse::<u8>().ok().map(|limit| limit < 100),
+        }
+    }
 }
 
 fn conservation_mode(value: u8) -> Result<()> {
-    match std::process::Command::new("sh")
-        .arg("-c")
-        .arg(format!("echo {} | tee {}", value, CONSERVATION_MODE_FILE))
-        .output()
-    {
-        Ok(_) => {
-            println!("conservation_mode is {}", value);
+    let result = match conservation() {
+        Some(control) => control.set(value == 1),
+        None => Err(anyhow::anyhow!("no conservation mode interface found")),
+    };
+
+    match result {
+        Ok(()) => {
+            info!("conservation_mode is {}", value);
             Ok(())
         }
         Err(e) => {
-            println!("unable to set conservation mode");
-            Err(e.into())
+            warn!(code = %EventCode::ThresholdWriteFailed, "unable to set conservation mode");
+            Err(e)
         }
     }
 }
 
 fn check_conservation_mode() -> Result<bool> {
-    match std::process::Command::new("cat")
-        .arg(CONSERVATION_MODE_FILE)
-        .output()
-    {
-        Ok(output) => {
-            let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            match value.as_str() {
-                "1" => Ok(true),
-                "0" => Ok(false),
-                _ => {
-                    println!("could not get value from conservation mode");
-                    Ok(false)
-                }
-            }
+    match conservation().map(|control| control.is_enabled()) {
+        Some(Some(enabled)) => Ok(enabled),
+        Some(None) => {
+            warn!(code = %EventCode::ThresholdReadFailed, "could not get value from conservation mode");
+            Ok(false)
         }
-        Err(_) => {
-            println!("could not get the value from conservation mode");
+        None => {
+            warn!(code = %EventCode::ThresholdReadFailed, "could not get the value from conservation mode");
             Ok(false)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_conservation() {
+        let dir = std::env::temp_dir().join(format!("ideapad_conservation_test_{}", std::process::id()));
+        let drivers = dir.join("drivers");
+        let supplies = dir.join("power_supply");
+        let batteries = vec!["BAT0".to_string()];
+        fs::create_dir_all(supplies.join("BAT0")).unwrap();
+        assert_eq!(find_conservation(&drivers, &supplies, &batteries), None);
+
+        // charge_behaviour holds the current level, it isn't conservation mode
+        fs::write(supplies.join("BAT0/charge_behaviour"), "[auto] inhibit-charge\n").unwrap();
+        assert_eq!(find_conservation(&drivers, &supplies, &batteries), None);
+
+        let end = supplies.join("BAT0/charge_control_end_threshold");
+        fs::write(&end, "100\n").unwrap();
+        let control = find_conservation(&drivers, &supplies, &batteries).unwrap();
+        assert_eq!(control, Conservation::EndThreshold(end.clone()));
+        assert_eq!(control.is_enabled(), Some(false));
+        control.set(true).unwrap();
+        assert_eq!(fs::read_to_string(&end).unwrap(), "80");
+        assert_eq!(control.is_enabled(), Some(true));
+
+        let types = supplies.join("BAT0/charge_types");
+        fs::write(&types, "Fast [Standard] Long_Life\n").unwrap();
+        assert_eq!(
+            find_conservation(&drivers, &supplies, &batteries),
+            Some(Conservation::PowerSupply { path: types, on: "Long_Life", off: "Standard" })
+        );
+
+        let device = drivers.join("ideapad_acpi/VPC2004:01");
+        fs::create_dir_all(&device).unwrap();
+        fs::write(device.join("conservation_mode"), "1\n").unwrap();
+        let control = find_conservation(&drivers, &supplies, &batteries).unwrap();
+        assert_eq!(control, Conservation::ModeFile(device.join("conservation_mode")));
+        assert_eq!(control.is_enabled(), Some(true));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
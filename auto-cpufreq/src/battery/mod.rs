@@ -1,40 +1,83 @@
 // src/battery/mod.rs
+use std::fmt;
 use std::fs;
-use std::path::Path;
-use std::process::Command;
-use anyhow::Result;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use anyhow::{Result, bail};
+use tracing::warn;
 
 pub mod asus;
+pub mod calibration;
+pub mod charge_behaviour;
+pub mod dell;
+pub mod framework;
+pub mod generic;
+pub mod history;
+pub mod huawei;
 pub mod ideapad_acpi;
 pub mod ideapad_laptop;
+pub mod profiles;
+pub mod system76;
 pub mod thinkpad;
 
 use crate::config::Config;
+use crate::event_code::EventCode;
+use crate::sensors;
 
-const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply/";
+pub(crate) const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply/";
 
-/// Detect which laptop module is loaded
+const PROC_MODULES: &str = "/proc/modules";
+
+/// Battery backends, one per vendor kernel module
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LaptopModule {
     IdeapadAcpi,
     IdeapadLaptop,
     ThinkpadAcpi,
     AsusWmi,
-    None,
+    DellLaptop,
+    HuaweiWmi,
+    System76Acpi,
+    /// cros_charge_control, or the framework_laptop DKMS module
+    Framework,
+    /// No vendor module, but the batteries expose the standard
+    /// `charge_control_*_threshold` attributes
+    Generic,
 }
 
+/// Vendor backends in the order they are set up, with the kernel modules
+/// that provide them
+const VENDOR_MODULES: &[(LaptopModule, &[&str])] = &[
+    (LaptopModule::IdeapadAcpi, &["ideapad_acpi"]),
+    (LaptopModule::IdeapadLaptop, &["ideapad_laptop"]),
+    (LaptopModule::ThinkpadAcpi, &["thinkpad_acpi"]),
+    (LaptopModule::AsusWmi, &["asus_wmi"]),
+    (LaptopModule::DellLaptop, &["dell_laptop"]),
+    (LaptopModule::HuaweiWmi, &["huawei_wmi"]),
+    (LaptopModule::System76Acpi, &["system76_acpi"]),
+    (LaptopModule::Framework, &["cros_charge_control", "framework_laptop"]),
+];
+
 impl LaptopModule {
-    pub fn detect() -> Self {
-        if is_module_loaded("ideapad_acpi") {
-            Self::IdeapadAcpi
-        } else if is_module_loaded("ideapad_laptop") {
-            Self::IdeapadLaptop
-        } else if is_module_loaded("thinkpad_acpi") {
-            Self::ThinkpadAcpi
-        } else if is_module_loaded("asus_wmi") {
-            Self::AsusWmi
+    /// Every backend that applies to this machine, empty when the battery
+    /// can't be managed. Some machines load several vendor modules (e.g.
+    /// ideapad_laptop next to asus_wmi style WMI interfaces); each of them
+    /// gets set up.
+    pub fn detect() -> Vec<Self> {
+        Self::from_loaded(&loaded_modules(), generic::is_supported)
+    }
+
+    fn from_loaded(loaded: &[String], generic_supported: impl FnOnce() -> bool) -> Vec<Self> {
+        let modules: Vec<Self> = VENDOR_MODULES
+            .iter()
+            .filter(|(_, names)| names.iter().any(|name| loaded.iter().any(|l| l == name)))
+            .map(|(module, _)| *module)
+            .collect();
+
+        if modules.is_empty() && generic_supported() {
+            vec![Self::Generic]
         } else {
-            Self::None
+            modules
         }
     }
 
@@ -44,18 +87,51 @@ impl LaptopModule {
             Self::IdeapadLaptop => "ideapad_laptop",
             Self::ThinkpadAcpi => "thinkpad_acpi",
             Self::AsusWmi => "asus_wmi",
-            Self::None => "none",
+            Self::DellLaptop => "dell_laptop",
+            Self::HuaweiWmi => "huawei_wmi",
+            Self::System76Acpi => "system76_acpi",
+            Self::Framework => "framework",
+            Self::Generic => "generic",
+        }
+    }
+
+    /// Names of `modules` for display, "none" when there are none
+    pub fn describe(modules: &[Self]) -> String {
+        if modules.is_empty() {
+            "none".to_string()
+        } else {
+            modules.iter().map(Self::name).collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    fn manager(&self) -> &'static dyn BatteryManager {
+        match self {
+            Self::IdeapadAcpi => &ideapad_acpi::IdeapadAcpiManager,
+            Self::IdeapadLaptop => &ideapad_laptop::IdeapadLaptopManager,
+            Self::ThinkpadAcpi => &thinkpad::ThinkpadManager,
+            Self::AsusWmi => &asus::AsusManager,
+            Self::DellLaptop => &dell::DellManager,
+            Self::HuaweiWmi => &huawei::HuaweiManager,
+            Self::System76Acpi => &system76::System76Manager,
+            Self::Framework => &framework::FrameworkManager,
+            Self::Generic => &generic::GenericManager,
         }
     }
 }
 
-fn is_module_loaded(module: &str) -> bool {
-    Command::new("lsmod")
-        .output()
-        .ok()
-        .and_then(|output| String::from_utf8(output.stdout).ok())
-        .map(|stdout| stdout.contains(module))
-        .unwrap_or(false)
+/// Module names from the first column of /proc/modules
+fn parse_modules(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
+fn loaded_modules() -> Vec<String> {
+    fs::read_to_string(PROC_MODULES)
+        .map(|content| parse_modules(&content))
+        .unwrap_or_default()
 }
 
 /// Get list of batteries in the system
@@ -82,54 +158,509 @@ pub fn get_batteries() -> Result<Vec<String>> {
     Ok(batteries)
 }
 
+/// One `type=Battery` power supply
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryState {
+    pub name: String,
+    pub path: PathBuf,
+    /// "Charging", "Discharging", "Full", "Not charging", ...
+    pub status: Option<String>,
+    /// Percent
+    pub capacity: Option<u8>,
+    /// Wh
+    pub energy_now: Option<f32>,
+    /// Wh
+    pub energy_full: Option<f32>,
+    /// W drawn or taken in
+    pub power: Option<f32>,
+}
+
+impl BatteryState {
+    fn read(name: String, path: PathBuf) -> Self {
+        let read = |file: &str| sensors::read_with_retry(&path.join(file)).ok().map(|s| s.trim().to_string());
+        let micro = |file: &str| sensors::read_value(&path.join(file)).map(|v| v / 1_000_000.0);
+
+        let power = micro("power_now").or_else(|| Some(micro("current_now")? * micro("voltage_now")?));
+
+        Self {
+            status: read("status"),
+            capacity: sensors::read_value(&path.join("capacity")).map(|c| c.clamp(0.0, 100.0) as u8),
+            energy_now: history::read_energy(&path, "now"),
+            energy_full: history::read_energy(&path, "full"),
+            power,
+            name,
+            path,
+        }
+    }
+
+    fn has_status(&self, status: &str) -> bool {
+        self.status.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(status))
+    }
+}
+
+/// The active entry of `usb_type`: "PD" from "C [PD] PD_PPS"
+fn parse_usb_type(content: &str) -> Option<String> {
+    let content = content.trim();
+    let active = match content.split_once('[') {
+        Some((_, rest)) => rest.split_once(']')?.0,
+        None if !content.contains(' ') => content,
+        None => return None,
+    };
+    (!active.is_empty() && active != "Unknown").then(|| active.to_string())
+}
+
+/// What a USB port of `usb_type` supplies without a PD contract
+fn nominal_watts(usb_type: &str) -> Option<f32> {
+    match usb_type {
+        "SDP" => Some(2.5),
+        "DCP" | "CDP" => Some(7.5),
+        // Type-C current at 5 V, up to 3 A
+        "C" => Some(15.0),
+        _ => None,
+    }
+}
+
+/// A `type=Mains` or `type=USB` power supply
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adapter {
+    pub name: String,
+    pub online: bool,
+    /// Active entry of `usb_type` ("PD", "C", "SDP", ...), USB supplies only
+    pub usb_type: Option<String>,
+    /// What the adapter can deliver: the negotiated `voltage_max`×`current_max`
+    /// (USB-PD), else the nominal power of its USB type. Not `voltage_now`×
+    /// `current_now`, which is what the laptop draws right now. Plain ACPI
+    /// adapters usually report neither and stay unknown.
+    pub watts: Option<f32>,
+}
+
+impl Adapter {
+    fn read(name: String, path: &Path, online: bool) -> Self {
+        let micro = |file: &str| sensors::read_value(&path.join(file)).map(|v| v / 1_000_000.0);
+        let product = |voltage: &str, current: &str| Some(micro(voltage)? * micro(current)?);
+
+        let usb_type = sensors::read_with_retry(&path.join("usb_type")).ok().and_then(|s| parse_usb_type(&s));
+        let watts = product("voltage_max", "current_max")
+            .filter(|w| *w > 0.0)
+            .or_else(|| nominal_watts(usb_type.as_deref()?));
+        Self { name, online, usb_type, watts }
+    }
+}
+
+/// AC adapters and every system battery, from one pass over the power supplies
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PowerSupplies {
+    /// `None` without a Mains supply (desktops, some tablets)
+    pub ac_online: Option<bool>,
+    pub adapters: Vec<Adapter>,
+    pub batteries: Vec<BatteryState>,
+}
+
+impl PowerSupplies {
+    pub fn scan() -> Self {
+        Self::scan_dir(Path::new(POWER_SUPPLY_DIR), &crate::core::get_power_supply_ignore_list())
+    }
+
+    fn scan_dir(dir: &Path, ignore_list: &[String]) -> Self {
+        let mut supplies = Self::default();
+
+        let mut entries: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries.flatten().collect(),
+            Err(_) => return supplies,
+        };
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if ignore_list.iter().any(|ignored| name.contains(ignored.as_str())) {
+                continue;
+            }
+
+            let path = entry.path();
+            let read = |file: &str| sensors::read_with_retry(&path.join(file)).ok().map(|s| s.trim().to_string());
+
+            match read("type").as_deref() {
+                Some("Mains") => {
+                    if let Some(online) = read("online") {
+                        supplies.ac_online = Some(supplies.ac_online == Some(true) || online == "1");
+                        supplies.adapters.push(Adapter::read(name, &path, online == "1"));
+                    }
+                }
+                // USB-C ports that can charge (ucsi, tcpm) also show up as supplies
+                Some("USB") => {
+                    let online = read("online").as_deref() == Some("1");
+                    supplies.adapters.push(Adapter::read(name, &path, online));
+                }
+                // Mice, keyboards and headsets report scope=Device
+                Some("Battery") if read("scope").as_deref() != Some("Device") => {
+                    supplies.batteries.push(BatteryState::read(name, path));
+                }
+                _ => {}
+            }
+        }
+
+        supplies
+    }
+
+    /// On AC, or any battery charging. Only a discharging battery with no
+    /// charger or charging battery anywhere counts as on battery.
+    pub fn is_charging(&self) -> bool {
+        self.ac_online == Some(true)
+            || self.batteries.iter().any(|b| b.has_status("Charging"))
+            || !self.batteries.iter().any(|b| b.has_status("Discharging"))
+    }
+
+    /// Power of the strongest connected adapter, `None` when none reports it
+    pub fn adapter_watts(&self) -> Option<f32> {
+        self.adapters.iter().filter(|a| a.online).filter_map(|a| a.watts).reduce(f32::max)
+    }
+
+    /// Plugged in, but a battery is discharging anyway
+    pub fn draining_on_ac(&self) -> bool {
+        self.ac_online == Some(true) && self.batteries.iter().any(|b| b.has_status("Discharging"))
+    }
+
+    /// Any battery is charging
+    pub fn any_battery_charging(&self) -> Option<bool> {
+        (!self.batteries.is_empty()).then(|| self.batteries.iter().any(|b| b.has_status("Charging")))
+    }
+
+    /// Charge across all batteries, weighted by their size when every one
+    /// reports energy, else the mean capacity
+    pub fn combined_level(&self) -> Option<u8> {
+        let energy: Option<(f32, f32)> = self.batteries.iter().try_fold((0.0, 0.0), |(now, full), b| {
+            Some((now + b.energy_now?, full + b.energy_full?))
+        });
+        if let Some((now, full)) = energy.filter(|(_, full)| *full > 0.0) {
+            return Some((now / full * 100.0).round().clamp(0.0, 100.0) as u8);
+        }
+
+        let levels: Vec<u32> = self.batteries.iter().filter_map(|b| b.capacity.map(u32::from)).collect();
+        (!levels.is_empty()).then(|| (levels.iter().sum::<u32>() / levels.len() as u32) as u8)
+    }
+
+    /// Sum of a per-battery value, `None` when no battery reports it
+    pub fn total(&self, value: impl Fn(&BatteryState) -> Option<f32>) -> Option<f32> {
+        self.batteries.iter().filter_map(value).fold(None, |sum, v| Some(sum.unwrap_or(0.0) + v))
+    }
+}
+
+/// Why a battery sysfs attribute couldn't be written
+#[derive(Debug)]
+pub enum SysfsError {
+    /// ENOENT: the driver doesn't expose this attribute
+    NotFound(PathBuf),
+    /// EACCES/EPERM: not running as root, or the attribute is read-only
+    PermissionDenied(PathBuf),
+    /// EINVAL: the driver rejected the value, e.g. a start threshold above the stop one
+    InvalidValue { path: PathBuf, value: String },
+    /// Refused before writing, see `crate::validate`
+    Rejected(crate::validate::InvalidValue),
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl SysfsError {
+    fn new(path: &Path, value: &str, source: io::Error) -> Self {
+        let path = path.to_path_buf();
+        match source.raw_os_error().map(nix::errno::Errno::from_i32) {
+            Some(nix::errno::Errno::ENOENT) => Self::NotFound(path),
+            Some(nix::errno::Errno::EACCES | nix::errno::Errno::EPERM) => Self::PermissionDenied(path),
+            Some(nix::errno::Errno::EINVAL) => Self::InvalidValue { path, value: value.to_string() },
+            _ => Self::Io { path, source },
+        }
+    }
+}
+
+impl fmt::Display for SysfsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "{} does NOT exist", path.display()),
+            Self::PermissionDenied(path) => write!(f, "permission denied writing {} (are you root?)", path.display()),
+            Self::InvalidValue { path, value } => write!(f, "{} rejected the value {}", path.display(), value),
+            Self::Io { path, source } => write!(f, "failed to write {}: {}", path.display(), source),
+            Self::Rejected(invalid) => write!(f, "not written: {}", invalid),
+        }
+    }
+}
+
+impl std::error::Error for SysfsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Rejected(invalid) => Some(invalid),
+            _ => None,
+        }
+    }
+}
+
+/// Write a sysfs attribute directly; the daemon already runs as root. The
+/// attribute is never created, so a missing one is reported as `NotFound`.
+pub fn write_attribute(path: &Path, value: impl fmt::Display) -> std::result::Result<(), SysfsError> {
+    let value = value.to_string();
+    fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(value.as_bytes()))
+        .map_err(|e| SysfsError::new(path, &value, e))
+}
+
+/// sysfs attribute names of a start/stop threshold pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ThresholdAttrs {
+    pub start: &'static str,
+    pub stop: &'static str,
+}
+
+/// Names used by thinkpad_acpi, ideapad and asus-wmi
+pub(crate) const LEGACY_ATTRS: ThresholdAttrs = ThresholdAttrs {
+    start: "charge_start_threshold",
+    stop: "charge_stop_threshold",
+};
+
+/// Names of the generic power_supply class attributes
+pub(crate) const CONTROL_ATTRS: ThresholdAttrs = ThresholdAttrs {
+    start: "charge_control_start_threshold",
+    stop: "charge_control_end_threshold",
+};
+
+/// Write the start and stop thresholds of `battery` under `dir`, returning
+/// what failed. The driver rejects a start value above the current stop
+/// threshold (EINVAL), in which case the stop threshold is written first.
+pub(crate) fn write_thresholds(dir: &Path, battery: &str, attrs: ThresholdAttrs, start: u8, stop: u8) -> Vec<SysfsError> {
+    if let Err(invalid) = crate::validate::thresholds(start, stop) {
+        return vec![SysfsError::Rejected(invalid)];
+    }
+
+    let start_path = dir.join(battery).join(attrs.start);
+    let stop_path = dir.join(battery).join(attrs.stop);
+
+    match write_attribute(&start_path, start) {
+        Err(SysfsError::InvalidValue { .. }) => [write_attribute(&stop_path, stop), write_attribute(&start_path, start)]
+            .into_iter()
+            .filter_map(|result| result.err())
+            .collect(),
+        first => [first, write_attribute(&stop_path, stop)]
+            .into_iter()
+            .filter_map(|result| result.err())
+            .collect(),
+    }
+}
+
+/// Set both charging thresholds of `battery`, warning about each failure and
+/// returning the first one so callers can report it
+pub(crate) fn set_thresholds(battery: &str, start: u8, stop: u8) -> Result<()> {
+    let mut errors = write_thresholds(Path::new(POWER_SUPPLY_DIR), battery, LEGACY_ATTRS, start, stop).into_iter();
+    let Some(first) = errors.next() else {
+        return Ok(());
+    };
+    for error in errors {
+        warn!(code = %EventCode::ThresholdWriteFailed, "{}", error);
+    }
+    Err(anyhow::Error::new(first).context(format!("failed to set {} charging thresholds", battery)))
+}
+
+/// Power supplies under `dir` that have `attribute`, sorted by name
+pub(crate) fn batteries_with(dir: &Path, attribute: &str) -> Vec<String> {
+    let mut batteries: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().join(attribute).exists())
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    batteries.sort();
+    batteries
+}
+
+/// Options of a sysfs choice attribute such as `charge_types` or
+/// `charge_behaviour` ("a [b] c") and the selected one, in brackets
+pub(crate) fn parse_choices(content: &str) -> (Vec<String>, Option<String>) {
+    let mut current = None;
+    let available = content
+        .split_whitespace()
+        .map(|choice| match choice.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+            Some(selected) => {
+                current = Some(selected.to_string());
+                selected.to_string()
+            }
+            None => choice.to_string(),
+        })
+        .collect();
+    (available, current)
+}
+
+/// Select `value` in the choice attribute at `path`, unless it already is
+pub(crate) fn select_choice(path: &Path, value: &str) -> Result<()> {
+    let (available, current) = parse_choices(&fs::read_to_string(path)?);
+
+    if current.as_deref() == Some(value) {
+        return Ok(());
+    }
+    if !available.iter().any(|choice| choice == value) {
+        bail!("{} doesn't support {} (available: {})", path.display(), value, available.join(", "));
+    }
+
+    write_attribute(path, value)?;
+    Ok(())
+}
+
 /// Common trait for battery threshold management
 pub trait BatteryManager {
     fn setup(&self, config: &Config) -> Result<()>;
     fn print_thresholds(&self) -> Result<()>;
 }
 
-/// Main battery setup function - delegates to appropriate module
+/// Main battery setup function - runs every applicable manager, carrying on
+/// past failures so one broken interface doesn't block the others
 pub fn battery_setup(config: &Config) -> Result<()> {
-    let module = LaptopModule::detect();
-    
-    match module {
-        LaptopModule::IdeapadAcpi => {
-            ideapad_acpi::IdeapadAcpiManager.setup(config)
-        }
-        LaptopModule::IdeapadLaptop => {
-            ideapad_laptop::IdeapadLaptopManager.setup(config)
-        }
-        LaptopModule::ThinkpadAcpi => {
-            thinkpad::ThinkpadManager.setup(config)
-        }
-        LaptopModule::AsusWmi => {
-            asus::AsusManager.setup(config)
-        }
-        LaptopModule::None => {
-            Ok(()) // No battery management needed
+    let mut first_error = None;
+
+    for module in LaptopModule::detect() {
+        let operation = format!("battery_setup.{}", module.name());
+        if let Err(e) = crate::telemetry::track(&operation, module.manager().setup(config)) {
+            warn!(code = %EventCode::BatterySetupFailed, "{} battery setup failed: {}", module.name(), e);
+            first_error.get_or_insert(e);
         }
     }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
 /// Print battery thresholds
 pub fn battery_get_thresholds() -> Result<()> {
-    let module = LaptopModule::detect();
-    
-    match module {
-        LaptopModule::IdeapadAcpi => {
-            ideapad_acpi::IdeapadAcpiManager.print_thresholds()
-        }
-        LaptopModule::IdeapadLaptop => {
-            ideapad_laptop::IdeapadLaptopManager.print_thresholds()
-        }
-        LaptopModule::ThinkpadAcpi => {
-            thinkpad::ThinkpadManager.print_thresholds()
-        }
-        LaptopModule::AsusWmi => {
-            asus::AsusManager.print_thresholds()
-        }
-        LaptopModule::None => {
-            Ok(()) // Nothing to print
+    for module in LaptopModule::detect() {
+        module.manager().print_thresholds()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_laptop_modules() {
+        let loaded = parse_modules(
+            "ideapad_laptop 61440 0 - Live 0x0000000000000000\n\
+             asus_wmi 77824 1 asus_nb_wmi, Live 0x0000000000000000\n\
+             sparse_keymap 12288 2 ideapad_laptop,asus_wmi, Live 0x0000000000000000\n",
+        );
+        assert_eq!(loaded, vec!["ideapad_laptop", "asus_wmi", "sparse_keymap"]);
+        assert_eq!(
+            LaptopModule::from_loaded(&loaded, || true),
+            vec![LaptopModule::IdeapadLaptop, LaptopModule::AsusWmi]
+        );
+
+        // Only whole names count: asus_wmi_sensors is not asus_wmi
+        let loaded = parse_modules("asus_wmi_sensors 16384 0 - Live 0x0\nframework_laptop 16384 0 - Live 0x0\n");
+        assert_eq!(LaptopModule::from_loaded(&loaded, || true), vec![LaptopModule::Framework]);
+
+        assert_eq!(LaptopModule::from_loaded(&[], || true), vec![LaptopModule::Generic]);
+        assert!(LaptopModule::from_loaded(&[], || false).is_empty());
+        assert_eq!(LaptopModule::describe(&[]), "none");
+        assert_eq!(LaptopModule::describe(&[LaptopModule::ThinkpadAcpi, LaptopModule::Generic]), "thinkpad_acpi, generic");
+    }
+
+    fn write_supply(dir: &Path, name: &str, files: &[(&str, &str)]) {
+        fs::create_dir_all(dir.join(name)).unwrap();
+        for (file, value) in files {
+            fs::write(dir.join(name).join(file), format!("{}\n", value)).unwrap();
         }
     }
+
+    #[test]
+    fn test_power_supplies_aggregate_batteries() {
+        let dir = std::env::temp_dir().join(format!("power_supplies_test_{}", std::process::id()));
+        write_supply(&dir, "AC", &[("type", "Mains"), ("online", "0")]);
+        write_supply(&dir, "BAT0", &[
+            ("type", "Battery"), ("status", "Not charging"), ("capacity", "90"),
+            ("energy_now", "20700000"), ("energy_full", "23000000"), ("power_now", "0"),
+        ]);
+        write_supply(&dir, "BAT1", &[
+            ("type", "Battery"), ("status", "Discharging"), ("capacity", "40"),
+            ("energy_now", "28000000"), ("energy_full", "70000000"), ("power_now", "8500000"),
+        ]);
+        write_supply(&dir, "hidpp_battery_0", &[("type", "Battery"), ("status", "Discharging")]);
+        write_supply(&dir, "hid-mouse-battery", &[("type", "Battery"), ("scope", "Device"), ("status", "Charging")]);
+
+        let ignore = vec!["hidpp_battery".to_string()];
+        let supplies = PowerSupplies::scan_dir(&dir, &ignore);
+        assert_eq!(supplies.ac_online, Some(false));
+        assert_eq!(supplies.batteries.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["BAT0", "BAT1"]);
+        assert!(!supplies.is_charging());
+        assert_eq!(supplies.any_battery_charging(), Some(false));
+        // (20.7 + 28) / (23 + 70) Wh
+        assert_eq!(supplies.combined_level(), Some(52));
+        assert_eq!(supplies.total(|b| b.power), Some(8.5));
+
+        // The second battery charging while the first one sits idle
+        write_supply(&dir, "BAT1", &[("status", "Charging")]);
+        // An idle laptop drawing 9.5 W says nothing about the adapter
+        write_supply(&dir, "AC", &[("online", "1"), ("voltage_now", "19000000"), ("current_now", "500000")]);
+        let supplies = PowerSupplies::scan_dir(&dir, &ignore);
+        assert!(supplies.is_charging());
+        assert_eq!(supplies.any_battery_charging(), Some(true));
+        assert_eq!(supplies.adapter_watts(), None);
+        assert!(!supplies.draining_on_ac());
+
+        // A 20 V / 1.5 A USB-PD contract next to the ACPI adapter
+        write_supply(&dir, "ucsi-source-psy-USBC000:001", &[
+            ("type", "USB"), ("online", "1"), ("usb_type", "C [PD] PD_PPS"),
+            ("voltage_max", "20000000"), ("current_max", "1500000"),
+        ]);
+        write_supply(&dir, "ucsi-source-psy-USBC000:002", &[("type", "USB"), ("online", "0"), ("usb_type", "[C] PD")]);
+        let supplies = PowerSupplies::scan_dir(&dir, &ignore);
+        assert_eq!(supplies.adapters[1].usb_type.as_deref(), Some("PD"));
+        assert_eq!(supplies.adapters[2].watts, Some(15.0));
+        assert_eq!(supplies.adapter_watts(), Some(30.0));
+        assert_eq!(parse_usb_type("Unknown SDP DCP [CDP] C PD"), Some("CDP".to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(PowerSupplies::scan_dir(&dir, &ignore).is_charging());
+    }
+
+    #[test]
+    fn test_write_thresholds() {
+        let dir = std::env::temp_dir().join(format!("battery_thresholds_test_{}", std::process::id()));
+        write_supply(&dir, "BAT0", &[("charge_start_threshold", "75"), ("charge_stop_threshold", "80")]);
+        write_supply(&dir, "BAT1", &[("charge_stop_threshold", "100")]);
+
+        assert!(write_thresholds(&dir, "BAT0", LEGACY_ATTRS, 40, 60).is_empty());
+        assert_eq!(fs::read_to_string(dir.join("BAT0/charge_start_threshold")).unwrap(), "40");
+        assert_eq!(fs::read_to_string(dir.join("BAT0/charge_stop_threshold")).unwrap(), "60");
+
+        // A driver without a start threshold still gets the stop one
+        let errors = write_thresholds(&dir, "BAT1", LEGACY_ATTRS, 40, 60);
+        assert!(matches!(errors.as_slice(), [SysfsError::NotFound(path)] if path.ends_with("charge_start_threshold")));
+        assert_eq!(fs::read_to_string(dir.join("BAT1/charge_stop_threshold")).unwrap(), "60");
+
+        // Crossed thresholds are refused before anything is written
+        let errors = write_thresholds(&dir, "BAT0", LEGACY_ATTRS, 90, 80);
+        assert!(matches!(errors.as_slice(), [SysfsError::Rejected(_)]));
+        assert_eq!(fs::read_to_string(dir.join("BAT0/charge_stop_threshold")).unwrap(), "60");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sysfs_error_kinds() {
+        let path = Path::new("/sys/class/power_supply/BAT0/charge_stop_threshold");
+        let error = |errno: nix::errno::Errno| SysfsError::new(path, "60", io::Error::from_raw_os_error(errno as i32));
+
+        assert!(matches!(error(nix::errno::Errno::ENOENT), SysfsError::NotFound(_)));
+        assert!(matches!(error(nix::errno::Errno::EACCES), SysfsError::PermissionDenied(_)));
+        assert!(matches!(error(nix::errno::Errno::EPERM), SysfsError::PermissionDenied(_)));
+        assert!(matches!(error(nix::errno::Errno::EINVAL), SysfsError::InvalidValue { ref value, .. } if value == "60"));
+        assert!(matches!(error(nix::errno::Errno::EIO), SysfsError::Io { .. }));
+        assert_eq!(
+            error(nix::errno::Errno::EINVAL).to_string(),
+            "/sys/class/power_supply/BAT0/charge_stop_threshold rejected the value 60"
+        );
+    }
 }
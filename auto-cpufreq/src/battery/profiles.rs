@@ -0,0 +1,153 @@
+// src/battery/profiles.rs
+//
+// Named charging threshold sets (e.g. "docked" 40-60, "travel" 90-100) defined
+// as `[battery_profile.<name>]` config sections. The selected one is kept in
+// the state store and takes precedence over the `[battery]` thresholds in every
+// `BatteryManager` backend.
+
+use anyhow::{Result, bail};
+use tracing::warn;
+use serde::Serialize;
+
+use crate::config::{Config, Snapshot, CONFIG};
+use crate::event_code::EventCode;
+use crate::state::StateStore;
+
+const SECTION_PREFIX: &str = "battery_profile.";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatteryProfile {
+    pub name: String,
+    pub start_threshold: u8,
+    pub stop_threshold: u8,
+}
+
+pub fn available(config: &Snapshot) -> Vec<String> {
+    config
+        .sections()
+        .filter_map(|section| section.strip_prefix(SECTION_PREFIX))
+        .map(String::from)
+        .collect()
+}
+
+pub fn load(config: &Snapshot, name: &str) -> Result<BatteryProfile> {
+    let name = name.trim().to_lowercase();
+    let section = format!("{}{}", SECTION_PREFIX, name);
+
+    if config.section(&section).is_none() {
+        let names = available(config);
+        if names.is_empty() {
+            bail!("Unknown battery profile '{}' (no [{}NAME] sections in the config)", name, SECTION_PREFIX);
+        }
+        bail!("Unknown battery profile '{}' (available: {})", name, names.join(", "));
+    }
+
+    let threshold = |key: &str, default: u8| -> Result<u8> {
+        match config.get(&section, key) {
+            None => Ok(default),
+            Some(value) => match value.trim().parse::<u8>() {
+                Ok(v) if v <= 100 => Ok(v),
+                _ => bail!("Invalid {} in [{}]: {} (expected 0-100)", key, section, value),
+            },
+        }
+    };
+
+    let start_threshold = threshold("charging_start_threshold", 0)?;
+    let stop_threshold = threshold("charging_stop_threshold", 100)?;
+    if start_threshold >= stop_threshold {
+        bail!(
+            "charging_start_threshold ({}) must be below charging_stop_threshold ({}) in [{}]",
+            start_threshold, stop_threshold, section
+        );
+    }
+
+    Ok(BatteryProfile { name, start_threshold, stop_threshold })
+}
+
+/// The profile selected with `--battery-profile`, if it still resolves. A
+/// battery calibration in progress overrides it with the full 0-100 range.
+pub fn active() -> Option<BatteryProfile> {
+    let state = StateStore::default().load();
+    if state.calibration.as_ref().is_some_and(super::calibration::Calibration::running) {
+        return Some(BatteryProfile { name: "calibration".to_string(), start_threshold: 0, stop_threshold: 100 });
+    }
+    let name = state.battery_profile?;
+    match load(&CONFIG.snapshot(), &name) {
+        Ok(profile) => Some(profile),
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "Ignoring selected battery profile: {}", e);
+            None
+        }
+    }
+}
+
+/// Store the selected profile (`None` goes back to the `[battery]` thresholds)
+/// and apply it right away through the detected backend
+pub fn select(name: Option<&str>) -> Result<()> {
+    let name = name
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !crate::profiles::RESET_VALUES.contains(&n.as_str()));
+
+    if let Some(ref name) = name {
+        load(&CONFIG.snapshot(), name)?;
+    }
+
+    StateStore::default().update(|s| s.battery_profile = name.clone())?;
+
+    match name {
+        Some(name) => println!("Selected battery profile: {}", name),
+        None => println!("Battery profile selection removed"),
+    }
+
+    super::battery_setup(&CONFIG)
+}
+
+/// Thresholds are managed when `[battery] enable_thresholds` is set or a
+/// battery profile has been selected
+pub fn thresholds_enabled(config: &Config) -> bool {
+    config.get_bool("battery", "enable_thresholds").unwrap_or(false) || active().is_some()
+}
+
+/// Threshold for `mode` ("start"/"stop") on `battery`: the selected battery
+/// profile's, else the `[battery.<name>]` value (e.g. `[battery.BAT1]`), else
+/// the `[battery]` value
+pub fn threshold(config: &Config, battery: &str, mode: &str) -> u8 {
+    if let Some(profile) = active() {
+        return if mode == "start" { profile.start_threshold } else { profile.stop_threshold };
+    }
+
+    let section = format!("battery.{}", battery);
+    match config.get_section_threshold(&section, mode) {
+        Ok(Some(value)) => return value,
+        Ok(None) => {}
+        Err(e) => warn!(code = %EventCode::ConfigInvalid, "Ignoring [{}] {} threshold: {}", section, mode, e),
+    }
+
+    config.get_threshold(mode).unwrap_or_else(|_| {
+        if mode == "start" { 0 } else { 100 }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_battery_profiles() {
+        let config = Snapshot::from_str_ini(
+            "[battery_profile.docked]\ncharging_start_threshold = 40\ncharging_stop_threshold = 60\n\
+             [battery_profile.travel]\ncharging_stop_threshold = 100\ncharging_start_threshold = 90\n\
+             [battery_profile.broken]\ncharging_start_threshold = 80\ncharging_stop_threshold = 60\n",
+        )
+        .unwrap();
+
+        assert_eq!(available(&config), vec!["broken", "docked", "travel"]);
+        assert_eq!(
+            load(&config, "Docked").unwrap(),
+            BatteryProfile { name: "docked".into(), start_threshold: 40, stop_threshold: 60 }
+        );
+        assert_eq!(load(&config, "travel").unwrap().start_threshold, 90);
+        assert!(load(&config, "broken").is_err());
+        assert!(load(&config, "missing").is_err());
+    }
+}
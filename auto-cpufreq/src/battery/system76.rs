@@ -0,0 +1,34 @@
+// src/battery/system76.rs
+//
+// System76 laptops (system76_acpi). The embedded controller firmware takes
+// start and end thresholds through the standard charge_control_*_threshold
+// battery attributes; the "full charge" preset in the firmware is 0/100.
+
+use std::path::Path;
+use anyhow::Result;
+use tracing::warn;
+
+use super::{BatteryManager, generic, POWER_SUPPLY_DIR};
+use crate::config::Config;
+use crate::event_code::EventCode;
+
+pub struct System76Manager;
+
+impl BatteryManager for System76Manager {
+    fn setup(&self, config: &Config) -> Result<()> {
+        if !super::profiles::thresholds_enabled(config) {
+            return Ok(());
+        }
+
+        let batteries = generic::supported_batteries_in(Path::new(POWER_SUPPLY_DIR));
+        if batteries.is_empty() {
+            warn!(code = %EventCode::BatterySetupFailed, "system76_acpi is loaded but no battery exposes charge thresholds");
+        }
+
+        generic::set_configured_thresholds(config, &batteries)
+    }
+
+    fn print_thresholds(&self) -> Result<()> {
+        generic::GenericManager.print_thresholds()
+    }
+}
@@ -0,0 +1,152 @@
+// src/battery_levels.rs
+//
+// Battery level tiers: `[battery_levels] below_50 = balanced, below_20 =
+// powersave_strict` maps charge levels to profiles (built-in or
+// `[profile.<name>]`), so the policy gets stricter as the battery drains
+// instead of one `[battery]` policy from full to empty. Every tier the level
+// is below applies, the lower ones on top of the higher ones: with the
+// example, at 15% the powersave_strict keys win and balanced fills in what it
+// leaves unset. Tiers only apply on battery. A tier is left once the level is
+// a couple of points above it again, so a reading that wobbles around a
+// threshold doesn't flip the settings every cycle.
+
+use anyhow::{Context, Result, bail};
+use tracing::{info, warn};
+
+use crate::battery::PowerSupplies;
+use crate::config::{CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::profiles::{self, Profile, ProfileSwitch};
+
+const SECTION: &str = "battery_levels";
+const KEY_PREFIX: &str = "below_";
+/// Points above a tier's threshold the level has to reach to leave it
+const HYSTERESIS: u8 = 2;
+
+/// Keyed by the threshold of the lowest tier
+static SWITCH: ProfileSwitch<u8> = ProfileSwitch::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tier {
+    /// Percent
+    pub below: u8,
+    pub profile: String,
+}
+
+/// `[battery_levels]`, highest threshold first. Every profile named must exist.
+pub fn settings(config: &Snapshot) -> Result<Vec<Tier>> {
+    let mut tiers = Vec::new();
+    for (key, value) in config.section(SECTION).into_iter().flatten() {
+        let below = key
+            .strip_prefix(KEY_PREFIX)
+            .and_then(|level| level.parse::<u8>().ok())
+            .filter(|level| (1..=100).contains(level));
+        let Some(below) = below else {
+            bail!("Invalid key in [{}]: {} (expected below_<percent>, e.g. below_20)", SECTION, key);
+        };
+        let profile = value.trim().to_lowercase();
+        profiles::load(config, &profile).with_context(|| format!("In [{}] {}", SECTION, key))?;
+        tiers.push(Tier { below, profile });
+    }
+    tiers.sort_by_key(|tier| std::cmp::Reverse(tier.below));
+    Ok(tiers)
+}
+
+/// Tiers that apply at `level`, highest first, given the lowest tier that
+/// applied last time
+fn applying(tiers: &[Tier], level: u8, last: Option<u8>) -> Vec<&Tier> {
+    tiers
+        .iter()
+        .filter(|tier| {
+            level < tier.below
+                || last.is_some_and(|last| tier.below >= last && level < tier.below.saturating_add(HYSTERESIS))
+        })
+        .collect()
+}
+
+/// The profiles of `tiers` merged, each on top of the one before
+fn merge(config: &Snapshot, tiers: &[&Tier]) -> Result<Option<Profile>> {
+    let mut merged: Option<Profile> = None;
+    for tier in tiers {
+        let profile = profiles::load(config, &tier.profile)?;
+        merged = Some(match merged {
+            Some(base) => profile.on_top_of(base),
+            None => profile,
+        });
+    }
+    Ok(merged)
+}
+
+/// The merged profile of the tiers that apply and the threshold of the
+/// lowest one, given the lowest one of the last cycle
+fn current(is_charging: bool, last: Option<u8>) -> Option<(u8, Profile)> {
+    if is_charging {
+        return None;
+    }
+    let config = CONFIG.snapshot();
+    let tiers = match settings(&config) {
+        Ok(tiers) if tiers.is_empty() => return None,
+        Ok(tiers) => tiers,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{:#}", e);
+            return None;
+        }
+    };
+    let level = PowerSupplies::scan().combined_level()?;
+    let applying = applying(&tiers, level, last);
+    let lowest = applying.last()?.below;
+    match merge(&config, &applying) {
+        Ok(profile) => Some((lowest, profile?)),
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{:#}", e);
+            None
+        }
+    }
+}
+
+/// Called by `set_autofreq`: the merged profile of the tiers the battery
+/// level is in, logging when the lowest one changes
+pub fn profile(is_charging: bool) -> Option<Profile> {
+    SWITCH.follow(current(is_charging, SWITCH.key()), |tier| match tier {
+        Some((below, profile)) => info!("Battery below {}%, using profile {}", below, profile.name),
+        None => info!("Battery level tiers no longer apply, back to the regular settings"),
+    })
+}
+
+pub fn preview(is_charging: bool) -> Option<Profile> {
+    SWITCH.preview(current(is_charging, SWITCH.key()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_level_tiers() {
+        let config = Snapshot::from_str_ini(
+            "[battery_levels]\nbelow_50 = balanced\nbelow_20 = strict\n\
+             [profile.strict]\nturbo = never\nscaling_max_freq = 1400000\n",
+        )
+        .unwrap();
+        let tiers = settings(&config).unwrap();
+        assert_eq!(tiers.iter().map(|t| t.below).collect::<Vec<_>>(), [50, 20]);
+
+        let lowest = |level: u8, last: Option<u8>| applying(&tiers, level, last).last().map(|t| t.below);
+        assert_eq!(lowest(80, None), None);
+        assert_eq!(lowest(49, None), Some(50));
+        assert_eq!(lowest(15, None), Some(20));
+        // Back up by one point stays in the tier, by two leaves it
+        assert_eq!(lowest(20, Some(20)), Some(20));
+        assert_eq!(lowest(22, Some(20)), Some(50));
+        assert_eq!(lowest(20, Some(50)), Some(50));
+
+        let merged = merge(&config, &applying(&tiers, 15, None)).unwrap().unwrap();
+        assert_eq!(merged.name, "strict");
+        assert_eq!(merged.turbo.as_deref(), Some("never"));
+        assert_eq!(merged.scaling_max_freq, Some(1_400_000));
+        assert_eq!(merged.energy_performance_preference.as_deref(), Some("balance_power"));
+
+        assert!(settings(&Snapshot::from_str_ini("[battery_levels]\nbelow_x = balanced\n").unwrap()).is_err());
+        assert!(settings(&Snapshot::from_str_ini("[battery_levels]\nbelow_20 = missing\n").unwrap()).is_err());
+    }
+}
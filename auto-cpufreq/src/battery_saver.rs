@@ -0,0 +1,261 @@
+// src/battery_saver.rs
+//
+// `--battery-saver on|off`: one switch for the usual battery saving steps.
+// While on, the daemon runs a powersave profile with turbo off and a capped
+// maximum frequency, ahead of every other profile. Switching on also dims the
+// screen and turns on Wi-Fi power saving; the values they had are kept in the
+// state file so switching off puts back exactly those, even after a reboot.
+// What is included is set in `[battery_saver]`.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::{CONFIG, Snapshot, parse_bool};
+use crate::cpu::per_core;
+use crate::event_code::EventCode;
+use crate::profiles::{self, Profile};
+use crate::state::StateStore;
+
+pub const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+/// Screen brightness while on, in percent of the maximum
+const DEFAULT_BRIGHTNESS: u8 = 40;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// kHz cap, `None` for half way into the hardware range
+    pub scaling_max_freq: Option<u32>,
+    /// Dim the screen to this percentage, `None` to leave it
+    pub brightness: Option<u8>,
+    pub wifi_powersave: bool,
+}
+
+/// `[battery_saver]` settings
+pub fn settings(config: &Snapshot) -> Result<Settings> {
+    let get = |key: &str| config.get("battery_saver", key).map(str::trim);
+
+    let scaling_max_freq = match get("scaling_max_freq") {
+        None => None,
+        Some(value) => match value.parse::<u32>() {
+            Ok(khz) if khz > 0 => Some(khz),
+            _ => bail!("Invalid scaling_max_freq in [battery_saver]: {} (expected kHz)", value),
+        },
+    };
+    // 0 leaves the screen alone
+    let brightness = match get("brightness") {
+        None => Some(DEFAULT_BRIGHTNESS),
+        Some(value) => match value.parse::<u8>() {
+            Ok(0) => None,
+            Ok(percent) if percent <= 100 => Some(percent),
+            _ => bail!("Invalid brightness in [battery_saver]: {} (expected a percentage)", value),
+        },
+    };
+    let wifi_powersave = match get("wifi_powersave") {
+        None => true,
+        Some(value) => match parse_bool(value) {
+            Some(enabled) => enabled,
+            None => bail!("Invalid wifi_powersave in [battery_saver]: {} (expected true or false)", value),
+        },
+    };
+
+    Ok(Settings { scaling_max_freq, brightness, wifi_powersave })
+}
+
+/// What switching on changed, to put back when switching off
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Restore {
+    /// Backlight device and its brightness before dimming
+    pub brightness: Vec<(String, u32)>,
+    /// Wireless interfaces that had power saving off
+    pub wifi_powersave: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatterySaver {
+    /// Unix time it was switched on
+    pub since: i64,
+    pub restore: Restore,
+}
+
+fn read_number(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Dim every backlight under `dir` brighter than `percent` of its maximum;
+/// returns the devices changed with their previous brightness
+fn dim_backlights(dir: &Path, percent: u8) -> Vec<(String, u32)> {
+    let mut dimmed = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let device = entry.path();
+        let (Some(current), Some(max)) = (read_number(&device.join("brightness")), read_number(&device.join("max_brightness"))) else {
+            continue;
+        };
+        let target = (max as u64 * percent as u64 / 100) as u32;
+        if current <= target {
+            continue;
+        }
+        match fs::write(device.join("brightness"), target.to_string()) {
+            Ok(()) => dimmed.push((entry.file_name().to_string_lossy().into_owned(), current)),
+            Err(e) => warn!(code = %EventCode::TuningFailed, "Failed to dim {}: {}", device.display(), e),
+        }
+    }
+    dimmed
+}
+
+fn restore_backlights(dir: &Path, saved: &[(String, u32)]) {
+    for (device, brightness) in saved {
+        let path = dir.join(device).join("brightness");
+        if let Err(e) = fs::write(&path, brightness.to_string()) {
+            warn!(code = %EventCode::RestoreFailed, "Failed to restore {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Interface names from `iw dev`
+fn parse_iw_interfaces(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Interface "))
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// `iw dev <if> get power_save` ("Power save: on")
+fn parse_power_save(stdout: &str) -> Option<bool> {
+    match stdout.trim().strip_prefix("Power save:")?.trim() {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Turn on power saving on every wireless interface that has it off;
+/// returns the interfaces changed
+fn enable_wifi_powersave() -> Vec<String> {
+    let Ok(interfaces) = crate::command::output("iw", &["dev"]) else {
+        return Vec::new();
+    };
+
+    let mut changed = Vec::new();
+    for interface in parse_iw_interfaces(&interfaces) {
+        let state = crate::command::output("iw", &["dev", &interface, "get", "power_save"]).ok();
+        if state.as_deref().and_then(parse_power_save) != Some(false) {
+            continue;
+        }
+        if crate::command::change_succeeds("iw", &["dev", &interface, "set", "power_save", "on"]) {
+            changed.push(interface);
+        } else {
+            warn!(code = %EventCode::TuningFailed, "Failed to turn on power saving on {}", interface);
+        }
+    }
+    changed
+}
+
+fn restore_wifi_powersave(interfaces: &[String]) {
+    for interface in interfaces {
+        if !crate::command::change_succeeds("iw", &["dev", interface, "set", "power_save", "off"]) {
+            warn!(code = %EventCode::RestoreFailed, "Failed to turn off power saving on {}", interface);
+        }
+    }
+}
+
+fn saver_profile(settings: &Settings) -> Profile {
+    let scaling_max_freq = settings.scaling_max_freq.or_else(|| {
+        per_core::hardware_limits(0).map(|(min, max)| min + (max - min) / 2)
+    });
+
+    Profile {
+        name: "battery-saver".to_string(),
+        governor: Some("powersave".to_string()),
+        turbo: Some("never".to_string()),
+        energy_performance_preference: Some("power".to_string()),
+        scaling_max_freq,
+        platform_profile: Some("low-power".to_string()),
+        ..Profile::default()
+    }
+}
+
+/// Called by `set_autofreq`: the battery saver profile while it is on
+pub fn profile() -> Option<Profile> {
+    StateStore::default().load().battery_saver?;
+    match settings(&CONFIG.snapshot()) {
+        Ok(settings) => Some(saver_profile(&settings)),
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            None
+        }
+    }
+}
+
+/// `--battery-saver on|off`
+pub fn set(on: bool) -> Result<()> {
+    let store = StateStore::default();
+    let current = store.load().battery_saver;
+
+    match (on, current) {
+        (true, Some(_)) => println!("Battery saver is already on"),
+        (false, None) => println!("Battery saver is already off"),
+        (true, None) => {
+            let settings = settings(&CONFIG.snapshot())?;
+            let restore = Restore {
+                brightness: settings.brightness.map(|p| dim_backlights(Path::new(BACKLIGHT_DIR), p)).unwrap_or_default(),
+                wifi_powersave: if settings.wifi_powersave { enable_wifi_powersave() } else { Vec::new() },
+            };
+            let since = chrono::Local::now().timestamp();
+            store.update(|s| s.battery_saver = Some(BatterySaver { since, restore }))?;
+            println!("Battery saver on: powersave governor, turbo off and a frequency cap from the next daemon cycle");
+        }
+        (false, Some(saver)) => {
+            store.update(|s| s.battery_saver = None)?;
+            restore_backlights(Path::new(BACKLIGHT_DIR), &saver.restore.brightness);
+            restore_wifi_powersave(&saver.restore.wifi_powersave);
+            profiles::reset_frequency_limits();
+            println!("Battery saver off, previous settings restored");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::command::mock::MockRunner;
+
+    #[test]
+    fn test_battery_saver() {
+        let config = |ini: &str| Snapshot::from_str_ini(ini).unwrap();
+        assert_eq!(
+            settings(&Snapshot::default()).unwrap(),
+            Settings { scaling_max_freq: None, brightness: Some(DEFAULT_BRIGHTNESS), wifi_powersave: true }
+        );
+        let custom = settings(&config("[battery_saver]\nscaling_max_freq = 1400000\nbrightness = 0\nwifi_powersave = no\n")).unwrap();
+        assert_eq!(custom, Settings { scaling_max_freq: Some(1_400_000), brightness: None, wifi_powersave: false });
+        assert_eq!(saver_profile(&custom).scaling_max_freq, Some(1_400_000));
+        assert!(settings(&config("[battery_saver]\nbrightness = 150\n")).is_err());
+
+        let dir = std::env::temp_dir().join(format!("acf-backlight-{}", std::process::id()));
+        for (device, brightness) in [("intel_backlight", "19200"), ("acpi_video0", "3")] {
+            fs::create_dir_all(dir.join(device)).unwrap();
+            fs::write(dir.join(device).join("brightness"), brightness).unwrap();
+            fs::write(dir.join(device).join("max_brightness"), if device == "acpi_video0" { "15" } else { "24000" }).unwrap();
+        }
+        let saved = dim_backlights(&dir, 40);
+        assert_eq!(saved, vec![("intel_backlight".to_string(), 19200)]);
+        assert_eq!(read_number(&dir.join("intel_backlight/brightness")), Some(9600));
+        restore_backlights(&dir, &saved);
+        assert_eq!(read_number(&dir.join("intel_backlight/brightness")), Some(19200));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let runner = MockRunner::default()
+            .on("iw dev", 0, "phy#0\n\tInterface wlp2s0\n\t\ttype managed\nphy#1\n\tInterface wlan1\n")
+            .on("iw dev wlp2s0 get power_save", 0, "Power save: off\n")
+            .on("iw dev wlan1 get power_save", 0, "Power save: on\n")
+            .on("iw dev wlp2s0 set power_save on", 0, "");
+        assert_eq!(crate::command::with_runner(Arc::new(runner), enable_wifi_powersave), vec!["wlp2s0"]);
+    }
+}
@@ -1,23 +1,37 @@
 // src/bin/auto_cpufreq.rs
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use auto_cpufreq::config::{CONFIG, find_config_file};
 use auto_cpufreq::core::*;
 use auto_cpufreq::globals::*;
 use auto_cpufreq::power_helper::*;
 use auto_cpufreq::battery;
-use auto_cpufreq::modules::{SystemMonitor, ViewType};
-use std::thread;
-use std::time::Duration;
+use auto_cpufreq::bundle;
+use auto_cpufreq::event_code::EventCode;
+use auto_cpufreq::logging;
+use auto_cpufreq::events::{CycleSchedule, IntervalLimits, PollInterval, Wakeup, Wakeups};
+use auto_cpufreq::modules::{OutputFormat, SystemInfo, SystemMonitor, ViewType};
+#[cfg(feature = "metrics")]
+use auto_cpufreq::modules::{report, watch};
 
 use auto_cpufreq::core::footer;
 
+const EXAMPLES: &str = "\
+Examples:
+  auto-cpufreq --monitor               Show what the daemon would change
+  sudo auto-cpufreq --live             Apply them until interrupted
+  sudo auto-cpufreq --install          Install the daemon
+  auto-cpufreq --stats                 Watch what the daemon is doing
+  sudo auto-cpufreq --force=powersave  Override the governor";
+
 #[derive(Parser, Debug)]
 #[command(name = "auto-cpufreq")]
 #[command(about = "Automatic CPU speed & power optimizer for Linux", long_about = None)]
+#[command(after_help = EXAMPLES)]
+#[command(group(clap::ArgGroup::new("plan_action").args(["install", "remove"]).multiple(true)))]
 struct Args {
-    /// Monitor and see suggestions for CPU optimizations
+    /// Monitor and see what the daemon would change, without changing anything
     #[arg(long)]
     monitor: bool,
 
@@ -40,6 +54,22 @@ struct Args {
     #[arg(long)]
     remove: bool,
 
+    /// With --remove: also delete the state file and overrides, audit log, stats history, log files and /etc/auto-cpufreq.toml or .conf
+    #[arg(long)]
+    purge: bool,
+
+    /// Put back the governor, turbo, EPP and frequency limits saved when the daemon first started
+    #[arg(long)]
+    restore_defaults: bool,
+
+    /// With --install or --remove: list the files and service commands without changing anything
+    #[arg(long, requires = "plan_action")]
+    dry_run: bool,
+
+    /// Service manager to use for --install, --remove and daemon status checks (systemd, openrc, dinit, runit, s6) instead of the detected one
+    #[arg(long, value_name = "INIT")]
+    init: Option<String>,
+
     /// Force use of either "powersave" or "performance" governors
     #[arg(long, value_name = "GOVERNOR")]
     force: Option<String>,
@@ -48,6 +78,23 @@ struct Args {
     #[arg(long, value_name = "MODE")]
     turbo: Option<String>,
 
+    /// Select a power profile (quiet, balanced, max-performance or a [profile.NAME] section), "reset" to clear, "list" to show them
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Battery saver: powersave governor, turbo off, a frequency cap, a dimmed screen and
+    /// Wi-Fi power saving (see [battery_saver]); "off" restores the previous settings
+    #[arg(long, value_name = "on|off")]
+    battery_saver: Option<String>,
+
+    /// Select a charging threshold set from a [battery_profile.NAME] section, "reset" to clear, "list" to show them
+    #[arg(long, value_name = "NAME")]
+    battery_profile: Option<String>,
+
+    /// Make --force/--turbo revert automatically after DURATION (e.g. 30m, 2h)
+    #[arg(long = "for", value_name = "DURATION")]
+    override_for: Option<String>,
+
     /// Use config file at defined path
     #[arg(long, value_name = "PATH")]
     config: Option<String>,
@@ -59,6 +106,53 @@ struct Args {
     #[arg(long, hide = true)]
     get_state: bool,
 
+    /// Print governor, turbo, EPP and AC state every second (one line each)
+    #[arg(long)]
+    watch: bool,
+
+    /// Only print a line when something changed (use with --watch)
+    #[arg(long, requires = "watch")]
+    changes_only: bool,
+
+    /// Print machine-readable JSON (with --stats, --debug, --capabilities, --history, --why, --version,
+    /// --get-state, --monitor, --live and --watch)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Stream --monitor/--live samples as csv or ndjson instead of drawing the view
+    #[arg(long, value_name = "FORMAT")]
+    output: Option<OutputFormat>,
+
+    /// Write the --output samples to FILE instead of stdout
+    #[arg(long, value_name = "FILE", requires = "output")]
+    output_file: Option<std::path::PathBuf>,
+
+    /// Stop --output after DURATION (e.g. 60s, 10m)
+    #[arg(long, value_name = "DURATION", requires = "output")]
+    duration: Option<String>,
+
+    /// Write a shareable system/power report (.html or .md)
+    #[arg(long, value_name = "FILE")]
+    export_report: Option<String>,
+
+    /// Charge behaviour of the battery: auto, inhibit-charge (hold the level on AC) or
+    /// force-discharge (run from the battery while plugged in), "reset" for auto
+    #[arg(long, value_name = "MODE")]
+    charge_behaviour: Option<String>,
+
+    /// Calibrate the battery: discharge to FLOOR % (default 5), charge to full, then restore
+    /// the thresholds. Run again to resume an interrupted calibration, "cancel" to abort it
+    #[arg(long, value_name = "FLOOR", num_args = 0..=1, default_missing_value = "")]
+    calibrate_battery: Option<String>,
+
+    /// Save config, overrides and selected profiles to a tar bundle, e.g. before a reinstall
+    #[arg(long, value_name = "FILE")]
+    export_settings: Option<String>,
+
+    /// Restore settings from a bundle written by --export-settings
+    #[arg(long, value_name = "FILE")]
+    import_settings: Option<String>,
+
     /// Turn off Bluetooth on boot
     #[arg(long)]
     bluetooth_boot_off: bool,
@@ -71,6 +165,62 @@ struct Args {
     #[arg(long)]
     debug: bool,
 
+    /// Show every setting in use, from the config file or defaults, with its source and validity
+    #[arg(long)]
+    show_effective_config: bool,
+
+    /// Check the config file for unknown sections and keys and invalid values
+    #[arg(long)]
+    check_config: bool,
+
+    /// Convert the INI config file in use to auto-cpufreq.toml next to it
+    #[arg(long)]
+    migrate_config: bool,
+
+    /// Print a value of the config file, e.g. --config-get charger.governor
+    #[arg(long, value_name = "SECTION.KEY")]
+    config_get: Option<String>,
+
+    /// Set a value in the config file, e.g. --config-set battery.charging_stop_threshold 80
+    #[arg(long, num_args = 2, value_names = ["SECTION.KEY", "VALUE"])]
+    config_set: Option<Vec<String>>,
+
+    /// Remove a value from the config file, so the default applies again
+    #[arg(long, value_name = "SECTION.KEY")]
+    config_unset: Option<String>,
+
+    /// Show which drivers, governors and power features this machine supports and which are used
+    #[arg(long)]
+    capabilities: bool,
+
+    /// Show frequency, temperature, usage and governor timelines recorded by the daemon
+    #[arg(long)]
+    history: bool,
+
+    /// Only show history from the last DURATION (e.g. 30m, 6h)
+    #[arg(long, value_name = "DURATION", requires = "history")]
+    since: Option<String>,
+
+    /// Print the history as CSV
+    #[arg(long, requires = "history")]
+    csv: bool,
+
+    /// Explain the daemon's last N governor, turbo and EPP changes (default 10)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+    why: Option<usize>,
+
+    /// Time N full daemon cycles on this machine (default 5), for checking performance changes
+    #[arg(long, hide = true, value_name = "N", num_args = 0..=1, default_missing_value = "5")]
+    bench_cycle: Option<usize>,
+
+    /// Show the anonymized hardware support report exactly as it would be sent (opt-in, see [telemetry])
+    #[arg(long)]
+    telemetry_preview: bool,
+
+    /// Log level: error, warn, info, debug or trace (overrides [logging] level)
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<String>,
+
     /// Show verbose/detailed output (use with --monitor, --live, --stats)
     #[arg(long, short)]
     verbose: bool,
@@ -82,6 +232,10 @@ struct Args {
     /// Support the project
     #[arg(long)]
     donate: bool,
+
+    /// Print a short banner and exit
+    #[arg(long, exclusive = true)]
+    summary: bool,
 }
 
 fn main() -> Result<()> {
@@ -90,64 +244,236 @@ fn main() -> Result<()> {
     // Display info if config file is used
     let config_path = find_config_file(args.config.as_deref());
     CONFIG.set_path(config_path.clone())?;
+    let mut log_settings = logging::Settings::from_config(&CONFIG.snapshot(), args.log_level.as_deref())?;
+    // The log file is the daemon's, a CLI run as a user couldn't open it anyway
+    if !args.daemon {
+        log_settings.file = None;
+    }
+    logging::init(&log_settings)?;
+
+    if let Some(ref init) = args.init {
+        select_init_system(init)?;
+    }
 
-    fn config_info_dialog() {
-        if CONFIG.has_config() {
+    let json = args.json;
+    let config_info_dialog = || {
+        if !json && CONFIG.has_config() {
             println!("\nUsing settings defined in {} file", CONFIG.get_path().display());
         }
-    }
+    };
 
-    // If no arguments provided, show help
-    if !has_any_flag(&args) {
+    if args.summary {
         println!("\n{}\n", "-".repeat(32) + " auto-cpufreq " + &"-".repeat(33));
         println!("Automatic CPU speed & power optimizer for Linux");
         println!("\nExample usage:\nauto-cpufreq --monitor");
         println!("\n-----\n");
-        
+
         return Ok(());
     }
 
+    // Nothing to do: show the full help and whether the daemon is up, and exit
+    // with clap's usage error code so scripts don't mistake it for success
+    if !has_any_flag(&args) {
+        Args::command().print_help()?;
+        print_daemon_status(&daemon_status());
+        std::process::exit(2);
+    }
+
+    if args.purge && !args.remove {
+        anyhow::bail!("--purge can only be used together with --remove");
+    }
+
+    if args.output.is_some() && !args.monitor && !args.live {
+        anyhow::bail!("--output can only be used together with --monitor or --live");
+    }
+
+    // Without a cpufreq driver there is nothing for these to change
+    if args.live || args.force.is_some() || args.turbo.is_some() {
+        auto_cpufreq::cpu::cpufreq::ensure_supported()?;
+    }
+
+    let override_ttl = match args.override_for {
+        Some(ref value) => {
+            if args.force.is_none() && args.turbo.is_none() {
+                anyhow::bail!("--for can only be used together with --force or --turbo");
+            }
+            Some(parse_override_ttl(value)?)
+        }
+        None => None,
+    };
+
     // Handle force governor override
     if let Some(ref force_val) = args.force {
         not_running_daemon_check()?;
-        root_check()?;
-        let state = AutoCpuFreqState::new();
-        set_override(&state, force_val)?;
+        // Through the daemon, which lets polkit decide for users other than root
+        match auto_cpufreq::control::request(&control_command("set-override", force_val, &args.override_for)) {
+            Some(reply) => println!("{}", reply?),
+            // A daemon without the socket picks the override up at its next check
+            None => {
+                root_check()?;
+                set_override(&AutoCpuFreqState::new(), force_val, override_ttl)?;
+            }
+        }
     }
 
     // Handle turbo override
     if let Some(ref turbo_val) = args.turbo {
         not_running_daemon_check()?;
+        match auto_cpufreq::control::request(&control_command("set-turbo", turbo_val, &args.override_for)) {
+            Some(reply) => println!("{}", reply?),
+            None => {
+                root_check()?;
+                set_turbo_override(&AutoCpuFreqState::new(), turbo_val, override_ttl)?;
+            }
+        }
+    }
+
+    if let Some(ref profile) = args.profile {
+        if profile == "list" {
+            let active = auto_cpufreq::state::StateStore::default().load().last_profile;
+            let names = auto_cpufreq::profiles::available(&CONFIG.snapshot());
+            if json {
+                print_json(&serde_json::json!({ "profiles": names, "active": active }));
+            } else {
+                for name in names {
+                    let marker = if active.as_deref() == Some(name.as_str()) { "*" } else { " " };
+                    println!("{} {}", marker, name);
+                }
+            }
+        } else {
+            not_running_daemon_check()?;
+            match auto_cpufreq::control::request(&format!("set-profile {}", profile)) {
+                Some(reply) => println!("{}", reply?),
+                None => {
+                    root_check()?;
+                    auto_cpufreq::profiles::select(Some(profile))?;
+                }
+            }
+        }
+    }
+
+    if let Some(ref profile) = args.battery_profile {
+        if profile == "list" {
+            let active = auto_cpufreq::state::StateStore::default().load().battery_profile;
+            let snapshot = CONFIG.snapshot();
+            let profiles: Vec<_> = battery::profiles::available(&snapshot)
+                .iter()
+                .filter_map(|name| battery::profiles::load(&snapshot, name).ok())
+                .collect();
+            if json {
+                print_json(&serde_json::json!({ "battery_profiles": profiles, "active": active }));
+            } else {
+                for profile in profiles {
+                    let marker = if active.as_deref() == Some(profile.name.as_str()) { "*" } else { " " };
+                    println!("{} {} ({}-{}%)", marker, profile.name, profile.start_threshold, profile.stop_threshold);
+                }
+            }
+        } else {
+            not_running_daemon_check()?;
+            match auto_cpufreq::control::request(&format!("set-battery-profile {}", profile)) {
+                Some(reply) => println!("{}", reply?),
+                None => {
+                    root_check()?;
+                    battery::profiles::select(Some(profile))?;
+                }
+            }
+        }
+    }
+
+    if args.restore_defaults {
         root_check()?;
-        let state = AutoCpuFreqState::new();
-        set_turbo_override(&state, turbo_val)?;
+        if daemon_status().state == ServiceState::Running {
+            anyhow::bail!("The daemon is running and would change the settings again; stop it first");
+        }
+        if !auto_cpufreq::shutdown::restore_saved()? {
+            println!("No saved settings to restore");
+        }
     }
 
-    if args.monitor {
+    if let Some(ref value) = args.battery_saver {
+        let on = match value.trim().to_lowercase().as_str() {
+            "on" => true,
+            "off" => false,
+            other => anyhow::bail!("Invalid --battery-saver value '{}' (expected on or off)", other),
+        };
         root_check()?;
-        battery::battery_setup(&CONFIG)?;
-        gnome_power_detect().ok();
-        tlp_service_detect().ok();
+        auto_cpufreq::battery_saver::set(on)?;
+    }
+
+    if let Some(ref mode) = args.charge_behaviour {
+        let Some(mode) = battery::charge_behaviour::parse(mode) else {
+            anyhow::bail!(
+                "Invalid charge behaviour '{}' (expected {} or reset)",
+                mode,
+                battery::charge_behaviour::MODES.join(", ")
+            );
+        };
+        root_check()?;
+        let batteries = battery::charge_behaviour::set(mode)?;
+        println!("Charge behaviour set to {} on {}", mode, batteries.join(", "));
+    }
+
+    if let Some(ref floor) = args.calibrate_battery {
+        root_check()?;
+        match floor.trim() {
+            "cancel" => battery::calibration::finish()?,
+            "" => battery::calibration::run(battery::calibration::DEFAULT_FLOOR)?,
+            floor => {
+                let floor = floor
+                    .parse::<u8>()
+                    .map_err(|_| anyhow::anyhow!("Invalid calibration floor '{}' (expected a percentage or cancel)", floor))?;
+                battery::calibration::run(floor)?;
+            }
+        }
+    }
+
+    if args.monitor {
+        // Conflict warnings are interactive, keep them out of JSON/CSV output
+        let quiet = json || args.output.is_some();
+
+        // Without root the dashboard still works, read-only
+        if nix::unistd::Uid::effective().is_root() {
+            battery::battery_setup(&CONFIG)?;
+        } else if !quiet {
+            tracing::warn!(code = %EventCode::NotRoot, "Not running as root: battery thresholds are not applied and fields marked \"needs root\" can't be read");
+        }
+        if !quiet {
+            gnome_power_detect().ok();
+            tlp_service_detect().ok();
+        }
 
-        if *TLP_STAT_EXISTS || (*SYSTEMCTL_EXISTS && gnome_power_status()?) {
+        if !quiet && (*TLP_STAT_EXISTS || (*SYSTEMCTL_EXISTS && gnome_power_status()?)) {
             println!("press Enter to continue or Ctrl + C to exit...");
             let mut input = String::new();
             std::io::stdin().read_line(&mut input)?;
         }
 
+        if !quiet && !auto_cpufreq::cpu::cpufreq::supported() {
+            tracing::warn!(code = %EventCode::CpufreqUnavailable, "{}; only showing what can be read", auto_cpufreq::cpu::cpufreq::missing_reason());
+        }
+
         let mut monitor = SystemMonitor::new_with_verbose(ViewType::Monitor, true, args.verbose);
-        monitor.run_blocking();
+        if let Some(format) = args.output {
+            export_samples(&mut monitor, format, &args)?;
+        } else if json {
+            monitor.run_blocking_json();
+        } else {
+            monitor.run_blocking();
+        }
         
     } else if args.live {
         root_check()?;
+        running_daemon_check()?;
         battery::battery_setup(&CONFIG)?;
 
-        gnome_power_detect_install().ok();
-        gnome_power_stop_live().ok();
-        tuned_stop_live().ok();
-        tlp_service_detect().ok();
+        // Same as --monitor: nothing but samples on stdout with --json/--output
+        let quiet = json || args.output.is_some();
+        if !quiet {
+            gnome_power_detect_install().ok();
+            tlp_service_detect().ok();
+        }
 
-        if *TLP_STAT_EXISTS || (*SYSTEMCTL_EXISTS && gnome_power_status()?) {
+        if !quiet && (*TLP_STAT_EXISTS || (*SYSTEMCTL_EXISTS && gnome_power_status()?)) {
             println!("press Enter to continue or Ctrl + C to exit...");
             let mut input = String::new();
             std::io::stdin().read_line(&mut input)?;
@@ -155,40 +481,87 @@ fn main() -> Result<()> {
 
         cpufreqctl()?;
 
-        // Spawn daemon thread
-        let daemon_handle = thread::spawn(|| {
-            loop {
-                thread::sleep(Duration::from_secs(1));
-                // set_autofreq() would be called here
-            }
-        });
-
+        // Adjusts the CPU on every refresh of the view
         let mut monitor = SystemMonitor::new_with_verbose(ViewType::Live, false, args.verbose);
-        monitor.run_blocking();
-        
-        daemon_handle.join().unwrap();
+        monitor.controller = Some(auto_cpufreq::live::Controller::start()?);
+        let result = if let Some(format) = args.output {
+            export_samples(&mut monitor, format, &args)
+        } else {
+            if json {
+                monitor.run_blocking_json();
+            } else {
+                monitor.run_blocking();
+            }
+            Ok(())
+        };
+        if let Some(controller) = monitor.controller.take() {
+            controller.finish();
+        }
+        result?;
         
     } else if args.daemon {
         config_info_dialog();
         root_check()?;
+        // Held until the daemon exits
+        let _pid_lock = auto_cpufreq::pidfile::PidLock::acquire()?;
         gnome_power_detect()?;
         tlp_service_detect()?;
 
+        if let Err(e) = battery::calibration::recover_stale() {
+            tracing::warn!(code = %EventCode::StateWriteFailed, "Failed to clear the interrupted battery calibration: {}", e);
+        }
         battery::battery_setup(&CONFIG)?;
+
+        if auto_cpufreq::ppd_shim::enabled(&CONFIG.snapshot()) {
+            std::thread::spawn(|| {
+                if let Err(e) = auto_cpufreq::ppd_shim::serve() {
+                    tracing::warn!(code = %EventCode::PpdShimFailed, "power-profiles-daemon shim stopped: {}", e);
+                }
+            });
+        }
         
+        // Without cpufreq only battery thresholds and the stats are kept up
+        let cpufreq = auto_cpufreq::cpu::cpufreq::supported();
+        if !cpufreq {
+            tracing::warn!(
+                code = %EventCode::CpufreqUnavailable,
+                "{}; the daemon will only manage battery thresholds and record stats",
+                auto_cpufreq::cpu::cpufreq::missing_reason()
+            );
+        }
+
+        if let Err(e) = auto_cpufreq::shutdown::remember() {
+            tracing::warn!(code = %EventCode::StateWriteFailed, "Failed to save the current settings, they won't be restored on exit: {}", e);
+        }
+
         println!("\n* Starting auto-cpufreq daemon");
-        println!("* Monitoring system and adjusting CPU frequency...\n");
+        if cpufreq {
+            println!("* Monitoring system and adjusting CPU frequency...\n");
+        } else {
+            println!("* Monitoring system (frequency scaling not available)...\n");
+        }
+
+        // Charger changes and resume from suspend wake the loop immediately
+        let mut wakeups = Wakeups::open();
+        // Pinged every cycle, so no wait may outlast half of WatchdogSec
+        let watchdog = auto_cpufreq::sd_notify::watchdog_interval();
+        let mut interval = PollInterval::new();
+        let mut schedule = CycleSchedule::new();
+        // What the last config reload was compared against
+        let mut applied_config = CONFIG.snapshot();
 
         loop {
             footer(79);
             
             // Update stats file
-            if let Err(e) = update_stats_file() {
-                eprintln!("WARNING: Failed to update stats file: {}", e);
+            if let Err(e) = refresh_stats_file() {
+                tracing::warn!(code = %EventCode::StateWriteFailed, "Failed to update stats file: {}", e);
             }
             
             // Ensure cpufreqctl is available
-            cpufreqctl()?;
+            if cpufreq {
+                cpufreqctl()?;
+            }
             
             // Show system info (first iteration only)
             static FIRST_RUN: std::sync::Once = std::sync::Once::new();
@@ -198,13 +571,73 @@ fn main() -> Result<()> {
             });
             
             // Main frequency adjustment logic
-            if let Err(e) = set_autofreq() {
-                eprintln!("ERROR: Failed to set auto frequency: {}", e);
+            // Re-read every cycle so config reloads take effect
+            let limits = IntervalLimits::from_config(&CONFIG.snapshot());
+            let (wait, status) = if !cpufreq {
+                (limits.max, "frequency scaling not available".to_string())
+            } else {
+                match set_autofreq() {
+                    Ok(stats) => (
+                        interval.next(&limits, stats.cpu_usage, stats.is_charging),
+                        auto_cpufreq::sd_notify::status_line(stats.governor, stats.is_charging),
+                    ),
+                    Err(e) => {
+                        tracing::error!(code = %EventCode::CycleFailed, "Failed to set auto frequency: {}", e);
+                        (limits.min, format!("cycle failed: {}", e))
+                    }
+                }
+            };
+            auto_cpufreq::sd_notify::cycle_done(&status);
+            let wait = watchdog.map_or(wait, |watchdog| wait.min(watchdog));
+            // Switch profiles on time rather than up to an interval late
+            let wait = auto_cpufreq::schedule::until_next_change()
+                .map_or(wait, |until| wait.min(until + std::time::Duration::from_secs(1)));
+            auto_cpufreq::telemetry::maybe_send();
+
+            println!("\t\t\"auto-cpufreq\" is about to refresh (next check in {}s)", wait.as_secs_f64());
+            let wakeup = wakeups.wait_until(schedule.next(wait));
+            if wakeup.is_some() {
+                schedule.reset();
             }
-            
-            countdown(2);
+            match wakeup {
+                Some(Wakeup::Shutdown) => {
+                    println!("\n* Stopping auto-cpufreq daemon");
+                    auto_cpufreq::sd_notify::stopping();
+                    auto_cpufreq::shutdown::on_exit();
+                    break;
+                }
+                Some(Wakeup::PowerSupply) => println!("\t\tPower source changed"),
+                Some(Wakeup::Control) => println!("\t\tSettings changed over the control socket"),
+                Some(Wakeup::ConfigReload) => {
+                    // Governor, turbo and EPP follow in the cycle that starts right away
+                    let current = CONFIG.snapshot();
+                    let changes = applied_config.changes(&current);
+                    let summary: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+                    tracing::info!(code = %EventCode::ConfigReloaded, "Config reloaded: {}", summary.join(", "));
+                    // [battery] and [battery.BAT1], not [battery_levels] or [battery_saver]
+                    let thresholds = |section: &str| section == "battery" || section.starts_with("battery.");
+                    if changes.iter().any(|change| thresholds(&change.section)) {
+                        if let Err(e) = battery::battery_setup(&CONFIG) {
+                            tracing::warn!(code = %EventCode::BatterySetupFailed, "Failed to re-apply battery thresholds: {}", e);
+                        }
+                    }
+                    applied_config = current;
+                }
+                Some(Wakeup::Resume) => {
+                    // Firmware may have reset thresholds, governor and turbo while suspended
+                    println!("\t\tResumed from suspend, re-applying settings");
+                    if let Err(e) = battery::battery_setup(&CONFIG) {
+                        tracing::warn!(code = %EventCode::BatterySetupFailed, "Failed to re-apply battery thresholds: {}", e);
+                    }
+                }
+                None => {}
+            }
+            println!("\t\tExecuted on: {}", chrono::Local::now().format("%c"));
         }
         
+    } else if args.install && args.dry_run {
+        install_daemon(true)?;
+
     } else if args.install {
         root_check()?;
         
@@ -212,7 +645,7 @@ fn main() -> Result<()> {
         tlp_service_detect()?;
         
         // Install daemon using appropriate init system
-        install_daemon()?;
+        install_daemon(false)?;
         
         println!("\nauto-cpufreq daemon installed and started");
         println!("\nTo view live stats, run:\nauto-cpufreq --stats");
@@ -239,13 +672,13 @@ fn main() -> Result<()> {
             let ans = input.trim().to_lowercase();
             if ans.is_empty() || ans == "y" || ans == "yes" {
                 // First remove the old daemon
-                remove_daemon()?;
+                remove_daemon(false)?;
                 
                 // TODO: implement new_update(&custom_dir)?;
                 println!("\nRe-enabling daemon...");
                 
                 // Reinstall daemon
-                install_daemon()?;
+                install_daemon(false)?;
                 
                 println!("\nauto-cpufreq is updated to the latest version");
                 app_version();
@@ -255,19 +688,40 @@ fn main() -> Result<()> {
         }
         
     } else if args.remove {
-        root_check()?;
-        remove_daemon()?;
+        if !args.dry_run {
+            root_check()?;
+        }
+        remove_daemon(args.dry_run)?;
+        // Stopping the service restores them unless restore_on_exit is off
+        if !args.dry_run && auto_cpufreq::shutdown::restore_saved()? {
+            println!("Restored the CPU settings from before the daemon started");
+        }
+        // Last, the saved settings above live in the state file
+        if args.purge {
+            auto_cpufreq::core::purge(args.dry_run)?;
+        }
         
     } else if args.stats {
-        root_check()?;
-
-        not_running_daemon_check()?;
+        let daemon = daemon_status();
+        if daemon.state != ServiceState::Running {
+            if json {
+                print_json(&serde_json::json!({ "daemon": daemon }));
+            } else {
+                print_daemon_status(&daemon);
+            }
+            anyhow::bail!("Daemon not running");
+        }
+        if daemon.stale && !json {
+            print_daemon_status(&daemon);
+        }
         config_info_dialog();
         
-        gnome_power_detect()?;
-        tlp_service_detect()?;
+        if !json {
+            gnome_power_detect()?;
+            tlp_service_detect()?;
+        }
 
-        if *TLP_STAT_EXISTS || (*SYSTEMCTL_EXISTS && gnome_power_status()?) {
+        if !json && (*TLP_STAT_EXISTS || (*SYSTEMCTL_EXISTS && gnome_power_status()?)) {
             println!("press Enter to continue or Ctrl + C to exit...");
             let mut input = String::new();
             std::io::stdin().read_line(&mut input)?;
@@ -275,6 +729,15 @@ fn main() -> Result<()> {
 
         let mut monitor = SystemMonitor::new_with_verbose(ViewType::Stats, false, args.verbose);
         monitor.update();
+
+        if json {
+            print_json(&serde_json::json!({
+                "config": CONFIG.has_config().then(|| CONFIG.get_path()),
+                "daemon": daemon,
+                "report": monitor.report,
+            }));
+            return Ok(());
+        }
         
         let rows = std::cmp::max(monitor.left.len(), monitor.right.len());
         let width = 80usize;
@@ -285,11 +748,43 @@ fn main() -> Result<()> {
             println!("{:<half$} │ {}", left, right, half=half);
         }
         
+    } else if args.watch {
+        #[cfg(feature = "metrics")]
+        watch::run_watch(args.changes_only, args.json)?;
+        #[cfg(not(feature = "metrics"))]
+        anyhow::bail!("--watch is not available in this build (enable the \"metrics\" feature)");
+        
+    } else if let Some(ref report_path) = args.export_report {
+        config_info_dialog();
+        #[cfg(feature = "metrics")]
+        report::export_report(std::path::Path::new(report_path))?;
+        #[cfg(not(feature = "metrics"))]
+        anyhow::bail!("--export-report {} is not available in this build (enable the \"metrics\" feature)", report_path);
+        
+    } else if let Some(ref bundle) = args.export_settings {
+        bundle::export(std::path::Path::new(bundle))?;
+
+    } else if let Some(ref bundle) = args.import_settings {
+        root_check()?;
+        bundle::import(std::path::Path::new(bundle))?;
+
     } else if args.get_state {
         not_running_daemon_check()?;
         let state = AutoCpuFreqState::new();
         let override_val = get_override(&state);
-        println!("{}", override_val.to_str());
+        if json {
+            let stored = state.store().load();
+            print_json(&serde_json::json!({
+                "override": override_val.to_str(),
+                "override_until": stored.governor_override_until,
+                "turbo_override": get_turbo_override(&state).to_str(),
+                "turbo_override_until": stored.turbo_override_until,
+                "profile": stored.last_profile,
+                "battery_profile": stored.battery_profile,
+            }));
+        } else {
+            println!("{}", override_val.to_str());
+        }
         
     } else if args.bluetooth_boot_off {
         footer(79);
@@ -306,6 +801,30 @@ fn main() -> Result<()> {
     } else if args.debug {
         config_info_dialog();
         root_check()?;
+
+        if json {
+            cpufreqctl()?;
+            let mut monitor = SystemMonitor::new_with_verbose(ViewType::Stats, false, true);
+            monitor.update();
+            let laptop_modules = battery::LaptopModule::detect();
+            tracing::warn!(code = %EventCode::Deprecated, "The laptop_module field of --debug --json is deprecated, read laptop_modules instead");
+            print_json(&serde_json::json!({
+                "version": get_version().ok(),
+                "config": CONFIG.has_config().then(|| CONFIG.get_path()),
+                "charging": charging().ok(),
+                "laptop_modules": laptop_modules.iter().map(|m| m.name()).collect::<Vec<_>>(),
+                // Deprecated alias from when only one backend was set up
+                "laptop_module": laptop_modules.first().map_or("none", |m| m.name()),
+                "governor": get_current_gov().ok(),
+                "report": monitor.report,
+                "thermal_zones": auto_cpufreq::thermal::zones(),
+                "turbo_temp_limit": auto_cpufreq::thermal::turbo_temp_limit(),
+                "skin_temp_limit": auto_cpufreq::thermal::skin_temp_limit(),
+                "intel_lpmd": lpmd_state(),
+            }));
+            return Ok(());
+        }
+
         battery::battery_get_thresholds()?;
         cpufreqctl()?;
         footer(79);
@@ -319,9 +838,131 @@ fn main() -> Result<()> {
         get_load();
         print_current_gov();
         get_turbo();
+        println!("Turbo temperature limit: {:.0} °C", auto_cpufreq::thermal::turbo_temp_limit());
+        if let Some(lpmd) = lpmd_state() {
+            println!("Intel LPMD: {}", lpmd);
+        }
+        if let Some(skin) = auto_cpufreq::thermal::skin_temp() {
+            println!("Skin temperature: {:.1} °C (limit {:.0} °C)", skin, auto_cpufreq::thermal::skin_temp_limit());
+        }
         footer(79);
         
+    } else if args.show_effective_config {
+        let effective = auto_cpufreq::config::effective::effective(args.config.is_some());
+        if json {
+            print_json(&serde_json::json!(effective));
+        } else {
+            footer(79);
+            effective.print();
+            footer(79);
+        }
+
+    } else if args.check_config {
+        if !CONFIG.has_config() {
+            anyhow::bail!("No config file found at {}", CONFIG.get_path().display());
+        }
+        let check = auto_cpufreq::config::schema::check_file(&CONFIG.get_path())?;
+        if json {
+            print_json(&serde_json::json!(check));
+        } else {
+            check.print();
+        }
+        // Non-zero with problems, for packaging and config management checks
+        if !check.findings.is_empty() {
+            std::process::exit(1);
+        }
+
+    } else if args.migrate_config {
+        let source = CONFIG.get_path();
+        let target = auto_cpufreq::config::migrate::migrate(&source)?;
+        println!("Converted {} to {}", source.display(), target.display());
+        match args.config {
+            Some(_) => println!("Pass --config {} to use it.", target.display()),
+            None => println!("{} is read from now on; the old file is kept but ignored, delete it once you no longer need it.", target.display()),
+        }
+        println!("Comments weren't carried over. Run `auto-cpufreq --check-config` to check the result.");
+
+    } else if let Some(ref name) = args.config_get {
+        match auto_cpufreq::config::edit::get(&CONFIG.get_path(), name)? {
+            Some(value) => println!("{}", value),
+            // Like `git config`, nothing printed and status 1 for an unset key
+            None => std::process::exit(1),
+        }
+
+    } else if let Some(ref set) = args.config_set {
+        let path = CONFIG.get_path();
+        auto_cpufreq::config::edit::set(&path, &set[0], &set[1])?;
+        println!("Set {} = {} in {}", set[0], set[1], path.display());
+        reload_daemon_config();
+
+    } else if let Some(ref name) = args.config_unset {
+        let path = CONFIG.get_path();
+        if auto_cpufreq::config::edit::unset(&path, name)? {
+            println!("Removed {} from {}", name, path.display());
+            reload_daemon_config();
+        } else {
+            println!("{} is not set in {}", name, path.display());
+        }
+
+    } else if args.capabilities {
+        let capabilities = auto_cpufreq::capabilities::Capabilities::detect();
+        if json {
+            print_json(&serde_json::json!(capabilities));
+        } else {
+            footer(79);
+            capabilities.print();
+            footer(79);
+        }
+
+    } else if args.history {
+        let since = match args.since {
+            Some(ref value) => Some(chrono::Local::now().timestamp() - parse_override_ttl(value)?.as_secs() as i64),
+            None => None,
+        };
+        let samples = auto_cpufreq::stats_history::History::default().samples(since);
+        if json {
+            print_json(&serde_json::json!({ "samples": samples }));
+        } else if args.csv {
+            print!("{}", auto_cpufreq::stats_history::to_csv(&samples));
+        } else {
+            println!("{}", auto_cpufreq::stats_history::timeline(&samples));
+        }
+
+    } else if let Some(count) = args.why {
+        let log = auto_cpufreq::audit::AuditLog::default();
+        // Oldest first, so it reads like a story
+        let decisions: Vec<_> = log.recent(count).into_iter().rev().collect();
+        if json {
+            print_json(&serde_json::json!({ "decisions": decisions }));
+        } else if decisions.is_empty() {
+            println!("No decisions recorded yet in {}", log.path().display());
+        } else {
+            for decision in decisions {
+                println!("{}\n", decision.explain());
+            }
+        }
+
+    } else if let Some(cycles) = args.bench_cycle {
+        root_check()?;
+        running_daemon_check()?;
+        cpufreqctl()?;
+        bench_cycles(cycles.max(1), json)?;
+
+    } else if args.telemetry_preview {
+        auto_cpufreq::telemetry::preview()?;
+
     } else if args.version {
+        if json {
+            let info = SystemInfo::new();
+            print_json(&serde_json::json!({
+                "version": get_version().ok(),
+                "distro_name": info.distro_name,
+                "distro_version": info.distro_version,
+                "kernel": info.kernel_version,
+            }));
+            return Ok(());
+        }
+
         footer(79);
         distro_info()?;
         app_version();
@@ -338,10 +979,104 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// `--output`: stream the monitor's samples to stdout or `--output-file`
+fn export_samples(monitor: &mut SystemMonitor, format: OutputFormat, args: &Args) -> Result<()> {
+    use anyhow::Context;
+
+    let duration = args.duration.as_deref().map(parse_override_ttl).transpose()?;
+    let mut out: Box<dyn std::io::Write> = match &args.output_file {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout()),
+    };
+
+    match monitor.run_export(format, duration, &mut out) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e).context("Failed to write samples"),
+        _ => Ok(()),
+    }
+}
+
+/// `--bench-cycle`: run `cycles` daemon cycles back to back and report how
+/// long each took. The first one has no earlier /proc/stat reading to diff
+/// against and includes the sampling wait. The settings from before are put
+/// back afterwards.
+fn bench_cycles(cycles: usize, json: bool) -> Result<()> {
+    auto_cpufreq::shutdown::remember()?;
+    let mut times = Vec::with_capacity(cycles);
+    let mut failure = None;
+    for _ in 0..cycles {
+        let start = std::time::Instant::now();
+        if let Err(e) = set_autofreq() {
+            failure = Some(e);
+            break;
+        }
+        times.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    auto_cpufreq::shutdown::restore_saved()?;
+    if let Some(e) = failure {
+        return Err(e.context("Daemon cycle failed"));
+    }
+
+    let mut sorted = times.clone();
+    sorted.sort_by(f64::total_cmp);
+    let (min, median, max) = (sorted[0], sorted[sorted.len() / 2], sorted[sorted.len() - 1]);
+    if json {
+        print_json(&serde_json::json!({ "cycles_ms": times, "min_ms": min, "median_ms": median, "max_ms": max }));
+    } else {
+        println!();
+        for (i, ms) in times.iter().enumerate() {
+            println!("cycle {:>3}: {:>8.2} ms{}", i + 1, ms, if i == 0 { " (cold)" } else { "" });
+        }
+        println!("min {:.2} ms, median {:.2} ms, max {:.2} ms", min, median, max);
+    }
+    Ok(())
+}
+
+/// `--force`/`--turbo` as a control socket command, with the `--for` duration
+fn control_command(command: &str, value: &str, ttl: &Option<String>) -> String {
+    match ttl {
+        Some(ttl) => format!("{} {} {}", command, value, ttl),
+        None => format!("{} {}", command, value),
+    }
+}
+
+fn print_json(value: &serde_json::Value) {
+    match serde_json::to_string_pretty(value) {
+        Ok(s) => println!("{}", s),
+        Err(e) => eprintln!("Failed to serialize JSON output: {}", e),
+    }
+}
+
+/// After editing the config file, so a running daemon applies it right away
+/// rather than when its file watcher (if built in) notices
+fn reload_daemon_config() {
+    match auto_cpufreq::control::request("reload-config") {
+        Some(Ok(_)) => println!("The running daemon reloaded the config"),
+        Some(Err(e)) => eprintln!("WARNING: The daemon didn't reload the config: {}", e),
+        None => {}
+    }
+}
+
 fn has_any_flag(args: &Args) -> bool {
     args.monitor || args.live || args.daemon || args.install || 
-    args.update.is_some() || args.remove || args.force.is_some() || 
-    args.turbo.is_some() || args.stats || args.get_state || 
+    args.update.is_some() || args.remove || args.purge || args.restore_defaults || args.force.is_some() || 
+    args.turbo.is_some() || args.override_for.is_some() || args.profile.is_some() || args.battery_saver.is_some() || args.battery_profile.is_some() || args.charge_behaviour.is_some() || args.calibrate_battery.is_some() || args.stats || args.get_state || 
+    args.watch || args.export_report.is_some() || args.export_settings.is_some() || args.import_settings.is_some() || 
     args.bluetooth_boot_off || args.bluetooth_boot_on || 
-    args.debug || args.version || args.donate
+    args.debug || args.show_effective_config || args.check_config || args.migrate_config || args.config_get.is_some() || args.config_set.is_some() || args.config_unset.is_some() || args.capabilities || args.history || args.why.is_some() || args.bench_cycle.is_some() || args.telemetry_preview || args.version || args.donate || args.summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+        assert!(Args::try_parse_from(["auto-cpufreq", "--install", "--dry-run"]).is_ok());
+        assert!(Args::try_parse_from(["auto-cpufreq", "--dry-run"]).is_err());
+        assert!(Args::try_parse_from(["auto-cpufreq", "--summary"]).is_ok());
+        assert!(Args::try_parse_from(["auto-cpufreq", "--summary", "--install"]).is_err());
+    }
 }
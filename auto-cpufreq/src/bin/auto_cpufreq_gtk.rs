@@ -4,6 +4,7 @@
 
 #[cfg(feature = "gui")]
 fn main() {
+    auto_cpufreq::logging::init(&Default::default()).ok();
     auto_cpufreq::gui::app::run_app();
 }
 
@@ -1,4 +1,5 @@
 fn main() {
+    auto_cpufreq::logging::init(&Default::default()).ok();
     auto_cpufreq::gui::tray::TrayApp::run();
     loop {
         std::thread::park();
@@ -0,0 +1,108 @@
+// src/boot_boost.rs
+//
+// Boot boost. For the first `[boot_boost] duration` seconds after the daemon
+// starts at boot, `set_autofreq` uses the performance governor with turbo, on
+// battery too, so login and session start aren't slowed down by powersave.
+// A daemon (re)started later in the session never boosts.
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::profiles::{Profile, ProfileSwitch};
+
+/// The daemon counts as started at boot when the system has been up for less
+/// than this on its first cycle
+const BOOT_START_UPTIME: Duration = Duration::from_secs(180);
+
+lazy_static::lazy_static! {
+    /// First cycle of a daemon started at boot, `None` otherwise
+    static ref BOOT_START: Option<Instant> = uptime()
+        .is_some_and(|uptime| uptime < BOOT_START_UPTIME)
+        .then(Instant::now);
+}
+
+/// Keyed by the boost duration
+static SWITCH: ProfileSwitch<Duration> = ProfileSwitch::new();
+
+/// `[boot_boost] duration`, `None` when the section or key is left out or 0
+pub fn duration(config: &Snapshot) -> Result<Option<Duration>> {
+    match config.get("boot_boost", "duration") {
+        None => Ok(None),
+        Some(value) => match value.trim().parse::<u64>() {
+            Ok(0) => Ok(None),
+            Ok(secs) => Ok(Some(Duration::from_secs(secs))),
+            Err(_) => bail!("Invalid duration in [boot_boost]: {} (expected seconds)", value),
+        },
+    }
+}
+
+/// Parse the first field of /proc/uptime
+fn parse_uptime(content: &str) -> Option<Duration> {
+    let secs = content.split_whitespace().next()?.parse::<f64>().ok()?;
+    (secs >= 0.0 && secs.is_finite()).then(|| Duration::from_secs_f64(secs))
+}
+
+fn uptime() -> Option<Duration> {
+    parse_uptime(&fs::read_to_string("/proc/uptime").ok()?)
+}
+
+fn boost_profile() -> Profile {
+    Profile {
+        name: "boot_boost".to_string(),
+        governor: Some("performance".to_string()),
+        turbo: Some("always".to_string()),
+        ..Profile::default()
+    }
+}
+
+/// The boost profile while `elapsed` is still inside the boot window
+fn current(elapsed: Option<Duration>) -> Option<(Duration, Profile)> {
+    let duration = match duration(&CONFIG.snapshot()) {
+        Ok(duration) => duration?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            return None;
+        }
+    };
+    elapsed.is_some_and(|elapsed| elapsed < duration).then(|| (duration, boost_profile()))
+}
+
+/// Called by `set_autofreq`: performance with turbo while the boot window is
+/// open, then `None` so the regular policy takes over
+pub fn profile() -> Option<Profile> {
+    SWITCH.follow(current(BOOT_START.map(|start| start.elapsed())), |boost| match boost {
+        Some((duration, _)) => info!("Boot boost: performance for the first {}s", duration.as_secs()),
+        None => info!("Boot boost over, back to the regular settings"),
+    })
+}
+
+/// Outside the daemon the boot window is measured from boot rather than from
+/// the daemon's start.
+pub fn preview() -> Option<Profile> {
+    SWITCH.preview(current(uptime()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boot_boost_duration() {
+        let config = Snapshot::from_str_ini("[boot_boost]\nduration = 90\n").unwrap();
+        assert_eq!(duration(&config).unwrap(), Some(Duration::from_secs(90)));
+        assert_eq!(duration(&Snapshot::from_str_ini("[boot_boost]\nduration = 0\n").unwrap()).unwrap(), None);
+        assert_eq!(duration(&Snapshot::from_str_ini("[charger]\nturbo = auto\n").unwrap()).unwrap(), None);
+        assert!(duration(&Snapshot::from_str_ini("[boot_boost]\nduration = 1m\n").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_uptime() {
+        assert_eq!(parse_uptime("42.50 160.03\n"), Some(Duration::from_millis(42_500)));
+        assert_eq!(parse_uptime(""), None);
+    }
+}
@@ -0,0 +1,347 @@
+// src/bundle.rs
+//
+// Settings bundle for moving a tuned setup to another machine or keeping it
+// across a reinstall (--export-settings / --import-settings). The bundle is a
+// plain tar archive holding the config file (governors, profiles, battery
+// thresholds and everything else set there), the portable part of the runtime
+// state (overrides, selected profile and battery profile) and a manifest
+// describing its origin. What the daemon remembers about this machine's
+// hardware (settings to restore on exit, parked cores, battery saver and
+// calibration) stays behind.
+// The config keeps its format; an INI one imported where a TOML config is read
+// is converted.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::battery::LaptopModule;
+use crate::config::migrate::to_toml;
+use crate::config::snapshot::is_toml;
+use crate::config::{CONFIG, Snapshot};
+use crate::state::{State, StateStore};
+
+const FORMAT_VERSION: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+const CONFIG_FILE: &str = "auto-cpufreq.conf";
+const TOML_CONFIG_FILE: &str = "auto-cpufreq.toml";
+const STATE_FILE: &str = "state.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    /// auto-cpufreq version that wrote the bundle
+    pub version: String,
+    pub created: String,
+    pub hostname: String,
+    /// Battery backends of the source machine, see `LaptopModule::describe`
+    pub laptop_module: String,
+    pub has_config: bool,
+}
+
+/// The part of `State` that means the same on another machine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortableState {
+    pub governor_override: Option<String>,
+    pub governor_override_until: Option<i64>,
+    pub governor_override_previous: Option<String>,
+    pub turbo_override: Option<String>,
+    pub turbo_override_until: Option<i64>,
+    pub turbo_override_previous: Option<String>,
+    pub last_profile: Option<String>,
+    pub battery_profile: Option<String>,
+}
+
+impl PortableState {
+    fn from_state(state: &State) -> Self {
+        Self {
+            governor_override: state.governor_override.clone(),
+            governor_override_until: state.governor_override_until,
+            governor_override_previous: state.governor_override_previous.clone(),
+            turbo_override: state.turbo_override.clone(),
+            turbo_override_until: state.turbo_override_until,
+            turbo_override_previous: state.turbo_override_previous.clone(),
+            last_profile: state.last_profile.clone(),
+            battery_profile: state.battery_profile.clone(),
+        }
+    }
+
+    /// Put these fields into `state`, leaving the machine's own ones alone
+    fn merge_into(self, state: &mut State) {
+        state.governor_override = self.governor_override;
+        state.governor_override_until = self.governor_override_until;
+        state.governor_override_previous = self.governor_override_previous;
+        state.turbo_override = self.turbo_override;
+        state.turbo_override_until = self.turbo_override_until;
+        state.turbo_override_previous = self.turbo_override_previous;
+        state.last_profile = self.last_profile;
+        state.battery_profile = self.battery_profile;
+    }
+}
+
+/// What `import` changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSummary {
+    pub manifest: Manifest,
+    /// Config file written, if the bundle had one
+    pub config: Option<PathBuf>,
+    /// Copy of the config that was replaced
+    pub backup: Option<PathBuf>,
+    pub state: State,
+}
+
+/// Scratch directory for building or unpacking a bundle, removed on drop.
+/// Created with a random name and mode 0700, never reusing an existing one.
+struct Staging(tempfile::TempDir);
+
+impl Staging {
+    fn new(purpose: &str) -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("auto-cpufreq-{}-", purpose))
+            .tempdir()
+            .context("Failed to create a staging directory")?;
+        Ok(Self(dir))
+    }
+
+    fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// A member of an unpacked bundle, which has to be a regular file: a
+    /// symlink in the archive could point anywhere on this machine
+    fn member(&self, name: &str) -> Result<PathBuf> {
+        let path = self.path().join(name);
+        let metadata = fs::symlink_metadata(&path).with_context(|| format!("Bundle has no {}", name))?;
+        if !metadata.file_type().is_file() {
+            bail!("{} in bundle is not a regular file", name);
+        }
+        Ok(path)
+    }
+}
+
+/// Creates or unpacks files, so it isn't cut short by a timeout
+fn tar(args: &[&str]) -> Result<()> {
+    let output = crate::command::change("tar", args)?;
+    if !output.success() {
+        bail!("tar {} failed: {}", args.first().unwrap_or(&""), output.stderr.trim());
+    }
+    Ok(())
+}
+
+fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Write a bundle of `config` (if any) and the state in `store` to `bundle`
+pub fn export_to(bundle: &Path, config: Option<&Path>, store: &StateStore) -> Result<Manifest> {
+    let staging = Staging::new("export")?;
+
+    let mut files = vec![MANIFEST, STATE_FILE];
+    if let Some(config) = config {
+        let name = if is_toml(config) { TOML_CONFIG_FILE } else { CONFIG_FILE };
+        fs::copy(config, staging.path().join(name))
+            .with_context(|| format!("Failed to read {}", config.display()))?;
+        files.push(name);
+    }
+
+    let state = PortableState::from_state(&store.load());
+    fs::write(staging.path().join(STATE_FILE), serde_json::to_string_pretty(&state)?)?;
+
+    let manifest = Manifest {
+        format: FORMAT_VERSION,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created: chrono::Local::now().to_rfc3339(),
+        hostname: hostname(),
+        laptop_module: LaptopModule::describe(&LaptopModule::detect()),
+        has_config: config.is_some(),
+    };
+    fs::write(staging.path().join(MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+
+    let bundle = bundle.to_string_lossy();
+    let staging_dir = staging.path().to_string_lossy();
+    let mut args = vec!["-cf", bundle.as_ref(), "-C", staging_dir.as_ref()];
+    args.extend(files);
+    tar(&args).with_context(|| format!("Failed to write {}", bundle))?;
+
+    Ok(manifest)
+}
+
+/// Unpack `bundle`, install its config at `config_target` (keeping the old
+/// one as `<name>.bak`) and merge the bundled state into `store`
+pub fn import_from(bundle: &Path, config_target: &Path, store: &StateStore) -> Result<ImportSummary> {
+    let staging = Staging::new("import")?;
+    tar(&["-xf", &bundle.to_string_lossy(), "-C", &staging.path().to_string_lossy()])
+        .with_context(|| format!("Failed to unpack {}", bundle.display()))?;
+
+    let manifest: Manifest = serde_json::from_str(
+        &fs::read_to_string(staging.member(MANIFEST).context("Not a settings bundle")?)?,
+    )
+    .context("Invalid manifest.json")?;
+    if manifest.format != FORMAT_VERSION {
+        bail!("Unsupported bundle format {} (expected {})", manifest.format, FORMAT_VERSION);
+    }
+
+    // Bundles from before PortableState hold the whole State, its other fields are ignored
+    let portable: PortableState = serde_json::from_str(&fs::read_to_string(staging.member(STATE_FILE)?)?)
+        .context("Invalid state.json in bundle")?;
+
+    let (mut config, mut backup) = (None, None);
+    if manifest.has_config {
+        let bundled = [TOML_CONFIG_FILE, CONFIG_FILE]
+            .into_iter()
+            .find(|name| fs::symlink_metadata(staging.path().join(name)).is_ok())
+            .map(|name| staging.member(name))
+            .context("Bundle is missing its config file")??;
+        let content = fs::read_to_string(&bundled)?;
+        let snapshot = match Snapshot::load(&bundled) {
+            Ok(snapshot) => snapshot,
+            Err(e) => bail!("Config file in bundle doesn't parse: {}", e),
+        };
+        let (config_target, content) = match (is_toml(&bundled), is_toml(config_target)) {
+            (true, _) => (config_target.with_extension("toml"), content),
+            // An INI file next to the TOML one in use would never be read
+            (false, true) => (config_target.to_path_buf(), to_toml(&snapshot, &bundle.display().to_string())),
+            (false, false) => (config_target.to_path_buf(), content),
+        };
+        let config_target = config_target.as_path();
+
+        if config_target.exists() {
+            let mut name = config_target.as_os_str().to_owned();
+            name.push(".bak");
+            let backup_path = PathBuf::from(name);
+            fs::copy(config_target, &backup_path)
+                .with_context(|| format!("Failed to back up {}", config_target.display()))?;
+            backup = Some(backup_path);
+        }
+        if let Some(parent) = config_target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(config_target, content).with_context(|| format!("Failed to write {}", config_target.display()))?;
+        config = Some(config_target.to_path_buf());
+    }
+
+    // Under the store's lock, as the running daemon writes it too; lapsed
+    // overrides revert once merged
+    let state = store.update(|state| {
+        portable.merge_into(state);
+        state.expire(chrono::Local::now().timestamp());
+    })?;
+
+    Ok(ImportSummary { manifest, config, backup, state })
+}
+
+/// `--export-settings`: the config in use and the daemon state
+pub fn export(bundle: &Path) -> Result<()> {
+    let config = CONFIG.has_config().then(|| CONFIG.get_path());
+    let manifest = export_to(bundle, config.as_deref(), &StateStore::default())?;
+
+    println!("Settings exported to {}", bundle.display());
+    match config {
+        Some(config) => println!("  config: {}", config.display()),
+        None => println!("  config: none (defaults in use)"),
+    }
+    println!("  state: {}", StateStore::default().path().display());
+    println!("  battery backend: {}", manifest.laptop_module);
+    Ok(())
+}
+
+/// `--import-settings`: install the bundle's config where this machine reads
+/// it from and restore the state
+pub fn import(bundle: &Path) -> Result<()> {
+    let target = CONFIG.get_path();
+    let summary = import_from(bundle, &target, &StateStore::default())?;
+
+    println!(
+        "Imported settings from {} (auto-cpufreq {}, {})",
+        if summary.manifest.hostname.is_empty() { "unknown host" } else { &summary.manifest.hostname },
+        summary.manifest.version,
+        summary.manifest.created
+    );
+    if let Some(config) = &summary.config {
+        println!("  config: {}", config.display());
+    }
+    if let Some(backup) = &summary.backup {
+        println!("  previous config saved as {}", backup.display());
+    }
+    if let Some(profile) = &summary.state.last_profile {
+        println!("  profile: {}", profile);
+    }
+
+    let modules = LaptopModule::describe(&LaptopModule::detect());
+    if summary.manifest.laptop_module != modules {
+        eprintln!(
+            "WARNING: The bundle's battery thresholds were set up for {}, this machine uses {}; check the [battery] section",
+            summary.manifest.laptop_module,
+            modules
+        );
+    }
+
+    println!("\nRestart the daemon (or wait for the config reload) to apply the imported settings");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = std::env::temp_dir().join(format!("bundle_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let config = dir.join("source.conf");
+        fs::write(&config, "[battery]\nenable_thresholds = true\ncharging_stop_threshold = 70\n").unwrap();
+        let source = StateStore::with_legacy_dir(dir.join("source-state.json"), dir.join("legacy"));
+        source
+            .save(&State {
+                turbo_override: Some("never".to_string()),
+                last_profile: Some("quiet".to_string()),
+                parked_cores: vec![3],
+                original_settings: Some(Default::default()),
+                telemetry_sent: Some(1),
+                ..State::default()
+            })
+            .unwrap();
+
+        let bundle = dir.join("bundle.tar");
+        let manifest = export_to(&bundle, Some(&config), &source).unwrap();
+        assert!(manifest.has_config);
+
+        let target_config = dir.join("etc/auto-cpufreq.conf");
+        fs::create_dir_all(target_config.parent().unwrap()).unwrap();
+        fs::write(&target_config, "[charger]\nturbo = auto\n").unwrap();
+        let target = StateStore::with_legacy_dir(dir.join("target-state.json"), dir.join("legacy"));
+        target.save(&State { parked_cores: vec![5], ..State::default() }).unwrap();
+
+        let summary = import_from(&bundle, &target_config, &target).unwrap();
+        assert_eq!(summary.manifest, manifest);
+        assert_eq!(fs::read_to_string(&target_config).unwrap(), fs::read_to_string(&config).unwrap());
+        assert_eq!(fs::read_to_string(dir.join("etc/auto-cpufreq.conf.bak")).unwrap(), "[charger]\nturbo = auto\n");
+        // Only the portable fields come along, the target keeps its own
+        let imported = target.load();
+        assert_eq!(imported.turbo_override.as_deref(), Some("never"));
+        assert_eq!(imported.last_profile.as_deref(), Some("quiet"));
+        assert_eq!(imported.parked_cores, vec![5]);
+        assert_eq!(imported.original_settings, None);
+        assert_eq!(imported.telemetry_sent, None);
+
+        fs::write(&bundle, "not a tarball").unwrap();
+        assert!(import_from(&bundle, &target_config, &target).is_err());
+
+        // Members have to be regular files, not links out of the staging dir
+        let evil = dir.join("evil");
+        fs::create_dir_all(&evil).unwrap();
+        fs::write(evil.join(MANIFEST), serde_json::to_string(&manifest).unwrap()).unwrap();
+        std::os::unix::fs::symlink("/etc/hostname", evil.join(STATE_FILE)).unwrap();
+        tar(&["-cf", &bundle.to_string_lossy(), "-C", &evil.to_string_lossy(), MANIFEST, STATE_FILE]).unwrap();
+        let error = import_from(&bundle, &target_config, &target).unwrap_err();
+        assert!(error.to_string().contains("not a regular file"), "{}", error);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+// src/capabilities.rs
+//
+// What this machine supports (scaling driver, governors, turbo/EPP/EPB knobs,
+// platform profile, RAPL, battery thresholds, ...) and which of those
+// auto-cpufreq will actually drive with the current config. Printed by
+// `--capabilities [--json]` and used by the GUI to hide controls that don't
+// apply, e.g. EPB on AMD.
+
+use std::fs;
+
+use serde::Serialize;
+
+use crate::battery::{self, LaptopModule};
+use crate::config::{CONFIG, Snapshot};
+use crate::core::{epb_supported, turbo_control};
+use crate::cpu::{amd_pstate, cpufreq, eas, intel_pstate, platform_profile, rapl};
+use crate::power_helper::{self, TunedMode, TUNED_STAT_EXISTS};
+use crate::AVAILABLE_GOVERNORS_SORTED;
+
+const SCALING_DRIVER: &str = "/sys/devices/system/cpu/cpu0/cpufreq/scaling_driver";
+const EPP_CHOICES: &str = "/sys/devices/system/cpu/cpu0/cpufreq/energy_performance_available_preferences";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Feature {
+    /// The hardware/kernel offers it
+    pub available: bool,
+    /// auto-cpufreq manages it with the current config
+    pub used: bool,
+    /// Backend, mode or choices, where that is useful to know
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Feature {
+    fn new(available: bool, used: bool, detail: Option<String>) -> Self {
+        Self { available, used: available && used, detail }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Capabilities {
+    /// cpufreq is present; without it only battery and reporting features work
+    pub cpufreq: bool,
+    pub scaling_driver: Option<String>,
+    pub governors: Vec<String>,
+    /// Governor pinned in `[charger]`, `None` when chosen by load
+    pub charger_governor: Option<String>,
+    /// Governor pinned in `[battery]`, `None` when chosen by load
+    pub battery_governor: Option<String>,
+    pub turbo: Feature,
+    pub epp: Feature,
+    pub epb: Feature,
+    pub amd_pstate: Feature,
+    pub intel_pstate: Feature,
+    pub hwp_dynamic_boost: Feature,
+    pub platform_profile: Feature,
+    pub eas: Feature,
+    pub rapl: Feature,
+    pub battery_thresholds: Feature,
+    pub skin_sensor: Feature,
+    pub docked: Feature,
+    pub gamemode: Feature,
+    pub tuned: Feature,
+}
+
+fn configured(config: &Snapshot, key: &str) -> bool {
+    config.get("charger", key).is_some() || config.get("battery", key).is_some()
+}
+
+fn pinned_governor(config: &Snapshot, section: &str, governors: &[String]) -> Option<String> {
+    config
+        .get(section, "governor")
+        .map(str::trim)
+        .filter(|gov| governors.iter().any(|g| g == gov))
+        .map(String::from)
+}
+
+impl Capabilities {
+    pub fn detect() -> Self {
+        let config = CONFIG.snapshot();
+        let governors = AVAILABLE_GOVERNORS_SORTED.clone();
+        let read = |path: &str| fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+
+        let epp_choices = read(EPP_CHOICES);
+        let laptop_modules = LaptopModule::detect();
+        let thresholds_available = !laptop_modules.is_empty();
+        let tuned_mode = power_helper::tuned_mode(&config).unwrap_or(TunedMode::Disable);
+        let eas = eas::Eas::detect();
+
+        Self {
+            cpufreq: cpufreq::supported(),
+            scaling_driver: read(SCALING_DRIVER),
+            charger_governor: pinned_governor(&config, "charger", &governors),
+            battery_governor: pinned_governor(&config, "battery", &governors),
+            governors,
+            turbo: Feature::new(turbo_control().is_some(), true, turbo_control().map(String::from)),
+            epp: Feature::new(epp_choices.is_some(), true, epp_choices),
+            epb: Feature::new(epb_supported(), true, None),
+            amd_pstate: Feature::new(amd_pstate::is_present(), true, amd_pstate::mode().map(|m| m.as_str().to_string())),
+            intel_pstate: Feature::new(intel_pstate::is_present(), true, intel_pstate::status()),
+            hwp_dynamic_boost: Feature::new(
+                intel_pstate::hwp_dynamic_boost().is_some(),
+                configured(&config, "hwp_dynamic_boost"),
+                None,
+            ),
+            platform_profile: Feature::new(
+                platform_profile::is_supported(),
+                configured(&config, "platform_profile") || crate::profiles::active().is_some_and(|p| p.platform_profile.is_some()),
+                Some(platform_profile::choices().join(" ")).filter(|c| !c.is_empty()),
+            ),
+            eas: Feature::new(eas.available(), eas.enabled(), eas.kernel.map(|(major, minor)| format!("kernel {}.{}", major, minor))),
+            rapl: Feature::new(rapl::is_present(), configured(&config, "rapl_pl1") || configured(&config, "rapl_pl2"), None),
+            battery_thresholds: Feature::new(
+                thresholds_available,
+                battery::profiles::thresholds_enabled(&CONFIG),
+                Some(LaptopModule::describe(&laptop_modules)),
+            ),
+            skin_sensor: Feature::new(crate::thermal::skin_temp().is_some(), true, None),
+            docked: Feature::new(
+                crate::docked::lid_closed().is_some(),
+                config.section("docked").is_some(),
+                Some(format!("{} external display(s)", crate::docked::external_displays())),
+            ),
+            gamemode: Feature::new(crate::gamemode::is_installed(), crate::gamemode::enabled(&config), None),
+            tuned: Feature::new(*TUNED_STAT_EXISTS, tuned_mode == TunedMode::Cooperate, None),
+        }
+    }
+
+    /// Name/feature pairs in display order
+    pub fn features(&self) -> Vec<(&'static str, &Feature)> {
+        vec![
+            ("Turbo boost", &self.turbo),
+            ("EPP", &self.epp),
+            ("EPB", &self.epb),
+            ("amd-pstate", &self.amd_pstate),
+            ("intel_pstate", &self.intel_pstate),
+            ("HWP dynamic boost", &self.hwp_dynamic_boost),
+            ("Platform profile", &self.platform_profile),
+            ("Energy-aware (EAS)", &self.eas),
+            ("RAPL power limits", &self.rapl),
+            ("Battery thresholds", &self.battery_thresholds),
+            ("Skin temperature", &self.skin_sensor),
+            ("Docked profile", &self.docked),
+            ("GameMode", &self.gamemode),
+            ("tuned cooperation", &self.tuned),
+        ]
+    }
+
+    pub fn print(&self) {
+        if !self.cpufreq {
+            println!("{}\n", cpufreq::missing_reason());
+        }
+        println!("Scaling driver: {}", self.scaling_driver.as_deref().unwrap_or("unknown"));
+        println!("Governors: {}", self.governors.join(", "));
+        println!("Charger governor: {}", self.charger_governor.as_deref().unwrap_or("by load"));
+        println!("Battery governor: {}", self.battery_governor.as_deref().unwrap_or("by load"));
+        println!();
+        for (name, feature) in self.features() {
+            let state = match (feature.available, feature.used) {
+                (true, true) => "used",
+                (true, false) => "available",
+                (false, _) => "not available",
+            };
+            match &feature.detail {
+                Some(detail) if feature.available => println!("{:<20} {} ({})", name, state, detail),
+                _ => println!("{:<20} {}", name, state),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_and_pinned_governor() {
+        assert!(!Feature::new(false, true, None).used);
+        assert!(Feature::new(true, true, None).used);
+
+        let governors = vec!["performance".to_string(), "powersave".to_string()];
+        let config = Snapshot::from_str_ini("[charger]\ngovernor = performance\n[battery]\ngovernor = conservative\n").unwrap();
+        assert_eq!(pinned_governor(&config, "charger", &governors).as_deref(), Some("performance"));
+        assert_eq!(pinned_governor(&config, "battery", &governors), None);
+    }
+}
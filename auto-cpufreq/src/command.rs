@@ -0,0 +1,270 @@
+// src/command.rs
+//
+// Running external tools (pacman, systemctl, tlp-stat, journalctl, ...).
+// Their output is parsed, so they always run in the C locale: on a system set
+// to e.g. pl_PL, `pacman -Qi` labels the version line "Wersja", and other
+// tools translate their field names the same way, which silently breaks
+// matching.
+//
+// Every tool goes through the `CommandRunner`, which captures its output.
+// Tools that are only queried are killed after a timeout so a hung systemd or
+// D-Bus can't block the daemon or GUI; ones that change something (`change`)
+// run to completion, since killing them half way could leave a setting half
+// applied. Tests swap in a mock runner with `with_runner`.
+
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail, Context};
+
+/// Timeout for `run`, `succeeds` and `output`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// `Command::new` with a locale-neutral environment. Use this for every tool
+/// whose output or exit status is inspected.
+pub fn new<S: AsRef<OsStr>>(program: S) -> Command {
+    let mut command = Command::new(program);
+    command
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        .env_remove("LANGUAGE");
+    command
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandOutput {
+    /// Exit code, `None` when killed by a signal
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+pub trait CommandRunner: Send + Sync {
+    /// Run `program` to completion, capturing its output. Fails when it can't
+    /// be started or is still running after `timeout`, if there is one.
+    fn run(&self, program: &str, args: &[&str], timeout: Option<Duration>) -> Result<CommandOutput>;
+}
+
+/// Spawns real processes
+pub struct SystemRunner;
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str], timeout: Option<Duration>) -> Result<CommandOutput> {
+        let mut child = new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run {}", program))?;
+
+        // Drain both pipes while waiting so a chatty command can't fill them and stall
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        let status = match timeout {
+            None => child.wait()?,
+            Some(timeout) => {
+                let deadline = Instant::now() + timeout;
+                loop {
+                    if let Some(status) = child.try_wait()? {
+                        break status;
+                    }
+                    if Instant::now() >= deadline {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        bail!("{} {} timed out after {:?}", program, args.join(" "), timeout);
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        };
+
+        Ok(CommandOutput {
+            code: status.code(),
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref RUNNER: RwLock<Arc<dyn CommandRunner>> = RwLock::new(Arc::new(SystemRunner));
+}
+
+thread_local! {
+    static THREAD_RUNNER: RefCell<Option<Arc<dyn CommandRunner>>> = const { RefCell::new(None) };
+}
+
+/// Replace the runner for the whole process
+pub fn set_runner(runner: Arc<dyn CommandRunner>) {
+    *RUNNER.write().unwrap() = runner;
+}
+
+/// Run `f` with `runner` on the current thread only (used by tests)
+pub fn with_runner<T>(runner: Arc<dyn CommandRunner>, f: impl FnOnce() -> T) -> T {
+    let previous = THREAD_RUNNER.with(|r| r.borrow_mut().replace(runner));
+    let result = f();
+    THREAD_RUNNER.with(|r| *r.borrow_mut() = previous);
+    result
+}
+
+fn runner() -> Arc<dyn CommandRunner> {
+    THREAD_RUNNER
+        .with(|r| r.borrow().clone())
+        .unwrap_or_else(|| RUNNER.read().unwrap().clone())
+}
+
+pub fn run_with_timeout(program: &str, args: &[&str], timeout: Duration) -> Result<CommandOutput> {
+    runner().run(program, args, Some(timeout))
+}
+
+/// Query `program`, giving up after `DEFAULT_TIMEOUT`
+pub fn run(program: &str, args: &[&str]) -> Result<CommandOutput> {
+    run_with_timeout(program, args, DEFAULT_TIMEOUT)
+}
+
+/// Run `program` to change something, without a timeout
+pub fn change(program: &str, args: &[&str]) -> Result<CommandOutput> {
+    runner().run(program, args, None)
+}
+
+/// Whether `change` ran `program` and it exited 0
+pub fn change_succeeds(program: &str, args: &[&str]) -> bool {
+    change(program, args).is_ok_and(|output| output.success())
+}
+
+/// Whether `program` ran and exited 0 in time
+pub fn succeeds(program: &str, args: &[&str]) -> bool {
+    run(program, args).is_ok_and(|output| output.success())
+}
+
+/// Run `program` and return its stdout, failing when it exits non-zero
+pub fn output(program: &str, args: &[&str]) -> Result<String> {
+    let output = run(program, args)?;
+    if !output.success() {
+        bail!(
+            "{} exited with {}: {}",
+            program,
+            output.code.map_or_else(|| "a signal".to_string(), |c| format!("code {}", c)),
+            output.stderr.trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Parse `pacman -Q <package>` output ("name version")
+fn parse_pacman_query(stdout: &str, package: &str) -> Option<String> {
+    stdout.lines().find_map(|line| {
+        let (name, version) = line.trim().split_once(' ')?;
+        (name == package).then(|| version.trim().to_string())
+    })
+}
+
+/// Installed version of a pacman package. `pacman -Q` prints a fixed
+/// "name version" line, unlike the translated key/value layout of `-Qi`.
+pub fn pacman_version(package: &str) -> Option<String> {
+    let stdout = output("pacman", &["-Q", package]).ok()?;
+    parse_pacman_query(&stdout, package)
+}
+
+/// Canned responses keyed by command line, for tests in any module
+#[cfg(test)]
+pub mod mock {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    pub struct MockRunner {
+        responses: Vec<(String, CommandOutput)>,
+        pub calls: Mutex<Vec<String>>,
+    }
+
+    impl MockRunner {
+        /// Answer `command_line` (program and args joined by spaces) with
+        /// `stdout` and exit code `code`
+        pub fn on(mut self, command_line: &str, code: i32, stdout: &str) -> Self {
+            let output = CommandOutput { code: Some(code), stdout: stdout.to_string(), stderr: String::new() };
+            self.responses.push((command_line.to_string(), output));
+            self
+        }
+    }
+
+    impl CommandRunner for MockRunner {
+        fn run(&self, program: &str, args: &[&str], _timeout: Option<Duration>) -> Result<CommandOutput> {
+            let line = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+            self.calls.lock().unwrap().push(line.clone());
+            match self.responses.iter().find(|(l, _)| *l == line) {
+                Some((_, output)) => Ok(output.clone()),
+                None => bail!("Failed to run {}: not mocked", program),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::mock::MockRunner;
+
+    #[test]
+    fn test_parse_pacman_query() {
+        assert_eq!(parse_pacman_query("auto-cpufreq 2.4.0-1\n", "auto-cpufreq").as_deref(), Some("2.4.0-1"));
+        assert_eq!(parse_pacman_query("auto-cpufreq-git 2.4.0.r5-1\n", "auto-cpufreq"), None);
+        assert_eq!(parse_pacman_query("", "auto-cpufreq"), None);
+    }
+
+    #[test]
+    fn test_command_runs_in_c_locale() {
+        let command = new("true");
+        let envs: Vec<_> = command.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("LC_ALL"), Some(OsStr::new("C")))));
+        assert!(envs.contains(&(OsStr::new("LANGUAGE"), None)));
+    }
+
+    #[test]
+    fn test_system_runner() {
+        let output = SystemRunner.run("sh", &["-c", "echo out; echo err >&2; exit 3"], Some(DEFAULT_TIMEOUT)).unwrap();
+        assert_eq!(output, CommandOutput { code: Some(3), stdout: "out\n".into(), stderr: "err\n".into() });
+
+        let started = Instant::now();
+        assert!(SystemRunner.run("sleep", &["5"], Some(Duration::from_millis(100))).is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        // Without a timeout it is waited for
+        assert!(SystemRunner.run("sleep", &["0.2"], None).unwrap().success());
+    }
+
+    #[test]
+    fn test_mock_runner() {
+        let mock = Arc::new(MockRunner::default().on("pacman -Q auto-cpufreq", 0, "auto-cpufreq 2.4.0-1\n"));
+        with_runner(mock.clone(), || {
+            assert_eq!(pacman_version("auto-cpufreq").as_deref(), Some("2.4.0-1"));
+            assert!(!succeeds("systemctl", &["is-active", "tlp"]));
+        });
+        assert_eq!(*mock.calls.lock().unwrap(), vec!["pacman -Q auto-cpufreq", "systemctl is-active tlp"]);
+    }
+}
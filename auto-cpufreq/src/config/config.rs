@@ -1,30 +1,70 @@
 // src/config/config.rs
 
 use anyhow::{Result, bail};
+#[cfg(feature = "config-watch")]
 use notify::{Watcher, RecursiveMode};
+#[cfg(feature = "config-watch")]
 use notify::event::{EventKind, ModifyKind, CreateKind, RemoveKind};
 
+use std::io::{ErrorKind, Write};
+use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+#[cfg(feature = "config-watch")]
+use std::sync::mpsc::{self, RecvTimeoutError};
+#[cfg(feature = "config-watch")]
+use std::time::Duration;
 
-use configparser::ini::Ini;
+use arc_swap::ArcSwap;
+
+use super::snapshot::Snapshot;
+
+/// Sockets to wake on a reload that changed something, see `Config::subscribe`
+type Subscribers = Arc<Mutex<Vec<UnixStream>>>;
+
+/// Swap in `snapshot` and tell the subscribers, unless nothing changed
+fn swap_in(config: &ArcSwap<Snapshot>, subscribers: &Subscribers, snapshot: Snapshot) {
+    if **config.load() == snapshot {
+        return;
+    }
+    config.store(Arc::new(snapshot));
+    // A full buffer already says "reloaded"; a closed socket is dropped
+    subscribers
+        .lock()
+        .unwrap()
+        .retain(|socket| !matches!((&*socket).write(&[1]), Err(e) if e.kind() != ErrorKind::WouldBlock));
+}
+
+/// A save is a burst of events (backup, truncate, writes, rename); reload
+/// once none came for this long
+#[cfg(feature = "config-watch")]
+const RELOAD_QUIET_PERIOD: Duration = Duration::from_millis(500);
+
+/// Load `path` unless it changed while being read, i.e. a write is still going on
+#[cfg(feature = "config-watch")]
+fn load_settled(path: &Path) -> Option<Result<Snapshot, String>> {
+    let stamp = |path: &Path| std::fs::metadata(path).ok().map(|m| (m.len(), m.modified().ok()));
+    let before = stamp(path);
+    let snapshot = Snapshot::load(path);
+    (stamp(path) == before).then_some(snapshot)
+}
 
 pub struct Config {
     path: Arc<Mutex<PathBuf>>,
-    config: Arc<Mutex<Ini>>,
+    config: Arc<ArcSwap<Snapshot>>,
+    subscribers: Subscribers,
+    #[cfg(feature = "config-watch")]
     watcher: Arc<Mutex<Option<notify::RecommendedWatcher>>>,
-    last_reload: Arc<Mutex<Instant>>,  // For debouncing
 }
 
 impl Config {
     pub fn new() -> Self {
         Config {
             path: Arc::new(Mutex::new(PathBuf::new())),
-            config: Arc::new(Mutex::new(Ini::new())),
+            config: Arc::new(ArcSwap::from_pointee(Snapshot::default())),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "config-watch")]
             watcher: Arc::new(Mutex::new(None)),
-            last_reload: Arc::new(Mutex::new(Instant::now())),
         }
     }
 
@@ -41,63 +81,67 @@ impl Config {
         Ok(())
     }
 
+    /// Without the `config-watch` feature the file is only read at startup
+    #[cfg(not(feature = "config-watch"))]
+    fn setup_watcher(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    #[cfg(feature = "config-watch")]
     fn setup_watcher(&self, path: &Path) -> Result<()> {
         let config_clone = Arc::clone(&self.config);
+        let subscribers = Arc::clone(&self.subscribers);
         let path_clone = Arc::clone(&self.path);
-        let last_reload_clone = Arc::clone(&self.last_reload);
+        let (events, bursts) = mpsc::channel();
 
+        let watched_path = Arc::clone(&self.path);
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
             match res {
                 Ok(event) => {
+                    // Name: `--config-set` replaces the file by renaming over it
                     let should_update = matches!(
                         event.kind,
                         EventKind::Create(CreateKind::File) |
                         EventKind::Modify(ModifyKind::Data(_)) |
+                        EventKind::Modify(ModifyKind::Name(_)) |
                         EventKind::Remove(RemoveKind::File)
                     );
 
-                    if should_update {
-                        // Debouncing: Only reload if 500ms passed since last reload
-                        let should_reload = {
-                            let mut last = last_reload_clone.lock().unwrap();
-                            let now = Instant::now();
-                            if now.duration_since(*last) > Duration::from_millis(500) {
-                                *last = now;
-                                true
-                            } else {
-                                false
-                            }
-                        };
-
-                        if !should_reload {
-                            return;
-                        }
-
-                        let current_path = path_clone.lock().unwrap().clone();
-                        
-                        // Check if the event is for our config file
-                        for path in &event.paths {
-                            if path == &current_path || 
-                               path.with_extension("").with_extension("") == current_path.with_extension("").with_extension("") {
-                                // Try to load config with proper error handling
-                                let mut new_config = Ini::new();
-                                if let Some(path_str) = current_path.to_str() {
-                                    if new_config.load(path_str).is_ok() {
-                                        // Only update if lock is available (avoid panic)
-                                        if let Ok(mut config) = config_clone.lock() {
-                                            *config = new_config;
-                                        }
-                                    }
-                                }
-                                break;
-                            }
-                        }
+                    // Only our config file itself, not editor backups or
+                    // swap files next to it
+                    let current_path = watched_path.lock().unwrap().clone();
+                    if should_update && event.paths.contains(&current_path) {
+                        let _ = events.send(());
                     }
                 }
                 Err(e) => eprintln!("Watch error: {:?}", e),
             }
         })?;
 
+        // Reload once a save has gone quiet, the watcher closing the channel ends it
+        std::thread::Builder::new()
+            .name("config-reload".to_string())
+            .spawn(move || {
+                while bursts.recv().is_ok() {
+                    loop {
+                        match bursts.recv_timeout(RELOAD_QUIET_PERIOD) {
+                            Ok(()) => continue,
+                            Err(RecvTimeoutError::Timeout) => {}
+                            Err(RecvTimeoutError::Disconnected) => return,
+                        }
+                        let current_path = path_clone.lock().unwrap().clone();
+                        match load_settled(&current_path) {
+                            Some(Ok(snapshot)) => swap_in(&config_clone, &subscribers, snapshot),
+                            // Keep the config in use, a correct save is a new burst
+                            Some(Err(_)) => {}
+                            // Still being written, wait for it to go quiet again
+                            None => continue,
+                        }
+                        break;
+                    }
+                }
+            })?;
+
         if let Some(parent) = path.parent() {
             watcher.watch(parent, RecursiveMode::NonRecursive)?;
         }
@@ -118,10 +162,9 @@ impl Config {
     pub fn update_config(&self) -> Result<()> {
         let path = self.path.lock().unwrap().clone();
         
-        let mut new_config = Ini::new();
-        match new_config.load(path.to_str().unwrap_or("")) {
-            Ok(_) => {
-                *self.config.lock().unwrap() = new_config;
+        match Snapshot::load(&path) {
+            Ok(snapshot) => {
+                swap_in(&self.config, &self.subscribers, snapshot);
                 Ok(())
             }
             Err(e) => {
@@ -132,18 +175,35 @@ impl Config {
         }
     }
 
+    /// A socket that becomes readable whenever the config is reloaded with
+    /// different content, from the file watcher or `reload-config`, so a
+    /// poll loop can act on it right away. Each byte read is one or more reloads.
+    pub fn subscribe(&self) -> std::io::Result<UnixStream> {
+        let (received, notify) = UnixStream::pair()?;
+        received.set_nonblocking(true)?;
+        notify.set_nonblocking(true)?;
+        self.subscribers.lock().unwrap().push(notify);
+        Ok(received)
+    }
+
+    /// Current parsed config. Cheap (no lock); holding on to it keeps reading the
+    /// same version even if the file is reloaded meanwhile.
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        self.config.load_full()
+    }
+
     pub fn get_string(&self, section: &str, key: &str) -> Result<Option<String>> {
-        let config = self.config.lock().unwrap();
-        Ok(config.get(section, key))
+        Ok(self.config.load().get(section, key).map(String::from))
     }
 
     pub fn get_bool(&self, section: &str, key: &str) -> Result<bool> {
         let value = self.get_string(section, key)?;
         
         match value.as_deref() {
-            Some("true") | Some("True") | Some("1") | Some("yes") | Some("Yes") => Ok(true),
-            Some("false") | Some("False") | Some("0") | Some("no") | Some("No") => Ok(false),
-            Some(v) => bail!("Invalid boolean value: {}", v),
+            Some(v) => match parse_bool(v) {
+                Some(b) => Ok(b),
+                None => bail!("Invalid boolean value: {}", v),
+            },
             None => Ok(false),
         }
     }
@@ -163,34 +223,57 @@ impl Config {
     }
 
     pub fn get_threshold(&self, mode: &str) -> Result<u8> {
+        let value = self.get_section_threshold("battery", mode)?;
+        Ok(value.unwrap_or(if mode == "start" { 0 } else { 100 }))
+    }
+
+    /// `charging_<mode>_threshold` from `section`, `None` when it isn't set
+    pub fn get_section_threshold(&self, section: &str, mode: &str) -> Result<Option<u8>> {
         let key = match mode {
             "start" => "charging_start_threshold",
             "stop" => "charging_stop_threshold",
             _ => bail!("Invalid threshold mode: {}", mode),
         };
 
-        let value = self.get_int("battery", key)?;
+        let value = self.get_int(section, key)?;
         
         match value {
-            Some(v) if (0..=100).contains(&v) => Ok(v as u8),
+            Some(v) if (0..=100).contains(&v) => Ok(Some(v as u8)),
             Some(v) => bail!("Threshold value out of range (0-100): {}", v),
-            None => Ok(if mode == "start" { 0 } else { 100 }),
+            None => Ok(None),
         }
     }
 
     pub fn has_option(&self, section: &str, key: &str) -> bool {
-        self.config
-            .lock()
-            .unwrap()
-            .get(section, key)
-            .is_some()
+        self.config.load().get(section, key).is_some()
+    }
+
+    /// Section names of the loaded config (lowercased by the parser)
+    pub fn sections(&self) -> Vec<String> {
+        self.config.load().sections().map(String::from).collect()
+    }
+
+    /// All sections with their key/value pairs, sorted for stable output
+    pub fn items(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let snapshot = self.config.load();
+        snapshot
+            .sections()
+            .map(|section| {
+                let keys = snapshot
+                    .section(section)
+                    .map(|keys| keys.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                    .unwrap_or_default();
+                (section.to_string(), keys)
+            })
+            .collect()
     }
 
     pub fn get(&self, section: &str, key: &str, fallback: &str) -> String {
-        self.get_string(section, key)
-            .ok()
-            .flatten()
-            .unwrap_or_else(|| fallback.to_string())
+        self.config
+            .load()
+            .get(section, key)
+            .unwrap_or(fallback)
+            .to_string()
     }
 }
 
@@ -209,12 +292,35 @@ lazy_static::lazy_static! {
     pub static ref CONFIG: Config = Config::new();
 }
 
+/// Boolean config value: true/false, yes/no, on/off or 1/0, in any case
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Config file at `stem` (a path without extension): the TOML file when there
+/// is one, else the legacy INI file when there is one, else the TOML path
+fn preferred_format(stem: &Path) -> PathBuf {
+    let toml = stem.with_extension("toml");
+    let ini = stem.with_extension("conf");
+    if !toml.is_file() && ini.is_file() {
+        ini
+    } else {
+        toml
+    }
+}
+
 /// Find the config file to use
 /// 
 /// Look for a config file in the following prioritization order:
 /// 1. Command line argument
 /// 2. User config file
 /// 3. System config file
+///
+/// In 2 and 3 `auto-cpufreq.toml` is read before `auto-cpufreq.conf`.
 pub fn find_config_file(args_config_file: Option<&str>) -> PathBuf {
     // Get home directory
     let home = get_home_dir();
@@ -224,8 +330,8 @@ pub fn find_config_file(args_config_file: Option<&str>) -> PathBuf {
         .map(PathBuf::from)
         .unwrap_or_else(|_| home.join(".config"));
     
-    let user_config_file = user_config_dir.join("auto-cpufreq/auto-cpufreq.conf");
-    let system_config_file = PathBuf::from("/etc/auto-cpufreq.conf");
+    let user_config_file = preferred_format(&user_config_dir.join("auto-cpufreq/auto-cpufreq"));
+    let system_config_file = preferred_format(Path::new("/etc/auto-cpufreq"));
 
     // (1) Command line argument was specified
     if let Some(config_path) = args_config_file {
@@ -249,7 +355,7 @@ pub fn find_config_file(args_config_file: Option<&str>) -> PathBuf {
 
 fn get_home_dir() -> PathBuf {
     // Try to get home directory from $SUDO_USER or $USER
-    let output = Command::new("sh")
+    let output = crate::command::new("sh")
         .arg("-c")
         .arg("getent passwd ${SUDO_USER:-$USER} | cut -d: -f6")
         .output();
@@ -285,6 +391,66 @@ mod tests {
         assert_eq!(config.get_threshold("stop").unwrap(), 100);
     }
 
+    #[test]
+    fn test_get_section_threshold() {
+        use std::io::Read;
+
+        let path = std::env::temp_dir().join(format!("auto_cpufreq_thresholds_{}.conf", std::process::id()));
+        std::fs::write(
+            &path,
+            "[battery]\ncharging_stop_threshold = 80\n[battery.BAT1]\ncharging_stop_threshold = 60\n",
+        )
+        .unwrap();
+        let config = Config::new();
+        let reloads = config.subscribe().unwrap();
+        config.set_path(path.clone()).unwrap();
+
+        assert_eq!(config.get_threshold("stop").unwrap(), 80);
+        assert_eq!(config.get_section_threshold("battery.BAT1", "stop").unwrap(), Some(60));
+        assert_eq!(config.get_section_threshold("battery.BAT0", "stop").unwrap(), None);
+
+        // Subscribers hear about a reload only when the content changed
+        let mut buf = [0u8; 8];
+        assert_eq!((&reloads).read(&mut buf).unwrap(), 1);
+        config.update_config().unwrap();
+        assert!((&reloads).read(&mut buf).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "config-watch")]
+    #[test]
+    fn test_watcher_reloads_after_the_save() {
+        use std::io::Read;
+
+        let dir = std::env::temp_dir().join(format!("auto_cpufreq_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("auto-cpufreq.conf");
+        std::fs::write(&path, "[charger]\ngovernor = performance\n").unwrap();
+        let config = Config::new();
+        config.set_path(path.clone()).unwrap();
+        let reloads = config.subscribe().unwrap();
+
+        // An editor backup next to it is not our file
+        std::fs::write(dir.join("auto-cpufreq.conf~"), "").unwrap();
+        // An in-place save: truncated first, written in pieces
+        let mut file = std::fs::File::create(&path).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        file.write_all(b"[charger]\n").unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        file.write_all(b"governor = powersave\n").unwrap();
+        drop(file);
+
+        std::thread::sleep(RELOAD_QUIET_PERIOD * 3);
+        assert_eq!(config.get_string("charger", "governor").unwrap().as_deref(), Some("powersave"));
+        // One reload for the whole save, not one with the half-written file
+        let mut buf = [0u8; 8];
+        assert_eq!((&reloads).read(&mut buf).unwrap(), 1);
+
+        drop(config);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_get_bool() {
         let config = Config::new();
@@ -313,4 +479,60 @@ mod tests {
             handle.join().unwrap();
         }
     }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+
+        const BOOL_WORDS: &[&str] = &["true", "false", "yes", "no", "on", "off", "1", "0"];
+
+        fn config_with(section: &str, key: &str, value: &str) -> Config {
+            let config = Config::new();
+            let ini = format!("[{}]\n{} = {}\n", section, key, value);
+            config.config.store(Arc::new(Snapshot::from_str_ini(&ini).unwrap()));
+            config
+        }
+
+        proptest! {
+            #[test]
+            fn threshold_in_range_is_accepted(value in 0i32..=100, stop in any::<bool>()) {
+                let (mode, key) = if stop { ("stop", "charging_stop_threshold") } else { ("start", "charging_start_threshold") };
+                let config = config_with("battery", key, &value.to_string());
+                prop_assert_eq!(config.get_threshold(mode).unwrap(), value as u8);
+                let per_battery = config_with("battery.BAT1", key, &value.to_string());
+                prop_assert_eq!(per_battery.get_section_threshold("battery.bat1", mode).unwrap(), Some(value as u8));
+            }
+
+            #[test]
+            fn threshold_out_of_range_is_rejected(value in prop_oneof![i32::MIN..0, 101..=i32::MAX]) {
+                let config = config_with("battery", "charging_stop_threshold", &value.to_string());
+                prop_assert!(config.get_threshold("stop").is_err());
+            }
+
+            #[test]
+            fn threshold_malformed_is_rejected(value in "[0-9]{0,3}[a-z%.,][a-z0-9%.,]{0,6}") {
+                let config = config_with("battery", "charging_start_threshold", &value);
+                prop_assert!(config.get_threshold("start").is_err());
+            }
+
+            #[test]
+            fn bool_accepts_any_case(word in prop::sample::select(BOOL_WORDS), mask in any::<u8>()) {
+                let mixed: String = word
+                    .chars()
+                    .enumerate()
+                    .map(|(i, c)| if mask & (1 << (i % 8)) != 0 { c.to_ascii_uppercase() } else { c })
+                    .collect();
+                let expected = matches!(word, "true" | "yes" | "on" | "1");
+                prop_assert_eq!(parse_bool(&mixed), Some(expected));
+                prop_assert_eq!(config_with("battery", "enable_thresholds", &mixed).get_bool("battery", "enable_thresholds").unwrap(), expected);
+            }
+
+            #[test]
+            fn bool_rejects_anything_else(value in "[a-zA-Z0-9_.-]{1,10}") {
+                prop_assume!(!BOOL_WORDS.contains(&value.to_lowercase().as_str()));
+                prop_assert_eq!(parse_bool(&value), None);
+                prop_assert!(config_with("battery", "enable_thresholds", &value).get_bool("battery", "enable_thresholds").is_err());
+            }
+        }
+    }
 }
@@ -0,0 +1,308 @@
+// src/config/edit.rs
+//
+// `--config-get`, `--config-set` and `--config-unset`: one setting at a time
+// from the command line, named `section.key` (`charger.governor`,
+// `battery.BAT1.charging_stop_threshold`; the key is what follows the last
+// dot). The file is edited line by line, so comments, blank lines and the
+// order of everything else stay as they are: a key that is already set gets
+// its value replaced where it is, a new key goes after the last one of its
+// section and a new section at the end of the file. Values are checked
+// against the `--check-config` schema before anything is written, and the
+// edited file must differ from the old one in that one key only.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use super::effective::Available;
+use super::migrate::{same_value, table_path, toml_key, toml_value};
+use super::schema::{ConfigSchema, KeySchema, Line, closest, key_hint, parse_line, parse_toml_line, suggest_section};
+use super::snapshot::{Snapshot, is_toml};
+use crate::globals::AVAILABLE_GOVERNORS_SORTED;
+
+/// What a line of the file holds, as far as editing is concerned
+#[derive(Debug, PartialEq)]
+enum Item {
+    Section(String),
+    Key(String),
+    Other,
+}
+
+/// `section.key` split at the last dot, lowercased like the parser does
+pub fn split_name(name: &str) -> Result<(String, String)> {
+    match name.trim().rsplit_once('.') {
+        Some((section, key)) if !section.is_empty() && !key.is_empty() => {
+            Ok((section.to_lowercase(), key.to_lowercase()))
+        }
+        _ => bail!("Expected section.key, e.g. charger.governor, not {}", name),
+    }
+}
+
+fn parse(content: &str, toml: bool) -> Result<Snapshot, String> {
+    match toml {
+        true => Snapshot::from_toml(content),
+        false => Snapshot::from_str_ini(content),
+    }
+}
+
+fn items(content: &str, toml: bool) -> Result<Vec<Item>> {
+    let item = |line: Line| match line {
+        Line::Section(name) => Item::Section(name),
+        Line::Entry { key, .. } => Item::Key(key),
+        Line::Blank | Line::Invalid => Item::Other,
+    };
+    if !toml {
+        return Ok(content.lines().map(|text| item(parse_line(text))).collect());
+    }
+
+    let snapshot = parse(content, true).map_err(|e| anyhow!("The config file doesn't parse: {}", e))?;
+    let mut section = None;
+    let mut items = Vec::new();
+    for text in content.lines() {
+        let line = item(parse_toml_line(text, section.as_deref(), &snapshot));
+        if let Item::Section(name) = &line {
+            section = Some(name.clone());
+        }
+        items.push(line);
+    }
+    Ok(items)
+}
+
+/// Lines of `key` in `section`, and the last line belonging to the section
+fn locate(items: &[Item], section: &str, key: &str) -> (Vec<usize>, Option<usize>) {
+    let mut current = None;
+    let mut found = Vec::new();
+    let mut last = None;
+    for (index, item) in items.iter().enumerate() {
+        match item {
+            Item::Section(name) => {
+                current = Some(name.as_str());
+                if name == section {
+                    last = Some(index);
+                }
+            }
+            Item::Key(name) if current == Some(section) => {
+                if name == key {
+                    found.push(index);
+                }
+                last = Some(index);
+            }
+            _ => {}
+        }
+    }
+    (found, last)
+}
+
+fn join(lines: Vec<String>) -> String {
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+/// `content` with `key` in `section` set to `value`
+fn set_in(content: &str, toml: bool, section: &str, key: &str, value: &str) -> Result<String> {
+    let schema = ConfigSchema::builtin();
+    let value_text = match toml {
+        true => {
+            let value_type = schema.section(section).and_then(|s| s.key(key)).map(|k| k.value);
+            toml_value(value_type, value).to_string()
+        }
+        false => value.trim().to_string(),
+    };
+    let entry = match toml {
+        true => format!("{} = {}", toml_key(key), value_text),
+        false => format!("{} = {}", key, value_text),
+    };
+
+    let (found, last) = locate(&items(content, toml)?, section, key);
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    match (found.last(), last) {
+        // The last one is the one that counts; keep its spelling and indentation
+        (Some(&at), _) => {
+            let line = &lines[at];
+            let separators: &[char] = if toml { &['='] } else { &['=', ':'] };
+            lines[at] = match line.find(separators) {
+                Some(sep) => {
+                    let rest = &line[sep + 1..];
+                    let space = &rest[..rest.len() - rest.trim_start().len()];
+                    format!("{}{}{}", &line[..=sep], space, value_text)
+                }
+                None => format!("{}{}", &line[..line.len() - line.trim_start().len()], entry),
+            };
+        }
+        (None, Some(last)) => lines.insert(last + 1, entry),
+        (None, None) => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            let header = match toml {
+                true => table_path(&schema, section),
+                false => section.to_string(),
+            };
+            lines.push(format!("[{}]", header));
+            lines.push(entry);
+        }
+    }
+    Ok(join(lines))
+}
+
+/// `content` without `key` in `section`, `None` when it isn't set
+fn unset_in(content: &str, toml: bool, section: &str, key: &str) -> Result<Option<String>> {
+    let (found, _) = locate(&items(content, toml)?, section, key);
+    if found.is_empty() {
+        return Ok(None);
+    }
+    let lines = content
+        .lines()
+        .enumerate()
+        .filter(|(index, _)| !found.contains(index))
+        .map(|(_, line)| line.to_string())
+        .collect();
+    Ok(Some(join(lines)))
+}
+
+/// Write `after` to `path` if it changes nothing but `key` in `section`
+fn write_checked(path: &Path, before: &str, after: &str, section: &str, key: &str, value: Option<&str>) -> Result<()> {
+    let toml = is_toml(path);
+    let old = parse(before, toml).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let new = parse(after, toml)
+        .map_err(|e| anyhow!("Editing {} would break it ({}), change it by hand", path.display(), e))?;
+
+    let only_key = old.changes(&new).iter().all(|change| change.section == section && change.key == key);
+    let value_ok = match value {
+        Some(value) => new.get(section, key).is_some_and(|set| same_value(set, value)),
+        None => new.get(section, key).is_none(),
+    };
+    if !only_key || !value_ok {
+        bail!("Can't edit {}.{} in {} on its own, change it by hand", section, key, path.display());
+    }
+
+    // Replaced atomically, so the daemon's watcher never reads half a file.
+    // Hidden, so the watcher doesn't take the tmp file for the config.
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    fs::write(&tmp, after).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(&tmp, metadata.permissions())
+            .with_context(|| format!("Failed to set permissions of {}", tmp.display()))?;
+    }
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+fn read(path: &Path) -> Result<String> {
+    match path.exists() {
+        true => fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display())),
+        false => Ok(String::new()),
+    }
+}
+
+/// The schema of `key` in `section`, or what it is probably a typo of
+fn known_key<'a>(schema: &'a ConfigSchema, section: &str, key: &str) -> Result<&'a KeySchema> {
+    let Some(section_schema) = schema.section(section) else {
+        match suggest_section(schema, section) {
+            Some(suggestion) => bail!("Unknown section [{}] (did you mean [{}]?)", section, suggestion),
+            None => bail!("Unknown section [{}]", section),
+        }
+    };
+    section_schema.key(key).ok_or_else(|| {
+        match closest(key, section_schema.keys.iter().map(|k| k.name)) {
+            Some(suggestion) => anyhow!("Unknown key {} in [{}] (did you mean {}?)", key, section, key_hint(suggestion)),
+            None => anyhow!("Unknown key {} in [{}]", key, section),
+        }
+    })
+}
+
+/// `--config-get`: the value of `name` in the config file at `path`
+pub fn get(path: &Path, name: &str) -> Result<Option<String>> {
+    let (section, key) = split_name(name)?;
+    let snapshot = parse(&read(path)?, is_toml(path)).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    Ok(snapshot.get(&section, &key).map(str::to_string))
+}
+
+/// `--config-set`: set `name` to `value` in the config file at `path`,
+/// creating the file if there is none
+pub fn set(path: &Path, name: &str, value: &str) -> Result<()> {
+    let (section, key) = split_name(name)?;
+    let schema = ConfigSchema::builtin();
+    let key_schema = known_key(&schema, &section, &key)?;
+
+    let platform_profiles = crate::cpu::platform_profile::choices();
+    let available = Available { governors: &AVAILABLE_GOVERNORS_SORTED, platform_profiles: &platform_profiles };
+    if let Some(message) = key_schema.validate(value, &available) {
+        match key_schema.suggest_value(value, &available) {
+            Some(suggestion) => bail!("Not setting [{}] {} (did you mean {}?)", section, message, suggestion),
+            None => bail!("Not setting [{}] {}", section, message),
+        }
+    }
+
+    let content = read(path)?;
+    let edited = set_in(&content, is_toml(path), &section, &key, value)?;
+    write_checked(path, &content, &edited, &section, &key, Some(value))
+}
+
+/// `--config-unset`: remove `name` from the config file at `path`, returning
+/// whether it was set
+pub fn unset(path: &Path, name: &str) -> Result<bool> {
+    let (section, key) = split_name(name)?;
+    let content = read(path)?;
+    let Some(edited) = unset_in(&content, is_toml(path), &section, &key)? else {
+        return Ok(false);
+    };
+    write_checked(path, &content, &edited, &section, &key, None)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_config() {
+        assert_eq!(
+            split_name("battery.BAT1.charging_stop_threshold").unwrap(),
+            ("battery.bat1".to_string(), "charging_stop_threshold".to_string())
+        );
+        assert!(split_name("governor").is_err());
+
+        let ini = "# laptop\n[charger]\n  governor: performance\n\n[battery]\n# stay cool\nturbo = never\n";
+        assert_eq!(
+            set_in(ini, false, "charger", "governor", "powersave").unwrap(),
+            "# laptop\n[charger]\n  governor: powersave\n\n[battery]\n# stay cool\nturbo = never\n"
+        );
+        assert_eq!(
+            set_in(ini, false, "charger", "turbo", "auto").unwrap(),
+            "# laptop\n[charger]\n  governor: performance\nturbo = auto\n\n[battery]\n# stay cool\nturbo = never\n"
+        );
+        assert!(set_in(ini, false, "thermal", "turbo_temp_limit", "85").unwrap().ends_with("turbo = never\n\n[thermal]\nturbo_temp_limit = 85\n"));
+        assert_eq!(
+            unset_in(ini, false, "battery", "turbo").unwrap().unwrap(),
+            "# laptop\n[charger]\n  governor: performance\n\n[battery]\n# stay cool\n"
+        );
+        assert_eq!(unset_in(ini, false, "battery", "governor").unwrap(), None);
+
+        let toml = "[battery]\nenable_thresholds = false # for now\n";
+        assert_eq!(
+            set_in(toml, true, "battery", "enable_thresholds", "yes").unwrap(),
+            "[battery]\nenable_thresholds = true\n"
+        );
+        assert_eq!(
+            set_in(toml, true, "cpu.core.0,2", "online", "false").unwrap(),
+            "[battery]\nenable_thresholds = false # for now\n\n[cpu.core.\"0,2\"]\nonline = false\n"
+        );
+
+        let dir = std::env::temp_dir().join(format!("acf-edit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("auto-cpufreq.conf");
+        set(&path, "battery.charging_stop_threshold", "80").unwrap();
+        assert_eq!(get(&path, "battery.charging_stop_threshold").unwrap().as_deref(), Some("80"));
+        assert!(set(&path, "battery.charging_stop_treshold", "80").unwrap_err().to_string().contains("did you mean charging_stop_threshold"));
+        assert!(set(&path, "battery.charging_stop_threshold", "180").is_err());
+        assert!(unset(&path, "battery.charging_stop_threshold").unwrap());
+        assert!(!unset(&path, "battery.charging_stop_threshold").unwrap());
+        assert_eq!(get(&path, "battery.charging_stop_threshold").unwrap(), None);
+        // Only the config, no tmp file left next to it
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
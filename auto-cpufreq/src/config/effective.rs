@@ -0,0 +1,279 @@
+// src/config/effective.rs
+//
+// `--show-effective-config`: every setting the daemon would use, the ones from
+// the config file merged over the built-in defaults, each with where it came
+// from and whether it passes validation. Meant for "why isn't my key taking
+// effect" questions: a typo'd value shows up as invalid, a key the daemon
+// falls back on shows up as a default. Only one config file is read (see
+// `find_config_file`), so a value comes either from that file or a default.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::schema::ConfigSchema;
+use super::{CONFIG, Snapshot};
+use crate::battery::{asus::ThermalPolicy, charge_behaviour};
+use crate::core::parse_epb;
+use crate::globals::AVAILABLE_GOVERNORS_SORTED;
+
+pub(crate) const SYSTEM_CONFIG_FILES: [&str; 2] = ["/etc/auto-cpufreq.toml", "/etc/auto-cpufreq.conf"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Default,
+    SystemFile,
+    UserFile,
+    /// File given with --config
+    CommandLine,
+}
+
+impl Source {
+    /// Where the config file at `path` was picked from
+    pub fn of_file(path: &Path, from_command_line: bool) -> Self {
+        if from_command_line {
+            Self::CommandLine
+        } else if SYSTEM_CONFIG_FILES.iter().any(|file| path == Path::new(file)) {
+            Self::SystemFile
+        } else {
+            Self::UserFile
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::SystemFile => "system file",
+            Self::UserFile => "user file",
+            Self::CommandLine => "--config",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveValue {
+    pub section: String,
+    pub key: String,
+    pub value: String,
+    pub source: Source,
+    /// Why the daemon won't use the value as written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invalid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub file: Option<PathBuf>,
+    pub source: Source,
+    pub values: Vec<EffectiveValue>,
+}
+
+/// Built-in values the daemon falls back on when a key isn't set
+const DEFAULTS: &[(&str, &str, &str)] = &[
+    ("charger", "turbo", "auto"),
+    ("charger", "energy_performance_preference", "balance_performance"),
+    ("charger", "energy_perf_bias", "balance_performance"),
+    ("battery", "turbo", "auto"),
+    ("battery", "energy_performance_preference", "power"),
+    ("battery", "energy_perf_bias", "balance_power"),
+    ("battery", "enable_thresholds", "false"),
+    ("battery", "charging_start_threshold", "0"),
+    ("battery", "charging_stop_threshold", "100"),
+    ("daemon", "min_interval", "2"),
+    ("daemon", "max_interval", "30"),
+    ("daemon", "usage_backend", "auto"),
+    ("thermal", "skin_temp_limit", "45"),
+];
+
+/// What the machine offers, for the checks that depend on it
+pub struct Available<'a> {
+    pub governors: &'a [String],
+    pub platform_profiles: &'a [String],
+}
+
+/// Why `value` for `key` won't be used, `None` when it looks fine. Only for
+/// the keys whose schema type needs their own parser or what this machine
+/// offers (`Format`, `Governor` and `PlatformProfile`, see `schema::ValueType`).
+pub(super) fn check(key: &str, value: &str, available: &Available) -> Option<String> {
+    let value = value.trim();
+    let ok = match key {
+        "governor" => crate::validate::governor_in(value, available.governors).is_ok(),
+        "energy_perf_bias" => parse_epb(value).is_some(),
+        "platform_profile" => {
+            available.platform_profiles.is_empty() || available.platform_profiles.iter().any(|p| p == value)
+        }
+        "asus_thermal_policy" => ThermalPolicy::parse(value).is_some(),
+        "charge_behaviour" => charge_behaviour::parse(value).is_some(),
+        "level" => crate::logging::parse_level(value).is_some(),
+        "fields" => crate::modules::system_monitor::parse_stats_fields(value).is_ok(),
+        "hours" => crate::quiet_hours::parse_windows(value).is_ok(),
+        "rules" => crate::schedule::parse_rules(value).is_ok(),
+        _ => true,
+    };
+
+    (!ok).then(|| match key {
+        "governor" => format!("not an available governor ({})", available.governors.join(", ")),
+        "platform_profile" => format!("not offered by the firmware ({})", available.platform_profiles.join(", ")),
+        _ => format!("invalid {} value", key),
+    })
+}
+
+/// Whether the parser of a `Choice` key takes `value`, `None` for keys whose
+/// listed values are all there is to check
+pub(super) fn parse_choice(key: &str, value: &str) -> Option<bool> {
+    use crate::power_helper::{ConflictPolicy, LpmdMode, TunedMode};
+    let value = value.trim();
+    Some(match key {
+        "turbo" => crate::core::TurboOverride::parse(value).is_some(),
+        "usage_backend" => crate::cpu::usage::UsageBackend::parse(value).is_some(),
+        "amd_pstate_mode" => crate::cpu::amd_pstate::AmdPstateMode::from_str(value).is_some(),
+        "dell_charge_mode" => crate::battery::dell::ChargeMode::parse(value).is_some(),
+        "tuned_mode" => TunedMode::parse(value).is_some(),
+        "lpmd_mode" => LpmdMode::parse(value).is_some(),
+        "conflict_policy" => ConflictPolicy::parse(value).is_some(),
+        _ => return None,
+    })
+}
+
+/// Problems between keys of one section that each look fine on their own
+pub(super) fn check_section(keys: &BTreeMap<String, (String, Source)>) -> Option<(&'static str, String)> {
+    let number = |key: &str| keys.get(key).and_then(|(v, _)| v.trim().parse::<u32>().ok());
+
+    if let (Some(start), Some(stop)) = (number("charging_start_threshold"), number("charging_stop_threshold")) {
+        if let Err(e) = crate::validate::thresholds(start.min(255) as u8, stop.min(255) as u8) {
+            return Some(("charging_start_threshold", e.to_string()));
+        }
+    }
+    if let Err(e) = crate::validate::frequency_limits(number("scaling_min_freq"), number("scaling_max_freq"), None) {
+        return Some(("scaling_min_freq", e.to_string()));
+    }
+    None
+}
+
+/// Merge `config` (read from a file of `source`) over the defaults. Values
+/// are checked against the schema of their section, so keys only some
+/// sections have (`[battery.BAT1]`, `[profile.quiet]`) get the same checks as
+/// `--check-config`; keys the schema doesn't know aren't checked here.
+pub fn explain(config: &Snapshot, source: Source, available: &Available) -> Vec<EffectiveValue> {
+    let schema = ConfigSchema::builtin();
+    let mut sections: BTreeMap<String, BTreeMap<String, (String, Source)>> = BTreeMap::new();
+
+    for (section, key, value) in DEFAULTS {
+        sections
+            .entry(section.to_string())
+            .or_default()
+            .insert(key.to_string(), (value.to_string(), Source::Default));
+    }
+    for section in config.sections() {
+        for (key, value) in config.section(section).into_iter().flatten() {
+            sections
+                .entry(section.to_string())
+                .or_default()
+                .insert(key.clone(), (value.clone(), source));
+        }
+    }
+
+    let mut values = Vec::new();
+    for (section, keys) in &sections {
+        let conflict = check_section(keys);
+        let section_schema = schema.section(section);
+        for (key, (value, source)) in keys {
+            let invalid = section_schema
+                .and_then(|s| s.key(key))
+                .and_then(|k| k.validate(value, available))
+                .or_else(|| conflict.as_ref().filter(|(k, _)| k == key).map(|(_, e)| e.clone()));
+            values.push(EffectiveValue {
+                section: section.clone(),
+                key: key.clone(),
+                value: value.clone(),
+                source: *source,
+                invalid,
+            });
+        }
+    }
+    values
+}
+
+/// The effective config of this run; `from_command_line` is whether the file
+/// was given with --config
+pub fn effective(from_command_line: bool) -> EffectiveConfig {
+    let path = CONFIG.get_path();
+    let file = CONFIG.has_config().then_some(path);
+    let source = match &file {
+        Some(path) => Source::of_file(path, from_command_line),
+        None => Source::Default,
+    };
+
+    let platform_profiles = crate::cpu::platform_profile::choices();
+    let available = Available { governors: &AVAILABLE_GOVERNORS_SORTED, platform_profiles: &platform_profiles };
+    let values = explain(&CONFIG.snapshot(), source, &available);
+
+    EffectiveConfig { file, source, values }
+}
+
+impl EffectiveConfig {
+    pub fn print(&self) {
+        match &self.file {
+            Some(file) => println!("Config file: {} ({})", file.display(), self.source.label()),
+            None => println!("Config file: none, using defaults"),
+        }
+
+        let mut section = None;
+        for value in &self.values {
+            if section != Some(&value.section) {
+                println!("\n[{}]", value.section);
+                section = Some(&value.section);
+            }
+            let setting = format!("{} = {}", value.key, value.value);
+            match &value.invalid {
+                Some(reason) => println!("  {:<52} {:<12} INVALID: {}", setting, value.source.label(), reason),
+                None => println!("  {:<52} {}", setting, value.source.label()),
+            }
+        }
+
+        let invalid = self.values.iter().filter(|v| v.invalid.is_some()).count();
+        if invalid > 0 {
+            println!("\n{} invalid value(s); the daemon falls back to defaults or skips them", invalid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_effective_config() {
+        let config = Snapshot::from_str_ini(
+            "[charger]\ngovernor = performance\nturbo = sometimes\n\
+             [battery]\ncharging_start_threshold = 90\ncharging_stop_threshold = 80\n\
+             [cpu.core.2]\nscaling_min_freq = 3000000\nscaling_max_freq = 800000\n\
+             [battery.BAT1]\ncharging_start_threshold = 40\ncharging_stop_threshold = 80\n\
+             [profile.quiet]\nturbo = never\nscaling_max_freq = 1600000\n",
+        )
+        .unwrap();
+        let governors = vec!["performance".to_string(), "powersave".to_string()];
+        let available = Available { governors: &governors, platform_profiles: &[] };
+        let values = explain(&config, Source::UserFile, &available);
+        let find = |section: &str, key: &str| values.iter().find(|v| v.section == section && v.key == key).unwrap();
+
+        assert_eq!(find("charger", "governor").source, Source::UserFile);
+        assert_eq!(find("charger", "governor").invalid, None);
+        assert_eq!(find("charger", "turbo").invalid.as_deref(), Some("turbo: expected one of always, never, auto"));
+        assert_eq!(find("charger", "energy_perf_bias").source, Source::Default);
+        assert_eq!(find("daemon", "max_interval").value, "30");
+        assert!(find("battery", "charging_start_threshold").invalid.is_some());
+        assert!(find("battery", "charging_stop_threshold").invalid.is_none());
+        assert!(find("cpu.core.2", "scaling_min_freq").invalid.is_some());
+        assert!(find("battery.bat1", "charging_stop_threshold").invalid.is_none());
+        assert!(find("battery.bat1", "charging_start_threshold").invalid.is_none());
+        assert!(values.iter().filter(|v| v.section == "profile.quiet").all(|v| v.invalid.is_none()));
+
+        assert_eq!(Source::of_file(Path::new("/etc/auto-cpufreq.toml"), false), Source::SystemFile);
+        assert_eq!(Source::of_file(Path::new("/etc/auto-cpufreq.conf"), false), Source::SystemFile);
+        assert_eq!(Source::of_file(Path::new("/home/me/.config/auto-cpufreq/auto-cpufreq.conf"), false), Source::UserFile);
+        assert_eq!(Source::of_file(Path::new("/etc/auto-cpufreq.conf"), true), Source::CommandLine);
+    }
+}
@@ -0,0 +1,154 @@
+// src/config/migrate.rs
+//
+// `--migrate-config`: convert an INI config to `auto-cpufreq.toml` next to it.
+// Sections become tables along their dotted names (`[cpu.core.0-7]` becomes
+// `[cpu.core."0-7"]` only where the name needs quoting) and values get the
+// TOML type the schema gives their key, so `enable_thresholds = true` is a
+// boolean and thresholds are integers; anything else, including keys the
+// schema doesn't know, stays a string. Comments aren't carried over. The INI
+// file is left alone: with both present the TOML one is read.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use super::parse_bool;
+use super::schema::{ConfigSchema, ValueType};
+use super::snapshot::{Snapshot, is_toml};
+
+/// A bare TOML key, or a quoted one when it has other characters
+pub(super) fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+/// Table header for INI section `section`. The part after a prefix such as
+/// `profile.` is one name even if it has dots.
+pub(super) fn table_path(schema: &ConfigSchema, section: &str) -> String {
+    let prefixed = schema
+        .sections
+        .iter()
+        .find(|s| s.prefix && section.strip_prefix(s.name).is_some_and(|rest| !rest.is_empty()));
+    let segments: Vec<&str> = match prefixed {
+        Some(prefixed) => {
+            let mut segments: Vec<&str> = prefixed.name.trim_end_matches('.').split('.').collect();
+            segments.push(&section[prefixed.name.len()..]);
+            segments
+        }
+        None => section.split('.').collect(),
+    };
+    segments.iter().map(|s| toml_key(s)).collect::<Vec<_>>().join(".")
+}
+
+pub(super) fn toml_value(value_type: Option<ValueType>, value: &str) -> toml::Value {
+    let value = value.trim();
+    let typed = match value_type {
+        Some(ValueType::Bool) => parse_bool(value).map(toml::Value::Boolean),
+        Some(ValueType::Integer { .. }) | Some(ValueType::Frequency) => value.parse().ok().map(toml::Value::Integer),
+        Some(ValueType::Positive) => match value.parse() {
+            Ok(integer) => Some(toml::Value::Integer(integer)),
+            Err(_) => value.parse().ok().map(toml::Value::Float),
+        },
+        _ => None,
+    };
+    typed.unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// `snapshot` as a TOML config, `source` naming the file it came from
+pub fn to_toml(snapshot: &Snapshot, source: &str) -> String {
+    let schema = ConfigSchema::builtin();
+    let mut out = format!(
+        "# Converted from {} by `auto-cpufreq --migrate-config`.\n\
+         # Comments of the original aren't carried over; auto_cpufreq.conf.example\n\
+         # describes every setting.\n",
+        source
+    );
+
+    for section in snapshot.sections() {
+        out.push_str(&format!("\n[{}]\n", table_path(&schema, section)));
+        let section_schema = schema.section(section);
+        for (key, value) in snapshot.section(section).into_iter().flatten() {
+            let value_type = section_schema.and_then(|s| s.key(key)).map(|k| k.value);
+            out.push_str(&format!("{} = {}\n", toml_key(key), toml_value(value_type, value)));
+        }
+    }
+    out
+}
+
+/// Whether two spellings of a value read the same (`yes` and `true`, `45` and `45.0`)
+pub(super) fn same_value(a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim(), b.trim());
+    a == b
+        || parse_bool(a).is_some_and(|a| parse_bool(b) == Some(a))
+        || a.parse::<f64>().is_ok_and(|a| b.parse::<f64>() == Ok(a))
+}
+
+fn same_settings(a: &Snapshot, b: &Snapshot) -> bool {
+    a.sections().eq(b.sections())
+        && a.sections().all(|section| {
+            let (a, b) = (a.section(section).unwrap(), b.section(section).unwrap());
+            a.len() == b.len() && a.iter().all(|(key, value)| b.get(key).is_some_and(|other| same_value(value, other)))
+        })
+}
+
+/// Write the INI config at `path` as TOML next to it, returning the new file
+pub fn migrate(path: &Path) -> Result<PathBuf> {
+    if is_toml(path) {
+        bail!("{} is already a TOML config", path.display());
+    }
+    if !path.is_file() {
+        bail!("No config file found at {}", path.display());
+    }
+    let target = path.with_extension("toml");
+    if target.exists() {
+        bail!("{} already exists, not overwriting it", target.display());
+    }
+
+    let snapshot = Snapshot::load(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let content = to_toml(&snapshot, &path.display().to_string());
+    // Nothing may get lost or change meaning on the way
+    let converted = Snapshot::from_toml(&content).map_err(|e| anyhow!("Converted config doesn't parse: {}", e))?;
+    if !same_settings(&snapshot, &converted) {
+        bail!("Converting {} would change its settings, leaving it as it is", path.display());
+    }
+
+    fs::write(&target, content).with_context(|| format!("Failed to write {}", target.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_to_toml() {
+        let dir = std::env::temp_dir().join(format!("acf-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let ini = dir.join("auto-cpufreq.conf");
+        fs::write(
+            &ini,
+            "# laptop\n[charger]\ngovernor = performance\nrapl_pl1 = 45.5\n\
+             [battery]\nenable_thresholds = yes\ncharging_stop_threshold = 80\n\
+             [cpu.core.0,2]\nonline = false\n[profile.my.quiet]\nturbo = never\n",
+        )
+        .unwrap();
+
+        let toml = migrate(&ini).unwrap();
+        assert_eq!(toml, dir.join("auto-cpufreq.toml"));
+        let content = fs::read_to_string(&toml).unwrap();
+        assert!(content.contains("[battery]\ncharging_stop_threshold = 80\nenable_thresholds = true\n"), "{}", content);
+        assert!(content.contains("[charger]\ngovernor = \"performance\"\nrapl_pl1 = 45.5\n"), "{}", content);
+        assert!(content.contains("[cpu.core.\"0,2\"]\nonline = false\n"), "{}", content);
+        assert!(content.contains("[profile.\"my.quiet\"]\nturbo = \"never\"\n"), "{}", content);
+        assert!(same_settings(&Snapshot::load(&ini).unwrap(), &Snapshot::load(&toml).unwrap()));
+
+        assert!(migrate(&ini).unwrap_err().to_string().contains("already exists"));
+        assert!(migrate(&toml).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,14 @@
 // src/config/mod.rs
 
+#[allow(clippy::module_inception)]
 pub mod config;
 pub mod config_event_handler;
+pub mod edit;
+pub mod effective;
+pub mod migrate;
+pub mod schema;
+pub mod snapshot;
 
-pub use config::{Config, find_config_file, CONFIG};
+pub use config::{Config, find_config_file, parse_bool, CONFIG};
 pub use config_event_handler::ConfigEventHandler;
+pub use snapshot::Snapshot;
@@ -0,0 +1,665 @@
+// src/config/schema.rs
+//
+// `--check-config`: every section and key the daemon reads, with the type and
+// allowed values of each, checked against the config file as written. Where
+// `--show-effective-config` shows what the daemon ends up using, this is about
+// the file itself: a key nothing reads (usually a typo, so the closest known
+// key is suggested), a section nothing reads, or a value the daemon would
+// refuse, each reported with its line. The daemon itself never rejects a file
+// over these, it only skips what it can't use.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::effective::{Available, Source, check, check_section, parse_choice};
+use super::parse_bool;
+use super::snapshot::{Snapshot, is_toml};
+use crate::globals::AVAILABLE_GOVERNORS_SORTED;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValueType {
+    Bool,
+    /// Whole number, `max` of `None` for no upper bound
+    Integer { min: u64, max: Option<u64> },
+    /// Above zero, fractions allowed
+    Positive,
+    /// kHz
+    Frequency,
+    /// Checked by the parser of the key where it has one (see
+    /// `effective::parse_choice`), `values` are what gets listed and suggested
+    Choice { values: &'static [&'static str] },
+    /// One the cpufreq driver offers
+    Governor,
+    /// One the firmware offers
+    PlatformProfile,
+    /// Checked by the parser of the key, see `effective::check`
+    Format { format: &'static str },
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct KeySchema {
+    pub name: &'static str,
+    #[serde(flatten)]
+    pub value: ValueType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionSchema {
+    /// Section name, or for `prefix` sections what their names start with
+    /// (`profile.` for `[profile.quiet]`)
+    pub name: &'static str,
+    pub prefix: bool,
+    pub keys: Vec<KeySchema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigSchema {
+    pub sections: Vec<SectionSchema>,
+}
+
+const fn key(name: &'static str, value: ValueType) -> KeySchema {
+    KeySchema { name, value }
+}
+
+const PERCENT: ValueType = ValueType::Integer { min: 0, max: Some(100) };
+const TURBO: ValueType = ValueType::Choice { values: &["always", "never", "auto"] };
+const INTEGRATION_MODE: ValueType = ValueType::Choice { values: &["cooperate", "disable", "ignore"] };
+
+/// What a profile can set, also read from [headless], [docked] and [weak_charger]
+const PROFILE_KEYS: &[KeySchema] = &[
+    key("governor", ValueType::Governor),
+    key("turbo", TURBO),
+    key("energy_performance_preference", ValueType::Text),
+    key("platform_profile", ValueType::PlatformProfile),
+    key("scaling_min_freq", ValueType::Frequency),
+    key("scaling_max_freq", ValueType::Frequency),
+];
+
+/// [charger] and [battery]
+const POWER_SOURCE_KEYS: &[KeySchema] = &[
+    key("energy_perf_bias", ValueType::Format { format: "performance, balance_performance, default, balance_power, power or 0-15" }),
+    key("hwp_dynamic_boost", ValueType::Bool),
+    key("min_perf_pct", PERCENT),
+    key("max_perf_pct", PERCENT),
+    key("min_perf_pct_low_load", PERCENT),
+    key("min_perf_pct_high_load", PERCENT),
+    key("max_perf_pct_low_load", PERCENT),
+    key("max_perf_pct_high_load", PERCENT),
+    key("asus_thermal_policy", ValueType::Format { format: "performance, balanced or silent" }),
+    key("rapl_pl1", ValueType::Positive),
+    key("rapl_pl2", ValueType::Positive),
+];
+
+const THRESHOLD_KEYS: &[KeySchema] = &[
+    key("charging_start_threshold", PERCENT),
+    key("charging_stop_threshold", PERCENT),
+];
+
+const BATTERY_KEYS: &[KeySchema] = &[
+    key("enable_thresholds", ValueType::Bool),
+    key("ideapad_laptop_conservation_mode", ValueType::Bool),
+    key(
+        "dell_charge_mode",
+        ValueType::Choice { values: &["standard", "adaptive", "express", "fast", "primarily_ac", "trickle", "custom"] },
+    ),
+    key("charge_behaviour", ValueType::Format { format: "auto, inhibit-charge or force-discharge" }),
+    key("battery_device", ValueType::Text),
+];
+
+fn section(name: &'static str, keys: &[&[KeySchema]]) -> SectionSchema {
+    SectionSchema { name, prefix: false, keys: keys.concat() }
+}
+
+fn prefixed(name: &'static str, keys: &[&[KeySchema]]) -> SectionSchema {
+    SectionSchema { name, prefix: true, keys: keys.concat() }
+}
+
+impl ConfigSchema {
+    /// Everything the daemon, CLI and GUI read from the config file
+    pub fn builtin() -> Self {
+        use ValueType::*;
+
+        let sections = vec![
+            section("charger", &[PROFILE_KEYS, POWER_SOURCE_KEYS]),
+            section("battery", &[PROFILE_KEYS, POWER_SOURCE_KEYS, THRESHOLD_KEYS, BATTERY_KEYS]),
+            prefixed("battery.", &[THRESHOLD_KEYS]),
+            section(
+                "platform",
+                &[&[
+                    key("amd_pstate_mode", Choice { values: &["active", "passive", "guided", "disable"] }),
+                    key("intel_pstate_mode", Choice { values: &["active", "passive"] }),
+                ]],
+            ),
+            prefixed(
+                "cpu.core.",
+                &[&[
+                    key("governor", Governor),
+                    key("scaling_min_freq", Frequency),
+                    key("scaling_max_freq", Frequency),
+                    key("online", Bool),
+                ]],
+            ),
+            section("thermal", &[&[key("turbo_temp_limit", Positive), key("skin_temp_limit", Positive)]]),
+            section(
+                "daemon",
+                &[&[
+                    key("min_interval", Positive),
+                    key("max_interval", Positive),
+                    key("usage_backend", Choice { values: &["auto", "procfs", "sysinfo"] }),
+                    key("restore_on_exit", Bool),
+                ]],
+            ),
+            section(
+                "logging",
+                &[&[
+                    key("level", Format { format: "error, warn, info, debug, trace or off" }),
+                    key("file", Text),
+                    key("max_files", Integer { min: 1, max: None }),
+                ]],
+            ),
+            section(
+                "stats",
+                &[&[
+                    key("fields", Format { format: "comma separated stats fields, e.g. governor, turbo, load, temps" }),
+                    key("verbose", Bool),
+                ]],
+            ),
+            section("performance_apps", &[&[key("apps", Text), key("on_battery", Bool)]]),
+            section(
+                "idle",
+                &[&[
+                    key("enabled", Bool),
+                    key("timeout", Integer { min: 1, max: None }),
+                    key("scaling_max_freq", Frequency),
+                ]],
+            ),
+            section("headless", &[PROFILE_KEYS]),
+            section(
+                "quiet_hours",
+                &[&[
+                    key("hours", Format { format: "HH:MM-HH:MM windows separated by commas" }),
+                    key("scaling_max_freq", Frequency),
+                ]],
+            ),
+            section(
+                "schedule",
+                &[&[key("rules", Format { format: "[days] HH:MM-HH:MM profile rules separated by commas" })]],
+            ),
+            section(
+                "battery_saver",
+                &[&[key("scaling_max_freq", Frequency), key("brightness", PERCENT), key("wifi_powersave", Bool)]],
+            ),
+            section("battery_levels", &[&[key("below_", Text)]]),
+            section("boot_boost", &[&[key("duration", Integer { min: 0, max: None })]]),
+            section("docked", &[PROFILE_KEYS, &[key("require_external_display", Bool)]]),
+            section("weak_charger", &[PROFILE_KEYS, &[key("max_watts", Positive), key("when_draining", Bool)]]),
+            section(
+                "integration",
+                &[&[
+                    key("respect_gamemode", Bool),
+                    key("gamemode_profile", Text),
+                    key("tuned_mode", INTEGRATION_MODE),
+                    key("tuned_profile_performance", Text),
+                    key("tuned_profile_balanced", Text),
+                    key("tuned_profile_powersave", Text),
+                    key("lpmd_mode", INTEGRATION_MODE),
+                    key("conflict_policy", Choice { values: &["warn", "disable", "coexist"] }),
+                    key("ppd_shim", Bool),
+                ]],
+            ),
+            prefixed("profile.", &[PROFILE_KEYS]),
+            prefixed("battery_profile.", &[THRESHOLD_KEYS]),
+            section(
+                "gui",
+                &[&[
+                    key("layout", Choice { values: &["default", "touch"] }),
+                ]],
+            ),
+            section("telemetry", &[&[key("enabled", Bool), key("endpoint", Text)]]),
+        ];
+
+        Self { sections }
+    }
+
+    /// Schema of the section called `name`
+    pub fn section(&self, name: &str) -> Option<&SectionSchema> {
+        self.sections.iter().find(|s| match s.prefix {
+            false => s.name == name,
+            true => name.strip_prefix(s.name).is_some_and(|rest| !rest.is_empty()),
+        })
+    }
+}
+
+/// How to write schema key `name`: `below_<percent>` for the `below_` family
+pub(super) fn key_hint(name: &str) -> String {
+    match name.ends_with('_') {
+        true => format!("{}<percent>", name),
+        false => name.to_string(),
+    }
+}
+
+impl SectionSchema {
+    /// A key name ending in `_` stands for that name followed by a
+    /// percentage, `below_` for `below_20`
+    pub fn key(&self, name: &str) -> Option<&KeySchema> {
+        self.keys.iter().find(|k| match k.name.ends_with('_') {
+            true => name.strip_prefix(k.name).is_some_and(|n| n.parse::<u8>().is_ok_and(|n| n <= 100)),
+            false => k.name == name,
+        })
+    }
+}
+
+impl KeySchema {
+    /// Why `value` won't be used, `None` when it is fine
+    pub fn validate(&self, value: &str, available: &Available) -> Option<String> {
+        let value = value.trim();
+        let expected = match self.value {
+            ValueType::Bool => parse_bool(value).is_none().then(|| "expected true or false".to_string()),
+            ValueType::Integer { min, max } => {
+                let ok = value.parse::<u64>().is_ok_and(|n| n >= min && max.is_none_or(|max| n <= max));
+                (!ok).then(|| match max {
+                    Some(max) => format!("expected a whole number from {} to {}", min, max),
+                    None => format!("expected a whole number of at least {}", min),
+                })
+            }
+            ValueType::Positive => {
+                (!value.parse::<f64>().is_ok_and(|n| n > 0.0)).then(|| "expected a number above 0".to_string())
+            }
+            ValueType::Frequency => {
+                value.parse::<u32>().is_err().then(|| "expected a frequency in kHz, e.g. 2000000".to_string())
+            }
+            ValueType::Choice { values } => {
+                let ok = parse_choice(self.name, value).unwrap_or_else(|| values.contains(&value));
+                (!ok).then(|| format!("expected one of {}", values.join(", ")))
+            }
+            ValueType::Format { format } => check(self.name, value, available).map(|_| format!("expected {}", format)),
+            ValueType::Governor | ValueType::PlatformProfile => check(self.name, value, available),
+            ValueType::Text => None,
+        };
+        expected.map(|e| format!("{}: {}", self.name, e))
+    }
+
+    /// Closest allowed value to a misspelled one
+    pub(super) fn suggest_value(&self, value: &str, available: &Available) -> Option<String> {
+        let value = value.trim().to_lowercase();
+        match self.value {
+            ValueType::Choice { values } => closest(&value, values.iter().copied()),
+            ValueType::Governor => closest(&value, available.governors.iter().map(String::as_str)),
+            ValueType::PlatformProfile => closest(&value, available.platform_profiles.iter().map(String::as_str)),
+            ValueType::Bool => closest(&value, ["true", "false"].into_iter()),
+            _ => None,
+        }
+        .map(String::from)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Problem {
+    /// Not `[section]` nor `key = value`
+    Syntax,
+    UnknownSection,
+    UnknownKey,
+    /// Set again further down; the last one wins
+    Duplicate,
+    InvalidValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    /// 1-based
+    pub line: usize,
+    /// The line as written
+    pub text: String,
+    pub problem: Problem,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidate closest to `word`, if close enough to be a typo of it
+pub(super) fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (word.chars().count() / 4).max(2);
+    candidates
+        .map(|c| (edit_distance(word, c), c))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+/// A line of an ini file, the way configparser reads it
+pub(super) enum Line<'a> {
+    Blank,
+    Section(String),
+    Entry { key: String, value: Option<&'a str> },
+    Invalid,
+}
+
+pub(super) fn parse_line(line: &str) -> Line<'_> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+        return Line::Blank;
+    }
+    if let Some(name) = line.strip_prefix('[') {
+        return match name.strip_suffix(']') {
+            Some(name) if !name.trim().is_empty() => Line::Section(name.trim().to_lowercase()),
+            _ => Line::Invalid,
+        };
+    }
+    match line.find(['=', ':']) {
+        Some(0) => Line::Invalid,
+        Some(at) => Line::Entry { key: line[..at].trim().to_lowercase(), value: Some(&line[at + 1..]) },
+        None => Line::Entry { key: line.to_lowercase(), value: None },
+    }
+}
+
+/// The known section an unknown one is probably a typo of
+pub(super) fn suggest_section(schema: &ConfigSchema, name: &str) -> Option<String> {
+    let fixed = schema.sections.iter().filter(|s| !s.prefix).map(|s| s.name);
+    if let Some(found) = closest(name, fixed) {
+        return Some(found.to_string());
+    }
+    // [profle.quiet] → [profile.quiet]
+    let (head, tail) = name.rsplit_once('.')?;
+    let head = format!("{}.", head);
+    let prefixes = schema.sections.iter().filter(|s| s.prefix).map(|s| s.name);
+    closest(&head, prefixes).map(|prefix| format!("{}{}", prefix, tail))
+}
+
+/// Name of a TOML table header: `cpu.core."0-7"` is `cpu.core.0-7`
+fn toml_table_name(header: &str) -> String {
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut quote = None;
+    for c in header.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '.') => segments.push(std::mem::take(&mut segment).trim().to_string()),
+            (_, c) => segment.push(c),
+        }
+    }
+    segments.push(segment.trim().to_string());
+    segments.join(".").to_lowercase()
+}
+
+/// A line of a TOML file that the TOML parser accepted. Values come from the
+/// parsed `snapshot`, the lines only tell where they are; dotted keys and the
+/// continuation lines of multi-line values are skipped.
+pub(super) fn parse_toml_line<'a>(line: &str, section: Option<&str>, snapshot: &'a Snapshot) -> Line<'a> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Line::Blank;
+    }
+    if let Some(header) = line.strip_prefix('[') {
+        let header = header.trim_start_matches('[');
+        return match header.rfind(']') {
+            Some(end) => Line::Section(toml_table_name(&header[..end])),
+            None => Line::Invalid,
+        };
+    }
+    let Some((key, _)) = line.split_once('=') else {
+        return Line::Blank;
+    };
+    let key = key.trim();
+    let key = match key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        Some(quoted) => quoted,
+        None if key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') => key,
+        None => return Line::Blank,
+    };
+    let key = key.to_lowercase();
+    match section {
+        Some(section) => match snapshot.get(section, &key) {
+            Some(value) => Line::Entry { key, value: Some(value) },
+            None => Line::Blank,
+        },
+        // Reported as outside of a section whatever its value
+        None => Line::Entry { key, value: None },
+    }
+}
+
+impl ConfigSchema {
+    /// Check INI config file `content` against the schema
+    pub fn check(&self, content: &str, available: &Available) -> Vec<Finding> {
+        self.check_lines(content, content.lines().map(parse_line).collect(), available)
+    }
+
+    /// Check TOML config file `content` against the schema
+    pub fn check_toml(&self, content: &str, available: &Available) -> Vec<Finding> {
+        let syntax = |line: usize, message: String| {
+            let text = content.lines().nth(line.saturating_sub(1)).unwrap_or_default().trim().to_string();
+            vec![Finding { line, text, problem: Problem::Syntax, message, suggestion: None }]
+        };
+        if let Err(e) = content.parse::<toml::Table>() {
+            let line = e.span().map_or(1, |span| content[..span.start].matches('\n').count() + 1);
+            return syntax(line, e.message().replace('\n', ", "));
+        }
+        let snapshot = match Snapshot::from_toml(content) {
+            Ok(snapshot) => snapshot,
+            Err(e) => return syntax(1, e),
+        };
+
+        let mut section = None;
+        let mut lines = Vec::new();
+        for text in content.lines() {
+            let line = parse_toml_line(text, section.as_deref(), &snapshot);
+            if let Line::Section(name) = &line {
+                section = Some(name.clone());
+            }
+            lines.push(line);
+        }
+        self.check_lines(content, lines, available)
+    }
+
+    fn check_lines(&self, content: &str, parsed: Vec<Line>, available: &Available) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut current: Option<(String, Option<&SectionSchema>)> = None;
+        // Values of each known section and the line each was set on, for the
+        // checks between keys
+        let mut values: BTreeMap<String, BTreeMap<String, (String, Source)>> = BTreeMap::new();
+        let mut lines: BTreeMap<(String, String), usize> = BTreeMap::new();
+
+        for (index, (text, line)) in content.lines().zip(parsed).enumerate() {
+            let number = index + 1;
+            let mut report = |problem, message: String, suggestion: Option<String>| {
+                findings.push(Finding { line: number, text: text.trim().to_string(), problem, message, suggestion });
+            };
+
+            match line {
+                Line::Blank => {}
+                Line::Invalid => report(Problem::Syntax, "expected [section] or key = value".to_string(), None),
+                Line::Section(name) => {
+                    let schema = self.section(&name);
+                    match schema {
+                        Some(schema) if schema.name == "cpu.core." => {
+                            let spec = &name[schema.name.len()..];
+                            let highest = crate::cpu::per_core::highest_core();
+                            if let Err(e) = crate::cpu::per_core::parse_core_spec(spec, highest) {
+                                report(Problem::UnknownSection, format!("[{}]: {}", name, e), None);
+                            }
+                        }
+                        Some(_) => {}
+                        None => {
+                            let suggestion = suggest_section(self, &name);
+                            report(Problem::UnknownSection, format!("unknown section [{}]", name), suggestion);
+                        }
+                    }
+                    current = Some((name, schema));
+                }
+                Line::Entry { key, value } => {
+                    let (section, schema) = match &current {
+                        // Keys of an unknown section aren't reported again
+                        Some((_, None)) => continue,
+                        Some((section, Some(schema))) => (section, schema),
+                        None => {
+                            report(Problem::Syntax, format!("{} is not in a [section]", key), None);
+                            continue;
+                        }
+                    };
+
+                    let Some(key_schema) = schema.key(&key) else {
+                        let known = schema.keys.iter().map(|k| k.name);
+                        let (message, suggestion) = match closest(&key, known) {
+                            Some(found) => (format!("unknown key {} in [{}]", key, section), Some(key_hint(found))),
+                            None => match self.sections.iter().find(|s| !s.prefix && s.key(&key).is_some()) {
+                                Some(other) => (format!("{} is not read from [{}], only from [{}]", key, section, other.name), None),
+                                None => (format!("unknown key {} in [{}]", key, section), None),
+                            },
+                        };
+                        report(Problem::UnknownKey, message, suggestion);
+                        continue;
+                    };
+
+                    let Some(value) = value else {
+                        report(Problem::InvalidValue, format!("{} has no value and is ignored", key), None);
+                        continue;
+                    };
+                    if let Some(first) = lines.insert((section.clone(), key.clone()), number) {
+                        report(Problem::Duplicate, format!("{} is also set on line {}, this one wins", key, first), None);
+                    }
+                    if let Some(message) = key_schema.validate(value, available) {
+                        report(Problem::InvalidValue, message, key_schema.suggest_value(value, available));
+                    }
+                    values
+                        .entry(section.clone())
+                        .or_default()
+                        .insert(key, (value.trim().to_string(), Source::UserFile));
+                }
+            }
+        }
+
+        for (section, keys) in &values {
+            if let Some((key, message)) = check_section(keys) {
+                if let Some(&line) = lines.get(&(section.clone(), key.to_string())) {
+                    let text = content.lines().nth(line - 1).unwrap_or_default().trim().to_string();
+                    findings.push(Finding { line, text, problem: Problem::InvalidValue, message, suggestion: None });
+                }
+            }
+        }
+        findings.sort_by_key(|f| f.line);
+        findings
+    }
+}
+
+/// Result of `--check-config`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigCheck {
+    pub file: String,
+    pub findings: Vec<Finding>,
+}
+
+/// Check the config file at `path` against what this machine offers
+pub fn check_file(path: &Path) -> anyhow::Result<ConfigCheck> {
+    use anyhow::Context;
+
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let platform_profiles = crate::cpu::platform_profile::choices();
+    let available = Available { governors: &AVAILABLE_GOVERNORS_SORTED, platform_profiles: &platform_profiles };
+    let schema = ConfigSchema::builtin();
+    let findings = match is_toml(path) {
+        true => schema.check_toml(&content, &available),
+        false => schema.check(&content, &available),
+    };
+    Ok(ConfigCheck { file: path.display().to_string(), findings })
+}
+
+impl ConfigCheck {
+    pub fn print(&self) {
+        println!("Checking {}\n", self.file);
+        for finding in &self.findings {
+            match &finding.suggestion {
+                Some(suggestion) => println!("line {}: {} (did you mean {}?)", finding.line, finding.message, suggestion),
+                None => println!("line {}: {}", finding.line, finding.message),
+            }
+            println!("    {}", finding.text);
+        }
+        match self.findings.len() {
+            0 => println!("No problems found"),
+            count => println!("\n{} problem(s) found", count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_config() {
+        let governors = vec!["performance".to_string(), "powersave".to_string()];
+        let available = Available { governors: &governors, platform_profiles: &[] };
+        let content = "\
+# comment
+[charger]
+goveror = performance
+turbo = nevr
+enable_thresholds = true
+
+[battery]
+governor = powersave
+charging_start_threshold = 90
+charging_stop_threshold = 80
+turbo = never
+turbo = auto
+
+[profle.quiet]
+turbo = never
+[cpu.core.4-7]
+scaling_max_freq = 2000000
+[cpu.core.x]
+[logging]
+max_files = 0
+";
+        let findings = ConfigSchema::builtin().check(content, &available);
+        let at = |line: usize| findings.iter().find(|f| f.line == line).unwrap();
+
+        assert_eq!(at(3).problem, Problem::UnknownKey);
+        assert_eq!(at(3).suggestion.as_deref(), Some("governor"));
+        assert_eq!(at(4).message, "turbo: expected one of always, never, auto");
+        assert_eq!(at(4).suggestion.as_deref(), Some("never"));
+        assert_eq!(at(5).message, "enable_thresholds is not read from [charger], only from [battery]");
+        assert_eq!(at(9).problem, Problem::InvalidValue);
+        assert_eq!(at(12).problem, Problem::Duplicate);
+        assert_eq!(at(14).suggestion.as_deref(), Some("profile.quiet"));
+        assert_eq!(at(18).problem, Problem::UnknownSection);
+        assert_eq!(at(20).message, "max_files: expected a whole number of at least 1");
+        assert_eq!(findings.len(), 8);
+
+        // The key's own parser decides: turbo is case-sensitive, proc is procfs
+        let choices = ConfigSchema::builtin().check("[charger]\nturbo = Always\n[daemon]\nusage_backend = proc\n", &available);
+        assert_eq!(choices.len(), 1);
+        assert_eq!((choices[0].line, choices[0].suggestion.as_deref()), (2, Some("always")));
+
+        let toml = "[charger]\ngoveror = \"performance\"\napps = [\n  \"x = y\",\n]\n\
+                    [cpu.core.\"0,2\"]\nonline = \"maybe\"\n[battery]\nturbo = \"never\"\n";
+        let findings = ConfigSchema::builtin().check_toml(toml, &available);
+        let lines: Vec<_> = findings.iter().map(|f| (f.line, f.problem)).collect();
+        assert_eq!(lines, [(2, Problem::UnknownKey), (3, Problem::UnknownKey), (7, Problem::InvalidValue)]);
+        let findings = ConfigSchema::builtin().check_toml("[battery]\nturbo = \"never\"\n\n[battery]\n", &available);
+        assert_eq!((findings[0].line, findings[0].problem), (4, Problem::Syntax));
+
+        assert_eq!(edit_distance("scaling_max_frq", "scaling_max_freq"), 1);
+        assert_eq!(closest("chargr", ["charger", "battery"].into_iter()), Some("charger"));
+        assert_eq!(closest("foo", ["charger", "battery"].into_iter()), None);
+    }
+}
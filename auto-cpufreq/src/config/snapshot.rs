@@ -0,0 +1,225 @@
+// src/config/snapshot.rs
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use configparser::ini::Ini;
+use serde::{Deserialize, Serialize};
+
+/// Where configparser puts keys that come before any `[section]`
+const DEFAULT_SECTION: &str = "default";
+
+/// Immutable, fully parsed view of the config file.
+///
+/// A new snapshot is built on every (re)load and swapped in atomically, so readers
+/// never take a lock and never see a half-loaded file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Snapshot {
+    /// Keys without a value (`key` alone on a line) are dropped, matching how
+    /// `Ini::get` reports them as unset
+    pub fn from_ini(ini: &Ini) -> Self {
+        let sections = ini
+            .get_map()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(section, keys)| {
+                let keys = keys
+                    .into_iter()
+                    .filter_map(|(k, v)| v.map(|v| (k, v)))
+                    .collect();
+                (section, keys)
+            })
+            .collect();
+
+        Self { sections }
+    }
+
+    /// Parse ini text, mostly useful for tests
+    pub fn from_str_ini(content: &str) -> Result<Self, String> {
+        let mut ini = Ini::new();
+        ini.read(content.to_string())?;
+        Ok(Self::from_ini(&ini))
+    }
+
+    /// Parse a TOML config. Tables are sections, nested ones named by their
+    /// path (`[cpu.core."0-7"]` is the INI `[cpu.core.0-7]`), and values are
+    /// kept as the text the INI file would have: `true`, `80`, arrays joined
+    /// with commas. Names are lowercased like the INI parser does.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let table: toml::Table = content.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut sections = BTreeMap::new();
+        flatten_toml(&table, None, &mut sections)?;
+        Ok(Self { sections })
+    }
+
+    /// Read the config file at `path`: TOML for `.toml` files, INI otherwise
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if is_toml(path) {
+            let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            Self::from_toml(&content)
+        } else {
+            let mut ini = Ini::new();
+            ini.load(path.to_str().unwrap_or(""))?;
+            Ok(Self::from_ini(&ini))
+        }
+    }
+
+    /// Section and key lookups are case-insensitive, like the underlying parser
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .get(&section.to_lowercase())
+            .and_then(|keys| keys.get(&key.to_lowercase()))
+            .map(String::as_str)
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.sections.keys().map(String::as_str)
+    }
+
+    pub fn section(&self, section: &str) -> Option<&BTreeMap<String, String>> {
+        self.sections.get(&section.to_lowercase())
+    }
+
+    /// Keys that were added, removed or changed on the way to `newer`
+    pub fn changes(&self, newer: &Snapshot) -> Vec<Change> {
+        let empty = BTreeMap::new();
+        let mut names: Vec<&String> = self.sections.keys().chain(newer.sections.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        let mut changes = Vec::new();
+        for section in names {
+            let old = self.sections.get(section).unwrap_or(&empty);
+            let new = newer.sections.get(section).unwrap_or(&empty);
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                if old.get(key) != new.get(key) {
+                    changes.push(Change {
+                        section: section.clone(),
+                        key: key.clone(),
+                        old: old.get(key).cloned(),
+                        new: new.get(key).cloned(),
+                    });
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// One key that differs between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub section: String,
+    pub key: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {} ", self.section, self.key)?;
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{} -> {}", old, new),
+            (None, Some(new)) => write!(f, "set to {}", new),
+            (Some(old), None) => write!(f, "removed (was {})", old),
+            (None, None) => f.write_str("unchanged"),
+        }
+    }
+}
+
+pub fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+fn flatten_toml(
+    table: &toml::Table,
+    path: Option<&str>,
+    sections: &mut BTreeMap<String, BTreeMap<String, String>>,
+) -> Result<(), String> {
+    for (key, value) in table {
+        let key = key.to_lowercase();
+        match value {
+            toml::Value::Table(inner) => {
+                let name = match path {
+                    Some(path) => format!("{}.{}", path, key),
+                    None => key,
+                };
+                flatten_toml(inner, Some(&name), sections)?;
+            }
+            value => {
+                let section = path.unwrap_or(DEFAULT_SECTION).to_string();
+                sections.entry(section).or_default().insert(key, toml_text(value)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn toml_text(value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Array(items) => Ok(items.iter().map(toml_text).collect::<Result<Vec<_>, _>>()?.join(", ")),
+        toml::Value::Table(_) => Err("tables inside arrays are not supported".to_string()),
+        other => Ok(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_get() {
+        let snapshot = Snapshot::from_str_ini("[Charger]\ngovernor = performance\nflag\n").unwrap();
+        assert_eq!(snapshot.get("charger", "governor"), Some("performance"));
+        assert_eq!(snapshot.get("CHARGER", "Governor"), Some("performance"));
+        assert_eq!(snapshot.get("charger", "flag"), None);
+        assert_eq!(snapshot.get("battery", "governor"), None);
+    }
+
+    #[test]
+    fn test_snapshot_serde_roundtrip() {
+        let snapshot = Snapshot::from_str_ini("[battery]\nturbo = never\n").unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let back: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot, back);
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let old = Snapshot::from_str_ini("[charger]\ngovernor = powersave\nturbo = auto\n[battery]\nturbo = never\n").unwrap();
+        let new = Snapshot::from_str_ini("[charger]\ngovernor = performance\nturbo = auto\nenergy_performance_preference = performance\n").unwrap();
+        let changes: Vec<String> = old.changes(&new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                "[battery] turbo removed (was never)",
+                "[charger] energy_performance_preference set to performance",
+                "[charger] governor powersave -> performance",
+            ]
+        );
+        assert!(new.changes(&new).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_from_toml() {
+        let toml = "[charger]\ngovernor = \"performance\"\nhwp_dynamic_boost = true\nrapl_pl1 = 45.5\n\
+                    [battery]\ncharging_stop_threshold = 80\n[battery.BAT1]\ncharging_stop_threshold = 60\n\
+                    [cpu.core.\"0,2\"]\nonline = false\n[performance_apps]\napps = [\"rustc\", \"blender\"]\n";
+        let ini = "[charger]\ngovernor = performance\nhwp_dynamic_boost = true\nrapl_pl1 = 45.5\n\
+                   [battery]\ncharging_stop_threshold = 80\n[battery.BAT1]\ncharging_stop_threshold = 60\n\
+                   [cpu.core.0,2]\nonline = false\n[performance_apps]\napps = rustc, blender\n";
+        assert_eq!(Snapshot::from_toml(toml).unwrap(), Snapshot::from_str_ini(ini).unwrap());
+        let error = Snapshot::from_toml("[battery]\nturbo = never\n").unwrap_err();
+        assert!(error.contains("line 2") && error.contains("invalid string"), "{}", error);
+        assert!(is_toml(Path::new("/etc/auto-cpufreq.toml")));
+    }
+}
@@ -0,0 +1,444 @@
+// src/control.rs
+//
+// Control socket of the running daemon, /run/auto-cpufreq.sock. A client
+// writes one command line and reads the reply until the daemon closes the
+// connection. The first reply line is `ok` or `error: <reason>`, anything
+// after it is the output of the command:
+//
+//   reload-config                       re-read the config file
+//   set-override <governor> [ttl]       like --force, optionally with --for
+//   set-turbo <always|never|auto> [ttl] like --turbo
+//   set-profile <name|reset>            like --profile
+//   set-battery-profile <name|reset>    like --battery-profile
+//   status [--json]                     governor, turbo, overrides, profile
+//
+// Clients are served one at a time on a thread of their own, with a few more
+// queued and any beyond that turned away as busy, so neither a slow client nor
+// the polkit check holds up the daemon's loop. A client gets one deadline for
+// its whole command and a small cap on its length, so a trickling or endless
+// line can't hold the queue or the daemon's memory. A command that changed
+// something wakes the daemon for a cycle right away, so `--force` and
+// `--turbo` take effect at once instead of at the next check. Anyone may ask
+// for the status. The other commands are run for root clients, and for others
+// when polkit allows the command's action (see polkit.rs), which is how the
+// GUI and unprivileged CLI sessions change settings. Under systemd the
+// socket comes from auto-cpufreq.socket instead, started along with the
+// service.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use nix::sys::socket::{getsockopt, sockopt::PeerCredentials, UnixCredentials};
+use tracing::{info, warn};
+
+use crate::config::CONFIG;
+use crate::core::{self, AutoCpuFreqState, GovernorOverride, TurboOverride};
+use crate::event_code::EventCode;
+
+pub const SOCKET_PATH: &str = "/run/auto-cpufreq.sock";
+
+/// A client that doesn't send its command within this doesn't hold up the others
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Longest command line accepted, far above any real command
+const MAX_COMMAND_LENGTH: u64 = 4096;
+
+/// Connections waiting while one is served; more are turned away as busy
+const QUEUE_LENGTH: usize = 8;
+
+/// polkit action of governor overrides, also what the ppd shim checks
+pub const SET_GOVERNOR_ACTION: &str = "org.auto-cpufreq.set-governor";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    ReloadConfig,
+    SetOverride { governor: GovernorOverride, ttl: Option<Duration> },
+    SetTurbo { turbo: TurboOverride, ttl: Option<Duration> },
+    SetProfile { name: String },
+    SetBatteryProfile { name: String },
+    Status { json: bool },
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let ttl = |value: Option<&&str>| value.map(|v| core::parse_override_ttl(v)).transpose();
+        match words.as_slice() {
+            ["reload-config"] => Ok(Self::ReloadConfig),
+            ["set-override", value] | ["set-override", value, _] => match GovernorOverride::parse(value) {
+                Some(governor) => Ok(Self::SetOverride { governor, ttl: ttl(words.get(2))? }),
+                None => bail!("Invalid governor override: {} (use performance, powersave or reset)", value),
+            },
+            ["set-turbo", value] | ["set-turbo", value, _] => match TurboOverride::parse(value) {
+                Some(turbo) => Ok(Self::SetTurbo { turbo, ttl: ttl(words.get(2))? }),
+                None => bail!("Invalid turbo override: {} (use always, never or auto)", value),
+            },
+            ["set-profile", name] => Ok(Self::SetProfile { name: name.to_string() }),
+            ["set-battery-profile", name] => Ok(Self::SetBatteryProfile { name: name.to_string() }),
+            ["status"] => Ok(Self::Status { json: false }),
+            ["status", "--json"] => Ok(Self::Status { json: true }),
+            [] => bail!("Empty command"),
+            _ => bail!(
+                "Unknown command: {} (use reload-config, set-override, set-turbo, set-profile, set-battery-profile or status)",
+                line.trim()
+            ),
+        }
+    }
+
+    /// Whether the daemon should run a cycle after it
+    fn changes_settings(&self) -> bool {
+        self.action_id().is_some()
+    }
+
+    /// polkit action a client other than root needs, `None` for anyone
+    pub fn action_id(&self) -> Option<&'static str> {
+        match self {
+            Self::ReloadConfig => Some("org.auto-cpufreq.reload-config"),
+            Self::SetOverride { .. } => Some(SET_GOVERNOR_ACTION),
+            Self::SetTurbo { .. } => Some("org.auto-cpufreq.set-turbo"),
+            Self::SetProfile { .. } => Some("org.auto-cpufreq.set-profile"),
+            Self::SetBatteryProfile { .. } => Some("org.auto-cpufreq.set-battery-profile"),
+            Self::Status { .. } => None,
+        }
+    }
+}
+
+fn status(json: bool) -> String {
+    let state = AutoCpuFreqState::new();
+    let stored = state.store().load();
+    let governor = core::get_current_gov().ok();
+    let turbo = core::turbo(None).ok();
+    if json {
+        return serde_json::json!({
+            "pid": std::process::id(),
+            "governor": governor,
+            "turbo": turbo,
+            "override": core::get_override(&state).to_str(),
+            "override_until": stored.governor_override_until,
+            "turbo_override": core::get_turbo_override(&state).to_str(),
+            "turbo_override_until": stored.turbo_override_until,
+            "profile": stored.last_profile,
+        })
+        .to_string();
+    }
+
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    [
+        format!("pid: {}", std::process::id()),
+        format!("governor: {}", governor.as_deref().unwrap_or("unknown")),
+        format!("turbo: {}", turbo.map(on_off).unwrap_or("unknown")),
+        format!("override: {}", core::get_override(&state).to_str()),
+        format!("turbo override: {}", core::get_turbo_override(&state).to_str()),
+        format!("profile: {}", stored.last_profile.as_deref().unwrap_or("none")),
+    ]
+    .join("\n")
+}
+
+/// Carry out `command` in the daemon, returning the reply body
+fn execute(command: &Command) -> Result<String> {
+    let state = AutoCpuFreqState::new();
+    match command {
+        Command::ReloadConfig => {
+            CONFIG.update_config()?;
+            Ok(format!("Reloaded {}", CONFIG.get_path().display()))
+        }
+        Command::SetOverride { governor, ttl } => {
+            core::store_override(&state, governor, *ttl)?;
+            Ok(match governor {
+                GovernorOverride::Default => "Governor override removed".to_string(),
+                _ => format!("Set governor override to {}{}", governor.to_str(), core::ttl_suffix(*ttl)),
+            })
+        }
+        Command::SetTurbo { turbo, ttl } => {
+            core::store_turbo_override(&state, turbo, *ttl)?;
+            Ok(match turbo {
+                TurboOverride::Auto => "Turbo override removed".to_string(),
+                _ => format!("Set turbo boost override to {}{}", turbo.to_str(), core::ttl_suffix(*ttl)),
+            })
+        }
+        Command::SetProfile { name } => {
+            crate::profiles::select(Some(name))?;
+            Ok(match crate::state::StateStore::default().load().last_profile {
+                Some(name) => format!("Selected profile: {}", name),
+                None => "Profile selection removed".to_string(),
+            })
+        }
+        Command::SetBatteryProfile { name } => {
+            crate::battery::profiles::select(Some(name))?;
+            Ok(match crate::state::StateStore::default().load().battery_profile {
+                Some(name) => format!("Selected battery profile: {}", name),
+                None => "Battery profile selection removed".to_string(),
+            })
+        }
+        Command::Status { json } => Ok(status(*json)),
+    }
+}
+
+/// Root may run anything, other clients what polkit allows them
+fn authorize(client: nix::Result<UnixCredentials>, action_id: &str) -> Result<()> {
+    let client = client.context("Can't tell who the client is")?;
+    if client.uid() == 0 {
+        return Ok(());
+    }
+    if !crate::polkit::is_authorized(action_id, client.pid() as u32, client.uid())? {
+        bail!("Not authorized for {}, run it as root or allow it in polkit", action_id);
+    }
+    Ok(())
+}
+
+/// The daemon's end of the socket
+pub struct ControlSocket {
+    /// Readable once a command changed a setting, a byte per command
+    changes: UnixStream,
+    /// `None` for a socket systemd created and removes
+    path: Option<PathBuf>,
+}
+
+impl ControlSocket {
+    pub fn bind() -> Result<Self> {
+        if let Some(fd) = crate::sd_notify::take_listen_fd() {
+            return Self::serve(UnixListener::from(fd), None);
+        }
+        Self::bind_at(SOCKET_PATH)
+    }
+
+    /// Only called with the PID file locked, so a socket already there is
+    /// left over from a daemon that didn't exit cleanly
+    pub fn bind_at(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        // Clients are checked per command, see `serve_client`
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o666))?;
+        Self::serve(listener, Some(path))
+    }
+
+    /// Start the threads that accept and answer clients
+    fn serve(listener: UnixListener, path: Option<PathBuf>) -> Result<Self> {
+        listener.set_nonblocking(false)?;
+        let (changes, notify) = UnixStream::pair()?;
+        changes.set_nonblocking(true)?;
+        // A full buffer already wakes the daemon
+        notify.set_nonblocking(true)?;
+
+        let (queue, clients) = mpsc::sync_channel(QUEUE_LENGTH);
+        std::thread::Builder::new()
+            .name("control-accept".to_string())
+            .spawn(move || accept_clients(listener, queue))?;
+        std::thread::Builder::new().name("control".to_string()).spawn(move || {
+            for stream in clients {
+                if serve_client(stream) {
+                    let _ = (&notify).write_all(&[1]);
+                }
+            }
+        })?;
+        Ok(Self { changes, path })
+    }
+
+    /// The stream that turns readable when a command changed a setting
+    pub(crate) fn changes(&self) -> &UnixStream {
+        &self.changes
+    }
+}
+
+impl AsFd for ControlSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.changes.as_fd()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Hand connections to the serving thread, or tell them the daemon is busy
+fn accept_clients(listener: UnixListener, queue: SyncSender<UnixStream>) {
+    for stream in listener.incoming() {
+        match stream.map(|stream| queue.try_send(stream)) {
+            Ok(Ok(())) => {}
+            Ok(Err(TrySendError::Full(mut stream))) => {
+                let _ = stream.set_write_timeout(Some(CLIENT_TIMEOUT));
+                let _ = stream.write_all(b"error: The daemon is busy, try again\n");
+            }
+            Ok(Err(TrySendError::Disconnected(_))) => return,
+            Err(e) => {
+                warn!(code = %EventCode::ControlRequestFailed, "Control socket: {}", e);
+                // e.g. out of file descriptors, don't spin on it
+                std::thread::sleep(CLIENT_TIMEOUT);
+            }
+        }
+    }
+}
+
+/// One connection: read the command, reply, and say whether it changed anything
+fn serve_client(mut stream: UnixStream) -> bool {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_write_timeout(Some(CLIENT_TIMEOUT));
+
+    let mut line = String::new();
+    let result = read_command(&stream, Instant::now() + CLIENT_TIMEOUT)
+        .and_then(|command| {
+            line = command;
+            Command::parse(&line)
+        })
+        .and_then(|command| {
+            if let Some(action_id) = command.action_id() {
+                authorize(getsockopt(&stream, PeerCredentials), action_id)?;
+            }
+            execute(&command).map(|reply| (command, reply))
+        });
+
+    let (reply, changed) = match result {
+        Ok((command, body)) => {
+            if command.changes_settings() {
+                info!("Control socket: {}", line.trim());
+            }
+            (format!("ok\n{}\n", body), command.changes_settings())
+        }
+        Err(e) => (format!("error: {}\n", e), false),
+    };
+    let _ = stream.write_all(reply.as_bytes());
+    changed
+}
+
+/// Read one command line, all of it by `deadline` and at most
+/// MAX_COMMAND_LENGTH bytes of it
+fn read_command(stream: &UnixStream, deadline: Instant) -> Result<String> {
+    let mut reader = stream.take(MAX_COMMAND_LENGTH);
+    let mut line = Vec::new();
+    let mut chunk = [0u8; 512];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            bail!("Timed out reading the command");
+        }
+        stream.set_read_timeout(Some(left))?;
+        let read = reader.read(&mut chunk).context("Failed to read the command")?;
+        line.extend_from_slice(&chunk[..read]);
+        if let Some(end) = line.iter().position(|&b| b == b'\n') {
+            line.truncate(end);
+            break;
+        }
+        if read == 0 {
+            if reader.limit() == 0 {
+                bail!("The command is longer than {} bytes", MAX_COMMAND_LENGTH);
+            }
+            break;
+        }
+    }
+    String::from_utf8(line).context("The command is not UTF-8")
+}
+
+/// Turn a reply into the body or the daemon's error
+fn parse_reply(reply: &str) -> Result<String> {
+    let (status, body) = reply.split_once('\n').unwrap_or((reply, ""));
+    match status.strip_prefix("error: ") {
+        Some(error) => bail!("{}", error),
+        None if status == "ok" => Ok(body.trim_end().to_string()),
+        None => bail!("Unexpected reply from the daemon: {}", status),
+    }
+}
+
+fn exchange(mut stream: UnixStream, command: &str) -> Result<String> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.write_all(format!("{}\n", command.trim()).as_bytes())?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).context("No reply from the daemon")?;
+    parse_reply(&reply)
+}
+
+/// Send `command` to the daemon at `path`; `None` when no daemon listens there.
+/// Without root, the polkit action for the command is obtained first, and the
+/// connection made after the prompt, which would outlast the daemon's
+/// CLIENT_TIMEOUT.
+pub fn request_at(path: &Path, command: &str) -> Option<Result<String>> {
+    let mut stream = UnixStream::connect(path).ok()?;
+    let action_id = Command::parse(command).ok().and_then(|c| c.action_id());
+    if let Some(action_id) = action_id.filter(|_| !nix::unistd::Uid::effective().is_root()) {
+        drop(stream);
+        if let Err(e) = crate::polkit::obtain(action_id) {
+            return Some(Err(e));
+        }
+        stream = match UnixStream::connect(path) {
+            Ok(stream) => stream,
+            Err(e) => return Some(Err(e).context("The daemon went away during authorization")),
+        };
+    }
+    Some(exchange(stream, command))
+}
+
+/// Send `command` to the running daemon; `None` when it has no control socket
+/// (not running, or a version without one)
+pub fn request(command: &str) -> Option<Result<String>> {
+    request_at(Path::new(SOCKET_PATH), command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn test_control_commands() {
+        assert_eq!(Command::parse("reload-config\n").unwrap(), Command::ReloadConfig);
+        assert_eq!(
+            Command::parse("set-override powersave 30m").unwrap(),
+            Command::SetOverride { governor: GovernorOverride::Powersave, ttl: Some(Duration::from_secs(1800)) }
+        );
+        assert_eq!(Command::parse("set-turbo auto").unwrap(), Command::SetTurbo { turbo: TurboOverride::Auto, ttl: None });
+        assert_eq!(Command::parse("status --json").unwrap(), Command::Status { json: true });
+        assert!(Command::parse("set-override turbo").is_err());
+        assert!(Command::parse("shutdown").is_err());
+        assert!(!Command::Status { json: false }.changes_settings());
+        assert_eq!(Command::parse("set-profile reset").unwrap().action_id(), Some("org.auto-cpufreq.set-profile"));
+
+        assert_eq!(parse_reply("ok\ngovernor: powersave\n").unwrap(), "governor: powersave");
+        assert_eq!(
+            parse_reply("error: Not authorized for org.auto-cpufreq.set-turbo\n").unwrap_err().to_string(),
+            "Not authorized for org.auto-cpufreq.set-turbo"
+        );
+
+        // A status round trip over a real socket
+        let path = std::env::temp_dir().join(format!("acf-control-{}.sock", std::process::id()));
+        let socket = ControlSocket::bind_at(&path).unwrap();
+        assert!(request_at(&path, "status").unwrap().unwrap().starts_with(&format!("pid: {}", std::process::id())));
+        // Nothing changed, so the daemon isn't woken
+        assert_eq!(socket.changes().read(&mut [0u8]).unwrap_err().kind(), ErrorKind::WouldBlock);
+        drop(socket);
+        assert!(!path.exists());
+        assert!(request_at(&path, "status").is_none());
+    }
+
+    #[test]
+    fn test_read_command_limits() {
+        let (client, server) = UnixStream::pair().unwrap();
+        (&client).write_all(b"status --json\nignored").unwrap();
+        assert_eq!(read_command(&server, Instant::now() + CLIENT_TIMEOUT).unwrap(), "status --json");
+
+        // An endless line is cut off at the cap
+        let (client, server) = UnixStream::pair().unwrap();
+        (&client).write_all(&[b'x'; MAX_COMMAND_LENGTH as usize + 1]).unwrap();
+        let error = read_command(&server, Instant::now() + CLIENT_TIMEOUT).unwrap_err();
+        assert!(error.to_string().contains("longer than"));
+
+        // A client that keeps trickling runs into the one deadline for the whole line
+        let (client, server) = UnixStream::pair().unwrap();
+        (&client).write_all(b"sta").unwrap();
+        let started = Instant::now();
+        let error = read_command(&server, started + Duration::from_millis(200)).unwrap_err();
+        assert!(started.elapsed() < CLIENT_TIMEOUT);
+        drop(client);
+        assert!(format!("{:#}", error).contains("command"));
+    }
+}
@@ -2,53 +2,22 @@
 use std::fs::{self, File};
 use std::io::{Write, BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
-use sysinfo::System;
-use crate::power_helper::SYSTEMCTL_EXISTS;
 use chrono::Local;
 use anyhow::{Result, bail, Context};
+use tracing::{error, info, warn};
+use serde::{Deserialize, Serialize};
 
-use crate::config::CONFIG;
+use crate::config::{CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::cpu::{amd_pstate, intel_pstate};
 use crate::globals::AVAILABLE_GOVERNORS_SORTED;
-
-// ============================================================================
-// OPTIMIZATION: Cached System Wrapper
-// ============================================================================
-pub struct CachedSystem {
-    sys: System,
-    last_refresh: Instant,
-    refresh_interval: Duration,
-}
-
-impl CachedSystem {
-    pub fn new(refresh_interval_secs: u64) -> Self {
-        Self {
-            sys: System::new_all(),
-            last_refresh: Instant::now() - Duration::from_secs(999), // Force initial refresh
-            refresh_interval: Duration::from_secs(refresh_interval_secs),
-        }
-    }
-
-    pub fn get_refreshed_system(&mut self) -> &mut System {
-        if self.last_refresh.elapsed() > self.refresh_interval {
-            self.sys.refresh_cpu();
-            std::thread::sleep(Duration::from_millis(200));
-            self.sys.refresh_cpu();
-            self.last_refresh = Instant::now();
-        }
-        &mut self.sys
-    }
-
-    pub fn force_refresh(&mut self) {
-        self.sys.refresh_cpu();
-        std::thread::sleep(Duration::from_millis(200));
-        self.sys.refresh_cpu();
-        self.last_refresh = Instant::now();
-    }
-}
+use crate::state::StateStore;
+use crate::profiles::{AppliedLimits, Profile};
+use crate::modules::system_info::SystemInfo;
+use crate::modules::system_monitor::{format_stats, stats_fields};
 
 // ============================================================================
 // OPTIMIZATION: Temperature Sensor Cache
@@ -106,37 +75,17 @@ impl TempSensorCache {
         self.last_scan = Instant::now();
     }
 
-    pub fn read_core_temp(&self, core_id: usize) -> f32 {
-        // Try specific core sensor first
-        if let Some(path) = self.sensor_paths.get(&core_id) {
-            if let Ok(temp_str) = fs::read_to_string(path) {
-                if let Ok(temp) = temp_str.trim().parse::<f32>() {
-                    return temp / 1000.0;
-                }
-            }
-        }
-        
-        // Fallback to package temp
-        if let Some(ref path) = self.package_temp_path {
-            if let Ok(temp_str) = fs::read_to_string(path) {
-                if let Ok(temp) = temp_str.trim().parse::<f32>() {
-                    return temp / 1000.0;
-                }
-            }
-        }
-        
-        0.0
+    /// `None` while neither the core's sensor nor the package one can be read
+    pub fn read_core_temp(&self, core_id: usize) -> Option<f32> {
+        // Try specific core sensor first, then fall back to package temp
+        self.sensor_paths
+            .get(&core_id)
+            .and_then(|path| crate::sensors::read_temperature(path))
+            .or_else(|| self.read_package_temp())
     }
 
-    pub fn read_package_temp(&self) -> f32 {
-        if let Some(ref path) = self.package_temp_path {
-            if let Ok(temp_str) = fs::read_to_string(path) {
-                if let Ok(temp) = temp_str.trim().parse::<f32>() {
-                    return temp / 1000.0;
-                }
-            }
-        }
-        0.0
+    pub fn read_package_temp(&self) -> Option<f32> {
+        self.package_temp_path.as_deref().and_then(crate::sensors::read_temperature)
     }
 
     // Rescan if sensors might have changed (rare)
@@ -147,17 +96,27 @@ impl TempSensorCache {
     }
 }
 
+impl Default for TempSensorCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Global instances with lazy initialization
 lazy_static::lazy_static! {
     static ref TEMP_CACHE: Arc<Mutex<TempSensorCache>> = Arc::new(Mutex::new(TempSensorCache::new()));
-    static ref CACHED_SYSTEM: Arc<Mutex<CachedSystem>> = Arc::new(Mutex::new(CachedSystem::new(2)));
+    /// When the daemon last wrote the stats file
+    static ref STATS_WRITTEN: Mutex<Option<Instant>> = Mutex::new(None);
 }
 
+/// Shortest time between two stats file updates from the daemon loop. The
+/// report behind it reads every sensor and core, too much for each cycle at
+/// the fastest polling; readers (`--stats`, the GUI) refresh slower anyway.
+const STATS_REFRESH: Duration = Duration::from_secs(10);
+
 // ============================================================================
 // Constants
 // ============================================================================
-const SCRIPTS_DIR: &str = "/usr/local/share/auto-cpufreq/scripts/";
-const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply/";
 pub const GITHUB: &str = "https://github.com/Zamanhuseyinli/auto-cpufreq-rust";
 
 pub const ALL_GOVERNORS: &[&str] = &[
@@ -169,22 +128,77 @@ pub const ALL_GOVERNORS: &[&str] = &[
     "powersave"
 ];
 
-fn read_auto_cpufreq_file(sub_path: &str) -> String {
-    let path = format!("/usr/local/share/auto-cpufreq/scripts/{}", sub_path);
-    fs::read_to_string(&path).unwrap_or_else(|_| {
-        eprintln!("Warning: File {} not found!", path);
-        String::new()
-    })
+/// `sub_path` under `dir` when a package installed it there (and may have
+/// patched it), else the copy built into the binary
+fn script_source(dir: &Path, sub_path: &str, embedded: &str) -> String {
+    fs::read_to_string(dir.join(sub_path)).unwrap_or_else(|_| embedded.to_string())
+}
+
+fn read_script(dir: &Path, sub_path: &str, embedded: &str) -> String {
+    crate::globals::relocate(&script_source(dir, sub_path, embedded), &crate::globals::PREFIX)
 }
 
-pub fn install_script() -> String { read_auto_cpufreq_file("auto-cpufreq-install.sh") }
-pub fn remove_script() -> String { read_auto_cpufreq_file("auto-cpufreq-remove.sh") }
-pub fn cpufreqctl_script() -> String { read_auto_cpufreq_file("cpufreqctl.sh") }
-pub fn systemd_service() -> String { read_auto_cpufreq_file("auto-cpufreq.service") }
-pub fn openrc_service() -> String { read_auto_cpufreq_file("auto-cpufreq-openrc") }
-pub fn dinit_service() -> String { read_auto_cpufreq_file("auto-cpufreq-dinit") }
-pub fn runit_service() -> String { read_auto_cpufreq_file("auto-cpufreq-runit") }
-pub fn s6_service() -> String { read_auto_cpufreq_file("auto-cpufreq-s6/run") }
+/// Where the unit templates start the daemon from
+const TEMPLATE_BINARY: &str = "/usr/local/bin/auto-cpufreq";
+
+/// The binary the service should start: the one running `--install`
+fn daemon_binary() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .filter(|exe| exe.is_file())
+        .unwrap_or_else(|| crate::globals::bin_dir().join("auto-cpufreq"))
+}
+
+/// Point a unit template at `binary` and check the result before it is written
+fn render_unit(name: &str, template: &str, binary: &Path) -> Result<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = binary.to_str().filter(|p| binary.is_absolute() && !p.contains(char::is_whitespace));
+    let Some(path) = path else {
+        bail!("Can't start {} from {}, the path must be absolute and without spaces", name, binary.display());
+    };
+    let executable = fs::metadata(binary).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0);
+    if !executable {
+        bail!("{} would start {}, which is not an executable file", name, path);
+    }
+    if !template.contains(TEMPLATE_BINARY) {
+        bail!("{} doesn't start {}, refusing to install it", name, TEMPLATE_BINARY);
+    }
+
+    // The binary first: the rest of /usr/local moves with the prefix
+    let unit = template.replace(TEMPLATE_BINARY, path);
+    Ok(crate::globals::relocate(&unit, &crate::globals::PREFIX))
+}
+
+macro_rules! script {
+    ($name:ident, $sub_path:literal) => {
+        pub fn $name() -> String {
+            read_script(&crate::globals::scripts_dir(), $sub_path, include_str!(concat!("../../scripts/", $sub_path)))
+        }
+    };
+}
+
+/// A unit file that starts the daemon, rendered for the running binary
+macro_rules! unit {
+    ($name:ident, $sub_path:literal) => {
+        pub fn $name() -> Result<String> {
+            let template =
+                script_source(&crate::globals::scripts_dir(), $sub_path, include_str!(concat!("../../scripts/", $sub_path)));
+            render_unit($sub_path, &template, &daemon_binary())
+        }
+    };
+}
+
+script!(install_script, "auto-cpufreq-install.sh");
+script!(remove_script, "auto-cpufreq-remove.sh");
+script!(cpufreqctl_script, "cpufreqctl.sh");
+unit!(systemd_service, "auto-cpufreq.service");
+script!(systemd_socket, "auto-cpufreq.socket");
+unit!(openrc_service, "auto-cpufreq-openrc");
+unit!(dinit_service, "auto-cpufreq-dinit");
+unit!(runit_service, "auto-cpufreq-runit");
+unit!(s6_service, "auto-cpufreq-s6/run");
+script!(ppd_shim_policy, "auto-cpufreq-power-profiles.conf");
 
 // ============================================================================
 // Global state structures
@@ -194,8 +208,7 @@ pub struct AutoCpuFreqState {
     pub performance_load_threshold: f32,
     pub powersave_load_threshold: f32,
     pub stats_file_path: PathBuf,
-    pub governor_override_path: PathBuf,
-    pub turbo_override_path: PathBuf,
+    pub state_file_path: PathBuf,
     pub is_aur: bool,
 }
 
@@ -203,10 +216,9 @@ impl AutoCpuFreqState {
     pub fn new() -> Self {
         let cpu_count = num_cpus::get();
         
-        let (stats_path, gov_path, turbo_path) = (
+        let (stats_path, state_path) = (
                 PathBuf::from("/var/run/auto-cpufreq.stats"),
-                PathBuf::from("/opt/auto-cpufreq/override.pickle"),
-                PathBuf::from("/opt/auto-cpufreq/turbo-override.pickle"),
+                PathBuf::from(crate::state::STATE_FILE),
         );
 
         Self {
@@ -214,19 +226,24 @@ impl AutoCpuFreqState {
             performance_load_threshold: (50 * cpu_count) as f32 / 100.0,
             powersave_load_threshold: (75 * cpu_count) as f32 / 100.0,
             stats_file_path: stats_path,
-            governor_override_path: gov_path,
-            turbo_override_path: turbo_path,
+            state_file_path: state_path,
             is_aur: Self::check_aur_install(),
         }
     }
 
     fn check_aur_install() -> bool {
         Path::new("/etc/arch-release").exists() && 
-        Command::new("pacman")
-            .args(&["-Qs", "auto-cpufreq"])
-            .output()
-            .map(|o| !o.stdout.is_empty())
-            .unwrap_or(false)
+        crate::command::run("pacman", &["-Qs", "auto-cpufreq"]).is_ok_and(|o| !o.stdout.is_empty())
+    }
+
+    pub fn store(&self) -> StateStore {
+        StateStore::new(&self.state_file_path)
+    }
+}
+
+impl Default for AutoCpuFreqState {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -237,14 +254,7 @@ pub fn get_version() -> Result<String> {
     let state = AutoCpuFreqState::new();
     
     if state.is_aur {
-        let output = Command::new("pacman")
-            .args(&["-Qi", "auto-cpufreq"])
-            .output()?;
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        
-        stdout.lines()
-            .find(|line| line.contains("Version"))
-            .map(|s| s.to_string())
+        crate::command::pacman_version("auto-cpufreq")
             .ok_or_else(|| anyhow::anyhow!("Version not found"))
     } else {
         get_formatted_version()
@@ -259,10 +269,16 @@ pub fn get_formatted_version() -> Result<String> {
 pub fn app_version() {
     match get_version() {
         Ok(v) => println!("auto-cpufreq version: {}", v),
-        Err(e) => eprintln!("Error getting version: {}", e),
+        Err(e) => error!("Error getting version: {}", e),
     }
 }
 
+#[cfg(not(feature = "update-check"))]
+pub fn check_for_update() -> Result<bool> {
+    bail!("Update check is not available in this build (enable the \"update-check\" feature)");
+}
+
+#[cfg(feature = "update-check")]
 pub fn check_for_update() -> Result<bool> {
     let latest_url = format!("{}/releases/latest", GITHUB.replace("github.com", "api.github.com/repos"));
     
@@ -295,6 +311,60 @@ pub fn check_for_update() -> Result<bool> {
 // ============================================================================
 // Governor management
 // ============================================================================
+
+/// Parse an override TTL such as `90s`, `30m` or `2h` (bare numbers are minutes)
+pub fn parse_override_ttl(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map(|i| value.split_at(i))
+        .unwrap_or((value, "m"));
+
+    let n: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration: {}", value))?;
+    let secs = match unit {
+        "s" => Some(n),
+        "m" | "min" => n.checked_mul(60),
+        "h" => n.checked_mul(3600),
+        _ => bail!("Invalid duration unit in {} (use s, m or h)", value),
+    };
+    let Some(secs) = secs.filter(|secs| i64::try_from(*secs).is_ok()) else {
+        bail!("Duration too long: {}", value);
+    };
+    if secs == 0 {
+        bail!("Duration must be greater than zero");
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+fn expiry_from_now(ttl: Duration) -> i64 {
+    Local::now().timestamp().saturating_add(i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// What a new override goes back to when its TTL runs out: the permanent
+/// override it replaces, also when it replaces another timed one. Permanent
+/// overrides and resets don't go back to anything.
+fn previous_override(
+    ttl: Option<Duration>,
+    value: &Option<String>,
+    current: &mut Option<String>,
+    previous: &mut Option<String>,
+    current_until: Option<i64>,
+) -> Option<String> {
+    match (ttl, value) {
+        (Some(_), Some(_)) if current_until.is_some() => previous.take(),
+        (Some(_), Some(_)) => current.take(),
+        _ => None,
+    }
+}
+
+pub(crate) fn ttl_suffix(ttl: Option<Duration>) -> String {
+    ttl.map(|d| format!(" for {} min", d.as_secs().div_ceil(60)))
+        .unwrap_or_default()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum GovernorOverride {
     Default,
@@ -303,14 +373,22 @@ pub enum GovernorOverride {
 }
 
 impl GovernorOverride {
-    pub fn from_str(s: &str) -> Self {
+    /// `--force` value; "reset" clears the override
+    pub fn parse(s: &str) -> Option<Self> {
         match s {
-            "powersave" => Self::Powersave,
-            "performance" => Self::Performance,
-            _ => Self::Default,
+            "powersave" => Some(Self::Powersave),
+            "performance" => Some(Self::Performance),
+            "reset" => Some(Self::Default),
+            _ => None,
         }
     }
 
+    /// Stored override, anything unknown counts as none
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        Self::parse(s).unwrap_or(Self::Default)
+    }
+
     pub fn to_str(&self) -> &str {
         match self {
             Self::Powersave => "powersave",
@@ -321,32 +399,36 @@ impl GovernorOverride {
 }
 
 pub fn get_override(state: &AutoCpuFreqState) -> GovernorOverride {
-    if state.governor_override_path.exists() {
-        fs::read_to_string(&state.governor_override_path)
-            .ok()
-            .map(|s| GovernorOverride::from_str(s.trim()))
-            .unwrap_or(GovernorOverride::Default)
-    } else {
-        GovernorOverride::Default
-    }
+    state.store()
+        .load()
+        .governor_override
+        .map(|s| GovernorOverride::from_str(&s))
+        .unwrap_or(GovernorOverride::Default)
+}
+
+/// Save `governor` as the override (`Default` clears it); the daemon applies
+/// it on its next cycle
+pub fn store_override(state: &AutoCpuFreqState, governor: &GovernorOverride, ttl: Option<Duration>) -> Result<()> {
+    let value = (*governor != GovernorOverride::Default).then(|| governor.to_str().to_string());
+    state.store().update(|s| {
+        s.governor_override_previous = previous_override(ttl, &value, &mut s.governor_override, &mut s.governor_override_previous, s.governor_override_until);
+        s.governor_override_until = value.as_ref().and(ttl.map(expiry_from_now));
+        s.governor_override = value;
+    })?;
+    Ok(())
 }
 
-pub fn set_override(state: &AutoCpuFreqState, override_val: &str) -> Result<()> {
-    match override_val {
-        "powersave" | "performance" => {
-            fs::write(&state.governor_override_path, override_val)?;
-            println!("Set governor override to {}", override_val);
-        }
-        "reset" => {
-            if state.governor_override_path.exists() {
-                fs::remove_file(&state.governor_override_path)?;
-            }
-            println!("Governor override removed");
-        }
-        _ => {
-            println!("Invalid option.");
-            println!("Use force=performance, force=powersave, or force=reset");
-        }
+/// `ttl` makes the override revert on its own after that long, to the
+/// permanent override from before or to automatic
+pub fn set_override(state: &AutoCpuFreqState, override_val: &str, ttl: Option<Duration>) -> Result<()> {
+    let Some(governor) = GovernorOverride::parse(override_val) else {
+        bail!("Invalid option: {} (use force=performance, force=powersave, or force=reset)", override_val);
+    };
+
+    store_override(state, &governor, ttl)?;
+    match governor {
+        GovernorOverride::Default => println!("Governor override removed"),
+        _ => println!("Set governor override to {}{}", override_val, ttl_suffix(ttl)),
     }
     Ok(())
 }
@@ -362,14 +444,22 @@ pub enum TurboOverride {
 }
 
 impl TurboOverride {
-    pub fn from_str(s: &str) -> Self {
+    /// `--turbo` value or a `turbo` config key
+    pub fn parse(s: &str) -> Option<Self> {
         match s {
-            "never" => Self::Never,
-            "always" => Self::Always,
-            _ => Self::Auto,
+            "never" => Some(Self::Never),
+            "always" => Some(Self::Always),
+            "auto" => Some(Self::Auto),
+            _ => None,
         }
     }
 
+    /// Stored override, anything unknown counts as auto
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        Self::parse(s).unwrap_or(Self::Auto)
+    }
+
     pub fn to_str(&self) -> &str {
         match self {
             Self::Never => "never",
@@ -380,32 +470,33 @@ impl TurboOverride {
 }
 
 pub fn get_turbo_override(state: &AutoCpuFreqState) -> TurboOverride {
-    if state.turbo_override_path.exists() {
-        fs::read_to_string(&state.turbo_override_path)
-            .ok()
-            .map(|s| TurboOverride::from_str(s.trim()))
-            .unwrap_or(TurboOverride::Auto)
-    } else {
-        TurboOverride::Auto
-    }
+    state.store()
+        .load()
+        .turbo_override
+        .map(|s| TurboOverride::from_str(&s))
+        .unwrap_or(TurboOverride::Auto)
+}
+
+/// Save `turbo` as the override (`Auto` clears it)
+pub fn store_turbo_override(state: &AutoCpuFreqState, turbo: &TurboOverride, ttl: Option<Duration>) -> Result<()> {
+    let value = (*turbo != TurboOverride::Auto).then(|| turbo.to_str().to_string());
+    state.store().update(|s| {
+        s.turbo_override_previous = previous_override(ttl, &value, &mut s.turbo_override, &mut s.turbo_override_previous, s.turbo_override_until);
+        s.turbo_override_until = value.as_ref().and(ttl.map(expiry_from_now));
+        s.turbo_override = value;
+    })?;
+    Ok(())
 }
 
-pub fn set_turbo_override(state: &AutoCpuFreqState, override_val: &str) -> Result<()> {
-    match override_val {
-        "never" | "always" => {
-            fs::write(&state.turbo_override_path, override_val)?;
-            println!("Set turbo boost override to {}", override_val);
-        }
-        "auto" => {
-            if state.turbo_override_path.exists() {
-                fs::remove_file(&state.turbo_override_path)?;
-            }
-            println!("Turbo override removed");
-        }
-        _ => {
-            println!("Invalid option.");
-            println!("Use turbo=always, turbo=never, or turbo=auto");
-        }
+pub fn set_turbo_override(state: &AutoCpuFreqState, override_val: &str, ttl: Option<Duration>) -> Result<()> {
+    let Some(turbo) = TurboOverride::parse(override_val) else {
+        bail!("Invalid option: {} (use turbo=always, turbo=never, or turbo=auto)", override_val);
+    };
+
+    store_turbo_override(state, &turbo, ttl)?;
+    match turbo {
+        TurboOverride::Auto => println!("Turbo override removed"),
+        _ => println!("Set turbo boost override to {}{}", override_val, ttl_suffix(ttl)),
     }
     Ok(())
 }
@@ -420,13 +511,20 @@ pub fn turbo(value: Option<bool>) -> Result<bool> {
     } else if cpufreq.exists() {
         (cpufreq, false)
     } else if amd_pstate.exists() {
-        let status = fs::read_to_string(amd_pstate)?.trim().to_string();
-        if status == "active" {
-            println!("CPU turbo is controlled by amd-pstate-epp driver");
+        // Active mode without the global boost knob: per-policy boost files
+        if !amd_pstate::boost_supported() {
+            warn!(code = %EventCode::TurboUnavailable, "CPU turbo is not available with amd-pstate on this kernel");
+            return Ok(false);
         }
-        return Ok(false);
+        if let Some(val) = value {
+            if let Err(e) = amd_pstate::set_boost(val) {
+                warn!(code = %EventCode::TurboUnavailable, "Changing CPU turbo is not supported ({}). Skipping.", e);
+                return Ok(false);
+            }
+        }
+        return Ok(amd_pstate::get_boost().unwrap_or(false));
     } else {
-        println!("Warning: CPU turbo is not available");
+        warn!(code = %EventCode::TurboUnavailable, "CPU turbo is not available");
         return Ok(false);
     };
     
@@ -435,7 +533,7 @@ pub fn turbo(value: Option<bool>) -> Result<bool> {
         match fs::write(control_file, format!("{}\n", write_val as u8)) {
             Ok(_) => {}
             Err(_) => {
-                println!("Warning: Changing CPU turbo is not supported. Skipping.");
+                warn!(code = %EventCode::TurboUnavailable, "Changing CPU turbo is not supported. Skipping.");
                 return Ok(false);
             }
         }
@@ -448,18 +546,144 @@ pub fn turbo(value: Option<bool>) -> Result<bool> {
     Ok((current != 0) ^ inverse)
 }
 
+/// Which knob `turbo()` uses on this system, without touching it
+pub fn turbo_control() -> Option<&'static str> {
+    if Path::new("/sys/devices/system/cpu/intel_pstate/no_turbo").exists() {
+        Some("intel_pstate/no_turbo")
+    } else if Path::new("/sys/devices/system/cpu/cpufreq/boost").exists() {
+        Some("cpufreq/boost")
+    } else if amd_pstate::is_present() && amd_pstate::boost_supported() {
+        Some("amd_pstate boost")
+    } else {
+        None
+    }
+}
+
 pub fn get_turbo() {
     match turbo(None) {
         Ok(state) => println!("Currently turbo boost is: {}", if state { "on" } else { "off" }),
-        Err(e) => eprintln!("Error getting turbo state: {}", e),
+        Err(e) => error!("Error getting turbo state: {}", e),
     }
 }
 
 pub fn set_turbo(value: bool) {
-    println!("Setting turbo boost: {}", if value { "on" } else { "off" });
+    info!(code = %EventCode::TurboChanged, "Setting turbo boost: {}", if value { "on" } else { "off" });
     let _ = turbo(Some(value));
 }
 
+// ============================================================================
+// EPB (energy_perf_bias) management
+// ============================================================================
+const CPU_SYSFS_DIR: &str = "/sys/devices/system/cpu";
+
+/// Named EPB values as documented in the kernel's intel_epb admin guide
+pub const EPB_NAMED_VALUES: &[(&str, u8)] = &[
+    ("performance", 0),
+    ("balance_performance", 4),
+    ("default", 6),
+    ("balance_power", 8),
+    ("power", 15),
+];
+
+/// List `/sys/devices/system/cpu/cpuN` directories, sorted by CPU number
+pub fn cpu_sysfs_dirs() -> Vec<PathBuf> {
+    let mut cpus: Vec<(usize, PathBuf)> = fs::read_dir(CPU_SYSFS_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    let name = e.file_name().to_string_lossy().to_string();
+                    name.strip_prefix("cpu")
+                        .and_then(|n| n.parse::<usize>().ok())
+                        .map(|n| (n, e.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    cpus.sort_by_key(|(n, _)| *n);
+    cpus.into_iter().map(|(_, p)| p).collect()
+}
+
+fn epb_path(cpu_dir: &Path) -> PathBuf {
+    cpu_dir.join("power/energy_perf_bias")
+}
+
+/// Parse a config EPB value: either a number between 0-15 or one of the named values
+pub fn parse_epb(value: &str) -> Option<u8> {
+    let value = value.trim();
+
+    if let Ok(n) = value.parse::<u8>() {
+        return if n <= 15 { Some(n) } else { None };
+    }
+
+    EPB_NAMED_VALUES
+        .iter()
+        .find(|(name, _)| *name == value)
+        .map(|(_, n)| *n)
+}
+
+/// Human-readable EPB value, using the named value when there is one
+pub fn epb_name(value: u8) -> String {
+    EPB_NAMED_VALUES
+        .iter()
+        .find(|(_, n)| *n == value)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+pub fn epb_supported() -> bool {
+    epb_path(&Path::new(CPU_SYSFS_DIR).join("cpu0")).exists()
+}
+
+/// Current hardware EPB value (read from cpu0)
+pub fn get_epb() -> Option<u8> {
+    fs::read_to_string(epb_path(&Path::new(CPU_SYSFS_DIR).join("cpu0")))
+        .ok()
+        .and_then(|s| s.trim().parse::<u8>().ok())
+}
+
+pub fn set_epb(value: &str) -> Result<()> {
+    let Some(epb) = parse_epb(value) else {
+        bail!("Invalid EPB value: {} (use 0-15 or one of performance, balance_performance, default, balance_power, power)", value);
+    };
+
+    info!("Setting EPB: {}", epb_name(epb));
+
+    for cpu_dir in cpu_sysfs_dirs() {
+        let path = epb_path(&cpu_dir);
+        if path.exists() {
+            fs::write(&path, format!("{}\n", epb))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn set_epb_based_on_power(is_charging: bool) -> Result<()> {
+    if !epb_supported() {
+        return Ok(());
+    }
+
+    let (section, fallback) = if is_charging {
+        ("charger", "balance_performance")
+    } else {
+        ("battery", "balance_power")
+    };
+    let value = CONFIG.get(section, "energy_perf_bias", fallback);
+
+    let Some(target) = parse_epb(&value) else {
+        bail!("Invalid energy_perf_bias value in [{}]: {}", section, value);
+    };
+
+    if get_epb() != Some(target) {
+        set_epb(&value)?;
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Distribution info
 // ============================================================================
@@ -486,7 +710,7 @@ pub fn distro_info() -> Result<()> {
     }
 
     println!("Linux distro: {} {}", dist_name, version);
-    println!("Linux kernel: {}", System::kernel_version().unwrap_or_default());
+    println!("Linux kernel: {}", crate::procfs::kernel_version().unwrap_or_default());
     
     Ok(())
 }
@@ -494,11 +718,11 @@ pub fn distro_info() -> Result<()> {
 // ============================================================================
 // OPTIMIZED: Temperature reading functions
 // ============================================================================
-pub fn read_cpu_temperature(core_id: usize) -> f32 {
+pub fn read_cpu_temperature(core_id: usize) -> Option<f32> {
     TEMP_CACHE.lock().unwrap().read_core_temp(core_id)
 }
 
-pub fn read_package_temperature() -> f32 {
+pub fn read_package_temperature() -> Option<f32> {
     TEMP_CACHE.lock().unwrap().read_package_temp()
 }
 
@@ -528,35 +752,28 @@ pub fn sysinfo() -> Result<()> {
         .to_string();
     println!("Driver: {}", driver);
     
-    // OPTIMIZED: Use cached system
-    let mut cached_sys = CACHED_SYSTEM.lock().unwrap();
-    let sys = cached_sys.get_refreshed_system();
+    let usage = crate::cpu::usage::sample();
     
-    if let Some(cpu) = sys.cpus().first() {
-        println!("\n{}", "-".repeat(30) + " Current CPU stats " + &"-".repeat(30));
-        println!("\nCPU max frequency: {:.0} MHz", cpu.frequency());
-    }
+    println!("\n{}", "-".repeat(30) + " Current CPU stats " + &"-".repeat(30));
+    println!("\nCPU max frequency: {:.0} MHz", crate::procfs::core_frequency(0));
     
     println!("\n{:<6} {:<8} {:<16} {:<10}", "Core", "Usage", "Temperature", "Frequency");
     
-    for (i, cpu) in sys.cpus().iter().enumerate() {
-        let temp = read_cpu_temperature(i);
-        let temp_str = if temp > 0.0 {
-            format!("{:.0} °C", temp)
-        } else {
-            "-- °C".to_string()
+    for (i, core_usage) in usage.cores.iter().enumerate() {
+        let temp_str = match read_cpu_temperature(i) {
+            Some(temp) => format!("{:.0} °C", temp),
+            None => "-- °C".to_string(),
         };
         
         println!("{:<6} {:<8.1}% {:<16} {:.0} MHz", 
             format!("CPU{}", i),
-            cpu.cpu_usage(),
+            core_usage,
             temp_str,
-            cpu.frequency()
+            crate::procfs::core_frequency(i)
         );
     }
     
-    let pkg_temp = read_package_temperature();
-    if pkg_temp > 0.0 {
+    if let Some(pkg_temp) = read_package_temperature() {
         println!("\nPackage temperature: {:.1} °C", pkg_temp);
     }
     
@@ -570,73 +787,18 @@ pub fn get_power_supply_ignore_list() -> Vec<String> {
     vec!["hidpp_battery".to_string()]
 }
 
+/// Whether the machine runs on AC power: a charger is online or any of the
+/// batteries is charging. Machines without a battery count as on AC.
 pub fn charging() -> Result<bool> {
-    let power_dir = Path::new(POWER_SUPPLY_DIR);
-    
-    if !power_dir.exists() {
-        return Ok(true);
-    }
-    
-    let mut entries: Vec<_> = fs::read_dir(power_dir)?
-        .filter_map(|e| e.ok())
-        .collect();
-    entries.sort_by_key(|e| e.file_name());
-    
-    let ignore_list = get_power_supply_ignore_list();
-    
-    if entries.is_empty() {
-        return Ok(true);
-    }
-    
-    for entry in entries {
-        let name = entry.file_name();
-        let name_str = name.to_string_lossy();
-        
-        if ignore_list.iter().any(|ignored| name_str.contains(ignored)) {
-            continue;
-        }
-        
-        let supply_path = entry.path();
-        let type_path = supply_path.join("type");
-        
-        if !type_path.exists() {
-            continue;
-        }
-        
-        let supply_type = fs::read_to_string(&type_path)?.trim().to_string();
-        
-        if supply_type == "Mains" {
-            let online_path = supply_path.join("online");
-            if online_path.exists() {
-                let online = fs::read_to_string(&online_path)?.trim().to_string();
-                if online == "1" {
-                    return Ok(true);
-                }
-            }
-        } else if supply_type == "Battery" {
-            let status_path = supply_path.join("status");
-            if status_path.exists() {
-                let status = fs::read_to_string(&status_path)?.trim().to_string();
-                if status == "Discharging" {
-                    return Ok(false);
-                }
-            }
-        }
-    }
-    
-    Ok(true)
+    Ok(crate::battery::PowerSupplies::scan().is_charging())
 }
 
 // ============================================================================
 // Governor functions
 // ============================================================================
 pub fn get_current_gov() -> Result<String> {
-    let output = Command::new("cpufreqctl.auto-cpufreq")
-        .arg("--governor")
-        .output()?;
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let gov = stdout.split_whitespace().next().unwrap_or("unknown");
+    let output = crate::command::run(&crate::globals::cpufreqctl_path().to_string_lossy(), &["--governor"])?;
+    let gov = output.stdout.split_whitespace().next().unwrap_or("unknown");
     
     Ok(gov.to_string())
 }
@@ -644,7 +806,7 @@ pub fn get_current_gov() -> Result<String> {
 pub fn print_current_gov() {
     match get_current_gov() {
         Ok(gov) => println!("Currently using: {} governor", gov),
-        Err(e) => eprintln!("Error getting governor: {}", e),
+        Err(e) => error!("Error getting governor: {}", e),
     }
 }
 
@@ -652,59 +814,45 @@ pub fn print_current_gov() {
 // cpufreqctl deployment
 // ============================================================================
 pub fn cpufreqctl() -> Result<()> {
-    let target = "/usr/local/bin/cpufreqctl.auto-cpufreq";
+    let target = crate::globals::cpufreqctl_path();
     
-    if !Path::new(target).exists() {
-        let source = PathBuf::from(SCRIPTS_DIR).join("cpufreqctl.sh");
-        fs::copy(source, target)?;
+    if !target.exists() {
+        let source = crate::globals::scripts_dir().join("cpufreqctl.sh");
+        fs::copy(source, &target)?;
         
-        Command::new("chmod")
-            .args(&["a+x", target])
-            .status()?;
+        crate::command::change("chmod", &["a+x", &target.to_string_lossy()])?;
     }
     
     Ok(())
 }
 
 pub fn cpufreqctl_restore() -> Result<()> {
-    let target = "/usr/local/bin/cpufreqctl.auto-cpufreq";
+    let target = crate::globals::cpufreqctl_path();
     
-    if Path::new(target).exists() {
-        fs::remove_file(target)?;
+    if target.exists() {
+        fs::remove_file(&target)?;
     }
     
     Ok(())
 }
 
-fn deploy_cpufreqctl() -> Result<()> {
-    let target = "/usr/local/bin/cpufreqctl.auto-cpufreq";
-    
-    if !Path::new(target).exists() {
-        println!("\n* Deploying cpufreqctl helper script");
-        fs::write(target, cpufreqctl_script())?;
-
-        Command::new("chmod")
-            .args(&["+x", target])
-            .status()?;
-    }
-    
-    Ok(())
-}
+// ============================================================================
+// Stats file update function
+// ============================================================================
 
-fn remove_cpufreqctl() -> Result<()> {
-    let target = "/usr/local/bin/cpufreqctl.auto-cpufreq";
-    
-    if Path::new(target).exists() {
-        println!("\n* Removing cpufreqctl helper script");
-        fs::remove_file(target)?;
+/// Called on every daemon cycle: `update_stats_file` once `STATS_REFRESH` has
+/// passed since the last time
+pub fn refresh_stats_file() -> Result<()> {
+    {
+        let mut written = STATS_WRITTEN.lock().unwrap();
+        if written.is_some_and(|at| at.elapsed() < STATS_REFRESH) {
+            return Ok(());
+        }
+        *written = Some(Instant::now());
     }
-    
-    Ok(())
+    update_stats_file()
 }
 
-// ============================================================================
-// Stats file update function
-// ============================================================================
 pub fn update_stats_file() -> Result<()> {
     let state = AutoCpuFreqState::new();
     
@@ -712,46 +860,18 @@ pub fn update_stats_file() -> Result<()> {
         fs::create_dir_all(parent)?;
     }
     
-    // OPTIMIZED: Use String buffer instead of multiple allocations
-    let mut stats = String::with_capacity(2048);
-    
-    use std::fmt::Write as FmtWrite;
-    
-    let _ = writeln!(&mut stats, "\n{}", "=".repeat(80));
-    let _ = writeln!(&mut stats, "auto-cpufreq daemon - {}", 
-        Local::now().format("%Y-%m-%d %H:%M:%S"));
-    let _ = writeln!(&mut stats, "{}\n", "=".repeat(80));
-    
-    // OPTIMIZED: Use cached system
-    let mut cached_sys = CACHED_SYSTEM.lock().unwrap();
-    let sys = cached_sys.get_refreshed_system();
-    
-    let cpu_usage: f32 = sys.cpus().iter()
-        .map(|c| c.cpu_usage())
-        .sum::<f32>() / sys.cpus().len() as f32;
-    
-    let loadavg = System::load_average();
-    
-    let _ = writeln!(&mut stats, "CPU usage: {:.1}%", cpu_usage);
-    let _ = writeln!(&mut stats, "Load: {:.2}, {:.2}, {:.2}", 
-        loadavg.one, loadavg.five, loadavg.fifteen);
-    
-    if let Ok(gov) = get_current_gov() {
-        let _ = writeln!(&mut stats, "Governor: {}", gov);
-    }
-    
-    if let Ok(turbo_state) = turbo(None) {
-        let _ = writeln!(&mut stats, "Turbo: {}", if turbo_state { "On" } else { "Off" });
-    }
-    
-    if let Ok(is_charging) = charging() {
-        let _ = writeln!(&mut stats, "Battery: {}", 
-            if is_charging { "Charging" } else { "Discharging" });
-    }
-    
-    let _ = writeln!(&mut stats, "\n{}", "-".repeat(80));
+    let report = SystemInfo::new().generate_daemon_report();
+
+    let now = Local::now();
+    let fields = stats_fields(&CONFIG.snapshot());
+    let stats = format_stats(&report, &now.format("%Y-%m-%d %H:%M:%S").to_string(), fields.as_deref());
     
     fs::write(&state.stats_file_path, stats)?;
+
+    let sample = crate::stats_history::Sample::from_report(&report, now.timestamp());
+    if let Err(e) = crate::stats_history::History::default().record(&sample) {
+        warn!(code = %EventCode::StateWriteFailed, "Failed to record history: {}", e);
+    }
     
     Ok(())
 }
@@ -760,25 +880,17 @@ pub fn update_stats_file() -> Result<()> {
 // Load information
 // ============================================================================
 pub fn get_load() -> (f64, f64) {
-    // OPTIMIZED: Use cached system
-    let mut cached_sys = CACHED_SYSTEM.lock().unwrap();
-    let sys = cached_sys.get_refreshed_system();
-    
-    let cpu_usage: f64 = sys.cpus().iter()
-        .map(|cpu| cpu.cpu_usage() as f64)
-        .sum::<f64>() / sys.cpus().len() as f64;
-    
-    let loadavg = System::load_average();
-    let load1m = loadavg.one;
+    let usage = crate::cpu::usage::sample();
+    let cpu_usage = usage.total as f64;
+    let load1m = crate::procfs::load_average().one;
     
     println!("\nTotal CPU usage: {:.1}%", cpu_usage);
     println!("Total system load: {:.2}", load1m);
     
     // OPTIMIZED: Calculate average temperature using cached sensors
     let temp_cache = TEMP_CACHE.lock().unwrap();
-    let temps: Vec<f32> = (0..sys.cpus().len())
-        .map(|i| temp_cache.read_core_temp(i))
-        .filter(|&t| t > 0.0)
+    let temps: Vec<f32> = (0..usage.cores.len())
+        .filter_map(|i| temp_cache.read_core_temp(i))
         .collect();
     
     if !temps.is_empty() {
@@ -792,7 +904,7 @@ pub fn get_load() -> (f64, f64) {
 }
 
 pub fn display_system_load_avg() {
-    let loadavg = System::load_average();
+    let loadavg = crate::procfs::load_average();
     println!(" (load average: {:.2}, {:.2}, {:.2})", 
         loadavg.one, loadavg.five, loadavg.fifteen);
 }
@@ -834,98 +946,15 @@ pub fn countdown(seconds: u64) {
 }
 
 // ============================================================================
-// OPTIMIZED: Improved daemon detection
+// Daemon detection
 // ============================================================================
-pub fn is_running(program: &str, argument: &str) -> bool {
-    // OPTIMIZATION: Try fast pidof first
-    if let Ok(output) = Command::new("pidof")
-        .arg("-x")
-        .arg(program)
-        .output()
-    {
-        if !output.stdout.is_empty() {
-            // Found PID, now verify it has the right argument
-            return check_proc_daemon_status(program, argument);
-        }
-    }
-    
-    // Fallback to full check
-    check_proc_daemon_status(program, argument)
-}
-
-fn check_proc_daemon_status(program: &str, argument: &str) -> bool {
-    let proc_path = Path::new("/proc");
-    
-    if !proc_path.exists() {
-        return is_running_sysinfo(program, argument);
-    }
-    
-    let entries = match fs::read_dir(proc_path) {
-        Ok(e) => e,
-        Err(_) => return is_running_sysinfo(program, argument),
-    };
-    
-    for entry in entries.filter_map(|e| e.ok()) {
-        let path = entry.path();
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_string_lossy();
-        
-        if !file_name_str.chars().all(|c| c.is_numeric()) {
-            continue;
-        }
-        
-        let cmdline_path = path.join("cmdline");
-        if let Ok(cmdline_bytes) = fs::read(&cmdline_path) {
-            let cmdline_str = String::from_utf8_lossy(&cmdline_bytes);
-            let args: Vec<&str> = cmdline_str.split('\0').filter(|s| !s.is_empty()).collect();
-            
-            if args.is_empty() {
-                continue;
-            }
-            
-            let has_program = args.iter().any(|arg| arg.contains(program));
-            
-            if has_program {
-                let has_argument = args.iter().any(|arg| arg.contains(argument));
-                
-                if has_argument {
-                    return true;
-                }
-            }
-        }
-    }
-    
-    false
-}
-
-fn is_running_sysinfo(program: &str, argument: &str) -> bool {
-    let mut sys = System::new();
-    sys.refresh_processes();
-    
-    for (_, process) in sys.processes() {
-        let exe_path = process.exe()
-            .and_then(|p| p.to_str())
-            .unwrap_or("");
-        let cmd = process.cmd();
-        let name = process.name();
-        
-        let has_program = 
-            name.contains(program) ||
-            exe_path.contains(program) ||
-            cmd.iter().any(|s| s.contains(program));
-        
-        let has_argument = cmd.iter().any(|s| s.contains(argument));
-        
-        if has_program && has_argument {
-            return true;
-        }
-    }
-    
-    false
+/// Whether a daemon holds the PID file lock
+pub fn is_running() -> bool {
+    crate::pidfile::daemon_pid().is_some()
 }
 
 pub fn daemon_running_check() -> Result<()> {
-    if is_running("auto-cpufreq", "--daemon") {
+    if is_running() {
         println!("\n{}\n", "-".repeat(24) + " auto-cpufreq running " + &"-".repeat(30));
         println!("ERROR: auto-cpufreq is running in daemon mode.");
         println!("\nMake sure to stop the daemon before running with --live or --monitor mode");
@@ -936,22 +965,168 @@ pub fn daemon_running_check() -> Result<()> {
     Ok(())
 }
 
-pub fn not_running_daemon_check() -> Result<()> {
-    if !is_running("auto-cpufreq", "--daemon") {
-        if *SYSTEMCTL_EXISTS {
-            let status = Command::new("systemctl")
-                .args(&["is-active", "auto-cpufreq"])
-                .output();
-
-            if let Ok(out) = status {
-                let active = String::from_utf8_lossy(&out.stdout);
-                if active.trim() == "active" {
-                    return Ok(());
+// ============================================================================
+// Daemon status
+// ============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceState {
+    Running,
+    Inactive,
+    Failed,
+    NotInstalled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DaemonStatus {
+    pub init: &'static str,
+    pub state: ServiceState,
+    /// Seconds since the daemon last wrote the stats file
+    pub stats_age: Option<u64>,
+    /// The stats file is older than the daemon's slowest refresh
+    pub stale: bool,
+    /// Most recent error the service logged (systemd journal only)
+    pub last_error: Option<String>,
+}
+
+impl DaemonStatus {
+    pub fn is_healthy(&self) -> bool {
+        self.state == ServiceState::Running && !self.stale
+    }
+
+    /// What to do about it, one command or hint per line
+    pub fn remediation(&self) -> Vec<String> {
+        let (start, logs) = match self.init {
+            "systemd" => ("sudo systemctl restart auto-cpufreq", Some("journalctl -u auto-cpufreq -b")),
+            "openrc" => ("sudo rc-service auto-cpufreq restart", None),
+            "dinit" => ("sudo dinitctl restart auto-cpufreq", None),
+            "runit" => ("sudo sv restart auto-cpufreq", None),
+            "s6" => ("sudo s6-svc -r /run/service/auto-cpufreq", None),
+            _ => ("sudo auto-cpufreq --install", None),
+        };
+
+        let mut hints = Vec::new();
+        match self.state {
+            ServiceState::NotInstalled => hints.push("Install the daemon: sudo auto-cpufreq --install".to_string()),
+            ServiceState::Inactive => hints.push(format!("Start the daemon: {}", start)),
+            ServiceState::Failed => {
+                if let Some(logs) = logs {
+                    hints.push(format!("See why it failed: {}", logs));
                 }
+                hints.push(format!("Restart it: {}", start));
             }
+            ServiceState::Running if self.stale => {
+                hints.push(format!("The daemon is running but stopped updating, restart it: {}", start));
+            }
+            ServiceState::Running => {}
         }
+        hints
+    }
+}
 
-        println!("\n{}\n", "-".repeat(24) + " auto-cpufreq not running " + &"-".repeat(30));
+fn service_installed(init: &str) -> bool {
+    let unit = match init {
+        "systemd" => PathBuf::from(SYSTEMD_UNIT),
+        "openrc" => PathBuf::from(OPENRC_SCRIPT),
+        "dinit" => PathBuf::from(DINIT_SERVICE),
+        "s6" => PathBuf::from(S6_SERVICE_DIR),
+        "runit" => match runit_paths() {
+            Ok((sv_path, _)) => PathBuf::from(format!("{}/sv/auto-cpufreq", sv_path)),
+            Err(_) => return false,
+        },
+        _ => return Path::new(INSTALL_MANIFEST).exists(),
+    };
+    unit.exists()
+}
+
+fn systemd_last_error() -> Option<String> {
+    let output = crate::command::run(
+        "journalctl",
+        &["-u", "auto-cpufreq", "-p", "err", "-n", "1", "-b", "--no-pager", "-o", "cat"],
+    )
+    .ok()?;
+    let line = output.stdout.trim().to_string();
+    (!line.is_empty() && !line.starts_with("-- No entries")).then_some(line)
+}
+
+pub fn daemon_status() -> DaemonStatus {
+    let init = init_system();
+    let running = is_running() || service_is_active(init);
+
+    let state = if running {
+        ServiceState::Running
+    } else if !service_installed(init) {
+        ServiceState::NotInstalled
+    } else if init == "systemd"
+        && crate::command::succeeds("systemctl", &["is-failed", "--quiet", "auto-cpufreq"])
+    {
+        ServiceState::Failed
+    } else {
+        ServiceState::Inactive
+    };
+
+    let stats_age = fs::metadata(AutoCpuFreqState::new().stats_file_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(|age| age.as_secs());
+
+    // The daemon rewrites the file every cycle, allow for two of its slowest ones
+    let max_interval = crate::events::IntervalLimits::from_config(&CONFIG.snapshot()).max;
+    let stale_after = (max_interval * 2 + Duration::from_secs(10)).as_secs();
+    let stale = running && stats_age.is_none_or(|age| age > stale_after);
+
+    let last_error = (state == ServiceState::Failed && init == "systemd")
+        .then(systemd_last_error)
+        .flatten();
+
+    DaemonStatus { init, state, stats_age, stale, last_error }
+}
+
+pub fn print_daemon_status(status: &DaemonStatus) {
+    let state = match status.state {
+        ServiceState::Running if status.stale => "running, not updating",
+        ServiceState::Running => "running",
+        ServiceState::Inactive => "inactive",
+        ServiceState::Failed => "failed",
+        ServiceState::NotInstalled => "not installed",
+    };
+
+    println!("\n{}\n", "-".repeat(28) + " auto-cpufreq daemon " + &"-".repeat(30));
+    println!("Status: {} ({})", state, status.init);
+    match status.stats_age {
+        Some(age) => println!("Last stats update: {}s ago", age),
+        None => println!("Last stats update: never"),
+    }
+    if let Some(error) = &status.last_error {
+        println!("Last error: {}", error);
+    }
+    for hint in status.remediation() {
+        println!("\n* {}", hint);
+    }
+    footer(79);
+}
+
+/// --live and the daemon would fight over the same settings
+pub fn running_daemon_check() -> Result<()> {
+    if is_running() || service_is_active(init_system()) {
+        println!("\n{}\n", "-".repeat(24) + " auto-cpufreq running " + &"-".repeat(34));
+        println!("ERROR: auto-cpufreq is running in daemon mode.");
+        println!("\nMake sure to stop the daemon before running with --live");
+        footer(79);
+        bail!("Daemon running");
+    }
+
+    Ok(())
+}
+
+pub fn not_running_daemon_check() -> Result<()> {
+    if !is_running() {
+        if service_is_active(init_system()) {
+            return Ok(());
+        }
+
+        println!("\n{}\n", "-".repeat(24) + " auto-cpufreq not running " + &"-".repeat(30));
         println!("ERROR: auto-cpufreq is not running in daemon mode.");
         println!("\nMake sure to run \"sudo auto-cpufreq --install\" first");
         footer(79);
@@ -964,56 +1139,49 @@ pub fn not_running_daemon_check() -> Result<()> {
 // ============================================================================
 // Install/Remove script runners
 // ============================================================================
-pub fn run_install_script() -> Result<()> {
-    println!("\n* Running pre-installation script");
-    
-    let temp_script = "/tmp/auto-cpufreq-install.sh";
-    fs::write(temp_script, install_script())?;
-    
-    Command::new("chmod")
-        .args(&["+x", temp_script])
-        .status()?;
-    
-    let status = Command::new("sh")
-        .arg(temp_script)
+fn run_script(name: &str, description: &str) -> Result<()> {
+    println!("\n* Running {} script", description);
+
+    let content = match name {
+        "auto-cpufreq-install.sh" => install_script(),
+        "auto-cpufreq-remove.sh" => remove_script(),
+        _ => bail!("Unknown script {}", name),
+    };
+    let temp_script = std::env::temp_dir().join(name);
+    fs::write(&temp_script, content)?;
+
+    crate::command::change("chmod", &["+x", &temp_script.to_string_lossy()])?;
+
+    let status = crate::command::new("sh")
+        .arg(&temp_script)
         .status()?;
-    
-    let _ = fs::remove_file(temp_script);
-    
+
+    let _ = fs::remove_file(&temp_script);
+
     if status.success() {
-        println!("* Pre-installation script completed successfully");
-        Ok(())
+        println!("* {} script completed successfully", capitalize(description));
     } else {
-        println!("* Warning: Pre-installation script completed with errors (continuing anyway)");
-        Ok(())
+        warn!(code = %EventCode::InstallStepFailed, "{} script completed with errors (continuing anyway)", capitalize(description));
     }
+    Ok(())
 }
 
-pub fn run_remove_script() -> Result<()> {
-    println!("\n* Running post-removal script");
-    
-    let temp_script = "/tmp/auto-cpufreq-remove.sh";
-    fs::write(temp_script, remove_script())?;
-    
-    Command::new("chmod")
-        .args(&["+x", temp_script])
-        .status()?;
-    
-    let status = Command::new("sh")
-        .arg(temp_script)
-        .status()?;
-    
-    let _ = fs::remove_file(temp_script);
-    
-    if status.success() {
-        println!("* Post-removal script completed successfully");
-        Ok(())
-    } else {
-        println!("* Warning: Post-removal script completed with errors (continuing anyway)");
-        Ok(())
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
 }
 
+pub fn run_install_script() -> Result<()> {
+    run_script("auto-cpufreq-install.sh", "pre-installation")
+}
+
+pub fn run_remove_script() -> Result<()> {
+    run_script("auto-cpufreq-remove.sh", "post-removal")
+}
+
 pub fn get_install_script() -> String { 
     install_script()
 }
@@ -1022,16 +1190,345 @@ pub fn get_remove_script() -> String {
     remove_script()
 }
 
+// ============================================================================
+// Install/Remove steps
+// ============================================================================
+
+/// One step of installing or removing the daemon. The steps are planned up
+/// front so `--install --dry-run` / `--remove --dry-run` can list exactly what
+/// would be written, deleted and run without touching the system.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStep {
+    /// Progress line, only printed when the steps are executed
+    Note(String),
+    WriteFile { path: PathBuf, content: String, executable: bool },
+    CreateDir(PathBuf),
+    RemoveFile(PathBuf),
+    RemoveDir(PathBuf),
+    /// Replaces an existing `link`
+    Symlink { target: PathBuf, link: PathBuf },
+    /// `required` commands abort the install when they can't be started
+    Command { program: String, args: Vec<String>, required: bool },
+    /// Bundled shell script from the scripts directory
+    Script { name: String, description: String },
+}
+
+impl InstallStep {
+    fn note(text: &str) -> Self {
+        Self::Note(text.to_string())
+    }
+
+    fn write(path: impl Into<PathBuf>, content: String, executable: bool) -> Self {
+        Self::WriteFile { path: path.into(), content, executable }
+    }
+
+    fn run(program: &str, args: &[&str]) -> Self {
+        Self::Command {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            required: true,
+        }
+    }
+
+    /// A command whose failure doesn't matter (e.g. stopping a stopped service)
+    fn try_run(program: &str, args: &[&str]) -> Self {
+        match Self::run(program, args) {
+            Self::Command { program, args, .. } => Self::Command { program, args, required: false },
+            step => step,
+        }
+    }
+
+    /// One line for the dry-run listing, `None` for notes
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            Self::Note(_) => None,
+            Self::WriteFile { path, content, executable } => {
+                let mut notes = Vec::new();
+                if *executable {
+                    notes.push("executable");
+                }
+                match fs::read_to_string(path) {
+                    Ok(old) if old == *content => notes.push("unchanged"),
+                    Ok(_) => notes.push("replaces the current file"),
+                    Err(_) => {}
+                }
+                let notes = if notes.is_empty() { String::new() } else { format!(" ({})", notes.join(", ")) };
+                Some(format!("write   {}{}", path.display(), notes))
+            }
+            Self::CreateDir(path) => Some(format!("mkdir   {}", path.display())),
+            Self::RemoveFile(path) => Some(format!("delete  {}", path.display())),
+            Self::RemoveDir(path) => Some(format!("delete  {} (recursively)", path.display())),
+            Self::Symlink { target, link } => {
+                Some(format!("link    {} -> {}", link.display(), target.display()))
+            }
+            Self::Command { program, args, .. } => Some(format!("run     {} {}", program, args.join(" "))),
+            Self::Script { name, description } => Some(format!(
+                "run     sh {} ({} script)",
+                crate::globals::scripts_dir().join(name).display(),
+                description
+            )),
+        }
+    }
+
+    /// Run the step and return the steps that undo it. Steps that find the
+    /// system already in the wanted state do nothing and need no undo, so
+    /// re-running an install is harmless.
+    fn execute(&self) -> Result<Vec<InstallStep>> {
+        let mut undo = Vec::new();
+
+        match self {
+            Self::Note(text) => println!("\n* {}", text),
+            Self::WriteFile { path, content, executable } => {
+                let previous = fs::read_to_string(path).ok();
+                if previous.as_deref() != Some(content.as_str()) {
+                    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+                    undo.push(match previous {
+                        Some(old) => Self::WriteFile { path: path.clone(), content: old, executable: *executable },
+                        None => Self::RemoveFile(path.clone()),
+                    });
+                }
+                if *executable {
+                    let status = crate::command::new("chmod").arg("+x").arg(path).status()?;
+                    if !status.success() {
+                        bail!("Failed to make {} executable", path.display());
+                    }
+                }
+            }
+            Self::CreateDir(path) => {
+                if !path.exists() {
+                    fs::create_dir_all(path).with_context(|| format!("Failed to create {}", path.display()))?;
+                    undo.push(Self::RemoveDir(path.clone()));
+                }
+            }
+            Self::RemoveFile(path) => { let _ = fs::remove_file(path); }
+            Self::RemoveDir(path) => { let _ = fs::remove_dir_all(path); }
+            Self::Symlink { target, link } => {
+                if fs::read_link(link).ok().as_deref() != Some(target.as_path()) {
+                    let _ = fs::remove_file(link);
+                    std::os::unix::fs::symlink(target, link)
+                        .with_context(|| format!("Failed to link {}", link.display()))?;
+                    undo.push(Self::RemoveFile(link.clone()));
+                }
+            }
+            Self::Command { program, args, required } => {
+                let status = crate::command::new(program).args(args).status();
+                if *required {
+                    let status = status.with_context(|| format!("Failed to run {}", program))?;
+                    if !status.success() {
+                        bail!("'{} {}' failed ({})", program, args.join(" "), status);
+                    }
+                }
+                undo.extend(undo_command(program, args));
+            }
+            Self::Script { name, description } => {
+                run_script(name, description)?;
+                if name == "auto-cpufreq-install.sh" {
+                    undo.push(Self::Script {
+                        name: "auto-cpufreq-remove.sh".into(),
+                        description: "post-removal".into(),
+                    });
+                }
+            }
+        }
+
+        Ok(undo)
+    }
+}
+
+/// The service manager command reverting `program args`, if it changes state
+fn undo_command(program: &str, args: &[String]) -> Option<InstallStep> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let undo = |program: &str, args: &[&str]| Some(InstallStep::try_run(program, args));
+
+    match (program, args.as_slice()) {
+        ("systemctl", ["start", unit]) => undo("systemctl", &["stop", unit]),
+        ("systemctl", ["enable", unit]) => undo("systemctl", &["disable", unit]),
+        ("systemctl", ["enable", "--now", unit]) => undo("systemctl", &["disable", "--now", unit]),
+        ("systemctl", ["mask", unit]) => undo("systemctl", &["unmask", unit]),
+        ("systemctl", ["stop", unit]) if crate::power_helper::CONFLICTING_UNITS.contains(unit) => {
+            undo("systemctl", &["start", unit])
+        }
+        ("rc-service", [service, "start"]) => undo("rc-service", &[service, "stop"]),
+        ("rc-update", ["add", service]) => undo("rc-update", &["del", service]),
+        ("dinitctl", ["start", service]) => undo("dinitctl", &["stop", service]),
+        ("dinitctl", ["enable", service]) => undo("dinitctl", &["disable", service]),
+        ("sv", ["start" | "up", service]) => undo("sv", &["stop", service]),
+        ("s6-service", ["add", bundle, service]) => undo("s6-service", &["delete", bundle, service]),
+        ("s6-rc", ["-u", rest @ ..]) => {
+            let mut args = vec!["-d"];
+            args.extend_from_slice(rest);
+            undo("s6-rc", &args)
+        }
+        _ => None,
+    }
+}
+
+/// Heading a step is listed under in the dry run
+fn dry_run_section(step: &InstallStep) -> Option<&'static str> {
+    match step {
+        InstallStep::Note(_) => None,
+        InstallStep::WriteFile { .. } | InstallStep::CreateDir(_) | InstallStep::Symlink { .. } => Some("Files written"),
+        InstallStep::RemoveFile(_) | InstallStep::RemoveDir(_) => Some("Files deleted"),
+        InstallStep::Command { program, args, .. }
+            if program == "systemctl"
+                && args.last().is_some_and(|unit| crate::power_helper::CONFLICTING_UNITS.contains(&unit.as_str())) =>
+        {
+            Some("Conflicting services")
+        }
+        InstallStep::Command { .. } => Some("Service commands"),
+        InstallStep::Script { .. } => Some("Scripts"),
+    }
+}
+
+const DRY_RUN_SECTIONS: [&str; 5] = ["Scripts", "Files written", "Service commands", "Conflicting services", "Files deleted"];
+
+fn print_dry_run(steps: &[InstallStep]) {
+    println!("\nDry run, nothing will be changed. The following would be done:");
+    for section in DRY_RUN_SECTIONS {
+        let lines: Vec<String> = steps
+            .iter()
+            .filter(|step| dry_run_section(step) == Some(section))
+            .filter_map(InstallStep::describe)
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        println!("\n{}:", section);
+        for line in lines {
+            println!("  {}", line);
+        }
+    }
+    println!();
+}
+
+/// Execute the steps, or with `dry_run` only list them
+pub fn run_install_steps(steps: &[InstallStep], dry_run: bool) -> Result<()> {
+    if dry_run {
+        print_dry_run(steps);
+        return Ok(());
+    }
+
+    steps.iter().try_for_each(|step| step.execute().map(|_| ()))
+}
+
+// ============================================================================
+// Install manifest
+// ============================================================================
+pub const INSTALL_MANIFEST: &str = "/var/lib/auto-cpufreq/install-manifest.json";
+
+/// Record of what an install changed, written after every step. An install
+/// that fails is rolled back from it; one that was interrupted (power loss,
+/// SIGKILL) is rolled back by the next `--install`, and `--remove` uses it to
+/// clean up files it wouldn't otherwise know about.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallManifest {
+    pub init: String,
+    pub complete: bool,
+    /// Steps reverting the install, in the order their changes were made
+    pub undo: Vec<InstallStep>,
+}
+
+impl InstallManifest {
+    fn path() -> &'static Path {
+        Path::new(INSTALL_MANIFEST)
+    }
+
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(Self::path()).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                warn!(code = %EventCode::InstallStepFailed, "Ignoring unreadable {}: {}", INSTALL_MANIFEST, e);
+                None
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = Self::path().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = Self::path().with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, Self::path()).with_context(|| format!("Failed to write {}", INSTALL_MANIFEST))
+    }
+
+    fn delete() {
+        let _ = fs::remove_file(Self::path());
+    }
+
+    /// Revert what was recorded after the first `keep` undo steps, newest
+    /// first. Best effort: a step that fails to revert doesn't stop the others.
+    fn rollback(mut self, keep: usize) {
+        for step in self.undo.drain(keep..).rev() {
+            if let Err(e) = step.execute() {
+                warn!(code = %EventCode::InstallStepFailed, "Rollback step failed: {}", e);
+            }
+        }
+
+        if self.undo.is_empty() {
+            Self::delete();
+        } else {
+            // What an earlier, complete install left in place stays recorded
+            self.complete = true;
+            if let Err(e) = self.save() {
+                warn!(code = %EventCode::InstallStepFailed, "{}", e);
+            }
+        }
+    }
+}
+
+/// Run an install plan as a transaction: on the first failing step everything
+/// done so far is undone and the error returned
+fn run_install_transaction(init: &str, steps: &[InstallStep]) -> Result<()> {
+    // Reinstalling over a complete install keeps its record, so --remove
+    // still knows about everything
+    let mut manifest = match InstallManifest::load() {
+        Some(previous) if previous.complete => InstallManifest { complete: false, ..previous },
+        Some(stale) => {
+            println!("\n* Rolling back an interrupted install ({})", stale.init);
+            stale.rollback(0);
+            InstallManifest::default()
+        }
+        None => InstallManifest::default(),
+    };
+    manifest.init = init.to_string();
+    let keep = manifest.undo.len();
+
+    // Reverted last, once the unit file is gone
+    if init == "systemd" {
+        manifest.undo.push(InstallStep::try_run("systemctl", &["daemon-reload"]));
+    }
+    manifest.save()?;
+
+    for step in steps {
+        match step.execute() {
+            Ok(undo) => {
+                manifest.undo.extend(undo);
+                manifest.save()?;
+            }
+            Err(e) => {
+                println!("\n* Install failed, rolling back changes");
+                manifest.rollback(keep);
+                return Err(e.context("auto-cpufreq daemon install failed, changes were rolled back"));
+            }
+        }
+    }
+
+    manifest.complete = true;
+    manifest.save()
+}
+
 // ============================================================================
 // Init system detection and daemon installation/removal
 // ============================================================================
 pub fn detect_init_system() -> &'static str {
-    let output = Command::new("ps")
-        .args(&["-p", "1", "-o", "comm="])
-        .output();
+    let output = crate::command::run("ps", &["-p", "1", "-o", "comm="]);
     
     if let Ok(out) = output {
-        let init = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        let init = out.stdout.trim().to_string();
         match init.as_str() {
             "systemd" => "systemd",
             "init" => "openrc",
@@ -1045,43 +1542,146 @@ pub fn detect_init_system() -> &'static str {
     }
 }
 
-pub fn install_daemon() -> Result<()> {
-    let init = detect_init_system();
-    
-    println!("\n{}", "=".repeat(80));
-    println!("Installing auto-cpufreq daemon ({} detected)", init);
-    println!("{}", "=".repeat(80));
-    
-    run_install_script()?;
-    
-    deploy_cpufreqctl()?;
-    
+pub const INIT_SYSTEMS: &[&str] = &["systemd", "openrc", "dinit", "runit", "s6"];
+
+/// (control command, runtime paths of which one must exist while it runs the system)
+fn init_system_markers(init: &str) -> Option<(&'static str, &'static [&'static str])> {
     match init {
-        "systemd" => install_systemd(),
-        "openrc" => install_openrc(),
-        "dinit" => install_dinit(),
-        "runit" => install_runit(),
-        "s6" => install_s6(),
+        // sd_booted() checks the same directory
+        "systemd" => Some(("systemctl", &["/run/systemd/system"])),
+        "openrc" => Some(("rc-service", &["/run/openrc/softlevel", "/run/openrc/started"])),
+        "dinit" => Some(("dinitctl", &["/run/dinitctl"])),
+        "runit" => Some(("sv", &["/run/runit", "/run/runit.stopit"])),
+        "s6" => Some(("s6-rc", &["/run/s6-rc"])),
+        _ => None,
+    }
+}
+
+/// Check that `init` is the service manager supervising system services, not
+/// just installed next to it (e.g. a user-level runit on a systemd machine)
+pub fn validate_init_system(init: &str) -> Result<()> {
+    let Some((command, markers)) = init_system_markers(init) else {
+        bail!("Unknown init system '{}' (supported: {})", init, INIT_SYSTEMS.join(", "));
+    };
+
+    if !crate::power_helper::does_command_exist(command) {
+        bail!("{} is not usable: '{}' was not found", init, command);
+    }
+
+    let manages_system = detect_init_system() == init || markers.iter().any(|m| Path::new(m).exists());
+    if !manages_system {
+        bail!(
+            "{} does not manage system services here (PID 1 is {}, none of {} exist)",
+            init,
+            detect_init_system(),
+            markers.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+static SELECTED_INIT: std::sync::OnceLock<&'static str> = std::sync::OnceLock::new();
+
+/// Use `init` instead of the detected init system for install, remove and
+/// daemon status checks (`--init`)
+pub fn select_init_system(init: &str) -> Result<()> {
+    let init = init.trim().to_lowercase();
+    validate_init_system(&init)?;
+
+    let init = INIT_SYSTEMS.iter().find(|i| **i == init).copied().unwrap_or("unknown");
+    let _ = SELECTED_INIT.set(init);
+    Ok(())
+}
+
+/// The init system selected with `--init`, otherwise the one running as PID 1
+pub fn init_system() -> &'static str {
+    SELECTED_INIT.get().copied().unwrap_or_else(detect_init_system)
+}
+
+/// Whether the auto-cpufreq service is up according to its service manager
+fn service_is_active(init: &str) -> bool {
+    let (program, args): (&str, &[&str]) = match init {
+        "systemd" => ("systemctl", &["is-active", "--quiet", "auto-cpufreq"]),
+        "openrc" => ("rc-service", &["auto-cpufreq", "status"]),
+        "dinit" => ("dinitctl", &["is-started", "auto-cpufreq"]),
+        "runit" => ("sv", &["check", "auto-cpufreq"]),
+        "s6" => ("s6-svstat", &["-u", "/run/service/auto-cpufreq"]),
+        _ => return false,
+    };
+
+    crate::command::succeeds(program, args)
+}
+
+/// Everything `--install` does on the given init system
+pub fn install_plan(init: &str) -> Result<Vec<InstallStep>> {
+    let mut steps = vec![InstallStep::Script {
+        name: "auto-cpufreq-install.sh".into(),
+        description: "pre-installation".into(),
+    }];
+
+    let cpufreqctl = crate::globals::cpufreqctl_path();
+    if !cpufreqctl.exists() {
+        steps.push(InstallStep::note("Deploying cpufreqctl helper script"));
+        steps.push(InstallStep::write(cpufreqctl, cpufreqctl_script(), true));
+    }
+
+    if crate::ppd_shim::enabled(&CONFIG.snapshot()) {
+        steps.push(InstallStep::note("Deploying D-Bus policy for the power-profiles-daemon shim"));
+        steps.push(InstallStep::write(crate::ppd_shim::DBUS_POLICY, ppd_shim_policy(), false));
+        steps.push(InstallStep::try_run(
+            "busctl",
+            &["call", "org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus", "ReloadConfig"],
+        ));
+    }
+
+    steps.extend(match init {
+        "systemd" => install_systemd()?,
+        "openrc" => install_openrc()?,
+        "dinit" => install_dinit()?,
+        "runit" => install_runit()?,
+        "s6" => install_s6()?,
         _ => {
             println!("\n* Unsupported init system detected, could not install the daemon\n");
             println!("* Please open an issue on https://github.com/Zamanhuseyinli/auto-cpufreq-rust\n");
             bail!("Unsupported init system: {}", init)
         }
+    });
+
+    steps.extend(disable_conflicting_services(init)?);
+
+    Ok(steps)
+}
+
+/// `[integration] conflict_policy = disable`: stop and mask TLP, tuned,
+/// intel_lpmd and power-profiles-daemon. The undo steps recorded in the
+/// install manifest unmask and restart them on --remove.
+fn disable_conflicting_services(init: &str) -> Result<Vec<InstallStep>> {
+    let units = crate::power_helper::conflicting_units()?;
+    if units.is_empty() {
+        return Ok(Vec::new());
     }
+    if init != "systemd" {
+        warn!(code = %EventCode::ConfigInvalid, "conflict_policy = disable is only supported with systemd, leaving {} running", units.join(", "));
+        return Ok(Vec::new());
+    }
+
+    let mut steps = Vec::new();
+    for unit in units {
+        steps.push(InstallStep::note(&format!("Masking {} (conflict_policy = disable)", unit)));
+        steps.push(InstallStep::run("systemctl", &["stop", unit]));
+        steps.push(InstallStep::run("systemctl", &["mask", unit]));
+    }
+    Ok(steps)
 }
 
-pub fn remove_daemon() -> Result<()> {
-    let init = detect_init_system();
-    
-    println!("\n{}", "=".repeat(80));
-    println!("Removing auto-cpufreq daemon ({} detected)", init);
-    println!("{}", "=".repeat(80));
-    
-    let result = match init {
+/// Everything `--remove` does on the given init system
+pub fn remove_plan(init: &str) -> Result<Vec<InstallStep>> {
+    let mut steps = match init {
         "systemd" => remove_systemd(),
         "openrc" => remove_openrc(),
         "dinit" => remove_dinit(),
-        "runit" => remove_runit(),
+        "runit" => remove_runit()?,
         "s6" => remove_s6(),
         _ => {
             println!("\n* Unsupported init system detected, could not remove the daemon");
@@ -1089,306 +1689,345 @@ pub fn remove_daemon() -> Result<()> {
             bail!("Unsupported init system: {}", init)
         }
     };
-    
-    remove_cpufreqctl()?;
-    
-    run_remove_script()?;
-    
-    result
+
+    let cpufreqctl = crate::globals::cpufreqctl_path();
+    if cpufreqctl.exists() {
+        steps.push(InstallStep::note("Removing cpufreqctl helper script"));
+        steps.push(InstallStep::RemoveFile(cpufreqctl));
+    }
+
+    if Path::new(crate::ppd_shim::DBUS_POLICY).exists() {
+        steps.push(InstallStep::note("Removing D-Bus policy of the power-profiles-daemon shim"));
+        steps.push(InstallStep::RemoveFile(crate::ppd_shim::DBUS_POLICY.into()));
+    }
+
+    // Files the recorded install created that this plan doesn't cover (e.g.
+    // deployed under another prefix or by an older version), and services
+    // conflict_policy = disable masked, to unmask and restart in reverse order
+    if let Some(manifest) = InstallManifest::load() {
+        let mut restore = Vec::new();
+        for step in manifest.undo {
+            if matches!(step, InstallStep::RemoveFile(_) | InstallStep::RemoveDir(_)) && !steps.contains(&step) {
+                steps.push(step);
+            } else if restores_conflicting_service(&step) {
+                restore.push(step);
+            }
+        }
+        if !restore.is_empty() {
+            steps.push(InstallStep::note("Re-enabling services masked on install"));
+            steps.extend(restore.into_iter().rev());
+        }
+    }
+
+    steps.push(InstallStep::Script {
+        name: "auto-cpufreq-remove.sh".into(),
+        description: "post-removal".into(),
+    });
+    steps.push(InstallStep::RemoveFile(INSTALL_MANIFEST.into()));
+
+    Ok(steps)
 }
 
-// ============================================================================
-// systemd
-// ============================================================================
-fn install_systemd() -> Result<()> {
-    println!("\n* Deploying auto-cpufreq systemd unit file");
-    
-    fs::write("/etc/systemd/system/auto-cpufreq.service", systemd_service())?;
-    
-    println!("\n* Reloading systemd manager configuration");
-    Command::new("systemctl")
-        .arg("daemon-reload")
-        .status()?;
-    
-    println!("\n* Starting auto-cpufreq daemon (systemd) service");
-    Command::new("systemctl")
-        .args(&["start", "auto-cpufreq"])
-        .status()?;
-    
-    println!("\n* Enabling auto-cpufreq daemon (systemd) at boot");
-    Command::new("systemctl")
-        .args(&["enable", "auto-cpufreq"])
-        .status()?;
-    
-    Ok(())
+/// What `--remove --purge` deletes on top of `--remove`: the state file with
+/// its overrides, the audit log, stats history and other files under
+/// /var/lib/auto-cpufreq, the stats file, log files and the system config
+pub fn purge_plan(config: &Snapshot) -> Vec<InstallStep> {
+    let state_dir = Path::new(crate::state::STATE_FILE).parent().unwrap_or(Path::new("/var/lib/auto-cpufreq"));
+    let mut files = vec![AutoCpuFreqState::new().stats_file_path];
+    files.extend(crate::state::legacy_files());
+    if let Ok(logging) = crate::logging::Settings::from_config(config, None) {
+        if let Some(log) = logging.file {
+            files.extend(crate::logging::log_files(&log));
+            files.push(log);
+        }
+    }
+    for config in crate::config::effective::SYSTEM_CONFIG_FILES {
+        files.push(config.into());
+        // Left by --import-settings
+        files.push(format!("{}.bak", config).into());
+    }
+
+    let mut steps = vec![InstallStep::note("Deleting state, logs and config")];
+    if state_dir.exists() {
+        steps.push(InstallStep::RemoveDir(state_dir.to_path_buf()));
+    }
+    steps.extend(files.into_iter().filter(|f| f.exists()).map(InstallStep::RemoveFile));
+    steps
 }
 
-fn remove_systemd() -> Result<()> {
-    println!("\n* Stopping auto-cpufreq daemon (systemd) service");
-    let _ = Command::new("systemctl")
-        .args(&["stop", "auto-cpufreq"])
-        .status();
-    
-    println!("\n* Disabling auto-cpufreq daemon (systemd) at boot");
-    let _ = Command::new("systemctl")
-        .args(&["disable", "auto-cpufreq"])
-        .status();
+/// `--remove --purge`, after the daemon is removed and the settings restored
+pub fn purge(dry_run: bool) -> Result<()> {
+    println!("\n{}", "=".repeat(80));
+    println!("Purging auto-cpufreq state, logs and config");
+    println!("{}", "=".repeat(80));
+
+    run_install_steps(&purge_plan(&CONFIG.snapshot()), dry_run)
+}
+
+/// An undo step that unmasks or restarts a service masked on install
+fn restores_conflicting_service(step: &InstallStep) -> bool {
+    match step {
+        InstallStep::Command { program, args, .. } => {
+            program == "systemctl"
+                && matches!(args.as_slice(), [action, unit]
+                    if (action == "unmask" || action == "start")
+                        && crate::power_helper::CONFLICTING_UNITS.contains(&unit.as_str()))
+        }
+        _ => false,
+    }
+}
+
+pub fn install_daemon(dry_run: bool) -> Result<()> {
+    let init = init_system();
     
-    println!("\n* Removing auto-cpufreq daemon (systemd) unit file");
-    let _ = fs::remove_file("/etc/systemd/system/auto-cpufreq.service");
+    println!("\n{}", "=".repeat(80));
+    println!("Installing auto-cpufreq daemon ({} detected)", init);
+    println!("{}", "=".repeat(80));
     
-    println!("\n* Reloading systemd manager configuration");
-    Command::new("systemctl")
-        .arg("daemon-reload")
-        .status()?;
+    let plan = install_plan(init)?;
+    if dry_run {
+        print_dry_run(&plan);
+        return Ok(());
+    }
+
+    run_install_transaction(init, &plan)
+}
+
+pub fn remove_daemon(dry_run: bool) -> Result<()> {
+    let init = init_system();
     
-    println!("\nReset failed");
-    Command::new("systemctl")
-        .arg("reset-failed")
-        .status()?;
+    println!("\n{}", "=".repeat(80));
+    println!("Removing auto-cpufreq daemon ({} detected)", init);
+    println!("{}", "=".repeat(80));
     
-    Ok(())
+    run_install_steps(&remove_plan(init)?, dry_run)
 }
 
 // ============================================================================
-// OpenRC
+// systemd
 // ============================================================================
-fn install_openrc() -> Result<()> {
-    println!("\n* Deploying auto-cpufreq openrc unit file");
-    
-    fs::write("/etc/init.d/auto-cpufreq", openrc_service())?;
-    
-    Command::new("chmod")
-        .args(&["+x", "/etc/init.d/auto-cpufreq"])
-        .status()?;
-    
-    println!("\n* Starting auto-cpufreq daemon (openrc) service");
-    Command::new("rc-service")
-        .args(&["auto-cpufreq", "start"])
-        .status()?;
-    
-    println!("\n* Enabling auto-cpufreq daemon (openrc) at boot");
-    Command::new("rc-update")
-        .args(&["add", "auto-cpufreq"])
-        .status()?;
-    
-    Ok(())
+const SYSTEMD_UNIT: &str = "/etc/systemd/system/auto-cpufreq.service";
+const SYSTEMD_SOCKET: &str = "/etc/systemd/system/auto-cpufreq.socket";
+
+fn install_systemd() -> Result<Vec<InstallStep>> {
+    Ok(vec![
+        InstallStep::note("Deploying auto-cpufreq systemd unit file"),
+        InstallStep::write(SYSTEMD_UNIT, systemd_service()?, false),
+        InstallStep::write(SYSTEMD_SOCKET, systemd_socket(), false),
+        InstallStep::note("Reloading systemd manager configuration"),
+        InstallStep::run("systemctl", &["daemon-reload"]),
+        // The service pulls in the control socket; drop the boot-time
+        // activation earlier installs enabled
+        InstallStep::try_run("systemctl", &["disable", "auto-cpufreq.socket"]),
+        InstallStep::note("Starting auto-cpufreq daemon (systemd) service"),
+        InstallStep::run("systemctl", &["start", "auto-cpufreq"]),
+        InstallStep::note("Enabling auto-cpufreq daemon (systemd) at boot"),
+        InstallStep::run("systemctl", &["enable", "auto-cpufreq"]),
+    ])
+}
+
+fn remove_systemd() -> Vec<InstallStep> {
+    vec![
+        InstallStep::note("Stopping auto-cpufreq daemon (systemd) service"),
+        InstallStep::try_run("systemctl", &["stop", "auto-cpufreq"]),
+        InstallStep::note("Disabling auto-cpufreq daemon (systemd) at boot"),
+        InstallStep::try_run("systemctl", &["disable", "auto-cpufreq"]),
+        InstallStep::note("Stopping auto-cpufreq control socket (systemd)"),
+        InstallStep::try_run("systemctl", &["disable", "--now", "auto-cpufreq.socket"]),
+        InstallStep::note("Removing auto-cpufreq daemon (systemd) unit file"),
+        InstallStep::RemoveFile(SYSTEMD_UNIT.into()),
+        InstallStep::RemoveFile(SYSTEMD_SOCKET.into()),
+        InstallStep::note("Reloading systemd manager configuration"),
+        InstallStep::run("systemctl", &["daemon-reload"]),
+        InstallStep::note("Reset failed"),
+        InstallStep::run("systemctl", &["reset-failed"]),
+    ]
 }
 
-fn remove_openrc() -> Result<()> {
-    println!("\n* Stopping auto-cpufreq daemon (openrc) service");
-    let _ = Command::new("rc-service")
-        .args(&["auto-cpufreq", "stop"])
-        .status();
-    
-    println!("\n* Disabling auto-cpufreq daemon (openrc) at boot");
-    let _ = Command::new("rc-update")
-        .args(&["del", "auto-cpufreq"])
-        .status();
-    
-    println!("\n* Removing auto-cpufreq daemon (openrc) unit file");
-    let _ = fs::remove_file("/etc/init.d/auto-cpufreq");
-    
-    Ok(())
+// ============================================================================
+// OpenRC
+// ============================================================================
+const OPENRC_SCRIPT: &str = "/etc/init.d/auto-cpufreq";
+
+fn install_openrc() -> Result<Vec<InstallStep>> {
+    Ok(vec![
+        InstallStep::note("Deploying auto-cpufreq openrc unit file"),
+        InstallStep::write(OPENRC_SCRIPT, openrc_service()?, true),
+        InstallStep::note("Starting auto-cpufreq daemon (openrc) service"),
+        InstallStep::run("rc-service", &["auto-cpufreq", "start"]),
+        InstallStep::note("Enabling auto-cpufreq daemon (openrc) at boot"),
+        InstallStep::run("rc-update", &["add", "auto-cpufreq"]),
+    ])
+}
+
+fn remove_openrc() -> Vec<InstallStep> {
+    vec![
+        InstallStep::note("Stopping auto-cpufreq daemon (openrc) service"),
+        InstallStep::try_run("rc-service", &["auto-cpufreq", "stop"]),
+        InstallStep::note("Disabling auto-cpufreq daemon (openrc) at boot"),
+        InstallStep::try_run("rc-update", &["del", "auto-cpufreq"]),
+        InstallStep::note("Removing auto-cpufreq daemon (openrc) unit file"),
+        InstallStep::RemoveFile(OPENRC_SCRIPT.into()),
+    ]
 }
 
 // ============================================================================
 // dinit
 // ============================================================================
-fn install_dinit() -> Result<()> {
-    println!("\n* Deploying auto-cpufreq (dinit) unit file");
-    
-    fs::write("/etc/dinit.d/auto-cpufreq", dinit_service())?;
-    
-    println!("\n* Starting auto-cpufreq daemon (dinit) service");
-    Command::new("dinitctl")
-        .args(&["start", "auto-cpufreq"])
-        .status()?;
-    
-    println!("\n* Enabling auto-cpufreq daemon (dinit) at boot");
-    Command::new("dinitctl")
-        .args(&["enable", "auto-cpufreq"])
-        .status()?;
-    
-    Ok(())
-}
-
-fn remove_dinit() -> Result<()> {
-    println!("\n* Stopping auto-cpufreq daemon (dinit) service");
-    let _ = Command::new("dinitctl")
-        .args(&["stop", "auto-cpufreq"])
-        .status();
-    
-    println!("\n* Disabling auto-cpufreq daemon (dinit) at boot");
-    let _ = Command::new("dinitctl")
-        .args(&["disable", "auto-cpufreq"])
-        .status();
-    
-    println!("\n* Removing auto-cpufreq daemon (dinit) unit file");
-    let _ = fs::remove_file("/etc/dinit.d/auto-cpufreq");
-    
-    Ok(())
+const DINIT_SERVICE: &str = "/etc/dinit.d/auto-cpufreq";
+
+fn install_dinit() -> Result<Vec<InstallStep>> {
+    Ok(vec![
+        InstallStep::note("Deploying auto-cpufreq (dinit) unit file"),
+        InstallStep::write(DINIT_SERVICE, dinit_service()?, false),
+        InstallStep::note("Starting auto-cpufreq daemon (dinit) service"),
+        InstallStep::run("dinitctl", &["start", "auto-cpufreq"]),
+        InstallStep::note("Enabling auto-cpufreq daemon (dinit) at boot"),
+        InstallStep::run("dinitctl", &["enable", "auto-cpufreq"]),
+    ])
+}
+
+fn remove_dinit() -> Vec<InstallStep> {
+    vec![
+        InstallStep::note("Stopping auto-cpufreq daemon (dinit) service"),
+        InstallStep::try_run("dinitctl", &["stop", "auto-cpufreq"]),
+        InstallStep::note("Disabling auto-cpufreq daemon (dinit) at boot"),
+        InstallStep::try_run("dinitctl", &["disable", "auto-cpufreq"]),
+        InstallStep::note("Removing auto-cpufreq daemon (dinit) unit file"),
+        InstallStep::RemoveFile(DINIT_SERVICE.into()),
+    ]
 }
 
 // ============================================================================
 // runit
 // ============================================================================
-fn install_runit() -> Result<()> {
-    let (sv_path, service_path) = if Path::new("/etc/os-release").exists() {
-        let os_release = fs::read_to_string("/etc/os-release")?;
-        let mut distro_id = String::new();
-        
-        for line in os_release.lines() {
-            if line.starts_with("ID=") {
-                distro_id = line.trim_start_matches("ID=").trim_matches('"').to_string();
-                break;
-            }
-        }
-        
-        match distro_id.as_str() {
-            "void" => ("/etc", "/var"),
-            "artix" => ("/etc/runit", "/run/runit"),
-            _ => {
-                println!("\n* Runit init detected but your distro is not supported\n");
-                println!("* Please open an issue on https://github.com/Zamanhuseyinli/auto-cpufreq-rust\n");
-                bail!("Unsupported runit distro: {}", distro_id);
-            }
+
+/// (`sv` directory parent, `service` directory parent) for the running distro
+fn runit_paths() -> Result<(&'static str, &'static str)> {
+    let os_release = fs::read_to_string("/etc/os-release").context("Could not detect distro for runit")?;
+    let distro_id = os_release
+        .lines()
+        .find_map(|line| line.strip_prefix("ID="))
+        .map(|id| id.trim_matches('"').to_string())
+        .unwrap_or_default();
+
+    match distro_id.as_str() {
+        "void" => Ok(("/etc", "/var")),
+        "artix" => Ok(("/etc/runit", "/run/runit")),
+        _ => {
+            println!("\n* Runit init detected but your distro is not supported\n");
+            println!("* Please open an issue on https://github.com/Zamanhuseyinli/auto-cpufreq-rust\n");
+            bail!("Unsupported runit distro: {}", distro_id);
         }
-    } else {
-        bail!("Could not detect distro for runit");
-    };
-    
-    println!("\n* Deploying auto-cpufreq (runit) unit file");
-    
-    let sv_dir = format!("{}/sv/auto-cpufreq", sv_path);
-    fs::create_dir_all(&sv_dir)?;
-    
-    let run_script = format!("{}/run", sv_dir);
-    fs::write(&run_script, runit_service())?;
-    
-    Command::new("chmod")
-        .args(&["+x", &run_script])
-        .status()?;
-    
-    println!("\n* Creating symbolic link ({}/service/auto-cpufreq -> {}/sv/auto-cpufreq)", service_path, sv_path);
-    
-    let service_link = format!("{}/service/auto-cpufreq", service_path);
-    let _ = fs::remove_file(&service_link);
-    
-    std::os::unix::fs::symlink(&sv_dir, &service_link)?;
-    
-    println!("\n* Starting auto-cpufreq daemon (runit)");
-    Command::new("sv")
-        .args(&["start", "auto-cpufreq"])
-        .status()?;
-    
-    Command::new("sv")
-        .args(&["up", "auto-cpufreq"])
-        .status()?;
-    
-    Ok(())
+    }
 }
 
-fn remove_runit() -> Result<()> {
-    let (sv_path, service_path) = if Path::new("/etc/os-release").exists() {
-        let os_release = fs::read_to_string("/etc/os-release")?;
-        let mut distro_id = String::new();
-        
-        for line in os_release.lines() {
-            if line.starts_with("ID=") {
-                distro_id = line.trim_start_matches("ID=").trim_matches('"').to_string();
-                break;
-            }
-        }
-        
-        match distro_id.as_str() {
-            "void" => ("/etc", "/var"),
-            "artix" => ("/etc/runit", "/run/runit"),
-            _ => bail!("Unsupported runit distro"),
-        }
-    } else {
-        bail!("Could not detect distro");
-    };
-    
-    println!("\n* Stopping auto-cpufreq daemon (runit) service");
-    let _ = Command::new("sv")
-        .args(&["stop", "auto-cpufreq"])
-        .status();
-    
-    println!("\n* Removing auto-cpufreq daemon (runit) unit file");
-    let _ = fs::remove_dir_all(format!("{}/sv/auto-cpufreq", sv_path));
-    let _ = fs::remove_file(format!("{}/service/auto-cpufreq", service_path));
-    
-    Ok(())
+fn install_runit() -> Result<Vec<InstallStep>> {
+    let (sv_path, service_path) = runit_paths()?;
+    let sv_dir = PathBuf::from(format!("{}/sv/auto-cpufreq", sv_path));
+
+    Ok(vec![
+        InstallStep::note("Deploying auto-cpufreq (runit) unit file"),
+        InstallStep::CreateDir(sv_dir.clone()),
+        InstallStep::write(sv_dir.join("run"), runit_service()?, true),
+        InstallStep::Note(format!(
+            "Creating symbolic link ({}/service/auto-cpufreq -> {}/sv/auto-cpufreq)",
+            service_path, sv_path
+        )),
+        InstallStep::Symlink {
+            target: sv_dir,
+            link: format!("{}/service/auto-cpufreq", service_path).into(),
+        },
+        InstallStep::note("Starting auto-cpufreq daemon (runit)"),
+        InstallStep::run("sv", &["start", "auto-cpufreq"]),
+        InstallStep::run("sv", &["up", "auto-cpufreq"]),
+    ])
+}
+
+fn remove_runit() -> Result<Vec<InstallStep>> {
+    let (sv_path, service_path) = runit_paths()?;
+
+    Ok(vec![
+        InstallStep::note("Stopping auto-cpufreq daemon (runit) service"),
+        InstallStep::try_run("sv", &["stop", "auto-cpufreq"]),
+        InstallStep::note("Removing auto-cpufreq daemon (runit) unit file"),
+        InstallStep::RemoveDir(format!("{}/sv/auto-cpufreq", sv_path).into()),
+        InstallStep::RemoveFile(format!("{}/service/auto-cpufreq", service_path).into()),
+    ])
 }
 
 // ============================================================================
 // s6
 // ============================================================================
-fn install_s6() -> Result<()> {
-    println!("\n* Deploying auto-cpufreq (s6) unit file");
-    
-    let s6_dir = "/etc/s6/sv/auto-cpufreq";
-    fs::create_dir_all(s6_dir)?;
-    
-    let run_script = format!("{}/run", s6_dir);
-    fs::write(&run_script, s6_service())?;
-    
-    Command::new("chmod")
-        .args(&["+x", &run_script])
-        .status()?;
-    
-    println!("\n* Add auto-cpufreq service (s6) to default bundle");
-    Command::new("s6-service")
-        .args(&["add", "default", "auto-cpufreq"])
-        .status()?;
-    
-    println!("\n* Starting auto-cpufreq daemon (s6)");
-    Command::new("s6-rc")
-        .args(&["-u", "change", "auto-cpufreq", "default"])
-        .status()?;
-    
-    println!("\n* Update daemon service bundle (s6)");
-    Command::new("s6-db-reload")
-        .status()?;
-    
-    Ok(())
-}
-
-fn remove_s6() -> Result<()> {
-    println!("\n* Disabling auto-cpufreq daemon (s6) at boot");
-    let _ = Command::new("s6-service")
-        .args(&["delete", "default", "auto-cpufreq"])
-        .status();
-    
-    println!("\n* Removing auto-cpufreq daemon (s6) unit file");
-    let _ = fs::remove_dir_all("/etc/s6/sv/auto-cpufreq");
-    
-    println!("\n* Update daemon service bundle (s6)");
-    Command::new("s6-db-reload")
-        .status()?;
-    
-    Ok(())
+const S6_SERVICE_DIR: &str = "/etc/s6/sv/auto-cpufreq";
+
+fn install_s6() -> Result<Vec<InstallStep>> {
+    Ok(vec![
+        InstallStep::note("Deploying auto-cpufreq (s6) unit file"),
+        InstallStep::CreateDir(S6_SERVICE_DIR.into()),
+        InstallStep::write(Path::new(S6_SERVICE_DIR).join("run"), s6_service()?, true),
+        InstallStep::note("Add auto-cpufreq service (s6) to default bundle"),
+        InstallStep::run("s6-service", &["add", "default", "auto-cpufreq"]),
+        InstallStep::note("Starting auto-cpufreq daemon (s6)"),
+        InstallStep::run("s6-rc", &["-u", "change", "auto-cpufreq", "default"]),
+        InstallStep::note("Update daemon service bundle (s6)"),
+        InstallStep::run("s6-db-reload", &[]),
+    ])
+}
+
+fn remove_s6() -> Vec<InstallStep> {
+    vec![
+        InstallStep::note("Disabling auto-cpufreq daemon (s6) at boot"),
+        InstallStep::try_run("s6-service", &["delete", "default", "auto-cpufreq"]),
+        InstallStep::note("Removing auto-cpufreq daemon (s6) unit file"),
+        InstallStep::RemoveDir(S6_SERVICE_DIR.into()),
+        InstallStep::note("Update daemon service bundle (s6)"),
+        InstallStep::run("s6-db-reload", &[]),
+    ]
 }
 
 // ============================================================================
 // Automatic frequency adjustment - Main daemon logic
 // ============================================================================
-fn get_appropriate_governor(is_charging: bool, cpu_usage: f32, load: f32) -> &'static str {
+/// The governor to use, with the reasons for it for the audit log
+pub fn get_appropriate_governor(
+    is_charging: bool,
+    cpu_usage: f32,
+    load: f32,
+    profile: Option<&Profile>,
+    performance_app: Option<&str>,
+) -> (&'static str, Vec<String>) {
     let state = AutoCpuFreqState::new();
     let override_val = get_override(&state);
+    let power = if is_charging { "AC" } else { "battery" };
+    let reasons = |extra: &[&str]| std::iter::once(power).chain(extra.iter().copied()).map(String::from).collect();
     
     match override_val {
-        GovernorOverride::Performance => return "performance",
-        GovernorOverride::Powersave => return "powersave",
+        GovernorOverride::Performance => return ("performance", vec!["override".into()]),
+        GovernorOverride::Powersave => return ("powersave", vec!["override".into()]),
         GovernorOverride::Default => {},
     }
+
+    if let Some(app) = performance_app {
+        if AVAILABLE_GOVERNORS_SORTED.iter().any(|g| g == "performance") {
+            return ("performance", vec![format!("{} running", app)]);
+        }
+    }
+
+    if let Some(profile) = profile {
+        if let Some(ref gov) = profile.governor {
+            match AVAILABLE_GOVERNORS_SORTED.iter().find(|&x| x == gov) {
+                Some(g) => return (g.as_str(), reasons(&[&format!("profile {}", profile.name)])),
+                None => warn!(code = %EventCode::ConfigInvalid, "Governor '{}' from the selected profile is not available", gov),
+            }
+        }
+    }
     
     if CONFIG.has_option("charger", "governor") && is_charging {
         let gov = CONFIG.get("charger", "governor", "");
         if !gov.is_empty() && AVAILABLE_GOVERNORS_SORTED.iter().any(|g| g == &gov) {
             if let Some(g) = AVAILABLE_GOVERNORS_SORTED.iter().find(|&x| x == &gov) {
-                return g.as_str();
+                return (g.as_str(), reasons(&["configured"]));
             }
         }
     }
@@ -1397,145 +2036,355 @@ fn get_appropriate_governor(is_charging: bool, cpu_usage: f32, load: f32) -> &'s
         let gov = CONFIG.get("battery", "governor", "");
         if !gov.is_empty() && AVAILABLE_GOVERNORS_SORTED.iter().any(|g| g == &gov) {
             if let Some(g) = AVAILABLE_GOVERNORS_SORTED.iter().find(|&x| x == &gov) {
-                return g.as_str();
+                return (g.as_str(), reasons(&["configured"]));
             }
         }
     }
     
     if is_charging {
-        if cpu_usage > 50.0 || load > state.performance_load_threshold {
-            if AVAILABLE_GOVERNORS_SORTED.contains(&"performance".to_string()) {
-                return "performance";
-            }
+        if (cpu_usage > 50.0 || load > state.performance_load_threshold)
+            && AVAILABLE_GOVERNORS_SORTED.contains(&"performance".to_string())
+        {
+            return ("performance", reasons(&["high load"]));
         }
         if AVAILABLE_GOVERNORS_SORTED.contains(&"schedutil".to_string()) {
-            return "schedutil";
+            return ("schedutil", reasons(&[]));
         } else if AVAILABLE_GOVERNORS_SORTED.contains(&"ondemand".to_string()) {
-            return "ondemand";
+            return ("ondemand", reasons(&[]));
         }
     } else {
-        if cpu_usage < 25.0 && load < state.powersave_load_threshold {
-            if AVAILABLE_GOVERNORS_SORTED.contains(&"powersave".to_string()) {
-                return "powersave";
-            }
+        // powersave would switch energy-aware scheduling off
+        if crate::cpu::eas::enabled() && AVAILABLE_GOVERNORS_SORTED.contains(&"schedutil".to_string()) {
+            return ("schedutil", reasons(&["energy-aware scheduling"]));
+        }
+        if cpu_usage < 25.0
+            && load < state.powersave_load_threshold
+            && AVAILABLE_GOVERNORS_SORTED.contains(&"powersave".to_string())
+        {
+            return ("powersave", reasons(&["idle"]));
         }
         if AVAILABLE_GOVERNORS_SORTED.contains(&"schedutil".to_string()) {
-            return "schedutil";
+            return ("schedutil", reasons(&[]));
         }
     }
     
-    AVAILABLE_GOVERNORS_SORTED.first()
+    let governor = AVAILABLE_GOVERNORS_SORTED.first()
         .map(|s| s.as_str())
-        .unwrap_or("schedutil")
+        .unwrap_or("schedutil");
+    (governor, reasons(&["fallback"]))
 }
 
-fn set_governor(governor: &str) -> Result<()> {
-    println!("Setting governor: {}", governor);
+pub(crate) fn set_governor(governor: &str) -> Result<()> {
+    crate::validate::governor(governor)?;
+    info!(code = %EventCode::GovernorChanged, "Setting governor: {}", governor);
     
-    let status = Command::new("cpufreqctl.auto-cpufreq")
-        .arg("--governor")
-        .arg("--set")
-        .arg(governor)
-        .status()
-        .context("Failed to set governor")?;
+    let output = crate::command::change(
+        &crate::globals::cpufreqctl_path().to_string_lossy(),
+        &["--governor", "--set", governor],
+    )
+    .context("Failed to set governor")?;
     
-    if !status.success() {
-        bail!("Governor change failed");
+    if !output.success() {
+        bail!("Governor change failed: {}", output.stderr.trim());
     }
     
     Ok(())
 }
 
-fn set_turbo_based_on_usage(cpu_usage: f32, is_charging: bool) -> Result<()> {
+/// Turbo on or off for this cycle with the reason for it, `None` to leave it as it is
+pub fn turbo_decision(
+    cpu_usage: f32,
+    avg_temp: Option<f32>,
+    is_charging: bool,
+    profile: Option<&Profile>,
+    performance_app: Option<&str>,
+) -> Option<(bool, String)> {
     let state = AutoCpuFreqState::new();
-    let turbo_override = get_turbo_override(&state);
-    
-    match turbo_override {
-        TurboOverride::Always => {
-            set_turbo(true);
-            return Ok(());
-        }
-        TurboOverride::Never => {
-            set_turbo(false);
-            return Ok(());
-        }
+    match get_turbo_override(&state) {
+        TurboOverride::Always => return Some((true, "override".into())),
+        TurboOverride::Never => return Some((false, "override".into())),
         TurboOverride::Auto => {},
     }
-    
-    if CONFIG.has_option("charger", "turbo") && is_charging {
-        let turbo_conf = CONFIG.get("charger", "turbo", "auto");
-        match turbo_conf.as_str() {
-            "always" => { set_turbo(true); return Ok(()); }
-            "never" => { set_turbo(false); return Ok(()); }
+
+    // Fan silence at night wins over load, apps and profiles
+    if crate::quiet_hours::active() {
+        return Some((false, "quiet hours".into()));
+    }
+
+    // Comfort limit: a hot chassis caps turbo even when the cores are cool
+    if crate::thermal::skin_too_hot() {
+        return Some((false, "skin temperature".into()));
+    }
+
+    if let Some(app) = performance_app {
+        return Some((true, format!("{} running", app)));
+    }
+
+    if let Some(profile) = profile {
+        match profile.turbo.as_deref() {
+            Some("always") => return Some((true, format!("profile {}", profile.name))),
+            Some("never") => return Some((false, format!("profile {}", profile.name))),
             _ => {}
         }
     }
-    
-    if CONFIG.has_option("battery", "turbo") && !is_charging {
-        let turbo_conf = CONFIG.get("battery", "turbo", "auto");
-        match turbo_conf.as_str() {
-            "always" => { set_turbo(true); return Ok(()); }
-            "never" => { set_turbo(false); return Ok(()); }
+
+    let section = if is_charging { "charger" } else { "battery" };
+    if CONFIG.has_option(section, "turbo") {
+        match TurboOverride::parse(CONFIG.get(section, "turbo", "auto").trim()) {
+            Some(TurboOverride::Always) => return Some((true, "configured".into())),
+            Some(TurboOverride::Never) => return Some((false, "configured".into())),
             _ => {}
         }
     }
-    
-    // OPTIMIZED: Use cached system and temps
-    let mut cached_sys = CACHED_SYSTEM.lock().unwrap();
-    let sys = cached_sys.get_refreshed_system();
-    
-    let temp_cache = TEMP_CACHE.lock().unwrap();
-    let cores = (0..sys.cpus().len())
-        .map(|i| temp_cache.read_core_temp(i))
-        .filter(|&t| t > 0.0)
-        .collect::<Vec<_>>();
-    
-    let avg_temp = if !cores.is_empty() {
-        cores.iter().sum::<f32>() / cores.len() as f32
-    } else {
-        0.0
-    };
-    
+
+    let avg_temp = avg_temp.unwrap_or(0.0);
     if is_charging {
-        if cpu_usage > 25.0 && avg_temp < 75.0 {
-            set_turbo(true);
-        } else if avg_temp >= 75.0 {
-            set_turbo(false);
-        }
-    } else {
-        if cpu_usage > 75.0 {
-            set_turbo(true);
+        if avg_temp >= crate::thermal::turbo_temp_limit() {
+            Some((false, "hot".into()))
+        } else if cpu_usage > 25.0 {
+            Some((true, "high load".into()))
         } else {
-            set_turbo(false);
+            None
         }
+    } else if cpu_usage > 75.0 {
+        Some((true, "high load".into()))
+    } else {
+        Some((false, "battery".into()))
     }
-    
-    Ok(())
 }
 
-pub fn set_autofreq() -> Result<()> {
+/// Average temperature of the cores that report one
+fn average_core_temp() -> Option<f32> {
+    let temp_cache = TEMP_CACHE.lock().unwrap();
+    let cores = (0..num_cpus::get())
+        .filter_map(|i| temp_cache.read_core_temp(i))
+        .collect::<Vec<_>>();
+
+    (!cores.is_empty()).then(|| cores.iter().sum::<f32>() / cores.len() as f32)
+}
+
+/// EPP of cpu0, `None` without HWP/amd-pstate EPP support
+fn current_epp() -> Option<String> {
+    fs::read_to_string(Path::new(CPU_SYSFS_DIR).join("cpu0/cpufreq/energy_performance_preference"))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn record_decision(decision: &crate::audit::Decision) {
+    if let Err(e) = crate::audit::AuditLog::default().record(decision) {
+        warn!(code = %EventCode::StateWriteFailed, "Failed to write the audit log: {}", e);
+    }
+}
+
+/// What a `set_autofreq` cycle observed and decided, used by the daemon to
+/// pace the next one and by --live to show the decisions
+#[derive(Debug, Clone)]
+pub struct CycleStats {
+    pub cpu_usage: f32,
+    pub is_charging: bool,
+    pub governor: &'static str,
+    pub governor_reasons: Vec<String>,
+    /// `None` when turbo was left as it was
+    pub turbo: Option<(bool, String)>,
+}
+
+/// What a cycle decides from its readings, before anything is written
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub inputs: crate::audit::Inputs,
+    /// Battery saver, boot boost, idle, ... or the selected profile
+    pub profile: Option<Profile>,
+    pub performance_app: Option<String>,
+    pub governor: &'static str,
+    pub governor_reasons: Vec<String>,
+    /// `None` when turbo is left as it is
+    pub turbo: Option<(bool, String)>,
+    /// EPP with the reasons for it, `None` when EPP is left as it is
+    pub epp: Option<(String, Vec<String>)>,
+}
+
+/// EPP the cycle sets, from the profile or the amd-pstate settings
+fn epp_decision(is_charging: bool, governor: &str, profile: Option<&Profile>) -> Option<(String, Vec<String>)> {
+    let (power, section) = if is_charging { ("AC", "charger") } else { ("battery", "battery") };
+    if let Some(profile) = profile.filter(|p| p.energy_performance_preference.is_some()) {
+        // The performance governor pins EPP to performance on active-mode drivers
+        if governor == "performance" || amd_pstate::get_epp().is_none() {
+            return None;
+        }
+        let epp = profile.energy_performance_preference.clone()?;
+        return Some((epp, vec![power.to_string(), format!("profile {}", profile.name)]));
+    }
+
+    if !amd_pstate::is_present() {
+        return None;
+    }
+    let epp = amd_pstate::target_epp(is_charging, governor)?;
+    let mut reasons = vec![power.to_string()];
+    if CONFIG.has_option(section, "energy_performance_preference") {
+        reasons.push("configured".to_string());
+    }
+    Some((epp, reasons))
+}
+
+/// Frequency limits of the profile applied on the last cycle
+static APPLIED_LIMITS: AppliedLimits = AppliedLimits::new();
+
+/// Battery saver, the boot boost window and a game registered with GameMode,
+/// then idle powersave, the weak charger, docked or headless settings and the
+/// battery level tiers, take precedence over the selected profile, which takes
+/// precedence over the schedule
+fn cycle_profile(is_charging: bool) -> Option<Profile> {
+    crate::battery_saver::profile()
+        .or_else(crate::boot_boost::profile)
+        .or_else(crate::gamemode::profile)
+        .or_else(|| crate::idle::profile(is_charging))
+        .or_else(|| crate::weak_charger::profile(is_charging))
+        .or_else(|| crate::docked::profile(is_charging))
+        .or_else(crate::headless::profile)
+        .or_else(|| crate::battery_levels::profile(is_charging))
+        .or_else(crate::profiles::active)
+        .or_else(crate::schedule::profile)
+}
+
+/// `cycle_profile` without logging transitions
+fn preview_profile(is_charging: bool) -> Option<Profile> {
+    crate::battery_saver::profile()
+        .or_else(crate::boot_boost::preview)
+        .or_else(crate::gamemode::preview)
+        .or_else(|| crate::idle::preview(is_charging))
+        .or_else(|| crate::weak_charger::preview(is_charging))
+        .or_else(|| crate::docked::preview(is_charging))
+        .or_else(crate::headless::preview)
+        .or_else(|| crate::battery_levels::preview(is_charging))
+        .or_else(crate::profiles::active)
+        .or_else(crate::schedule::preview)
+}
+
+fn plan(is_charging: bool, profile: Option<Profile>) -> Plan {
+    let cpu_usage = crate::cpu::usage::sample().total;
+    let load = crate::procfs::load_average().one as f32;
+    let performance_app = crate::process_watch::detect(is_charging);
+    let inputs = crate::audit::Inputs { cpu_usage, load, temperature: average_core_temp(), charging: is_charging };
+
+    let (governor, governor_reasons) =
+        get_appropriate_governor(is_charging, cpu_usage, load, profile.as_ref(), performance_app.as_deref());
+    let turbo = turbo_decision(cpu_usage, inputs.temperature, is_charging, profile.as_ref(), performance_app.as_deref());
+    let epp = epp_decision(is_charging, governor, profile.as_ref());
+
+    Plan { inputs, profile, performance_app, governor, governor_reasons, turbo, epp }
+}
+
+/// What the next daemon cycle would decide, without writing anything, for
+/// the monitor's preview
+pub fn preview_cycle() -> Result<Plan> {
     let is_charging = charging()?;
-    
-    // OPTIMIZED: Use cached system
-    let mut cached_sys = CACHED_SYSTEM.lock().unwrap();
-    let sys = cached_sys.get_refreshed_system();
-    
-    let cpu_usage: f32 = sys.cpus().iter()
-        .map(|c| c.cpu_usage())
-        .sum::<f32>() / sys.cpus().len() as f32;
-    
-    let load = System::load_average().one as f32;
-    
-    let target_governor = get_appropriate_governor(is_charging, cpu_usage, load);
-    let current_governor = get_current_gov().unwrap_or_else(|_| "unknown".to_string());
+    Ok(plan(is_charging, preview_profile(is_charging)))
+}
+
+pub fn set_autofreq() -> Result<CycleStats> {
+    let is_charging = charging()?;
+
+    if amd_pstate::is_present() {
+        amd_pstate::setup_mode();
+    }
+
+    let epp_before = current_epp();
+    let plan = plan(is_charging, cycle_profile(is_charging));
+    if let Some(ref app) = plan.performance_app {
+        info!("Performance app running: {}", app);
+    }
+    let (inputs, profile, target_governor) = (plan.inputs, plan.profile.as_ref(), plan.governor);
+
+    // cpu0 may carry a [cpu.core.N] override, so compare against a core that follows the global governor
+    let current_governor = crate::cpu::per_core::unmanaged_governor()
+        .unwrap_or_else(|| get_current_gov().unwrap_or_else(|_| "unknown".to_string()));
     
     if target_governor != current_governor {
-        set_governor(target_governor)?;
+        crate::telemetry::track("governor", set_governor(target_governor))?;
+        record_decision(&crate::audit::Decision::new("governor", target_governor, plan.governor_reasons.clone(), inputs));
     }
+
+    if let Err(e) = crate::power_helper::tuned_follow(target_governor, is_charging) {
+        warn!(code = %EventCode::TuningFailed, "Failed to switch tuned profile: {}", e);
+    }
+
+    if let Err(e) = crate::power_helper::lpmd_follow(target_governor) {
+        warn!(code = %EventCode::TuningFailed, "Failed to set intel_lpmd low-power mode: {}", e);
+    }
+
+    let profile_sets_epp = profile.is_some_and(|p| p.energy_performance_preference.is_some());
+    if amd_pstate::is_present() && !profile_sets_epp {
+        if let Err(e) = crate::telemetry::track("amd_pstate", amd_pstate::apply(is_charging, target_governor)) {
+            warn!(code = %EventCode::TuningFailed, "Failed to apply amd-pstate settings: {}", e);
+        }
+    }
+
+    if intel_pstate::is_present() {
+        if let Err(e) = crate::telemetry::track("intel_pstate", intel_pstate::apply(is_charging, inputs.cpu_usage)) {
+            warn!(code = %EventCode::TuningFailed, "Failed to apply intel_pstate settings: {}", e);
+        }
+    }
+
+    if let Err(e) = crate::telemetry::track("rapl", crate::cpu::rapl::apply(is_charging)) {
+        warn!(code = %EventCode::TuningFailed, "Failed to set RAPL power limits: {}", e);
+    }
+
+    let profile_sets_platform = profile.is_some_and(|p| p.platform_profile.is_some());
+    if !profile_sets_platform {
+        if let Err(e) = crate::telemetry::track("platform_profile", crate::cpu::platform_profile::apply(is_charging)) {
+            warn!(code = %EventCode::TuningFailed, "Failed to set platform profile: {}", e);
+        }
+    }
+
+    if let Err(e) = crate::telemetry::track("asus_thermal_policy", crate::battery::asus::apply_thermal_policy(is_charging, profile)) {
+        warn!(code = %EventCode::TuningFailed, "Failed to set ASUS thermal policy: {}", e);
+    }
+
+    // Lift what the last winner capped before the new one applies its own limits
+    if APPLIED_LIMITS.follow(profile) {
+        crate::profiles::reset_frequency_limits();
+    }
+
+    if let Some(profile) = profile {
+        if let Err(e) = crate::profiles::apply(profile, target_governor) {
+            warn!(code = %EventCode::TuningFailed, "Failed to apply profile '{}': {}", profile.name, e);
+        }
+    }
+
+    if let Err(e) = crate::telemetry::track("per_core", crate::cpu::per_core::apply_per_core_settings()) {
+        warn!(code = %EventCode::TuningFailed, "Failed to apply per-core settings: {}", e);
+    }
+
+    crate::quiet_hours::apply();
     
-    set_turbo_based_on_usage(cpu_usage, is_charging)?;
-    
-    Ok(())
+    if let Some((on, reason)) = &plan.turbo {
+        let was_on = turbo(None).ok();
+        set_turbo(*on);
+        if was_on.is_some_and(|was_on| was_on != *on) {
+            let value = if *on { "on" } else { "off" };
+            record_decision(&crate::audit::Decision::new("turbo", value, vec![reason.clone()], inputs));
+        }
+    }
+
+    if let Err(e) = crate::telemetry::track("epb", set_epb_based_on_power(is_charging)) {
+        warn!(code = %EventCode::TuningFailed, "Failed to set EPB: {}", e);
+    }
+
+    if let (Some(before), Some(epp)) = (epp_before, current_epp()) {
+        if before != epp {
+            let power = if is_charging { "AC" } else { "battery" };
+            let reasons = plan.epp.map(|(_, reasons)| reasons).unwrap_or_else(|| vec![power.to_string()]);
+            record_decision(&crate::audit::Decision::new("epp", &epp, reasons, inputs));
+        }
+    }
+
+    Ok(CycleStats {
+        cpu_usage: inputs.cpu_usage,
+        is_charging,
+        governor: target_governor,
+        governor_reasons: plan.governor_reasons,
+        turbo: plan.turbo,
+    })
 }
 
 #[cfg(test)]
@@ -1549,6 +2398,133 @@ mod tests {
         assert_eq!(GovernorOverride::from_str("invalid"), GovernorOverride::Default);
     }
     
+    #[test]
+    fn test_install_and_remove_plans() {
+        let install = install_plan("systemd").unwrap();
+        assert!(install.iter().any(|s| matches!(s, InstallStep::WriteFile { path, .. } if path == Path::new(SYSTEMD_UNIT))));
+        assert!(install.contains(&InstallStep::run("systemctl", &["enable", "auto-cpufreq"])));
+
+        let remove = remove_plan("openrc").unwrap();
+        assert!(remove.contains(&InstallStep::RemoveFile(OPENRC_SCRIPT.into())));
+        assert_eq!(
+            InstallStep::try_run("rc-update", &["del", "auto-cpufreq"]).describe().as_deref(),
+            Some("run     rc-update del auto-cpufreq")
+        );
+        assert_eq!(dry_run_section(&InstallStep::run("systemctl", &["mask", "tuned"])), Some("Conflicting services"));
+        assert_eq!(dry_run_section(&InstallStep::run("systemctl", &["enable", "auto-cpufreq"])), Some("Service commands"));
+        let unit = std::env::temp_dir().join(format!("acf-dry-run-{}.service", std::process::id()));
+        fs::write(&unit, "[Unit]\n").unwrap();
+        assert_eq!(
+            InstallStep::write(&unit, "[Unit]\n".into(), false).describe(),
+            Some(format!("write   {} (unchanged)", unit.display()))
+        );
+        fs::remove_file(&unit).unwrap();
+        // Only files that exist are listed
+        assert!(purge_plan(&Snapshot::default()).iter().all(|step| match step {
+            InstallStep::RemoveFile(path) | InstallStep::RemoveDir(path) => path.exists(),
+            step => matches!(step, InstallStep::Note(_)),
+        }));
+
+        assert!(install_plan("unknown").is_err());
+        assert!(validate_init_system("upstart").is_err());
+
+        let status = DaemonStatus {
+            init: "systemd",
+            state: ServiceState::Failed,
+            stats_age: Some(600),
+            stale: false,
+            last_error: None,
+        };
+        assert!(!status.is_healthy());
+        assert_eq!(status.remediation().len(), 2);
+    }
+
+    #[test]
+    fn test_scripts_fall_back_to_embedded() {
+        let dir = std::env::temp_dir().join(format!("acf-scripts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let embedded = "[Service]\nExecStart=/usr/local/bin/auto-cpufreq --daemon\n";
+        let expected = crate::globals::relocate(embedded, &crate::globals::PREFIX);
+        assert_eq!(read_script(&dir, "auto-cpufreq.service", embedded), expected);
+
+        // A copy installed next to the binary wins
+        fs::write(dir.join("auto-cpufreq.service"), "[Service]\nNice=5\n").unwrap();
+        assert_eq!(read_script(&dir, "auto-cpufreq.service", embedded), "[Service]\nNice=5\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_unit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("acf-render-{}", std::process::id()));
+        let binary = dir.join("bin/auto-cpufreq");
+        fs::create_dir_all(binary.parent().unwrap()).unwrap();
+        fs::write(&binary, "").unwrap();
+        let openrc = include_str!("../../scripts/auto-cpufreq-openrc");
+
+        // Present but not executable
+        assert!(render_unit("auto-cpufreq-openrc", openrc, &binary).is_err());
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        let unit = render_unit("auto-cpufreq-openrc", openrc, &binary).unwrap();
+        assert!(unit.contains(&format!("command=\"{}\"", binary.display())));
+
+        assert!(render_unit("auto-cpufreq-openrc", openrc, Path::new("bin/auto-cpufreq")).is_err());
+        assert!(render_unit("auto-cpufreq-openrc", openrc, &dir.join("missing")).is_err());
+        assert!(render_unit("auto-cpufreq.service", "[Service]\n", &binary).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_step_undo() {
+        let dir = std::env::temp_dir().join(format!("install_step_test_{}", std::process::id()));
+        let file = dir.join("auto-cpufreq.service");
+
+        let undo = InstallStep::CreateDir(dir.clone()).execute().unwrap();
+        assert_eq!(undo, vec![InstallStep::RemoveDir(dir.clone())]);
+
+        let write = InstallStep::write(&file, "[Unit]\n".into(), false);
+        assert_eq!(write.execute().unwrap(), vec![InstallStep::RemoveFile(file.clone())]);
+        // Already deployed: nothing to do, nothing to undo
+        assert!(write.execute().unwrap().is_empty());
+
+        let manifest = InstallManifest { init: "systemd".into(), complete: false, undo };
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(serde_json::from_str::<InstallManifest>(&json).unwrap(), manifest);
+
+        assert_eq!(
+            undo_command("systemctl", &["enable".into(), "auto-cpufreq".into()]),
+            Some(InstallStep::try_run("systemctl", &["disable", "auto-cpufreq"]))
+        );
+        assert_eq!(undo_command("systemctl", &["daemon-reload".into()]), None);
+        assert_eq!(
+            undo_command("systemctl", &["mask".into(), "tlp".into()]),
+            Some(InstallStep::try_run("systemctl", &["unmask", "tlp"]))
+        );
+        assert_eq!(
+            undo_command("systemctl", &["stop".into(), "power-profiles-daemon".into()]),
+            Some(InstallStep::try_run("systemctl", &["start", "power-profiles-daemon"]))
+        );
+        assert_eq!(undo_command("systemctl", &["stop".into(), "auto-cpufreq".into()]), None);
+        assert!(restores_conflicting_service(&InstallStep::try_run("systemctl", &["unmask", "tuned"])));
+        assert!(!restores_conflicting_service(&InstallStep::try_run("systemctl", &["disable", "auto-cpufreq"])));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_override_ttl() {
+        assert_eq!(parse_override_ttl("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_override_ttl("45").unwrap(), Duration::from_secs(2700));
+        assert_eq!(parse_override_ttl("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_override_ttl("90s").unwrap(), Duration::from_secs(90));
+        assert!(parse_override_ttl("0m").is_err());
+        assert!(parse_override_ttl("1d").is_err());
+        assert!(parse_override_ttl("99999999999999999999h").is_err());
+        assert!(parse_override_ttl("18446744073709551615h").is_err());
+        assert!(parse_override_ttl("soon").is_err());
+    }
+
     #[test]
     fn test_turbo_override() {
         assert_eq!(TurboOverride::from_str("never"), TurboOverride::Never);
@@ -1556,10 +2532,76 @@ mod tests {
         assert_eq!(TurboOverride::from_str("auto"), TurboOverride::Auto);
     }
 
+    #[test]
+    fn test_parse_epb() {
+        assert_eq!(parse_epb("performance"), Some(0));
+        assert_eq!(parse_epb("balance_power"), Some(8));
+        assert_eq!(parse_epb(" 7 "), Some(7));
+        assert_eq!(parse_epb("16"), None);
+        assert_eq!(parse_epb("turbo"), None);
+        assert_eq!(epb_name(4), "balance_performance");
+        assert_eq!(epb_name(7), "7");
+    }
+
     #[test]
     fn test_temp_cache() {
         let cache = TempSensorCache::new();
-        let temp = cache.read_core_temp(0);
-        assert!(temp >= 0.0);
+        assert!(cache.read_core_temp(0).is_none_or(|t| t > 0.0));
+    }
+
+    mod props {
+        use super::*;
+        use crate::config::Snapshot;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn overrides_round_trip(governor in prop::sample::select(vec!["powersave", "performance"]),
+                                    turbo in prop::sample::select(vec!["always", "never", "auto"])) {
+                let parsed = GovernorOverride::parse(governor).unwrap();
+                prop_assert_eq!(parsed.to_str(), governor);
+                prop_assert_eq!(GovernorOverride::from_str(parsed.to_str()), parsed);
+
+                let parsed = TurboOverride::parse(turbo).unwrap();
+                prop_assert_eq!(parsed.to_str(), turbo);
+                prop_assert_eq!(TurboOverride::from_str(parsed.to_str()), parsed);
+            }
+
+            #[test]
+            fn unknown_overrides_are_rejected(value in "\\PC{0,12}") {
+                prop_assume!(!["powersave", "performance", "reset", "always", "never", "auto"].contains(&value.as_str()));
+                prop_assert_eq!(GovernorOverride::parse(&value), None);
+                prop_assert_eq!(TurboOverride::parse(&value), None);
+                // Stored state that no longer parses falls back to automatic
+                prop_assert_eq!(GovernorOverride::from_str(&value), GovernorOverride::Default);
+                prop_assert_eq!(TurboOverride::from_str(&value), TurboOverride::Auto);
+            }
+
+            #[test]
+            fn config_turbo_matches_cli(value in "[a-zA-Z]{1,8}") {
+                // `turbo` in a profile section is accepted exactly when --turbo would accept it
+                let config = Snapshot::from_str_ini(&format!("[profile.test]\nturbo = {}\n", value)).unwrap();
+                let profile = crate::profiles::load(&config, "test");
+                prop_assert_eq!(profile.is_ok(), TurboOverride::parse(&value).is_some());
+                if let Ok(profile) = profile {
+                    prop_assert_eq!(profile.turbo.as_deref(), Some(value.as_str()));
+                }
+            }
+
+            #[test]
+            fn override_ttl_parses_or_errors(n in 0u64..100_000, unit in prop::sample::select(vec!["", "s", "m", "min", "h", "d", "x"])) {
+                let result = parse_override_ttl(&format!("{}{}", n, unit));
+                let secs = match unit {
+                    "s" => Some(n),
+                    "" | "m" | "min" => Some(n * 60),
+                    "h" => Some(n * 3600),
+                    _ => None,
+                };
+                match secs.filter(|s| *s > 0) {
+                    Some(secs) => prop_assert_eq!(result.unwrap(), Duration::from_secs(secs)),
+                    None => prop_assert!(result.is_err()),
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,218 @@
+// src/cpu/amd_pstate.rs
+//
+// amd-pstate / amd-pstate-epp backend. In active (EPP) mode the governor is only
+// a hint (performance/powersave), power policy is expressed through
+// energy_performance_preference and boost lives in per-policy `boost` files on
+// kernels that don't expose the global cpufreq/boost knob.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use anyhow::{Result, bail, Context};
+use tracing::{info, warn};
+
+use crate::config::CONFIG;
+use crate::core::cpu_sysfs_dirs;
+use crate::event_code::EventCode;
+
+const STATUS_PATH: &str = "/sys/devices/system/cpu/amd_pstate/status";
+const GLOBAL_BOOST_PATH: &str = "/sys/devices/system/cpu/cpufreq/boost";
+const CPUFREQ_DIR: &str = "/sys/devices/system/cpu/cpufreq";
+
+static MODE_SETUP: Once = Once::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmdPstateMode {
+    Active,
+    Passive,
+    Guided,
+    Disable,
+}
+
+impl AmdPstateMode {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim() {
+            "active" => Some(Self::Active),
+            "passive" => Some(Self::Passive),
+            "guided" => Some(Self::Guided),
+            "disable" => Some(Self::Disable),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Passive => "passive",
+            Self::Guided => "guided",
+            Self::Disable => "disable",
+        }
+    }
+}
+
+pub fn is_present() -> bool {
+    Path::new(STATUS_PATH).exists()
+}
+
+pub fn mode() -> Option<AmdPstateMode> {
+    fs::read_to_string(STATUS_PATH)
+        .ok()
+        .and_then(|s| AmdPstateMode::from_str(&s))
+}
+
+pub fn set_mode(mode: AmdPstateMode) -> Result<()> {
+    info!("Setting amd-pstate mode: {}", mode.as_str());
+    fs::write(STATUS_PATH, mode.as_str())
+        .with_context(|| format!("Failed to write {}", STATUS_PATH))
+}
+
+fn epp_path(cpu_dir: &Path) -> PathBuf {
+    cpu_dir.join("cpufreq/energy_performance_preference")
+}
+
+pub fn available_epp() -> Vec<String> {
+    let path = Path::new(CPUFREQ_DIR).join("policy0/energy_performance_available_preferences");
+    fs::read_to_string(path)
+        .map(|s| s.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Current EPP (read from cpu0)
+pub fn get_epp() -> Option<String> {
+    cpu_sysfs_dirs()
+        .first()
+        .and_then(|dir| fs::read_to_string(epp_path(dir)).ok())
+        .map(|s| s.trim().to_string())
+}
+
+pub fn set_epp(value: &str) -> Result<()> {
+    let available = available_epp();
+    if !available.is_empty() && !available.iter().any(|v| v == value) {
+        bail!("Invalid EPP value: {} (available: {})", value, available.join(", "));
+    }
+
+    info!("Setting EPP: {}", value);
+
+    for cpu_dir in cpu_sysfs_dirs() {
+        let path = epp_path(&cpu_dir);
+        if path.exists() {
+            fs::write(&path, value)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Boost control files: the global knob when present (passive/guided), otherwise
+/// the per-policy files active mode exposes since Linux 6.11
+fn boost_paths() -> Vec<PathBuf> {
+    let global = PathBuf::from(GLOBAL_BOOST_PATH);
+    if global.exists() {
+        return vec![global];
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(CPUFREQ_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.file_name().to_string_lossy().starts_with("policy"))
+                .map(|e| e.path().join("boost"))
+                .filter(|p| p.exists())
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+pub fn boost_supported() -> bool {
+    !boost_paths().is_empty()
+}
+
+pub fn get_boost() -> Option<bool> {
+    boost_paths()
+        .first()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| s.trim().parse::<u8>().ok())
+        .map(|v| v != 0)
+}
+
+pub fn set_boost(on: bool) -> Result<()> {
+    let paths = boost_paths();
+    if paths.is_empty() {
+        bail!("Boost control is not available with amd-pstate on this kernel");
+    }
+
+    for path in paths {
+        fs::write(&path, if on { "1" } else { "0" })
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Switch to `[platform] amd_pstate_mode` once, before the first governor decision,
+/// so the available governors read at startup match the running mode
+pub fn setup_mode() {
+    MODE_SETUP.call_once(|| {
+        let value = CONFIG.get("platform", "amd_pstate_mode", "");
+        if value.is_empty() {
+            return;
+        }
+
+        match AmdPstateMode::from_str(&value) {
+            Some(target) if mode() != Some(target) => {
+                if let Err(e) = set_mode(target) {
+                    warn!(code = %EventCode::TuningFailed, "Failed to set amd-pstate mode: {}", e);
+                }
+            }
+            Some(_) => {}
+            None => warn!(code = %EventCode::ConfigInvalid, "Invalid amd_pstate_mode in [platform]: {}", value),
+        }
+    });
+}
+
+/// The EPP `apply` sets, `None` outside active mode or with the performance
+/// governor, which pins EPP to performance in active mode
+pub fn target_epp(is_charging: bool, governor: &str) -> Option<String> {
+    if mode() != Some(AmdPstateMode::Active) || governor == "performance" {
+        return None;
+    }
+
+    let (section, fallback) = if is_charging {
+        ("charger", "balance_performance")
+    } else {
+        ("battery", "power")
+    };
+    Some(CONFIG.get(section, "energy_performance_preference", fallback))
+}
+
+/// Called by `set_autofreq` on AMD systems. `governor` is the governor that was just
+/// picked.
+pub fn apply(is_charging: bool, governor: &str) -> Result<()> {
+    let Some(target) = target_epp(is_charging, governor) else {
+        return Ok(());
+    };
+
+    if get_epp().as_deref() != Some(target.as_str()) {
+        set_epp(&target)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_from_str() {
+        assert_eq!(AmdPstateMode::from_str("active\n"), Some(AmdPstateMode::Active));
+        assert_eq!(AmdPstateMode::from_str("guided"), Some(AmdPstateMode::Guided));
+        assert_eq!(AmdPstateMode::from_str("epp"), None);
+        assert_eq!(AmdPstateMode::Passive.as_str(), "passive");
+    }
+}
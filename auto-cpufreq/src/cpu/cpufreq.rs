@@ -0,0 +1,74 @@
+// src/cpu/cpufreq.rs
+//
+// Whether the kernel offers CPU frequency scaling at all. Some virtual
+// machines and unusual kernels have no cpufreq driver, so
+// /sys/devices/system/cpu/cpu0/cpufreq is missing and governor, turbo and
+// frequency changes have nothing to act on. The binary checks this at
+// startup: commands that change the CPU fail with the reason, the monitor
+// still reports what it can read, and the daemon keeps only battery
+// thresholds and the stats going.
+
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+
+/// cpu0 has a cpufreq policy with a governor under `cpu_dir`
+fn supported_in(cpu_dir: &Path) -> bool {
+    cpu_dir.join("cpu0/cpufreq/scaling_governor").exists()
+}
+
+pub fn supported() -> bool {
+    supported_in(Path::new(CPU_DIR))
+}
+
+/// Hypervisor from `systemd-detect-virt`, `None` on bare metal or without it
+fn virtualization() -> Option<String> {
+    crate::command::output("systemd-detect-virt", &["--vm"])
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && v != "none")
+}
+
+fn describe(virtualization: Option<&str>) -> String {
+    let cause = match virtualization {
+        Some(vm) => format!("running in a {} virtual machine, which doesn't pass it through", vm),
+        None => "no cpufreq driver is loaded for this CPU".to_string(),
+    };
+    format!("CPU frequency scaling isn't available: {}/cpu0/cpufreq is missing ({})", CPU_DIR, cause)
+}
+
+/// Why scaling isn't available, for errors and warnings
+pub fn missing_reason() -> String {
+    describe(virtualization().as_deref())
+}
+
+/// Fail with the reason when frequency scaling isn't available
+pub fn ensure_supported() -> Result<()> {
+    if !supported() {
+        bail!("{}", missing_reason());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_cpufreq_support() {
+        let dir = std::env::temp_dir().join(format!("acf-cpufreq-{}", std::process::id()));
+        fs::create_dir_all(dir.join("cpu0")).unwrap();
+        assert!(!supported_in(&dir));
+        fs::create_dir_all(dir.join("cpu0/cpufreq")).unwrap();
+        fs::write(dir.join("cpu0/cpufreq/scaling_governor"), "powersave\n").unwrap();
+        assert!(supported_in(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(describe(Some("kvm")).contains("running in a kvm virtual machine"));
+        assert!(describe(None).ends_with("(no cpufreq driver is loaded for this CPU)"));
+    }
+}
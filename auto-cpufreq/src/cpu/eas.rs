@@ -0,0 +1,152 @@
+// src/cpu/eas.rs
+//
+// Energy-aware scheduling: on machines with big and little cores (ARM
+// big.LITTLE, Intel hybrid with a recent kernel) the scheduler places tasks by
+// an energy model, but only while schedutil is the governor. EAS is on when the
+// `kernel.sched_energy_aware` sysctl reads 1; the kernel only offers that
+// sysctl (5.0+) when the CPU capacities differ. Fixed governors such as
+// powersave or ondemand switch it off, so on these machines the decision engine
+// and the monitor suggestions stay with schedutil on battery.
+
+use std::fs;
+use std::path::Path;
+
+const SCHED_ENERGY_AWARE: &str = "/proc/sys/kernel/sched_energy_aware";
+/// Present when the kernel has utilization clamping (uclamp, 5.3+)
+const UCLAMP_MIN: &str = "/proc/sys/kernel/sched_util_clamp_min";
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+/// First kernel with EAS and its sysctl
+const MIN_KERNEL: (u32, u32) = (5, 0);
+
+/// Major and minor of a `uname -r` release such as "6.8.0-45-generic"
+pub fn parse_kernel_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.trim().split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Whether the `cpu*/cpu_capacity` values under `dir` differ
+fn asymmetric_in(dir: &Path) -> bool {
+    let mut capacities: Vec<u32> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| fs::read_to_string(e.path().join("cpu_capacity")).ok())
+                .filter_map(|c| c.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    capacities.sort_unstable();
+    capacities.dedup();
+    capacities.len() > 1
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Eas {
+    pub kernel: Option<(u32, u32)>,
+    /// `sched_energy_aware`, `None` when the kernel doesn't offer it
+    pub sysctl: Option<bool>,
+    /// Cores of different capacity
+    pub asymmetric: bool,
+    pub uclamp: bool,
+}
+
+impl Eas {
+    pub fn detect() -> Self {
+        let release = crate::command::run("uname", &["-r"]).map(|o| o.stdout).unwrap_or_default();
+        Self {
+            kernel: parse_kernel_version(&release),
+            sysctl: fs::read_to_string(SCHED_ENERGY_AWARE).ok().map(|v| v.trim() == "1"),
+            asymmetric: asymmetric_in(Path::new(CPU_DIR)),
+            uclamp: Path::new(UCLAMP_MIN).exists(),
+        }
+    }
+
+    /// The kernel can do EAS on this machine
+    pub fn available(&self) -> bool {
+        self.sysctl.is_some() && self.asymmetric && self.kernel.is_none_or(|k| k >= MIN_KERNEL)
+    }
+
+    /// EAS is switched on, and works whenever schedutil is the governor
+    pub fn enabled(&self) -> bool {
+        self.available() && self.sysctl == Some(true)
+    }
+
+    /// For the monitor: `None` when EAS isn't available
+    pub fn status(&self, governor: Option<&str>) -> Option<String> {
+        if !self.available() {
+            return None;
+        }
+        let status = match (self.enabled(), governor) {
+            (false, _) => "off (sched_energy_aware = 0)",
+            (true, Some("schedutil")) => "active",
+            (true, _) => "inactive, needs schedutil",
+        };
+        Some(format!("{}{}", status, if self.uclamp { ", uclamp" } else { "" }))
+    }
+}
+
+lazy_static::lazy_static! {
+    // Kernel and core layout don't change while running, only the sysctl does
+    static ref STATIC: Eas = Eas::detect();
+}
+
+/// EAS is on, read fresh every call for the sysctl
+pub fn enabled() -> bool {
+    Eas { sysctl: fs::read_to_string(SCHED_ENERGY_AWARE).ok().map(|v| v.trim() == "1"), ..*STATIC }.enabled()
+}
+
+/// Governor to suggest in the monitor. With EAS on, schedutil on battery
+/// instead of powersave, and never ondemand/conservative, which bypass it.
+pub fn suggest_governor(available: &[String], on_ac: bool, eas: bool) -> Option<String> {
+    let has = |gov: &str| available.iter().any(|g| g == gov);
+    if eas && has("schedutil") {
+        if on_ac && has("performance") {
+            return Some("performance".to_string());
+        }
+        return Some("schedutil".to_string());
+    }
+
+    if on_ac {
+        available.first().cloned()
+    } else {
+        available.last().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eas_detection_and_suggestions() {
+        assert_eq!(parse_kernel_version("6.8.0-45-generic"), Some((6, 8)));
+        assert_eq!(parse_kernel_version("5.15-rc1"), Some((5, 15)));
+        assert_eq!(parse_kernel_version("linux"), None);
+
+        let dir = std::env::temp_dir().join(format!("eas_test_{}", std::process::id()));
+        for (cpu, capacity) in [("cpu0", "1024"), ("cpu1", "1024"), ("cpu2", "446")] {
+            fs::create_dir_all(dir.join(cpu)).unwrap();
+            fs::write(dir.join(cpu).join("cpu_capacity"), capacity).unwrap();
+        }
+        assert!(asymmetric_in(&dir));
+        fs::write(dir.join("cpu2").join("cpu_capacity"), "1024").unwrap();
+        assert!(!asymmetric_in(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let eas = Eas { kernel: Some((6, 8)), sysctl: Some(true), asymmetric: true, uclamp: true };
+        assert!(eas.enabled());
+        assert_eq!(eas.status(Some("schedutil")).as_deref(), Some("active, uclamp"));
+        assert_eq!(eas.status(Some("powersave")).as_deref(), Some("inactive, needs schedutil, uclamp"));
+        assert!(!Eas { kernel: Some((4, 19)), ..eas }.available());
+        assert!(!Eas { sysctl: Some(false), ..eas }.enabled());
+        assert_eq!(Eas::default().status(None), None);
+
+        let governors: Vec<String> = ["ondemand", "conservative", "schedutil", "powersave"].map(String::from).to_vec();
+        assert_eq!(suggest_governor(&governors, true, false).as_deref(), Some("ondemand"));
+        assert_eq!(suggest_governor(&governors, true, true).as_deref(), Some("schedutil"));
+        assert_eq!(suggest_governor(&governors, false, true).as_deref(), Some("schedutil"));
+        assert_eq!(suggest_governor(&governors, false, false).as_deref(), Some("powersave"));
+    }
+}
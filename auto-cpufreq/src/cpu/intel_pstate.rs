@@ -0,0 +1,229 @@
+// src/cpu/intel_pstate.rs
+//
+// intel_pstate global knobs: HWP dynamic boost and the min/max performance
+// percentages. Turbo (no_turbo) keeps going through `core::turbo`.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, bail, Context};
+use serde::Serialize;
+use tracing::info;
+
+use crate::config::{CONFIG, Snapshot};
+
+const INTEL_PSTATE_DIR: &str = "/sys/devices/system/cpu/intel_pstate";
+
+/// CPU usage (%) below which `*_low_load` values apply
+pub const LOW_LOAD_USAGE: f32 = 25.0;
+/// CPU usage (%) above which `*_high_load` values apply
+pub const HIGH_LOAD_USAGE: f32 = 50.0;
+
+/// Current intel_pstate state, as shown by `--stats` and the GUI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntelPstateInfo {
+    pub status: Option<String>,
+    pub hwp_dynamic_boost: Option<bool>,
+    pub min_perf_pct: Option<u8>,
+    pub max_perf_pct: Option<u8>,
+}
+
+/// What the config asks for in the current situation; `None` leaves a knob alone
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerfPolicy {
+    pub hwp_dynamic_boost: Option<bool>,
+    pub min_perf_pct: Option<u8>,
+    pub max_perf_pct: Option<u8>,
+}
+
+fn knob(name: &str) -> std::path::PathBuf {
+    Path::new(INTEL_PSTATE_DIR).join(name)
+}
+
+fn read_knob(name: &str) -> Option<String> {
+    fs::read_to_string(knob(name)).ok().map(|s| s.trim().to_string())
+}
+
+fn write_knob(name: &str, value: &str) -> Result<()> {
+    let path = knob(name);
+    fs::write(&path, value).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn is_present() -> bool {
+    Path::new(INTEL_PSTATE_DIR).exists()
+}
+
+pub fn status() -> Option<String> {
+    read_knob("status")
+}
+
+/// `None` when HWP isn't enabled (the file only exists with HWP)
+pub fn hwp_dynamic_boost() -> Option<bool> {
+    read_knob("hwp_dynamic_boost").map(|v| v == "1")
+}
+
+pub fn set_hwp_dynamic_boost(on: bool) -> Result<()> {
+    if !knob("hwp_dynamic_boost").exists() {
+        bail!("hwp_dynamic_boost is not available (HWP disabled or unsupported)");
+    }
+    info!("Setting HWP dynamic boost: {}", if on { "on" } else { "off" });
+    write_knob("hwp_dynamic_boost", if on { "1" } else { "0" })
+}
+
+pub fn min_perf_pct() -> Option<u8> {
+    read_knob("min_perf_pct").and_then(|v| v.parse().ok())
+}
+
+pub fn max_perf_pct() -> Option<u8> {
+    read_knob("max_perf_pct").and_then(|v| v.parse().ok())
+}
+
+/// Write the performance limits, ordering the writes so min never exceeds max
+pub fn set_perf_pct(min: Option<u8>, max: Option<u8>) -> Result<()> {
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            bail!("min_perf_pct ({}) is above max_perf_pct ({})", min, max);
+        }
+    }
+
+    let write = |name: &str, value: u8, current: Option<u8>| -> Result<()> {
+        if current != Some(value) {
+            info!("Setting {}: {}%", name, value);
+            write_knob(name, &value.to_string())?;
+        }
+        Ok(())
+    };
+
+    let current_max = max_perf_pct();
+    if min.is_some() && min > current_max {
+        if let Some(max) = max {
+            write("max_perf_pct", max, current_max)?;
+        }
+        if let Some(min) = min {
+            write("min_perf_pct", min, min_perf_pct())?;
+        }
+    } else {
+        if let Some(min) = min {
+            write("min_perf_pct", min, min_perf_pct())?;
+        }
+        if let Some(max) = max {
+            write("max_perf_pct", max, max_perf_pct())?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn info() -> Option<IntelPstateInfo> {
+    if !is_present() {
+        return None;
+    }
+
+    Some(IntelPstateInfo {
+        status: status(),
+        hwp_dynamic_boost: hwp_dynamic_boost(),
+        min_perf_pct: min_perf_pct(),
+        max_perf_pct: max_perf_pct(),
+    })
+}
+
+fn parse_pct(config: &Snapshot, section: &str, key: &str) -> Result<Option<u8>> {
+    match config.get(section, key) {
+        Some(value) => match value.trim().parse::<u8>() {
+            Ok(pct) if pct <= 100 => Ok(Some(pct)),
+            _ => bail!("Invalid {} in [{}]: {} (expected 0-100)", key, section, value),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Pick the policy for the power source and load. `min_perf_pct`/`max_perf_pct`
+/// are the baseline, `*_low_load` and `*_high_load` override them while CPU usage
+/// is below `LOW_LOAD_USAGE` or above `HIGH_LOAD_USAGE`.
+pub fn policy_for(config: &Snapshot, is_charging: bool, cpu_usage: f32) -> Result<PerfPolicy> {
+    let section = if is_charging { "charger" } else { "battery" };
+
+    let hwp_dynamic_boost = match config.get(section, "hwp_dynamic_boost") {
+        Some("true") | Some("1") | Some("yes") => Some(true),
+        Some("false") | Some("0") | Some("no") => Some(false),
+        Some(v) => bail!("Invalid hwp_dynamic_boost in [{}]: {}", section, v),
+        None => None,
+    };
+
+    let suffix = if cpu_usage < LOW_LOAD_USAGE {
+        Some("_low_load")
+    } else if cpu_usage > HIGH_LOAD_USAGE {
+        Some("_high_load")
+    } else {
+        None
+    };
+
+    let pct = |key: &str| -> Result<Option<u8>> {
+        if let Some(suffix) = suffix {
+            if let Some(v) = parse_pct(config, section, &format!("{}{}", key, suffix))? {
+                return Ok(Some(v));
+            }
+        }
+        parse_pct(config, section, key)
+    };
+
+    Ok(PerfPolicy {
+        hwp_dynamic_boost,
+        min_perf_pct: pct("min_perf_pct")?,
+        max_perf_pct: pct("max_perf_pct")?,
+    })
+}
+
+/// Called by `set_autofreq` on systems running intel_pstate
+pub fn apply(is_charging: bool, cpu_usage: f32) -> Result<()> {
+    // In passive mode intel_cpufreq owns the limits through the cpufreq policy
+    if status().as_deref() != Some("active") {
+        return Ok(());
+    }
+
+    let policy = policy_for(&CONFIG.snapshot(), is_charging, cpu_usage)?;
+
+    if let Some(boost) = policy.hwp_dynamic_boost {
+        if hwp_dynamic_boost().is_some_and(|current| current != boost) {
+            set_hwp_dynamic_boost(boost)?;
+        }
+    }
+
+    if policy.min_perf_pct.is_some() || policy.max_perf_pct.is_some() {
+        set_perf_pct(policy.min_perf_pct, policy.max_perf_pct)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_for() {
+        let config = Snapshot::from_str_ini(
+            "[battery]\nhwp_dynamic_boost = false\nmax_perf_pct = 80\nmax_perf_pct_low_load = 50\n\
+             [charger]\nhwp_dynamic_boost = true\nmin_perf_pct_high_load = 60\n",
+        )
+        .unwrap();
+
+        let idle = policy_for(&config, false, 5.0).unwrap();
+        assert_eq!(idle.hwp_dynamic_boost, Some(false));
+        assert_eq!(idle.max_perf_pct, Some(50));
+
+        let busy = policy_for(&config, false, 90.0).unwrap();
+        assert_eq!(busy.max_perf_pct, Some(80));
+
+        let plugged = policy_for(&config, true, 90.0).unwrap();
+        assert_eq!(plugged.hwp_dynamic_boost, Some(true));
+        assert_eq!(plugged.min_perf_pct, Some(60));
+        assert_eq!(plugged.max_perf_pct, None);
+    }
+
+    #[test]
+    fn test_policy_rejects_out_of_range() {
+        let config = Snapshot::from_str_ini("[battery]\nmax_perf_pct = 120\n").unwrap();
+        assert!(policy_for(&config, false, 30.0).is_err());
+    }
+}
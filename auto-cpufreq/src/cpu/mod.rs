@@ -0,0 +1,9 @@
+// src/cpu/mod.rs
+pub mod amd_pstate;
+pub mod cpufreq;
+pub mod eas;
+pub mod intel_pstate;
+pub mod per_core;
+pub mod platform_profile;
+pub mod rapl;
+pub mod usage;
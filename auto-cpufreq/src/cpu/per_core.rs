@@ -0,0 +1,468 @@
+// src/cpu/per_core.rs
+//
+// Per-core governor, frequency limits and core parking, configured with
+// `[cpu.core.N]` sections. N can be a single CPU ("3"), a range ("4-7") or a
+// comma separated list ("0,2,4"). Cores without a section follow the global
+// governor picked by `set_autofreq`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Result, bail, Context};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, Snapshot, parse_bool};
+use crate::event_code::EventCode;
+use crate::globals::AVAILABLE_GOVERNORS_SORTED;
+use crate::state::StateStore;
+
+const CPU_SYSFS_DIR: &str = "/sys/devices/system/cpu";
+const SECTION_PREFIX: &str = "cpu.core.";
+/// Largest NR_CPUS the kernel can be built with
+const MAX_CORES: usize = 8192;
+
+/// Whether a limit outside the hardware range was reported already
+static CLAMP_WARNED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoreSettings {
+    pub governor: Option<String>,
+    /// kHz, same unit as scaling_min_freq in sysfs
+    pub scaling_min_freq: Option<u32>,
+    /// kHz, same unit as scaling_max_freq in sysfs
+    pub scaling_max_freq: Option<u32>,
+    /// `false` parks (offlines) the core
+    pub online: Option<bool>,
+}
+
+impl CoreSettings {
+    fn merge(&mut self, other: &CoreSettings) {
+        if other.governor.is_some() {
+            self.governor = other.governor.clone();
+        }
+        if other.scaling_min_freq.is_some() {
+            self.scaling_min_freq = other.scaling_min_freq;
+        }
+        if other.scaling_max_freq.is_some() {
+            self.scaling_max_freq = other.scaling_max_freq;
+        }
+        if other.online.is_some() {
+            self.online = other.online;
+        }
+    }
+}
+
+/// Highest CPU number a core list may name: the kernel's `kernel_max`, so a
+/// config shared with a bigger machine still parses, or the NR_CPUS ceiling
+pub fn highest_core() -> usize {
+    read_sysfs(&Path::new(CPU_SYSFS_DIR).join("kernel_max"))
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(MAX_CORES - 1)
+}
+
+/// Parse the N part of a `[cpu.core.N]` section: "3", "4-7" or "0,2,4". CPUs
+/// above `highest` are refused before a range is expanded, so a typo can't
+/// make it allocate billions of entries.
+pub fn parse_core_spec(spec: &str, highest: usize) -> Result<Vec<usize>> {
+    let mut cores = Vec::new();
+    let parse = |number: &str| {
+        let core = number
+            .trim()
+            .parse::<usize>()
+            .with_context(|| format!("'{}' is not a core number, range (4-7) or list (0,2,4)", spec))?;
+        if core > highest {
+            bail!("CPU{} in '{}' does not exist, the highest is CPU{}", core, spec, highest);
+        }
+        Ok(core)
+    };
+
+    for part in spec.split(',') {
+        if let Some((start, end)) = part.split_once('-') {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                bail!("'{}' is a range running backwards", spec);
+            }
+            cores.extend(start..=end);
+        } else {
+            cores.push(parse(part)?);
+        }
+    }
+
+    cores.sort_unstable();
+    cores.dedup();
+    Ok(cores)
+}
+
+fn section_settings(config: &Snapshot, section: &str) -> Result<CoreSettings> {
+    let mut settings = CoreSettings::default();
+
+    if let Some(governor) = config.get(section, "governor") {
+        if !AVAILABLE_GOVERNORS_SORTED.iter().any(|g| g == governor) {
+            bail!("Governor '{}' in [{}] is not available on this system", governor, section);
+        }
+        settings.governor = Some(governor.to_string());
+    }
+
+    for key in ["scaling_min_freq", "scaling_max_freq"] {
+        if let Some(value) = config.get(section, key) {
+            let freq = value
+                .trim()
+                .parse::<u32>()
+                .with_context(|| format!("Invalid {} in [{}]: {} (expected kHz)", key, section, value))?;
+            if key == "scaling_min_freq" {
+                settings.scaling_min_freq = Some(freq);
+            } else {
+                settings.scaling_max_freq = Some(freq);
+            }
+        }
+    }
+
+    if let Some(value) = config.get(section, "online") {
+        match parse_bool(value) {
+            Some(online) => settings.online = Some(online),
+            None => bail!("Invalid online value in [{}]: {}", section, value),
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Collect per-core settings from all `[cpu.core.N]` sections. When several of
+/// them cover the same core, the one naming fewer cores wins key by key, so
+/// `[cpu.core.3]` refines `[cpu.core.0-7]` wherever they appear in the file.
+pub fn load_core_settings() -> Result<BTreeMap<usize, CoreSettings>> {
+    core_settings(&CONFIG.snapshot())
+}
+
+fn core_settings(config: &Snapshot) -> Result<BTreeMap<usize, CoreSettings>> {
+    let mut sections = Vec::new();
+    let highest = highest_core();
+    for section in config.sections() {
+        let Some(spec) = section.strip_prefix(SECTION_PREFIX) else { continue };
+        let cores = parse_core_spec(spec, highest).with_context(|| format!("Invalid core list in [{}]", section))?;
+        sections.push((section, cores));
+    }
+    // Broadest first; sections covering as many cores keep their name order
+    sections.sort_by_key(|(_, cores)| std::cmp::Reverse(cores.len()));
+
+    let mut result: BTreeMap<usize, CoreSettings> = BTreeMap::new();
+    for (section, cores) in sections {
+        let settings = section_settings(config, section)?;
+        for core in cores {
+            result.entry(core).or_default().merge(&settings);
+        }
+    }
+
+    Ok(result)
+}
+
+fn cpu_dir(core: usize) -> PathBuf {
+    Path::new(CPU_SYSFS_DIR).join(format!("cpu{}", core))
+}
+
+fn read_sysfs(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn write_sysfs(path: &Path, value: &str) -> Result<()> {
+    fs::write(path, format!("{}\n", value))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub(crate) fn is_online(core: usize) -> bool {
+    // cpu0 usually has no `online` file because it can't be offlined
+    read_sysfs(&cpu_dir(core).join("online"))
+        .map(|s| s == "1")
+        .unwrap_or(true)
+}
+
+fn set_online(core: usize, online: bool) -> Result<()> {
+    let path = cpu_dir(core).join("online");
+    if !path.exists() {
+        bail!("CPU{} can't be taken offline", core);
+    }
+
+    if is_online(core) != online {
+        info!("Setting CPU{} {}", core, if online { "online" } else { "offline" });
+        write_sysfs(&path, if online { "1" } else { "0" })?;
+    }
+
+    // Kept in the state file so a restarted daemon still brings them back
+    let store = StateStore::default();
+    if store.load().parked_cores.contains(&core) == online {
+        store.update(|state| {
+            state.parked_cores.retain(|parked| *parked != core);
+            if !online {
+                state.parked_cores.push(core);
+                state.parked_cores.sort_unstable();
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn set_frequency_limits(core: usize, min: Option<u32>, max: Option<u32>) -> Result<()> {
+    let cpufreq = cpu_dir(core).join("cpufreq");
+    let read_khz = |name: &str| read_sysfs(&cpufreq.join(name)).and_then(|s| s.parse::<u32>().ok());
+
+    // Out of range limits are clamped rather than failing the cycle: the same
+    // config is shared between machines with different CPUs
+    let (min, max) = match hardware_limits(core) {
+        Some(hardware) => {
+            if let Err(e) = crate::validate::frequency_limits(min, max, Some(hardware)) {
+                if !CLAMP_WARNED.swap(true, Ordering::Relaxed) {
+                    warn!(code = %EventCode::ConfigInvalid, "CPU{}: {}, clamping to the hardware range", core, e);
+                }
+            }
+            (clamp_khz(min, hardware), clamp_khz(max, hardware))
+        }
+        None => (min, max),
+    };
+    crate::validate::frequency_limits(min, max, None).with_context(|| format!("CPU{}", core))?;
+
+    let write_khz = |name: &str, value: u32| -> Result<()> {
+        if read_khz(name) != Some(value) {
+            info!("Setting CPU{} {}: {} kHz", core, name, value);
+            write_sysfs(&cpufreq.join(name), &value.to_string())?;
+        }
+        Ok(())
+    };
+
+    // Raising the floor above the current ceiling is rejected by the kernel,
+    // so move the ceiling first in that case
+    let current_max = read_khz("scaling_max_freq").unwrap_or(u32::MAX);
+    match min {
+        Some(min) if min > current_max => {
+            if let Some(max) = max {
+                write_khz("scaling_max_freq", max)?;
+            }
+            write_khz("scaling_min_freq", min)?;
+        }
+        _ => {
+            if let Some(min) = min {
+                write_khz("scaling_min_freq", min)?;
+            }
+            if let Some(max) = max {
+                write_khz("scaling_max_freq", max)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn clamp_khz(value: Option<u32>, (hw_min, hw_max): (u32, u32)) -> Option<u32> {
+    value.map(|khz| khz.max(hw_min).min(hw_max))
+}
+
+/// Hardware frequency range of a core in kHz (cpuinfo_min_freq, cpuinfo_max_freq)
+pub(crate) fn hardware_limits(core: usize) -> Option<(u32, u32)> {
+    let cpufreq = cpu_dir(core).join("cpufreq");
+    let read_khz = |name: &str| read_sysfs(&cpufreq.join(name)).and_then(|s| s.parse::<u32>().ok());
+    Some((read_khz("cpuinfo_min_freq")?, read_khz("cpuinfo_max_freq")?))
+}
+
+/// Current scaling_max_freq of a core in kHz
+pub(crate) fn scaling_max_freq(core: usize) -> Option<u32> {
+    read_sysfs(&cpu_dir(core).join("cpufreq/scaling_max_freq")).and_then(|s| s.parse::<u32>().ok())
+}
+
+/// A core's online state, governor and frequency limits as found, so they
+/// can be put back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorePolicy {
+    pub core: usize,
+    /// Older state files only held online cores
+    #[serde(default = "online")]
+    pub online: bool,
+    pub governor: Option<String>,
+    /// kHz
+    pub scaling_min_freq: Option<u32>,
+    /// kHz
+    pub scaling_max_freq: Option<u32>,
+}
+
+impl CorePolicy {
+    /// Every present core that is offline or has a cpufreq policy
+    pub fn capture_all() -> Vec<Self> {
+        all_cores()
+            .into_iter()
+            .filter_map(|core| {
+                let cpufreq = cpu_dir(core).join("cpufreq");
+                let read_khz = |name: &str| read_sysfs(&cpufreq.join(name)).and_then(|s| s.parse::<u32>().ok());
+                let policy = Self {
+                    core,
+                    online: is_online(core),
+                    governor: read_sysfs(&cpufreq.join("scaling_governor")),
+                    scaling_min_freq: read_khz("scaling_min_freq"),
+                    scaling_max_freq: read_khz("scaling_max_freq"),
+                };
+                (policy.governor.is_some() || !policy.online).then_some(policy)
+            })
+            .collect()
+    }
+
+    /// Put the online state, governor and limits back. A core parked since
+    /// comes back online first, as an offline core's limits can't be written.
+    pub(crate) fn restore(&self) -> Result<()> {
+        if is_online(self.core) != self.online {
+            set_online(self.core, self.online)?;
+        }
+        if !self.online {
+            return Ok(());
+        }
+        if let Some(governor) = &self.governor {
+            set_core_governor(self.core, governor)?;
+        }
+        if self.scaling_min_freq.is_some() || self.scaling_max_freq.is_some() {
+            set_frequency_limits(self.core, self.scaling_min_freq, self.scaling_max_freq)?;
+        }
+        Ok(())
+    }
+}
+
+fn online() -> bool {
+    true
+}
+
+/// Numbers of all CPUs present in sysfs, online or not, in ascending order
+pub(crate) fn all_cores() -> Vec<usize> {
+    crate::core::cpu_sysfs_dirs()
+        .iter()
+        .filter_map(|dir| dir.file_name()?.to_str()?.strip_prefix("cpu")?.parse::<usize>().ok())
+        .collect()
+}
+
+fn set_core_governor(core: usize, governor: &str) -> Result<()> {
+    crate::validate::governor(governor)?;
+    let path = cpu_dir(core).join("cpufreq/scaling_governor");
+    if read_sysfs(&path).as_deref() != Some(governor) {
+        info!("Setting CPU{} governor: {}", core, governor);
+        write_sysfs(&path, governor)?;
+    }
+    Ok(())
+}
+
+fn apply_core(core: usize, settings: &CoreSettings) -> Result<()> {
+    if !cpu_dir(core).exists() {
+        bail!("CPU{} does not exist", core);
+    }
+
+    if let Some(online) = settings.online {
+        set_online(core, online)?;
+        if !online {
+            return Ok(());
+        }
+    }
+
+    if let Some(governor) = &settings.governor {
+        set_core_governor(core, governor)?;
+    }
+
+    if settings.scaling_min_freq.is_some() || settings.scaling_max_freq.is_some() {
+        set_frequency_limits(core, settings.scaling_min_freq, settings.scaling_max_freq)?;
+    }
+
+    Ok(())
+}
+
+/// Apply all `[cpu.core.N]` sections. Called by `set_autofreq` after the global
+/// governor is set, so per-core overrides always win. Cores we parked earlier
+/// that are no longer configured offline are brought back online.
+pub fn apply_per_core_settings() -> Result<()> {
+    let settings = load_core_settings()?;
+
+    let unparked: Vec<usize> = StateStore::default()
+        .load()
+        .parked_cores
+        .into_iter()
+        .filter(|core| settings.get(core).and_then(|s| s.online) != Some(false))
+        .collect();
+    for core in unparked {
+        set_online(core, true)?;
+    }
+
+    for (core, core_settings) in &settings {
+        if let Err(e) = apply_core(*core, core_settings) {
+            warn!(code = %EventCode::TuningFailed, "Failed to apply [cpu.core.{}] settings: {}", core, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Governor of the first online core without a per-core governor override.
+/// Returns `None` when no per-core governor is configured, in which case the
+/// global governor can be read from any core.
+pub fn unmanaged_governor() -> Option<String> {
+    let settings = load_core_settings().ok()?;
+    if settings.values().all(|s| s.governor.is_none()) {
+        return None;
+    }
+
+    all_cores()
+        .into_iter()
+        .filter(|core| settings.get(core).is_none_or(|s| s.governor.is_none()))
+        .filter(|core| is_online(*core))
+        .find_map(|core| read_sysfs(&cpu_dir(core).join("cpufreq/scaling_governor")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_core_spec() {
+        assert_eq!(parse_core_spec("3", 15).unwrap(), vec![3]);
+        assert_eq!(parse_core_spec("4-7", 15).unwrap(), vec![4, 5, 6, 7]);
+        assert_eq!(parse_core_spec("0, 2,2,4-5", 15).unwrap(), vec![0, 2, 4, 5]);
+        assert!(parse_core_spec("7-4", 15).is_err());
+        assert!(parse_core_spec("p-cores", 15).is_err());
+        assert!(parse_core_spec("16", 15).is_err());
+        // Refused without expanding the range first
+        let error = parse_core_spec("0-4000000000", 15).unwrap_err();
+        assert_eq!(error.to_string(), "CPU4000000000 in '0-4000000000' does not exist, the highest is CPU15");
+    }
+
+    #[test]
+    fn test_core_settings_merge() {
+        let mut base = CoreSettings {
+            governor: Some("schedutil".to_string()),
+            scaling_max_freq: Some(3_000_000),
+            ..Default::default()
+        };
+        base.merge(&CoreSettings {
+            governor: Some("powersave".to_string()),
+            online: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(base.governor.as_deref(), Some("powersave"));
+        assert_eq!(base.scaling_max_freq, Some(3_000_000));
+        assert_eq!(base.online, Some(false));
+    }
+
+    #[test]
+    fn test_clamp_khz() {
+        let hardware = (400_000, 4_800_000);
+        assert_eq!(clamp_khz(Some(5_000_000), hardware), Some(4_800_000));
+        assert_eq!(clamp_khz(Some(100_000), hardware), Some(400_000));
+        assert_eq!(clamp_khz(Some(800_000), hardware), Some(800_000));
+        assert_eq!(clamp_khz(None, hardware), None);
+    }
+
+    #[test]
+    fn test_narrower_sections_win() {
+        let config = Snapshot::from_str_ini(
+            "[cpu.core.3]\nonline = no\n[cpu.core.0-7]\nonline = yes\nscaling_max_freq = 2000000\n",
+        )
+        .unwrap();
+        let settings = core_settings(&config).unwrap();
+        assert_eq!(settings[&3].online, Some(false));
+        assert_eq!(settings[&3].scaling_max_freq, Some(2_000_000));
+        assert_eq!(settings[&4].online, Some(true));
+    }
+}
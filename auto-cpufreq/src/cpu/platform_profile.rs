@@ -0,0 +1,119 @@
+// src/cpu/platform_profile.rs
+//
+// ACPI platform profile (/sys/firmware/acpi/platform_profile). Firmware uses it
+// to pick fan curves and power limits; the available names depend on the
+// vendor driver (thinkpad_acpi, ideapad_laptop, asus-wmi, hp-wmi, ...).
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, bail, Context};
+use tracing::info;
+
+use crate::config::CONFIG;
+
+const PLATFORM_PROFILE: &str = "/sys/firmware/acpi/platform_profile";
+const PLATFORM_PROFILE_CHOICES: &str = "/sys/firmware/acpi/platform_profile_choices";
+
+pub fn is_supported() -> bool {
+    Path::new(PLATFORM_PROFILE).exists()
+}
+
+pub fn choices() -> Vec<String> {
+    fs::read_to_string(PLATFORM_PROFILE_CHOICES)
+        .map(|s| s.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+pub fn current() -> Option<String> {
+    fs::read_to_string(PLATFORM_PROFILE)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// Profiles that mean roughly the same thing across vendor drivers, most generic first
+const EQUIVALENTS: &[&[&str]] = &[
+    &["low-power", "quiet", "cool"],
+    &["balanced", "balanced-performance"],
+    &["performance", "balanced-performance"],
+];
+
+/// `value` if the firmware offers it, otherwise the closest equivalent it does
+/// offer (e.g. "quiet" on a driver that only knows "low-power")
+fn resolve_in(value: &str, choices: &[String]) -> Option<String> {
+    if choices.is_empty() || choices.iter().any(|c| c == value) {
+        return Some(value.to_string());
+    }
+
+    EQUIVALENTS
+        .iter()
+        .filter(|group| group.contains(&value))
+        .flat_map(|group| group.iter())
+        .find(|name| choices.iter().any(|c| c == *name))
+        .map(|name| name.to_string())
+}
+
+pub fn resolve(value: &str) -> Option<String> {
+    resolve_in(value, &choices())
+}
+
+fn validate(value: &str, choices: &[String]) -> Result<()> {
+    if !choices.is_empty() && !choices.iter().any(|c| c == value) {
+        bail!("Platform profile '{}' is not available (choices: {})", value, choices.join(", "));
+    }
+    Ok(())
+}
+
+pub fn set(value: &str) -> Result<()> {
+    if !is_supported() {
+        bail!("platform_profile is not supported on this system");
+    }
+    validate(value, &choices())?;
+
+    if current().as_deref() != Some(value) {
+        info!("Setting platform profile: {}", value);
+        fs::write(PLATFORM_PROFILE, value)
+            .with_context(|| format!("Failed to write {}", PLATFORM_PROFILE))?;
+    }
+
+    Ok(())
+}
+
+/// Called by `set_autofreq`: applies `platform_profile` from `[charger]` or
+/// `[battery]`. Nothing is changed when the key isn't set.
+pub fn apply(is_charging: bool) -> Result<()> {
+    let section = if is_charging { "charger" } else { "battery" };
+    let value = CONFIG.get(section, "platform_profile", "");
+    if value.is_empty() || !is_supported() {
+        return Ok(());
+    }
+    set(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let choices: Vec<String> = ["low-power", "balanced", "performance"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(validate("balanced", &choices).is_ok());
+        assert!(validate("quiet", &choices).is_err());
+        // Old kernels without the choices file: let the kernel decide
+        assert!(validate("quiet", &[]).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_equivalents() {
+        let choices: Vec<String> = ["quiet", "balanced", "performance"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(resolve_in("low-power", &choices).as_deref(), Some("quiet"));
+        assert_eq!(resolve_in("balanced", &choices).as_deref(), Some("balanced"));
+        assert_eq!(resolve_in("custom", &choices), None);
+    }
+}
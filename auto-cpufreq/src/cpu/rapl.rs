@@ -0,0 +1,296 @@
+// src/cpu/rapl.rs
+//
+// Intel RAPL (Running Average Power Limit) through the powercap framework
+// (/sys/class/powercap/intel-rapl:N). The package domain's energy counter gives
+// the current power draw; its constraints are PL1 (long_term) and PL2
+// (short_term), which can be lowered per power source for a strict battery
+// power budget. A power source without limits gets the firmware's back, as
+// found when the daemon started.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail, Context};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::config::{CONFIG, Snapshot};
+use crate::state::StateStore;
+
+const POWERCAP_DIR: &str = "/sys/class/powercap";
+const DOMAIN_PREFIX: &str = "intel-rapl:";
+
+/// Time between the two energy readings when there is no earlier sample
+const SAMPLE_WINDOW: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RaplInfo {
+    /// Package power draw (W), averaged since the previous reading
+    pub package_power: Option<f32>,
+    /// Long-term limit (W)
+    pub pl1: Option<f32>,
+    /// Short-term limit (W)
+    pub pl2: Option<f32>,
+}
+
+/// Which of the domain's constraints is PL1 or PL2
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Pl1,
+    Pl2,
+}
+
+impl Limit {
+    fn constraint_name(self) -> &'static str {
+        match self {
+            Limit::Pl1 => "long_term",
+            Limit::Pl2 => "short_term",
+        }
+    }
+}
+
+/// Top-level package domains (`intel-rapl:0`, `intel-rapl:1` on multi-socket
+/// machines), without their core/uncore/dram subzones
+fn package_domains() -> Vec<PathBuf> {
+    let mut domains: Vec<PathBuf> = fs::read_dir(POWERCAP_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .and_then(|n| n.strip_prefix(DOMAIN_PREFIX))
+                        .is_some_and(|rest| !rest.contains(':'))
+                })
+                .filter(|p| read_trimmed(&p.join("name")).is_some_and(|n| n.starts_with("package")))
+                .collect()
+        })
+        .unwrap_or_default();
+    domains.sort();
+    domains
+}
+
+pub fn is_present() -> bool {
+    !package_domains().is_empty()
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+/// Index of the `constraint_N_*` files for `limit` in `domain`
+fn constraint_index(domain: &Path, limit: Limit) -> Option<usize> {
+    (0..4).find(|i| {
+        read_trimmed(&domain.join(format!("constraint_{}_name", i))).as_deref() == Some(limit.constraint_name())
+    })
+}
+
+/// Summed energy counters of all packages (µJ) with the counters' wrap range
+fn read_energy() -> Option<(u64, u64)> {
+    let domains = package_domains();
+    let mut total = 0;
+    let mut range = 0;
+    for domain in &domains {
+        total += read_u64(&domain.join("energy_uj"))?;
+        range += read_u64(&domain.join("max_energy_range_uj")).unwrap_or(u64::MAX / 2);
+    }
+    (!domains.is_empty()).then_some((total, range))
+}
+
+/// Whether the energy counters can be read, they are root-only on most kernels
+pub fn energy_readable() -> bool {
+    package_domains().first().is_some_and(|domain| fs::read_to_string(domain.join("energy_uj")).is_ok())
+}
+
+/// Energy used between two counter readings, allowing for one wrap-around
+fn energy_delta(previous: u64, current: u64, range: u64) -> u64 {
+    if current >= previous {
+        current - previous
+    } else {
+        range.saturating_sub(previous) + current
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref LAST_SAMPLE: Mutex<Option<(Instant, u64)>> = Mutex::new(None);
+    /// The limits to go back to, loaded once
+    static ref FIRMWARE_LIMITS: Mutex<Option<Limits>> = Mutex::new(None);
+}
+
+/// Whether the limits last written by `apply` came from the config
+static LIMITED: AtomicBool = AtomicBool::new(false);
+
+/// PL1 and PL2 of the first package (W)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Limits {
+    pub pl1: Option<f32>,
+    pub pl2: Option<f32>,
+}
+
+impl Limits {
+    pub fn capture() -> Option<Self> {
+        is_present().then(|| Self { pl1: limit(Limit::Pl1), pl2: limit(Limit::Pl2) })
+    }
+
+    pub fn restore(&self) -> Result<()> {
+        if let Some(pl1) = self.pl1 {
+            set_limit(Limit::Pl1, pl1)?;
+        }
+        if let Some(pl2) = self.pl2 {
+            set_limit(Limit::Pl2, pl2)?;
+        }
+        Ok(())
+    }
+}
+
+/// The limits saved when the daemon started, or the current ones when
+/// nothing was saved
+fn firmware_limits() -> Limits {
+    *FIRMWARE_LIMITS.lock().unwrap().get_or_insert_with(|| {
+        StateStore::default()
+            .load()
+            .original_settings
+            .and_then(|original| original.rapl)
+            .or_else(Limits::capture)
+            .unwrap_or_default()
+    })
+}
+
+/// Package power draw in watts. The first call takes two readings a short
+/// moment apart; later calls average over the time since the previous call.
+/// The energy counters are root-only on most kernels.
+pub fn package_power() -> Option<f32> {
+    let mut last = LAST_SAMPLE.lock().unwrap();
+
+    let (previous_at, previous) = match *last {
+        Some(sample) => sample,
+        None => {
+            let (energy, _) = read_energy()?;
+            let at = Instant::now();
+            std::thread::sleep(SAMPLE_WINDOW);
+            (at, energy)
+        }
+    };
+
+    let (energy, range) = read_energy()?;
+    let now = Instant::now();
+    *last = Some((now, energy));
+
+    let elapsed = now.duration_since(previous_at).as_secs_f32();
+    (elapsed > 0.0).then(|| energy_delta(previous, energy, range) as f32 / 1_000_000.0 / elapsed)
+}
+
+/// Current limit of the first package (W)
+pub fn limit(limit: Limit) -> Option<f32> {
+    let domain = package_domains().into_iter().next()?;
+    let index = constraint_index(&domain, limit)?;
+    read_u64(&domain.join(format!("constraint_{}_power_limit_uw", index))).map(|uw| uw as f32 / 1_000_000.0)
+}
+
+pub fn info() -> Option<RaplInfo> {
+    if !is_present() {
+        return None;
+    }
+    Some(RaplInfo {
+        package_power: package_power(),
+        pl1: limit(Limit::Pl1),
+        pl2: limit(Limit::Pl2),
+    })
+}
+
+/// Set PL1 or PL2 (W) on every package
+pub fn set_limit(limit: Limit, watts: f32) -> Result<()> {
+    let domains = package_domains();
+    if domains.is_empty() {
+        bail!("RAPL is not available on this system");
+    }
+
+    let microwatts = (watts * 1_000_000.0).round() as u64;
+    for domain in domains {
+        let Some(index) = constraint_index(&domain, limit) else {
+            continue;
+        };
+        let path = domain.join(format!("constraint_{}_power_limit_uw", index));
+        if read_u64(&path) == Some(microwatts) {
+            continue;
+        }
+
+        info!("Setting RAPL {:?} to {} W ({})", limit, watts, domain.display());
+        fs::write(&path, microwatts.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// `rapl_pl1` / `rapl_pl2` (W) from `[charger]` or `[battery]`
+fn limits_for(config: &Snapshot, is_charging: bool) -> Result<(Option<f32>, Option<f32>)> {
+    let section = if is_charging { "charger" } else { "battery" };
+    let watts = |key: &str| -> Result<Option<f32>> {
+        match config.get(section, key) {
+            None => Ok(None),
+            Some(value) => match value.trim().parse::<f32>() {
+                Ok(w) if w > 0.0 => Ok(Some(w)),
+                _ => bail!("Invalid {} in [{}]: {} (expected watts)", key, section, value),
+            },
+        }
+    };
+
+    let (pl1, pl2) = (watts("rapl_pl1")?, watts("rapl_pl2")?);
+    if let (Some(pl1), Some(pl2)) = (pl1, pl2) {
+        if pl2 < pl1 {
+            bail!("rapl_pl2 ({} W) is below rapl_pl1 ({} W) in [{}]", pl2, pl1, section);
+        }
+    }
+    Ok((pl1, pl2))
+}
+
+/// Called by `set_autofreq`: applies the configured power limits for the
+/// current power source, the firmware's for those it doesn't set. Without
+/// any configured for either source the limits are left alone, so tools that
+/// adjust them (thermald) aren't fought over.
+pub fn apply(is_charging: bool) -> Result<()> {
+    let (pl1, pl2) = limits_for(&CONFIG.snapshot(), is_charging)?;
+    if !is_present() {
+        return Ok(());
+    }
+
+    let limited = pl1.is_some() || pl2.is_some();
+    if !limited && !LIMITED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let firmware = firmware_limits();
+    Limits { pl1: pl1.or(firmware.pl1), pl2: pl2.or(firmware.pl2) }.restore()?;
+    LIMITED.store(limited, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_delta_wraps() {
+        assert_eq!(energy_delta(100, 250, 1000), 150);
+        assert_eq!(energy_delta(900, 50, 1000), 150);
+    }
+
+    #[test]
+    fn test_limits_for() {
+        let config = Snapshot::from_str_ini("[battery]\nrapl_pl1 = 12\nrapl_pl2 = 20\n[charger]\nrapl_pl2 = 5\nrapl_pl1 = 10\n").unwrap();
+        assert_eq!(limits_for(&config, false).unwrap(), (Some(12.0), Some(20.0)));
+        assert!(limits_for(&config, true).is_err());
+
+        let config = Snapshot::from_str_ini("[battery]\nrapl_pl1 = lots\n").unwrap();
+        assert!(limits_for(&config, false).is_err());
+        assert_eq!(limits_for(&config, true).unwrap(), (None, None));
+    }
+}
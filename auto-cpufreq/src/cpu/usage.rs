@@ -0,0 +1,164 @@
+// src/cpu/usage.rs
+//
+// CPU usage sampling for the daemon. By default usage is the delta between two
+// /proc/stat readings, the previous one normally being from the last daemon
+// cycle, so no extra wait is needed. `[daemon] usage_backend = sysinfo` goes
+// back to the sysinfo crate, which is also used when /proc/stat is unreadable.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use sysinfo::System;
+use tracing::warn;
+
+use crate::config::CONFIG;
+use crate::event_code::EventCode;
+use crate::procfs::{self, CpuUsage, StatSnapshot};
+
+/// Calls within this window share one sample, so everything a daemon cycle
+/// does sees the same usage. Short enough for a sub-second `min_interval`.
+const SAMPLE_TTL: Duration = Duration::from_millis(250);
+/// Measurement window when there is no earlier reading to diff against
+const SAMPLE_WINDOW: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageBackend {
+    /// /proc/stat when readable, sysinfo otherwise
+    Auto,
+    Procfs,
+    Sysinfo,
+}
+
+impl UsageBackend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "procfs" | "proc" => Some(Self::Procfs),
+            "sysinfo" => Some(Self::Sysinfo),
+            _ => None,
+        }
+    }
+
+    fn resolve(self) -> Self {
+        match self {
+            Self::Auto if procfs::read_stat().is_some() => Self::Procfs,
+            Self::Auto => Self::Sysinfo,
+            other => other,
+        }
+    }
+}
+
+struct Sampler {
+    /// Raw `usage_backend` value and what it resolved to
+    configured: Option<(String, UsageBackend)>,
+    last_stat: Option<StatSnapshot>,
+    sys: Option<System>,
+    sampled_at: Option<Instant>,
+    usage: CpuUsage,
+}
+
+impl Sampler {
+    fn backend(&mut self) -> UsageBackend {
+        let value = CONFIG.get("daemon", "usage_backend", "auto");
+        if let Some((configured, backend)) = &self.configured {
+            if *configured == value {
+                return *backend;
+            }
+        }
+
+        let backend = UsageBackend::parse(&value)
+            .unwrap_or_else(|| {
+                warn!(code = %EventCode::ConfigInvalid, "Invalid usage_backend in [daemon]: {}, using auto", value);
+                UsageBackend::Auto
+            })
+            .resolve();
+        self.configured = Some((value, backend));
+        self.last_stat = None;
+        self.sys = None;
+        self.sampled_at = None;
+        backend
+    }
+
+    fn sample(&mut self) -> CpuUsage {
+        let backend = self.backend();
+        if self.sampled_at.is_some_and(|at| at.elapsed() < SAMPLE_TTL) {
+            return self.usage.clone();
+        }
+
+        self.usage = match backend {
+            UsageBackend::Sysinfo => self.sample_sysinfo(),
+            _ => self.sample_procfs(),
+        };
+        self.sampled_at = Some(Instant::now());
+        self.usage.clone()
+    }
+
+    fn sample_procfs(&mut self) -> CpuUsage {
+        let Some(mut prev) = self.last_stat.take().or_else(procfs::read_stat) else {
+            return CpuUsage::default();
+        };
+        let mut cur = procfs::read_stat().unwrap_or_default();
+        // First call, or the previous reading is too recent to diff against
+        if cur.all.total.saturating_sub(prev.all.total) == 0 {
+            prev = cur;
+            std::thread::sleep(SAMPLE_WINDOW);
+            cur = procfs::read_stat().unwrap_or_default();
+        }
+
+        let usage = procfs::usage_between(&prev, &cur);
+        self.last_stat = Some(cur);
+        usage
+    }
+
+    fn sample_sysinfo(&mut self) -> CpuUsage {
+        let sys = self.sys.get_or_insert_with(|| {
+            let mut sys = System::new();
+            sys.refresh_cpu();
+            std::thread::sleep(SAMPLE_WINDOW.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL));
+            sys
+        });
+        sys.refresh_cpu();
+
+        let cores: Vec<f32> = sys.cpus().iter().map(|c| c.cpu_usage()).collect();
+        let total = if cores.is_empty() { 0.0 } else { cores.iter().sum::<f32>() / cores.len() as f32 };
+        CpuUsage { total, cores }
+    }
+}
+
+lazy_static! {
+    static ref SAMPLER: Mutex<Sampler> = Mutex::new(Sampler {
+        configured: None,
+        last_stat: None,
+        sys: None,
+        sampled_at: None,
+        usage: CpuUsage::default(),
+    });
+}
+
+/// CPU usage since the previous sample, per core and averaged. Only the first
+/// call (or one after a backend change) blocks for a measurement window.
+pub fn sample() -> CpuUsage {
+    SAMPLER.lock().unwrap().sample()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backend() {
+        assert_eq!(UsageBackend::parse("auto"), Some(UsageBackend::Auto));
+        assert_eq!(UsageBackend::parse(" ProcFS "), Some(UsageBackend::Procfs));
+        assert_eq!(UsageBackend::parse("sysinfo"), Some(UsageBackend::Sysinfo));
+        assert_eq!(UsageBackend::parse("psutil"), None);
+        assert_eq!(UsageBackend::Sysinfo.resolve(), UsageBackend::Sysinfo);
+    }
+
+    #[test]
+    fn test_sample() {
+        let usage = sample();
+        assert!((0.0..=100.0).contains(&usage.total));
+        assert!(usage.cores.iter().all(|u| (0.0..=100.0).contains(u)));
+    }
+}
@@ -0,0 +1,169 @@
+// src/docked.rs
+//
+// Docked (clamshell) detection. With the lid closed on AC power, and by default
+// at least one external display connected, `set_autofreq` uses the governor,
+// turbo, EPP and other profile keys from the `[docked]` config section. The lid
+// state comes from the ACPI button driver, displays from the DRM connectors.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use tracing::{info, warn};
+
+use crate::config::{parse_bool, CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::profiles::{self, Profile, ProfileSwitch};
+
+const SECTION: &str = "docked";
+const LID_DIR: &str = "/proc/acpi/button/lid";
+const DRM_DIR: &str = "/sys/class/drm";
+
+/// Connector types of built-in panels
+const INTERNAL_CONNECTORS: &[&str] = &["eDP", "LVDS", "DSI"];
+
+static SWITCH: ProfileSwitch<()> = ProfileSwitch::new();
+
+/// Parse `/proc/acpi/button/lid/*/state` ("state:      closed")
+fn parse_lid_state(content: &str) -> Option<bool> {
+    match content.strip_prefix("state:")?.trim() {
+        "closed" => Some(true),
+        "open" => Some(false),
+        _ => None,
+    }
+}
+
+fn lid_closed_in(lid_dir: &Path) -> Option<bool> {
+    fs::read_dir(lid_dir)
+        .ok()?
+        .flatten()
+        .find_map(|e| parse_lid_state(&fs::read_to_string(e.path().join("state")).ok()?))
+}
+
+/// `Some(true)` with the lid closed, `None` on machines without a lid switch
+pub fn lid_closed() -> Option<bool> {
+    lid_closed_in(Path::new(LID_DIR))
+}
+
+/// Connected connectors other than the built-in panel under `drm_dir`, named
+/// `card<N>-<type>-<index>`
+fn external_displays_in(drm_dir: &Path) -> usize {
+    fs::read_dir(drm_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| {
+                    let name = e.file_name().to_string_lossy().into_owned();
+                    name.split_once('-').is_some_and(|(card, connector)| {
+                        card.starts_with("card") && !INTERNAL_CONNECTORS.iter().any(|t| connector.starts_with(t))
+                    })
+                })
+                .filter(|e| {
+                    fs::read_to_string(e.path().join("status")).is_ok_and(|status| status.trim() == "connected")
+                })
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// Number of connected external displays
+pub fn external_displays() -> usize {
+    external_displays_in(Path::new(DRM_DIR))
+}
+
+/// The `[docked]` profile, `None` when the section isn't in the config
+pub fn settings(config: &Snapshot) -> Result<Option<Profile>> {
+    if config.section(SECTION).is_none() {
+        return Ok(None);
+    }
+
+    let mut profile = Profile { name: SECTION.to_string(), ..Profile::default() };
+    profiles::apply_overrides(config, SECTION, &mut profile)?;
+    Ok(Some(profile))
+}
+
+/// `require_external_display` in `[docked]`, on by default
+fn require_external_display(config: &Snapshot) -> bool {
+    config
+        .get(SECTION, "require_external_display")
+        .and_then(parse_bool)
+        .unwrap_or(true)
+}
+
+/// Lid closed on AC, with an external display unless that requirement is off
+pub fn is_docked(config: &Snapshot, is_charging: bool) -> bool {
+    is_charging
+        && lid_closed() == Some(true)
+        && (!require_external_display(config) || external_displays() > 0)
+}
+
+/// The `[docked]` profile while docked
+fn current(is_charging: bool) -> Option<((), Profile)> {
+    let config = CONFIG.snapshot();
+    let profile = match settings(&config) {
+        Ok(profile) => profile?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            return None;
+        }
+    };
+    is_docked(&config, is_charging).then_some(((), profile))
+}
+
+/// Called by `set_autofreq`: the `[docked]` profile while docked. Frequency
+/// limits set by it are lifted again once the machine is undocked.
+pub fn profile(is_charging: bool) -> Option<Profile> {
+    SWITCH.follow(current(is_charging), |docked| match docked {
+        Some(_) => info!("Lid closed on AC, using the [docked] settings"),
+        None => info!("No longer docked, back to the regular settings"),
+    })
+}
+
+pub fn preview(is_charging: bool) -> Option<Profile> {
+    SWITCH.preview(current(is_charging))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docked_settings() {
+        let config = Snapshot::from_str_ini(
+            "[docked]\ngovernor = performance\nturbo = always\nrequire_external_display = no\n",
+        )
+        .unwrap();
+        let profile = settings(&config).unwrap().unwrap();
+        assert_eq!(profile.governor.as_deref(), Some("performance"));
+        assert_eq!(profile.turbo.as_deref(), Some("always"));
+        assert!(!require_external_display(&config));
+
+        assert_eq!(settings(&Snapshot::from_str_ini("[charger]\nturbo = auto\n").unwrap()).unwrap(), None);
+        assert!(settings(&Snapshot::from_str_ini("[docked]\nturbo = maybe\n").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_lid_and_displays() {
+        assert_eq!(parse_lid_state("state:      closed\n"), Some(true));
+        assert_eq!(parse_lid_state("state:      open\n"), Some(false));
+        assert_eq!(parse_lid_state("unsupported\n"), None);
+
+        let dir = std::env::temp_dir().join(format!("docked_test_{}", std::process::id()));
+        for (connector, status) in [
+            ("card1-eDP-1", "connected"),
+            ("card1-HDMI-A-1", "connected"),
+            ("card1-DP-2", "disconnected"),
+            ("card0-DP-1", "connected"),
+        ] {
+            fs::create_dir_all(dir.join(connector)).unwrap();
+            fs::write(dir.join(connector).join("status"), format!("{}\n", status)).unwrap();
+        }
+        fs::create_dir_all(dir.join("LID0")).unwrap();
+        fs::write(dir.join("LID0").join("state"), "state:      closed\n").unwrap();
+
+        assert_eq!(external_displays_in(&dir), 2);
+        assert_eq!(lid_closed_in(&dir), Some(true));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+// src/event_code.rs
+//
+// Stable codes for the events downstream tooling and support scripts look
+// for, so they can match `ACF-010` instead of an English message that may be
+// reworded. Log events carry the code as a `code` field (`CODE=` in the
+// journal, `code=` in the log file, in front of the message on a terminal)
+// and audit log entries have it next to the change. Codes are never reused
+// or renumbered; new events get new numbers.
+
+use std::fmt;
+
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Serialized as its code, `"ACF-010"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventCode {
+    GovernorChanged,
+    TurboChanged,
+    EppChanged,
+    CycleFailed,
+    TurboUnavailable,
+    CpufreqUnavailable,
+    NotRoot,
+    ThresholdWriteFailed,
+    BatterySetupFailed,
+    ThresholdReadFailed,
+    ConfigInvalid,
+    ConfigReloaded,
+    Deprecated,
+    SettingsRestored,
+    RestoreFailed,
+    TuningFailed,
+    StateWriteFailed,
+    StateCorrupt,
+    TelemetryFailed,
+    LogFileFailed,
+    EventSourceFailed,
+    ControlRequestFailed,
+    PpdShimFailed,
+    NotifyFailed,
+    InstallStepFailed,
+}
+
+impl EventCode {
+    pub const ALL: [EventCode; 25] = [
+        EventCode::GovernorChanged,
+        EventCode::TurboChanged,
+        EventCode::EppChanged,
+        EventCode::CycleFailed,
+        EventCode::TurboUnavailable,
+        EventCode::CpufreqUnavailable,
+        EventCode::NotRoot,
+        EventCode::ThresholdWriteFailed,
+        EventCode::BatterySetupFailed,
+        EventCode::ThresholdReadFailed,
+        EventCode::ConfigInvalid,
+        EventCode::ConfigReloaded,
+        EventCode::Deprecated,
+        EventCode::SettingsRestored,
+        EventCode::RestoreFailed,
+        EventCode::TuningFailed,
+        EventCode::StateWriteFailed,
+        EventCode::StateCorrupt,
+        EventCode::TelemetryFailed,
+        EventCode::LogFileFailed,
+        EventCode::EventSourceFailed,
+        EventCode::ControlRequestFailed,
+        EventCode::PpdShimFailed,
+        EventCode::NotifyFailed,
+        EventCode::InstallStepFailed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventCode::GovernorChanged => "ACF-001",
+            EventCode::TurboChanged => "ACF-002",
+            EventCode::EppChanged => "ACF-003",
+            EventCode::CycleFailed => "ACF-004",
+            EventCode::TurboUnavailable => "ACF-005",
+            EventCode::CpufreqUnavailable => "ACF-006",
+            EventCode::NotRoot => "ACF-007",
+            EventCode::ThresholdWriteFailed => "ACF-010",
+            EventCode::BatterySetupFailed => "ACF-011",
+            EventCode::ThresholdReadFailed => "ACF-012",
+            EventCode::ConfigInvalid => "ACF-030",
+            EventCode::ConfigReloaded => "ACF-031",
+            EventCode::Deprecated => "ACF-032",
+            EventCode::SettingsRestored => "ACF-040",
+            EventCode::RestoreFailed => "ACF-041",
+            EventCode::TuningFailed => "ACF-050",
+            EventCode::StateWriteFailed => "ACF-060",
+            EventCode::StateCorrupt => "ACF-061",
+            EventCode::TelemetryFailed => "ACF-062",
+            EventCode::LogFileFailed => "ACF-063",
+            EventCode::EventSourceFailed => "ACF-070",
+            EventCode::ControlRequestFailed => "ACF-071",
+            EventCode::PpdShimFailed => "ACF-072",
+            EventCode::NotifyFailed => "ACF-073",
+            EventCode::InstallStepFailed => "ACF-080",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            EventCode::GovernorChanged => "governor changed",
+            EventCode::TurboChanged => "turbo boost switched on or off",
+            EventCode::EppChanged => "energy performance preference changed",
+            EventCode::CycleFailed => "a daemon cycle failed to adjust the CPU",
+            EventCode::TurboUnavailable => "turbo boost can't be changed on this system",
+            EventCode::CpufreqUnavailable => "CPU frequency scaling isn't available, only the rest is managed",
+            EventCode::NotRoot => "not running as root, so nothing is changed and some values can't be read",
+            EventCode::ThresholdWriteFailed => "a charging threshold write failed",
+            EventCode::BatterySetupFailed => "a battery interface failed to set up",
+            EventCode::ThresholdReadFailed => "charging thresholds couldn't be read",
+            EventCode::ConfigInvalid => "a config value is invalid and was ignored",
+            EventCode::ConfigReloaded => "the config file was reloaded with changes",
+            EventCode::Deprecated => "a deprecated option or output field was used",
+            EventCode::SettingsRestored => "the settings from before the daemon started were put back",
+            EventCode::RestoreFailed => "putting back a saved setting failed",
+            EventCode::TuningFailed => "a secondary setting (EPB, RAPL, platform profile, ...) couldn't be applied",
+            EventCode::StateWriteFailed => "the state, stats, history or audit file couldn't be written",
+            EventCode::StateCorrupt => "the state file couldn't be parsed and was started over",
+            EventCode::TelemetryFailed => "the telemetry report couldn't be sent",
+            EventCode::LogFileFailed => "the log file couldn't be opened",
+            EventCode::EventSourceFailed => "an event source (uevents, resume, signals, control socket, config watch) couldn't be set up",
+            EventCode::ControlRequestFailed => "a control socket connection failed",
+            EventCode::PpdShimFailed => "the power-profiles-daemon shim stopped or couldn't apply a profile",
+            EventCode::NotifyFailed => "the status couldn't be reported to systemd",
+            EventCode::InstallStepFailed => "an install, remove or rollback step failed",
+        }
+    }
+
+    /// The code of an audit log entry for `setting`
+    pub fn for_setting(setting: &str) -> Option<Self> {
+        match setting {
+            "governor" => Some(EventCode::GovernorChanged),
+            "turbo" => Some(EventCode::TurboChanged),
+            "epp" => Some(EventCode::EppChanged),
+            _ => None,
+        }
+    }
+
+    /// The event `code` ("ACF-010") stands for
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+}
+
+impl fmt::Display for EventCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for EventCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for EventCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Self::parse(&code).ok_or_else(|| de::Error::custom(format!("unknown event code {}", code)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_event_codes() {
+        let codes: HashSet<&str> = EventCode::ALL.iter().map(|c| c.as_str()).collect();
+        assert_eq!(codes.len(), EventCode::ALL.len());
+
+        for code in EventCode::ALL {
+            let number = code.as_str().strip_prefix("ACF-").unwrap();
+            assert!(number.len() == 3 && number.chars().all(|c| c.is_ascii_digit()), "{}", code);
+            // Serialized form is the code itself
+            assert_eq!(serde_json::to_string(&code).unwrap(), format!("\"{}\"", code));
+            assert_eq!(serde_json::from_str::<EventCode>(&format!("\"{}\"", code)).unwrap(), code);
+        }
+
+        assert_eq!(EventCode::for_setting("turbo"), Some(EventCode::TurboChanged));
+        assert_eq!(EventCode::ThresholdWriteFailed.to_string(), "ACF-010");
+        assert!(serde_json::from_str::<EventCode>("\"ACF-999\"").is_err());
+    }
+}
@@ -0,0 +1,662 @@
+// src/events.rs
+//
+// Wake-up sources for the daemon loop. Kernel uevents for the power_supply
+// subsystem arrive over a netlink socket (the same stream udevd listens to),
+// so AC plug/unplug is handled right away instead of at the next poll. logind's
+// PrepareForSleep signal, followed through `busctl monitor`, marks the resume
+// from suspend, after which firmware may have reset governor and turbo. Between
+// events the loop polls for load changes with an interval that stretches while
+// nothing is happening, within the `[daemon]` min/max bounds. SIGTERM and
+// SIGINT end the wait too, so the daemon can restore settings and exit, and so
+// does a command on the control socket that changed an override, and a config
+// reload (file watcher or `reload-config`) that changed a setting.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail, Context};
+use nix::poll::{poll, PollFd, PollFlags};
+use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use nix::sys::socket::{
+    bind, recv, socket, AddressFamily, MsgFlags, NetlinkAddr, SockFlag, SockProtocol, SockType,
+};
+
+use tracing::warn;
+
+use crate::config::{Snapshot, CONFIG};
+use crate::control::ControlSocket;
+use crate::event_code::EventCode;
+use crate::power_helper::does_command_exist;
+
+/// Multicast group of raw kernel uevents (group 2 carries udevd's re-broadcasts)
+const KERNEL_UEVENT_GROUP: u32 = 1;
+/// Quiet period after an event so a burst (AC + battery + USB-C port) is handled once
+const DEBOUNCE: Duration = Duration::from_millis(150);
+/// How often a `busctl monitor` that exited is started again before giving up
+const SLEEP_MONITOR_RESTARTS: u32 = 3;
+
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(2);
+pub const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(30);
+/// Ceiling while on AC, unless max_interval is lower
+const AC_MAX_INTERVAL: Duration = Duration::from_secs(10);
+/// Change in CPU usage (percentage points) between cycles that counts as activity
+const USAGE_CHANGE_THRESHOLD: f32 = 15.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uevent {
+    pub action: String,
+    pub devpath: String,
+    pub properties: HashMap<String, String>,
+}
+
+impl Uevent {
+    pub fn subsystem(&self) -> Option<&str> {
+        self.properties.get("SUBSYSTEM").map(String::as_str)
+    }
+}
+
+/// Parse a kernel uevent datagram: `action@devpath` followed by NUL separated
+/// `KEY=value` pairs
+pub fn parse_uevent(buf: &[u8]) -> Option<Uevent> {
+    let mut fields = buf.split(|b| *b == 0).filter(|f| !f.is_empty());
+    let header = std::str::from_utf8(fields.next()?).ok()?;
+    let (action, devpath) = header.split_once('@')?;
+
+    let properties = fields
+        .filter_map(|f| std::str::from_utf8(f).ok())
+        .filter_map(|f| f.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+    Some(Uevent {
+        action: action.to_string(),
+        devpath: devpath.to_string(),
+        properties,
+    })
+}
+
+pub struct UeventMonitor {
+    fd: OwnedFd,
+}
+
+impl UeventMonitor {
+    pub fn open() -> Result<Self> {
+        let fd = socket(
+            AddressFamily::Netlink,
+            SockType::Raw,
+            SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK,
+            SockProtocol::NetlinkKObjectUEvent,
+        )
+        .context("Failed to open uevent netlink socket")?;
+
+        bind(fd.as_raw_fd(), &NetlinkAddr::new(0, KERNEL_UEVENT_GROUP))
+            .context("Failed to subscribe to kernel uevents")?;
+
+        Ok(Self { fd })
+    }
+
+    /// Read every queued uevent, returning whether one was for power_supply
+    fn drain(&self) -> bool {
+        let mut buf = [0u8; 8192];
+        let mut power_event = false;
+
+        while let Ok(len) = recv(self.fd.as_fd().as_raw_fd(), &mut buf, MsgFlags::empty()) {
+            if len == 0 {
+                break;
+            }
+            if let Some(event) = parse_uevent(&buf[..len]) {
+                power_event |= event.subsystem() == Some("power_supply");
+            }
+        }
+
+        power_event
+    }
+
+}
+
+const LOGIND_MATCH: &str = "type='signal',sender='org.freedesktop.login1',\
+    interface='org.freedesktop.login1.Manager',member='PrepareForSleep'";
+
+/// `Some(true)` before suspend and `Some(false)` after resume, from a line of
+/// `busctl --json=short monitor` output
+pub fn parse_prepare_for_sleep(line: &str) -> Option<bool> {
+    let message: serde_json::Value = serde_json::from_str(line).ok()?;
+    if message.get("member")?.as_str()? != "PrepareForSleep" {
+        return None;
+    }
+    message.get("payload")?.get("data")?.get(0)?.as_bool()
+}
+
+/// Follows logind's PrepareForSleep signal on the system bus. A reader thread
+/// turns each resume into a byte on a socket pair so it can be polled together
+/// with the uevent socket.
+pub struct SleepMonitor {
+    child: Child,
+    resumed: UnixStream,
+}
+
+impl SleepMonitor {
+    pub fn open() -> Result<Self> {
+        if !does_command_exist("busctl") {
+            bail!("busctl is not available to follow suspend/resume");
+        }
+
+        let mut child = crate::command::new("busctl")
+            .args(["--system", "--json=short", "monitor", "--match", LOGIND_MATCH])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start busctl monitor")?;
+        let stdout = child.stdout.take().context("busctl monitor has no output")?;
+
+        let (resumed, notify) = UnixStream::pair()?;
+        resumed.set_nonblocking(true)?;
+
+        std::thread::spawn(move || {
+            let mut notify = notify;
+            for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+                match parse_prepare_for_sleep(&line) {
+                    Some(true) => println!("\t\tSystem is going to sleep"),
+                    Some(false) if notify.write_all(&[1]).is_err() => break,
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(Self { child, resumed })
+    }
+
+    /// Consume queued resume notifications. `Closed` once busctl has exited.
+    fn drain(&self) -> Drained {
+        drain_socket(&self.resumed)
+    }
+}
+
+impl Drop for SleepMonitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Write end of the shutdown socket pair, for the signal handler
+static SHUTDOWN_FD: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_shutdown_signal(_: nix::libc::c_int) {
+    let fd = SHUTDOWN_FD.load(Ordering::Relaxed);
+    if fd >= 0 {
+        // write(2) is async-signal-safe; a full buffer already means "stop"
+        unsafe { nix::libc::write(fd, [1u8].as_ptr().cast(), 1) };
+    }
+}
+
+/// SIGTERM (`systemctl stop`) and SIGINT (Ctrl+C) turned into a byte on a
+/// socket pair, so they can be polled with the other wake-up sources instead
+/// of killing the daemon mid-cycle
+pub struct ShutdownSignal {
+    received: UnixStream,
+    _notify: UnixStream,
+}
+
+impl ShutdownSignal {
+    pub fn install() -> Result<Self> {
+        let (received, notify) = UnixStream::pair()?;
+        received.set_nonblocking(true)?;
+        notify.set_nonblocking(true)?;
+        SHUTDOWN_FD.store(notify.as_raw_fd(), Ordering::Relaxed);
+
+        let action = SigAction::new(SigHandler::Handler(on_shutdown_signal), SaFlags::SA_RESTART, SigSet::empty());
+        for signal in [Signal::SIGTERM, Signal::SIGINT] {
+            // Safe: the handler only loads an atomic and calls write(2)
+            unsafe { sigaction(signal, &action) }.with_context(|| format!("Failed to handle {}", signal))?;
+        }
+        Ok(Self { received, _notify: notify })
+    }
+
+    /// Consume queued signals, returning whether there were any
+    pub fn drain(&self) -> bool {
+        drain_socket(&self.received) == Drained::Received
+    }
+
+    /// Sleep for `timeout`, returning early with true when a signal arrives
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.drain() {
+                return true;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+
+            let timeout_ms = remaining.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32;
+            match poll(&mut [PollFd::new(&self.received, PollFlags::POLLIN)], timeout_ms) {
+                Ok(_) | Err(nix::errno::Errno::EINTR) => {}
+                Err(_) => {
+                    std::thread::sleep(remaining);
+                    return self.drain();
+                }
+            }
+        }
+    }
+}
+
+/// What reading a notification socket found
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Drained {
+    Nothing,
+    Received,
+    /// The other end is gone: the socket stays readable at EOF from now on
+    Closed,
+}
+
+/// Read everything queued on a non-blocking notification socket
+fn drain_socket(socket: &UnixStream) -> Drained {
+    let mut buf = [0u8; 64];
+    let mut drained = Drained::Nothing;
+    while let Ok(len) = (&*socket).read(&mut buf) {
+        if len == 0 {
+            // Report what came before the close first, the close on the next call
+            return match drained {
+                Drained::Received => Drained::Received,
+                _ => Drained::Closed,
+            };
+        }
+        drained = Drained::Received;
+    }
+    drained
+}
+
+/// Why `Wakeups::wait` returned early
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wakeup {
+    /// AC plugged/unplugged or a battery state change
+    PowerSupply,
+    /// The system just resumed from suspend
+    Resume,
+    /// SIGTERM or SIGINT: restore settings and exit
+    Shutdown,
+    /// A command on the control socket changed a setting
+    Control,
+    /// The config file was reloaded with different settings
+    ConfigReload,
+}
+
+/// Everything that can cut the daemon's wait short. Sources that can't be set
+/// up (e.g. no netlink in a restricted container, no busctl) are skipped with a
+/// warning; with none left the wait is a plain sleep.
+pub struct Wakeups {
+    uevents: Option<UeventMonitor>,
+    sleep: Option<SleepMonitor>,
+    /// Times the sleep monitor was started again after busctl exited
+    sleep_restarts: u32,
+    shutdown: Option<ShutdownSignal>,
+    control: Option<ControlSocket>,
+    config_reloads: Option<UnixStream>,
+}
+
+impl Wakeups {
+    pub fn open() -> Self {
+        let uevents = UeventMonitor::open()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "{}, falling back to polling", e))
+            .ok();
+        let sleep = SleepMonitor::open()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "{}, settings are re-applied at the next check after resume", e))
+            .ok();
+        let shutdown = ShutdownSignal::install()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "{}, stopping won't restore settings", e))
+            .ok();
+        let control = ControlSocket::bind()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "{}, --force and --turbo apply at the next check", e))
+            .ok();
+        let config_reloads = CONFIG
+            .subscribe()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "{}, config changes apply at the next check", e))
+            .ok();
+        Self { uevents, sleep, sleep_restarts: 0, shutdown, control, config_reloads }
+    }
+
+    /// busctl exited: start it again a few times, then do without resume events
+    /// rather than polling its closed pipe in a busy loop
+    fn restart_sleep_monitor(&mut self) {
+        self.sleep = None;
+        if self.sleep_restarts >= SLEEP_MONITOR_RESTARTS {
+            warn!(code = %EventCode::EventSourceFailed, "busctl monitor keeps exiting, settings are re-applied at the next check after resume");
+            return;
+        }
+        self.sleep_restarts += 1;
+        self.sleep = SleepMonitor::open()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "{}, settings are re-applied at the next check after resume", e))
+            .ok();
+    }
+
+    /// Block until `deadline`, returning early on a power_supply event, a
+    /// resume from suspend, a shutdown signal, a control command or a config
+    /// reload
+    pub fn wait_until(&mut self, deadline: Instant) -> Option<Wakeup> {
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+
+            let mut fds = Vec::new();
+            if let Some(uevents) = &self.uevents {
+                fds.push(PollFd::new(&uevents.fd, PollFlags::POLLIN));
+            }
+            if let Some(sleep) = &self.sleep {
+                fds.push(PollFd::new(&sleep.resumed, PollFlags::POLLIN));
+            }
+            if let Some(shutdown) = &self.shutdown {
+                fds.push(PollFd::new(&shutdown.received, PollFlags::POLLIN));
+            }
+            if let Some(control) = &self.control {
+                fds.push(PollFd::new(control, PollFlags::POLLIN));
+            }
+            if let Some(config_reloads) = &self.config_reloads {
+                fds.push(PollFd::new(config_reloads, PollFlags::POLLIN));
+            }
+            if fds.is_empty() {
+                std::thread::sleep(remaining);
+                return None;
+            }
+
+            // Round up so the deadline isn't missed by a fraction of a millisecond
+            let timeout_ms = remaining.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32;
+            match poll(&mut fds, timeout_ms) {
+                Ok(n) if n > 0 => {}
+                // A signal arrived, its byte is read on the next round
+                Err(nix::errno::Errno::EINTR) => continue,
+                _ => return None,
+            }
+
+            if self.shutdown.as_ref().is_some_and(|shutdown| shutdown.drain()) {
+                return Some(Wakeup::Shutdown);
+            }
+
+            match self.sleep.as_ref().map(SleepMonitor::drain) {
+                Some(Drained::Received) => {
+                    // Drop the uevents the resume itself triggers
+                    std::thread::sleep(DEBOUNCE);
+                    if let Some(uevents) = &self.uevents {
+                        uevents.drain();
+                    }
+                    return Some(Wakeup::Resume);
+                }
+                Some(Drained::Closed) => self.restart_sleep_monitor(),
+                _ => {}
+            }
+
+            if self.uevents.as_ref().is_some_and(|uevents| uevents.drain()) {
+                // Let the rest of the burst arrive, then swallow it
+                std::thread::sleep(DEBOUNCE);
+                if let Some(uevents) = &self.uevents {
+                    uevents.drain();
+                }
+                return Some(Wakeup::PowerSupply);
+            }
+
+            // Status requests are answered without waking the loop
+            let control = match self.control.as_ref().map(|control| drain_socket(control.changes())) {
+                Some(Drained::Received) => true,
+                Some(Drained::Closed) => {
+                    self.control = None;
+                    false
+                }
+                _ => false,
+            };
+            // Checked after the control socket, whose reload-config reloads in place
+            match self.config_reloads.as_ref().map(drain_socket) {
+                Some(Drained::Received) => return Some(Wakeup::ConfigReload),
+                Some(Drained::Closed) => self.config_reloads = None,
+                _ => {}
+            }
+            if control {
+                return Some(Wakeup::Control);
+            }
+        }
+    }
+}
+
+/// Bounds of the load polling interval, from `[daemon] min_interval` and
+/// `[daemon] max_interval` (seconds, fractions allowed)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalLimits {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Default for IntervalLimits {
+    fn default() -> Self {
+        Self { min: DEFAULT_MIN_INTERVAL, max: DEFAULT_MAX_INTERVAL }
+    }
+}
+
+impl IntervalLimits {
+    pub fn from_config(config: &Snapshot) -> Self {
+        let seconds = |key: &str, default: Duration| match config.get("daemon", key) {
+            None => default,
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(secs) if secs > 0.0 && secs.is_finite() => Duration::from_secs_f64(secs),
+                _ => {
+                    warn!(code = %EventCode::ConfigInvalid, "Invalid {} in [daemon]: {}", key, value);
+                    default
+                }
+            },
+        };
+
+        let min = seconds("min_interval", DEFAULT_MIN_INTERVAL);
+        let max = seconds("max_interval", DEFAULT_MAX_INTERVAL);
+        if max < min {
+            warn!(code = %EventCode::ConfigInvalid, "[daemon] max_interval is below min_interval, using {}s", min.as_secs_f64());
+        }
+
+        Self { min, max: max.max(min) }
+    }
+}
+
+/// Polling interval for load changes: back to the minimum whenever usage moves
+/// or the power source flips, doubled after each quiet cycle. On AC the
+/// ceiling is lower since saving wakeups matters less there.
+pub struct PollInterval {
+    current: Duration,
+    last_usage: Option<f32>,
+    last_charging: Option<bool>,
+}
+
+impl Default for PollInterval {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PollInterval {
+    pub fn new() -> Self {
+        Self {
+            current: Duration::ZERO,
+            last_usage: None,
+            last_charging: None,
+        }
+    }
+
+    pub fn next(&mut self, limits: &IntervalLimits, cpu_usage: f32, is_charging: bool) -> Duration {
+        let usage_changed = self
+            .last_usage
+            .is_some_and(|last| (cpu_usage - last).abs() >= USAGE_CHANGE_THRESHOLD);
+        let power_changed = self.last_charging.is_some_and(|last| last != is_charging);
+
+        let ceiling = if is_charging {
+            limits.max.min(AC_MAX_INTERVAL).max(limits.min)
+        } else {
+            limits.max
+        };
+
+        self.current = if usage_changed || power_changed || self.current.is_zero() {
+            limits.min
+        } else {
+            (self.current * 2).clamp(limits.min, ceiling)
+        };
+
+        self.last_usage = Some(cpu_usage);
+        self.last_charging = Some(is_charging);
+        self.current
+    }
+}
+
+/// Deadlines of the daemon cycles on the monotonic clock. Each one is the
+/// previous deadline plus the interval rather than "now" plus the interval, so
+/// the time spent in a cycle doesn't accumulate as drift and stats timestamps
+/// stay evenly spaced.
+#[derive(Debug, Default)]
+pub struct CycleSchedule {
+    deadline: Option<Instant>,
+}
+
+impl CycleSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deadline of the next cycle
+    pub fn next(&mut self, interval: Duration) -> Instant {
+        self.next_at(Instant::now(), interval)
+    }
+
+    fn next_at(&mut self, now: Instant, interval: Duration) -> Instant {
+        // A cycle that overran by a whole interval (or a suspend) starts a new
+        // cadence instead of firing the missed cycles back to back
+        let base = match self.deadline {
+            Some(deadline) if now.saturating_duration_since(deadline) < interval => deadline,
+            _ => now,
+        };
+        let deadline = base + interval;
+        self.deadline = Some(deadline);
+        deadline
+    }
+
+    /// Start a new cadence, after an event woke the daemon early
+    pub fn reset(&mut self) {
+        self.deadline = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closed_sleep_monitor_is_dropped() {
+        let (resumed, notify) = UnixStream::pair().unwrap();
+        resumed.set_nonblocking(true).unwrap();
+        let child = crate::command::new("true").spawn().unwrap();
+        let mut wakeups = Wakeups {
+            uevents: None,
+            sleep: Some(SleepMonitor { child, resumed }),
+            sleep_restarts: SLEEP_MONITOR_RESTARTS,
+            shutdown: None,
+            control: None,
+            config_reloads: None,
+        };
+
+        // What busctl's reader thread does when it exits
+        (&notify).write_all(&[1]).unwrap();
+        drop(notify);
+        assert_eq!(wakeups.wait_until(Instant::now() + Duration::from_secs(1)), Some(Wakeup::Resume));
+        // At EOF: dropped instead of waking the loop over and over
+        let started = Instant::now();
+        assert_eq!(wakeups.wait_until(started + Duration::from_millis(200)), None);
+        assert!(wakeups.sleep.is_none());
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_parse_uevent() {
+        let msg = b"change@/devices/LNXSYSTM:00/ACPI0003:00/power_supply/AC\0ACTION=change\0\
+                    DEVPATH=/devices/LNXSYSTM:00/ACPI0003:00/power_supply/AC\0\
+                    SUBSYSTEM=power_supply\0POWER_SUPPLY_ONLINE=1\0SEQNUM=4242\0";
+        let event = parse_uevent(msg).unwrap();
+        assert_eq!(event.action, "change");
+        assert!(event.devpath.ends_with("power_supply/AC"));
+        assert_eq!(event.subsystem(), Some("power_supply"));
+        assert_eq!(event.properties.get("POWER_SUPPLY_ONLINE").map(String::as_str), Some("1"));
+
+        assert_eq!(parse_uevent(b"libudev\0garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_prepare_for_sleep() {
+        let signal = |data: bool| {
+            format!(
+                r#"{{"type":"signal","sender":":1.4","path":"/org/freedesktop/login1","interface":"org.freedesktop.login1.Manager","member":"PrepareForSleep","payload":{{"type":"b","data":[{}]}}}}"#,
+                data
+            )
+        };
+        assert_eq!(parse_prepare_for_sleep(&signal(true)), Some(true));
+        assert_eq!(parse_prepare_for_sleep(&signal(false)), Some(false));
+        assert_eq!(parse_prepare_for_sleep(r#"{"type":"signal","member":"SessionNew","payload":{"data":["3"]}}"#), None);
+        assert_eq!(parse_prepare_for_sleep("Monitoring bus message stream."), None);
+    }
+
+    #[test]
+    fn test_cycle_schedule() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(2);
+        let mut schedule = CycleSchedule::new();
+
+        let first = schedule.next_at(start, interval);
+        assert_eq!(first, start + interval);
+        // Work done in the cycle doesn't push the next deadline out
+        assert_eq!(schedule.next_at(first + Duration::from_millis(300), interval), first + interval);
+
+        // Overrunning by more than an interval re-anchors on the current time
+        let late = first + Duration::from_secs(10);
+        assert_eq!(schedule.next_at(late, interval), late + interval);
+
+        schedule.reset();
+        assert_eq!(schedule.next_at(start, interval), start + interval);
+    }
+
+    #[test]
+    fn test_poll_interval_backs_off_and_resets() {
+        let limits = IntervalLimits::default();
+        let mut interval = PollInterval::new();
+        assert_eq!(interval.next(&limits, 10.0, false), Duration::from_secs(2));
+        assert_eq!(interval.next(&limits, 12.0, false), Duration::from_secs(4));
+        for _ in 0..10 {
+            interval.next(&limits, 12.0, false);
+        }
+        assert_eq!(interval.next(&limits, 11.0, false), DEFAULT_MAX_INTERVAL);
+        assert_eq!(interval.next(&limits, 60.0, false), DEFAULT_MIN_INTERVAL);
+
+        // AC flip resets, and AC caps the back-off lower
+        assert_eq!(interval.next(&limits, 60.0, true), DEFAULT_MIN_INTERVAL);
+        for _ in 0..10 {
+            interval.next(&limits, 60.0, true);
+        }
+        assert_eq!(interval.next(&limits, 60.0, true), AC_MAX_INTERVAL);
+    }
+
+    #[test]
+    fn test_interval_limits_from_config() {
+        let config = Snapshot::from_str_ini("[daemon]\nmin_interval = 1\nmax_interval = 20\n").unwrap();
+        let limits = IntervalLimits::from_config(&config);
+        assert_eq!(limits.min, Duration::from_secs(1));
+        assert_eq!(limits.max, Duration::from_secs(20));
+
+        let config = Snapshot::from_str_ini("[daemon]\nmin_interval = 5\nmax_interval = 3\n").unwrap();
+        assert_eq!(IntervalLimits::from_config(&config).max, Duration::from_secs(5));
+
+        let config = Snapshot::from_str_ini("[daemon]\nmin_interval = 0.5\n").unwrap();
+        assert_eq!(IntervalLimits::from_config(&config).min, Duration::from_millis(500));
+
+        let config = Snapshot::from_str_ini("[daemon]\nmin_interval = fast\n").unwrap();
+        assert_eq!(IntervalLimits::from_config(&config), IntervalLimits::default());
+    }
+}
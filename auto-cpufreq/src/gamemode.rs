@@ -0,0 +1,147 @@
+// src/gamemode.rs
+//
+// Feral GameMode integration. gamemoded runs on each user's session bus and
+// counts the games registered with it (its ClientCount property). While that
+// count is above zero, `set_autofreq` uses a performance profile in place of
+// the selected one; once the last game exits the regular settings come back on
+// the next cycle. The daemon runs as root, so each logged-in user's bus is
+// queried through `busctl --machine=<user>@.host`.
+
+use std::fs;
+use std::time::Duration;
+
+use nix::unistd::{Uid, User};
+use tracing::{info, warn};
+
+use crate::config::{parse_bool, CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::power_helper::does_command_exist;
+use crate::profiles::{self, Profile, ProfileSwitch};
+
+const BUS_NAME: &str = "com.feralinteractive.GameMode";
+const OBJECT_PATH: &str = "/com/feralinteractive/GameMode";
+const RUNTIME_DIR: &str = "/run/user";
+
+/// Profile used while a game is registered, unless `gamemode_profile` is set
+const DEFAULT_PROFILE: &str = "max-performance";
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+lazy_static::lazy_static! {
+    static ref GAMEMODE_EXISTS: bool = does_command_exist("gamemoded") && does_command_exist("busctl");
+}
+
+/// Keyed by the profile name
+static SWITCH: ProfileSwitch<String> = ProfileSwitch::new();
+
+/// `[integration] respect_gamemode`, on by default
+pub fn enabled(config: &Snapshot) -> bool {
+    config
+        .get("integration", "respect_gamemode")
+        .and_then(parse_bool)
+        .unwrap_or(true)
+}
+
+/// Parse `busctl get-property` output for an int32 ("i 2")
+fn parse_client_count(stdout: &str) -> Option<i32> {
+    stdout.trim().strip_prefix("i ")?.trim().parse().ok()
+}
+
+/// Users with a session bus (`/run/user/<uid>/bus`)
+fn session_users() -> Vec<Uid> {
+    fs::read_dir(RUNTIME_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().join("bus").exists())
+                .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+                .map(Uid::from_raw)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn client_count(uid: Uid) -> Option<i32> {
+    let machine;
+    let mut args = vec!["--user"];
+    if uid != Uid::effective() {
+        machine = format!("{}@.host", User::from_uid(uid).ok()??.name);
+        args.extend(["--machine", machine.as_str()]);
+    }
+    args.extend(["get-property", BUS_NAME, OBJECT_PATH, BUS_NAME, "ClientCount"]);
+
+    let output = crate::command::run_with_timeout("busctl", &args, QUERY_TIMEOUT).ok()?;
+    output.success().then(|| parse_client_count(&output.stdout)).flatten()
+}
+
+/// Whether gamemoded (and busctl to query it) is installed
+pub fn is_installed() -> bool {
+    *GAMEMODE_EXISTS
+}
+
+/// Whether any user has a game registered with GameMode
+pub fn is_active() -> bool {
+    *GAMEMODE_EXISTS && session_users().into_iter().any(|uid| client_count(uid).is_some_and(|n| n > 0))
+}
+
+/// The `gamemode_profile` and its name while a game is registered
+fn current() -> Option<(String, Profile)> {
+    let config = CONFIG.snapshot();
+    if !enabled(&config) || !is_active() {
+        return None;
+    }
+    let name = config.get("integration", "gamemode_profile").unwrap_or(DEFAULT_PROFILE);
+    match profiles::load(&config, name) {
+        Ok(profile) => Some((name.to_string(), profile)),
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "Invalid gamemode_profile: {}", e);
+            None
+        }
+    }
+}
+
+/// Called by `set_autofreq`: the profile to use while a game is registered.
+/// Frequency limits set by it are lifted again once GameMode goes idle.
+pub fn profile() -> Option<Profile> {
+    SWITCH.follow(current(), |name| match name {
+        Some((name, _)) => info!("GameMode: game registered, using the {} profile", name),
+        None => info!("GameMode: no games registered, back to the regular settings"),
+    })
+}
+
+pub fn preview() -> Option<Profile> {
+    SWITCH.preview(current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gamemode_settings() {
+        assert_eq!(parse_client_count("i 2\n"), Some(2));
+        assert_eq!(parse_client_count("i 0"), Some(0));
+        assert_eq!(parse_client_count("s \"x\""), None);
+
+        let config = Snapshot::from_str_ini("[integration]\nrespect_gamemode = false\n").unwrap();
+        assert!(!enabled(&config));
+        assert!(enabled(&Snapshot::from_str_ini("[battery]\nturbo = auto\n").unwrap()));
+    }
+
+    #[test]
+    fn test_gamemode_lifts_lower_caps() {
+        let config = Snapshot::from_str_ini("[profile.battery-low]\nscaling_max_freq = 1400000\n").unwrap();
+        let game = profiles::load(&config, DEFAULT_PROFILE).unwrap();
+        assert!(!game.sets_frequency_limits());
+
+        // Battery level tiers or idle capped the last cycle, then a game registers
+        let battery_low = profiles::load(&config, "battery-low").unwrap();
+        let idle = Profile { name: "idle".to_string(), scaling_max_freq: Some(1_200_000), ..Profile::default() };
+        for capped in [battery_low, idle] {
+            let limits = profiles::AppliedLimits::new();
+            assert!(!limits.follow(Some(&capped)));
+            assert!(limits.follow(Some(&game)));
+            assert!(!limits.follow(Some(&game)));
+        }
+    }
+}
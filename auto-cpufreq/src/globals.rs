@@ -1,7 +1,6 @@
 // src/globals.rs
 
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
 pub const ALL_GOVERNORS: &[&str] = &[
     "performance",
@@ -12,9 +11,6 @@ pub const ALL_GOVERNORS: &[&str] = &[
     "powersave",
 ];
 
-pub const CONSERVATION_MODE_FILE: &str = 
-    "/sys/bus/platform/drivers/ideapad_acpi/VPC2004:00/conservation_mode";
-
 pub const GITHUB: &str = "https://github.com/Zamanhuseyinli/auto-cpufreq-rust";
 
 pub const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply/";
@@ -26,29 +22,112 @@ pub const CPU_TEMP_SENSOR_PRIORITY: &[&str] = &[
     "zenpower",
 ];
 
+/// Install prefix baked in at build time (`AUTO_CPUFREQ_PREFIX=/opt/auto-cpufreq cargo build`)
+pub const DEFAULT_PREFIX: &str = match option_env!("AUTO_CPUFREQ_PREFIX") {
+    Some(prefix) => prefix,
+    None => "/usr/local",
+};
+
 lazy_static::lazy_static! {
+    pub static ref PREFIX: PathBuf = resolve_prefix();
     pub static ref IS_INSTALLED_WITH_AUR: bool = check_aur_install();
     pub static ref AVAILABLE_GOVERNORS: Vec<String> = get_available_governors();
     pub static ref AVAILABLE_GOVERNORS_SORTED: Vec<String> = sort_governors(&AVAILABLE_GOVERNORS);
 }
 
+// ============================================================================
+// Install paths
+// ============================================================================
+
+/// Prefix lookup order: `AUTO_CPUFREQ_PREFIX` at runtime, then the directory the
+/// running binary was installed into (`<prefix>/bin/auto-cpufreq` with a matching
+/// `<prefix>/share/auto-cpufreq`), then the compile-time default. The middle step
+/// is what keeps relocated static builds (/opt, Nix store) working unconfigured.
+/// Helpers under the prefix run as root, so the first two are only used when
+/// `trusted` holds for them.
+fn resolve_prefix() -> PathBuf {
+    let from_env = std::env::var_os("AUTO_CPUFREQ_PREFIX").filter(|p| !p.is_empty()).map(PathBuf::from);
+    let from_exe = || std::env::current_exe().ok().and_then(|exe| prefix_from_exe(&exe));
+
+    from_env
+        .filter(|prefix| trusted(prefix))
+        .or_else(|| from_exe().filter(|prefix| trusted(prefix)))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_PREFIX))
+}
+
+/// Whether `prefix`, its `bin` directory and every directory above them are
+/// owned by root and not world-writable, so nobody else can swap in the
+/// scripts and helpers run from there
+fn trusted(prefix: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(bin) = prefix.join("bin").canonicalize() else {
+        return false;
+    };
+    bin.ancestors().all(|dir| {
+        std::fs::metadata(dir).is_ok_and(|meta| meta.uid() == 0 && meta.mode() & 0o002 == 0)
+    })
+}
+
+fn prefix_from_exe(exe: &Path) -> Option<PathBuf> {
+    let bin = exe.parent()?;
+    if bin.file_name()? != "bin" {
+        return None;
+    }
+    let prefix = bin.parent()?;
+    prefix
+        .join("share/auto-cpufreq")
+        .is_dir()
+        .then(|| prefix.to_path_buf())
+}
+
+pub fn share_dir() -> PathBuf {
+    PREFIX.join("share/auto-cpufreq")
+}
+
+pub fn scripts_dir() -> PathBuf {
+    share_dir().join("scripts")
+}
+
+pub fn bin_dir() -> PathBuf {
+    PREFIX.join("bin")
+}
+
+pub fn cpufreqctl_path() -> PathBuf {
+    bin_dir().join("cpufreqctl.auto-cpufreq")
+}
+
+pub fn icon_path() -> PathBuf {
+    share_dir().join("images/icon.png")
+}
+
+pub fn style_css_path() -> PathBuf {
+    scripts_dir().join("style.css")
+}
+
+pub fn locale_dir() -> PathBuf {
+    share_dir().join("locale")
+}
+
+/// Rewrite the stock `/usr/local` paths in bundled scripts and service files to
+/// the actual prefix
+pub fn relocate(content: &str, prefix: &Path) -> String {
+    if prefix == Path::new("/usr/local") {
+        return content.to_string();
+    }
+    content.replace("/usr/local/", &format!("{}/", prefix.display()))
+}
+
 fn check_aur_install() -> bool {
     Path::new("/etc/arch-release").exists()
-        && Command::new("pacman")
-            .args(&["-Qs", "auto-cpufreq"])
-            .output()
-            .map(|o| !o.stdout.is_empty())
-            .unwrap_or(false)
+        && crate::command::run("pacman", &["-Qs", "auto-cpufreq"]).is_ok_and(|o| !o.stdout.is_empty())
 }
 
 fn get_available_governors() -> Vec<String> {
-    Command::new("cat")
-        .arg("/sys/devices/system/cpu/cpu0/cpufreq/scaling_available_governors")
-        .output()
+    crate::command::run("cat", &["/sys/devices/system/cpu/cpu0/cpufreq/scaling_available_governors"])
         .ok()
-        .and_then(|output| String::from_utf8(output.stdout).ok())
-        .map(|s| {
-            s.trim()
+        .map(|output| {
+            output.stdout
                 .split_whitespace()
                 .map(String::from)
                 .collect()
@@ -78,4 +157,26 @@ mod tests {
         assert_eq!(ALL_GOVERNORS[0], "performance");
         assert_eq!(ALL_GOVERNORS[ALL_GOVERNORS.len() - 1], "powersave");
     }
+
+    #[test]
+    fn test_relocate() {
+        let unit = "ExecStart=/usr/local/bin/auto-cpufreq --daemon";
+        assert_eq!(relocate(unit, Path::new("/usr/local")), unit);
+        assert_eq!(
+            relocate(unit, Path::new("/opt/auto-cpufreq")),
+            "ExecStart=/opt/auto-cpufreq/bin/auto-cpufreq --daemon"
+        );
+    }
+
+    #[test]
+    fn test_prefix_from_exe() {
+        let dir = std::env::temp_dir().join(format!("acf-prefix-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("share/auto-cpufreq")).unwrap();
+        assert_eq!(prefix_from_exe(&dir.join("bin/auto-cpufreq")), Some(dir.clone()));
+        assert_eq!(prefix_from_exe(&dir.join("libexec/auto-cpufreq")), None);
+        // Below the world-writable temp dir
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        assert!(!trusted(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -13,19 +13,22 @@ use std::rc::Rc;
 use std::process::Command;
 use glib;
 use sysinfo::System; 
+use crate::capabilities::Capabilities;
 use crate::core::*;
 use crate::power_helper::BLUETOOTHCTL_EXISTS;
 use crate::modules::system_info::{SystemInfo, SystemReport};
 use super::objects::*;
+use super::i18n::{self, tr};
+use super::layout;
 
 const HBOX_PADDING: i32 = 20;
 
-fn css_file() -> &'static str {
-    "/usr/local/share/auto-cpufreq/scripts/style.css"
+fn css_file() -> std::path::PathBuf {
+    crate::globals::style_css_path()
 }
 
-fn icon_file() -> &'static str {
-    "/usr/local/share/auto-cpufreq/images/icon.png"
+fn icon_file() -> std::path::PathBuf {
+    crate::globals::icon_path()
 }
 
 pub struct ToolWindow {
@@ -36,6 +39,7 @@ pub struct ToolWindow {
     battery_info: Option<BatteryInfoBox>,
     cpu_freq_scaling: Option<CPUFreqScalingBox>,
     system_stats_box: Option<SystemStatisticsBox>,
+    decision_timeline: Option<DecisionTimeline>,
 }
 
 impl ToolWindow {
@@ -57,6 +61,7 @@ impl ToolWindow {
             battery_info: None,
             cpu_freq_scaling: None,
             system_stats_box: None,
+            decision_timeline: None,
         }));
 
         tool_window
@@ -83,14 +88,14 @@ pub fn build(&mut self) {
     }
 }
 fn check_daemon_running() -> bool {
-    // Method 1: Check via is_running (process list)
-    if is_running("auto-cpufreq", "--daemon") {
+    // Method 1: Check the PID file lock
+    if is_running() {
         return true;
     }
 
     // Method 2: Check systemd service
-    if let Ok(output) = Command::new("systemctl")
-        .args(&["is-active", "auto-cpufreq"])
+    if let Ok(output) = crate::command::new("systemctl")
+        .args(["is-active", "auto-cpufreq"])
         .output()
     {
         let status = String::from_utf8_lossy(&output.stdout);
@@ -119,16 +124,16 @@ fn check_daemon_running() -> bool {
         vbox.set_halign(gtk::Align::Center);
         vbox.set_valign(gtk::Align::Center);
 
-        let label = Label::new(Some("auto-cpufreq daemon is not running"));
+        let label = Label::new(Some(&tr("auto-cpufreq daemon is not running")));
         let sublabel = Label::new(Some(
-            "Install the daemon for permanent optimization, or use Monitor mode to preview"
+            &tr("Install the daemon for permanent optimization, or use Monitor mode to preview")
         ));
 
         let button_box = GtkBox::new(Orientation::Horizontal, 10);
         button_box.set_halign(gtk::Align::Center);
 
-        let install_button = Button::with_label("Install Daemon");
-        let monitor_button = Button::with_label("Monitor Mode");
+        let install_button = Button::with_label(&tr("Install Daemon"));
+        let monitor_button = Button::with_label(&tr("Monitor Mode"));
 
         // Clone window for closures
         let window_clone = self.window.clone();
@@ -175,25 +180,44 @@ fn check_daemon_running() -> bool {
         let radio_view = RadioButtonView::new();
         vbox_right.append(radio_view.widget());
 
+        let capabilities = Capabilities::detect();
+
         // Turbo override (if supported)
-        let stats = get_stats();
-        if !stats.contains("Warning: CPU turbo is not available") {
+        if capabilities.turbo.available {
             let turbo_view = CPUTurboOverride::new();
             vbox_right.append(turbo_view.widget());
         }
 
+        // Power profile selection
+        let profile_selector = ProfileSelector::new();
+        vbox_right.append(profile_selector.widget());
+
+        // Charging threshold sets, only when the config defines some
+        if let Some(battery_profiles) = ProfileSelector::battery() {
+            vbox_right.append(battery_profiles.widget());
+        }
+
+        // Charge behaviour, only when the battery driver supports it
+        if let Some(charge_behaviour) = ProfileSelector::charge_behaviour() {
+            vbox_right.append(charge_behaviour.widget());
+        }
+
         // Battery info
         let battery_info = BatteryInfoBox::new();
         vbox_right.append(battery_info.widget());
 
         // CPU Freq Scaling
-        let cpu_freq_scaling = CPUFreqScalingBox::new();
+        let cpu_freq_scaling = CPUFreqScalingBox::new(&capabilities);
         vbox_right.append(cpu_freq_scaling.widget());
 
         // System Statistics
         let system_stats_box = SystemStatisticsBox::new();
         vbox_right.append(system_stats_box.widget());
 
+        // Last governor decisions
+        let decision_timeline = DecisionTimeline::new();
+        vbox_right.append(decision_timeline.widget());
+
         // Bluetooth control
         if *BLUETOOTHCTL_EXISTS {
             let bluetooth_control = BluetoothBootControl::new();
@@ -206,6 +230,12 @@ fn check_daemon_running() -> bool {
 
         hbox.append(&scrolled_right);
 
+        // Stack the stats and controls columns on narrow (portrait/tablet) windows
+        layout::apply_adaptive_orientation(&hbox, self.window.default_width());
+        self.window.connect_default_width_notify(clone!(@weak hbox => move |window| {
+            layout::apply_adaptive_orientation(&hbox, window.default_width());
+        }));
+
         self.window.set_child(Some(&hbox));
 
         // Store references for refresh
@@ -215,6 +245,7 @@ fn check_daemon_running() -> bool {
         self.battery_info = Some(battery_info);
         self.cpu_freq_scaling = Some(cpu_freq_scaling);
         self.system_stats_box = Some(system_stats_box);
+        self.decision_timeline = Some(decision_timeline);
 
         // Setup auto-refresh
         self.setup_refresh();
@@ -226,6 +257,7 @@ fn check_daemon_running() -> bool {
         let battery_info = self.battery_info.clone();
         let cpu_freq_scaling = self.cpu_freq_scaling.clone();
         let system_stats_box = self.system_stats_box.clone();
+        let decision_timeline = self.decision_timeline.clone();
 
         glib::timeout_add_seconds_local(5, move || {
             if let Some(ref stats) = system_stats {
@@ -248,6 +280,10 @@ fn check_daemon_running() -> bool {
                 let mut stats_box_mut = stats_box.clone();
                 stats_box_mut.refresh();
             }
+            if let Some(ref timeline) = decision_timeline {
+                let mut timeline_mut = timeline.clone();
+                timeline_mut.refresh();
+            }
 
             glib::ControlFlow::Continue
         });
@@ -338,13 +374,13 @@ impl MonitorModeView {
         let header = GtkBox::new(Orientation::Horizontal, 0);
         header.set_margin_bottom(10);
 
-        let title = Label::new(Some("Monitor Mode"));
+        let title = Label::new(Some(&tr("Monitor Mode")));
         title.set_widget_name("bold");
         title.set_halign(gtk::Align::Start);
         title.set_hexpand(true);
         header.append(&title);
 
-        let back_button = Button::with_label("Back");
+        let back_button = Button::with_label(&tr("Back"));
         let window_weak = parent_window.downgrade();
         let running = Rc::new(RefCell::new(true));
         let running_clone = running.clone();
@@ -357,16 +393,16 @@ impl MonitorModeView {
                 vbox.set_halign(gtk::Align::Center);
                 vbox.set_valign(gtk::Align::Center);
 
-                let label = Label::new(Some("auto-cpufreq daemon is not running"));
+                let label = Label::new(Some(&tr("auto-cpufreq daemon is not running")));
                 let sublabel = Label::new(Some(
-                    "Install the daemon for permanent optimization, or use Monitor mode to preview"
+                    &tr("Install the daemon for permanent optimization, or use Monitor mode to preview")
                 ));
 
                 let button_box = GtkBox::new(Orientation::Horizontal, 10);
                 button_box.set_halign(gtk::Align::Center);
 
-                let install_button = Button::with_label("Install Daemon");
-                let monitor_button = Button::with_label("Monitor Mode");
+                let install_button = Button::with_label(&tr("Install Daemon"));
+                let monitor_button = Button::with_label(&tr("Monitor Mode"));
 
                 let window_clone = window.clone();
                 install_button.connect_clicked(move |_| {
@@ -489,10 +525,8 @@ impl MonitorModeView {
         label
     }
 
-    fn create_separator(text: &str) -> Label {
-        let label = Label::new(Some(&format!("{} {} {}", "-".repeat(28), text, "-".repeat(28))));
-        label.set_halign(gtk::Align::Start);
-        label
+    fn create_separator(text: &str) -> GtkBox {
+        i18n::section_header(text)
     }
 
     fn update_display(left_box: &GtkBox, right_box: &GtkBox, title: &Label, report: &SystemReport) {
@@ -500,7 +534,7 @@ impl MonitorModeView {
         Self::clear_box(right_box);
 
         let current_time = chrono::Local::now().format("%H:%M:%S");
-        title.set_text(&format!("Monitor Mode - {}", current_time));
+        title.set_text(&format!("{} - {}", tr("Monitor Mode"), current_time));
 
         left_box.append(&Self::create_separator("System Information"));
         left_box.append(&Self::create_label(&format!("Linux distro: {} {}", report.distro_name, report.distro_ver), gtk::Align::Start));
@@ -530,7 +564,7 @@ impl MonitorModeView {
 
         for core in &report.cores_info {
             left_box.append(&Self::create_label(
-                &format!("CPU{:<2}    {:>4.1}%    {:>6.0} °C    {:>6.0} MHz", core.id, core.usage, core.temperature, core.frequency),
+                &format!("CPU{:<2}    {:>4.1}%    {:>9}    {:>6.0} MHz", core.id, core.usage, core.temperature.map(|t| format!("{:.0} °C", t)).unwrap_or_else(|| "-- °C".to_string()), core.frequency),
                 gtk::Align::Start
             ));
         }
@@ -599,8 +633,7 @@ impl MonitorModeView {
         right_box.append(&Self::create_label(&format!("Total CPU usage: {:.1} %", report.cpu_usage), gtk::Align::Start));
         right_box.append(&Self::create_label(&format!("Total system load: {:.2}", report.load), gtk::Align::Start));
 
-        if !report.cores_info.is_empty() {
-            let avg_temp: f32 = report.cores_info.iter().map(|c| c.temperature).sum::<f32>() / report.cores_info.len() as f32;
+        if let Some(avg_temp) = report.average_temperature() {
             right_box.append(&Self::create_label(&format!("Average temp. of all cores: {:.2} °C", avg_temp), gtk::Align::Start));
         }
 
@@ -612,10 +645,9 @@ impl MonitorModeView {
             ));
         }
 
-        if !report.cores_info.is_empty() {
-            let avg_temp: f32 = report.cores_info.iter().map(|c| c.temperature).sum::<f32>() / report.cores_info.len() as f32;
+        if let Some(avg_temp) = report.average_temperature() {
             let usage_status = if report.cpu_usage < 70.0 { "Optimal" } else { "High" };
-            let temp_status = if avg_temp > 75.0 { "high" } else { "normal" };
+            let temp_status = if avg_temp > crate::thermal::turbo_temp_limit() { "high" } else { "normal" };
             right_box.append(&Self::create_label(
                 &format!("{} total CPU usage: {:.1}%, {} average core temp: {:.1}°C", usage_status, report.cpu_usage, temp_status, avg_temp),
                 gtk::Align::Start
@@ -655,11 +687,48 @@ impl Drop for MonitorModeView {
 }
 
 pub fn run_app() {
+    if !super::root_guard::check("auto-cpufreq-gtk") {
+        std::process::exit(1);
+    }
+
+    // Without a session bus GApplication can't enforce a single instance, so
+    // guard with a lock file instead and run non-unique. Without a runtime dir
+    // for the lock either, the GUI runs unguarded.
+    let unique = super::instance::session_bus_available();
+    let _lock = if unique {
+        None
+    } else {
+        match super::instance::InstanceLock::acquire() {
+            Ok(lock) => lock,
+            Err(e) => {
+                eprintln!("{:#}", e);
+                return;
+            }
+        }
+    };
+
+    let flags = if unique {
+        gtk::gio::ApplicationFlags::empty()
+    } else {
+        gtk::gio::ApplicationFlags::NON_UNIQUE
+    };
+
     let app = Application::builder()
-        .application_id("org.auto_cpufreq.GUI")
+        .application_id(super::instance::application_id())
+        .flags(flags)
         .build();
 
+    // Needed for `[gui]` settings and the "Using settings defined in ..." labels
+    let _ = crate::CONFIG.set_path(crate::find_config_file(None));
+
     app.connect_activate(|app| {
+        // A second launch lands here in the primary instance: raise the window
+        // we already have instead of probing the system all over again
+        if let Some(window) = app.active_window().or_else(|| app.windows().into_iter().next()) {
+            window.present();
+            return;
+        }
+
         let tool_window = ToolWindow::new(app);
         ToolWindow::load_css();
         
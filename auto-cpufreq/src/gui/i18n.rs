@@ -0,0 +1,113 @@
+// src/gui/i18n.rs
+
+use std::collections::HashMap;
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Label, Orientation, Separator};
+
+
+lazy_static::lazy_static! {
+    static ref CATALOG: HashMap<String, String> = load_catalog();
+}
+
+/// Language candidates from the environment, most specific first
+/// (e.g. `de_DE.UTF-8` -> ["de_DE", "de"])
+fn language_candidates() -> Vec<String> {
+    let lang = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+
+    let lang = lang.split(['.', '@']).next().unwrap_or("");
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut candidates = vec![lang.to_string()];
+    if let Some((short, _)) = lang.split_once('_') {
+        candidates.push(short.to_string());
+    }
+    candidates
+}
+
+/// Catalogs are plain ini files (`<lang>.ini`) with a `[strings]` section
+/// mapping the English text to its translation
+fn load_catalog() -> HashMap<String, String> {
+    for lang in language_candidates() {
+        let path = crate::globals::locale_dir().join(format!("{}.ini", lang));
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            return parse_catalog(&content);
+        }
+    }
+
+    HashMap::new()
+}
+
+/// The `[strings]` entries of a catalog, keys exactly as the English text is
+/// written in the code. Read by hand rather than with configparser, which
+/// lowercases keys and also splits on the `:` many messages contain.
+fn parse_catalog(content: &str) -> HashMap<String, String> {
+    let mut in_strings = false;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(['#', ';']))
+        .filter_map(|line| {
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_strings = section.trim() == "strings";
+                return None;
+            }
+            let (msgid, translation) = line.split_once('=')?;
+            in_strings.then(|| (msgid.trim().to_string(), translation.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Translate a GUI string, falling back to the English text
+pub fn tr(msgid: &str) -> String {
+    CATALOG.get(msgid).cloned().unwrap_or_else(|| msgid.to_string())
+}
+
+/// Translated section header shared by the main view and monitor mode: the
+/// title between two rules, as long as the `section-header` rule in style.css
+/// makes them
+pub fn section_header(title: &str) -> GtkBox {
+    let header = GtkBox::new(Orientation::Horizontal, 6);
+    header.set_halign(gtk::Align::Start);
+    header.add_css_class("section-header");
+
+    let rule = || {
+        let separator = Separator::new(Orientation::Horizontal);
+        separator.set_valign(gtk::Align::Center);
+        separator
+    };
+    header.append(&rule());
+    header.append(&Label::new(Some(&tr(title))));
+    header.append(&rule());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_catalog() {
+        let catalog = parse_catalog(
+            "# German\n[strings]\nBattery Stats = Akkustatistik\nRevert after = Zurücksetzen nach\n\
+             Status: ok = Status: gut\n[other]\nBack = Nein\n",
+        );
+        assert_eq!(catalog.get("Battery Stats").map(String::as_str), Some("Akkustatistik"));
+        assert_eq!(catalog.get("Status: ok").map(String::as_str), Some("Status: gut"));
+        // Keys keep their case, and only [strings] counts
+        assert_eq!(catalog.get("battery stats"), None);
+        assert_eq!(catalog.get("Back"), None);
+        assert_eq!(catalog.len(), 3);
+    }
+
+    #[test]
+    fn test_tr_fallback() {
+        assert_eq!(tr("Some untranslated string"), "Some untranslated string");
+    }
+}
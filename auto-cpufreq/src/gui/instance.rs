@@ -0,0 +1,151 @@
+// src/gui/instance.rs
+//
+// Single-instance handling for the GUI. GApplication already makes a second
+// launch activate the first one over the session bus; this module picks an
+// application id that is unique per graphical session (so a user logged into an
+// X11 and a Wayland session at once gets a window on each), and falls back to a
+// lock file in the runtime dir when there is no session bus to register on
+// (e.g. launched via `su`/`sudo` into another user's display).
+
+use std::fs::{self, File, OpenOptions};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use nix::errno::Errno;
+use nix::fcntl::{FlockArg, OFlag, flock};
+
+pub const BASE_APPLICATION_ID: &str = "org.auto_cpufreq.GUI";
+
+/// Identifies the graphical session the GUI is shown on, as a D-Bus name
+/// element (`wayland-0` -> `wayland_0`, `:1.0` -> `x11_1_0`)
+pub fn session_key() -> Option<String> {
+    let raw = match std::env::var("WAYLAND_DISPLAY").ok().filter(|v| !v.is_empty()) {
+        Some(display) => display,
+        None => {
+            let display = std::env::var("DISPLAY").ok().filter(|v| !v.is_empty())?;
+            format!("x11{}", display)
+        }
+    };
+    Some(sanitize(&raw))
+}
+
+fn sanitize(raw: &str) -> String {
+    let mut key: String = raw
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    // Name elements may not start with a digit
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        key.insert(0, '_');
+    }
+    key
+}
+
+pub fn application_id_for(session: Option<&str>) -> String {
+    match session {
+        Some(key) => format!("{}.{}", BASE_APPLICATION_ID, key),
+        None => BASE_APPLICATION_ID.to_string(),
+    }
+}
+
+pub fn application_id() -> String {
+    application_id_for(session_key().as_deref())
+}
+
+/// Whether GApplication can register on a session bus
+pub fn session_bus_available() -> bool {
+    if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some_and(|v| !v.is_empty()) {
+        return true;
+    }
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| Path::new(&dir).join("bus").exists())
+        .unwrap_or(false)
+}
+
+/// Lock file used when there is no session bus, in `XDG_RUNTIME_DIR` so other
+/// users can't plant or squat it. Locked like the daemon's pidfile: the flock is
+/// held for the life of the process and the kernel drops it however we exit.
+pub struct InstanceLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Lock this session's file, `Ok(None)` when there is no runtime dir to
+    /// keep it in and the GUI runs unguarded. Fails when another instance
+    /// holds the lock.
+    pub fn acquire() -> Result<Option<Self>> {
+        let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+        let key = session_key().unwrap_or_else(|| "nodisplay".to_string());
+        let path = PathBuf::from(dir).join(format!("auto-cpufreq-gtk-{}.lock", key));
+        Self::acquire_at(path).map(Some)
+    }
+
+    fn acquire_at(path: PathBuf) -> Result<Self> {
+        // Never follow a symlink planted in place of the lock file
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .custom_flags(OFlag::O_NOFOLLOW.bits())
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        match flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+            Ok(()) => Ok(Self { file, path }),
+            Err(Errno::EWOULDBLOCK) => bail!("auto-cpufreq-gtk is already running in this session"),
+            Err(e) => bail!("Failed to lock {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Removed while still locked, so no other instance can have taken it over
+        let _ = fs::remove_file(&self.path);
+        let _ = flock(self.file.as_raw_fd(), FlockArg::Unlock);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_application_id_is_valid_dbus_name() {
+        assert_eq!(sanitize("wayland-0"), "wayland_0");
+        assert_eq!(sanitize("x11:1.0"), "x11_1_0");
+        assert_eq!(sanitize("0"), "_0");
+        assert_eq!(application_id_for(None), "org.auto_cpufreq.GUI");
+        assert_eq!(
+            application_id_for(Some("wayland_0")),
+            "org.auto_cpufreq.GUI.wayland_0"
+        );
+    }
+
+    #[test]
+    fn test_instance_lock() {
+        let dir = std::env::temp_dir().join(format!("acf-gui-lock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("instance.lock");
+
+        let lock = InstanceLock::acquire_at(path.clone()).unwrap();
+        assert!(InstanceLock::acquire_at(path.clone()).is_err());
+        drop(lock);
+        assert!(!path.exists());
+        let _lock = InstanceLock::acquire_at(path.clone()).unwrap();
+
+        // A planted symlink is refused rather than followed
+        let link = dir.join("planted.lock");
+        std::os::unix::fs::symlink(dir.join("target"), &link).unwrap();
+        assert!(InstanceLock::acquire_at(link).is_err());
+        assert!(!dir.join("target").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
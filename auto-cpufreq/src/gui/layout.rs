@@ -0,0 +1,142 @@
+// src/gui/layout.rs
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Label, Orientation, ToggleButton};
+
+use crate::config::CONFIG;
+
+/// Window width (px) below which the main view stacks its columns vertically
+pub const NARROW_WIDTH: i32 = 720;
+
+/// Minimum height of tappable controls in touch mode (GNOME HIG touch target)
+const TOUCH_TARGET_SIZE: i32 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutMode {
+    Default,
+    Touch,
+}
+
+impl LayoutMode {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        match s.trim() {
+            "touch" | "compact" => Self::Touch,
+            _ => Self::Default,
+        }
+    }
+
+    /// `AUTO_CPUFREQ_GUI_LAYOUT` takes priority over `[gui] layout` in the config file
+    fn detect() -> Self {
+        match std::env::var("AUTO_CPUFREQ_GUI_LAYOUT") {
+            Ok(value) => Self::from_str(&value),
+            Err(_) => Self::from_str(&CONFIG.get("gui", "layout", "default")),
+        }
+    }
+
+    pub fn is_touch(&self) -> bool {
+        *self == Self::Touch
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref LAYOUT_MODE: LayoutMode = LayoutMode::detect();
+}
+
+/// A toggle for one choice of an option row. GTK presses a toggle as soon as
+/// it is clicked; here only `select` does, once the choice went through, so a
+/// cancelled confirmation or a failed authorization leaves the row as it was.
+pub fn choice_button(text: &str) -> ToggleButton {
+    let button = ToggleButton::with_label(text);
+    button.set_halign(gtk::Align::End);
+    button.connect_clicked(|button| button.set_active(false));
+    button
+}
+
+/// Show `active` as the pressed choice of a row; it can't be picked again
+pub fn select(buttons: &[&ToggleButton], active: usize) {
+    for (i, button) in buttons.iter().enumerate() {
+        button.set_active(i == active);
+        button.set_sensitive(i != active);
+    }
+}
+
+/// Callback for a change running in the background: `select` `active` once
+/// it went through
+pub fn select_when_done(buttons: &[&ToggleButton], active: usize) -> impl FnOnce(bool) + 'static {
+    let buttons: Vec<ToggleButton> = buttons.iter().map(|button| (*button).clone()).collect();
+    move |changed| {
+        if changed {
+            select(&buttons.iter().collect::<Vec<_>>(), active);
+        }
+    }
+}
+
+/// Build a labelled row of mutually exclusive toggles.
+///
+/// The default layout keeps the original single horizontal line. In touch mode the
+/// label sits above a linked, homogeneous row of large toggles so each option is an
+/// easy tap target on convertibles.
+pub fn option_row(label: &Label, buttons: &[&ToggleButton]) -> GtkBox {
+    if !LAYOUT_MODE.is_touch() {
+        let row = GtkBox::new(Orientation::Horizontal, 5);
+        row.set_hexpand(true);
+        row.append(label);
+        for button in buttons {
+            row.append(*button);
+        }
+        return row;
+    }
+
+    let row = GtkBox::new(Orientation::Vertical, 6);
+    row.set_hexpand(true);
+    label.set_halign(gtk::Align::Start);
+    row.append(label);
+
+    let toggles = GtkBox::new(Orientation::Horizontal, 0);
+    toggles.set_homogeneous(true);
+    toggles.set_hexpand(true);
+    toggles.add_css_class("linked");
+
+    for button in buttons {
+        button.set_halign(gtk::Align::Fill);
+        button.set_hexpand(true);
+        button.set_height_request(TOUCH_TARGET_SIZE);
+        button.add_css_class("touch");
+        toggles.append(*button);
+    }
+
+    row.append(&toggles);
+    row
+}
+
+/// Switch a two-column container between side-by-side and stacked depending on width.
+///
+/// An `AdwBreakpoint` would do this declaratively, but it needs libadwaita 1.4
+/// bindings built against the same gtk-rs release as `gtk` 0.7 here, and an
+/// `AdwApplicationWindow`; until the GTK stack is bumped this follows the window
+/// width by hand.
+pub fn apply_adaptive_orientation(container: &GtkBox, width: i32) {
+    let orientation = if width > 0 && width < NARROW_WIDTH {
+        Orientation::Vertical
+    } else {
+        Orientation::Horizontal
+    };
+
+    if container.orientation() != orientation {
+        container.set_orientation(orientation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_mode_from_str() {
+        assert_eq!(LayoutMode::from_str("touch"), LayoutMode::Touch);
+        assert_eq!(LayoutMode::from_str("compact"), LayoutMode::Touch);
+        assert_eq!(LayoutMode::from_str("default"), LayoutMode::Default);
+        assert_eq!(LayoutMode::from_str("bogus"), LayoutMode::Default);
+    }
+}
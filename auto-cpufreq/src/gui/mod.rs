@@ -1,8 +1,23 @@
 // src/gui/mod.rs
 
+#[cfg(feature = "gui")]
 pub mod app;
+#[cfg(feature = "gui")]
+pub mod i18n;
+#[cfg(feature = "gui")]
+pub mod instance;
+#[cfg(feature = "gui")]
+pub mod layout;
+#[cfg(feature = "gui")]
 pub mod objects;
+#[cfg(feature = "tray")]
+pub mod privileged;
+#[cfg(feature = "tray")]
+pub mod root_guard;
+#[cfg(feature = "tray")]
 pub mod tray;
 
+#[cfg(feature = "gui")]
 pub use app::ToolWindow;
+#[cfg(feature = "tray")]
 pub use tray::TrayApp;
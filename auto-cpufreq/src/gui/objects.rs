@@ -1,17 +1,38 @@
 // src/gui/objects.rs 
 
-use gtk::{self, Box as GtkBox, Button, Label, Orientation, Revealer, RevealerTransitionType, ScrolledWindow};
+use gtk::{
+    self, Box as GtkBox, Button, ButtonsType, DialogFlags, Label, MessageDialog, MessageType,
+    Orientation, ResponseType, Revealer, RevealerTransitionType, ScrolledWindow, ToggleButton,
+};
 use gtk::prelude::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::fs;
 use std::process::Command;
 use sysinfo::System; 
+use crate::capabilities::Capabilities;
 use crate::core::*;
 use crate::globals::*;
 use crate::power_helper::BLUETOOTHCTL_EXISTS;
 use crate::modules::system_info::SystemInfo;
-
+use super::i18n::{section_header, tr};
+use super::layout::{choice_button, option_row, select, select_when_done};
+use super::privileged;
+
+/// Minutes offered by the "revert" choice of the override confirmation
+const OVERRIDE_UNDO_MINUTES: u64 = 30;
+
+/// Control socket command and the equivalent CLI flags for an override
+fn override_request(command: &str, flag: &str, value: &str, ttl_minutes: Option<u64>) -> (String, Vec<String>) {
+    let mut args = vec![format!("{}={}", flag, value)];
+    match ttl_minutes {
+        Some(minutes) => {
+            args.push(format!("--for={}m", minutes));
+            (format!("{} {} {}m", command, value, minutes), args)
+        }
+        None => (format!("{} {}", command, value), args),
+    }
+}
 
 fn auto_cpufreq_stats_path() -> &'static str {
     "/var/run/auto-cpufreq.stats"
@@ -35,17 +56,7 @@ pub fn get_stats() -> String {
 }
 pub fn get_version() -> String {
     if *IS_INSTALLED_WITH_AUR {
-        std::process::Command::new("pacman")
-            .args(&["-Qi", "auto-cpufreq"])
-            .output()
-            .ok()
-            .and_then(|output| String::from_utf8(output.stdout).ok())
-            .and_then(|s| {
-                s.lines()
-                    .find(|line| line.contains("Version"))
-                    .map(String::from)
-            })
-            .unwrap_or_else(|| "Unknown".to_string())
+        crate::command::pacman_version("auto-cpufreq").unwrap_or_else(|| "Unknown".to_string())
     } else {
         get_formatted_version().unwrap_or_else(|_| "Unknown".to_string())
     }
@@ -82,30 +93,61 @@ pub fn get_bluetooth_boot_status() -> Option<String> {
     }
 }
 
+/// Explain what an override does before applying it and offer to apply it only
+/// temporarily. `apply` receives the TTL in minutes, `None` for a permanent override.
+fn confirm_override<F: Fn(Option<u64>) + 'static>(anchor: &impl IsA<gtk::Widget>, title: &str, effect: &str, apply: F) {
+    let parent = anchor.root().and_downcast::<gtk::Window>();
+
+    let mut details = effect.to_string();
+    if matches!(charging(), Ok(false)) {
+        details.push_str(&format!("\n\n{}", tr("The system is currently running on battery.")));
+    }
+
+    let dialog = MessageDialog::new(
+        parent.as_ref(),
+        DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::None,
+        title,
+    );
+    dialog.set_secondary_text(Some(&details));
+    dialog.add_button(&tr("Cancel"), ResponseType::Cancel);
+    dialog.add_button(
+        &format!("{} {} min", tr("Revert after"), OVERRIDE_UNDO_MINUTES),
+        ResponseType::Other(1),
+    );
+    dialog.add_button(&tr("Keep"), ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Other(1));
+
+    dialog.connect_response(move |dialog, response| {
+        match response {
+            ResponseType::Accept => apply(None),
+            ResponseType::Other(1) => apply(Some(OVERRIDE_UNDO_MINUTES)),
+            _ => {}
+        }
+        dialog.close();
+    });
+    dialog.present();
+}
+
 // RadioButtonView for Governor Override
 pub struct RadioButtonView {
     container: GtkBox,
-    default: Button,
-    powersave: Button,
-    performance: Button,
+    default: ToggleButton,
+    powersave: ToggleButton,
+    performance: ToggleButton,
     set_by_app: Rc<RefCell<bool>>,
     selected: Rc<RefCell<Option<String>>>,
 }
 
 impl RadioButtonView {
     pub fn new() -> Self {
-        let container = GtkBox::new(Orientation::Horizontal, 5);
-        container.set_hexpand(true);
-
-        let label = Label::new(Some("Governor Override"));
+        let label = Label::new(Some(&tr("Governor Override")));
         label.set_widget_name("bold");
 
-        let default = Button::with_label("Default");
-        default.set_halign(gtk::Align::End);
-        let powersave = Button::with_label("Powersave");
-        powersave.set_halign(gtk::Align::End);
-        let performance = Button::with_label("Performance");
-        performance.set_halign(gtk::Align::End);
+        let default = choice_button(&tr("Default"));
+        let powersave = choice_button(&tr("Powersave"));
+        let performance = choice_button(&tr("Performance"));
 
         let set_by_app = Rc::new(RefCell::new(true));
         let selected = Rc::new(RefCell::new(Some("Default".to_string())));
@@ -119,10 +161,7 @@ impl RadioButtonView {
         default.connect_clicked(move |_| {
             if !*set_by_app_clone.borrow() {
                 *sel_clone.borrow_mut() = Some("Default".to_string());
-                Self::on_button_toggled("reset");
-                default_clone.set_sensitive(false);
-                powersave_clone.set_sensitive(true);
-                performance_clone.set_sensitive(true);
+                Self::on_button_toggled("reset", None, select_when_done(&[&default_clone, &powersave_clone, &performance_clone], 0));
             }
         });
 
@@ -132,13 +171,21 @@ impl RadioButtonView {
         let powersave_clone2 = powersave.clone();
         let performance_clone2 = performance.clone();
         
-        powersave.connect_clicked(move |_| {
+        powersave.connect_clicked(move |button| {
             if !*set_by_app_clone.borrow() {
-                *sel_clone.borrow_mut() = Some("Powersave".to_string());
-                Self::on_button_toggled("powersave");
-                default_clone2.set_sensitive(true);
-                powersave_clone2.set_sensitive(false);
-                performance_clone2.set_sensitive(true);
+                let sel = sel_clone.clone();
+                let default = default_clone2.clone();
+                let powersave = powersave_clone2.clone();
+                let performance = performance_clone2.clone();
+                confirm_override(
+                    button,
+                    &tr("Force the powersave governor?"),
+                    &tr("Keeps the CPU at low clock speeds. Battery life goes up, but the system may feel sluggish under load."),
+                    move |ttl| {
+                        *sel.borrow_mut() = Some("Powersave".to_string());
+                        Self::on_button_toggled("powersave", ttl, select_when_done(&[&default, &powersave, &performance], 1));
+                    },
+                );
             }
         });
 
@@ -148,20 +195,25 @@ impl RadioButtonView {
         let powersave_clone3 = powersave.clone();
         let performance_clone3 = performance.clone();
         
-        performance.connect_clicked(move |_| {
+        performance.connect_clicked(move |button| {
             if !*set_by_app_clone.borrow() {
-                *sel_clone.borrow_mut() = Some("Performance".to_string());
-                Self::on_button_toggled("performance");
-                default_clone3.set_sensitive(true);
-                powersave_clone3.set_sensitive(true);
-                performance_clone3.set_sensitive(false);
+                let sel = sel_clone.clone();
+                let default = default_clone3.clone();
+                let powersave = powersave_clone3.clone();
+                let performance = performance_clone3.clone();
+                confirm_override(
+                    button,
+                    &tr("Force the performance governor?"),
+                    &tr("Keeps the CPU at high clock speeds. Expect noticeably shorter battery life and higher temperatures."),
+                    move |ttl| {
+                        *sel.borrow_mut() = Some("Performance".to_string());
+                        Self::on_button_toggled("performance", ttl, select_when_done(&[&default, &powersave, &performance], 2));
+                    },
+                );
             }
         });
 
-        container.append(&label);
-        container.append(&default);
-        container.append(&powersave);
-        container.append(&performance);
+        let container = option_row(&label, &[&default, &powersave, &performance]);
 
         let mut view = Self {
             container,
@@ -175,17 +227,9 @@ impl RadioButtonView {
         view
     }
 
-    fn on_button_toggled(override_val: &str) {
-        let result = Command::new("pkexec")
-            .arg("auto-cpufreq")
-            .arg(format!("--force={}", override_val))
-            .status();
-
-        if let Ok(status) = result {
-            if status.code() == Some(126) || status.code() == Some(127) {
-                eprintln!("Authorization failed");
-            }
-        }
+    fn on_button_toggled(override_val: &str, ttl_minutes: Option<u64>, done: impl FnOnce(bool) + 'static) {
+        let (command, args) = override_request("set-override", "--force", override_val, ttl_minutes);
+        privileged::run_in_background(command, args, done);
     }
 
     fn set_selected(&mut self) {
@@ -199,9 +243,7 @@ impl RadioButtonView {
         };
         *self.selected.borrow_mut() = Some(label.to_string());
         
-        self.default.set_sensitive(active_btn != 0);
-        self.powersave.set_sensitive(active_btn != 1);
-        self.performance.set_sensitive(active_btn != 2);
+        select(&[&self.default, &self.powersave, &self.performance], active_btn);
         
         *self.set_by_app.borrow_mut() = false;
     }
@@ -214,27 +256,21 @@ impl RadioButtonView {
 // CPUTurboOverride widget
 pub struct CPUTurboOverride {
     container: GtkBox,
-    auto: Button,
-    never: Button,
-    always: Button,
+    auto: ToggleButton,
+    never: ToggleButton,
+    always: ToggleButton,
     set_by_app: Rc<RefCell<bool>>,
     selected: Rc<RefCell<Option<String>>>,
 }
 
 impl CPUTurboOverride {
     pub fn new() -> Self {
-        let container = GtkBox::new(Orientation::Horizontal, 5);
-        container.set_hexpand(true);
-
-        let label = Label::new(Some("CPU Turbo Override"));
+        let label = Label::new(Some(&tr("CPU Turbo Override")));
         label.set_widget_name("bold");
 
-        let auto = Button::with_label("Auto");
-        auto.set_halign(gtk::Align::End);
-        let never = Button::with_label("Never");
-        never.set_halign(gtk::Align::End);
-        let always = Button::with_label("Always");
-        always.set_halign(gtk::Align::End);
+        let auto = choice_button(&tr("Auto"));
+        let never = choice_button(&tr("Never"));
+        let always = choice_button(&tr("Always"));
 
         let set_by_app = Rc::new(RefCell::new(true));
         let selected = Rc::new(RefCell::new(Some("Auto".to_string())));
@@ -248,10 +284,7 @@ impl CPUTurboOverride {
         auto.connect_clicked(move |_| {
             if !*set_by_app_clone.borrow() {
                 *sel_clone.borrow_mut() = Some("Auto".to_string());
-                Self::on_button_toggled("auto");
-                auto_clone.set_sensitive(false);
-                never_clone.set_sensitive(true);
-                always_clone.set_sensitive(true);
+                Self::on_button_toggled("auto", None, select_when_done(&[&auto_clone, &never_clone, &always_clone], 0));
             }
         });
 
@@ -261,13 +294,21 @@ impl CPUTurboOverride {
         let never_clone2 = never.clone();
         let always_clone2 = always.clone();
         
-        never.connect_clicked(move |_| {
+        never.connect_clicked(move |button| {
             if !*set_by_app_clone.borrow() {
-                *sel_clone.borrow_mut() = Some("Never".to_string());
-                Self::on_button_toggled("never");
-                auto_clone2.set_sensitive(true);
-                never_clone2.set_sensitive(false);
-                always_clone2.set_sensitive(true);
+                let sel = sel_clone.clone();
+                let auto = auto_clone2.clone();
+                let never = never_clone2.clone();
+                let always = always_clone2.clone();
+                confirm_override(
+                    button,
+                    &tr("Disable turbo boost?"),
+                    &tr("Lowers power draw and temperatures and extends battery life, at the cost of peak performance."),
+                    move |ttl| {
+                        *sel.borrow_mut() = Some("Never".to_string());
+                        Self::on_button_toggled("never", ttl, select_when_done(&[&auto, &never, &always], 1));
+                    },
+                );
             }
         });
 
@@ -277,20 +318,25 @@ impl CPUTurboOverride {
         let never_clone3 = never.clone();
         let always_clone3 = always.clone();
         
-        always.connect_clicked(move |_| {
+        always.connect_clicked(move |button| {
             if !*set_by_app_clone.borrow() {
-                *sel_clone.borrow_mut() = Some("Always".to_string());
-                Self::on_button_toggled("always");
-                auto_clone3.set_sensitive(true);
-                never_clone3.set_sensitive(true);
-                always_clone3.set_sensitive(false);
+                let sel = sel_clone.clone();
+                let auto = auto_clone3.clone();
+                let never = never_clone3.clone();
+                let always = always_clone3.clone();
+                confirm_override(
+                    button,
+                    &tr("Keep turbo boost always on?"),
+                    &tr("Faster bursts under load, but higher power draw, more heat and shorter battery life."),
+                    move |ttl| {
+                        *sel.borrow_mut() = Some("Always".to_string());
+                        Self::on_button_toggled("always", ttl, select_when_done(&[&auto, &never, &always], 2));
+                    },
+                );
             }
         });
 
-        container.append(&label);
-        container.append(&auto);
-        container.append(&never);
-        container.append(&always);
+        let container = option_row(&label, &[&auto, &never, &always]);
 
         let mut view = Self {
             container,
@@ -304,17 +350,9 @@ impl CPUTurboOverride {
         view
     }
 
-    fn on_button_toggled(override_val: &str) {
-        let result = Command::new("pkexec")
-            .arg("auto-cpufreq")
-            .arg(format!("--turbo={}", override_val))
-            .status();
-
-        if let Ok(status) = result {
-            if status.code() == Some(126) || status.code() == Some(127) {
-                eprintln!("Authorization failed");
-            }
-        }
+    fn on_button_toggled(override_val: &str, ttl_minutes: Option<u64>, done: impl FnOnce(bool) + 'static) {
+        let (command, args) = override_request("set-turbo", "--turbo", override_val, ttl_minutes);
+        privileged::run_in_background(command, args, done);
     }
 
     fn set_selected(&mut self) {
@@ -328,9 +366,7 @@ impl CPUTurboOverride {
         };
         *self.selected.borrow_mut() = Some(label.to_string());
         
-        self.auto.set_sensitive(active_btn != 0);
-        self.never.set_sensitive(active_btn != 1);
-        self.always.set_sensitive(active_btn != 2);
+        select(&[&self.auto, &self.never, &self.always], active_btn);
         
         *self.set_by_app.borrow_mut() = false;
     }
@@ -340,13 +376,121 @@ impl CPUTurboOverride {
     }
 }
 
+// ProfileSelector widget
+pub struct ProfileSelector {
+    container: GtkBox,
+}
+
+impl ProfileSelector {
+    pub fn new() -> Self {
+        let names = crate::profiles::available(&crate::CONFIG.snapshot());
+        let labels = names.clone();
+        let active = crate::state::StateStore::default().load().last_profile;
+        Self::build(&tr("Power Profile"), names, labels, active, "--profile")
+    }
+
+    /// Charging threshold sets from `[battery_profile.NAME]`, `None` when the
+    /// config defines none
+    pub fn battery() -> Option<Self> {
+        use crate::battery::profiles;
+
+        let snapshot = crate::CONFIG.snapshot();
+        let profiles: Vec<_> = profiles::available(&snapshot)
+            .iter()
+            .filter_map(|name| profiles::load(&snapshot, name).ok())
+            .collect();
+        if profiles.is_empty() {
+            return None;
+        }
+
+        let names = profiles.iter().map(|p| p.name.clone()).collect();
+        let labels = profiles
+            .iter()
+            .map(|p| format!("{} ({}-{}%)", p.name, p.start_threshold, p.stop_threshold))
+            .collect();
+        let active = crate::state::StateStore::default().load().battery_profile;
+        Some(Self::build(&tr("Charging Thresholds"), names, labels, active, "--battery-profile"))
+    }
+
+    /// Battery charge behaviour (hold level / force discharge), `None` when
+    /// no battery supports it
+    pub fn charge_behaviour() -> Option<Self> {
+        use crate::battery::charge_behaviour;
+
+        if charge_behaviour::batteries().is_empty() {
+            return None;
+        }
+
+        let names = vec!["inhibit-charge".to_string(), "force-discharge".to_string()];
+        let labels = vec![tr("Hold charge"), tr("Discharge on AC")];
+        // "auto" is the Automatic entry
+        let active = charge_behaviour::current().filter(|mode| mode != "auto");
+        Some(Self::build(&tr("Charge Behaviour"), names, labels, active, "--charge-behaviour"))
+    }
+
+    fn build(title: &str, names: Vec<String>, labels: Vec<String>, active: Option<String>, flag: &'static str) -> Self {
+        let label = Label::new(Some(title));
+        label.set_widget_name("bold");
+
+        // Index 0 is "Automatic" (no profile selected)
+        let names: Vec<String> = std::iter::once("reset".to_string()).chain(names).collect();
+        let labels: Vec<String> = std::iter::once(tr("Automatic")).chain(labels).collect();
+
+        let buttons: Rc<Vec<ToggleButton>> = Rc::new(
+            labels
+                .iter()
+                .map(|text| choice_button(text))
+                .collect(),
+        );
+
+        let active_index = active
+            .and_then(|name| names.iter().position(|n| *n == name))
+            .unwrap_or(0);
+        Self::mark_active(&buttons, active_index);
+
+        for (index, button) in buttons.iter().enumerate() {
+            let buttons = buttons.clone();
+            let name = names[index].clone();
+            button.connect_clicked(move |_| {
+                let buttons = buttons.clone();
+                Self::on_profile_selected(flag, &name, move |changed| {
+                    if changed {
+                        Self::mark_active(&buttons, index);
+                    }
+                });
+            });
+        }
+
+        let refs: Vec<&ToggleButton> = buttons.iter().collect();
+        let container = option_row(&label, &refs);
+
+        Self { container }
+    }
+
+    fn mark_active(buttons: &[ToggleButton], active: usize) {
+        select(&buttons.iter().collect::<Vec<_>>(), active);
+    }
+
+    fn on_profile_selected(flag: &str, name: &str, done: impl FnOnce(bool) + 'static) {
+        let command = match flag {
+            "--battery-profile" => "set-battery-profile",
+            _ => "set-profile",
+        };
+        privileged::run_in_background(format!("{} {}", command, name), vec![format!("{}={}", flag, name)], done);
+    }
+
+    pub fn widget(&self) -> &GtkBox {
+        &self.container
+    }
+}
+
 // BluetoothBootControl widget
 pub struct BluetoothBootControl {
     container: GtkBox,
     _advanced_btn: Button,
     _revealer: Revealer,
-    on_btn: Button,
-    off_btn: Button,
+    on_btn: ToggleButton,
+    off_btn: ToggleButton,
     set_by_app: Rc<RefCell<bool>>,
     selected: Rc<RefCell<Option<String>>>,
 }
@@ -356,29 +500,22 @@ impl BluetoothBootControl {
         let container = GtkBox::new(Orientation::Vertical, 10);
         container.set_hexpand(true);
 
-        let advanced_btn = Button::with_label("Advanced Settings");
+        let advanced_btn = Button::with_label(&tr("Advanced Settings"));
         advanced_btn.set_halign(gtk::Align::Start);
 
         let revealer = Revealer::new();
         revealer.set_transition_type(RevealerTransitionType::SlideDown);
         revealer.set_transition_duration(200);
 
-        let inner_box = GtkBox::new(Orientation::Horizontal, 5);
-        inner_box.set_hexpand(true);
-
-        let label = Label::new(Some("Bluetooth on Boot"));
+        let label = Label::new(Some(&tr("Bluetooth on Boot")));
         label.set_widget_name("bold");
-        let on_btn = Button::with_label("On");
-        on_btn.set_halign(gtk::Align::End);
-        let off_btn = Button::with_label("Off");
-        off_btn.set_halign(gtk::Align::End);
+        let on_btn = choice_button(&tr("On"));
+        let off_btn = choice_button(&tr("Off"));
 
         let selected = Rc::new(RefCell::new(Some("On".to_string())));
         let set_by_app = Rc::new(RefCell::new(true));
 
-        inner_box.append(&label);
-        inner_box.append(&on_btn);
-        inner_box.append(&off_btn);
+        let inner_box = option_row(&label, &[&on_btn, &off_btn]);
 
         revealer.set_child(Some(&inner_box));
 
@@ -391,9 +528,9 @@ impl BluetoothBootControl {
             let revealed = revealer_clone.reveals_child();
             revealer_clone.set_reveal_child(!revealed);
             if revealed {
-                btn_clone.set_label("Advanced Settings");
+                btn_clone.set_label(&tr("Advanced Settings"));
             } else {
-                btn_clone.set_label("Hide Advanced Settings");
+                btn_clone.set_label(&tr("Hide Advanced Settings"));
             }
         });
 
@@ -406,8 +543,7 @@ impl BluetoothBootControl {
             if !*set_by_app_clone.borrow() {
                 *sel_clone.borrow_mut() = Some("On".to_string());
                 Self::on_button_toggled("on");
-                on_clone.set_sensitive(false);
-                off_clone.set_sensitive(true);
+                select(&[&on_clone, &off_clone], 0);
             }
         });
 
@@ -420,8 +556,7 @@ impl BluetoothBootControl {
             if !*set_by_app_clone.borrow() {
                 *sel_clone.borrow_mut() = Some("Off".to_string());
                 Self::on_button_toggled("off");
-                on_clone2.set_sensitive(true);
-                off_clone2.set_sensitive(false);
+                select(&[&on_clone2, &off_clone2], 1);
             }
         });
 
@@ -471,8 +606,7 @@ impl BluetoothBootControl {
             }
         };
         
-        self.on_btn.set_sensitive(active_btn != 0);
-        self.off_btn.set_sensitive(active_btn != 1);
+        select(&[&self.on_btn, &self.off_btn], active_btn);
         
         *self.set_by_app.borrow_mut() = false;
     }
@@ -501,7 +635,7 @@ impl CurrentGovernorBox {
     pub fn new() -> Self {
         let container = GtkBox::new(Orientation::Horizontal, 25);
 
-        let static_label = Label::new(Some("Current Governor"));
+        let static_label = Label::new(Some(&tr("Current Governor")));
         static_label.set_widget_name("bold");
 
         let governor_label = Label::new(Some(""));
@@ -557,8 +691,7 @@ impl BatteryInfoBox {
     pub fn new() -> Self {
         let container = GtkBox::new(Orientation::Vertical, 2);
 
-        let header = Label::new(Some(&("-".repeat(20) + " Battery Stats " + &"-".repeat(20))));
-        header.set_halign(gtk::Align::Start);
+        let header = section_header("Battery Stats");
 
         let status_label = Label::new(Some(""));
         status_label.set_halign(gtk::Align::Start);
@@ -639,6 +772,12 @@ pub struct CPUFreqScalingBox {
     governor_label: Rc<RefCell<Label>>,
     epp_label: Rc<RefCell<Label>>,
     epb_label: Rc<RefCell<Label>>,
+    pstate_label: Rc<RefCell<Label>>,
+    platform_label: Rc<RefCell<Label>>,
+    conflicts_label: Rc<RefCell<Label>>,
+    // EPP/EPB rows are left out entirely where the CPU has no such knob
+    show_epp: bool,
+    show_epb: bool,
 }
 
 impl Clone for CPUFreqScalingBox {
@@ -648,16 +787,20 @@ impl Clone for CPUFreqScalingBox {
             governor_label: self.governor_label.clone(),
             epp_label: self.epp_label.clone(),
             epb_label: self.epb_label.clone(),
+            pstate_label: self.pstate_label.clone(),
+            platform_label: self.platform_label.clone(),
+            conflicts_label: self.conflicts_label.clone(),
+            show_epp: self.show_epp,
+            show_epb: self.show_epb,
         }
     }
 }
 
 impl CPUFreqScalingBox {
-    pub fn new() -> Self {
+    pub fn new(capabilities: &Capabilities) -> Self {
         let container = GtkBox::new(Orientation::Vertical, 2);
 
-        let header = Label::new(Some(&("-".repeat(20) + " CPU Frequency Scaling " + &"-".repeat(20))));
-        header.set_halign(gtk::Align::Start);
+        let header = section_header("CPU Frequency Scaling");
 
         let governor_label = Label::new(Some(""));
         governor_label.set_halign(gtk::Align::Start);
@@ -673,11 +816,29 @@ impl CPUFreqScalingBox {
         container.append(&epp_label);
         container.append(&epb_label);
 
+        let pstate_label = Label::new(Some(""));
+        pstate_label.set_halign(gtk::Align::Start);
+        container.append(&pstate_label);
+
+        let platform_label = Label::new(Some(""));
+        platform_label.set_halign(gtk::Align::Start);
+        container.append(&platform_label);
+
+        let conflicts_label = Label::new(Some(""));
+        conflicts_label.set_halign(gtk::Align::Start);
+        conflicts_label.set_wrap(true);
+        container.append(&conflicts_label);
+
         let mut box_widget = Self {
             container,
             governor_label: Rc::new(RefCell::new(governor_label)),
             epp_label: Rc::new(RefCell::new(epp_label)),
             epb_label: Rc::new(RefCell::new(epb_label)),
+            pstate_label: Rc::new(RefCell::new(pstate_label)),
+            platform_label: Rc::new(RefCell::new(platform_label)),
+            conflicts_label: Rc::new(RefCell::new(conflicts_label)),
+            show_epp: capabilities.epp.available,
+            show_epb: capabilities.epb.available,
         };
 
         box_widget.refresh();
@@ -699,20 +860,57 @@ impl CPUFreqScalingBox {
         let gov = report.current_gov.unwrap_or_else(|| "Unknown".to_string());
         self.governor_label.borrow().set_text(&format!("Setting to use: \"{}\" governor", gov));
 
-        if let Some(epp) = report.current_epp {
+        if let Some(epp) = report.current_epp.filter(|_| self.show_epp) {
             self.epp_label.borrow().set_text(&format!("EPP setting: {}", epp));
             self.epp_label.borrow().set_visible(true);
         } else {
-            self.epp_label.borrow().set_text("Not setting EPP (not supported by system)");
-            self.epp_label.borrow().set_visible(true);
+            self.epp_label.borrow().set_visible(false);
         }
 
-        if let Some(epb) = report.current_epb {
+        if let Some(epb) = report.current_epb.filter(|_| self.show_epb) {
             self.epb_label.borrow().set_text(&format!("Setting to use: \"{}\" EPB", epb));
             self.epb_label.borrow().set_visible(true);
         } else {
             self.epb_label.borrow().set_visible(false);
         }
+
+        let pstate_text = report.intel_pstate.as_ref().and_then(|p| {
+            let mut parts = Vec::new();
+            if let Some(boost) = p.hwp_dynamic_boost {
+                parts.push(format!("{}: {}", tr("HWP dynamic boost"), if boost { tr("On") } else { tr("Off") }));
+            }
+            if let (Some(min), Some(max)) = (p.min_perf_pct, p.max_perf_pct) {
+                parts.push(format!("{}: {}% - {}%", tr("Performance range"), min, max));
+            }
+            (!parts.is_empty()).then(|| parts.join(", "))
+        });
+
+        match pstate_text {
+            Some(text) => {
+                self.pstate_label.borrow().set_text(&text);
+                self.pstate_label.borrow().set_visible(true);
+            }
+            None => self.pstate_label.borrow().set_visible(false),
+        }
+
+        match &report.platform_profile {
+            Some(profile) => {
+                self.platform_label.borrow().set_text(&format!("{}: {}", tr("Platform profile"), profile));
+                self.platform_label.borrow().set_visible(true);
+            }
+            None => self.platform_label.borrow().set_visible(false),
+        }
+
+        if report.conflicts.any() {
+            self.conflicts_label.borrow().set_text(&format!(
+                "{}: {}",
+                tr("Conflicting services"),
+                report.conflicts.describe().join(", ")
+            ));
+            self.conflicts_label.borrow().set_visible(true);
+        } else {
+            self.conflicts_label.borrow().set_visible(false);
+        }
     }
 
     pub fn widget(&self) -> &GtkBox {
@@ -751,8 +949,7 @@ impl SystemStatisticsBox {
     pub fn new() -> Self {
         let container = GtkBox::new(Orientation::Vertical, 2);
 
-        let header = Label::new(Some(&("-".repeat(20) + " System Statistics " + &"-".repeat(20))));
-        header.set_halign(gtk::Align::Start);
+        let header = section_header("System Statistics");
 
         let cpu_usage_label = Label::new(Some(""));
         cpu_usage_label.set_halign(gtk::Align::Start);
@@ -814,8 +1011,7 @@ impl SystemStatisticsBox {
         self.cpu_usage_label.borrow().set_text(&format!("Total CPU usage: {:.1} %", report.cpu_usage));
         self.load_label.borrow().set_text(&format!("Total system load: {:.2}", report.load));
 
-        if !report.cores_info.is_empty() {
-            let avg_temp: f32 = report.cores_info.iter().map(|c| c.temperature).sum::<f32>() / report.cores_info.len() as f32;
+        if let Some(avg_temp) = report.average_temperature() {
             self.temp_label.borrow().set_text(&format!("Average temp. of all cores: {:.2} °C", avg_temp));
             self.temp_label.borrow().set_visible(true);
         } else {
@@ -837,10 +1033,9 @@ impl SystemStatisticsBox {
             self.load_status_label.borrow().set_visible(false);
         }
 
-        if !report.cores_info.is_empty() {
-            let avg_temp: f32 = report.cores_info.iter().map(|c| c.temperature).sum::<f32>() / report.cores_info.len() as f32;
+        if let Some(avg_temp) = report.average_temperature() {
             let usage_status = if report.cpu_usage < 70.0 { "Optimal" } else { "High" };
-            let temp_status = if avg_temp > 75.0 { "high" } else { "normal" };
+            let temp_status = if avg_temp > crate::thermal::turbo_temp_limit() { "high" } else { "normal" };
             self.usage_status_label.borrow().set_text(&format!("{} total CPU usage: {:.1}%, {} average core temp: {:.1}°C", usage_status, report.cpu_usage, temp_status, avg_temp));
             self.usage_status_label.borrow().set_visible(true);
         } else {
@@ -860,6 +1055,60 @@ impl SystemStatisticsBox {
     }
 }
 
+// DecisionTimeline - the daemon's last governor decisions from the audit log
+const TIMELINE_ENTRIES: usize = 20;
+
+pub struct DecisionTimeline {
+    container: GtkBox,
+    entries_box: GtkBox,
+}
+
+impl Clone for DecisionTimeline {
+    fn clone(&self) -> Self {
+        Self {
+            container: self.container.clone(),
+            entries_box: self.entries_box.clone(),
+        }
+    }
+}
+
+impl DecisionTimeline {
+    pub fn new() -> Self {
+        let container = GtkBox::new(Orientation::Vertical, 2);
+        let entries_box = GtkBox::new(Orientation::Vertical, 2);
+
+        container.append(&section_header("Decisions"));
+        container.append(&entries_box);
+
+        let mut box_widget = Self { container, entries_box };
+        box_widget.refresh();
+        box_widget
+    }
+
+    pub fn refresh(&mut self) {
+        while let Some(child) = self.entries_box.first_child() {
+            self.entries_box.remove(&child);
+        }
+
+        let decisions = crate::audit::AuditLog::default().recent(TIMELINE_ENTRIES);
+        if decisions.is_empty() {
+            let label = Label::new(Some(&tr("No decisions recorded yet")));
+            label.set_halign(gtk::Align::Start);
+            self.entries_box.append(&label);
+        }
+        for decision in decisions {
+            let label = Label::new(Some(&decision.summary()));
+            label.set_halign(gtk::Align::Start);
+            label.set_selectable(true);
+            self.entries_box.append(&label);
+        }
+    }
+
+    pub fn widget(&self) -> &GtkBox {
+        &self.container
+    }
+}
+
 // SystemStatsLabel - FIXED: Use RefCell
 pub struct SystemStatsLabel {
     scrolled: ScrolledWindow,
@@ -934,8 +1183,8 @@ impl SystemStatsLabel {
         
         let cores = SystemInfo::get_cpu_info(sys);
         for core in cores {
-            text.push_str(&format!("CPU{:<2}    {:>4.1}%    {:>6.0} °C    {:>6.0} MHz\n",
-                core.id, core.usage, core.temperature, core.frequency));
+            text.push_str(&format!("CPU{:<2}    {:>4.1}%    {:>9}    {:>6.0} MHz\n",
+                core.id, core.usage, core.temperature.map(|t| format!("{:.0} °C", t)).unwrap_or_else(|| "-- °C".to_string()), core.frequency));
         }
         
         if let Some(fan) = SystemInfo::cpu_fan_speed() {
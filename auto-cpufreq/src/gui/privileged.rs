@@ -0,0 +1,44 @@
+// src/gui/privileged.rs
+//
+// Settings changes made from the GUI and the tray. They go to the daemon's
+// control socket, where polkit decides per action (set-governor, set-turbo,
+// set-profile, ...) whether this user may make them, so nothing in the session
+// runs as root. Without a daemon listening (not installed yet, or a version
+// without the socket) they fall back to `pkexec auto-cpufreq <flags>`. The
+// GUI runs them with `run_in_background`, so the polkit prompt and the socket
+// don't block its main loop.
+
+use std::process::Command;
+
+/// pkexec exit codes for a refused or dismissed authorization
+const PKEXEC_DENIED: [i32; 2] = [126, 127];
+
+/// Send `command` to the daemon, or run `auto-cpufreq <cli_args>` through
+/// pkexec; whether the change was made
+pub fn run(command: &str, cli_args: &[String]) -> bool {
+    match crate::control::request(command) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            false
+        }
+        None => match Command::new("pkexec").arg("auto-cpufreq").args(cli_args).status() {
+            Ok(status) if status.success() => true,
+            Ok(status) => {
+                if status.code().is_some_and(|code| PKEXEC_DENIED.contains(&code)) {
+                    eprintln!("Authorization failed");
+                }
+                false
+            }
+            Err(_) => false,
+        },
+    }
+}
+
+/// `run` on a worker thread; `done` gets its result back on the GTK main loop
+pub fn run_in_background(command: String, cli_args: Vec<String>, done: impl FnOnce(bool) + 'static) {
+    glib::MainContext::default().spawn_local(async move {
+        let changed = gio::spawn_blocking(move || run(&command, &cli_args)).await.unwrap_or(false);
+        done(changed);
+    });
+}
@@ -0,0 +1,65 @@
+// src/gui/root_guard.rs
+//
+// The GUI and tray are session components: GTK, the session bus and the
+// status notifier all belong to the logged-in user, and running them as root
+// (e.g. `sudo auto-cpufreq-gtk`) leaves root-owned files in the user's home and
+// fails to reach the user's bus. They refuse to start as root; the privileged
+// actions they offer already ask polkit one at a time, overrides and profiles
+// over the daemon's control socket and install or remove through
+// `pkexec auto-cpufreq ...`. Setting
+// AUTO_CPUFREQ_ALLOW_ROOT=1 starts them anyway with a warning.
+
+pub const ALLOW_ROOT_ENV: &str = "AUTO_CPUFREQ_ALLOW_ROOT";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verdict {
+    Run,
+    Warn,
+    Refuse,
+}
+
+fn verdict(is_root: bool, allow_root: bool) -> Verdict {
+    match (is_root, allow_root) {
+        (false, _) => Verdict::Run,
+        (true, true) => Verdict::Warn,
+        (true, false) => Verdict::Refuse,
+    }
+}
+
+/// Whether `component` may start; prints why not when it may not
+pub fn check(component: &str) -> bool {
+    let allow_root = std::env::var(ALLOW_ROOT_ENV).is_ok_and(|v| v == "1");
+
+    match verdict(nix::unistd::Uid::effective().is_root(), allow_root) {
+        Verdict::Run => true,
+        Verdict::Warn => {
+            eprintln!("\n{}", "!".repeat(79));
+            eprintln!("WARNING: {} is running as root ({}=1).", component, ALLOW_ROOT_ENV);
+            eprintln!("Session services (D-Bus, settings, the tray) may not work and files in");
+            eprintln!("your home directory can end up owned by root.");
+            eprintln!("{}\n", "!".repeat(79));
+            true
+        }
+        Verdict::Refuse => {
+            eprintln!("ERROR: {} should not be run as root.", component);
+            eprintln!("\nStart it as your normal user: {}", component);
+            eprintln!("Actions that need root (installing the daemon, overrides, profiles) ask for");
+            eprintln!("your password through polkit when you use them.");
+            eprintln!("\nTo start it as root anyway, set {}=1", ALLOW_ROOT_ENV);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_verdict() {
+        assert_eq!(verdict(false, false), Verdict::Run);
+        assert_eq!(verdict(false, true), Verdict::Run);
+        assert_eq!(verdict(true, true), Verdict::Warn);
+        assert_eq!(verdict(true, false), Verdict::Refuse);
+    }
+}
@@ -1,9 +1,9 @@
 use ksni::{Tray, TrayService, MenuItem, ToolTip};
-use ksni::menu::StandardItem;
+use ksni::menu::{RadioGroup, RadioItem, StandardItem};
 use std::process::Command;
 
 fn get_icon_path() -> String {
-    "/usr/local/share/auto-cpufreq/images/icon.png".to_string()
+    crate::globals::icon_path().to_string_lossy().into_owned()
 }
 
 pub struct AutoCpufreqTray;
@@ -50,6 +50,11 @@ impl Tray for AutoCpufreqTray {
                 }),
                 ..Default::default()
             }),
+            SubMenu(ksni::menu::SubMenu {
+                label: "Profile".into(),
+                submenu: vec![profile_menu()],
+                ..Default::default()
+            }),
             Separator,
             Standard(StandardItem {
                 label: "Quit".into(),
@@ -60,8 +65,46 @@ impl Tray for AutoCpufreqTray {
     }
 }
 
+/// "Automatic" followed by every available profile, with the selected one checked
+fn profile_menu() -> MenuItem<AutoCpufreqTray> {
+    let profiles = crate::profiles::available(&crate::CONFIG.snapshot());
+    let active = crate::state::StateStore::default().load().last_profile;
+    let selected = active
+        .and_then(|name| profiles.iter().position(|p| *p == name))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    let mut options = vec![RadioItem {
+        label: "Automatic".into(),
+        ..Default::default()
+    }];
+    options.extend(profiles.iter().map(|name| RadioItem {
+        label: name.replace('_', "__"),
+        ..Default::default()
+    }));
+
+    MenuItem::RadioGroup(RadioGroup {
+        selected,
+        select: Box::new(move |_, index| {
+            let name = index
+                .checked_sub(1)
+                .and_then(|i| profiles.get(i).cloned())
+                .unwrap_or_else(|| "reset".to_string());
+            // Off the tray's thread, the polkit prompt may take a while
+            std::thread::spawn(move || {
+                super::privileged::run(&format!("set-profile {}", name), &[format!("--profile={}", name)])
+            });
+        }),
+        options,
+    })
+}
+
 impl TrayApp {
     pub fn run() {
+        if !super::root_guard::check("auto-cpufreq-tray") {
+            std::process::exit(1);
+        }
+
         let service = TrayService::new(AutoCpufreqTray);
         service.spawn();
         println!("auto-cpufreq tray icon is running via D-Bus...");
@@ -0,0 +1,195 @@
+// src/headless.rs
+//
+// Session type awareness. While no local graphical session is logged in (only
+// SSH sessions, or nobody at all), `set_autofreq` uses the `[headless]`
+// settings: powersave with turbo off unless the section says otherwise. Useful
+// for laptops that double as a part-time server. Sessions come from logind,
+// and are only queried again when logind's session list changes.
+
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::power_helper::does_command_exist;
+use crate::profiles::{self, Profile, ProfileSwitch};
+
+const SECTION: &str = "headless";
+
+/// logind session types of a local desktop
+const GRAPHICAL_TYPES: &[&str] = &["x11", "wayland", "mir"];
+
+/// logind keeps one state file per session here, rewritten when it changes
+const SESSIONS_DIR: &str = "/run/systemd/sessions";
+
+/// How long a loginctl answer is trusted when SESSIONS_DIR can't be read
+const SESSION_TTL: Duration = Duration::from_secs(60);
+
+/// Last loginctl answer and the session files it was computed from
+struct SessionCache {
+    fingerprint: Option<Vec<(String, SystemTime)>>,
+    checked: Instant,
+    graphical: Option<bool>,
+}
+
+lazy_static::lazy_static! {
+    static ref LOGINCTL_EXISTS: bool = does_command_exist("loginctl");
+    static ref SESSIONS: Mutex<Option<SessionCache>> = Mutex::new(None);
+}
+
+static SWITCH: ProfileSwitch<()> = ProfileSwitch::new();
+
+/// The `[headless]` profile, `None` when the section isn't in the config
+pub fn settings(config: &Snapshot) -> Result<Option<Profile>> {
+    if config.section(SECTION).is_none() {
+        return Ok(None);
+    }
+
+    let mut profile = Profile {
+        name: SECTION.to_string(),
+        governor: Some("powersave".to_string()),
+        turbo: Some("never".to_string()),
+        energy_performance_preference: Some("power".to_string()),
+        ..Profile::default()
+    };
+    profiles::apply_overrides(config, SECTION, &mut profile)?;
+    Ok(Some(profile))
+}
+
+/// Parse `loginctl show-session -p Type -p Class -p Remote` output: whether
+/// this is a local graphical user session
+fn parse_session_graphical(stdout: &str) -> bool {
+    let value = |key: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::trim)
+    };
+
+    value("Remote") != Some("yes")
+        && value("Class") == Some("user")
+        && value("Type").is_some_and(|t| GRAPHICAL_TYPES.contains(&t))
+}
+
+/// Names and modification times of logind's session files, `None` when the
+/// directory can't be read
+fn sessions_fingerprint() -> Option<Vec<(String, SystemTime)>> {
+    let mut sessions: Vec<_> = fs::read_dir(SESSIONS_DIR)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            Some((entry.file_name().to_string_lossy().into_owned(), entry.metadata().ok()?.modified().ok()?))
+        })
+        .collect();
+    sessions.sort();
+    Some(sessions)
+}
+
+/// Whether the cached answer still holds for the current session files
+fn cache_valid(cache: &SessionCache, fingerprint: &Option<Vec<(String, SystemTime)>>) -> bool {
+    match fingerprint {
+        Some(_) => cache.fingerprint == *fingerprint,
+        None => cache.fingerprint.is_none() && cache.checked.elapsed() < SESSION_TTL,
+    }
+}
+
+/// `Some(true)` when logged in on a local desktop, `None` without logind.
+/// loginctl only runs again once a session was added, removed or changed.
+pub fn graphical_session_active() -> Option<bool> {
+    if !*LOGINCTL_EXISTS {
+        return None;
+    }
+
+    let fingerprint = sessions_fingerprint();
+    let mut cache = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.as_ref().filter(|cached| cache_valid(cached, &fingerprint)) {
+        return cached.graphical;
+    }
+
+    let graphical = query_sessions();
+    *cache = Some(SessionCache { fingerprint, checked: Instant::now(), graphical });
+    graphical
+}
+
+/// Ask loginctl whether any session is a local graphical one
+fn query_sessions() -> Option<bool> {
+    let sessions = crate::command::output("loginctl", &["list-sessions", "--no-legend"]).ok()?;
+    Some(
+        sessions
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .any(|id| {
+                crate::command::output("loginctl", &["show-session", id, "-p", "Type", "-p", "Class", "-p", "Remote"])
+                    .is_ok_and(|output| parse_session_graphical(&output))
+            }),
+    )
+}
+
+/// The `[headless]` profile while nobody is logged in on a local desktop
+fn current() -> Option<((), Profile)> {
+    let profile = match settings(&CONFIG.snapshot()) {
+        Ok(profile) => profile?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            return None;
+        }
+    };
+    (graphical_session_active() == Some(false)).then_some(((), profile))
+}
+
+/// Called by `set_autofreq`: the `[headless]` profile while nobody is logged
+/// in on a local desktop
+pub fn profile() -> Option<Profile> {
+    SWITCH.follow(current(), |headless| match headless {
+        Some(_) => info!("No graphical session, using the [headless] settings"),
+        None => info!("Graphical session started, back to the regular settings"),
+    })
+}
+
+pub fn preview() -> Option<Profile> {
+    SWITCH.preview(current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_settings() {
+        let config = Snapshot::from_str_ini("[headless]\nscaling_max_freq = 1200000\n").unwrap();
+        let profile = settings(&config).unwrap().unwrap();
+        assert_eq!(profile.governor.as_deref(), Some("powersave"));
+        assert_eq!(profile.turbo.as_deref(), Some("never"));
+        assert_eq!(profile.scaling_max_freq, Some(1_200_000));
+
+        let config = Snapshot::from_str_ini("[headless]\ngovernor = schedutil\nturbo = auto\n").unwrap();
+        assert_eq!(settings(&config).unwrap().unwrap().turbo.as_deref(), Some("auto"));
+        assert_eq!(settings(&Snapshot::from_str_ini("[charger]\nturbo = auto\n").unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_cache_valid() {
+        let files = vec![("2".to_string(), SystemTime::UNIX_EPOCH)];
+        let cache = SessionCache { fingerprint: Some(files.clone()), checked: Instant::now(), graphical: Some(true) };
+        assert!(cache_valid(&cache, &Some(files)));
+        assert!(!cache_valid(&cache, &Some(vec![("2".to_string(), SystemTime::UNIX_EPOCH + Duration::from_secs(1))])));
+        assert!(!cache_valid(&cache, &Some(Vec::new())));
+        assert!(!cache_valid(&cache, &None));
+
+        let cache = SessionCache { fingerprint: None, checked: Instant::now(), graphical: Some(false) };
+        assert!(cache_valid(&cache, &None));
+        assert!(!cache_valid(&cache, &Some(Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_session_graphical() {
+        assert!(parse_session_graphical("Type=wayland\nClass=user\nRemote=no\n"));
+        assert!(!parse_session_graphical("Type=tty\nClass=user\nRemote=yes\n"));
+        assert!(!parse_session_graphical("Type=x11\nClass=greeter\nRemote=no\n"));
+        assert!(!parse_session_graphical("Type=x11\nClass=user\nRemote=yes\n"));
+    }
+}
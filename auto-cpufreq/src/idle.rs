@@ -0,0 +1,232 @@
+// src/idle.rs
+//
+// Idle/AFK detection. After `[idle] timeout` minutes without keyboard or
+// touchpad input on battery, `set_autofreq` switches to a deep powersave
+// profile with a reduced maximum frequency. Idle time comes from logind's
+// IdleHint (set by the desktop's screen saver); sessions without it fall back
+// to watching the input device interrupt counters in /proc/interrupts.
+
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, bail};
+use tracing::{info, warn};
+
+use crate::config::{parse_bool, CONFIG, Snapshot};
+use crate::cpu::per_core;
+use crate::event_code::EventCode;
+use crate::power_helper::does_command_exist;
+use crate::profiles::{Profile, ProfileSwitch};
+
+const DEFAULT_TIMEOUT_MINUTES: u64 = 10;
+
+/// /proc/interrupts descriptions of keyboard and touchpad controllers
+const INPUT_IRQ_NAMES: &[&str] = &["i8042", "hid", "elan", "syna", "keyboard", "mouse", "touchpad"];
+
+lazy_static::lazy_static! {
+    static ref LOGINCTL_EXISTS: bool = does_command_exist("loginctl");
+    /// Last input interrupt total and when it last changed
+    static ref INPUT_ACTIVITY: Mutex<Option<(u64, Instant)>> = Mutex::new(None);
+}
+
+/// Keyed by the idle timeout
+static SWITCH: ProfileSwitch<Duration> = ProfileSwitch::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdleSettings {
+    pub timeout: Duration,
+    /// kHz cap while idle, `None` for half way into the hardware range
+    pub scaling_max_freq: Option<u32>,
+}
+
+/// `[idle]` settings, `None` unless `enabled = true`
+pub fn settings(config: &Snapshot) -> Result<Option<IdleSettings>> {
+    let enabled = config
+        .get("idle", "enabled")
+        .and_then(parse_bool)
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+
+    let timeout = match config.get("idle", "timeout") {
+        None => DEFAULT_TIMEOUT_MINUTES,
+        Some(value) => match value.trim().parse::<u64>() {
+            Ok(minutes) if minutes > 0 => minutes,
+            _ => bail!("Invalid timeout in [idle]: {} (expected minutes)", value),
+        },
+    };
+    let scaling_max_freq = match config.get("idle", "scaling_max_freq") {
+        None => None,
+        Some(value) => match value.trim().parse::<u32>() {
+            Ok(khz) if khz > 0 => Some(khz),
+            _ => bail!("Invalid scaling_max_freq in [idle]: {} (expected kHz)", value),
+        },
+    };
+
+    Ok(Some(IdleSettings { timeout: Duration::from_secs(timeout * 60), scaling_max_freq }))
+}
+
+/// Parse `loginctl show-session -p IdleHint -p IdleSinceHint -p Remote` output
+/// into the idle start (µs since the epoch), `Some(None)` for an active session
+/// and `None` for remote (ssh) sessions and ones that don't report idle state
+fn parse_session_idle(stdout: &str) -> Option<Option<u64>> {
+    let value = |key: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::trim)
+    };
+
+    if value("Remote") == Some("yes") {
+        return None;
+    }
+
+    match value("IdleHint")? {
+        "no" => Some(None),
+        "yes" => value("IdleSinceHint")?.parse::<u64>().ok().filter(|us| *us > 0).map(Some),
+        _ => None,
+    }
+}
+
+/// Time since the most recently active logind session went idle, zero while
+/// any session is in use
+fn logind_idle_time() -> Option<Duration> {
+    if !*LOGINCTL_EXISTS {
+        return None;
+    }
+
+    let sessions = crate::command::output("loginctl", &["list-sessions", "--no-legend"]).ok()?;
+    let now_us = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_micros() as u64;
+
+    sessions
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(|id| {
+            let output = crate::command::output(
+                "loginctl",
+                &["show-session", id, "-p", "IdleHint", "-p", "IdleSinceHint", "-p", "Remote"],
+            )
+            .ok()?;
+            parse_session_idle(&output)
+        })
+        .map(|since| match since {
+            None => Duration::ZERO,
+            Some(since_us) => Duration::from_micros(now_us.saturating_sub(since_us)),
+        })
+        .min()
+}
+
+/// Sum of the interrupt counts of keyboard/touchpad lines in /proc/interrupts
+fn parse_input_interrupts(content: &str) -> Option<u64> {
+    let counts: Vec<u64> = content
+        .lines()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            INPUT_IRQ_NAMES.iter().any(|name| line.contains(name))
+        })
+        .map(|line| {
+            line.split_whitespace()
+                .skip(1)
+                .map_while(|field| field.parse::<u64>().ok())
+                .sum::<u64>()
+        })
+        .collect();
+    (!counts.is_empty()).then(|| counts.iter().sum())
+}
+
+/// Time since the input interrupt counters last moved, measured from the
+/// first call
+fn interrupt_idle_time() -> Option<Duration> {
+    let total = parse_input_interrupts(&fs::read_to_string("/proc/interrupts").ok()?)?;
+    let mut activity = INPUT_ACTIVITY.lock().unwrap();
+
+    match *activity {
+        Some((last_total, since)) if last_total == total => Some(since.elapsed()),
+        _ => {
+            *activity = Some((total, Instant::now()));
+            Some(Duration::ZERO)
+        }
+    }
+}
+
+/// How long the machine has been without user input, if that can be told
+pub fn idle_time() -> Option<Duration> {
+    logind_idle_time().or_else(interrupt_idle_time)
+}
+
+fn idle_profile(settings: &IdleSettings) -> Profile {
+    let scaling_max_freq = settings.scaling_max_freq.or_else(|| {
+        per_core::hardware_limits(0).map(|(min, max)| min + (max - min) / 2)
+    });
+
+    Profile {
+        name: "idle".to_string(),
+        governor: Some("powersave".to_string()),
+        turbo: Some("never".to_string()),
+        energy_performance_preference: Some("power".to_string()),
+        scaling_max_freq,
+        platform_profile: Some("low-power".to_string()),
+        ..Profile::default()
+    }
+}
+
+/// The idle profile once the machine has been idle on battery long enough
+fn current(is_charging: bool) -> Option<(Duration, Profile)> {
+    let settings = match settings(&CONFIG.snapshot()) {
+        Ok(settings) => settings?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            return None;
+        }
+    };
+    let idle = !is_charging && idle_time().is_some_and(|t| t >= settings.timeout);
+    idle.then(|| (settings.timeout, idle_profile(&settings)))
+}
+
+/// Called by `set_autofreq`: the deep powersave profile once the machine has
+/// been idle on battery for the configured time. The frequency cap is lifted
+/// again as soon as there is input or the charger is plugged in.
+pub fn profile(is_charging: bool) -> Option<Profile> {
+    SWITCH.follow(current(is_charging), |idle| match idle {
+        Some((timeout, _)) => info!("No input for {} minutes on battery, entering deep powersave", timeout.as_secs() / 60),
+        None => info!("Leaving idle powersave"),
+    })
+}
+
+pub fn preview(is_charging: bool) -> Option<Profile> {
+    SWITCH.preview(current(is_charging))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_settings() {
+        let config = Snapshot::from_str_ini("[idle]\nenabled = true\ntimeout = 5\nscaling_max_freq = 1200000\n").unwrap();
+        assert_eq!(
+            settings(&config).unwrap(),
+            Some(IdleSettings { timeout: Duration::from_secs(300), scaling_max_freq: Some(1_200_000) })
+        );
+
+        assert_eq!(settings(&Snapshot::from_str_ini("[idle]\ntimeout = 5\n").unwrap()).unwrap(), None);
+        assert!(settings(&Snapshot::from_str_ini("[idle]\nenabled = true\ntimeout = soon\n").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_idle_sources() {
+        assert_eq!(parse_session_idle("IdleHint=no\nIdleSinceHint=0\n"), Some(None));
+        assert_eq!(parse_session_idle("IdleHint=yes\nIdleSinceHint=1700000000000000\n"), Some(Some(1_700_000_000_000_000)));
+        assert_eq!(parse_session_idle("IdleHint=yes\nIdleSinceHint=0\n"), None);
+        assert_eq!(parse_session_idle("IdleHint=no\nIdleSinceHint=0\nRemote=yes\n"), None);
+
+        let interrupts = "           CPU0       CPU1\n\
+                          1:        120         30  IR-IO-APIC    1-edge      i8042\n\
+                         51:          7          3  IR-IO-APIC   27-fasteoi   ELAN0670:00\n\
+                        120:       9000       8000  IR-PCI-MSI 327680-edge      xhci_hcd\n";
+        assert_eq!(parse_input_interrupts(interrupts), Some(160));
+        assert_eq!(parse_input_interrupts("           CPU0\n 9:  5  IO-APIC  9-fasteoi  acpi\n"), None);
+    }
+}
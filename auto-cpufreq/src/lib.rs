@@ -1,14 +1,48 @@
 pub mod globals;
+pub mod command;
 pub mod tlp_stat_parser;
 pub mod power_helper;
 pub mod config;
 pub mod core;
 pub mod battery;
+pub mod cpu;
+pub mod state;
+pub mod profiles;
+pub mod thermal;
+pub mod process_watch;
+pub mod gamemode;
+pub mod idle;
+pub mod docked;
+pub mod weak_charger;
+pub mod boot_boost;
+pub mod battery_saver;
+pub mod battery_levels;
+pub mod headless;
+pub mod sensors;
+pub mod quiet_hours;
+pub mod schedule;
+pub mod capabilities;
+pub mod events;
+pub mod shutdown;
+pub mod pidfile;
+pub mod control;
+pub mod sd_notify;
+pub mod polkit;
+pub mod live;
+pub mod bundle;
+pub mod procfs;
+pub mod validate;
+pub mod audit;
+pub mod stats_history;
+pub mod telemetry;
+pub mod ppd_shim;
+pub mod logging;
+pub mod event_code;
 pub mod modules;
 
 // Re-exports
 pub use globals::*;
 pub use config::{CONFIG, find_config_file}; // CONFIG re-export now works
 
-#[cfg(feature = "gui")]
+#[cfg(any(feature = "gui", feature = "tray"))]
 pub mod gui;
@@ -0,0 +1,159 @@
+// src/live.rs
+//
+// The controller behind `--live`: the daemon's adjustment cycle, run from the
+// monitor's refresh so its decisions show up in the view next to what they
+// changed. Cycles follow the daemon's adaptive interval. Their messages are
+// kept off the terminal rather than drawn through the view; warnings still
+// reach the `[logging] file` and every change the audit log. On quit, Ctrl+C or
+// SIGTERM the CPU settings from before the start are restored and the power
+// services stopped for the session are started again.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use tracing::warn;
+
+use crate::config::CONFIG;
+use crate::core::CycleStats;
+use crate::event_code::EventCode;
+use crate::events::{IntervalLimits, PollInterval, ShutdownSignal};
+use crate::power_helper;
+
+/// Outcome of the last cycle, for the view
+#[derive(Debug, Clone)]
+pub struct LastCycle {
+    pub at: DateTime<Local>,
+    pub result: Result<CycleStats, String>,
+    pub next_in: Duration,
+}
+
+impl LastCycle {
+    /// Lines for the monitor's status column
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![format!("Live decisions ({})", self.at.format("%H:%M:%S")), String::new()];
+        match &self.result {
+            Ok(stats) => {
+                lines.push(format!("Governor: {} ({})", stats.governor, stats.governor_reasons.join(", ")));
+                lines.push(match &stats.turbo {
+                    Some((on, reason)) => format!("Turbo: {} ({})", if *on { "on" } else { "off" }, reason),
+                    None => "Turbo: unchanged".to_string(),
+                });
+            }
+            Err(e) => lines.push(format!("Cycle failed: {}", e)),
+        }
+        lines.push(format!("Next check in {:.0}s", self.next_in.as_secs_f64()));
+        lines
+    }
+}
+
+#[derive(Debug, Default)]
+struct StoppedServices {
+    power_profiles_daemon: bool,
+    tuned: bool,
+    intel_lpmd: bool,
+}
+
+pub struct Controller {
+    interval: PollInterval,
+    next_cycle: Instant,
+    pub last: Option<LastCycle>,
+    shutdown: Option<ShutdownSignal>,
+    stop_requested: bool,
+    stopped: StoppedServices,
+}
+
+impl Controller {
+    /// Stop the services that would fight over the settings and remember the
+    /// current ones. The daemon must not be running.
+    pub fn start() -> Result<Self> {
+        let stopped = StoppedServices {
+            power_profiles_daemon: power_helper::gnome_power_stop_live()?,
+            tuned: power_helper::tuned_stop_live()?,
+            intel_lpmd: power_helper::lpmd_stop_live()?,
+        };
+        if let Err(e) = crate::shutdown::remember() {
+            warn!(code = %EventCode::StateWriteFailed, "Failed to save the current settings, they won't be restored on exit: {}", e);
+        }
+        let shutdown = ShutdownSignal::install()
+            .map_err(|e| warn!(code = %EventCode::EventSourceFailed, "Ctrl+C won't restore the settings: {}", e))
+            .ok();
+
+        Ok(Self {
+            interval: PollInterval::new(),
+            next_cycle: Instant::now(),
+            last: None,
+            shutdown,
+            stop_requested: false,
+            stopped,
+        })
+    }
+
+    /// Run a cycle when one is due
+    pub fn tick(&mut self) {
+        if Instant::now() < self.next_cycle {
+            return;
+        }
+
+        let limits = IntervalLimits::from_config(&CONFIG.snapshot());
+        let result = crate::logging::muted(crate::core::set_autofreq).map_err(|e| e.to_string());
+        let next_in = match &result {
+            Ok(stats) => self.interval.next(&limits, stats.cpu_usage, stats.is_charging),
+            Err(_) => limits.min,
+        };
+        self.next_cycle = Instant::now() + next_in;
+        self.last = Some(LastCycle { at: Local::now(), result, next_in });
+    }
+
+    /// Ctrl+C or SIGTERM arrived
+    pub fn stop_requested(&mut self) -> bool {
+        if let Some(shutdown) = &self.shutdown {
+            self.stop_requested |= shutdown.drain();
+        }
+        self.stop_requested
+    }
+
+    /// Put back the settings and services from before `start`
+    pub fn finish(self) {
+        // stderr, so it doesn't end up in --json/--output samples
+        eprintln!("\n* Restoring the settings from before --live");
+        if let Err(e) = crate::shutdown::restore_saved() {
+            warn!(code = %EventCode::StateWriteFailed, "Failed to update the state file: {}", e);
+        }
+
+        let restarts = [
+            (self.stopped.power_profiles_daemon, "power-profiles-daemon", power_helper::gnome_power_start_live as fn() -> Result<()>),
+            (self.stopped.tuned, "tuned", power_helper::tuned_start_live),
+            (self.stopped.intel_lpmd, "intel_lpmd", power_helper::lpmd_start_live),
+        ];
+        for (stopped, name, start) in restarts {
+            if stopped {
+                if let Err(e) = start() {
+                    warn!(code = %EventCode::RestoreFailed, "Failed to start {} again: {}", name, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_last_cycle() {
+        let stats = CycleStats {
+            cpu_usage: 12.0,
+            is_charging: false,
+            governor: "powersave",
+            governor_reasons: vec!["battery".to_string(), "low load".to_string()],
+            turbo: Some((false, "battery".to_string())),
+        };
+        let cycle = LastCycle { at: Local::now(), result: Ok(stats), next_in: Duration::from_secs(4) };
+        let lines = cycle.describe();
+        assert_eq!(lines[2..], ["Governor: powersave (battery, low load)", "Turbo: off (battery)", "Next check in 4s"]);
+
+        let failed = LastCycle { result: Err("no cpufreq".to_string()), ..cycle };
+        assert_eq!(failed.describe()[2], "Cycle failed: no cpufreq");
+    }
+}
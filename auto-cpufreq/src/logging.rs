@@ -0,0 +1,317 @@
+// src/logging.rs
+//
+// Log output of the daemon and the CLI, built on `tracing`. On a terminal
+// messages keep the familiar form ("WARNING: ...", info lines as plain text),
+// warnings and errors prefixed with the module they come from and their event
+// code, if any ("WARNING: [battery] ACF-010 ..."). When stderr is the stream
+// systemd connected to the journal (JOURNAL_STREAM holds its device and inode;
+// shells of a user session inherit the variable, so being set isn't enough)
+// they go to the journal natively, with priorities and the code as `CODE=`, so
+// `journalctl -p warning -u auto-cpufreq` and `journalctl CODE=ACF-010` work.
+// `[logging] file` adds a daily rotated log file with timestamps, written by
+// the daemon only. The level comes from `--log-level`, else `[logging] level`,
+// else info. `muted` keeps a thread's messages off the terminal, for `--live`
+// cycles that would draw over the view.
+
+use std::cell::Cell;
+use std::fmt;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, filter_fn};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::Snapshot;
+
+pub const LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+/// Rotated log files kept next to the current one
+const DEFAULT_MAX_FILES: usize = 7;
+
+pub fn parse_level(value: &str) -> Option<LevelFilter> {
+    match value.trim().to_lowercase().as_str() {
+        "error" => Some(LevelFilter::ERROR),
+        "warn" | "warning" => Some(LevelFilter::WARN),
+        "info" => Some(LevelFilter::INFO),
+        "debug" => Some(LevelFilter::DEBUG),
+        "trace" => Some(LevelFilter::TRACE),
+        "off" => Some(LevelFilter::OFF),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub level: LevelFilter,
+    /// `[logging] file`, rotated daily
+    pub file: Option<PathBuf>,
+    pub max_files: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { level: LevelFilter::INFO, file: None, max_files: DEFAULT_MAX_FILES }
+    }
+}
+
+impl Settings {
+    /// `cli_level` (`--log-level`) wins over `[logging] level`
+    pub fn from_config(config: &Snapshot, cli_level: Option<&str>) -> Result<Self> {
+        let level = match cli_level.or(config.get("logging", "level")) {
+            Some(value) => match parse_level(value) {
+                Some(level) => level,
+                None => bail!("Invalid log level '{}' (expected {} or off)", value, LEVELS.join(", ")),
+            },
+            None => LevelFilter::INFO,
+        };
+        let max_files = match config.get("logging", "max_files") {
+            Some(value) => value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .with_context(|| format!("Invalid max_files '{}' in [logging]", value))?,
+            None => DEFAULT_MAX_FILES,
+        };
+        let file = config.get("logging", "file").map(str::trim).filter(|f| !f.is_empty()).map(PathBuf::from);
+
+        Ok(Self { level, file, max_files })
+    }
+}
+
+thread_local! {
+    static CONSOLE_MUTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with the terminal output of its messages dropped; the journal and
+/// the log file still get them
+pub fn muted<T>(f: impl FnOnce() -> T) -> T {
+    struct Unmute(bool);
+    impl Drop for Unmute {
+        fn drop(&mut self) {
+            CONSOLE_MUTED.with(|muted| muted.set(self.0));
+        }
+    }
+
+    let _unmute = Unmute(CONSOLE_MUTED.with(|muted| muted.replace(true)));
+    f()
+}
+
+fn console_muted() -> bool {
+    CONSOLE_MUTED.with(Cell::get)
+}
+
+/// Module of the crate an event comes from, "battery" for
+/// `auto_cpufreq::battery::thinkpad`; `None` for the binary itself
+fn module_of(target: &str) -> Option<&str> {
+    let mut segments = target.split("::");
+    match segments.next() {
+        Some("auto_cpufreq") => segments.next(),
+        _ => None,
+    }
+}
+
+/// The message, the `code` field and any other fields of an event
+#[derive(Default)]
+struct CliFields {
+    message: String,
+    code: Option<String>,
+    others: String,
+}
+
+impl Visit for CliFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "code" => self.code = Some(value.to_string()),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            "code" => self.code = Some(format!("{:?}", value)),
+            name => self.others.push_str(&format!(" {}={:?}", name, value)),
+        }
+    }
+}
+
+/// Terminal format: "WARNING: ..." and "ERROR: ..." like the rest of the
+/// output, info messages as they are
+struct CliFormat;
+
+impl<S, N> FormatEvent<S, N> for CliFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let metadata = event.metadata();
+        match *metadata.level() {
+            Level::ERROR => write!(writer, "ERROR: ")?,
+            Level::WARN => write!(writer, "WARNING: ")?,
+            Level::INFO => {}
+            level => write!(writer, "{}: ", level)?,
+        }
+
+        let mut fields = CliFields::default();
+        event.record(&mut fields);
+        if *metadata.level() != Level::INFO {
+            if let Some(module) = module_of(metadata.target()) {
+                write!(writer, "[{}] ", module)?;
+            }
+            if let Some(code) = &fields.code {
+                write!(writer, "{} ", code)?;
+            }
+        }
+        writeln!(writer, "{}{}", fields.message, fields.others)
+    }
+}
+
+fn rolling_appender(path: &Path, max_files: usize) -> Result<RollingFileAppender> {
+    let directory = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = path.file_stem().and_then(|s| s.to_str()).unwrap_or("auto-cpufreq");
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(prefix)
+        .max_log_files(max_files);
+    if let Some(extension) = path.extension().and_then(|s| s.to_str()) {
+        builder = builder.filename_suffix(extension);
+    }
+    builder.build(directory).with_context(|| format!("Failed to open log file in {}", directory.display()))
+}
+
+/// The dated files `[logging] file = path` has been rotated into
+pub fn log_files(path: &Path) -> Vec<PathBuf> {
+    let directory = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = format!("{}.", path.file_stem().and_then(|s| s.to_str()).unwrap_or("auto-cpufreq"));
+    let suffix = path.extension().and_then(|s| s.to_str()).map(|e| format!(".{}", e)).unwrap_or_default();
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Only the appender's `<stem>.YYYY-MM-DD[.ext]`, not whatever
+            // else shares the stem (e.g. syslog.1 next to syslog)
+            name.strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(&suffix))
+                .is_some_and(|date| date.len() == 10 && chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// `JOURNAL_STREAM`'s `<device>:<inode>` of the stream connected to the journal
+fn parse_journal_stream(value: &str) -> Option<(nix::libc::dev_t, nix::libc::ino_t)> {
+    let (device, inode) = value.split_once(':')?;
+    Some((device.parse().ok()?, inode.parse().ok()?))
+}
+
+/// Whether stderr is still the journal stream, rather than a terminal of a
+/// shell that merely inherited the variable
+fn stderr_is_journal() -> bool {
+    let Some(stream) = std::env::var("JOURNAL_STREAM").ok().as_deref().and_then(parse_journal_stream) else {
+        return false;
+    };
+    nix::sys::stat::fstat(std::io::stderr().as_raw_fd()).is_ok_and(|stat| (stat.st_dev, stat.st_ino) == stream)
+}
+
+/// Install the global subscriber. Call once, early in `main`.
+pub fn init(settings: &Settings) -> Result<()> {
+    let journald = if stderr_is_journal() {
+        // No prefix, so the event code is CODE= rather than F_CODE=
+        tracing_journald::layer()
+            .ok()
+            .map(|l| l.with_syslog_identifier("auto-cpufreq".to_string()).with_field_prefix(None))
+    } else {
+        None
+    };
+    let terminal = journald.is_none().then(|| {
+        tracing_subscriber::fmt::layer()
+            .event_format(CliFormat)
+            .with_writer(std::io::stderr)
+            .with_filter(filter_fn(|_| !console_muted()))
+    });
+
+    // A log file that can't be opened shouldn't keep the daemon from starting
+    let (file, file_error) = match settings.file.as_deref().map(|f| rolling_appender(f, settings.max_files)) {
+        Some(Ok(appender)) => (Some(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(appender)), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(settings.level)
+        .with(journald)
+        .with(terminal)
+        .with(file)
+        .try_init()
+        .context("Logging is already initialized")?;
+
+    if let Some(e) = file_error {
+        tracing::warn!(code = %crate::event_code::EventCode::LogFileFailed, "{:#}", e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logging_settings() {
+        let config = |ini: &str| Snapshot::from_str_ini(ini).unwrap();
+
+        let defaults = Settings::from_config(&Snapshot::default(), None).unwrap();
+        assert_eq!(defaults, Settings::default());
+        assert_eq!(defaults.level, LevelFilter::INFO);
+
+        let configured = config("[logging]\nlevel = Warning\nfile = /var/log/auto-cpufreq.log\nmax_files = 3\n");
+        let settings = Settings::from_config(&configured, None).unwrap();
+        assert_eq!(settings.level, LevelFilter::WARN);
+        assert_eq!(settings.file.as_deref(), Some(Path::new("/var/log/auto-cpufreq.log")));
+        assert_eq!(settings.max_files, 3);
+        assert_eq!(Settings::from_config(&configured, Some("debug")).unwrap().level, LevelFilter::DEBUG);
+
+        assert!(Settings::from_config(&configured, Some("loud")).is_err());
+        assert!(Settings::from_config(&config("[logging]\nmax_files = 0\n"), None).is_err());
+
+        assert_eq!(module_of("auto_cpufreq::battery::thinkpad"), Some("battery"));
+        assert_eq!(module_of("auto_cpufreq"), None);
+        assert_eq!(module_of("zbus::connection"), None);
+
+        assert_eq!(parse_journal_stream("8:3542"), Some((8, 3542)));
+        assert_eq!(parse_journal_stream("8"), None);
+        assert_eq!(parse_journal_stream("a:b"), None);
+
+        assert!(muted(console_muted));
+        assert!(!console_muted());
+    }
+
+    #[test]
+    fn test_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["syslog", "syslog.1", "syslog.2026-10-01", "syslog.2026-10-02", "syslog.2026-10-02.gz", "syslog.old-10-02"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let names = |path: &Path| {
+            log_files(path).iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&dir.path().join("syslog")), ["syslog.2026-10-01", "syslog.2026-10-02"]);
+
+        std::fs::write(dir.path().join("daemon.2026-10-03.log"), "").unwrap();
+        assert_eq!(names(&dir.path().join("daemon.log")), ["daemon.2026-10-03.log"]);
+    }
+}
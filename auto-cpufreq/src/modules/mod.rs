@@ -1,5 +1,11 @@
 pub mod system_info;
 pub mod system_monitor;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "metrics")]
+pub mod report;
+#[cfg(feature = "metrics")]
+pub mod watch;
 
 // Re-export commonly used items
 pub use system_info::*;
@@ -0,0 +1,424 @@
+// src/modules/report.rs - Shareable system/power report (--export-report)
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use anyhow::{Result, bail, Context};
+use chrono::Local;
+use sysinfo::System;
+
+use crate::modules::system_info::{CoreInfo, SystemInfo, SystemReport};
+use crate::stats_history::{self, History, Sample};
+use crate::CONFIG;
+
+/// The daemon's recorded history shown in the charts, seconds back from now
+const HISTORY_WINDOW: i64 = 24 * 60 * 60;
+/// Points per chart; the samples of the window are averaged down to this
+const CHART_POINTS: usize = 120;
+
+const NO_HISTORY: &str = "No history recorded in the last 24 hours, it is written by the running daemon";
+
+const SPARK_CHARS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Pick the format from the file extension (.html/.htm or .md/.markdown)
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        match ext.as_str() {
+            "html" | "htm" => Ok(Self::Html),
+            "md" | "markdown" => Ok(Self::Markdown),
+            _ => bail!("Unsupported report format '{}' (use .html or .md)", path.display()),
+        }
+    }
+}
+
+/// Everything that goes into a report, gathered once so both renderers agree
+pub struct ReportData {
+    pub generated: String,
+    pub version: String,
+    pub report: SystemReport,
+    pub config_path: Option<String>,
+    pub config: Vec<(String, Vec<(String, String)>)>,
+    /// Samples the daemon recorded over the last HISTORY_WINDOW, oldest first
+    pub history: Vec<Sample>,
+}
+
+/// Chart series of the recorded history, averaged down to CHART_POINTS
+struct HistorySeries {
+    usage: Vec<f32>,
+    frequency: Vec<f32>,
+    temperature: Vec<f32>,
+}
+
+impl HistorySeries {
+    fn new(samples: &[Sample]) -> Self {
+        let buckets = stats_history::buckets(samples, CHART_POINTS);
+        // Buckets without samples (the daemon wasn't running) are left out
+        let series = |value: fn(&Sample) -> Option<f32>| -> Vec<f32> {
+            buckets
+                .iter()
+                .filter(|bucket| bucket.iter().any(|s| value(s).is_some()))
+                .map(|bucket| average(bucket.iter().filter_map(value)))
+                .collect()
+        };
+        Self {
+            usage: series(|s| Some(s.cpu_usage)),
+            frequency: series(|s| Some(s.frequency)),
+            temperature: series(|s| s.temperature),
+        }
+    }
+}
+
+/// "2026-10-16 08:00 - 2026-10-16 20:00" span of the recorded history
+fn history_span(samples: &[Sample]) -> String {
+    let time = |sample: Option<&Sample>| {
+        sample.map(|s| stats_history::local_time(s.time, "%Y-%m-%d %H:%M")).unwrap_or_default()
+    };
+    format!("{} - {}", time(samples.first()), time(samples.last()))
+}
+
+impl ReportData {
+    pub fn collect() -> Self {
+        Self::collect_with(&History::default())
+    }
+
+    fn collect_with(history: &History) -> Self {
+        // Two CPU refreshes, the current usage is the difference
+        let mut sys = System::new_all();
+        thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        sys.refresh_cpu();
+
+        let report = SystemInfo::new().generate_system_report(&sys);
+
+        Self {
+            generated: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            version: crate::core::get_formatted_version().unwrap_or_default(),
+            report,
+            config_path: CONFIG
+                .has_config()
+                .then(|| CONFIG.get_path().display().to_string()),
+            config: CONFIG
+                .items()
+                .into_iter()
+                .filter(|(_, keys)| !keys.is_empty())
+                .collect(),
+            history: history.samples(Some(Local::now().timestamp() - HISTORY_WINDOW)),
+        }
+    }
+
+    /// Key/value rows shared by both formats, grouped by section title
+    fn sections(&self) -> Vec<(&'static str, Vec<(&'static str, String)>)> {
+        let r = &self.report;
+        let unknown = || "Unknown".to_string();
+        let battery = &r.battery_info;
+
+        let turbo = match r.is_turbo_on {
+            (Some(on), _) => if on { "On" } else { "Off" }.to_string(),
+            (None, Some(auto)) => format!("Auto ({})", if auto { "enabled" } else { "disabled" }),
+            _ => unknown(),
+        };
+
+        vec![
+            ("Hardware", vec![
+                ("Linux distro", format!("{} {}", r.distro_name, r.distro_ver)),
+                ("Linux kernel", r.kernel_version.clone()),
+                ("Processor", r.processor_model.clone()),
+                ("Cores", r.total_core.map(|c| c.to_string()).unwrap_or_else(unknown)),
+                ("Architecture", r.arch.clone()),
+                ("Driver", r.cpu_driver.clone().unwrap_or_else(unknown)),
+                ("CPU max frequency", r.cpu_max_freq.map(|f| format!("{:.0} MHz", f)).unwrap_or_else(unknown)),
+                ("CPU min frequency", r.cpu_min_freq.map(|f| format!("{:.0} MHz", f)).unwrap_or_else(unknown)),
+            ]),
+            ("Power", vec![
+                ("AC plugged", battery.is_ac_plugged.map(|ac| if ac { "Yes" } else { "No" }.to_string()).unwrap_or_else(unknown)),
+                ("Battery level", battery.battery_level.map(|b| format!("{}%", b)).unwrap_or_else(unknown)),
+                ("Power consumption", battery.power_consumption.map(|p| format!("{:.1} W", p)).unwrap_or_else(unknown)),
+                ("Average rate", battery.average_rate.map(|p| format!("{:.1} W", p)).unwrap_or_else(unknown)),
+                ("Start threshold", battery.charging_start_threshold.map(|t| format!("{}%", t)).unwrap_or_else(|| "Not set".to_string())),
+                ("Stop threshold", battery.charging_stop_threshold.map(|t| format!("{}%", t)).unwrap_or_else(|| "Not set".to_string())),
+            ]),
+            ("Current Stats", vec![
+                ("Governor", r.current_gov.clone().unwrap_or_else(unknown)),
+                ("EPP", r.current_epp.clone().unwrap_or_else(|| "Not supported".to_string())),
+                ("EPB", r.current_epb.clone().unwrap_or_else(|| "Not supported".to_string())),
+                ("HWP dynamic boost", r.intel_pstate.as_ref()
+                    .and_then(|p| p.hwp_dynamic_boost)
+                    .map(|b| if b { "On" } else { "Off" }.to_string())
+                    .unwrap_or_else(|| "Not supported".to_string())),
+                ("Performance range", r.intel_pstate.as_ref()
+                    .and_then(|p| Some(format!("{}% - {}%", p.min_perf_pct?, p.max_perf_pct?)))
+                    .unwrap_or_else(|| "Not supported".to_string())),
+                ("Platform profile", r.platform_profile.clone().unwrap_or_else(|| "Not supported".to_string())),
+                ("Conflicting services", if r.conflicts.any() { r.conflicts.describe().join(", ") } else { "None".to_string() }),
+                ("Turbo boost", turbo),
+                ("CPU usage", format!("{:.1}%", r.cpu_usage)),
+                ("System load", format!("{:.2}", r.load)),
+                ("CPU fan speed", r.cpu_fan_speed.map(|f| format!("{} RPM", f)).unwrap_or_else(unknown)),
+            ]),
+        ]
+    }
+}
+
+fn average(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+    if count == 0 { 0.0 } else { sum / count as f32 }
+}
+
+/// Unicode sparkline for the markdown report, scaled to the series' own range
+pub fn sparkline(values: &[f32]) -> String {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+    let top = SPARK_CHARS.len() - 1;
+
+    values
+        .iter()
+        .map(|v| {
+            let idx = if range > 0.0 { ((v - min) / range * top as f32).round() as usize } else { 0 };
+            SPARK_CHARS[idx.min(top)]
+        })
+        .collect()
+}
+
+/// "52 °C", or "--" while the core's sensor can't be read
+fn temperature(core: &CoreInfo) -> String {
+    core.temperature.map(|t| format!("{:.0} °C", t)).unwrap_or_else(|| "--".to_string())
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_markdown(data: &ReportData) -> String {
+    let mut out = String::with_capacity(4096);
+
+    let _ = writeln!(out, "# auto-cpufreq report\n");
+    let _ = writeln!(out, "Generated {} by auto-cpufreq {}\n", data.generated, data.version);
+
+    for (title, rows) in data.sections() {
+        let _ = writeln!(out, "## {}\n", title);
+        let _ = writeln!(out, "| | |\n|---|---|");
+        for (key, value) in rows {
+            let _ = writeln!(out, "| {} | {} |", key, value.replace('|', "\\|"));
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(out, "## Cores\n");
+    let _ = writeln!(out, "| Core | Usage | Temp | Freq |\n|---|---|---|---|");
+    for core in &data.report.cores_info {
+        let _ = writeln!(out, "| CPU{} | {:.1}% | {} | {:.0} MHz |",
+            core.id, core.usage, temperature(core), core.frequency);
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "## Configuration\n");
+    match &data.config_path {
+        Some(path) if !data.config.is_empty() => {
+            let _ = writeln!(out, "Using settings defined in `{}`\n", path);
+            let _ = writeln!(out, "```ini");
+            for (section, keys) in &data.config {
+                let _ = writeln!(out, "[{}]", section);
+                for (key, value) in keys {
+                    let _ = writeln!(out, "{} = {}", key, value);
+                }
+                out.push('\n');
+            }
+            let _ = writeln!(out, "```\n");
+        }
+        _ => { let _ = writeln!(out, "No config file in use, defaults apply\n"); }
+    }
+
+    let _ = writeln!(out, "## History\n");
+    if data.history.is_empty() {
+        let _ = writeln!(out, "{}", NO_HISTORY);
+    } else {
+        let series = HistorySeries::new(&data.history);
+        let _ = writeln!(out, "{} ({} samples)\n", history_span(&data.history), data.history.len());
+        let _ = writeln!(out, "```");
+        let _ = writeln!(out, "CPU usage   {}  {:.1}% avg", sparkline(&series.usage), average(series.usage.iter().copied()));
+        let _ = writeln!(out, "Frequency   {}  {:.0} MHz avg", sparkline(&series.frequency), average(series.frequency.iter().copied()));
+        if !series.temperature.is_empty() {
+            let _ = writeln!(out, "Temperature {}  {:.1} °C avg", sparkline(&series.temperature), average(series.temperature.iter().copied()));
+        }
+        let _ = writeln!(out, "```");
+    }
+
+    out
+}
+
+/// Inline SVG line chart, so the HTML report stays a single self-contained file
+fn svg_chart(title: &str, unit: &str, values: &[f32]) -> String {
+    const WIDTH: f32 = 480.0;
+    const HEIGHT: f32 = 120.0;
+
+    let max = values.iter().copied().fold(0.0_f32, f32::max).max(1.0);
+    let step = if values.len() > 1 { WIDTH / (values.len() - 1) as f32 } else { 0.0 };
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("{:.1},{:.1}", i as f32 * step, HEIGHT - v / max * HEIGHT))
+        .collect();
+
+    format!(
+        "<figure><figcaption>{} (max {:.1} {})</figcaption>\
+         <svg viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\">\
+         <polyline fill=\"none\" stroke=\"#3584e4\" stroke-width=\"2\" points=\"{}\"/></svg></figure>\n",
+        html_escape(title), max, html_escape(unit), WIDTH, HEIGHT, WIDTH, HEIGHT, points.join(" ")
+    )
+}
+
+pub fn render_html(data: &ReportData) -> String {
+    let mut out = String::with_capacity(8192);
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>auto-cpufreq report</title>\n<style>\n");
+    out.push_str("body { font-family: sans-serif; max-width: 60em; margin: 2em auto; }\n");
+    out.push_str("table { border-collapse: collapse; margin-bottom: 1em; }\n");
+    out.push_str("td, th { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }\n");
+    out.push_str("pre { background: #f4f4f4; padding: 0.6em; }\n");
+    out.push_str("</style>\n</head>\n<body>\n");
+
+    let _ = writeln!(out, "<h1>auto-cpufreq report</h1>");
+    let _ = writeln!(out, "<p>Generated {} by auto-cpufreq {}</p>",
+        html_escape(&data.generated), html_escape(&data.version));
+
+    for (title, rows) in data.sections() {
+        let _ = writeln!(out, "<h2>{}</h2>\n<table>", title);
+        for (key, value) in rows {
+            let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", key, html_escape(&value));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Cores</h2>\n<table>\n<tr><th>Core</th><th>Usage</th><th>Temp</th><th>Freq</th></tr>\n");
+    for core in &data.report.cores_info {
+        let _ = writeln!(out, "<tr><td>CPU{}</td><td>{:.1}%</td><td>{}</td><td>{:.0} MHz</td></tr>",
+            core.id, core.usage, temperature(core), core.frequency);
+    }
+    out.push_str("</table>\n");
+
+    out.push_str("<h2>Configuration</h2>\n");
+    match &data.config_path {
+        Some(path) if !data.config.is_empty() => {
+            let _ = writeln!(out, "<p>Using settings defined in <code>{}</code></p>\n<pre>", html_escape(path));
+            for (section, keys) in &data.config {
+                let _ = writeln!(out, "[{}]", html_escape(section));
+                for (key, value) in keys {
+                    let _ = writeln!(out, "{} = {}", html_escape(key), html_escape(value));
+                }
+                out.push('\n');
+            }
+            out.push_str("</pre>\n");
+        }
+        _ => out.push_str("<p>No config file in use, defaults apply</p>\n"),
+    }
+
+    out.push_str("<h2>History</h2>\n");
+    if data.history.is_empty() {
+        let _ = writeln!(out, "<p>{}</p>", NO_HISTORY);
+    } else {
+        let series = HistorySeries::new(&data.history);
+        let _ = writeln!(out, "<p>{} ({} samples)</p>",
+            html_escape(&history_span(&data.history)), data.history.len());
+        out.push_str(&svg_chart("CPU usage", "%", &series.usage));
+        out.push_str(&svg_chart("Average frequency", "MHz", &series.frequency));
+        if !series.temperature.is_empty() {
+            out.push_str(&svg_chart("Average temperature", "°C", &series.temperature));
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Write a report to `path`, the format follows the file extension
+pub fn export_report(path: &Path) -> Result<()> {
+    let format = ReportFormat::from_path(path)?;
+
+    let data = ReportData::collect();
+
+    let content = match format {
+        ReportFormat::Html => render_html(&data),
+        ReportFormat::Markdown => render_markdown(&data),
+    };
+
+    fs::write(path, content).with_context(|| format!("Failed to write report to {}", path.display()))?;
+    println!("Report written to {}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_format_from_path() {
+        assert_eq!(ReportFormat::from_path(Path::new("report.html")).unwrap(), ReportFormat::Html);
+        assert_eq!(ReportFormat::from_path(Path::new("report.MD")).unwrap(), ReportFormat::Markdown);
+        assert!(ReportFormat::from_path(Path::new("report.pdf")).is_err());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0]), "▁▅█");
+        assert_eq!(sparkline(&[3.0, 3.0]), "▁▁");
+    }
+
+    #[test]
+    fn test_history_from_the_daemon_store() {
+        let path = std::env::temp_dir().join(format!("acf-report-history-{}.bin", std::process::id()));
+        let history = History::new(&path, 64);
+        let now = Local::now().timestamp();
+        let sample = |time: i64, usage: f32| Sample {
+            time,
+            frequency: 2000.0,
+            temperature: None,
+            cpu_usage: usage,
+            load: 0.5,
+            power: None,
+            governor: "powersave".to_string(),
+            ac: false,
+            turbo: None,
+            battery: Some(80),
+        };
+        // Older than the window, left out
+        history.record(&sample(now - HISTORY_WINDOW - 60, 99.0)).unwrap();
+        history.record(&sample(now - 120, 10.0)).unwrap();
+        history.record(&sample(now - 60, 30.0)).unwrap();
+
+        let data = ReportData::collect_with(&history);
+        assert_eq!(data.history.len(), 2);
+        let series = HistorySeries::new(&data.history);
+        assert_eq!(series.usage, vec![10.0, 30.0]);
+        assert!(series.temperature.is_empty());
+        let markdown = render_markdown(&data);
+        assert!(markdown.contains("(2 samples)"));
+        assert!(!markdown.contains("Temperature "));
+
+        std::fs::remove_file(&path).unwrap();
+        let empty = ReportData::collect_with(&History::new(&path, 64));
+        assert!(render_html(&empty).contains(NO_HISTORY));
+    }
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(html_escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
@@ -5,21 +5,28 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
 
+use serde::Serialize;
 use sysinfo::System;
 
 use crate::CONFIG;
 use crate::POWER_SUPPLY_DIR;
 use crate::AVAILABLE_GOVERNORS_SORTED;
+use crate::cpu::intel_pstate::{self, IntelPstateInfo};
+use crate::battery::{history, PowerSupplies};
+use crate::cpu::platform_profile;
+use crate::cpu::rapl::{self, RaplInfo};
+use crate::power_helper::{self, ConflictState};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CoreInfo {
     pub id: usize,
     pub usage: f32,
-    pub temperature: f32,
+    /// °C, `None` while no sensor can be read
+    pub temperature: Option<f32>,
     pub frequency: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BatteryInfo {
     pub is_charging: Option<bool>,
     pub is_ac_plugged: Option<bool>,
@@ -27,9 +34,17 @@ pub struct BatteryInfo {
     pub charging_stop_threshold: Option<i32>,
     pub battery_level: Option<u8>,
     pub power_consumption: Option<f32>,
+    /// Average charge/discharge rate (W) over the last minutes
+    pub average_rate: Option<f32>,
+    /// Seconds
+    pub time_to_empty: Option<u64>,
+    /// Seconds, up to the stop threshold when one is set
+    pub time_to_full: Option<u64>,
+    /// Wh drawn from the battery since monitoring started
+    pub session_energy: Option<f32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SystemReport {
     pub distro_name: String,
     pub distro_ver: String,
@@ -50,6 +65,31 @@ pub struct SystemReport {
     pub cores_info: Vec<CoreInfo>,
     pub battery_info: BatteryInfo,
     pub is_turbo_on: (Option<bool>, Option<bool>),
+    pub intel_pstate: Option<IntelPstateInfo>,
+    pub platform_profile: Option<String>,
+    /// Energy-aware scheduling state, `None` when the machine can't do EAS
+    pub eas: Option<String>,
+    /// Skin/chassis sensor (°C), separate from the core temperatures
+    pub skin_temp: Option<f32>,
+    /// `[thermal] skin_temp_limit` (°C)
+    pub skin_temp_limit: f32,
+    pub rapl: Option<RaplInfo>,
+    /// power-profiles-daemon/TLP/tuned, refreshed every minute
+    pub conflicts: ConflictState,
+    /// intel_lpmd state, `None` when it isn't installed
+    pub intel_lpmd: Option<String>,
+    /// Config file in use, `None` when running on defaults
+    pub config_path: Option<String>,
+    /// Fields left empty because reading them needs root
+    pub unavailable: Vec<String>,
+}
+
+impl SystemReport {
+    /// Average over the cores whose sensor could be read
+    pub fn average_temperature(&self) -> Option<f32> {
+        let temps: Vec<f32> = self.cores_info.iter().filter_map(|c| c.temperature).collect();
+        (!temps.is_empty()).then(|| temps.iter().sum::<f32>() / temps.len() as f32)
+    }
 }
 
 // ============================================================================
@@ -117,24 +157,11 @@ impl TempSensorCache {
         self.last_scan = Instant::now();
     }
 
-    fn read_core_temp(&self, core_id: usize) -> f32 {
-        if let Some(path) = self.sensor_paths.get(&core_id) {
-            if let Ok(temp_str) = fs::read_to_string(path) {
-                if let Ok(temp) = temp_str.trim().parse::<f32>() {
-                    return temp / 1000.0;
-                }
-            }
-        }
-        
-        if let Some(ref path) = self.package_temp_path {
-            if let Ok(temp_str) = fs::read_to_string(path) {
-                if let Ok(temp) = temp_str.trim().parse::<f32>() {
-                    return temp / 1000.0;
-                }
-            }
-        }
-        
-        0.0
+    fn read_core_temp(&self, core_id: usize) -> Option<f32> {
+        self.sensor_paths
+            .get(&core_id)
+            .and_then(|path| crate::sensors::read_temperature(path))
+            .or_else(|| self.package_temp_path.as_deref().and_then(crate::sensors::read_temperature))
     }
 
     fn read_fan_speed(&self) -> Option<i32> {
@@ -297,6 +324,12 @@ pub struct SystemInfo {
     pub kernel_version: String,
 }
 
+impl Default for SystemInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SystemInfo {
     pub fn new() -> Self {
         let distro_name = Self::read_os_release_name().unwrap_or_else(|| "UNKNOWN".into());
@@ -320,7 +353,7 @@ impl SystemInfo {
         if let Ok(content) = fs::read_to_string("/etc/os-release") {
             for line in content.lines() {
                 if line.starts_with("PRETTY_NAME=") {
-                    return Some(line.splitn(2, '=').nth(1).unwrap_or("").trim_matches('"').to_string());
+                    return Some(line.split_once('=').map(|(_, v)| v).unwrap_or("").trim_matches('"').to_string());
                 }
             }
         }
@@ -331,7 +364,7 @@ impl SystemInfo {
         if let Ok(content) = fs::read_to_string("/etc/os-release") {
             for line in content.lines() {
                 if line.starts_with("VERSION=") {
-                    return Some(line.splitn(2, '=').nth(1).unwrap_or("").trim_matches('"').to_string());
+                    return Some(line.split_once('=').map(|(_, v)| v).unwrap_or("").trim_matches('"').to_string());
                 }
             }
         }
@@ -339,7 +372,7 @@ impl SystemInfo {
     }
 
     fn uname_release() -> Option<String> {
-        std::process::Command::new("uname").arg("-r").output().ok()
+        crate::command::new("uname").arg("-r").output().ok()
             .and_then(|o| String::from_utf8(o.stdout).ok())
             .map(|s| s.trim().to_string())
     }
@@ -394,13 +427,8 @@ impl SystemInfo {
         Some(CONFIG.get(section, "energy_performance_preference", "balance_power"))
     }
 
-    pub fn current_epb(is_ac_plugged: bool) -> Option<String> {
-        let epb_path = Path::new("/sys/devices/system/cpu/intel_pstate");
-        if !epb_path.exists() {
-            return None;
-        }
-        let section = if is_ac_plugged { "charger" } else { "battery" };
-        Some(CONFIG.get(section, "energy_perf_bias", "balance_power"))
+    pub fn current_epb() -> Option<String> {
+        crate::core::get_epb().map(crate::core::epb_name)
     }
 
     pub fn cpu_usage(sys: &System) -> f32 {
@@ -437,8 +465,7 @@ impl SystemInfo {
     pub fn avg_temp(sys: &System) -> i32 {
         let temps: Vec<f32> = Self::get_cpu_info(sys)
             .iter()
-            .map(|c| c.temperature)
-            .filter(|&t| t > 0.0)
+            .filter_map(|c| c.temperature)
             .collect();
         
         if temps.is_empty() { 
@@ -473,8 +500,8 @@ impl SystemInfo {
 
         if amd_pstate.exists() {
             if let Ok(s) = fs::read_to_string(amd_pstate) {
-                if s.trim() == "active" { return (None, Some(true)); }
-                return (None, Some(false));
+                let active = s.trim() == "active";
+                return (crate::cpu::amd_pstate::get_boost(), Some(active));
             }
             return (None, None);
         }
@@ -494,18 +521,19 @@ impl SystemInfo {
         let mut cache = BATTERY_PATH_CACHE.lock().unwrap();
         cache.maybe_rescan();
 
-        let mut is_ac_plugged = Some(true);
-
-        // Check mains status
-        if let Some(ref mains_path) = cache.mains_path {
-            if let Ok(online) = fs::read_to_string(mains_path.join("online")) {
-                is_ac_plugged = Some(online.trim() == "1");
-            }
+        // Status, level and energy cover all batteries (e.g. ThinkPads with
+        // BAT0 + BAT1), or just `battery_device` when one is configured
+        let mut supplies = PowerSupplies::scan();
+        let battery_device = CONFIG.get("battery", "battery_device", "");
+        if !battery_device.is_empty() {
+            supplies.batteries.retain(|b| b.name == battery_device);
         }
 
+        let is_ac_plugged = Some(supplies.ac_online.unwrap_or(true));
+
         let battery_path = match &cache.battery_path {
-            Some(p) => p,
-            None => {
+            Some(p) if !supplies.batteries.is_empty() => p,
+            _ => {
                 return BatteryInfo {
                     is_charging: None,
                     is_ac_plugged: Some(true),
@@ -513,16 +541,15 @@ impl SystemInfo {
                     charging_stop_threshold: None,
                     battery_level: None,
                     power_consumption: None,
+                    average_rate: None,
+                    time_to_empty: None,
+                    time_to_full: None,
+                    session_energy: None,
                 };
             }
         };
 
         // OPTIMIZED: Batch read all battery files
-        let status = fs::read_to_string(battery_path.join("status")).ok();
-        let capacity = fs::read_to_string(battery_path.join("capacity")).ok();
-        let energy_rate = fs::read_to_string(battery_path.join("power_now"))
-            .or_else(|_| fs::read_to_string(battery_path.join("current_now")))
-            .ok();
         let charge_start = fs::read_to_string(battery_path.join("charge_start_threshold"))
             .or_else(|_| fs::read_to_string(battery_path.join("charge_control_start_threshold")))
             .ok();
@@ -530,12 +557,27 @@ impl SystemInfo {
             .or_else(|_| fs::read_to_string(battery_path.join("charge_control_end_threshold")))
             .ok();
 
-        let is_charging = status.as_ref().map(|s| s.trim().to_lowercase() == "charging");
-        let battery_level = capacity.and_then(|c| c.trim().parse::<u8>().ok());
-        let power_consumption = energy_rate.and_then(|e| e.trim().parse::<f32>().ok()).map(|v| v / 1_000_000.0);
+        let is_charging = supplies.any_battery_charging();
+        let battery_level = supplies.combined_level();
+        let power_consumption = supplies.total(|b| b.power);
         let charging_start_threshold = charge_start.and_then(|s| s.trim().parse::<i32>().ok());
         let charging_stop_threshold = charge_stop.and_then(|s| s.trim().parse::<i32>().ok());
 
+        let energy_now = supplies.total(|b| b.energy_now);
+        let energy_full = supplies.total(|b| b.energy_full).map(|full| {
+            full * charging_stop_threshold.filter(|t| (1..100).contains(t)).unwrap_or(100) as f32 / 100.0
+        });
+        let estimates = {
+            let mut history = history::BATTERY_HISTORY.lock().unwrap();
+            history.record(history::Sample {
+                at: std::time::Instant::now(),
+                energy: energy_now,
+                power: power_consumption,
+                charging: is_charging.unwrap_or(false),
+            });
+            history.estimates(energy_full)
+        };
+
         BatteryInfo {
             is_charging,
             is_ac_plugged,
@@ -543,29 +585,70 @@ impl SystemInfo {
             charging_stop_threshold,
             battery_level,
             power_consumption,
+            average_rate: estimates.average_rate,
+            time_to_empty: estimates.time_to_empty.map(|d| d.as_secs()),
+            time_to_full: estimates.time_to_full.map(|d| d.as_secs()),
+            session_energy: energy_now.map(|_| estimates.session_energy),
         }
     }
 
     pub fn turbo_on_suggestion(sys: &System) -> bool {
         let usage = Self::cpu_usage(sys);
         if usage >= 20.0 { return true; }
-        if usage <= 25.0 && Self::avg_temp(sys) as f32 >= 70.0 { return false; }
+        if usage <= 25.0 && Self::avg_temp(sys) as f32 >= crate::thermal::turbo_temp_limit() - 5.0 { return false; }
         false
     }
 
     pub fn governor_suggestion() -> Option<String> {
         let batt = Self::battery_info();
-        if batt.is_ac_plugged.unwrap_or(true) {
-            AVAILABLE_GOVERNORS_SORTED.first().cloned()
-        } else {
-            AVAILABLE_GOVERNORS_SORTED.last().cloned()
+        crate::cpu::eas::suggest_governor(
+            &AVAILABLE_GOVERNORS_SORTED,
+            batt.is_ac_plugged.unwrap_or(true),
+            crate::cpu::eas::enabled(),
+        )
+    }
+
+    /// Names of the report fields an unprivileged run can't fill in
+    fn unavailable_fields() -> Vec<String> {
+        if nix::unistd::Uid::effective().is_root() {
+            return Vec::new();
+        }
+        let mut fields = Vec::new();
+        if rapl::is_present() && !rapl::energy_readable() {
+            fields.push("package_power".to_string());
         }
+        fields
     }
 
     // OPTIMIZED: Generate report without redundant refreshes
     pub fn generate_system_report(&self, sys: &System) -> SystemReport {
+        self.build_report(Self::get_cpu_info(sys), Self::cpu_usage(sys))
+    }
+
+    /// The same report with usage read from /proc/stat, for the daemon which
+    /// doesn't keep a sysinfo `System` around
+    pub fn generate_daemon_report(&self) -> SystemReport {
+        let usage = crate::cpu::usage::sample();
+        let temp_cache = TEMP_CACHE.lock().unwrap();
+        let cores = usage
+            .cores
+            .iter()
+            .enumerate()
+            .map(|(i, &core_usage)| CoreInfo {
+                id: i,
+                usage: core_usage,
+                frequency: crate::procfs::core_frequency(i),
+                temperature: temp_cache.read_core_temp(i),
+            })
+            .collect();
+        drop(temp_cache);
+
+        self.build_report(cores, usage.total)
+    }
+
+    fn build_report(&self, cores: Vec<CoreInfo>, cpu_usage: f32) -> SystemReport {
         let battery = Self::battery_info();
-        let cores = Self::get_cpu_info(sys);
+        let current_gov = Self::current_gov();
 
         SystemReport {
             distro_name: self.distro_name.clone(),
@@ -574,12 +657,13 @@ impl SystemInfo {
             processor_model: self.processor_model.clone(),
             total_core: self.total_cores,
             kernel_version: self.kernel_version.clone(),
-            current_gov: Self::current_gov(),
+            eas: crate::cpu::eas::Eas::detect().status(current_gov.as_deref()),
+            current_gov,
             current_epp: battery.is_ac_plugged.and_then(Self::current_epp),
-            current_epb: battery.is_ac_plugged.and_then(Self::current_epb),
+            current_epb: Self::current_epb(),
             cpu_driver: self.cpu_driver.clone(),
             cpu_fan_speed: Self::cpu_fan_speed(),
-            cpu_usage: Self::cpu_usage(sys),
+            cpu_usage,
             cpu_max_freq: Self::cpu_max_freq(),
             cpu_min_freq: Self::cpu_min_freq(),
             load: Self::system_load(),
@@ -587,6 +671,15 @@ impl SystemInfo {
             cores_info: cores,
             battery_info: battery,
             is_turbo_on: Self::turbo_on(),
+            intel_pstate: intel_pstate::info(),
+            platform_profile: platform_profile::current(),
+            skin_temp: crate::thermal::skin_temp(),
+            skin_temp_limit: crate::thermal::skin_temp_limit(),
+            rapl: rapl::info(),
+            conflicts: power_helper::conflict_state(),
+            intel_lpmd: power_helper::lpmd_state(),
+            config_path: CONFIG.has_config().then(|| CONFIG.get_path().display().to_string()),
+            unavailable: Self::unavailable_fields(),
         }
     }
 }
@@ -603,13 +696,13 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_millis(200));
         sys.refresh_cpu();
         let _ = s.generate_system_report(&sys);
+        let _ = s.generate_daemon_report();
     }
 
     #[test]
     fn test_temp_cache() {
         let cache = TEMP_CACHE.lock().unwrap();
-        let temp = cache.read_core_temp(0);
-        assert!(temp >= 0.0);
+        assert!(cache.read_core_temp(0).is_none_or(|t| t > 0.0));
     }
 
     #[test]
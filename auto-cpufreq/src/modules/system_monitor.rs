@@ -1,10 +1,14 @@
 // src/modules/system_monitor.rs - OPTIMIZED VERSION
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::fmt::Write as FmtWrite;
+use std::io::Write as IoWrite;
 
 use sysinfo::System;
 
+use crate::battery::history::format_duration;
+use crate::config::{CONFIG, Snapshot, parse_bool};
+use crate::core::Plan;
 use crate::modules::system_info::{SystemInfo, SystemReport};
 
 #[derive(Debug, Clone, Copy)]
@@ -24,6 +28,25 @@ impl std::fmt::Display for ViewType {
     }
 }
 
+/// `--output` formats: one sample per refresh instead of the terminal view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Csv,
+    Ndjson,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "csv" => Ok(OutputFormat::Csv),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            _ => Err(format!("invalid output format '{}' (expected csv or ndjson)", value)),
+        }
+    }
+}
+
 // ============================================================================
 // OPTIMIZATION: String buffer pooling
 // ============================================================================
@@ -38,10 +61,6 @@ impl StringBuffer {
         }
     }
 
-    fn clear(&mut self) {
-        self.buffer.clear();
-    }
-
     fn write_str(&mut self, s: &str) {
         self.buffer.push_str(s);
     }
@@ -55,16 +74,113 @@ impl StringBuffer {
     }
 }
 
+/// Sections of the monitor and the stats file, picked with `[stats] fields`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsField {
+    /// Distro, kernel, processor and config file
+    System,
+    /// Frequency limits, the per-core table and the fan
+    Cores,
+    Battery,
+    /// Governor, EPP/EPB, platform profile and the other scaling knobs
+    Governor,
+    /// CPU usage and load averages
+    Load,
+    Temps,
+    /// RAPL package power and limits
+    Power,
+    Turbo,
+}
+
+impl StatsField {
+    pub const ALL: [StatsField; 8] = [
+        StatsField::System,
+        StatsField::Cores,
+        StatsField::Battery,
+        StatsField::Governor,
+        StatsField::Load,
+        StatsField::Temps,
+        StatsField::Power,
+        StatsField::Turbo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StatsField::System => "system",
+            StatsField::Cores => "cores",
+            StatsField::Battery => "battery",
+            StatsField::Governor => "governor",
+            StatsField::Load => "load",
+            StatsField::Temps => "temps",
+            StatsField::Power => "power",
+            StatsField::Turbo => "turbo",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "temperature" | "temperatures" => Some(StatsField::Temps),
+            name => Self::ALL.into_iter().find(|f| f.name() == name),
+        }
+    }
+}
+
+/// A `[stats] fields` value: `governor, turbo` or `["governor", "turbo"]`
+pub fn parse_stats_fields(value: &str) -> anyhow::Result<Vec<StatsField>> {
+    let names = value.trim().trim_start_matches('[').trim_end_matches(']');
+    let mut fields = Vec::new();
+    for name in names.split([',', ' ']).map(|n| n.trim().trim_matches(['"', '\''])).filter(|n| !n.is_empty()) {
+        match StatsField::parse(name) {
+            Some(field) if !fields.contains(&field) => fields.push(field),
+            Some(_) => {}
+            None => anyhow::bail!(
+                "Unknown stats field '{}' (expected {})",
+                name,
+                StatsField::ALL.map(StatsField::name).join(", ")
+            ),
+        }
+    }
+    if fields.is_empty() {
+        anyhow::bail!("[stats] fields is empty");
+    }
+    Ok(fields)
+}
+
+/// `[stats] fields`, `None` for everything (also when the value is invalid)
+pub fn stats_fields(config: &Snapshot) -> Option<Vec<StatsField>> {
+    let value = config.get("stats", "fields")?;
+    parse_stats_fields(value)
+        .map_err(|e| tracing::warn!(code = %crate::event_code::EventCode::ConfigInvalid, "{}, showing all stats", e))
+        .ok()
+}
+
+/// What the columns show besides the report itself
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatOptions {
+    pub verbose: bool,
+    /// Sections to show, `None` for all of them
+    pub fields: Option<Vec<StatsField>>,
+    /// What the next daemon cycle would decide, `None` outside --monitor
+    pub preview: Option<Plan>,
+}
+
+impl FormatOptions {
+    fn shows(&self, field: StatsField) -> bool {
+        self.fields.as_ref().is_none_or(|fields| fields.contains(&field))
+    }
+}
+
 pub struct SystemMonitor {
     pub view: ViewType,
     pub suggestion: bool,
     pub verbose: bool,
     pub left: Vec<String>,
     pub right: Vec<String>,
+    /// Report behind the last `update()`, for JSON output
+    pub report: Option<SystemReport>,
+    /// Adjusts the CPU on each `update()` in the live view
+    pub controller: Option<crate::live::Controller>,
     sys: System,
-    // OPTIMIZED: Reusable string buffers
-    left_buffer: StringBuffer,
-    right_buffer: StringBuffer,
 }
 
 impl SystemMonitor {
@@ -74,96 +190,209 @@ impl SystemMonitor {
 
     pub fn new_with_verbose(view: ViewType, suggestion: bool, verbose: bool) -> Self {
         let sys = System::new_all();
-        
-        Self { 
-            view, 
+
+        Self {
+            view,
             suggestion,
             verbose,
-            left: Vec::new(), 
+            left: Vec::new(),
             right: Vec::new(),
+            report: None,
+            controller: None,
             sys,
-            left_buffer: StringBuffer::new(),
-            right_buffer: StringBuffer::new(),
         }
     }
 
     pub fn update(&mut self) {
+        if let Some(controller) = &mut self.controller {
+            controller.tick();
+        }
+
         // OPTIMIZED: Single refresh sequence
         self.sys.refresh_cpu();
         std::thread::sleep(Duration::from_millis(200));
         self.sys.refresh_cpu();
-        
+
         let sys_info = SystemInfo::new();
         let report = sys_info.generate_system_report(&self.sys);
         self.format_system_info(&report);
+        self.report = Some(report);
     }
 
-    // OPTIMIZED: Helper to format options efficiently
-    fn format_option<T: std::fmt::Display + std::fmt::Debug>(opt: Option<T>, verbose: bool) -> String {
-        if verbose {
-            format!("{:?}", opt)
-        } else {
-            opt.map(|v| v.to_string()).unwrap_or_else(|| "Unknown".to_string())
+    fn format_options(&self) -> FormatOptions {
+        let config = CONFIG.snapshot();
+        let verbose_config = config.get("stats", "verbose").and_then(parse_bool).unwrap_or(false);
+        FormatOptions {
+            verbose: self.verbose || verbose_config,
+            fields: stats_fields(&config),
+            preview: self.suggestion.then(|| crate::core::preview_cycle().ok()).flatten(),
         }
     }
 
-    fn format_battery_status(is_charging: Option<bool>, is_ac_plugged: Option<bool>, verbose: bool) -> String {
-        if verbose {
-            format!("is_charging: {:?}, is_ac_plugged: {:?}", is_charging, is_ac_plugged)
-        } else {
-            match (is_charging, is_ac_plugged) {
-                (Some(true), _) => "Charging".to_string(),
-                (Some(false), Some(false)) => "Discharging".to_string(),
-                (Some(false), Some(true)) => "Charged".to_string(),
-                _ => "Unknown".to_string(),
+    pub fn format_system_info(&mut self, report: &SystemReport) {
+        let options = self.format_options();
+        self.left = format_left_column(report, &options).to_lines();
+        self.right = format_right_column(report, &options).to_lines();
+        if let Some(last) = self.controller.as_ref().and_then(|c| c.last.as_ref()) {
+            let mut decisions = last.describe();
+            decisions.push(String::new());
+            self.right.splice(0..0, decisions);
+        }
+    }
+
+    /// The live controller got Ctrl+C or SIGTERM, the view should close
+    pub fn stop_requested(&mut self) -> bool {
+        self.controller.as_mut().is_some_and(|c| c.stop_requested())
+    }
+
+    /// Like `run_blocking`, but prints one JSON report per line instead of the columns.
+    /// Returns when stdout is closed.
+    pub fn run_blocking_json(&mut self) {
+        // Stops quietly once stdout is closed (e.g. piped into `head`)
+        let _ = self.run_export(OutputFormat::Ndjson, None, &mut std::io::stdout());
+    }
+
+    /// Write a sample to `out` every refresh, until `duration` has passed
+    /// (forever without one) or writing fails
+    pub fn run_export(&mut self, format: OutputFormat, duration: Option<Duration>, out: &mut dyn IoWrite) -> std::io::Result<()> {
+        let started = Instant::now();
+        // CSV columns are fixed by the cores of the first sample
+        let mut csv_cores: Option<Vec<usize>> = None;
+
+        loop {
+            self.update();
+
+            if let Some(report) = &self.report {
+                let timestamp = chrono::Local::now().to_rfc3339();
+                match format {
+                    OutputFormat::Ndjson => {
+                        let line = serde_json::json!({
+                            "timestamp": timestamp,
+                            "view": self.view.to_string().to_lowercase(),
+                            "report": report,
+                        });
+                        writeln!(out, "{}", line)?;
+                    }
+                    OutputFormat::Csv => {
+                        let cores = match &csv_cores {
+                            Some(cores) => cores,
+                            None => {
+                                let cores = csv_cores.insert(report.cores_info.iter().map(|c| c.id).collect());
+                                writeln!(out, "{}", csv_header(cores))?;
+                                cores
+                            }
+                        };
+                        writeln!(out, "{}", csv_row(report, &timestamp, cores))?;
+                    }
+                }
+                out.flush()?;
+            }
+
+            if duration.is_some_and(|d| started.elapsed() >= d) {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_secs(2));
+            if self.stop_requested() {
+                return Ok(());
             }
         }
     }
 
-    // OPTIMIZED: Format using string buffers instead of Vec allocations
-    pub fn format_system_info(&mut self, report: &SystemReport) {
-        // Clear buffers
-        self.left_buffer.clear();
-        self.right_buffer.clear();
+    /// Interactive view on a terminal (the "tui" feature), otherwise the
+    /// formatted columns printed to stdout every 2s. Returns when the user quits.
+    pub fn run_blocking(&mut self) {
+        #[cfg(feature = "tui")]
+        if std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+            if let Err(e) = crate::modules::tui::run(self) {
+                tracing::error!("Terminal UI failed: {}", e);
+            }
+            return;
+        }
+
+        loop {
+            self.update();
+
+            // Clear screen
+            print!("\x1B[2J\x1B[1;1H");
+
+            // OPTIMIZATION: Daha geniş ekran - 100 karakter
+            let width = 100usize;
+            let half = width / 2 - 2;
+            let rows = std::cmp::max(self.left.len(), self.right.len());
+
+            for i in 0..rows {
+                let left = self.left.get(i).map(String::as_str).unwrap_or("");
+                let right = self.right.get(i).map(String::as_str).unwrap_or("");
+
+                if left.len() > half {
+                    let truncate_at = half.saturating_sub(3);
+                    println!("{:<half$}... │ {}", &left[..truncate_at], right, half=half);
+                } else {
+                    println!("{:<half$} │ {}", left, right, half=half);
+                }
+            }
+
+            thread::sleep(Duration::from_secs(2));
+            if self.stop_requested() {
+                return;
+            }
+        }
+    }
+}
 
-        // ========== LEFT COLUMN ==========
-        self.format_left_column(report);
-        
-        // ========== RIGHT COLUMN ==========
-        self.format_right_column(report);
+// OPTIMIZED: Helper to format options efficiently
+fn format_option<T: std::fmt::Display + std::fmt::Debug>(opt: Option<T>, verbose: bool) -> String {
+    if verbose {
+        format!("{:?}", opt)
+    } else {
+        opt.map(|v| v.to_string()).unwrap_or_else(|| "Unknown".to_string())
+    }
+}
 
-        // Convert buffers to line vectors
-        self.left = self.left_buffer.to_lines();
-        self.right = self.right_buffer.to_lines();
+fn format_battery_status(is_charging: Option<bool>, is_ac_plugged: Option<bool>, verbose: bool) -> String {
+    if verbose {
+        format!("is_charging: {:?}, is_ac_plugged: {:?}", is_charging, is_ac_plugged)
+    } else {
+        match (is_charging, is_ac_plugged) {
+            (Some(true), _) => "Charging".to_string(),
+            (Some(false), Some(false)) => "Discharging".to_string(),
+            (Some(false), Some(true)) => "Charged".to_string(),
+            _ => "Unknown".to_string(),
+        }
     }
+}
 
-    fn format_left_column(&mut self, report: &SystemReport) {
-        let buf = &mut self.left_buffer;
+/// System information, CPU frequencies and the per-core table
+fn format_left_column(report: &SystemReport, options: &FormatOptions) -> StringBuffer {
+    let mut buf = StringBuffer::new();
 
+    if options.shows(StatsField::System) {
         // System Information
         buf.write_str("System Information\n\n");
         buf.write_fmt(format_args!("Linux distro: {} {}\n", report.distro_name, report.distro_ver));
         buf.write_fmt(format_args!("Linux kernel: {}\n", report.kernel_version));
         buf.write_fmt(format_args!("Processor: {}\n", report.processor_model));
-        
-        if self.verbose {
+
+        if options.verbose {
             buf.write_fmt(format_args!("Cores: {:?}\n", report.total_core));
             buf.write_fmt(format_args!("Driver: {:?}\n", report.cpu_driver));
         } else {
-            buf.write_fmt(format_args!("Cores: {}\n", Self::format_option(report.total_core, false)));
+            buf.write_fmt(format_args!("Cores: {}\n", format_option(report.total_core, false)));
             buf.write_fmt(format_args!("Driver: {}\n", report.cpu_driver.as_deref().unwrap_or("Unknown")));
         }
-        
+
         buf.write_fmt(format_args!("Architecture: {}\n\n", report.arch));
 
-        if crate::CONFIG.has_config() {
-            buf.write_fmt(format_args!("Using settings defined in {}\n\n", crate::CONFIG.get_path().display()));
+        if let Some(path) = &report.config_path {
+            buf.write_fmt(format_args!("Using settings defined in {}\n\n", path));
         }
+    }
 
+    if options.shows(StatsField::Cores) {
         // Current CPU Stats
         buf.write_str("Current CPU Stats\n\n");
-        
-        if self.verbose {
+
+        if options.verbose {
             buf.write_fmt(format_args!("CPU max frequency: {:?} MHz\n", report.cpu_max_freq));
             buf.write_fmt(format_args!("CPU min frequency: {:?} MHz\n\n", report.cpu_min_freq));
         } else {
@@ -172,19 +401,18 @@ impl SystemMonitor {
             buf.write_fmt(format_args!("CPU max frequency: {} MHz\n", max_freq));
             buf.write_fmt(format_args!("CPU min frequency: {} MHz\n\n", min_freq));
         }
-        
+
         // Core info header
         buf.write_fmt(format_args!("{:<5} {:<7} {:<11} {:<8}\n", "Core", "Usage", "Temp", "Freq"));
 
         // Core info rows
         for core in &report.cores_info {
-            let temp_str = if core.temperature > 0.0 {
-                format!("{:.0}°C", core.temperature)
-            } else {
-                "--°C".to_string()
+            let temp_str = match core.temperature {
+                Some(temperature) => format!("{:.0}°C", temperature),
+                None => "--°C".to_string(),
             };
-            
-            buf.write_fmt(format_args!("{:<5} {:>6.1}% {:<11} {:>5.0} MHz\n", 
+
+            buf.write_fmt(format_args!("{:<5} {:>6.1}% {:<11} {:>5.0} MHz\n",
                 format!("CPU{}", core.id),
                 core.usage,
                 temp_str,
@@ -198,22 +426,72 @@ impl SystemMonitor {
         }
     }
 
-    fn format_right_column(&mut self, report: &SystemReport) {
-        let buf = &mut self.right_buffer;
+    buf
+}
+
+/// Battery, frequency scaling and system statistics
+/// `current → target (reasons)`, just the target when nothing changes
+fn preview_line(name: &str, current: Option<&str>, target: &str, reasons: &[String]) -> String {
+    let mut line = match current {
+        Some(current) if current != target => format!("{}: {} → {}", name, current, target),
+        _ => format!("{}: {}", name, target),
+    };
+    if !reasons.is_empty() {
+        let _ = write!(line, " ({})", reasons.join(", "));
+    }
+    line
+}
+
+/// The --monitor dry run: what the daemon would set on its next cycle
+fn format_preview(buf: &mut StringBuffer, report: &SystemReport, plan: &Plan) {
+    buf.write_str("Daemon decisions (dry run)\n\n");
+    if let Some(profile) = &plan.profile {
+        buf.write_fmt(format_args!("Profile: {}\n", profile.name));
+    }
+    buf.write_fmt(format_args!(
+        "{}\n",
+        preview_line("Governor", report.current_gov.as_deref(), plan.governor, &plan.governor_reasons)
+    ));
+
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    match &plan.turbo {
+        Some((on, reason)) => buf.write_fmt(format_args!(
+            "{}\n",
+            preview_line("Turbo", report.is_turbo_on.0.map(on_off), on_off(*on), std::slice::from_ref(reason))
+        )),
+        None => buf.write_str("Turbo: left as is\n"),
+    }
+    match &plan.epp {
+        Some((epp, reasons)) => {
+            buf.write_fmt(format_args!("{}\n", preview_line("EPP", report.current_epp.as_deref(), epp, reasons)))
+        }
+        None if report.current_epp.is_some() => buf.write_str("EPP: left as is\n"),
+        None => {}
+    }
+    buf.write_str("\n");
+}
+
+fn format_right_column(report: &SystemReport, options: &FormatOptions) -> StringBuffer {
+    let mut buf = StringBuffer::new();
 
+    if let Some(plan) = &options.preview {
+        format_preview(&mut buf, report, plan);
+    }
+
+    if options.shows(StatsField::Battery) {
         // Battery Stats
         buf.write_str("Battery Stats\n\n");
-        
-        if self.verbose {
+
+        if options.verbose {
             buf.write_fmt(format_args!("Battery info: {:?}\n\n", report.battery_info));
         } else {
-            let battery_status = Self::format_battery_status(
-                report.battery_info.is_charging, 
+            let battery_status = format_battery_status(
+                report.battery_info.is_charging,
                 report.battery_info.is_ac_plugged,
                 false
             );
             buf.write_fmt(format_args!("Battery status: {}\n", battery_status));
-            
+
             let battery_level = report.battery_info.battery_level
                 .map(|b| format!("{}%", b))
                 .unwrap_or_else(|| "Unknown".to_string());
@@ -232,16 +510,39 @@ impl SystemMonitor {
             let stop_threshold = report.battery_info.charging_stop_threshold
                 .map(|t| format!("{}%", t))
                 .unwrap_or_else(|| "Not set".to_string());
-            buf.write_fmt(format_args!("Stop threshold: {}\n\n", stop_threshold));
+            buf.write_fmt(format_args!("Stop threshold: {}\n", stop_threshold));
+
+            let info = &report.battery_info;
+            if let Some(rate) = info.average_rate {
+                let kind = if info.is_charging.unwrap_or(false) { "charge" } else { "discharge" };
+                buf.write_fmt(format_args!("Avg. {} rate: {:.1} W\n", kind, rate));
+            }
+            if let Some(secs) = info.time_to_empty {
+                buf.write_fmt(format_args!("Time to empty: {}\n", format_duration(Duration::from_secs(secs))));
+            }
+            if let Some(secs) = info.time_to_full {
+                buf.write_fmt(format_args!("Time to full: {}\n", format_duration(Duration::from_secs(secs))));
+            }
+            if let Some(energy) = info.session_energy.filter(|e| *e > 0.0) {
+                buf.write_fmt(format_args!("Used this session: {:.1} Wh\n", energy));
+            }
+            buf.write_str("\n");
         }
+    }
 
+    if options.shows(StatsField::Governor) {
         // CPU Frequency Scaling
         buf.write_str("CPU Frequency Scaling\n\n");
-        
-        if self.verbose {
+
+        if options.verbose {
             buf.write_fmt(format_args!("Current governor: {:?}\n", report.current_gov));
             buf.write_fmt(format_args!("EPP: {:?}\n", report.current_epp));
             buf.write_fmt(format_args!("EPB: {:?}\n", report.current_epb));
+            buf.write_fmt(format_args!("intel_pstate: {:?}\n", report.intel_pstate));
+            buf.write_fmt(format_args!("Platform profile: {:?}\n", report.platform_profile));
+            buf.write_fmt(format_args!("Energy-aware scheduling: {:?}\n", report.eas));
+            buf.write_fmt(format_args!("Intel LPMD: {:?}\n", report.intel_lpmd));
+            buf.write_fmt(format_args!("Conflicts: {:?}\n", report.conflicts));
         } else {
             let current_gov = report.current_gov.as_deref().unwrap_or("Unknown");
             buf.write_fmt(format_args!("Current governor: {}\n", current_gov));
@@ -255,97 +556,177 @@ impl SystemMonitor {
             if let Some(epb) = &report.current_epb {
                 buf.write_fmt(format_args!("EPB: {}\n", epb));
             }
-        }
 
-        if self.suggestion {
-            if let Some(sugg) = SystemInfo::governor_suggestion() {
-                if report.current_gov.as_deref() != Some(&sugg) {
-                    buf.write_fmt(format_args!("Suggested governor: {}\n", sugg));
+            if let Some(profile) = &report.platform_profile {
+                buf.write_fmt(format_args!("Platform profile: {}\n", profile));
+            }
+
+            if let Some(eas) = &report.eas {
+                buf.write_fmt(format_args!("Energy-aware scheduling: {}\n", eas));
+            }
+
+            if let Some(lpmd) = &report.intel_lpmd {
+                buf.write_fmt(format_args!("Intel LPMD: {}\n", lpmd));
+            }
+
+            if let Some(pstate) = &report.intel_pstate {
+                if let Some(boost) = pstate.hwp_dynamic_boost {
+                    buf.write_fmt(format_args!("HWP dynamic boost: {}\n", if boost { "On" } else { "Off" }));
+                }
+                if let (Some(min), Some(max)) = (pstate.min_perf_pct, pstate.max_perf_pct) {
+                    buf.write_fmt(format_args!("Performance range: {}% - {}%\n", min, max));
                 }
             }
+
+            if report.conflicts.any() {
+                buf.write_fmt(format_args!("Conflicting services: {}\n", report.conflicts.describe().join(", ")));
+            }
         }
 
         buf.write_str("\n");
+    }
 
-        // System Statistics
+    // System Statistics
+    let statistics = [StatsField::Load, StatsField::Temps, StatsField::Power, StatsField::Turbo];
+    if statistics.into_iter().any(|f| options.shows(f)) {
         buf.write_str("System Statistics\n\n");
+    }
+    if options.shows(StatsField::Load) {
         buf.write_fmt(format_args!("CPU usage: {:.1}%\n", report.cpu_usage));
         buf.write_fmt(format_args!("System load: {:.2}\n", report.load));
+    }
 
-        if !report.cores_info.is_empty() {
-            let avg_temp: f32 = report.cores_info.iter()
-                .map(|c| c.temperature)
-                .filter(|&t| t > 0.0)
-                .sum::<f32>();
-            let temp_count = report.cores_info.iter()
-                .filter(|c| c.temperature > 0.0)
-                .count();
-            
-            if temp_count > 0 {
-                let avg_temp = avg_temp / temp_count as f32;
-                buf.write_fmt(format_args!("Average temp: {:.1} °C\n", avg_temp));
-            }
+    if options.shows(StatsField::Temps) {
+        if let Some(avg_temp) = report.average_temperature() {
+            buf.write_fmt(format_args!("Average temp: {:.1} °C\n", avg_temp));
         }
+    }
 
-        if let Some((a, b, c)) = report.avg_load {
-            let load_status = if report.load < 1.0 { "optimal" } else { "high" };
-            buf.write_fmt(format_args!("Load {}: {:.2}, {:.2}, {:.2}\n", load_status, a, b, c));
+    if let Some(rapl) = report.rapl.as_ref().filter(|_| options.shows(StatsField::Power)) {
+        if let Some(power) = rapl.package_power {
+            buf.write_fmt(format_args!("Package power: {:.1} W\n", power));
+        } else if report.unavailable.iter().any(|f| f == "package_power") {
+            buf.write_str("Package power: n/a (needs root)\n");
         }
-
-        // Turbo status
-        if self.verbose {
-            buf.write_fmt(format_args!("Turbo boost: {:?}\n", report.is_turbo_on));
-        } else {
-            let turbo_status = match (report.is_turbo_on.0, report.is_turbo_on.1) {
-                (Some(on), _) => if on { "On" } else { "Off" }.to_string(),
-                (None, Some(auto)) => format!("Auto ({})", if auto { "enabled" } else { "disabled" }),
-                _ => "Unknown".to_string(),
-            };
-            buf.write_fmt(format_args!("Turbo boost: {}\n", turbo_status));
+        if let (Some(pl1), Some(pl2)) = (rapl.pl1, rapl.pl2) {
+            buf.write_fmt(format_args!("Power limits: PL1 {:.0} W, PL2 {:.0} W\n", pl1, pl2));
         }
+    }
 
-        if self.suggestion {
-            if let Some(on) = report.is_turbo_on.0 {
-                let sugg = SystemInfo::turbo_on_suggestion(&self.sys);
-                if sugg != on {
-                    buf.write_fmt(format_args!("Suggested turbo: {}\n", if sugg { "On" } else { "Off" }));
-                }
-            }
-        }
+    if let Some(skin) = report.skin_temp.filter(|_| options.shows(StatsField::Temps)) {
+        let status = if skin >= report.skin_temp_limit { " (above limit, turbo capped)" } else { "" };
+        buf.write_fmt(format_args!("Skin temp: {:.1} °C{}\n", skin, status));
     }
 
-    /// Simple blocking run that prints the formatted columns to stdout every 2s.
-  pub fn run_blocking(&mut self) {
-        loop {
-            self.update();
+    if let Some((a, b, c)) = report.avg_load.filter(|_| options.shows(StatsField::Load)) {
+        let load_status = if report.load < 1.0 { "optimal" } else { "high" };
+        buf.write_fmt(format_args!("Load {}: {:.2}, {:.2}, {:.2}\n", load_status, a, b, c));
+    }
 
-            // Clear screen
-            print!("\x1B[2J\x1B[1;1H");
+    if !options.shows(StatsField::Turbo) {
+        return buf;
+    }
 
-            // OPTIMIZATION: Daha geniş ekran - 100 karakter
-            let width = 100usize;
-            let half = width / 2 - 2;
-            let rows = std::cmp::max(self.left.len(), self.right.len());
+    // Turbo status
+    if options.verbose {
+        buf.write_fmt(format_args!("Turbo boost: {:?}\n", report.is_turbo_on));
+    } else {
+        let turbo_status = match (report.is_turbo_on.0, report.is_turbo_on.1) {
+            (Some(on), _) => if on { "On" } else { "Off" }.to_string(),
+            (None, Some(auto)) => format!("Auto ({})", if auto { "enabled" } else { "disabled" }),
+            _ => "Unknown".to_string(),
+        };
+        buf.write_fmt(format_args!("Turbo boost: {}\n", turbo_status));
+    }
 
-            for i in 0..rows {
-                let left = self.left.get(i).map(String::as_str).unwrap_or("");
-                let right = self.right.get(i).map(String::as_str).unwrap_or("");
+    buf
+}
 
-                if left.len() > half {
-                    let truncate_at = half.saturating_sub(3);
-                    print!("{:<half$}... │ {}\n", &left[..truncate_at], right, half=half);
-                } else {
-                    println!("{:<half$} │ {}", left, right, half=half);
-                }
+fn csv_header(cores: &[usize]) -> String {
+    let mut header = String::from(
+        "timestamp,governor,epp,cpu_usage,load,turbo,ac_plugged,battery_percent,power_w,package_power_w,skin_temp_c",
+    );
+    for id in cores {
+        let _ = write!(header, ",cpu{id}_usage,cpu{id}_mhz,cpu{id}_temp_c");
+    }
+    header
+}
+
+/// One `--output csv` line; unknown values are left empty
+fn csv_row(report: &SystemReport, timestamp: &str, cores: &[usize]) -> String {
+    fn field<T: std::fmt::Display>(value: Option<T>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    let mut row = format!(
+        "{},{},{},{:.1},{:.2},{},{},{},{},{},{}",
+        timestamp,
+        field(report.current_gov.as_deref()),
+        field(report.current_epp.as_deref()),
+        report.cpu_usage,
+        report.load,
+        field(report.is_turbo_on.0),
+        field(report.battery_info.is_ac_plugged),
+        field(report.battery_info.battery_level),
+        field(report.battery_info.power_consumption.map(|w| format!("{:.2}", w))),
+        field(report.rapl.as_ref().and_then(|r| r.package_power).map(|w| format!("{:.2}", w))),
+        field(report.skin_temp.map(|t| format!("{:.1}", t))),
+    );
+    for id in cores {
+        match report.cores_info.iter().find(|c| c.id == *id) {
+            Some(core) => {
+                let temperature = core.temperature.map(|t| format!("{:.1}", t));
+                let _ = write!(row, ",{:.1},{:.0},{}", core.usage, core.frequency, field(temperature));
             }
+            // Core went offline since the header was written
+            None => row.push_str(",,,"),
+        }
+    }
+    row
+}
 
-            thread::sleep(Duration::from_secs(2));
+/// Contents of the daemon's stats file for one cycle, limited to `fields`
+/// (all of them for `None`)
+pub fn format_stats(report: &SystemReport, timestamp: &str, fields: Option<&[StatsField]>) -> String {
+    let shows = |field: StatsField| fields.is_none_or(|fields| fields.contains(&field));
+    let mut buf = StringBuffer::new();
+
+    buf.write_fmt(format_args!("\n{}\n", "=".repeat(80)));
+    buf.write_fmt(format_args!("auto-cpufreq daemon - {}\n", timestamp));
+    buf.write_fmt(format_args!("{}\n\n", "=".repeat(80)));
+
+    if shows(StatsField::Load) {
+        buf.write_fmt(format_args!("CPU usage: {:.1}%\n", report.cpu_usage));
+        if let Some((one, five, fifteen)) = report.avg_load {
+            buf.write_fmt(format_args!("Load: {:.2}, {:.2}, {:.2}\n", one, five, fifteen));
         }
     }
+
+    if let Some(gov) = report.current_gov.as_ref().filter(|_| shows(StatsField::Governor)) {
+        buf.write_fmt(format_args!("Governor: {}\n", gov));
+    }
+
+    if let Some(on) = report.is_turbo_on.0.filter(|_| shows(StatsField::Turbo)) {
+        buf.write_fmt(format_args!("Turbo: {}\n", if on { "On" } else { "Off" }));
+    }
+
+    if let Some(ac) = report.battery_info.is_ac_plugged.filter(|_| shows(StatsField::Battery)) {
+        buf.write_fmt(format_args!("Battery: {}\n", if ac { "Charging" } else { "Discharging" }));
+    }
+
+    buf.write_fmt(format_args!("\n{}\n", "-".repeat(80)));
+    buf.buffer
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+
+    use crate::cpu::intel_pstate::IntelPstateInfo;
+    use crate::cpu::rapl::RaplInfo;
+    use crate::modules::system_info::{BatteryInfo, CoreInfo};
+    use crate::power_helper::ConflictState;
 
     #[test]
     fn test_string_buffer() {
@@ -365,4 +746,217 @@ mod tests {
         assert!(!monitor.left.is_empty());
         assert!(!monitor.right.is_empty());
     }
+
+    #[test]
+    fn test_csv_export() {
+        let report = sample_report();
+        let header = csv_header(&[0, 1]);
+        assert!(header.ends_with(",cpu0_usage,cpu0_mhz,cpu0_temp_c,cpu1_usage,cpu1_mhz,cpu1_temp_c"));
+
+        let row = csv_row(&report, "2026-10-16T14:02:11+02:00", &[0, 1, 2]);
+        assert_eq!(
+            row,
+            "2026-10-16T14:02:11+02:00,powersave,balance_power,12.5,0.42,false,false,64,7.50,4.20,36.5,15.0,1200,48.0,10.0,900,46.0,,,"
+        );
+        assert_eq!(row.split(',').count(), header.split(',').count() + 3);
+        assert_eq!("NDJSON".parse::<OutputFormat>(), Ok(OutputFormat::Ndjson));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_stats_fields() {
+        assert_eq!(parse_stats_fields(r#"["governor", "turbo","Temperature"]"#).unwrap(), vec![
+            StatsField::Governor,
+            StatsField::Turbo,
+            StatsField::Temps
+        ]);
+        assert_eq!(parse_stats_fields("battery, load battery").unwrap(), vec![StatsField::Battery, StatsField::Load]);
+        assert!(parse_stats_fields("governor, fans").is_err());
+        assert!(parse_stats_fields("[]").is_err());
+
+        let config = Snapshot::from_str_ini("[stats]\nfields = nonsense\n").unwrap();
+        assert_eq!(stats_fields(&config), None);
+
+        let options = FormatOptions { fields: Some(vec![StatsField::Governor, StatsField::Turbo]), ..FormatOptions::default() };
+        let text = columns(&sample_report(), &options);
+        assert!(text.contains("Current governor: powersave") && text.contains("Turbo boost: Off"));
+        assert!(!text.contains("Battery Stats") && !text.contains("CPU usage") && !text.contains("Linux kernel"));
+
+        let stats = format_stats(&sample_report(), "2024-05-01 12:00:00", Some(&[StatsField::Battery]));
+        assert!(stats.contains("Battery: Discharging") && !stats.contains("Governor"));
+    }
+
+    /// A discharging Intel laptop with every sensor present
+    fn sample_report() -> SystemReport {
+        SystemReport {
+            distro_name: "Fedora Linux".to_string(),
+            distro_ver: "40".to_string(),
+            arch: "x86_64".to_string(),
+            processor_model: "Intel(R) Core(TM) i7-1165G7 @ 2.80GHz".to_string(),
+            total_core: Some(2),
+            kernel_version: "6.8.9-300.fc40.x86_64".to_string(),
+            current_gov: Some("powersave".to_string()),
+            current_epp: Some("balance_power".to_string()),
+            current_epb: Some("balance_power".to_string()),
+            cpu_driver: Some("intel_pstate".to_string()),
+            cpu_fan_speed: Some(2100),
+            cpu_usage: 12.5,
+            cpu_max_freq: Some(4700.0),
+            cpu_min_freq: Some(400.0),
+            load: 0.42,
+            avg_load: Some((0.42, 0.55, 0.61)),
+            cores_info: vec![
+                CoreInfo { id: 0, usage: 15.0, temperature: Some(48.0), frequency: 1200.0 },
+                CoreInfo { id: 1, usage: 10.0, temperature: Some(46.0), frequency: 900.0 },
+            ],
+            battery_info: BatteryInfo {
+                is_charging: Some(false),
+                is_ac_plugged: Some(false),
+                charging_start_threshold: Some(75),
+                charging_stop_threshold: Some(80),
+                battery_level: Some(64),
+                power_consumption: Some(7.5),
+                average_rate: Some(7.2),
+                time_to_empty: Some(4 * 3600 + 25 * 60),
+                time_to_full: None,
+                session_energy: Some(3.4),
+            },
+            is_turbo_on: (Some(false), Some(false)),
+            intel_pstate: Some(IntelPstateInfo {
+                status: Some("active".to_string()),
+                hwp_dynamic_boost: Some(false),
+                min_perf_pct: Some(9),
+                max_perf_pct: Some(100),
+            }),
+            platform_profile: Some("low-power".to_string()),
+            eas: None,
+            skin_temp: Some(36.5),
+            skin_temp_limit: 45.0,
+            rapl: Some(RaplInfo { package_power: Some(4.2), pl1: Some(28.0), pl2: Some(64.0) }),
+            conflicts: ConflictState::default(),
+            intel_lpmd: None,
+            config_path: Some("/etc/auto-cpufreq.conf".to_string()),
+            unavailable: Vec::new(),
+        }
+    }
+
+    /// Both columns, separated by a `--` line
+    fn columns(report: &SystemReport, options: &FormatOptions) -> String {
+        format!(
+            "{}--\n{}",
+            format_left_column(report, options).buffer,
+            format_right_column(report, options).buffer
+        )
+    }
+
+    /// Compare against `testdata/monitor/<name>.txt`; run with
+    /// UPDATE_GOLDEN=1 to rewrite the file after an intended change
+    fn assert_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/monitor").join(format!("{}.txt", name));
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_GOLDEN=1 to create it)", path.display(), e));
+        assert_eq!(actual, expected, "output differs from {}", path.display());
+    }
+
+    #[test]
+    fn test_golden_default() {
+        assert_golden("default", &columns(&sample_report(), &FormatOptions::default()));
+    }
+
+    #[test]
+    fn test_golden_verbose() {
+        let options = FormatOptions { verbose: true, ..FormatOptions::default() };
+        assert_golden("verbose", &columns(&sample_report(), &options));
+    }
+
+    #[test]
+    fn test_golden_preview() {
+        let plan = Plan {
+            inputs: crate::audit::Inputs { cpu_usage: 62.0, load: 2.1, temperature: Some(47.0), charging: false },
+            profile: None,
+            performance_app: Some("rustc".to_string()),
+            governor: "performance",
+            governor_reasons: vec!["rustc running".to_string()],
+            turbo: Some((true, "rustc running".to_string())),
+            epp: None,
+        };
+        let options = FormatOptions { preview: Some(plan), ..FormatOptions::default() };
+        let report = SystemReport {
+            conflicts: ConflictState { ppd_active: true, ..ConflictState::default() },
+            intel_lpmd: Some("running, cooperating".to_string()),
+            eas: Some("inactive, needs schedutil".to_string()),
+            ..sample_report()
+        };
+        assert_golden("preview", &columns(&report, &options));
+    }
+
+    #[test]
+    fn test_golden_no_battery() {
+        let report = SystemReport {
+            battery_info: BatteryInfo {
+                is_charging: None,
+                is_ac_plugged: None,
+                charging_start_threshold: None,
+                charging_stop_threshold: None,
+                battery_level: None,
+                power_consumption: None,
+                average_rate: None,
+                time_to_empty: None,
+                time_to_full: None,
+                session_energy: None,
+            },
+            ..sample_report()
+        };
+        assert_golden("no_battery", &columns(&report, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn test_golden_unprivileged() {
+        let report = SystemReport {
+            rapl: Some(RaplInfo { package_power: None, pl1: Some(28.0), pl2: Some(64.0) }),
+            unavailable: vec!["package_power".to_string()],
+            ..sample_report()
+        };
+        assert_golden("unprivileged", &columns(&report, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn test_golden_no_sensors() {
+        let mut report = SystemReport {
+            cpu_fan_speed: None,
+            skin_temp: None,
+            rapl: None,
+            config_path: None,
+            ..sample_report()
+        };
+        for core in &mut report.cores_info {
+            core.temperature = None;
+        }
+        assert_golden("no_sensors", &columns(&report, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn test_golden_turbo_unsupported() {
+        let report = SystemReport {
+            is_turbo_on: (None, None),
+            current_epp: None,
+            current_epb: None,
+            intel_pstate: None,
+            platform_profile: None,
+            cpu_driver: Some("acpi-cpufreq".to_string()),
+            ..sample_report()
+        };
+        assert_golden("turbo_unsupported", &columns(&report, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn test_golden_stats_file() {
+        assert_golden("stats", &format_stats(&sample_report(), "2024-05-01 12:00:00", None));
+    }
 }
@@ -0,0 +1,341 @@
+// src/modules/tui.rs
+//
+// Interactive terminal UI for `--monitor` and `--live` (the "tui" feature).
+// The per-core table sorts by any column, sparklines show how the average
+// frequency and temperature moved over the last minutes, and `g`/`t` step
+// through the governor and turbo overrides the daemon applies, like --force
+// and --turbo. The terminal is put back on quit, on errors and on panics.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Sparkline, Table, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::core::{self, AutoCpuFreqState, GovernorOverride, ServiceState, TurboOverride};
+use crate::modules::system_info::{CoreInfo, SystemReport};
+use crate::modules::system_monitor::SystemMonitor;
+
+const REFRESH: Duration = Duration::from_secs(2);
+/// How long to wait for a key between redraws
+const INPUT_POLL: Duration = Duration::from_millis(250);
+/// Samples kept for the sparklines, about 7 minutes at the refresh rate
+const HISTORY: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Core,
+    Usage,
+    Temperature,
+    Frequency,
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 4] = [SortColumn::Core, SortColumn::Usage, SortColumn::Temperature, SortColumn::Frequency];
+
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Core => "Core",
+            SortColumn::Usage => "Usage",
+            SortColumn::Temperature => "Temp",
+            SortColumn::Frequency => "Freq",
+        }
+    }
+}
+
+/// Order of the per-core table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self { column: SortColumn::Core, descending: false }
+    }
+}
+
+impl Sort {
+    fn apply(&self, cores: &mut [CoreInfo]) {
+        cores.sort_by(|a, b| {
+            let order = match self.column {
+                SortColumn::Core => a.id.cmp(&b.id),
+                SortColumn::Usage => a.usage.total_cmp(&b.usage),
+                SortColumn::Temperature => a.temperature.unwrap_or(f32::MIN).total_cmp(&b.temperature.unwrap_or(f32::MIN)),
+                SortColumn::Frequency => a.frequency.total_cmp(&b.frequency),
+            };
+            if self.descending { order.reverse() } else { order }
+        });
+    }
+
+    /// Next column, busiest first for everything but the core number
+    fn next(self) -> Self {
+        let column = self.column.next();
+        Self { column, descending: column != SortColumn::Core }
+    }
+}
+
+/// `g`: automatic, performance, powersave, automatic, ...
+fn next_governor_override(current: &GovernorOverride) -> GovernorOverride {
+    match current {
+        GovernorOverride::Default => GovernorOverride::Performance,
+        GovernorOverride::Performance => GovernorOverride::Powersave,
+        GovernorOverride::Powersave => GovernorOverride::Default,
+    }
+}
+
+/// `t`: auto, always, never, auto, ...
+fn next_turbo_override(current: &TurboOverride) -> TurboOverride {
+    match current {
+        TurboOverride::Auto => TurboOverride::Always,
+        TurboOverride::Always => TurboOverride::Never,
+        TurboOverride::Never => TurboOverride::Auto,
+    }
+}
+
+fn push_sample(history: &mut VecDeque<u64>, value: u64) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+#[derive(Default)]
+struct App {
+    sort: Sort,
+    /// Average core frequency (MHz) per refresh
+    frequency: VecDeque<u64>,
+    /// Average core temperature (°C) per refresh, 0 without sensors
+    temperature: VecDeque<u64>,
+    /// Outcome of the last key press, shown in the footer
+    status: Option<String>,
+    quit: bool,
+}
+
+impl App {
+    fn record(&mut self, report: &SystemReport) {
+        let average = |values: Vec<f32>| {
+            if values.is_empty() { 0 } else { (values.iter().sum::<f32>() / values.len() as f32).round() as u64 }
+        };
+        push_sample(&mut self.frequency, average(report.cores_info.iter().map(|c| c.frequency).collect()));
+        push_sample(
+            &mut self.temperature,
+            average(report.cores_info.iter().filter_map(|c| c.temperature).collect()),
+        );
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('r') => self.sort.descending = !self.sort.descending,
+            KeyCode::Char('g') => self.status = Some(cycle_governor_override()),
+            KeyCode::Char('t') => self.status = Some(cycle_turbo_override()),
+            _ => {}
+        }
+    }
+}
+
+/// Note for overrides saved while nothing applies them
+fn daemon_note() -> &'static str {
+    if core::daemon_status().state == ServiceState::Running {
+        ""
+    } else {
+        " (applied once the daemon runs)"
+    }
+}
+
+const NEEDS_ROOT: &str = "Overrides need root, restart with sudo";
+
+fn cycle_governor_override() -> String {
+    if !nix::unistd::Uid::effective().is_root() {
+        return NEEDS_ROOT.to_string();
+    }
+    let state = AutoCpuFreqState::new();
+    let next = next_governor_override(&core::get_override(&state));
+    match core::store_override(&state, &next, None) {
+        Ok(()) if next == GovernorOverride::Default => "Governor override removed".to_string(),
+        Ok(()) => format!("Governor override: {}{}", next.to_str(), daemon_note()),
+        Err(e) => format!("Failed to set the governor override: {}", e),
+    }
+}
+
+fn cycle_turbo_override() -> String {
+    if !nix::unistd::Uid::effective().is_root() {
+        return NEEDS_ROOT.to_string();
+    }
+    let state = AutoCpuFreqState::new();
+    let next = next_turbo_override(&core::get_turbo_override(&state));
+    match core::store_turbo_override(&state, &next, None) {
+        Ok(()) if next == TurboOverride::Auto => "Turbo override removed".to_string(),
+        Ok(()) => format!("Turbo override: {}{}", next.to_str(), daemon_note()),
+        Err(e) => format!("Failed to set the turbo override: {}", e),
+    }
+}
+
+/// Run until `q`, Esc or Ctrl+C
+pub fn run(monitor: &mut SystemMonitor) -> Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, monitor);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, monitor: &mut SystemMonitor) -> Result<()> {
+    let mut app = App::default();
+    let mut updated: Option<Instant> = None;
+
+    while !app.quit && !monitor.stop_requested() {
+        if updated.is_none_or(|t| t.elapsed() >= REFRESH) {
+            monitor.update();
+            if let Some(report) = &monitor.report {
+                app.record(report);
+            }
+            updated = Some(Instant::now());
+        }
+
+        terminal.draw(|frame| draw(frame, monitor, &app))?;
+
+        if event::poll(INPUT_POLL)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key.code, key.modifiers);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn draw(frame: &mut Frame, monitor: &SystemMonitor, app: &App) {
+    let Some(report) = &monitor.report else {
+        return;
+    };
+    let [header, body, graphs, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(8),
+        Constraint::Length(6),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(
+        Paragraph::new(format!(
+            " auto-cpufreq {} | {} | {} {} | kernel {}",
+            monitor.view, report.processor_model, report.distro_name, report.distro_ver, report.kernel_version
+        ))
+        .style(Style::new().add_modifier(Modifier::REVERSED)),
+        header,
+    );
+
+    let [cores_area, details_area] = Layout::horizontal([Constraint::Length(40), Constraint::Min(30)]).areas(body);
+    frame.render_widget(cores_table(report, app.sort), cores_area);
+    frame.render_widget(
+        Paragraph::new(monitor.right.iter().map(|l| Line::raw(l.as_str())).collect::<Vec<_>>())
+            .block(Block::new().borders(Borders::ALL).title(" Status "))
+            .wrap(Wrap { trim: false }),
+        details_area,
+    );
+
+    let [frequency_area, temperature_area] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(graphs);
+    let graph = |title: String, history: &VecDeque<u64>, width: u16| {
+        let shown = history.len().saturating_sub(width.saturating_sub(2) as usize);
+        Sparkline::default()
+            .block(Block::new().borders(Borders::ALL).title(title))
+            .data(history.iter().skip(shown).copied().collect::<Vec<_>>())
+    };
+    let last = |history: &VecDeque<u64>| history.back().copied().unwrap_or(0);
+    frame.render_widget(
+        graph(format!(" Frequency {} MHz ", last(&app.frequency)), &app.frequency, frequency_area.width),
+        frequency_area,
+    );
+    let temperature_title = match last(&app.temperature) {
+        0 => " Temperature (no sensor) ".to_string(),
+        temp => format!(" Temperature {} °C ", temp),
+    };
+    frame.render_widget(graph(temperature_title, &app.temperature, temperature_area.width), temperature_area);
+
+    let keys = " q quit  s sort  r reverse  g governor override  t turbo override";
+    let footer_text = match &app.status {
+        Some(status) => format!("{}  |  {}", keys, status),
+        None => keys.to_string(),
+    };
+    frame.render_widget(Paragraph::new(footer_text).style(Style::new().add_modifier(Modifier::DIM)), footer);
+}
+
+fn cores_table(report: &SystemReport, sort: Sort) -> Table<'static> {
+    let mut cores = report.cores_info.clone();
+    sort.apply(&mut cores);
+
+    let header = Row::new(SortColumn::ALL.map(|column| {
+        if column == sort.column {
+            format!("{}{}", column.title(), if sort.descending { " ▼" } else { " ▲" })
+        } else {
+            column.title().to_string()
+        }
+    }))
+    .style(Style::new().add_modifier(Modifier::BOLD));
+
+    let rows = cores.into_iter().map(|core| {
+        let temperature = core.temperature.map(|t| format!("{:.0} °C", t)).unwrap_or_else(|| "--".to_string());
+        Row::new([
+            format!("CPU{}", core.id),
+            format!("{:.1}%", core.usage),
+            temperature,
+            format!("{:.0} MHz", core.frequency),
+        ])
+    });
+
+    let limits = match (report.cpu_min_freq, report.cpu_max_freq) {
+        (Some(min), Some(max)) => format!(" Cores {:.0}-{:.0} MHz ", min, max),
+        _ => " Cores ".to_string(),
+    };
+    Table::new(rows, [Constraint::Length(7), Constraint::Length(8), Constraint::Length(8), Constraint::Length(10)])
+        .header(header)
+        .block(Block::new().borders(Borders::ALL).title(limits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorting_and_override_cycles() {
+        let core = |id, usage, frequency| CoreInfo { id, usage, temperature: None, frequency };
+        let mut cores = vec![core(0, 10.0, 800.0), core(1, 90.0, 3600.0), core(2, 50.0, 2400.0)];
+
+        let by_usage = Sort::default().next();
+        assert_eq!(by_usage, Sort { column: SortColumn::Usage, descending: true });
+        by_usage.apply(&mut cores);
+        assert_eq!(cores.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 2, 0]);
+        Sort::default().apply(&mut cores);
+        assert_eq!(cores.iter().map(|c| c.id).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(by_usage.next().next().next(), Sort::default());
+
+        let mut governor = GovernorOverride::Default;
+        for expected in [GovernorOverride::Performance, GovernorOverride::Powersave, GovernorOverride::Default] {
+            governor = next_governor_override(&governor);
+            assert_eq!(governor, expected);
+        }
+        assert_eq!(next_turbo_override(&TurboOverride::Never), TurboOverride::Auto);
+
+        let mut history = VecDeque::new();
+        for value in 0..HISTORY as u64 + 5 {
+            push_sample(&mut history, value);
+        }
+        assert_eq!((history.len(), history.front().copied()), (HISTORY, Some(5)));
+    }
+}
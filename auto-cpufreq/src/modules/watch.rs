@@ -0,0 +1,141 @@
+// src/modules/watch.rs - Line-oriented state watcher for scripts (--watch)
+use std::fs;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Local;
+
+use crate::modules::system_info::SystemInfo;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+const EPP_PATH: &str = "/sys/devices/system/cpu/cpu0/cpufreq/energy_performance_preference";
+
+/// The values `--watch` reports on, read straight from sysfs
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchState {
+    pub governor: Option<String>,
+    pub turbo: Option<bool>,
+    pub epp: Option<String>,
+    pub ac_plugged: Option<bool>,
+}
+
+impl WatchState {
+    pub fn read() -> Self {
+        Self {
+            governor: SystemInfo::current_gov(),
+            turbo: SystemInfo::turbo_on().0,
+            epp: fs::read_to_string(EPP_PATH).ok().map(|s| s.trim().to_string()),
+            ac_plugged: SystemInfo::battery_info().is_ac_plugged,
+        }
+    }
+
+    /// Names of the fields that differ from `prev`
+    pub fn changed_fields(&self, prev: &WatchState) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.governor != prev.governor { changed.push("governor"); }
+        if self.turbo != prev.turbo { changed.push("turbo"); }
+        if self.epp != prev.epp { changed.push("epp"); }
+        if self.ac_plugged != prev.ac_plugged { changed.push("ac"); }
+        changed
+    }
+
+    pub fn format_text(&self, timestamp: &str, changed: &[&str]) -> String {
+        let on_off = |v: Option<bool>, on: &str, off: &str| match v {
+            Some(true) => on.to_string(),
+            Some(false) => off.to_string(),
+            None => "unknown".to_string(),
+        };
+
+        let mut line = format!(
+            "{} governor={} turbo={} epp={} ac={}",
+            timestamp,
+            self.governor.as_deref().unwrap_or("unknown"),
+            on_off(self.turbo, "on", "off"),
+            self.epp.as_deref().unwrap_or("unsupported"),
+            on_off(self.ac_plugged, "yes", "no"),
+        );
+        if !changed.is_empty() {
+            line.push_str(&format!(" changed={}", changed.join(",")));
+        }
+        line
+    }
+
+    pub fn format_json(&self, timestamp: &str, changed: &[&str]) -> String {
+        serde_json::json!({
+            "timestamp": timestamp,
+            "governor": self.governor,
+            "turbo": self.turbo,
+            "epp": self.epp,
+            "ac": self.ac_plugged,
+            "changed": changed,
+        })
+        .to_string()
+    }
+}
+
+/// Print one line per interval, or only when something changed with `changes_only`.
+/// The first line always carries the initial state. Returns when stdout is closed
+/// (e.g. the reading end of a pipe went away).
+pub fn run_watch(changes_only: bool, json: bool) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut prev: Option<WatchState> = None;
+
+    loop {
+        let state = WatchState::read();
+        let changed = prev.as_ref().map(|p| state.changed_fields(p)).unwrap_or_default();
+
+        if prev.is_none() || !changes_only || !changed.is_empty() {
+            let timestamp = Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+            let line = if json {
+                state.format_json(&timestamp, &changed)
+            } else {
+                state.format_text(&timestamp, &changed)
+            };
+
+            let mut out = stdout.lock();
+            if writeln!(out, "{}", line).and_then(|_| out.flush()).is_err() {
+                return Ok(());
+            }
+        }
+
+        prev = Some(state);
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(governor: &str, ac: bool) -> WatchState {
+        WatchState {
+            governor: Some(governor.to_string()),
+            turbo: Some(false),
+            epp: None,
+            ac_plugged: Some(ac),
+        }
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let a = state("powersave", false);
+        let b = state("performance", true);
+        assert!(a.changed_fields(&a).is_empty());
+        assert_eq!(b.changed_fields(&a), vec!["governor", "ac"]);
+    }
+
+    #[test]
+    fn test_format() {
+        let s = state("powersave", true);
+        assert_eq!(
+            s.format_text("t", &["ac"]),
+            "t governor=powersave turbo=off epp=unsupported ac=yes changed=ac"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&s.format_json("t", &[])).unwrap();
+        assert_eq!(json["governor"], "powersave");
+        assert_eq!(json["epp"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,114 @@
+// src/pidfile.rs
+//
+// Single-instance lock for the daemon. `--daemon` takes an exclusive flock on
+// /run/auto-cpufreq.pid and writes its PID into it; a second daemon fails to
+// get the lock and exits instead of fighting the first over the governor.
+// Other commands tell whether the daemon runs by whether the file is locked,
+// which is a single syscall rather than a scan of every process's cmdline.
+// The kernel drops the lock when the daemon dies however it exits, so a file
+// left behind by a crash or SIGKILL doesn't count as a running daemon.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use nix::errno::Errno;
+use nix::fcntl::{FlockArg, flock};
+
+pub const PID_FILE: &str = "/run/auto-cpufreq.pid";
+
+/// Held by the running daemon; the lock goes with the open file
+#[derive(Debug)]
+pub struct PidLock {
+    file: File,
+    path: PathBuf,
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut content = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
+
+impl PidLock {
+    /// Lock /run/auto-cpufreq.pid, failing when another daemon holds it
+    pub fn acquire() -> Result<Self> {
+        Self::acquire_at(PID_FILE)
+    }
+
+    pub fn acquire_at(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        // Not truncated before the lock is ours, the PID in it may be the running daemon's
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        match flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+            Ok(()) => {}
+            Err(Errno::EWOULDBLOCK) => match read_pid(&mut file) {
+                Some(pid) => bail!("auto-cpufreq is already running (PID {})", pid),
+                None => bail!("auto-cpufreq is already running ({} is locked)", path.display()),
+            },
+            Err(e) => bail!("Failed to lock {}: {}", path.display(), e),
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", std::process::id()).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Self { file, path })
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        // Removed while still locked, so no other daemon can have taken it over
+        let _ = fs::remove_file(&self.path);
+        let _ = flock(self.file.as_raw_fd(), FlockArg::Unlock);
+    }
+}
+
+/// PID of the daemon holding the lock at `path`, `None` when none does
+pub fn locked_by(path: &Path) -> Option<u32> {
+    let mut file = File::open(path).ok()?;
+    match flock(file.as_raw_fd(), FlockArg::LockSharedNonblock) {
+        // Nobody holds it: a file left behind by a daemon that was killed
+        Ok(()) => None,
+        // A PID of 0 still means running, only the file content is off
+        Err(Errno::EWOULDBLOCK) => Some(read_pid(&mut file).unwrap_or(0)),
+        Err(_) => None,
+    }
+}
+
+/// PID of the running daemon
+pub fn daemon_pid() -> Option<u32> {
+    locked_by(Path::new(PID_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_instance() {
+        let path = std::env::temp_dir().join(format!("acf-pidfile-{}.pid", std::process::id()));
+        // Left behind by a killed daemon: not locked, so not running
+        fs::write(&path, "4242\n").unwrap();
+        assert_eq!(locked_by(&path), None);
+
+        let lock = PidLock::acquire_at(&path).unwrap();
+        assert_eq!(locked_by(&path), Some(std::process::id()));
+        let second = PidLock::acquire_at(&path).unwrap_err();
+        assert_eq!(second.to_string(), format!("auto-cpufreq is already running (PID {})", std::process::id()));
+
+        drop(lock);
+        assert!(!path.exists());
+        assert_eq!(locked_by(&path), None);
+    }
+}
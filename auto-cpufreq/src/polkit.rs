@@ -0,0 +1,81 @@
+// src/polkit.rs
+//
+// Fine-grained authorization for clients of the control socket that don't run
+// as root, such as the GUI or `auto-cpufreq --force` from a user session. Each
+// command maps to a polkit action (org.auto-cpufreq.set-governor, ...) in
+// org.auto-cpufreq.pkexec.policy, so an admin can allow e.g. overrides for
+// the wheel group without handing out a root shell. The client asks for the
+// action for its own process first, which is where the password prompt of the
+// session's agent shows up; the daemon then checks the same process without
+// interaction, and only succeeds while that authorization is kept (the
+// actions default to auth_admin_keep). Both sides use pkcheck(1), so there is
+// no D-Bus client to link.
+
+use std::fs;
+
+use anyhow::{Context, Result, bail};
+
+use crate::power_helper::does_command_exist;
+
+/// Start time of `pid` in clock ticks since boot, which with the PID and uid
+/// identifies the process to polkit even if the PID is reused
+pub fn start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_start_time(&stat)
+}
+
+fn parse_start_time(stat: &str) -> Option<u64> {
+    // The command name may contain spaces and parentheses, fields start after the last ')'
+    let fields = &stat[stat.rfind(')')? + 1..];
+    // starttime is field 22; field 3 (state) is the first after the name
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+fn pkcheck(action_id: &str, pid: u32, uid: u32, interactive: bool) -> Result<bool> {
+    if !does_command_exist("pkcheck") {
+        bail!("polkit (pkcheck) is not installed, run the command as root instead");
+    }
+    let start = start_time(pid).with_context(|| format!("Process {} is gone", pid))?;
+    let subject = format!("{},{},{}", pid, start, uid);
+
+    let mut command = crate::command::new("pkcheck");
+    command.args(["--action-id", action_id, "--process", &subject]);
+    if interactive {
+        command.arg("--allow-user-interaction");
+    }
+    let status = command.status().context("Failed to run pkcheck")?;
+    match status.code() {
+        Some(0) => Ok(true),
+        // Not authorized, authentication needed, or the prompt was dismissed
+        Some(1..=3) => Ok(false),
+        _ => bail!("pkcheck failed ({})", status),
+    }
+}
+
+/// Daemon side: whether the client process may perform `action_id`
+pub fn is_authorized(action_id: &str, pid: u32, uid: u32) -> Result<bool> {
+    pkcheck(action_id, pid, uid, false)
+}
+
+/// Client side: obtain `action_id` for this process, prompting through the
+/// session's authentication agent when needed
+pub fn obtain(action_id: &str) -> Result<()> {
+    let uid = nix::unistd::Uid::current().as_raw();
+    if !pkcheck(action_id, std::process::id(), uid, true)? {
+        bail!("Not authorized for {}", action_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_start_time() {
+        let stat = "4242 (auto cpufreq) S 1 4242 4242 0 -1 4194560 1380 0 0 0 12 4 0 0 20 0 1 0 98765 21397504 1234";
+        assert_eq!(parse_start_time(stat), Some(98765));
+        assert_eq!(parse_start_time("4242 (broken"), None);
+        assert!(start_time(std::process::id()).is_some());
+    }
+}
@@ -1,21 +1,19 @@
 // src/power_helper.rs
 
-use anyhow::{Result, Context};
+use anyhow::{Result, Context, bail};
+use tracing::{error, info};
+use serde::Serialize;
 use std::fs;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::config::{CONFIG, Snapshot};
 use crate::core::GITHUB;
 use crate::tlp_stat_parser::TLPStatusParser;
 
 // Check if a command exists
 pub fn does_command_exist(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
+    crate::command::succeeds("which", &[cmd])
 }
 
 lazy_static::lazy_static! {
@@ -24,6 +22,7 @@ lazy_static::lazy_static! {
     pub static ref SYSTEMCTL_EXISTS: bool = does_command_exist("systemctl");
     pub static ref TLP_STAT_EXISTS: bool = does_command_exist("tlp-stat");
     pub static ref TUNED_STAT_EXISTS: bool = does_command_exist("tuned");
+    pub static ref INTEL_LPMD_EXISTS: bool = does_command_exist("intel_lpmd");
 }
 
 pub fn header() {
@@ -44,33 +43,43 @@ pub fn gnome_power_status() -> Result<bool> {
         return Ok(false);
     }
 
-    let status = Command::new("systemctl")
-        .args(&["is-active", "--quiet", "power-profiles-daemon"]) 
-        .status()
+    let output = crate::command::run("systemctl", &["is-active", "--quiet", "power-profiles-daemon"])
         .context("Failed to check GNOME power profiles daemon status")?;
 
-    Ok(status.success())
+    Ok(output.success())
 }
 
 // Alert in case TLP service is running
 pub fn tlp_service_detect() -> Result<()> {
-    if !*TLP_STAT_EXISTS {
+    if !*TLP_STAT_EXISTS || conflict_policy(&CONFIG.snapshot())? == ConflictPolicy::Coexist {
         return Ok(());
     }
 
-    let output = Command::new("tlp-stat")
-        .arg("-s")
-        .output()
-        .context("Failed to run tlp-stat")?;
-
-    let status_output = String::from_utf8_lossy(&output.stdout);
-    let tlp_status = TLPStatusParser::new(&status_output);
+    let output = crate::command::run("tlp-stat", &["-s", "-c"]).context("Failed to run tlp-stat")?;
+    let tlp_status = TLPStatusParser::new(&output.stdout);
 
     if tlp_status.is_enabled() {
         warning();
         println!("Detected you are running a TLP service!");
-        println!("This daemon might interfere with auto-cpufreq which can lead to unexpected results.");
-        println!("We strongly encourage you to remove TLP unless you really know what you are doing.");
+
+        let conflicts = tlp_status.conflicts(&CONFIG.snapshot(), crate::battery::profiles::thresholds_enabled(&CONFIG));
+        if conflicts.is_empty() {
+            println!("This daemon might interfere with auto-cpufreq which can lead to unexpected results.");
+        } else {
+            println!("These TLP settings conflict with auto-cpufreq:\n");
+            for conflict in &conflicts {
+                println!(
+                    "  {}=\"{}\" ({}): {}",
+                    conflict.setting.key, conflict.setting.value, conflict.setting.source, conflict.area
+                );
+            }
+            println!("\nSet them to an empty value in /etc/tlp.conf (e.g. CPU_BOOST_ON_AC=\"\"), or disable TLP.");
+        }
+        if conflict_policy(&CONFIG.snapshot())? == ConflictPolicy::Disable {
+            println!("TLP is masked when the auto-cpufreq daemon is installed (conflict_policy = disable).");
+        } else {
+            println!("We strongly encourage you to remove TLP unless you really know what you are doing.");
+        }
     }
 
     Ok(())
@@ -78,19 +87,23 @@ pub fn tlp_service_detect() -> Result<()> {
 
 // Alert in case GNOME power profile service is running
 pub fn gnome_power_detect() -> Result<()> {
-    if !*SYSTEMCTL_EXISTS {
+    let policy = conflict_policy(&CONFIG.snapshot())?;
+    if !*SYSTEMCTL_EXISTS || policy == ConflictPolicy::Coexist {
         return Ok(());
     }
 
     if gnome_power_status()? {
         warning();
         println!("Detected running GNOME Power Profiles daemon service!");
-        println!("\nThis daemon might interfere with auto-cpufreq and will be automatically");
-        println!("disabled when auto-cpufreq daemon is installed and");
-        println!("it will be re-enabled after auto-cpufreq is removed.");
-        println!("Steps to perform this action using auto-cpufreq: power_helper script:");
-        println!("git clone {}.git", GITHUB);
-        println!("python3 -m auto_cpufreq.power_helper --gnome_power_disable");
+        if policy == ConflictPolicy::Disable {
+            println!("\nThis daemon might interfere with auto-cpufreq and will be automatically");
+            println!("disabled when auto-cpufreq daemon is installed and");
+            println!("it will be re-enabled after auto-cpufreq is removed.");
+        } else {
+            println!("\nThis daemon might interfere with auto-cpufreq. Set conflict_policy = disable");
+            println!("in the [integration] section to have it disabled when auto-cpufreq daemon");
+            println!("is installed and re-enabled after auto-cpufreq is removed.");
+        }
         println!("\nReference: {}#configuring-auto-cpufreq", GITHUB);
     }
 
@@ -115,42 +128,307 @@ pub fn gnome_power_detect_install() -> Result<()> {
 }
 
 
-// Stop GNOME >= 40 power profiles (live)
-pub fn gnome_power_stop_live() -> Result<()> {
+// Stop GNOME >= 40 power profiles (live), returns whether it was running
+pub fn gnome_power_stop_live() -> Result<bool> {
     if !*SYSTEMCTL_EXISTS {
-        return Ok(());
+        return Ok(false);
     }
 
     if gnome_power_status()? && *POWERPROFILESCTL_EXISTS {
-        Command::new("powerprofilesctl")
-            .args(&["set", "balanced"]) 
-            .status()?;
-        
-        Command::new("systemctl")
-            .args(&["stop", "power-profiles-daemon"]) 
-            .status()?;
+        crate::command::change("powerprofilesctl", &["set", "balanced"])?;
+        crate::command::change("systemctl", &["stop", "power-profiles-daemon"])?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+// Stop tuned (live), unless tuned_mode says to leave it running; returns
+// whether it was running
+pub fn tuned_stop_live() -> Result<bool> {
+    if *SYSTEMCTL_EXISTS && *TUNED_STAT_EXISTS && tuned_mode(&CONFIG.snapshot())? == TunedMode::Disable
+        && crate::command::succeeds("systemctl", &["is-active", "--quiet", "tuned"])
+    {
+        crate::command::change("systemctl", &["stop", "tuned"])?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+// How to treat a running tuned, from `[integration] tuned_mode`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TunedMode {
+    // Switch tuned profiles to match auto-cpufreq's decisions
+    Cooperate,
+    // Stop tuned (default)
+    Disable,
+    // Leave tuned alone
+    Ignore,
+}
+
+impl TunedMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "disable" => Some(Self::Disable),
+            "cooperate" => Some(Self::Cooperate),
+            "ignore" => Some(Self::Ignore),
+            _ => None,
+        }
+    }
+}
+
+pub fn tuned_mode(config: &Snapshot) -> Result<TunedMode> {
+    match config.get("integration", "tuned_mode") {
+        None => Ok(TunedMode::Disable),
+        Some(value) => TunedMode::parse(value).with_context(|| {
+            format!("Invalid tuned_mode in [integration]: {} (expected cooperate, disable or ignore)", value.trim())
+        }),
+    }
+}
+
+// How to treat a running intel_lpmd (Intel Low Power Mode Daemon, Meteor Lake
+// and later), from `[integration] lpmd_mode`. lpmd confines work to the
+// low-power E-cores while the system is lightly loaded; auto-cpufreq keeps
+// the governor, EPP and turbo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LpmdMode {
+    // Leave low-power mode to lpmd, but switch it off while auto-cpufreq
+    // picks performance (default)
+    Cooperate,
+    // Stop lpmd
+    Disable,
+    // Leave lpmd alone
+    Ignore,
+}
+
+impl LpmdMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "cooperate" => Some(Self::Cooperate),
+            "disable" => Some(Self::Disable),
+            "ignore" => Some(Self::Ignore),
+            _ => None,
+        }
+    }
+}
+
+pub fn lpmd_mode(config: &Snapshot) -> Result<LpmdMode> {
+    match config.get("integration", "lpmd_mode") {
+        None => Ok(LpmdMode::Cooperate),
+        Some(value) => LpmdMode::parse(value).with_context(|| {
+            format!("Invalid lpmd_mode in [integration]: {} (expected cooperate, disable or ignore)", value.trim())
+        }),
+    }
+}
+
+pub fn lpmd_status() -> bool {
+    *SYSTEMCTL_EXISTS && *INTEL_LPMD_EXISTS && crate::command::succeeds("systemctl", &["is-active", "--quiet", "intel_lpmd"])
+}
+
+// Stop intel_lpmd (live) when lpmd_mode says so, returns whether it was stopped
+pub fn lpmd_stop_live() -> Result<bool> {
+    if lpmd_status() && lpmd_mode(&CONFIG.snapshot())? == LpmdMode::Disable {
+        crate::command::change("systemctl", &["stop", "intel_lpmd"])?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+// Start intel_lpmd again after --live
+pub fn lpmd_start_live() -> Result<()> {
+    if *SYSTEMCTL_EXISTS && *INTEL_LPMD_EXISTS {
+        crate::command::change("systemctl", &["start", "intel_lpmd"])?;
     }
 
     Ok(())
 }
 
-// Stop tuned (live)
-pub fn tuned_stop_live() -> Result<()> {
-    if *SYSTEMCTL_EXISTS && *TUNED_STAT_EXISTS {
-        Command::new("systemctl")
-            .args(&["stop", "tuned"]) 
-            .status()?;
+// `intel_lpmd_control` argument for the governor auto-cpufreq picked: no
+// E-core confinement while running for performance, lpmd's own choice otherwise
+fn lpmd_control_for(governor: &str) -> &'static str {
+    if governor == "performance" { "OFF" } else { "AUTO" }
+}
+
+lazy_static::lazy_static! {
+    static ref LPMD_LAST_CONTROL: Mutex<Option<&'static str>> = Mutex::new(None);
+}
+
+// Called by `set_autofreq` after the governor decision: in cooperate mode,
+// hand low-power mode to the running lpmd or take it away
+pub fn lpmd_follow(governor: &str) -> Result<()> {
+    if !*INTEL_LPMD_EXISTS || lpmd_mode(&CONFIG.snapshot())? != LpmdMode::Cooperate {
+        return Ok(());
+    }
+
+    let control = lpmd_control_for(governor);
+    let mut last = LPMD_LAST_CONTROL.lock().unwrap();
+    if !lpmd_running() {
+        // A restarted lpmd starts from its own default, so it gets told again
+        *last = None;
+        return Ok(());
+    }
+    if *last == Some(control) {
+        return Ok(());
+    }
+
+    info!("Setting intel_lpmd low-power mode: {}", control);
+    let output = crate::command::change("intel_lpmd_control", &[control])?;
+    if !output.success() {
+        bail!("intel_lpmd_control {} failed: {}", control, output.stderr.trim());
+    }
+    *last = Some(control);
+    Ok(())
+}
+
+lazy_static::lazy_static! {
+    static ref LPMD_STATUS_CACHE: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+}
+
+// `lpmd_status`, re-checked at most once per CONFLICT_REFRESH: the daemon
+// and the monitor ask every cycle
+fn lpmd_running() -> bool {
+    let mut cache = LPMD_STATUS_CACHE.lock().unwrap();
+    match *cache {
+        Some((checked, running)) if checked.elapsed() < CONFLICT_REFRESH => running,
+        _ => {
+            let running = lpmd_status();
+            *cache = Some((Instant::now(), running));
+            running
+        }
+    }
+}
+
+// For --debug and the monitor: `None` when intel_lpmd isn't installed
+pub fn lpmd_state() -> Option<String> {
+    if !*INTEL_LPMD_EXISTS {
+        return None;
+    }
+    let running = lpmd_running();
+    let mode = match lpmd_mode(&CONFIG.snapshot()) {
+        Ok(LpmdMode::Cooperate) => "cooperating",
+        Ok(LpmdMode::Disable) => "conflicting (lpmd_mode = disable)",
+        Ok(LpmdMode::Ignore) => "ignored",
+        Err(_) => "invalid lpmd_mode",
+    };
+    Some(if running { format!("running, {}", mode) } else { "installed, not running".to_string() })
+}
+
+// What to do about TLP, tuned and power-profiles-daemon, from
+// `[integration] conflict_policy`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    // Print a warning and leave them running (default)
+    Warn,
+    // Stop and mask them on --install, unmask and restart them on --remove
+    Disable,
+    // Leave them running without a warning
+    Coexist,
+}
+
+impl ConflictPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "warn" => Some(Self::Warn),
+            "disable" => Some(Self::Disable),
+            "coexist" => Some(Self::Coexist),
+            _ => None,
+        }
+    }
+}
+
+pub fn conflict_policy(config: &Snapshot) -> Result<ConflictPolicy> {
+    match config.get("integration", "conflict_policy") {
+        None => Ok(ConflictPolicy::Warn),
+        Some(value) => ConflictPolicy::parse(value).with_context(|| {
+            format!("Invalid conflict_policy in [integration]: {} (expected warn, disable or coexist)", value.trim())
+        }),
+    }
+}
+
+// systemd units that fight with auto-cpufreq over the same settings
+pub const CONFLICTING_UNITS: &[&str] = &["tlp", "power-profiles-daemon", "tuned", "intel_lpmd"];
+
+// Units `conflict_policy = disable` takes over, given which of them are
+// enabled or running. tuned and intel_lpmd are only included when
+// `tuned_mode`/`lpmd_mode` is disable, in the other modes they are meant to
+// keep running.
+fn units_to_disable(config: &Snapshot, in_use: impl Fn(&str) -> bool) -> Result<Vec<&'static str>> {
+    if conflict_policy(config)? != ConflictPolicy::Disable {
+        return Ok(Vec::new());
+    }
+    let tuned_disabled = tuned_mode(config)? == TunedMode::Disable;
+    let lpmd_disabled = lpmd_mode(config)? == LpmdMode::Disable;
+
+    Ok(CONFLICTING_UNITS
+        .iter()
+        .copied()
+        .filter(|unit| *unit != "tuned" || tuned_disabled)
+        .filter(|unit| *unit != "intel_lpmd" || lpmd_disabled)
+        .filter(|unit| in_use(unit))
+        .collect())
+}
+
+// Conflicting units to stop and mask on --install
+pub fn conflicting_units() -> Result<Vec<&'static str>> {
+    if !*SYSTEMCTL_EXISTS {
+        return Ok(Vec::new());
+    }
+    units_to_disable(&CONFIG.snapshot(), |unit| {
+        crate::command::succeeds("systemctl", &["is-enabled", "--quiet", unit])
+            || crate::command::succeeds("systemctl", &["is-active", "--quiet", unit])
+    })
+}
+
+// tuned profile matching the governor auto-cpufreq picked, overridable with
+// `tuned_profile_performance`/`_balanced`/`_powersave` in [integration]
+fn tuned_profile_for(config: &Snapshot, governor: &str, is_charging: bool) -> String {
+    let (key, default) = if governor == "performance" {
+        ("tuned_profile_performance", "throughput-performance")
+    } else if is_charging {
+        ("tuned_profile_balanced", "balanced")
+    } else {
+        ("tuned_profile_powersave", "powersave")
+    };
+    config.get("integration", key).map(|v| v.trim().to_string()).unwrap_or_else(|| default.to_string())
+}
+
+lazy_static::lazy_static! {
+    static ref TUNED_LAST_PROFILE: Mutex<Option<String>> = Mutex::new(None);
+}
+
+// With `tuned_mode = cooperate`, moves tuned to the profile for this cycle's
+// governor and power source. tuned-adm only runs when that profile changes,
+// and is skipped when tuned already has it active.
+pub fn tuned_follow(governor: &str, is_charging: bool) -> Result<()> {
+    let config = CONFIG.snapshot();
+    if !*TUNED_STAT_EXISTS || tuned_mode(&config)? != TunedMode::Cooperate {
+        return Ok(());
     }
 
+    let profile = tuned_profile_for(&config, governor, is_charging);
+    let mut last = TUNED_LAST_PROFILE.lock().unwrap();
+    if last.as_deref() == Some(profile.as_str()) {
+        return Ok(());
+    }
+
+    let current = crate::command::run("tuned-adm", &["active"]).ok().and_then(|o| parse_tuned_active(&o.stdout));
+    if current.as_deref() != Some(profile.as_str()) {
+        info!("Switching tuned profile to {}", profile);
+        let output = crate::command::change("tuned-adm", &["profile", &profile])?;
+        if !output.success() {
+            bail!("tuned-adm profile {} failed: {}", profile, output.stderr.trim());
+        }
+    }
+    *last = Some(profile);
     Ok(())
 }
 
 // Start GNOME >= 40 power profiles (live)
 pub fn gnome_power_start_live() -> Result<()> {
     if *SYSTEMCTL_EXISTS {
-        Command::new("systemctl")
-            .args(&["start", "power-profiles-daemon"]) 
-            .status()?;
+        crate::command::change("systemctl", &["start", "power-profiles-daemon"])?;
     }
 
     Ok(())
@@ -159,9 +437,7 @@ pub fn gnome_power_start_live() -> Result<()> {
 // Start tuned (live)
 pub fn tuned_start_live() -> Result<()> {
     if *SYSTEMCTL_EXISTS && *TUNED_STAT_EXISTS {
-        Command::new("systemctl")
-            .args(&["start", "tuned"]) 
-            .status()?;
+        crate::command::change("systemctl", &["start", "tuned"])?;
     }
 
     Ok(())
@@ -175,14 +451,9 @@ pub fn gnome_power_svc_enable() -> Result<()> {
 
     println!("* Enabling GNOME power profiles\n");
     
-    Command::new("systemctl")
-        .args(&["unmask", "power-profiles-daemon"]) 
-        .status()
+    crate::command::change("systemctl", &["unmask", "power-profiles-daemon"])
         .context("Failed to unmask power-profiles-daemon")?;
-    
-    Command::new("systemctl")
-        .args(&["enable", "--now", "power-profiles-daemon"]) 
-        .status()
+    crate::command::change("systemctl", &["enable", "--now", "power-profiles-daemon"])
         .context("Failed to enable power-profiles-daemon")?;
 
     Ok(())
@@ -196,15 +467,8 @@ pub fn tuned_svc_enable() -> Result<()> {
 
     println!("* Enabling TuneD\n");
     
-    Command::new("systemctl")
-        .args(&["unmask", "tuned"]) 
-        .status()
-        .context("Failed to unmask tuned")?;
-    
-    Command::new("systemctl")
-        .args(&["enable", "--now", "tuned"]) 
-        .status()
-        .context("Failed to enable tuned")?;
+    crate::command::change("systemctl", &["unmask", "tuned"]).context("Failed to unmask tuned")?;
+    crate::command::change("systemctl", &["enable", "--now", "tuned"]).context("Failed to enable tuned")?;
 
     Ok(())
 }
@@ -216,10 +480,9 @@ pub fn gnome_power_svc_status() -> Result<()> {
     }
 
     println!("* GNOME power profiles status");
-    Command::new("systemctl")
-        .args(&["status", "power-profiles-daemon"]) 
-        .status()
+    let output = crate::command::run("systemctl", &["status", "power-profiles-daemon"])
         .context("Failed to get GNOME power profiles status")?;
+    print!("{}", output.stdout);
 
     Ok(())
 }
@@ -297,7 +560,7 @@ pub fn bluetooth_disable() -> Result<()> {
     println!("  If you want bluetooth enabled on boot run: auto-cpufreq --bluetooth_boot_on");
     
     if !set_bluetooth_auto_enable(false)? {
-        println!("\nERROR:\nWas unable to turn off bluetooth on boot");
+        error!("Was unable to turn off bluetooth on boot");
     }
 
     Ok(())
@@ -313,7 +576,7 @@ pub fn bluetooth_enable() -> Result<()> {
     println!("* Turn on bluetooth on boot");
     
     if !set_bluetooth_auto_enable(true)? {
-        println!("\nERROR:\nWas unable to turn on bluetooth on boot");
+        error!("Was unable to turn on bluetooth on boot");
     }
 
     Ok(())
@@ -335,3 +598,155 @@ pub fn gnome_power_rm_reminder() -> Result<()> {
     Ok(())
 }
 
+// Services that fight auto-cpufreq over the same knobs. Checking them means
+// spawning systemctl/tlp-stat/tuned-adm, so the result is cached and only
+// refreshed every CONFLICT_REFRESH.
+const CONFLICT_REFRESH: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConflictState {
+    /// power-profiles-daemon is running
+    pub ppd_active: bool,
+    /// TLP reports itself as enabled
+    pub tlp_enabled: bool,
+    /// Profile of the running tuned daemon
+    pub tuned_profile: Option<String>,
+    /// intel_lpmd is running and `lpmd_mode` is disable
+    pub lpmd_active: bool,
+}
+
+impl ConflictState {
+    pub fn any(&self) -> bool {
+        self.ppd_active || self.tlp_enabled || self.tuned_profile.is_some() || self.lpmd_active
+    }
+
+    /// Human-readable names of the conflicting services
+    pub fn describe(&self) -> Vec<String> {
+        let mut services = Vec::new();
+        if self.ppd_active {
+            services.push("power-profiles-daemon".to_string());
+        }
+        if self.tlp_enabled {
+            services.push("TLP".to_string());
+        }
+        if let Some(profile) = &self.tuned_profile {
+            services.push(format!("tuned ({})", profile));
+        }
+        if self.lpmd_active {
+            services.push("intel_lpmd".to_string());
+        }
+        services
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CONFLICT_CACHE: Mutex<Option<(Instant, ConflictState)>> = Mutex::new(None);
+}
+
+// Parse `tuned-adm active` ("Current active profile: balanced")
+fn parse_tuned_active(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Current active profile:"))
+        .map(|profile| profile.trim().to_string())
+        .filter(|profile| !profile.is_empty())
+}
+
+fn detect_conflicts() -> ConflictState {
+    let tlp_enabled = *TLP_STAT_EXISTS
+        && crate::command::run("tlp-stat", &["-s"]).is_ok_and(|o| TLPStatusParser::new(&o.stdout).is_enabled());
+
+    // A cooperating tuned follows auto-cpufreq, so it isn't a conflict
+    let tuned_profile = if *SYSTEMCTL_EXISTS
+        && *TUNED_STAT_EXISTS
+        && tuned_mode(&CONFIG.snapshot()).map_or(true, |mode| mode != TunedMode::Cooperate)
+        && crate::command::succeeds("systemctl", &["is-active", "--quiet", "tuned"])
+    {
+        crate::command::run("tuned-adm", &["active"])
+            .ok()
+            .and_then(|o| parse_tuned_active(&o.stdout))
+            .or_else(|| Some("unknown profile".to_string()))
+    } else {
+        None
+    };
+
+    // Likewise a cooperating or ignored lpmd
+    let lpmd_active = lpmd_mode(&CONFIG.snapshot()).is_ok_and(|mode| mode == LpmdMode::Disable) && lpmd_status();
+
+    ConflictState {
+        ppd_active: gnome_power_status().unwrap_or(false),
+        tlp_enabled,
+        tuned_profile,
+        lpmd_active,
+    }
+}
+
+// Conflicting services, re-checked at most once per CONFLICT_REFRESH
+pub fn conflict_state() -> ConflictState {
+    let mut cache = CONFLICT_CACHE.lock().unwrap();
+    match &*cache {
+        Some((checked, state)) if checked.elapsed() < CONFLICT_REFRESH => state.clone(),
+        _ => {
+            let state = detect_conflicts();
+            *cache = Some((Instant::now(), state.clone()));
+            state
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_state() {
+        assert_eq!(parse_tuned_active("Current active profile: balanced\n").as_deref(), Some("balanced"));
+        assert_eq!(parse_tuned_active("No current active profile.\n"), None);
+
+        let state = ConflictState { ppd_active: true, tuned_profile: Some("powersave".into()), ..ConflictState::default() };
+        assert!(state.any());
+        assert_eq!(state.describe(), vec!["power-profiles-daemon", "tuned (powersave)"]);
+        assert_eq!(ConflictState { lpmd_active: true, ..ConflictState::default() }.describe(), vec!["intel_lpmd"]);
+        assert!(!ConflictState::default().any());
+    }
+
+    #[test]
+    fn test_tuned_mode() {
+        let config = Snapshot::from_str_ini("[integration]\ntuned_mode = Cooperate\ntuned_profile_powersave = balanced-battery\n").unwrap();
+        assert_eq!(tuned_mode(&config).unwrap(), TunedMode::Cooperate);
+        assert_eq!(tuned_profile_for(&config, "performance", false), "throughput-performance");
+        assert_eq!(tuned_profile_for(&config, "schedutil", true), "balanced");
+        assert_eq!(tuned_profile_for(&config, "powersave", false), "balanced-battery");
+
+        assert_eq!(tuned_mode(&Snapshot::from_str_ini("[battery]\nturbo = auto\n").unwrap()).unwrap(), TunedMode::Disable);
+        assert!(tuned_mode(&Snapshot::from_str_ini("[integration]\ntuned_mode = stop\n").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_lpmd_mode() {
+        assert_eq!(lpmd_mode(&Snapshot::default()).unwrap(), LpmdMode::Cooperate);
+        assert_eq!(lpmd_mode(&Snapshot::from_str_ini("[integration]\nlpmd_mode = ignore\n").unwrap()).unwrap(), LpmdMode::Ignore);
+        assert!(lpmd_mode(&Snapshot::from_str_ini("[integration]\nlpmd_mode = off\n").unwrap()).is_err());
+        assert_eq!(lpmd_control_for("performance"), "OFF");
+        assert_eq!(lpmd_control_for("powersave"), "AUTO");
+    }
+
+    #[test]
+    fn test_conflict_policy() {
+        let in_use = |unit: &str| unit != "power-profiles-daemon";
+        let config = Snapshot::from_str_ini("[integration]\nconflict_policy = Disable\n").unwrap();
+        assert_eq!(conflict_policy(&config).unwrap(), ConflictPolicy::Disable);
+        assert_eq!(units_to_disable(&config, in_use).unwrap(), vec!["tlp", "tuned"]);
+
+        let config = Snapshot::from_str_ini("[integration]\nconflict_policy = disable\ntuned_mode = cooperate\n").unwrap();
+        assert_eq!(units_to_disable(&config, in_use).unwrap(), vec!["tlp"]);
+
+        let config = Snapshot::from_str_ini("[integration]\nconflict_policy = disable\nlpmd_mode = Disable\n").unwrap();
+        assert_eq!(units_to_disable(&config, in_use).unwrap(), vec!["tlp", "tuned", "intel_lpmd"]);
+
+        let config = Snapshot::from_str_ini("[integration]\ntuned_mode = ignore\n").unwrap();
+        assert_eq!(conflict_policy(&config).unwrap(), ConflictPolicy::Warn);
+        assert!(units_to_disable(&config, in_use).unwrap().is_empty());
+        assert!(conflict_policy(&Snapshot::from_str_ini("[integration]\nconflict_policy = mask\n").unwrap()).is_err());
+    }
+}
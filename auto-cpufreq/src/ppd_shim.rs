@@ -0,0 +1,440 @@
+// src/ppd_shim.rs
+//
+// Stand-in for power-profiles-daemon (`[integration] ppd_shim = true`): the
+// daemon owns `net.hadess.PowerProfiles` and its newer name
+// `org.freedesktop.UPower.PowerProfiles` on the system bus, so the power mode
+// switches of GNOME and KDE keep working with auto-cpufreq in control. Their
+// power-saver/balanced/performance choices map to the governor override, like
+// `--force powersave|reset|performance`, and a change made with --force shows
+// up in the panels. Profile holds (HoldProfile, e.g. by a game launcher) apply
+// the held profile until the last hold is released, or until the client that
+// took it leaves the bus. Clients other than root need the same polkit action
+// as `--force` over the control socket to change or hold a profile. Only works
+// while the real power-profiles-daemon is not running, since the bus names are
+// taken.
+
+use anyhow::{Result, bail};
+
+use crate::config::{Snapshot, parse_bool};
+use crate::core::GovernorOverride;
+
+/// Profiles offered to clients, from most frugal to fastest
+pub const PROFILES: &[&str] = &["power-saver", "balanced", "performance"];
+
+/// Bus names and object paths of the interface (older and newer naming)
+pub const BUS_NAMES: &[(&str, &str)] = &[
+    ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+    ("org.freedesktop.UPower.PowerProfiles", "/org/freedesktop/UPower/PowerProfiles"),
+];
+
+/// Bus policy letting root own the names, deployed by --install
+pub const DBUS_POLICY: &str = "/etc/dbus-1/system.d/auto-cpufreq-power-profiles.conf";
+
+pub fn enabled(config: &Snapshot) -> bool {
+    config.get("integration", "ppd_shim").and_then(parse_bool).unwrap_or(false)
+}
+
+/// The --force value a profile maps to
+pub fn override_for(profile: &str) -> Option<&'static str> {
+    match profile {
+        "power-saver" => Some("powersave"),
+        "balanced" => Some("reset"),
+        "performance" => Some("performance"),
+        _ => None,
+    }
+}
+
+/// The profile clients see for the current override
+pub fn profile_for(governor_override: &GovernorOverride) -> &'static str {
+    match governor_override {
+        GovernorOverride::Powersave => "power-saver",
+        GovernorOverride::Default => "balanced",
+        GovernorOverride::Performance => "performance",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hold {
+    pub cookie: u32,
+    pub profile: &'static str,
+    pub reason: String,
+    pub application_id: String,
+    /// Unique bus name of the client that took it
+    pub owner: String,
+}
+
+/// Profile holds, as power-profiles-daemon keeps them: power-saver holds win
+/// over performance ones, and the profile selected before the first hold comes
+/// back once the last one is released
+#[derive(Debug, Default)]
+pub struct Holds {
+    holds: Vec<Hold>,
+    next_cookie: u32,
+    before: Option<&'static str>,
+    /// Holds dropped by a profile change, for ProfileReleased signals
+    released: Vec<u32>,
+}
+
+impl Holds {
+    pub fn list(&self) -> &[Hold] {
+        &self.holds
+    }
+
+    fn effective(&self) -> Option<&'static str> {
+        if self.holds.iter().any(|h| h.profile == "power-saver") {
+            Some("power-saver")
+        } else {
+            self.holds.last().map(|h| h.profile)
+        }
+    }
+
+    /// Add a hold for `owner` while `current` is selected; returns its cookie
+    /// and the profile to apply
+    pub fn hold(
+        &mut self,
+        profile: &str,
+        reason: &str,
+        application_id: &str,
+        owner: &str,
+        current: &'static str,
+    ) -> Result<(u32, &'static str)> {
+        let profile = match profile {
+            "power-saver" => "power-saver",
+            "performance" => "performance",
+            _ => bail!("Only power-saver and performance can be held, not {}", profile),
+        };
+
+        if self.holds.is_empty() {
+            self.before = Some(current);
+        }
+        self.next_cookie += 1;
+        self.holds.push(Hold {
+            cookie: self.next_cookie,
+            profile,
+            reason: reason.to_string(),
+            application_id: application_id.to_string(),
+            owner: owner.to_string(),
+        });
+        Ok((self.next_cookie, self.effective().unwrap_or(profile)))
+    }
+
+    /// Drop the hold with `cookie`; returns the profile to apply, `None` for
+    /// an unknown cookie
+    pub fn release(&mut self, cookie: u32) -> Option<&'static str> {
+        let index = self.holds.iter().position(|h| h.cookie == cookie)?;
+        self.holds.remove(index);
+        Some(self.after_release())
+    }
+
+    /// Drop the holds of a client that left the bus; returns the profile to
+    /// apply, `None` when it held none
+    pub fn release_owner(&mut self, owner: &str) -> Option<&'static str> {
+        let (gone, kept): (Vec<Hold>, Vec<Hold>) = self.holds.drain(..).partition(|h| h.owner == owner);
+        self.holds = kept;
+        if gone.is_empty() {
+            return None;
+        }
+        self.released.extend(gone.iter().map(|h| h.cookie));
+        Some(self.after_release())
+    }
+
+    fn after_release(&mut self) -> &'static str {
+        match self.effective() {
+            Some(profile) => profile,
+            None => self.before.take().unwrap_or("balanced"),
+        }
+    }
+
+    /// A profile picked by the user ends all holds
+    pub fn clear(&mut self) {
+        self.released.extend(self.holds.drain(..).map(|h| h.cookie));
+        self.before = None;
+    }
+
+    pub fn take_released(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.released)
+    }
+}
+
+#[cfg(feature = "ppd-shim")]
+mod server {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use anyhow::{Context, Result, bail};
+    use dbus::arg::{PropMap, RefArg, Variant};
+    use dbus::blocking::{Connection, LocalConnection};
+    use dbus::blocking::stdintf::org_freedesktop_dbus::{PropertiesPropertiesChanged, RequestNameReply};
+    use dbus::message::{MatchRule, SignalArgs};
+    use dbus::{Message, MethodErr};
+    use dbus_tree::{Access, EmitsChangedSignal, Factory, Interface, MTFn};
+    use tracing::{info, warn};
+
+    use super::{BUS_NAMES, Holds, PROFILES, override_for, profile_for};
+    use crate::control::SET_GOVERNOR_ACTION;
+    use crate::core::{AutoCpuFreqState, get_override, set_override};
+    use crate::event_code::EventCode;
+
+    type Shared = Rc<RefCell<Holds>>;
+
+    const BUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Root may change the profile, other clients what polkit allows them.
+    /// `bus` is a second connection, for asking the bus who `sender` is.
+    fn authorize(bus: &Connection, sender: Option<dbus::strings::BusName>) -> Result<()> {
+        let sender = sender.context("Can't tell who the client is")?;
+        let proxy = bus.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", BUS_TIMEOUT);
+        let (uid,): (u32,) = proxy.method_call("org.freedesktop.DBus", "GetConnectionUnixUser", (&*sender,))?;
+        if uid == 0 {
+            return Ok(());
+        }
+        let (pid,): (u32,) = proxy.method_call("org.freedesktop.DBus", "GetConnectionUnixProcessID", (&*sender,))?;
+        if !crate::polkit::is_authorized(SET_GOVERNOR_ACTION, pid, uid)? {
+            bail!("Not authorized for {}", SET_GOVERNOR_ACTION);
+        }
+        Ok(())
+    }
+
+    fn denied(e: anyhow::Error) -> MethodErr {
+        ("org.freedesktop.DBus.Error.AccessDenied", e.to_string()).into()
+    }
+
+    fn current_profile() -> &'static str {
+        profile_for(&get_override(&AutoCpuFreqState::new()))
+    }
+
+    fn select(profile: &str) -> Result<()> {
+        match override_for(profile) {
+            Some(value) => set_override(&AutoCpuFreqState::new(), value, None),
+            None => bail!("Unknown power profile: {} (expected {})", profile, PROFILES.join(", ")),
+        }
+    }
+
+    fn prop_map(entries: &[(&str, String)]) -> PropMap {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), Variant(Box::new(value.clone()) as Box<dyn RefArg>)))
+            .collect()
+    }
+
+    fn profiles() -> Vec<PropMap> {
+        PROFILES
+            .iter()
+            .map(|profile| {
+                prop_map(&[
+                    ("Profile", profile.to_string()),
+                    ("Driver", "auto-cpufreq".to_string()),
+                    ("CpuDriver", "auto-cpufreq".to_string()),
+                    ("PlatformDriver", "auto-cpufreq".to_string()),
+                ])
+            })
+            .collect()
+    }
+
+    fn hold_list(holds: &Holds) -> Vec<PropMap> {
+        holds
+            .list()
+            .iter()
+            .map(|h| {
+                prop_map(&[
+                    ("Profile", h.profile.to_string()),
+                    ("Reason", h.reason.clone()),
+                    ("ApplicationId", h.application_id.clone()),
+                ])
+            })
+            .collect()
+    }
+
+    fn interface(f: &Factory<MTFn<()>, ()>, name: &'static str, holds: &Shared, bus: &Rc<Connection>) -> Interface<MTFn<()>, ()> {
+        let (set_holds, hold_holds, release_holds, list_holds) = (holds.clone(), holds.clone(), holds.clone(), holds.clone());
+        let (set_bus, hold_bus, release_bus) = (bus.clone(), bus.clone(), bus.clone());
+
+        f.interface(name, ())
+            .add_p(
+                f.property::<&str, _>("ActiveProfile", ())
+                    .access(Access::ReadWrite)
+                    .emits_changed(EmitsChangedSignal::False)
+                    .on_get(|i, _| {
+                        i.append(current_profile());
+                        Ok(())
+                    })
+                    .on_set(move |i, m| {
+                        let profile: &str = i.read()?;
+                        authorize(&set_bus, m.msg.sender()).map_err(denied)?;
+                        select(profile).map_err(|e| MethodErr::invalid_arg(&e.to_string()))?;
+                        set_holds.borrow_mut().clear();
+                        Ok(())
+                    }),
+            )
+            .add_p(f.property::<&str, _>("PerformanceInhibited", ()).on_get(|i, _| {
+                i.append("");
+                Ok(())
+            }))
+            .add_p(f.property::<&str, _>("PerformanceDegraded", ()).on_get(|i, _| {
+                i.append("");
+                Ok(())
+            }))
+            .add_p(f.property::<Vec<PropMap>, _>("Profiles", ()).on_get(|i, _| {
+                i.append(profiles());
+                Ok(())
+            }))
+            .add_p(f.property::<Vec<&str>, _>("Actions", ()).on_get(|i, _| {
+                i.append(Vec::<&str>::new());
+                Ok(())
+            }))
+            .add_p(f.property::<Vec<PropMap>, _>("ActiveProfileHolds", ()).on_get(move |i, _| {
+                i.append(hold_list(&list_holds.borrow()));
+                Ok(())
+            }))
+            .add_p(f.property::<&str, _>("Version", ()).on_get(|i, _| {
+                i.append(format!("auto-cpufreq {}", env!("CARGO_PKG_VERSION")));
+                Ok(())
+            }))
+            .add_m(
+                f.method("HoldProfile", (), move |m| {
+                    let (profile, reason, application_id): (&str, &str, &str) = m.msg.read3()?;
+                    let owner = m.msg.sender().map(|s| s.to_string()).unwrap_or_default();
+                    authorize(&hold_bus, m.msg.sender()).map_err(denied)?;
+                    let (cookie, apply) = hold_holds
+                        .borrow_mut()
+                        .hold(profile, reason, application_id, &owner, current_profile())
+                        .map_err(|e| MethodErr::invalid_arg(&e.to_string()))?;
+                    select(apply).map_err(|e| MethodErr::failed(&e))?;
+                    info!("{} holds the {} power profile ({})", application_id, profile, reason);
+                    Ok(vec![m.msg.method_return().append1(cookie)])
+                })
+                .inarg::<&str, _>("profile")
+                .inarg::<&str, _>("reason")
+                .inarg::<&str, _>("application_id")
+                .outarg::<u32, _>("cookie"),
+            )
+            .add_m(
+                f.method("ReleaseProfile", (), move |m| {
+                    let cookie: u32 = m.msg.read1()?;
+                    authorize(&release_bus, m.msg.sender()).map_err(denied)?;
+                    let Some(apply) = release_holds.borrow_mut().release(cookie) else {
+                        return Err(MethodErr::invalid_arg(&cookie));
+                    };
+                    select(apply).map_err(|e| MethodErr::failed(&e))?;
+                    Ok(vec![m.msg.method_return()])
+                })
+                .inarg::<u32, _>("cookie"),
+            )
+            .add_s(f.signal("ProfileReleased", ()).sarg::<u32, _>("cookie"))
+    }
+
+    fn properties_changed(conn: &LocalConnection, holds: &Holds, profile: &str) {
+        for (name, path) in BUS_NAMES {
+            let mut changed: PropMap = HashMap::new();
+            changed.insert("ActiveProfile".into(), Variant(Box::new(profile.to_string())));
+            changed.insert("ActiveProfileHolds".into(), Variant(Box::new(hold_list(holds))));
+            let signal = PropertiesPropertiesChanged {
+                interface_name: name.to_string(),
+                changed_properties: changed,
+                invalidated_properties: Vec::new(),
+            };
+            let _ = dbus::channel::Sender::send(conn, signal.to_emit_message(&(*path).into()));
+        }
+    }
+
+    pub fn serve() -> Result<()> {
+        let conn = LocalConnection::new_system()?;
+        for (name, _) in BUS_NAMES {
+            if conn.request_name(*name, false, false, true)? != RequestNameReply::PrimaryOwner {
+                bail!("{} is already taken, is power-profiles-daemon running?", name);
+            }
+        }
+
+        let bus = Rc::new(Connection::new_system()?);
+        let holds: Shared = Rc::new(RefCell::new(Holds::default()));
+        let f = Factory::new_fn::<()>();
+        let mut tree = f.tree(());
+        for (name, path) in BUS_NAMES {
+            tree = tree.add(f.object_path(*path, ()).introspectable().add(interface(&f, name, &holds, &bus)));
+        }
+        tree.start_receive(&conn);
+
+        // A client that exits without releasing its holds releases them
+        let gone_holds = holds.clone();
+        let rule = MatchRule::new_signal("org.freedesktop.DBus", "NameOwnerChanged").with_sender("org.freedesktop.DBus");
+        conn.add_match(rule, move |(name, _old, new): (String, String, String), _, _| {
+            if new.is_empty() {
+                let apply = gone_holds.borrow_mut().release_owner(&name);
+                if let Some(Err(e)) = apply.map(select) {
+                    warn!(code = %EventCode::PpdShimFailed, "Failed to apply the power profile after {} left: {}", name, e);
+                }
+            }
+            true
+        })?;
+        info!("Serving the power-profiles-daemon interface on the system bus");
+
+        let mut last = (current_profile(), 0);
+        loop {
+            conn.process(Duration::from_secs(1))?;
+
+            for cookie in holds.borrow_mut().take_released() {
+                for (name, path) in BUS_NAMES {
+                    let signal = Message::signal(&(*path).into(), &(*name).into(), &"ProfileReleased".into()).append1(cookie);
+                    let _ = dbus::channel::Sender::send(&conn, signal);
+                }
+            }
+
+            // Changes from D-Bus clients, --force or an expired override alike
+            let now = (current_profile(), holds.borrow().list().len());
+            if now != last {
+                properties_changed(&conn, &holds.borrow(), now.0);
+                last = now;
+            }
+        }
+    }
+}
+
+/// Serve the interface until the connection fails; runs on its own thread
+#[cfg(feature = "ppd-shim")]
+pub fn serve() -> Result<()> {
+    server::serve()
+}
+
+#[cfg(not(feature = "ppd-shim"))]
+pub fn serve() -> Result<()> {
+    bail!("The power-profiles-daemon shim is not available in this build (enable the \"ppd-shim\" feature)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_holds() {
+        assert_eq!(override_for("power-saver"), Some("powersave"));
+        assert_eq!(override_for("low-power"), None);
+        assert_eq!(profile_for(&GovernorOverride::Default), "balanced");
+        for profile in PROFILES {
+            assert_eq!(profile_for(&GovernorOverride::from_str(override_for(profile).unwrap())), *profile);
+        }
+
+        let mut holds = Holds::default();
+        assert!(holds.hold("balanced", "", "app", ":1.1", "balanced").is_err());
+        assert_eq!(holds.hold("performance", "game", "steam", ":1.1", "balanced").unwrap(), (1, "performance"));
+        assert_eq!(holds.hold("power-saver", "low battery", "gnome", ":1.2", "performance").unwrap(), (2, "power-saver"));
+        assert_eq!(holds.release(2), Some("performance"));
+        assert_eq!(holds.release(2), None);
+        assert_eq!(holds.release(1), Some("balanced"));
+
+        holds.hold("performance", "game", "steam", ":1.1", "power-saver").unwrap();
+        holds.clear();
+        assert!(holds.list().is_empty());
+        assert_eq!(holds.take_released(), vec![3]);
+        assert!(holds.take_released().is_empty());
+
+        // Holds of a client that left the bus go with it
+        holds.hold("performance", "game", "steam", ":1.1", "balanced").unwrap();
+        holds.hold("performance", "render", "blender", ":1.3", "balanced").unwrap();
+        assert_eq!(holds.release_owner(":1.9"), None);
+        assert_eq!(holds.release_owner(":1.1"), Some("performance"));
+        assert_eq!(holds.take_released(), vec![4]);
+        assert_eq!(holds.release_owner(":1.3"), Some("balanced"));
+    }
+}
@@ -0,0 +1,100 @@
+// src/process_watch.rs
+//
+// "Performance apps": while one of the processes listed in `[performance_apps]`
+// is running (a compiler, a game, a video editor), `set_autofreq` switches to
+// the performance governor and turbo regardless of the usage/load thresholds,
+// much like gamemode. `--force`/`--turbo` overrides and the skin temperature
+// limit still win.
+
+use std::fs;
+use std::path::Path;
+
+use crate::config::{parse_bool, CONFIG, Snapshot};
+
+const PROC_DIR: &str = "/proc";
+
+/// The kernel truncates `/proc/<pid>/comm` to 15 bytes
+const COMM_LEN: usize = 15;
+
+/// `apps` from `[performance_apps]`, split on commas and whitespace
+pub fn configured_apps(config: &Snapshot) -> Vec<String> {
+    config
+        .get("performance_apps", "apps")
+        .map(|apps| {
+            apps.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|app| !app.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the performance apps also apply on battery (`on_battery`, default on)
+fn enabled_on_battery(config: &Snapshot) -> bool {
+    config
+        .get("performance_apps", "on_battery")
+        .and_then(parse_bool)
+        .unwrap_or(true)
+}
+
+fn matches(comm: &str, app: &str) -> bool {
+    comm == app || (comm.len() == COMM_LEN && app.len() > COMM_LEN && app.starts_with(comm))
+}
+
+/// First configured app with a process under `proc_dir`
+fn find_running(proc_dir: &Path, apps: &[String]) -> Option<String> {
+    if apps.is_empty() {
+        return None;
+    }
+
+    fs::read_dir(proc_dir)
+        .ok()?
+        .flatten()
+        .filter(|e| e.file_name().to_str().is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit())))
+        .filter_map(|e| fs::read_to_string(e.path().join("comm")).ok())
+        .find_map(|comm| {
+            let comm = comm.trim_end_matches('\n');
+            apps.iter().find(|app| matches(comm, app)).cloned()
+        })
+}
+
+/// Called by `set_autofreq`: the running performance app, if any
+pub fn detect(is_charging: bool) -> Option<String> {
+    let config = CONFIG.snapshot();
+    if !is_charging && !enabled_on_battery(&config) {
+        return None;
+    }
+    find_running(Path::new(PROC_DIR), &configured_apps(&config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_apps() {
+        let config = Snapshot::from_str_ini("[performance_apps]\napps = rustc, cc1plus  blender,\non_battery = no\n").unwrap();
+        assert_eq!(configured_apps(&config), vec!["rustc", "cc1plus", "blender"]);
+        assert!(!enabled_on_battery(&config));
+
+        let config = Snapshot::from_str_ini("[battery]\ngovernor = powersave\n").unwrap();
+        assert!(configured_apps(&config).is_empty());
+        assert!(enabled_on_battery(&config));
+    }
+
+    #[test]
+    fn test_find_running() {
+        let dir = std::env::temp_dir().join(format!("process_watch_test_{}", std::process::id()));
+        for (pid, comm) in [("1", "systemd"), ("42", "davinci-resolve"), ("self", "rustc")] {
+            fs::create_dir_all(dir.join(pid)).unwrap();
+            fs::write(dir.join(pid).join("comm"), format!("{}\n", comm)).unwrap();
+        }
+
+        let apps = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(find_running(&dir, &apps(&["blender", "davinci-resolve-studio"])).as_deref(), Some("davinci-resolve-studio"));
+        assert_eq!(find_running(&dir, &apps(&["rustc"])), None);
+        assert_eq!(find_running(&dir, &[]), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+// src/procfs.rs
+//
+// /proc/stat, load average and kernel release read straight from /proc. The
+// daemon path uses these instead of sysinfo, which keeps a full process and
+// component table around just to answer "how busy are the cores". Sampling
+// over time lives in `cpu::usage`.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+const STAT_PATH: &str = "/proc/stat";
+const LOADAVG_PATH: &str = "/proc/loadavg";
+const OSRELEASE_PATH: &str = "/proc/sys/kernel/osrelease";
+
+/// Jiffies spent idle and in total by one `cpu` line of /proc/stat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub idle: u64,
+    pub total: u64,
+}
+
+/// One /proc/stat reading: the aggregate `cpu` line and the `cpuN` lines,
+/// keyed by N. Offline cores have no line, so the numbering can have gaps.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatSnapshot {
+    pub all: CpuTimes,
+    pub cores: BTreeMap<usize, CpuTimes>,
+}
+
+/// Busy percentage per core and averaged over all of them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CpuUsage {
+    pub total: f32,
+    pub cores: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+fn parse_cpu_line(fields: &str) -> Option<CpuTimes> {
+    let values: Vec<u64> = fields
+        .split_whitespace()
+        .map(|v| v.parse().ok())
+        .collect::<Option<_>>()?;
+    if values.len() < 4 {
+        return None;
+    }
+    // user nice system idle iowait irq softirq steal; guest time is already
+    // included in user/nice
+    let idle = values[3] + values.get(4).copied().unwrap_or(0);
+    let total = values.iter().take(8).sum();
+    Some(CpuTimes { idle, total })
+}
+
+/// Parse the `cpu` lines of /proc/stat
+pub fn parse_stat(content: &str) -> Option<StatSnapshot> {
+    let mut all = None;
+    let mut cores = BTreeMap::new();
+
+    for line in content.lines() {
+        let Some(rest) = line.strip_prefix("cpu") else { continue };
+        match rest.split_once(' ') {
+            Some(("", fields)) => all = parse_cpu_line(fields),
+            Some((index, fields)) => {
+                if let Ok(index) = index.parse::<usize>() {
+                    cores.insert(index, parse_cpu_line(fields)?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(StatSnapshot { all: all?, cores })
+}
+
+/// Busy percentage between two readings of the same cpu line
+pub fn busy_percent(prev: CpuTimes, cur: CpuTimes) -> f32 {
+    let total = cur.total.saturating_sub(prev.total);
+    if total == 0 {
+        return 0.0;
+    }
+    let idle = cur.idle.saturating_sub(prev.idle).min(total);
+    (total - idle) as f32 * 100.0 / total as f32
+}
+
+/// Usage between two /proc/stat readings, indexed by core number. Offline
+/// cores and cores that came online in between are reported as idle.
+pub fn usage_between(prev: &StatSnapshot, cur: &StatSnapshot) -> CpuUsage {
+    let count = cur.cores.keys().next_back().map_or(0, |last| last + 1);
+    CpuUsage {
+        total: busy_percent(prev.all, cur.all),
+        cores: (0..count)
+            .map(|i| match (prev.cores.get(&i), cur.cores.get(&i)) {
+                (Some(&p), Some(&c)) => busy_percent(p, c),
+                _ => 0.0,
+            })
+            .collect(),
+    }
+}
+
+pub fn read_stat() -> Option<StatSnapshot> {
+    parse_stat(&fs::read_to_string(STAT_PATH).ok()?)
+}
+
+pub fn parse_loadavg(content: &str) -> Option<LoadAverage> {
+    let mut parts = content.split_whitespace().map(|p| p.parse::<f64>().ok());
+    Some(LoadAverage {
+        one: parts.next()??,
+        five: parts.next()??,
+        fifteen: parts.next()??,
+    })
+}
+
+pub fn load_average() -> LoadAverage {
+    fs::read_to_string(LOADAVG_PATH)
+        .ok()
+        .and_then(|s| parse_loadavg(&s))
+        .unwrap_or_default()
+}
+
+pub fn kernel_version() -> Option<String> {
+    fs::read_to_string(OSRELEASE_PATH).ok().map(|s| s.trim().to_string())
+}
+
+/// Current frequency of a core in MHz, 0 when cpufreq isn't available
+pub fn core_frequency(core: usize) -> f32 {
+    fs::read_to_string(format!("/sys/devices/system/cpu/cpu{}/cpufreq/scaling_cur_freq", core))
+        .ok()
+        .and_then(|s| s.trim().parse::<f32>().ok())
+        .map_or(0.0, |khz| khz / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT: &str = "cpu  100 0 50 800 50 0 0 0 0 0\n\
+                        cpu0 60 0 20 400 20 0 0 0 0 0\n\
+                        cpu1 40 0 30 400 30 0 0 0 0 0\n\
+                        intr 12345 0 0\n\
+                        ctxt 6789\n";
+
+    #[test]
+    fn test_parse_stat() {
+        let snapshot = parse_stat(STAT).unwrap();
+        assert_eq!(snapshot.all, CpuTimes { idle: 850, total: 1000 });
+        assert_eq!(
+            snapshot.cores,
+            BTreeMap::from([(0, CpuTimes { idle: 420, total: 500 }), (1, CpuTimes { idle: 430, total: 500 })])
+        );
+        // cpu1 offline: cpu2 keeps its own number
+        let snapshot = parse_stat("cpu  1 0 1 8 0\ncpu2 1 0 1 8 0\ncpu0 1 0 1 8 0\n").unwrap();
+        assert_eq!(snapshot.cores.keys().copied().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(parse_stat("intr 1 2 3\n"), None);
+        assert_eq!(parse_stat("cpu  1 2\n"), None);
+    }
+
+    #[test]
+    fn test_usage_between() {
+        let prev = parse_stat(STAT).unwrap();
+        let cur = parse_stat(
+            "cpu  200 0 100 1600 100 0 0 0\ncpu0 160 0 20 420 20 0 0 0\ncpu1 40 0 80 1180 80 0 0 0\ncpu2 5 0 5 90 0 0 0 0\n",
+        )
+        .unwrap();
+        let usage = usage_between(&prev, &cur);
+        assert!((usage.total - 15.0).abs() < 0.01);
+        assert!((usage.cores[0] - 83.33).abs() < 0.01);
+        assert!((usage.cores[1] - 5.68).abs() < 0.01);
+        assert_eq!(usage.cores[2], 0.0);
+
+        // cpu1 went offline: cpu2 isn't diffed against cpu1's old times
+        let offline = parse_stat("cpu  300 0 100 1700 100 0 0 0\ncpu0 260 0 20 420 20 0 0 0\ncpu2 10 0 10 180 0 0 0 0\n").unwrap();
+        let usage = usage_between(&cur, &offline);
+        assert_eq!(usage.cores.len(), 3);
+        assert_eq!(usage.cores[1], 0.0);
+        assert!((usage.cores[2] - 10.0).abs() < 0.01);
+        assert_eq!(busy_percent(prev.all, prev.all), 0.0);
+    }
+
+    #[test]
+    fn test_parse_loadavg() {
+        assert_eq!(
+            parse_loadavg("0.52 0.58 0.59 1/467 12345\n"),
+            Some(LoadAverage { one: 0.52, five: 0.58, fifteen: 0.59 })
+        );
+        assert_eq!(parse_loadavg("0.52\n"), None);
+    }
+}
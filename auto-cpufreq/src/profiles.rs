@@ -0,0 +1,357 @@
+// src/profiles.rs
+//
+// Named power profiles bundling governor, turbo, EPP, frequency limits and the
+// ACPI platform profile. A few profiles are built in; `[profile.<name>]` config
+// sections override their keys or define new ones. The selected profile is kept
+// in the state store and applied by `set_autofreq` on every cycle, on top of the
+// `[charger]`/`[battery]` settings. `--force`/`--turbo` overrides still win.
+// The modules that switch to a profile on their own (idle, docked, the
+// schedule, ...) track it with a `ProfileSwitch`.
+
+use std::sync::Mutex;
+
+use anyhow::{Result, bail, Context};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::{CONFIG, Snapshot};
+use crate::core::TurboOverride;
+use crate::cpu::{amd_pstate, per_core, platform_profile};
+use crate::event_code::EventCode;
+use crate::state::StateStore;
+
+const SECTION_PREFIX: &str = "profile.";
+
+pub const BUILTIN_PROFILES: &[&str] = &["quiet", "balanced", "max-performance"];
+
+/// Values that clear the selected profile
+pub const RESET_VALUES: &[&str] = &["none", "reset", "default"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub governor: Option<String>,
+    /// "always", "never" or "auto"
+    pub turbo: Option<String>,
+    pub energy_performance_preference: Option<String>,
+    /// kHz, applied to every online core
+    pub scaling_min_freq: Option<u32>,
+    /// kHz, applied to every online core
+    pub scaling_max_freq: Option<u32>,
+    pub platform_profile: Option<String>,
+}
+
+impl Profile {
+    /// This profile, with what it leaves unset taken from `base`
+    pub fn on_top_of(self, base: Profile) -> Profile {
+        Profile {
+            name: self.name,
+            governor: self.governor.or(base.governor),
+            turbo: self.turbo.or(base.turbo),
+            energy_performance_preference: self.energy_performance_preference.or(base.energy_performance_preference),
+            scaling_min_freq: self.scaling_min_freq.or(base.scaling_min_freq),
+            scaling_max_freq: self.scaling_max_freq.or(base.scaling_max_freq),
+            platform_profile: self.platform_profile.or(base.platform_profile),
+        }
+    }
+
+    pub fn sets_frequency_limits(&self) -> bool {
+        self.scaling_min_freq.is_some() || self.scaling_max_freq.is_some()
+    }
+}
+
+/// The profile a module switched to on the last cycle, with the key `K` of the
+/// state that picked it (`()` for a plain on/off switch)
+pub(crate) struct ProfileSwitch<K> {
+    active: Mutex<Option<(K, Profile)>>,
+}
+
+impl<K: Clone + PartialEq> ProfileSwitch<K> {
+    pub(crate) const fn new() -> Self {
+        Self { active: Mutex::new(None) }
+    }
+
+    /// Key of the state in effect on the last cycle
+    pub(crate) fn key(&self) -> Option<K> {
+        self.active.lock().unwrap().as_ref().map(|(key, _)| key.clone())
+    }
+
+    /// Switch to `current` and return its profile, `announce` logs the new
+    /// state when the key changes. Frequency limits are left to
+    /// `AppliedLimits`: once a module above this one wins, this one isn't
+    /// consulted again until it loses.
+    pub(crate) fn follow(&self, current: Option<(K, Profile)>, announce: impl FnOnce(Option<(&K, &Profile)>)) -> Option<Profile> {
+        let mut active = self.active.lock().unwrap();
+        if active.as_ref().map(|(key, _)| key) != current.as_ref().map(|(key, _)| key) {
+            announce(current.as_ref().map(|(key, profile)| (key, profile)));
+        }
+        *active = current.clone();
+        current.map(|(_, profile)| profile)
+    }
+
+    /// What `follow` would return for `current`, without announcing the
+    /// switch, for the monitor's preview
+    pub(crate) fn preview(&self, current: Option<(K, Profile)>) -> Option<Profile> {
+        current.map(|(_, profile)| profile)
+    }
+}
+
+/// The profile `set_autofreq` applied on the last cycle. Only the winning
+/// profile is applied, so this is the one place that knows which frequency
+/// limits are in effect.
+pub(crate) struct AppliedLimits {
+    applied: Mutex<Option<Profile>>,
+}
+
+impl AppliedLimits {
+    pub(crate) const fn new() -> Self {
+        Self { applied: Mutex::new(None) }
+    }
+
+    /// Record `winner` as this cycle's profile. True when the profile before
+    /// set frequency limits and the winner changed, so they have to be lifted
+    /// before `winner` is applied.
+    pub(crate) fn follow(&self, winner: Option<&Profile>) -> bool {
+        let key = |p: &Profile| (p.name.clone(), p.scaling_min_freq, p.scaling_max_freq);
+        let mut applied = self.applied.lock().unwrap();
+        let changed = applied.as_ref().map(key) != winner.map(key);
+        let reset = changed && applied.as_ref().is_some_and(Profile::sets_frequency_limits);
+        *applied = winner.cloned();
+        reset
+    }
+}
+
+fn builtin(name: &str) -> Option<Profile> {
+    let profile = |governor: Option<&str>, turbo: &str, epp: &str, platform: &str| Profile {
+        name: name.to_string(),
+        governor: governor.map(String::from),
+        turbo: Some(turbo.to_string()),
+        energy_performance_preference: Some(epp.to_string()),
+        platform_profile: Some(platform.to_string()),
+        ..Profile::default()
+    };
+
+    match name {
+        "quiet" => Some(profile(Some("powersave"), "never", "power", "low-power")),
+        "balanced" => Some(profile(None, "auto", "balance_power", "balanced")),
+        "max-performance" => Some(profile(Some("performance"), "always", "performance", "performance")),
+        _ => None,
+    }
+}
+
+/// Built-in profiles followed by the ones only defined in the config
+pub fn available(config: &Snapshot) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_PROFILES.iter().map(|s| s.to_string()).collect();
+    for section in config.sections() {
+        if let Some(name) = section.strip_prefix(SECTION_PREFIX) {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Resolve a profile: the built-in defaults (if any) with `[profile.<name>]` on top
+pub fn load(config: &Snapshot, name: &str) -> Result<Profile> {
+    let name = name.trim().to_lowercase();
+    let section = format!("{}{}", SECTION_PREFIX, name);
+
+    let mut profile = match (builtin(&name), config.section(&section)) {
+        (Some(profile), _) => profile,
+        (None, Some(_)) => Profile { name: name.clone(), ..Profile::default() },
+        (None, None) => bail!(
+            "Unknown profile '{}' (available: {})",
+            name,
+            available(config).join(", ")
+        ),
+    };
+
+    apply_overrides(config, &section, &mut profile)?;
+    Ok(profile)
+}
+
+/// Override `profile` with the profile keys (governor, turbo, EPP, platform
+/// profile, frequency limits) set in `section`
+pub(crate) fn apply_overrides(config: &Snapshot, section: &str, profile: &mut Profile) -> Result<()> {
+    let get = |key: &str| config.get(section, key).map(|v| v.trim().to_string());
+
+    if let Some(governor) = get("governor") {
+        profile.governor = Some(governor);
+    }
+    if let Some(turbo) = get("turbo") {
+        if TurboOverride::parse(&turbo).is_none() {
+            bail!("Invalid turbo in [{}]: {} (expected always, never or auto)", section, turbo);
+        }
+        profile.turbo = Some(turbo);
+    }
+    if let Some(epp) = get("energy_performance_preference") {
+        profile.energy_performance_preference = Some(epp);
+    }
+    if let Some(platform) = get("platform_profile") {
+        profile.platform_profile = Some(platform);
+    }
+    for key in ["scaling_min_freq", "scaling_max_freq"] {
+        if let Some(value) = get(key) {
+            let freq = value
+                .parse::<u32>()
+                .with_context(|| format!("Invalid {} in [{}]: {} (expected kHz)", key, section, value))?;
+            if key == "scaling_min_freq" {
+                profile.scaling_min_freq = Some(freq);
+            } else {
+                profile.scaling_max_freq = Some(freq);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The profile selected with `--profile`, if any. A profile that no longer
+/// resolves (e.g. its section was removed from the config) is ignored.
+pub fn active() -> Option<Profile> {
+    let name = StateStore::default().load().last_profile?;
+    match load(&CONFIG.snapshot(), &name) {
+        Ok(profile) => Some(profile),
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "Ignoring selected profile: {}", e);
+            None
+        }
+    }
+}
+
+/// Store the selected profile, `None` (or a `RESET_VALUES` name) clears it.
+/// Frequency limits from the previous profile are lifted so the new one starts
+/// from the hardware range.
+pub fn select(name: Option<&str>) -> Result<()> {
+    let name = name
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !RESET_VALUES.contains(&n.as_str()));
+
+    if let Some(ref name) = name {
+        load(&CONFIG.snapshot(), name)?;
+    }
+
+    let previous = active();
+    StateStore::default().update(|s| s.last_profile = name.clone())?;
+
+    if previous.is_some_and(|p| p.sets_frequency_limits()) {
+        reset_frequency_limits();
+    }
+
+    match name {
+        Some(name) => println!("Selected profile: {}", name),
+        None => println!("Profile selection removed"),
+    }
+    Ok(())
+}
+
+/// Lift frequency limits back to the hardware range on every online core
+pub(crate) fn reset_frequency_limits() {
+    for core in per_core::all_cores() {
+        if !per_core::is_online(core) {
+            continue;
+        }
+        if let Some((min, max)) = per_core::hardware_limits(core) {
+            if let Err(e) = per_core::set_frequency_limits(core, Some(min), Some(max)) {
+                warn!(code = %EventCode::TuningFailed, "{}", e);
+            }
+        }
+    }
+}
+
+/// Apply the parts of a profile that `set_autofreq` doesn't route through its
+/// governor and turbo decisions. `governor` is the governor that was just set.
+pub fn apply(profile: &Profile, governor: &str) -> Result<()> {
+    if let Some(epp) = &profile.energy_performance_preference {
+        // The performance governor pins EPP to performance on active-mode drivers
+        if governor != "performance" && amd_pstate::get_epp().is_some_and(|current| &current != epp) {
+            amd_pstate::set_epp(epp)?;
+        }
+    }
+
+    if profile.scaling_min_freq.is_some() || profile.scaling_max_freq.is_some() {
+        for core in per_core::all_cores() {
+            if per_core::is_online(core) {
+                per_core::set_frequency_limits(core, profile.scaling_min_freq, profile.scaling_max_freq)?;
+            }
+        }
+    }
+
+    if let Some(platform) = &profile.platform_profile {
+        if platform_profile::is_supported() {
+            // Built-in profiles use generic names, map them to what the firmware offers
+            let value = platform_profile::resolve(platform).unwrap_or_else(|| platform.clone());
+            platform_profile::set(&value)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_switch() {
+        let switch = ProfileSwitch::new();
+        let quiet = || builtin("quiet").unwrap();
+        let mut announced = Vec::new();
+
+        let mut announce = |state: Option<(&u8, &Profile)>| announced.push(state.map(|(key, _)| *key));
+
+        assert_eq!(switch.follow(Some((50, quiet())), &mut announce), Some(quiet()));
+        assert_eq!(switch.follow(Some((50, quiet())), &mut announce), Some(quiet()));
+        assert_eq!(switch.key(), Some(50));
+        switch.follow(Some((20, quiet())), &mut announce);
+        assert_eq!(switch.follow(None, &mut announce), None);
+        assert_eq!(announced, vec![Some(50), Some(20), None]);
+        assert_eq!(switch.key(), None);
+        assert_eq!(switch.preview(Some((50, quiet()))), Some(quiet()));
+    }
+
+    #[test]
+    fn test_applied_limits() {
+        let limits = AppliedLimits::new();
+        let capped = Profile { name: "idle".to_string(), scaling_max_freq: Some(1_200_000), ..Profile::default() };
+        let quiet = builtin("quiet").unwrap();
+
+        assert!(!limits.follow(Some(&capped)));
+        assert!(!limits.follow(Some(&capped)));
+        // A new cap from the same profile replaces the old one
+        let lower = Profile { scaling_max_freq: Some(1_000_000), ..capped.clone() };
+        assert!(limits.follow(Some(&lower)));
+        assert!(limits.follow(Some(&quiet)));
+        assert!(!limits.follow(None));
+        assert!(!limits.follow(Some(&capped)));
+        assert!(limits.follow(None));
+    }
+
+    #[test]
+    fn test_load_builtin_with_overrides() {
+        let config = Snapshot::from_str_ini(
+            "[profile.quiet]\nscaling_max_freq = 1800000\nturbo = auto\n\
+             [profile.gaming]\ngovernor = performance\n",
+        )
+        .unwrap();
+
+        let quiet = load(&config, "quiet").unwrap();
+        assert_eq!(quiet.governor.as_deref(), Some("powersave"));
+        assert_eq!(quiet.turbo.as_deref(), Some("auto"));
+        assert_eq!(quiet.scaling_max_freq, Some(1_800_000));
+
+        let gaming = load(&config, "Gaming").unwrap();
+        assert_eq!(gaming.governor.as_deref(), Some("performance"));
+        assert_eq!(gaming.turbo, None);
+
+        assert!(load(&config, "missing").is_err());
+        assert_eq!(available(&config), vec!["quiet", "balanced", "max-performance", "gaming"]);
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_turbo() {
+        let config = Snapshot::from_str_ini("[profile.balanced]\nturbo = sometimes\n").unwrap();
+        assert!(load(&config, "balanced").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+// src/quiet_hours.rs
+//
+// Quiet hours: time windows (`[quiet_hours] hours = 23:00-07:00`) in which
+// turbo stays off whatever the load, and the maximum frequency is optionally
+// capped, so the fans stay quiet at night. Unlike the idle or headless
+// settings this is not a profile: the governor and everything else are still
+// decided as usual, quiet hours are layered on top. Only --turbo overrides
+// take priority.
+
+use anyhow::{Context, Result, bail};
+use chrono::{NaiveTime, Timelike};
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, Snapshot};
+use crate::cpu::per_core;
+use crate::event_code::EventCode;
+use crate::profiles::{self, Profile, ProfileSwitch};
+
+const SECTION: &str = "quiet_hours";
+
+/// Keyed by the window in effect
+static SWITCH: ProfileSwitch<Window> = ProfileSwitch::new();
+
+/// A daily window, wrapping past midnight when `end` is earlier than `start`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Window {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl std::fmt::Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+pub(crate) fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").with_context(|| format!("Invalid time '{}' (expected HH:MM)", value.trim()))
+}
+
+/// `23:00-07:00`, several separated by commas
+pub fn parse_windows(value: &str) -> Result<Vec<Window>> {
+    let mut windows = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((start, end)) = part.split_once('-') else {
+            bail!("Invalid quiet hours '{}' (expected e.g. 23:00-07:00)", part);
+        };
+        let window = Window { start: parse_time(start)?, end: parse_time(end)? };
+        if window.start == window.end {
+            bail!("Quiet hours '{}' start and end at the same time", part);
+        }
+        windows.push(window);
+    }
+    if windows.is_empty() {
+        bail!("No quiet hours given");
+    }
+    Ok(windows)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub windows: Vec<Window>,
+    /// kHz cap while quiet, `None` for turbo off only
+    pub scaling_max_freq: Option<u32>,
+}
+
+/// `[quiet_hours]`, `None` without an `hours` key
+pub fn settings(config: &Snapshot) -> Result<Option<Settings>> {
+    let Some(hours) = config.get(SECTION, "hours") else {
+        return Ok(None);
+    };
+    let scaling_max_freq = match config.get(SECTION, "scaling_max_freq").map(str::trim) {
+        None => None,
+        Some(value) => match value.parse::<u32>() {
+            Ok(khz) if khz > 0 => Some(khz),
+            _ => bail!("Invalid scaling_max_freq in [quiet_hours]: {} (expected kHz)", value),
+        },
+    };
+    Ok(Some(Settings { windows: parse_windows(hours)?, scaling_max_freq }))
+}
+
+/// The window `time` falls in
+fn window_at(settings: &Settings, time: NaiveTime) -> Option<Window> {
+    settings.windows.iter().copied().find(|w| w.contains(time))
+}
+
+fn current() -> Option<(Settings, Window)> {
+    let settings = match settings(&CONFIG.snapshot()) {
+        Ok(settings) => settings?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            return None;
+        }
+    };
+    let now = chrono::Local::now().time().with_second(0)?;
+    let window = window_at(&settings, now)?;
+    Some((settings, window))
+}
+
+/// What quiet hours change, in the form `ProfileSwitch` keeps track of
+fn quiet_profile(settings: &Settings) -> Profile {
+    Profile {
+        name: SECTION.to_string(),
+        turbo: Some("never".to_string()),
+        scaling_max_freq: settings.scaling_max_freq,
+        ..Profile::default()
+    }
+}
+
+/// Whether turbo is banned right now, for the turbo decision
+pub fn active() -> bool {
+    current().is_some()
+}
+
+/// Called by `set_autofreq` after the profile and per-core settings: caps the
+/// maximum frequency while quiet, and lifts the cap once quiet hours are over
+pub fn apply() {
+    let current = current().map(|(settings, window)| (window, quiet_profile(&settings)));
+    let was_quiet = SWITCH.key().is_some();
+    let quiet = SWITCH.follow(current, |quiet| match quiet {
+        Some((window, _)) => info!("Quiet hours ({}) started, turbo off", window),
+        None => info!("Quiet hours over"),
+    });
+
+    if was_quiet && quiet.is_none() {
+        // The profile's own limits come back on the next cycle
+        profiles::reset_frequency_limits();
+    }
+
+    let Some(cap) = quiet.and_then(|profile| profile.scaling_max_freq) else {
+        return;
+    };
+    for core in per_core::all_cores().into_iter().filter(|c| per_core::is_online(*c)) {
+        let Some((min, max)) = per_core::hardware_limits(core) else {
+            continue;
+        };
+        // A lower limit set by a profile this cycle stays
+        let target = cap.clamp(min, max).min(per_core::scaling_max_freq(core).unwrap_or(max));
+        if let Err(e) = per_core::set_frequency_limits(core, None, Some(target)) {
+            warn!(code = %EventCode::TuningFailed, "Failed to cap CPU{} for quiet hours: {}", core, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours() {
+        let time = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").unwrap();
+
+        let config = Snapshot::from_str_ini("[quiet_hours]\nhours = 23:00-07:00, 12:30-13:00\nscaling_max_freq = 1800000\n").unwrap();
+        let settings = settings(&config).unwrap().unwrap();
+        assert_eq!(settings.scaling_max_freq, Some(1_800_000));
+        assert_eq!(window_at(&settings, time("23:30")).map(|w| w.to_string()).as_deref(), Some("23:00-07:00"));
+        assert!(window_at(&settings, time("06:59")).is_some());
+        assert!(window_at(&settings, time("07:00")).is_none());
+        assert!(window_at(&settings, time("12:45")).is_some());
+        assert!(window_at(&settings, time("22:59")).is_none());
+
+        assert_eq!(super::settings(&Snapshot::default()).unwrap(), None);
+        assert!(parse_windows("23:00").is_err());
+        assert!(parse_windows("25:00-07:00").is_err());
+        assert!(parse_windows("07:00-07:00").is_err());
+    }
+}
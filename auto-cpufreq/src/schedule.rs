@@ -0,0 +1,240 @@
+// src/schedule.rs
+//
+// Profiles by time of day: `[schedule] rules = 23:00-07:00 quiet, mon-fri
+// 09:00-17:30 max-performance` runs the quiet profile overnight and full speed
+// during work hours. Each rule is an optional day or day range, an HH:MM-HH:MM
+// window that may wrap past midnight (it then belongs to the day it starts on)
+// and a profile name, built-in or `[profile.<name>]`; the first rule that
+// matches wins. Times are wall-clock times in the system time zone, looked up
+// on every cycle, so a DST change or a new time zone takes effect without a
+// restart. A profile picked with `--profile` takes precedence over the schedule.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Weekday};
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, Snapshot};
+use crate::event_code::EventCode;
+use crate::profiles::{self, Profile, ProfileSwitch};
+use crate::quiet_hours::{Window, parse_time};
+
+const SECTION: &str = "schedule";
+
+/// Keyed by the rule in effect
+static SWITCH: ProfileSwitch<Rule> = ProfileSwitch::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    /// First and last day, wrapping past Sunday; `None` for every day
+    pub days: Option<(Weekday, Weekday)>,
+    pub window: Window,
+    pub profile: String,
+}
+
+fn day_in((first, last): (Weekday, Weekday), day: Weekday) -> bool {
+    let (first, last, day) = (first.num_days_from_monday(), last.num_days_from_monday(), day.num_days_from_monday());
+    if first <= last {
+        first <= day && day <= last
+    } else {
+        day >= first || day <= last
+    }
+}
+
+impl Rule {
+    pub fn matches(&self, now: NaiveDateTime) -> bool {
+        let time = now.time();
+        if !self.window.contains(time) {
+            return false;
+        }
+        let Some(days) = self.days else {
+            return true;
+        };
+        // After midnight in a window that started the evening before
+        let started = match self.window.start > self.window.end && time < self.window.end {
+            true => now.weekday().pred(),
+            false => now.weekday(),
+        };
+        day_in(days, started)
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let day = |day: Weekday| day.to_string().to_lowercase();
+        match self.days {
+            Some((first, last)) if first == last => write!(f, "{} ", day(first))?,
+            Some((first, last)) => write!(f, "{}-{} ", day(first), day(last))?,
+            None => {}
+        }
+        write!(f, "{} {}", self.window, self.profile)
+    }
+}
+
+fn parse_day(value: &str) -> Result<Weekday> {
+    value.parse().ok().with_context(|| format!("Invalid day '{}' (expected mon, tue, ...)", value))
+}
+
+fn parse_rule(value: &str) -> Result<Rule> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let (days, window, profile) = match fields[..] {
+        [window, profile] => (None, window, profile),
+        [days, window, profile] => {
+            let days = match days.split_once('-') {
+                Some((first, last)) => (parse_day(first)?, parse_day(last)?),
+                None => (parse_day(days)?, parse_day(days)?),
+            };
+            (Some(days), window, profile)
+        }
+        _ => bail!("Invalid schedule rule '{}' (expected e.g. mon-fri 09:00-17:30 max-performance)", value),
+    };
+
+    let Some((start, end)) = window.split_once('-') else {
+        bail!("Invalid time window '{}' (expected e.g. 09:00-17:30)", window);
+    };
+    let window = Window { start: parse_time(start)?, end: parse_time(end)? };
+    if window.start == window.end {
+        bail!("Schedule rule '{}' starts and ends at the same time", value);
+    }
+    Ok(Rule { days, window, profile: profile.to_lowercase() })
+}
+
+/// `mon-fri 09:00-17:30 max-performance`, several separated by commas
+pub fn parse_rules(value: &str) -> Result<Vec<Rule>> {
+    let rules = value
+        .split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(parse_rule)
+        .collect::<Result<Vec<_>>>()?;
+    if rules.is_empty() {
+        bail!("No schedule rules given");
+    }
+    Ok(rules)
+}
+
+/// `[schedule] rules`, `None` without it. Every profile named must exist.
+pub fn settings(config: &Snapshot) -> Result<Option<Vec<Rule>>> {
+    let Some(value) = config.get(SECTION, "rules") else {
+        return Ok(None);
+    };
+    let rules = parse_rules(value)?;
+    for rule in &rules {
+        profiles::load(config, &rule.profile).with_context(|| format!("In schedule rule '{}'", rule))?;
+    }
+    Ok(Some(rules))
+}
+
+/// The rule in effect at local time `now`
+pub fn rule_at(rules: &[Rule], now: NaiveDateTime) -> Option<&Rule> {
+    rules.iter().find(|rule| rule.matches(now))
+}
+
+/// The next time after `now` at which a rule starts or ends. A time skipped by
+/// a DST change happens when the clock jumps, one that occurs twice the first
+/// time.
+pub fn next_change<Tz: TimeZone>(rules: &[Rule], now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    // The first minute after a gap is the instant the clock jumped
+    let resolve = |local: NaiveDateTime| {
+        (0..=24 * 60)
+            .map(|minutes| local + chrono::Duration::minutes(minutes))
+            .find_map(|local| zone.from_local_datetime(&local).earliest())
+    };
+
+    let today = now.naive_local().date();
+    (0..=7)
+        .filter_map(|days| today.checked_add_days(chrono::Days::new(days)))
+        .flat_map(|date| rules.iter().flat_map(move |rule| [date.and_time(rule.window.start), date.and_time(rule.window.end)]))
+        .filter_map(resolve)
+        .filter(|time| time > now)
+        .min()
+}
+
+/// The rule in effect and its profile
+fn current() -> Option<(Rule, Profile)> {
+    let config = CONFIG.snapshot();
+    let rules = match settings(&config) {
+        Ok(rules) => rules?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{:#}", e);
+            return None;
+        }
+    };
+    let rule = rule_at(&rules, chrono::Local::now().naive_local())?.clone();
+    let profile = profiles::load(&config, &rule.profile).ok()?;
+    Some((rule, profile))
+}
+
+/// Called by `set_autofreq`: the profile of the rule in effect, logging when
+/// it changes
+pub fn profile() -> Option<Profile> {
+    SWITCH.follow(current(), |rule| match rule {
+        Some((rule, _)) => info!("Schedule: profile {} ({})", rule.profile, rule),
+        None => info!("Schedule: no rule applies, back to the regular settings"),
+    })
+}
+
+pub fn preview() -> Option<Profile> {
+    SWITCH.preview(current())
+}
+
+/// How long until the schedule switches, so the daemon can wake up on time
+pub fn until_next_change() -> Option<Duration> {
+    let rules = settings(&CONFIG.snapshot()).ok()??;
+    let now = chrono::Local::now();
+    (next_change(&rules, &now)? - now).to_std().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, NaiveDate};
+    use chrono_tz::Europe::Berlin;
+
+    #[test]
+    fn test_schedule_rules() {
+        let config = Snapshot::from_str_ini(
+            "[schedule]\nrules = fri-sun 22:00-02:00 quiet, mon-fri 09:00-17:30 Max-Performance, 23:00-07:00 night\n\
+             [profile.night]\ngovernor = powersave\n",
+        )
+        .unwrap();
+        let rules = settings(&config).unwrap().unwrap();
+        assert_eq!(rules[0].to_string(), "fri-sun 22:00-02:00 quiet");
+        assert_eq!(rules[1].profile, "max-performance");
+
+        // 2024-03-01 is a Friday
+        let at = |day: u32, time: &str| {
+            let now = NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_time(parse_time(time).unwrap());
+            rule_at(&rules, now).map(|rule| rule.profile.as_str())
+        };
+        assert_eq!(at(1, "10:00"), Some("max-performance"));
+        assert_eq!(at(2, "10:00"), None);
+        assert_eq!(at(1, "23:30"), Some("quiet"));
+        // Saturday 01:00 is still Friday night; Monday 01:00 belongs to Sunday
+        assert_eq!(at(2, "01:00"), Some("quiet"));
+        assert_eq!(at(4, "01:00"), Some("quiet"));
+        assert_eq!(at(5, "01:00"), Some("night"));
+        assert_eq!(at(5, "23:30"), Some("night"));
+
+        let zone = FixedOffset::east_opt(3600).unwrap();
+        let now = zone.from_local_datetime(&NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(18, 0, 0).unwrap()).unwrap();
+        assert_eq!(next_change(&rules, &now).unwrap().naive_local().to_string(), "2024-03-01 22:00:00");
+
+        // Berlin skips 02:00-03:00 on 2024-03-31 and has 02:00-03:00 twice on 2024-10-27
+        let berlin = |date: &str| Berlin.from_local_datetime(&date.parse::<NaiveDate>().unwrap().and_hms_opt(0, 0, 0).unwrap()).unwrap();
+        let night = parse_rules("02:30-04:00 quiet").unwrap();
+        let spring = next_change(&night, &berlin("2024-03-31")).unwrap();
+        assert_eq!(spring.to_rfc3339(), "2024-03-31T03:00:00+02:00");
+        let autumn = next_change(&night, &berlin("2024-10-27")).unwrap();
+        assert_eq!(autumn.to_rfc3339(), "2024-10-27T02:30:00+02:00");
+        assert_eq!(next_change(&night, &autumn).unwrap().to_rfc3339(), "2024-10-27T04:00:00+01:00");
+
+        assert!(parse_rules("09:00-17:30").is_err());
+        assert!(parse_rules("someday 09:00-17:30 quiet").is_err());
+        assert!(parse_rules("09:00-09:00 quiet").is_err());
+        let unknown = Snapshot::from_str_ini("[schedule]\nrules = 09:00-17:00 turbo\n").unwrap();
+        assert!(settings(&unknown).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+// src/sd_notify.rs
+//
+// The systemd side of the daemon: the unit is `Type=notify`, so the daemon
+// reports READY=1 once the first cycle has applied its settings, keeps a
+// one-line STATUS ("governor=powersave, battery") for `systemctl status`, pings
+// WATCHDOG=1 every cycle and says STOPPING=1 on its way out. A daemon that
+// hangs mid-cycle misses its pings and is restarted. When the service pulls in
+// auto-cpufreq.socket, systemd creates the control socket and hands it over
+// (LISTEN_FDS) instead of the daemon binding it. Outside systemd, or under
+// another init, NOTIFY_SOCKET is unset and all of this does nothing.
+
+use std::env;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+/// First file descriptor passed by socket activation
+const LISTEN_FDS_START: i32 = 3;
+
+fn address(socket: &str) -> Result<SocketAddr> {
+    // A leading @ stands for the abstract namespace
+    match socket.strip_prefix('@') {
+        Some(name) => SocketAddr::from_abstract_name(name),
+        None => SocketAddr::from_pathname(Path::new(socket)),
+    }
+    .with_context(|| format!("Invalid NOTIFY_SOCKET {}", socket))
+}
+
+/// Send `state` (newline separated `KEY=value` pairs) to `socket`
+fn notify_at(socket: &str, state: &str) -> Result<()> {
+    let sender = UnixDatagram::unbound()?;
+    sender
+        .send_to_addr(state.as_bytes(), &address(socket)?)
+        .with_context(|| format!("Failed to notify systemd at {}", socket))?;
+    Ok(())
+}
+
+/// Send `state` to systemd; `Ok(false)` when not run as a notify service
+pub fn notify(state: &str) -> Result<bool> {
+    let Ok(socket) = env::var("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+    notify_at(&socket, state)?;
+    Ok(true)
+}
+
+/// Whether a variable naming a PID, when set, names this process
+fn for_this_process(var: &str) -> bool {
+    env::var(var).map_or(true, |pid| pid.parse() == Ok(std::process::id()))
+}
+
+/// How often to send WATCHDOG=1: half of `WatchdogSec`, `None` without one
+pub fn watchdog_interval() -> Option<Duration> {
+    if !for_this_process("WATCHDOG_PID") {
+        return None;
+    }
+    let usec: u64 = env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec / 2))
+}
+
+/// `STATUS=` text for a cycle's outcome
+pub fn status_line(governor: &str, is_charging: bool) -> String {
+    format!("governor={}, {}", governor, if is_charging { "AC" } else { "battery" })
+}
+
+/// Report the outcome of a cycle and that the daemon is alive. The first call
+/// also tells systemd that startup is done.
+pub fn cycle_done(status: &str) {
+    static READY: std::sync::Once = std::sync::Once::new();
+    let mut state = format!("WATCHDOG=1\nSTATUS={}", status);
+    READY.call_once(|| state.insert_str(0, "READY=1\n"));
+    if let Err(e) = notify(&state) {
+        tracing::warn!(code = %crate::event_code::EventCode::NotifyFailed, "{}", e);
+    }
+}
+
+pub fn stopping() {
+    let _ = notify("STOPPING=1\nSTATUS=Restoring settings");
+}
+
+/// The socket systemd opened for us, when started with auto-cpufreq.socket.
+/// Taken once: the variables are cleared so children don't see them.
+pub fn take_listen_fd() -> Option<OwnedFd> {
+    if !for_this_process("LISTEN_PID") || env::var_os("LISTEN_PID").is_none() {
+        return None;
+    }
+    let count: i32 = env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    env::remove_var("LISTEN_PID");
+    env::remove_var("LISTEN_FDS");
+    env::remove_var("LISTEN_FDNAMES");
+    // auto-cpufreq.socket has a single ListenStream=
+    // Safe: systemd passes these descriptors open and owned by this process
+    (count >= 1).then(|| unsafe { OwnedFd::from_raw_fd(LISTEN_FDS_START) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_datagram() {
+        assert_eq!(status_line("powersave", false), "governor=powersave, battery");
+        assert_eq!(status_line("performance", true), "governor=performance, AC");
+
+        let path = env::temp_dir().join(format!("acf-notify-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let systemd = UnixDatagram::bind(&path).unwrap();
+        notify_at(path.to_str().unwrap(), "READY=1\nSTATUS=governor=powersave, battery").unwrap();
+        let mut buf = [0u8; 128];
+        let len = systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1\nSTATUS=governor=powersave, battery");
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(address("@/org/freedesktop/systemd1/notify").unwrap().as_abstract_name().is_some());
+        assert!(notify_at("/nonexistent/notify", "WATCHDOG=1").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// src/sensors.rs
+//
+// Reading numeric sensor and battery values from sysfs. hwmon and
+// power_supply attributes fail for a moment with EIO or ENODEV around suspend
+// and resume or while a driver module is reloaded. Treating that as 0 °C or
+// "no battery" fed bogus values into the turbo temperature logic, so a failed
+// read is retried with a short backoff, and if it still fails the last good
+// value is used as long as it isn't older than `STALE_AFTER`. Past that, or
+// when the attribute doesn't exist at all, the value is unavailable (`None`)
+// and callers show it as such rather than inventing a number.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+
+/// Reads of one attribute before giving up
+const ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for each further one
+const BACKOFF: Duration = Duration::from_millis(5);
+/// How long a last good value stands in for failed reads
+pub const STALE_AFTER: Duration = Duration::from_secs(30);
+
+lazy_static::lazy_static! {
+    static ref LAST_GOOD: Mutex<LastGood> = Mutex::new(LastGood::default());
+}
+
+/// Errors a driver returns while it is suspended or being reloaded
+fn is_transient(error: &io::Error) -> bool {
+    [Errno::EIO, Errno::ENODEV, Errno::ENXIO, Errno::EAGAIN, Errno::EBUSY]
+        .iter()
+        .any(|e| error.raw_os_error() == Some(*e as i32))
+}
+
+/// Read `path`, retrying transient errors with a backoff
+pub fn read_with_retry(path: &Path) -> io::Result<String> {
+    let mut delay = BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match fs::read_to_string(path) {
+            Err(e) if attempt < ATTEMPTS && is_transient(&e) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// What a read failed with, deciding whether the last good value may stand in
+enum Failure {
+    /// Transient error or a value that doesn't parse (e.g. empty mid-reload)
+    Transient,
+    /// Missing attribute, permissions: nothing to fall back on
+    Permanent,
+}
+
+fn read_f32(path: &Path) -> Result<f32, Failure> {
+    match read_with_retry(path) {
+        Ok(value) => value.trim().parse().map_err(|_| Failure::Transient),
+        Err(e) if is_transient(&e) => Err(Failure::Transient),
+        Err(_) => Err(Failure::Permanent),
+    }
+}
+
+/// Last value successfully read from each attribute
+#[derive(Debug, Default)]
+struct LastGood {
+    values: HashMap<PathBuf, (f32, Instant)>,
+}
+
+impl LastGood {
+    /// `read` for `path` at `now`, falling back to a recent good value
+    fn resolve(&mut self, path: &Path, read: Result<f32, Failure>, now: Instant) -> Option<f32> {
+        match read {
+            Ok(value) => {
+                self.values.insert(path.to_path_buf(), (value, now));
+                Some(value)
+            }
+            Err(Failure::Transient) => match self.values.get(path) {
+                Some(&(value, at)) if now.duration_since(at) <= STALE_AFTER => Some(value),
+                _ => {
+                    self.values.remove(path);
+                    None
+                }
+            },
+            Err(Failure::Permanent) => {
+                self.values.remove(path);
+                None
+            }
+        }
+    }
+}
+
+/// Numeric value of a sysfs attribute, `None` when unavailable
+pub fn read_value(path: &Path) -> Option<f32> {
+    let read = read_f32(path);
+    LAST_GOOD.lock().unwrap().resolve(path, read, Instant::now())
+}
+
+/// A temperature attribute (millidegrees) in °C. 0 counts as unavailable:
+/// sensors report it while they are still coming up.
+pub fn read_temperature(path: &Path) -> Option<f32> {
+    read_value(path).map(|t| t / 1000.0).filter(|&t| t > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_good_fallback() {
+        let path = Path::new("/sys/class/hwmon/hwmon0/temp1_input");
+        let start = Instant::now();
+        let mut last_good = LastGood::default();
+
+        assert_eq!(last_good.resolve(path, Ok(52_000.0), start), Some(52_000.0));
+        // EIO during resume: the previous reading stands in for a while
+        assert_eq!(last_good.resolve(path, Err(Failure::Transient), start + Duration::from_secs(5)), Some(52_000.0));
+        assert_eq!(last_good.resolve(path, Err(Failure::Transient), start + STALE_AFTER + Duration::from_secs(1)), None);
+
+        last_good.resolve(path, Ok(48_000.0), start);
+        assert_eq!(last_good.resolve(path, Err(Failure::Permanent), start), None);
+        assert_eq!(last_good.resolve(path, Err(Failure::Transient), start), None);
+
+        assert!(is_transient(&io::Error::from_raw_os_error(Errno::EIO as i32)));
+        assert!(!is_transient(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+}
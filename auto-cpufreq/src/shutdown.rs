@@ -0,0 +1,170 @@
+// src/shutdown.rs
+//
+// What the daemon puts back when it is stopped. The CPU policy found at
+// startup (governor, turbo, EPP, each core's governor and frequency limits
+// and the RAPL power limits)
+// is kept in the state file before the first cycle changes anything. On
+// SIGTERM/SIGINT (`systemctl stop`, Ctrl+C) it is restored and the stats
+// written one last time, so stopping the service doesn't leave the machine in
+// powersave with turbo off. When a run ends without getting here (crash,
+// SIGKILL), its saved values stay and the next run restores those rather than
+// the ones it started with. `[daemon] restore_on_exit = false` leaves
+// everything as last set but keeps the saved policy, which `--remove` and
+// `--restore-defaults` put back.
+
+use std::fs;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::{CONFIG, Snapshot, parse_bool};
+use crate::cpu::{amd_pstate, rapl};
+use crate::cpu::per_core::CorePolicy;
+use crate::event_code::EventCode;
+use crate::state::StateStore;
+
+const GOVERNOR_PATH: &str = "/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Original {
+    pub governor: Option<String>,
+    pub turbo: Option<bool>,
+    /// cpu0's EPP, `None` without EPP support
+    pub epp: Option<String>,
+    /// Empty in state files written before per-core policies were saved
+    #[serde(default)]
+    pub cores: Vec<CorePolicy>,
+    /// RAPL PL1/PL2, `None` without RAPL
+    #[serde(default)]
+    pub rapl: Option<rapl::Limits>,
+}
+
+impl Original {
+    pub fn capture() -> Self {
+        Self {
+            governor: fs::read_to_string(GOVERNOR_PATH).ok().map(|g| g.trim().to_string()),
+            turbo: crate::core::turbo(None).ok(),
+            epp: amd_pstate::get_epp(),
+            cores: CorePolicy::capture_all(),
+            rapl: rapl::Limits::capture(),
+        }
+    }
+
+    /// Put every value back, carrying on past failures
+    fn restore(&self) {
+        if let Some(governor) = &self.governor {
+            if let Err(e) = crate::core::set_governor(governor) {
+                warn!(code = %EventCode::RestoreFailed, "Failed to restore governor {}: {}", governor, e);
+            }
+        }
+        // Cores that had their own governor or limits
+        for core in &self.cores {
+            if let Err(e) = core.restore() {
+                warn!(code = %EventCode::RestoreFailed, "Failed to restore CPU{}: {}", core.core, e);
+            }
+        }
+        if let Some(on) = self.turbo {
+            crate::core::set_turbo(on);
+        }
+        if let Some(limits) = &self.rapl {
+            if let Err(e) = limits.restore() {
+                warn!(code = %EventCode::RestoreFailed, "Failed to restore the RAPL power limits: {}", e);
+            }
+        }
+        // After the governor, which may pin EPP (performance does on active-mode drivers)
+        if let Some(epp) = &self.epp {
+            if amd_pstate::get_epp().as_ref() != Some(epp) {
+                if let Err(e) = amd_pstate::set_epp(epp) {
+                    warn!(code = %EventCode::RestoreFailed, "Failed to restore EPP {}: {}", epp, e);
+                }
+            }
+        }
+    }
+}
+
+/// `[daemon] restore_on_exit`, on by default
+pub fn restore_enabled(config: &Snapshot) -> bool {
+    config.get("daemon", "restore_on_exit").and_then(parse_bool).unwrap_or(true)
+}
+
+/// Save `current` unless an earlier run that didn't exit cleanly already did
+fn remember_in(store: &StateStore, current: Original) -> Result<()> {
+    store.update(|s| {
+        s.original_settings.get_or_insert(current);
+    })?;
+    Ok(())
+}
+
+/// At daemon start, before the first cycle
+pub fn remember() -> Result<()> {
+    remember_in(&StateStore::default(), Original::capture())
+}
+
+/// Restore the saved policy and forget it; `false` when none was saved
+pub fn restore_saved() -> Result<bool> {
+    let store = StateStore::default();
+    let Some(original) = store.load().original_settings else {
+        return Ok(false);
+    };
+    info!(code = %EventCode::SettingsRestored, "Restoring the settings from before the daemon started");
+    crate::profiles::reset_frequency_limits();
+    original.restore();
+    store.update(|s| s.original_settings = None)?;
+    Ok(true)
+}
+
+/// On SIGTERM/SIGINT, before the daemon exits
+pub fn on_exit() {
+    if let Err(e) = crate::core::update_stats_file() {
+        warn!(code = %EventCode::StateWriteFailed, "Failed to update stats file: {}", e);
+    }
+
+    if !restore_enabled(&CONFIG.snapshot()) {
+        return;
+    }
+    if let Err(e) = restore_saved() {
+        warn!(code = %EventCode::StateWriteFailed, "Failed to update the state file: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remember_keeps_first_capture() {
+        let dir = std::env::temp_dir().join(format!("acf-shutdown-{}", std::process::id()));
+        let store = StateStore::with_legacy_dir(dir.join("state.json"), dir.join("legacy"));
+        let before = Original {
+            governor: Some("schedutil".to_string()),
+            turbo: Some(true),
+            epp: None,
+            cores: vec![CorePolicy {
+                core: 2,
+                online: true,
+                governor: Some("performance".to_string()),
+                scaling_min_freq: Some(800_000),
+                scaling_max_freq: Some(3_600_000),
+            }],
+            rapl: Some(rapl::Limits { pl1: Some(28.0), pl2: Some(64.0) }),
+        };
+        let left_behind = Original { governor: Some("powersave".to_string()), turbo: Some(false), ..Default::default() };
+
+        remember_in(&store, before.clone()).unwrap();
+        // A restart after a crash must not take auto-cpufreq's own settings as the originals
+        remember_in(&store, left_behind).unwrap();
+        assert_eq!(store.load().original_settings, Some(before));
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Saved by a version without per-core policies
+        let old: Original = serde_json::from_str(r#"{"governor":"schedutil","turbo":true,"epp":null}"#).unwrap();
+        assert!(old.cores.is_empty());
+        // Saved before offline cores were captured too
+        let old: CorePolicy = serde_json::from_str(r#"{"core":1,"governor":"powersave","scaling_min_freq":null,"scaling_max_freq":null}"#).unwrap();
+        assert!(old.online);
+
+        assert!(restore_enabled(&Snapshot::default()));
+        assert!(!restore_enabled(&Snapshot::from_str_ini("[daemon]\nrestore_on_exit = no\n").unwrap()));
+    }
+}
@@ -0,0 +1,421 @@
+// src/state.rs
+//
+// Persistent daemon state (overrides, selected profiles) in a single JSON
+// file. Replaces the per-setting `*.pickle` files inherited from the Python
+// version.
+//
+// The state directory as a whole (state file, audit log, stats history,
+// telemetry counters, install manifest) carries a format version in its
+// `version` file. When a release changes how something in there is stored,
+// it adds a step to `MIGRATIONS`, and the first `StateStore` read or update
+// in a process upgrades the directory, one version at a time, instead of
+// later reads quietly ignoring files in the old format. A directory written
+// by a newer release is left alone. Formats that version themselves (the
+// history.bin header) still do.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Result, Context, bail};
+use nix::fcntl::{FlockArg, flock};
+use nix::unistd::{access, AccessFlags};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::event_code::EventCode;
+
+pub const STATE_DIR: &str = "/var/lib/auto-cpufreq";
+pub const STATE_FILE: &str = "/var/lib/auto-cpufreq/state.json";
+/// Format version of the state directory, written by the last migration
+pub const STATE_VERSION: u32 = 1;
+const VERSION_FILE: &str = "version";
+
+const LEGACY_DIR: &str = "/opt/auto-cpufreq";
+const LEGACY_GOVERNOR_OVERRIDE: &str = "override.pickle";
+const LEGACY_TURBO_OVERRIDE: &str = "turbo-override.pickle";
+
+lazy_static::lazy_static! {
+    /// State directories this process already brought up to date
+    static ref MIGRATED: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// "powersave" / "performance", unset means automatic
+    pub governor_override: Option<String>,
+    /// Unix time at which `governor_override` lapses, unset for a permanent override
+    pub governor_override_until: Option<i64>,
+    /// "never" / "always", unset means automatic
+    pub turbo_override: Option<String>,
+    pub turbo_override_until: Option<i64>,
+    /// Permanent overrides a timed one replaced, back once it lapses
+    pub governor_override_previous: Option<String>,
+    pub turbo_override_previous: Option<String>,
+    pub last_profile: Option<String>,
+    /// `[battery_profile.<name>]` whose charging thresholds are applied
+    pub battery_profile: Option<String>,
+    /// Battery calibration in progress (--calibrate-battery)
+    pub calibration: Option<crate::battery::calibration::Calibration>,
+    /// Unix time the last telemetry report was sent (opt-in, see telemetry.rs)
+    pub telemetry_sent: Option<i64>,
+    /// Set while --battery-saver is on, with what to restore
+    pub battery_saver: Option<crate::battery_saver::BatterySaver>,
+    /// Governor/turbo/EPP from before the daemon started, cleared on a clean exit
+    pub original_settings: Option<crate::shutdown::Original>,
+    /// Cores taken offline by `[cpu.core.N] online = false`, to bring back
+    pub parked_cores: Vec<usize>,
+}
+
+impl State {
+    /// Revert overrides whose TTL has run out to what they replaced
+    pub fn expire(&mut self, now: i64) {
+        if self.governor_override_until.is_some_and(|until| until <= now) {
+            self.governor_override = self.governor_override_previous.take();
+            self.governor_override_until = None;
+        }
+        if self.turbo_override_until.is_some_and(|until| until <= now) {
+            self.turbo_override = self.turbo_override_previous.take();
+            self.turbo_override_until = None;
+        }
+    }
+}
+
+pub struct StateStore {
+    path: PathBuf,
+    legacy_dir: PathBuf,
+}
+
+impl Default for StateStore {
+    fn default() -> Self {
+        Self::new(STATE_FILE)
+    }
+}
+
+/// Override files of the Python version, imported by the version 1 migration
+pub fn legacy_files() -> Vec<PathBuf> {
+    let dir = Path::new(LEGACY_DIR);
+    vec![dir.join(LEGACY_GOVERNOR_OVERRIDE), dir.join(LEGACY_TURBO_OVERRIDE)]
+}
+
+impl StateStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::with_legacy_dir(path, LEGACY_DIR)
+    }
+
+    pub fn with_legacy_dir(path: impl Into<PathBuf>, legacy_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            legacy_dir: legacy_dir.into(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Current state, with expired overrides already dropped. A missing or
+    /// unreadable file reads as the default state so callers without root (GUI,
+    /// `--get-state`) never fail here.
+    pub fn load(&self) -> State {
+        self.migrate_once();
+        let mut state = self.load_raw();
+        state.expire(chrono::Local::now().timestamp());
+        state
+    }
+
+    fn load_raw(&self) -> State {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(code = %EventCode::StateCorrupt, "Ignoring corrupt state file {}: {}", self.path.display(), e);
+                State::default()
+            }),
+            Err(_) => State::default(),
+        }
+    }
+
+    /// Read-modify-write. The file is replaced atomically so the daemon never
+    /// reads a partial write, and the whole update holds `lock` so the CLI and
+    /// the daemon can't each write back a state missing the other's change.
+    pub fn update(&self, f: impl FnOnce(&mut State)) -> Result<State> {
+        self.migrate_once();
+        let _lock = self.lock()?;
+        let mut state = self.load();
+        f(&mut state);
+        self.save(&state)?;
+        Ok(state)
+    }
+
+    /// Upgrade the state directory the first time this process uses it. Only
+    /// a process that can write there does; the others read it as it is until
+    /// the daemon (or the next root command) has upgraded it.
+    fn migrate_once(&self) {
+        let Some(dir) = self.path.parent() else {
+            return;
+        };
+        // Marked first: the migrations go through this store themselves
+        if !MIGRATED.lock().unwrap_or_else(|e| e.into_inner()).insert(dir.to_path_buf()) {
+            return;
+        }
+        if dir.exists() && access(dir, AccessFlags::W_OK).is_err() {
+            return;
+        }
+
+        match StateDir::with_legacy_dir(dir, &self.legacy_dir).migrate() {
+            Ok(done) => {
+                for description in done {
+                    info!("Upgraded the state directory: {}", description);
+                }
+            }
+            Err(e) => warn!(code = %EventCode::StateWriteFailed, "{:#}", e),
+        }
+    }
+
+    /// Exclusive flock on `<state file>.lock`, held until the file is dropped.
+    /// A separate file because `save` replaces the state file itself.
+    fn lock(&self) -> Result<File> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let path = self.path.with_extension("json.lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        flock(file.as_raw_fd(), FlockArg::LockExclusive).with_context(|| format!("Failed to lock {}", path.display()))?;
+        Ok(file)
+    }
+
+    pub fn save(&self, state: &State) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(state)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to replace {}", self.path.display()))
+    }
+
+    /// Import the old override files into the state file, without replacing
+    /// overrides set since. They are only removed once the state file is written.
+    fn import_legacy(&self) -> Result<()> {
+        let governor_path = self.legacy_dir.join(LEGACY_GOVERNOR_OVERRIDE);
+        let turbo_path = self.legacy_dir.join(LEGACY_TURBO_OVERRIDE);
+        if !governor_path.exists() && !turbo_path.exists() {
+            return Ok(());
+        }
+
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let governor = read(&governor_path).filter(|v| v == "powersave" || v == "performance");
+        let turbo = read(&turbo_path).filter(|v| v == "never" || v == "always");
+
+        self.update(|state| {
+            if state.governor_override.is_none() {
+                state.governor_override = governor;
+            }
+            if state.turbo_override.is_none() {
+                state.turbo_override = turbo;
+            }
+        })?;
+        let _ = fs::remove_file(&governor_path);
+        let _ = fs::remove_file(&turbo_path);
+        Ok(())
+    }
+}
+
+/// One step in upgrading the state directory
+pub struct Migration {
+    /// Version of the directory once this step is done
+    pub to: u32,
+    pub description: &'static str,
+    run: fn(&StateDir) -> Result<()>,
+}
+
+/// Every format change of the state directory, oldest first. Directories from
+/// before versioning are version 0.
+const MIGRATIONS: &[Migration] = &[Migration {
+    to: 1,
+    description: "import the override files of the Python version into state.json",
+    run: |dir| dir.store().import_legacy(),
+}];
+
+/// The state directory and the steps to bring it to `STATE_VERSION`
+pub struct StateDir {
+    dir: PathBuf,
+    legacy_dir: PathBuf,
+}
+
+impl Default for StateDir {
+    fn default() -> Self {
+        Self::with_legacy_dir(STATE_DIR, LEGACY_DIR)
+    }
+}
+
+impl StateDir {
+    pub fn with_legacy_dir(dir: impl Into<PathBuf>, legacy_dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), legacy_dir: legacy_dir.into() }
+    }
+
+    fn store(&self) -> StateStore {
+        StateStore::with_legacy_dir(self.dir.join("state.json"), &self.legacy_dir)
+    }
+
+    /// Format version of the directory, 0 when it has none yet. A version
+    /// file that can't be read or parsed is an error, not version 0, so the
+    /// migrations aren't run again over an upgraded directory.
+    pub fn version(&self) -> Result<u32> {
+        let path = self.dir.join(VERSION_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => content
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid state version in {}: {}", path.display(), content.trim())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Replaced atomically, a torn write would read as an invalid version
+    fn set_version(&self, version: u32) -> Result<()> {
+        fs::create_dir_all(&self.dir).with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.dir.join(VERSION_FILE);
+        let tmp = self.dir.join(format!("{}.tmp", VERSION_FILE));
+        fs::write(&tmp, format!("{}\n", version)).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {}", path.display()))
+    }
+
+    /// Run the migrations the directory hasn't had yet, returning what they
+    /// did. The version is written after each step, so a failed step is
+    /// retried next time without repeating the ones before it.
+    pub fn migrate(&self) -> Result<Vec<&'static str>> {
+        let version = self.version()?;
+        if version > STATE_VERSION {
+            bail!(
+                "{} is from a newer auto-cpufreq (state version {}, this one knows up to {}), leaving it as it is",
+                self.dir.display(),
+                version,
+                STATE_VERSION
+            );
+        }
+
+        let mut done = Vec::new();
+        for migration in MIGRATIONS.iter().filter(|m| m.to > version) {
+            (migration.run)(self).with_context(|| {
+                format!("Failed to upgrade {} to state version {} ({})", self.dir.display(), migration.to, migration.description)
+            })?;
+            self.set_version(migration.to)?;
+            done.push(migration.description);
+        }
+        Ok(done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("acf-state-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_update_roundtrip() {
+        let dir = temp_dir("roundtrip");
+        let store = StateStore::with_legacy_dir(dir.join("lib/state.json"), dir.join("legacy"));
+
+        assert_eq!(store.load(), State::default());
+        store.update(|s| s.turbo_override = Some("never".into())).unwrap();
+        assert_eq!(store.load().turbo_override.as_deref(), Some("never"));
+        assert_eq!(store.load().governor_override, None);
+
+        // Concurrent updates don't lose each other's changes
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let store = StateStore::with_legacy_dir(store.path(), dir.join("legacy"));
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        store.update(|s| s.parked_cores.push(thread * 10 + i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+        assert_eq!(store.load().parked_cores.len(), 40);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut state = State {
+            governor_override: Some("performance".into()),
+            governor_override_until: Some(100),
+            turbo_override: Some("always".into()),
+            turbo_override_until: Some(200),
+            turbo_override_previous: Some("never".into()),
+            ..State::default()
+        };
+
+        state.expire(99);
+        assert_eq!(state.governor_override.as_deref(), Some("performance"));
+
+        state.expire(100);
+        assert_eq!(state.governor_override, None);
+        assert_eq!(state.governor_override_until, None);
+        assert_eq!(state.turbo_override.as_deref(), Some("always"));
+
+        // Back to the permanent override the timed one replaced
+        state.expire(200);
+        assert_eq!(state.turbo_override.as_deref(), Some("never"));
+        assert_eq!(state.turbo_override_previous, None);
+    }
+
+    #[test]
+    fn test_migrates_legacy_files() {
+        let dir = temp_dir("migrate");
+        fs::write(dir.join(LEGACY_GOVERNOR_OVERRIDE), "performance").unwrap();
+        fs::write(dir.join(LEGACY_TURBO_OVERRIDE), "always\n").unwrap();
+        let state_dir = StateDir::with_legacy_dir(dir.join("lib"), &dir);
+        let store = state_dir.store();
+        // Set after the upgrade but before anything read the state: not replaced
+        store.save(&State { turbo_override: Some("never".into()), ..State::default() }).unwrap();
+        assert_eq!(state_dir.version().unwrap(), 0);
+
+        // The first read upgrades the directory
+        let state = store.load();
+        assert_eq!(state.governor_override.as_deref(), Some("performance"));
+        assert_eq!(state.turbo_override.as_deref(), Some("never"));
+        assert!(!dir.join(LEGACY_GOVERNOR_OVERRIDE).exists());
+        assert!(!dir.join(LEGACY_TURBO_OVERRIDE).exists());
+        assert_eq!(state_dir.version().unwrap(), STATE_VERSION);
+        assert!(state_dir.migrate().unwrap().is_empty());
+
+        fs::write(dir.join("lib").join(VERSION_FILE), format!("{}\n", STATE_VERSION + 1)).unwrap();
+        assert!(state_dir.migrate().unwrap_err().to_string().contains("newer auto-cpufreq"));
+
+        // Unreadable isn't the same as unversioned
+        fs::remove_file(dir.join("lib").join(VERSION_FILE)).unwrap();
+        fs::create_dir(dir.join("lib").join(VERSION_FILE)).unwrap();
+        assert!(state_dir.version().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
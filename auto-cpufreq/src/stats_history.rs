@@ -0,0 +1,388 @@
+// src/stats_history.rs
+//
+// History of the daemon's samples (average frequency, temperature, usage,
+// load, power draw, governor, AC, turbo, battery level), one per stats file
+// update, for `auto-cpufreq --history`. Stored as a fixed-size ring buffer of 32-byte
+// records behind a small header, so the file never grows past about 2 MiB and
+// a sample is written in place without rewriting the rest:
+//
+//   header: "ACFH" | version u8 | 3 reserved | capacity u32 | written u64
+//   record: time i64 | frequency f32 | temperature f32 | usage f32 | load f32
+//           | power f32 | governor u8 | flags u8 | battery u8 | reserved
+//
+// Numbers are little endian; a missing temperature or power is NaN, a missing
+// battery level 255.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::modules::system_info::SystemReport;
+
+pub const HISTORY_FILE: &str = "/var/lib/auto-cpufreq/history.bin";
+/// Samples kept: a week at one per 10 s, longer when the daemon polls slower
+const CAPACITY: u32 = 65_536;
+
+const MAGIC: &[u8; 4] = b"ACFH";
+const VERSION: u8 = 1;
+const HEADER_LEN: u64 = 20;
+const RECORD_LEN: u64 = 32;
+
+/// Governor names by their index in a record; 0 is anything else
+const GOVERNORS: &[&str] = &["other", "performance", "powersave", "schedutil", "ondemand", "conservative", "userspace"];
+
+const FLAG_AC: u8 = 1;
+const FLAG_TURBO: u8 = 2;
+const FLAG_TURBO_KNOWN: u8 = 4;
+
+/// Width of the --history sparklines
+const TIMELINE_WIDTH: usize = 60;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Governor periods listed under the timelines
+const MAX_PERIODS: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    /// Unix time
+    pub time: i64,
+    /// Average over the cores, MHz
+    pub frequency: f32,
+    /// Average core temperature, °C
+    pub temperature: Option<f32>,
+    pub cpu_usage: f32,
+    pub load: f32,
+    /// Battery charge/discharge power, W
+    pub power: Option<f32>,
+    pub governor: String,
+    pub ac: bool,
+    pub turbo: Option<bool>,
+    pub battery: Option<u8>,
+}
+
+fn average(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+fn optional(value: f32) -> Option<f32> {
+    (!value.is_nan()).then_some(value)
+}
+
+impl Sample {
+    pub fn from_report(report: &SystemReport, time: i64) -> Self {
+        Self {
+            time,
+            frequency: average(report.cores_info.iter().map(|c| c.frequency)).unwrap_or(0.0),
+            temperature: average(report.cores_info.iter().filter_map(|c| c.temperature)),
+            cpu_usage: report.cpu_usage,
+            load: report.load,
+            power: report.battery_info.power_consumption,
+            governor: report.current_gov.clone().unwrap_or_else(|| "other".to_string()),
+            ac: report.battery_info.is_ac_plugged.unwrap_or(false),
+            turbo: report.is_turbo_on.0,
+            battery: report.battery_info.battery_level,
+        }
+    }
+
+    fn encode(&self) -> [u8; RECORD_LEN as usize] {
+        let mut record = [0u8; RECORD_LEN as usize];
+        record[0..8].copy_from_slice(&self.time.to_le_bytes());
+        for (i, value) in [
+            self.frequency,
+            self.temperature.unwrap_or(f32::NAN),
+            self.cpu_usage,
+            self.load,
+            self.power.unwrap_or(f32::NAN),
+        ]
+        .iter()
+        .enumerate()
+        {
+            record[8 + i * 4..12 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+        record[28] = GOVERNORS.iter().position(|g| *g == self.governor).unwrap_or(0) as u8;
+        record[29] = match self.turbo {
+            Some(true) => FLAG_TURBO | FLAG_TURBO_KNOWN,
+            Some(false) => FLAG_TURBO_KNOWN,
+            None => 0,
+        } | if self.ac { FLAG_AC } else { 0 };
+        record[30] = self.battery.unwrap_or(u8::MAX);
+        record
+    }
+
+    fn decode(record: &[u8]) -> Self {
+        let f32_at = |offset: usize| f32::from_le_bytes(record[offset..offset + 4].try_into().unwrap());
+        let flags = record[29];
+        Self {
+            time: i64::from_le_bytes(record[0..8].try_into().unwrap()),
+            frequency: f32_at(8),
+            temperature: optional(f32_at(12)),
+            cpu_usage: f32_at(16),
+            load: f32_at(20),
+            power: optional(f32_at(24)),
+            governor: GOVERNORS.get(record[28] as usize).unwrap_or(&GOVERNORS[0]).to_string(),
+            ac: flags & FLAG_AC != 0,
+            turbo: (flags & FLAG_TURBO_KNOWN != 0).then_some(flags & FLAG_TURBO != 0),
+            battery: (record[30] != u8::MAX).then_some(record[30]),
+        }
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+    capacity: u32,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(HISTORY_FILE, CAPACITY)
+    }
+}
+
+/// Capacity and number of samples ever written, from a valid header
+fn parse_header(header: &[u8]) -> Option<(u32, u64)> {
+    if header.len() < HEADER_LEN as usize || &header[0..4] != MAGIC || header[4] != VERSION {
+        return None;
+    }
+    let capacity = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    let written = u64::from_le_bytes(header[12..20].try_into().unwrap());
+    Some((capacity, written))
+}
+
+fn write_header(file: &mut File, capacity: u32, written: u64) -> std::io::Result<()> {
+    let mut header = [0u8; HEADER_LEN as usize];
+    header[0..4].copy_from_slice(MAGIC);
+    header[4] = VERSION;
+    header[8..12].copy_from_slice(&capacity.to_le_bytes());
+    header[12..20].copy_from_slice(&written.to_le_bytes());
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header)
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>, capacity: u32) -> Self {
+        Self { path: path.into(), capacity }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, sample: &Sample) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+
+        let mut header = [0u8; HEADER_LEN as usize];
+        let existing = file.read_exact(&mut header).ok().and_then(|_| parse_header(&header));
+        // A file from another version or capacity starts over
+        let written = match existing {
+            Some((capacity, written)) if capacity == self.capacity => written,
+            _ => {
+                file.set_len(0)?;
+                0
+            }
+        };
+
+        let slot = written % self.capacity as u64;
+        file.seek(SeekFrom::Start(HEADER_LEN + slot * RECORD_LEN))?;
+        file.write_all(&sample.encode())?;
+        write_header(&mut file, self.capacity, written + 1)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Samples from `since` (Unix time) on, oldest first
+    pub fn samples(&self, since: Option<i64>) -> Vec<Sample> {
+        let Ok(data) = fs::read(&self.path) else {
+            return Vec::new();
+        };
+        let Some((capacity, written)) = parse_header(&data) else {
+            return Vec::new();
+        };
+        let capacity = capacity as u64;
+        let count = written.min(capacity);
+        let first = if written > capacity { written % capacity } else { 0 };
+
+        (0..count)
+            .filter_map(|i| {
+                let offset = (HEADER_LEN + (first + i) % capacity * RECORD_LEN) as usize;
+                data.get(offset..offset + RECORD_LEN as usize).map(Sample::decode)
+            })
+            .filter(|s| since.is_none_or(|since| s.time >= since))
+            .collect()
+    }
+}
+
+pub(crate) fn local_time(time: i64, format: &str) -> String {
+    DateTime::from_timestamp(time, 0)
+        .map(|t| t.with_timezone(&Local).format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// One spark per value scaled between the smallest and largest, blank for gaps
+fn sparkline(values: &[Option<f32>]) -> String {
+    let present = || values.iter().flatten().copied();
+    let min = present().fold(f32::INFINITY, f32::min);
+    let max = present().fold(f32::NEG_INFINITY, f32::max);
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if max <= min => SPARKS[0],
+            Some(v) => SPARKS[(((v - min) / (max - min)) * (SPARKS.len() - 1) as f32).round() as usize],
+        })
+        .collect()
+}
+
+/// Split `samples` into `width` equal spans of time
+pub(crate) fn buckets(samples: &[Sample], width: usize) -> Vec<&[Sample]> {
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        return Vec::new();
+    };
+    let span = (last.time - first.time).max(1) as f64;
+    let mut buckets = vec![&samples[0..0]; width];
+    let mut start = 0;
+    for (index, bucket) in buckets.iter_mut().enumerate() {
+        let end_time = first.time as f64 + span * (index + 1) as f64 / width as f64;
+        let end = start + samples[start..].iter().take_while(|s| s.time as f64 <= end_time || index == width - 1).count();
+        *bucket = &samples[start..end];
+        start = end;
+    }
+    buckets
+}
+
+/// Runs of the same governor as (start, end, governor)
+fn governor_periods(samples: &[Sample]) -> Vec<(i64, i64, &str)> {
+    let mut periods: Vec<(i64, i64, &str)> = Vec::new();
+    for sample in samples {
+        match periods.last_mut() {
+            Some(period) if period.2 == sample.governor => period.1 = sample.time,
+            _ => periods.push((sample.time, sample.time, &sample.governor)),
+        }
+    }
+    periods
+}
+
+type Reading = fn(&Sample) -> Option<f32>;
+
+/// The --history text view
+pub fn timeline(samples: &[Sample]) -> String {
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        return "No history recorded yet, it is written by the running daemon".to_string();
+    };
+
+    let buckets = buckets(samples, TIMELINE_WIDTH);
+    let series = |value: Reading| -> Vec<Option<f32>> {
+        buckets.iter().map(|bucket| average(bucket.iter().filter_map(value))).collect()
+    };
+    let range = |values: &[Option<f32>]| {
+        let present = || values.iter().flatten().copied();
+        (present().fold(f32::INFINITY, f32::min), present().fold(f32::NEG_INFINITY, f32::max))
+    };
+
+    let mut text = format!(
+        "History from {} to {} ({} samples)\n\n",
+        local_time(first.time, "%Y-%m-%d %H:%M"),
+        local_time(last.time, "%H:%M"),
+        samples.len()
+    );
+    let rows: [(&str, Reading, &str); 3] = [
+        ("Frequency", |s| Some(s.frequency), "MHz"),
+        ("Temperature", |s| s.temperature, "°C"),
+        ("CPU usage", |s| Some(s.cpu_usage), "%"),
+    ];
+    for (label, value, unit) in rows {
+        let values = series(value);
+        if values.iter().all(Option::is_none) {
+            continue;
+        }
+        let (min, max) = range(&values);
+        text.push_str(&format!("{:<12} {}  {:.0} - {:.0} {}\n", label, sparkline(&values), min, max, unit));
+    }
+
+    text.push_str("\nGovernor\n");
+    let periods = governor_periods(samples);
+    for (start, end, governor) in &periods[periods.len().saturating_sub(MAX_PERIODS)..] {
+        text.push_str(&format!("  {} - {}  {}\n", local_time(*start, "%H:%M"), local_time(*end, "%H:%M"), governor));
+    }
+    text
+}
+
+/// `--history --csv`
+pub fn to_csv(samples: &[Sample]) -> String {
+    let optional = |value: Option<f32>, precision: usize| value.map(|v| format!("{:.*}", precision, v)).unwrap_or_default();
+    let mut csv = String::from("time,frequency_mhz,temperature_c,cpu_usage,load,power_w,governor,ac,turbo,battery_percent\n");
+    for s in samples {
+        csv.push_str(&format!(
+            "{},{:.0},{},{:.1},{:.2},{},{},{},{},{}\n",
+            local_time(s.time, "%Y-%m-%dT%H:%M:%S%z"),
+            s.frequency,
+            optional(s.temperature, 1),
+            s.cpu_usage,
+            s.load,
+            optional(s.power, 2),
+            s.governor,
+            s.ac,
+            s.turbo.map(|t| t.to_string()).unwrap_or_default(),
+            s.battery.map(|b| b.to_string()).unwrap_or_default(),
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: i64, governor: &str) -> Sample {
+        Sample {
+            time,
+            frequency: 1000.0 + time as f32 * 100.0,
+            temperature: (time % 2 == 0).then_some(50.0),
+            cpu_usage: 10.0,
+            load: 0.5,
+            power: None,
+            governor: governor.to_string(),
+            ac: time % 3 == 0,
+            turbo: Some(true),
+            battery: Some(80),
+        }
+    }
+
+    #[test]
+    fn test_history_ring_buffer() {
+        let dir = std::env::temp_dir().join(format!("acf-history-{}", std::process::id()));
+        let history = History::new(dir.join("history.bin"), 4);
+        assert!(history.samples(None).is_empty());
+
+        for time in 0..6 {
+            history.record(&sample(time, if time < 4 { "powersave" } else { "performance" })).unwrap();
+        }
+        assert_eq!(fs::metadata(history.path()).unwrap().len(), HEADER_LEN + 4 * RECORD_LEN);
+        let samples = history.samples(None);
+        assert_eq!(samples.iter().map(|s| s.time).collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert_eq!(samples[0], sample(2, "powersave"));
+        assert_eq!(history.samples(Some(4)).len(), 2);
+        assert_eq!(governor_periods(&samples), vec![(2, 3, "powersave"), (4, 5, "performance")]);
+
+        // A different capacity starts a new file
+        History::new(history.path(), 8).record(&sample(9, "schedutil")).unwrap();
+        assert_eq!(History::new(history.path(), 8).samples(None).len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(sparkline(&[Some(0.0), None, Some(5.0), Some(10.0)]), "▁ ▅█");
+        assert_eq!(buckets(&samples, 2).iter().map(|b| b.len()).collect::<Vec<_>>(), vec![2, 2]);
+        let csv = to_csv(&samples[..1]);
+        assert!(csv.lines().nth(1).unwrap().ends_with(",1200,50.0,10.0,0.50,,powersave,false,true,80"));
+    }
+}
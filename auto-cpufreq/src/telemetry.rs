@@ -0,0 +1,213 @@
+// src/telemetry.rs
+//
+// Opt-in hardware support ping (`[telemetry] enabled = true`, off by default).
+// The report holds no identifiers and no config values: CPU model, scaling
+// driver, kernel release, the battery backends in use and how often each key
+// operation (governor, EPP/EPB, platform profile, thresholds, ...) succeeded or
+// failed. The daemon sends it at most once a week to `endpoint`. Operation
+// counts are kept in a local file whether or not sending is enabled, and both
+// the report the daemon sends and `--telemetry-preview` are built from that
+// file by `Report::collect`, so the preview shows exactly what would be sent.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::{CONFIG, Snapshot, parse_bool};
+use crate::event_code::EventCode;
+use crate::modules::system_info::SystemInfo;
+use crate::state::StateStore;
+
+pub const OPERATIONS_FILE: &str = "/var/lib/auto-cpufreq/telemetry.json";
+/// Minimum time between two reports (a week)
+const SEND_INTERVAL: i64 = 7 * 24 * 3600;
+/// Longest the operations file lags behind the daemon's counts
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub version: String,
+    pub cpu_model: String,
+    pub architecture: String,
+    pub scaling_driver: Option<String>,
+    pub kernel: String,
+    pub battery_backends: Vec<String>,
+    pub operations: BTreeMap<String, Outcome>,
+}
+
+/// This process's counts and when they were last written out
+struct Operations {
+    counts: BTreeMap<String, Outcome>,
+    written: Option<Instant>,
+    dirty: bool,
+}
+
+impl Operations {
+    fn write(&mut self) {
+        if let Ok(json) = serde_json::to_string_pretty(&self.counts) {
+            let _ = fs::write(OPERATIONS_FILE, json);
+        }
+        self.written = Some(Instant::now());
+        self.dirty = false;
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref OPERATIONS: Mutex<Option<Operations>> = Mutex::new(None);
+}
+
+fn load_operations(path: &Path) -> BTreeMap<String, Outcome> {
+    fs::read_to_string(path).ok().and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+/// Write out counts not written yet
+fn flush() {
+    let mut operations = OPERATIONS.lock().unwrap();
+    if let Some(operations) = operations.as_mut().filter(|o| o.dirty) {
+        operations.write();
+    }
+}
+
+/// `[telemetry] enabled` and `endpoint`; `None` unless both are set
+pub fn endpoint(config: &Snapshot) -> Option<String> {
+    let enabled = config.get("telemetry", "enabled").and_then(parse_bool).unwrap_or(false);
+    let endpoint = config.get("telemetry", "endpoint").map(str::trim).filter(|e| !e.is_empty());
+    endpoint.filter(|_| enabled).map(String::from)
+}
+
+/// Count the outcome of `operation` and hand the result back, so calls can
+/// be wrapped in place: `if let Err(e) = telemetry::track("rapl", apply())`
+pub fn track<T, E>(operation: &str, result: Result<T, E>) -> Result<T, E> {
+    let mut operations = OPERATIONS.lock().unwrap();
+    let operations = operations.get_or_insert_with(|| Operations {
+        counts: load_operations(Path::new(OPERATIONS_FILE)),
+        written: None,
+        dirty: false,
+    });
+
+    let outcome = operations.counts.entry(operation.to_string()).or_default();
+    // Written right away when an operation is first seen or starts failing,
+    // otherwise at most every FLUSH_INTERVAL rather than every cycle
+    let news = outcome.succeeded + outcome.failed == 0 || (result.is_err() && outcome.failed == 0);
+    match result {
+        Ok(_) => outcome.succeeded += 1,
+        Err(_) => outcome.failed += 1,
+    }
+    operations.dirty = true;
+    if news || operations.written.is_none_or(|written| written.elapsed() >= FLUSH_INTERVAL) {
+        operations.write();
+    }
+    result
+}
+
+impl Report {
+    /// The report as of the operations file, for sending and for the preview
+    pub fn collect() -> Self {
+        let info = SystemInfo::new();
+        let operations = load_operations(Path::new(OPERATIONS_FILE));
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            cpu_model: info.processor_model,
+            architecture: info.architecture,
+            scaling_driver: info.cpu_driver,
+            kernel: info.kernel_version,
+            battery_backends: crate::battery::LaptopModule::detect().iter().map(|m| m.name().to_string()).collect(),
+            operations,
+        }
+    }
+}
+
+/// `--telemetry-preview`: the report as it would be sent, and whether it will be
+pub fn preview() -> Result<()> {
+    let report = Report::collect();
+    match endpoint(&CONFIG.snapshot()) {
+        Some(endpoint) => println!("Telemetry is enabled, the daemon sends this weekly to {}:\n", endpoint),
+        None => println!("Telemetry is disabled, nothing is sent. With enabled = true and an endpoint in [telemetry], this would be sent:\n"),
+    }
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+#[cfg(feature = "telemetry")]
+fn send(endpoint: &str, report: &Report) -> Result<()> {
+    use anyhow::Context;
+
+    let response = reqwest::blocking::Client::new()
+        .post(endpoint)
+        .header("User-Agent", "auto-cpufreq-rust")
+        .json(report)
+        .send()
+        .with_context(|| format!("Failed to reach {}", endpoint))?;
+    if !response.status().is_success() {
+        bail!("Telemetry endpoint answered {}", response.status());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "telemetry"))]
+fn send(_endpoint: &str, _report: &Report) -> Result<()> {
+    bail!("Telemetry is not available in this build (enable the \"telemetry\" feature)");
+}
+
+/// Whether a report is due `now`, given when the last one was sent
+fn due(last_sent: Option<i64>, now: i64) -> bool {
+    last_sent.is_none_or(|sent| now - sent >= SEND_INTERVAL)
+}
+
+/// Called by the daemon every cycle: send the report in the background when
+/// telemetry is enabled and the last one is a week old
+pub fn maybe_send() {
+    let Some(endpoint) = endpoint(&CONFIG.snapshot()) else {
+        return;
+    };
+    let store = StateStore::default();
+    let now = chrono::Local::now().timestamp();
+    if !due(store.load().telemetry_sent, now) {
+        return;
+    }
+    // Marked up front so a failing endpoint isn't retried every cycle
+    if let Err(e) = store.update(|s| s.telemetry_sent = Some(now)) {
+        warn!(code = %EventCode::StateWriteFailed, "Failed to save telemetry state: {}", e);
+        return;
+    }
+
+    flush();
+    std::thread::spawn(move || {
+        if let Err(e) = send(&endpoint, &Report::collect()) {
+            warn!(code = %EventCode::TelemetryFailed, "Failed to send telemetry: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telemetry_settings() {
+        let config = |ini: &str| Snapshot::from_str_ini(ini).unwrap();
+        assert_eq!(endpoint(&config("[telemetry]\nendpoint = https://example.org/report\n")), None);
+        assert_eq!(endpoint(&config("[telemetry]\nenabled = true\n")), None);
+        assert_eq!(
+            endpoint(&config("[telemetry]\nenabled = yes\nendpoint = https://example.org/report\n")).as_deref(),
+            Some("https://example.org/report")
+        );
+
+        assert!(due(None, 0));
+        assert!(!due(Some(100), 100 + SEND_INTERVAL - 1));
+        assert!(due(Some(100), 100 + SEND_INTERVAL));
+    }
+}
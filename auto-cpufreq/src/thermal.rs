@@ -0,0 +1,244 @@
+// src/thermal.rs
+//
+// Thermal limits learned from the ACPI/platform thermal zones. The firmware's
+// passive trip point is where it starts throttling on its own, so turbo is
+// backed off a few degrees below it instead of at a fixed temperature that is
+// too low for some machines and too high for others. Skin/chassis sensors,
+// where the platform has them, cap turbo on their own limit.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::CONFIG;
+use crate::event_code::EventCode;
+
+const THERMAL_DIR: &str = "/sys/class/thermal";
+
+/// Used when no zone exposes a usable trip point
+pub const DEFAULT_TURBO_TEMP_LIMIT: f32 = 75.0;
+/// Degrees below the passive trip point at which turbo is turned off
+const PASSIVE_MARGIN: f32 = 10.0;
+/// Degrees below the critical trip point, when there is no passive one
+const CRITICAL_MARGIN: f32 = 25.0;
+
+/// Zone types that track the CPU/SoC rather than e.g. the battery or wifi
+const CPU_ZONE_TYPES: &[&str] = &["x86_pkg_temp", "acpitz", "cpu", "soc", "tcpu", "k10temp", "zenpower"];
+/// Zone types of surface/chassis sensors (what a lap actually feels)
+const SKIN_ZONE_TYPES: &[&str] = &["skin", "chassis", "tskn", "tskin"];
+
+/// Skin temperature (°C) above which turbo is turned off, whatever the core temperature
+pub const DEFAULT_SKIN_TEMP_LIMIT: f32 = 45.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TripPoint {
+    /// passive, active, hot or critical
+    pub kind: String,
+    /// °C
+    pub temp: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThermalZone {
+    pub name: String,
+    pub kind: String,
+    pub trips: Vec<TripPoint>,
+}
+
+impl ThermalZone {
+    fn is_cpu(&self) -> bool {
+        let kind = self.kind.to_lowercase();
+        CPU_ZONE_TYPES.iter().any(|t| kind.contains(t))
+    }
+
+    fn is_skin(&self) -> bool {
+        let kind = self.kind.to_lowercase();
+        SKIN_ZONE_TYPES.iter().any(|t| kind.contains(t))
+    }
+
+    fn lowest_trip(&self, kind: &str) -> Option<f32> {
+        self.trips
+            .iter()
+            .filter(|t| t.kind == kind)
+            .map(|t| t.temp)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_millidegrees(path: &Path) -> Option<f32> {
+    read_trimmed(path)?
+        .parse::<i64>()
+        .ok()
+        .map(|millideg| millideg as f32 / 1000.0)
+}
+
+pub fn parse_zone(dir: &Path) -> Option<ThermalZone> {
+    let name = dir.file_name()?.to_str()?.to_string();
+    let kind = read_trimmed(&dir.join("type"))?;
+
+    let mut trips = Vec::new();
+    for index in 0.. {
+        let Some(trip_kind) = read_trimmed(&dir.join(format!("trip_point_{}_type", index))) else {
+            break;
+        };
+        let temp = read_millidegrees(&dir.join(format!("trip_point_{}_temp", index)));
+
+        // Disabled trips read as 0 or huge sentinel values
+        if let Some(temp) = temp.filter(|t| *t > 20.0 && *t < 150.0) {
+            trips.push(TripPoint { kind: trip_kind, temp });
+        }
+    }
+
+    Some(ThermalZone { name, kind, trips })
+}
+
+pub fn zones() -> Vec<ThermalZone> {
+    let mut zones: Vec<ThermalZone> = fs::read_dir(THERMAL_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+                .filter_map(|e| parse_zone(&e.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    zones.sort_by(|a, b| a.name.cmp(&b.name));
+    zones
+}
+
+/// Turbo temperature limit derived from trip points: a margin below the lowest
+/// passive trip of a CPU zone, else below the lowest critical trip
+pub fn derive_turbo_limit(zones: &[ThermalZone]) -> Option<f32> {
+    let cpu_zones = || zones.iter().filter(|z| z.is_cpu());
+
+    let passive = cpu_zones()
+        .filter_map(|z| z.lowest_trip("passive"))
+        .min_by(|a, b| a.total_cmp(b));
+    if let Some(passive) = passive {
+        return Some(passive - PASSIVE_MARGIN);
+    }
+
+    cpu_zones()
+        .filter_map(|z| z.lowest_trip("critical"))
+        .min_by(|a, b| a.total_cmp(b))
+        .map(|critical| critical - CRITICAL_MARGIN)
+}
+
+lazy_static::lazy_static! {
+    /// Trip points are fixed by firmware, read them once
+    static ref PLATFORM_TURBO_LIMIT: Option<f32> = derive_turbo_limit(&zones());
+}
+
+/// Temperature (°C) above which turbo is turned off. `[thermal] turbo_temp_limit`
+/// wins over the value derived from trip points.
+pub fn turbo_temp_limit() -> f32 {
+    let configured = CONFIG.get("thermal", "turbo_temp_limit", "");
+    if !configured.is_empty() {
+        match configured.trim().parse::<f32>() {
+            Ok(limit) if limit > 0.0 => return limit,
+            _ => warn!(code = %EventCode::ConfigInvalid, "Invalid turbo_temp_limit in [thermal]: {}", configured),
+        }
+    }
+
+    PLATFORM_TURBO_LIMIT.unwrap_or(DEFAULT_TURBO_TEMP_LIMIT)
+}
+
+lazy_static::lazy_static! {
+    /// `temp` files of the skin/chassis zones, if the platform has any
+    static ref SKIN_SENSORS: Vec<std::path::PathBuf> = zones()
+        .iter()
+        .filter(|z| z.is_skin())
+        .map(|z| Path::new(THERMAL_DIR).join(&z.name).join("temp"))
+        .collect();
+}
+
+/// Hottest skin/chassis sensor reading, `None` when there is no such sensor
+pub fn skin_temp() -> Option<f32> {
+    SKIN_SENSORS
+        .iter()
+        .filter_map(|path| read_millidegrees(path))
+        .filter(|t| *t > 0.0)
+        .max_by(|a, b| a.total_cmp(b))
+}
+
+/// `[thermal] skin_temp_limit`, default 45 °C
+pub fn skin_temp_limit() -> f32 {
+    let configured = CONFIG.get("thermal", "skin_temp_limit", "");
+    if !configured.is_empty() {
+        match configured.trim().parse::<f32>() {
+            Ok(limit) if limit > 0.0 => return limit,
+            _ => warn!(code = %EventCode::ConfigInvalid, "Invalid skin_temp_limit in [thermal]: {}", configured),
+        }
+    }
+
+    DEFAULT_SKIN_TEMP_LIMIT
+}
+
+/// True when a skin sensor is at or above the limit. Core temperatures can be
+/// fine while the chassis under a palm rest or on a lap is uncomfortably hot.
+pub fn skin_too_hot() -> bool {
+    skin_temp().is_some_and(|temp| temp >= skin_temp_limit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(kind: &str, trips: &[(&str, f32)]) -> ThermalZone {
+        ThermalZone {
+            name: "thermal_zone0".into(),
+            kind: kind.into(),
+            trips: trips
+                .iter()
+                .map(|(k, t)| TripPoint { kind: k.to_string(), temp: *t })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_derive_turbo_limit() {
+        let zones = vec![
+            zone("iwlwifi_1", &[("passive", 50.0)]),
+            zone("acpitz", &[("critical", 110.0)]),
+            zone("x86_pkg_temp", &[("passive", 95.0), ("critical", 100.0)]),
+        ];
+        assert_eq!(derive_turbo_limit(&zones), Some(85.0));
+
+        let zones = vec![zone("acpitz", &[("critical", 110.0)])];
+        assert_eq!(derive_turbo_limit(&zones), Some(85.0));
+
+        assert_eq!(derive_turbo_limit(&[zone("battery", &[("critical", 60.0)])]), None);
+    }
+
+    #[test]
+    fn test_skin_zone_detection() {
+        assert!(zone("SKIN", &[]).is_skin());
+        assert!(zone("TSKN", &[]).is_skin());
+        assert!(zone("chassis-therm", &[]).is_skin());
+        assert!(!zone("x86_pkg_temp", &[]).is_skin());
+        assert!(!zone("skin", &[]).is_cpu());
+    }
+
+    #[test]
+    fn test_parse_zone() {
+        let dir = std::env::temp_dir().join(format!("thermal_zone_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("type"), "x86_pkg_temp\n").unwrap();
+        fs::write(dir.join("trip_point_0_type"), "passive\n").unwrap();
+        fs::write(dir.join("trip_point_0_temp"), "97000\n").unwrap();
+        fs::write(dir.join("trip_point_1_type"), "critical\n").unwrap();
+        fs::write(dir.join("trip_point_1_temp"), "0\n").unwrap();
+
+        let zone = parse_zone(&dir).unwrap();
+        assert_eq!(zone.kind, "x86_pkg_temp");
+        assert_eq!(zone.trips, vec![TripPoint { kind: "passive".into(), temp: 97.0 }]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,27 +1,85 @@
 // src/tlp_stat_parser.rs
+//
+// Parser for `tlp-stat` output. The report is split in `+++ <name>` sections
+// (TLP Status, Processor, Battery Care, USB, ...) of `key = value` lines;
+// `tlp-stat -c` adds the configured settings as `<file> L<line>: KEY="value"`.
+// `conflicts` maps those settings to the parts of the system auto-cpufreq
+// manages as well.
 
 use std::collections::HashMap;
 
+use crate::config::Snapshot;
+
+/// One `KEY="value"` line from the "Configured Settings" section
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlpSetting {
+    pub key: String,
+    pub value: String,
+    /// File and line it was set in, e.g. "/etc/tlp.conf L0031"
+    pub source: String,
+}
+
+/// A TLP setting that fights over something auto-cpufreq also sets
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlpConflict {
+    pub setting: TlpSetting,
+    /// What both of them manage, e.g. "CPU governor"
+    pub area: &'static str,
+}
+
 pub struct TLPStatusParser {
     data: HashMap<String, String>,
+    sections: HashMap<String, Vec<(String, String)>>,
+    settings: Vec<TlpSetting>,
+}
+
+/// `<file> L<nnnn>: KEY="value"`
+fn parse_setting(line: &str) -> Option<TlpSetting> {
+    let (location, assignment) = line.split_once(": ")?;
+    let (_, line_number) = location.rsplit_once(' ')?;
+    let digits = line_number.strip_prefix('L')?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let (key, value) = assignment.split_once('=')?;
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') {
+        return None;
+    }
+
+    Some(TlpSetting {
+        key: key.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        source: location.trim().to_string(),
+    })
 }
 
 impl TLPStatusParser {
     pub fn new(tlp_stat_output: &str) -> Self {
         let mut parser = Self {
             data: HashMap::new(),
+            sections: HashMap::new(),
+            settings: Vec::new(),
         };
         parser.parse(tlp_stat_output);
         parser
     }
 
     fn parse(&mut self, data: &str) {
+        let mut section = String::new();
+
         for line in data.lines() {
-            if let Some((key, val)) = line.split_once('=') {
-                self.data.insert(
-                    key.trim().to_lowercase(),
-                    val.trim().to_string(),
-                );
+            if let Some(name) = line.strip_prefix("+++ ") {
+                section = name.trim().trim_end_matches(':').to_lowercase();
+                continue;
+            }
+
+            if let Some(setting) = parse_setting(line) {
+                self.settings.push(setting);
+            } else if let Some((key, val)) = line.split_once('=') {
+                let (key, val) = (key.trim().to_lowercase(), val.trim().to_string());
+                self.sections.entry(section.clone()).or_default().push((key.clone(), val.clone()));
+                self.data.insert(key, val);
             }
         }
     }
@@ -33,17 +91,85 @@ impl TLPStatusParser {
     pub fn is_enabled(&self) -> bool {
         self.get_key("state") == "enabled"
     }
+
+    /// `key = value` pairs of a `+++` section, by its lowercased name (e.g.
+    /// "processor", "battery care", "usb"); keys are lowercased too
+    pub fn section(&self, name: &str) -> Option<&[(String, String)]> {
+        self.sections.get(&name.to_lowercase()).map(Vec::as_slice)
+    }
+
+    /// Settings listed by `tlp-stat -c`
+    pub fn settings(&self) -> &[TlpSetting] {
+        &self.settings
+    }
+
+    /// Configured TLP settings that overlap with what auto-cpufreq manages
+    /// under `config`. Charge thresholds and platform profile only count when
+    /// auto-cpufreq is set up to manage them too.
+    pub fn conflicts(&self, config: &Snapshot, thresholds_enabled: bool) -> Vec<TlpConflict> {
+        let configured = |key: &str| config.get("charger", key).is_some() || config.get("battery", key).is_some();
+
+        self.settings
+            .iter()
+            .filter(|s| !s.value.is_empty())
+            .filter_map(|setting| {
+                let key = setting.key.as_str();
+                let area = if key.starts_with("CPU_SCALING_GOVERNOR_") {
+                    "CPU governor"
+                } else if key.starts_with("CPU_ENERGY_PERF_POLICY_") {
+                    "energy performance preference"
+                } else if key.starts_with("CPU_BOOST_") || key.starts_with("CPU_HWP_DYN_BOOST_") {
+                    "turbo boost"
+                } else if (key.starts_with("CPU_SCALING_MIN_FREQ_") || key.starts_with("CPU_SCALING_MAX_FREQ_"))
+                    && (configured("scaling_min_freq") || configured("scaling_max_freq"))
+                {
+                    "CPU frequency limits"
+                } else if key.starts_with("PLATFORM_PROFILE_") && configured("platform_profile") {
+                    "platform profile"
+                } else if (key.starts_with("START_CHARGE_THRESH_") || key.starts_with("STOP_CHARGE_THRESH_"))
+                    && thresholds_enabled
+                {
+                    "battery charge thresholds"
+                } else {
+                    return None;
+                };
+                Some(TlpConflict { setting: setting.clone(), area })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const TLP_STAT: &str = "\
+--- TLP 1.6.1 --------------------------------------------
+
++++ TLP Status
+State          = enabled
+Mode           = battery
+
++++ Configured Settings:
+defaults.conf L0004: TLP_ENABLE=\"1\"
+defaults.conf L0021: CPU_ENERGY_PERF_POLICY_ON_BAT=\"balance_power\"
+/etc/tlp.conf L0031: START_CHARGE_THRESH_BAT0=\"75\"
+/etc/tlp.conf L0032: STOP_CHARGE_THRESH_BAT0=\"80\"
+/etc/tlp.conf L0040: CPU_SCALING_GOVERNOR_ON_AC=\"performance\"
+/etc/tlp.conf L0050: USB_AUTOSUSPEND=\"1\"
+
++++ Processor
+/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor = powersave
+
++++ USB
+Autosuspend         = enabled
+";
+
     #[test]
     fn test_tlp_parser() {
         let output = "state=enabled\nversion=1.3.1\nmode=battery";
         let parser = TLPStatusParser::new(output);
-        
+
         assert!(parser.is_enabled());
         assert_eq!(parser.get_key("version"), "1.3.1");
         assert_eq!(parser.get_key("mode"), "battery");
@@ -53,7 +179,7 @@ mod tests {
     fn test_tlp_parser_disabled() {
         let output = "state=disabled";
         let parser = TLPStatusParser::new(output);
-        
+
         assert!(!parser.is_enabled());
     }
 
@@ -62,4 +188,31 @@ mod tests {
         let parser = TLPStatusParser::new("");
         assert!(!parser.is_enabled());
     }
+
+    #[test]
+    fn test_tlp_parser_sections_and_conflicts() {
+        let parser = TLPStatusParser::new(TLP_STAT);
+
+        assert!(parser.is_enabled());
+        assert_eq!(parser.section("USB").unwrap(), [("autosuspend".to_string(), "enabled".to_string())]);
+        assert_eq!(parser.section("processor").unwrap().len(), 1);
+        assert_eq!(parser.settings().len(), 6);
+        assert_eq!(
+            parser.settings()[2],
+            TlpSetting { key: "START_CHARGE_THRESH_BAT0".into(), value: "75".into(), source: "/etc/tlp.conf L0031".into() }
+        );
+
+        let config = Snapshot::default();
+        let areas = |enabled| parser.conflicts(&config, enabled).into_iter().map(|c| c.area).collect::<Vec<_>>();
+        assert_eq!(areas(false), vec!["energy performance preference", "CPU governor"]);
+        assert_eq!(
+            areas(true),
+            vec![
+                "energy performance preference",
+                "battery charge thresholds",
+                "battery charge thresholds",
+                "CPU governor"
+            ]
+        );
+    }
 }
@@ -0,0 +1,117 @@
+// src/validate.rs
+//
+// Checks run before writing user supplied values (config keys, profiles, CLI
+// flags) to sysfs, so an obviously wrong value is refused with a clear error
+// instead of being passed to the kernel, which either rejects it with a bare
+// EINVAL or, worse, accepts something unintended. The kernel still has the
+// last word; these only catch what can be known up front.
+
+use std::fmt;
+
+use crate::globals::AVAILABLE_GOVERNORS_SORTED;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidValue {
+    /// Governor the cpufreq driver doesn't offer
+    UnknownGovernor { governor: String, available: Vec<String> },
+    /// Charging threshold above 100%
+    ThresholdRange { value: u8 },
+    /// Charging start threshold not below the stop threshold
+    ThresholdOrder { start: u8, stop: u8 },
+    /// Frequency (kHz) outside cpuinfo_min_freq..cpuinfo_max_freq
+    FrequencyRange { value: u32, min: u32, max: u32 },
+    /// scaling_min_freq above scaling_max_freq (kHz)
+    FrequencyOrder { min: u32, max: u32 },
+}
+
+impl fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownGovernor { governor, available } => {
+                write!(f, "governor '{}' is not available (choices: {})", governor, available.join(", "))
+            }
+            Self::ThresholdRange { value } => write!(f, "charging threshold {} is above 100", value),
+            Self::ThresholdOrder { start, stop } => {
+                write!(f, "charging start threshold {} must be below the stop threshold {}", start, stop)
+            }
+            Self::FrequencyRange { value, min, max } => {
+                write!(f, "frequency {} kHz is outside the hardware range {}-{} kHz", value, min, max)
+            }
+            Self::FrequencyOrder { min, max } => {
+                write!(f, "scaling_min_freq {} kHz is above scaling_max_freq {} kHz", min, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidValue {}
+
+/// `governor` is in `available`, or there is no list to check against
+pub fn governor_in(governor: &str, available: &[String]) -> Result<(), InvalidValue> {
+    if available.is_empty() || available.iter().any(|g| g == governor) {
+        Ok(())
+    } else {
+        Err(InvalidValue::UnknownGovernor { governor: governor.to_string(), available: available.to_vec() })
+    }
+}
+
+/// `governor` is one the cpufreq driver offers
+pub fn governor(governor: &str) -> Result<(), InvalidValue> {
+    governor_in(governor, &AVAILABLE_GOVERNORS_SORTED)
+}
+
+/// Both thresholds are percentages and start < stop
+pub fn thresholds(start: u8, stop: u8) -> Result<(), InvalidValue> {
+    if let Some(value) = [start, stop].into_iter().find(|v| *v > 100) {
+        return Err(InvalidValue::ThresholdRange { value });
+    }
+    if start >= stop {
+        return Err(InvalidValue::ThresholdOrder { start, stop });
+    }
+    Ok(())
+}
+
+/// Frequency limits (kHz) lie within the hardware range `(min, max)`, when
+/// known, and don't cross each other
+pub fn frequency_limits(min: Option<u32>, max: Option<u32>, hardware: Option<(u32, u32)>) -> Result<(), InvalidValue> {
+    if let Some((hw_min, hw_max)) = hardware {
+        if let Some(value) = [min, max].into_iter().flatten().find(|v| *v < hw_min || *v > hw_max) {
+            return Err(InvalidValue::FrequencyRange { value, min: hw_min, max: hw_max });
+        }
+    }
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(InvalidValue::FrequencyOrder { min, max });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_values() {
+        let available = vec!["performance".to_string(), "powersave".to_string()];
+        assert!(governor_in("powersave", &available).is_ok());
+        assert!(matches!(governor_in("schedutil", &available), Err(InvalidValue::UnknownGovernor { .. })));
+        assert!(governor_in("schedutil", &[]).is_ok());
+
+        assert!(thresholds(0, 100).is_ok());
+        assert_eq!(thresholds(80, 80), Err(InvalidValue::ThresholdOrder { start: 80, stop: 80 }));
+        assert_eq!(thresholds(40, 120), Err(InvalidValue::ThresholdRange { value: 120 }));
+
+        let hw = Some((400_000, 4_800_000));
+        assert!(frequency_limits(Some(800_000), Some(3_000_000), hw).is_ok());
+        assert!(frequency_limits(Some(100_000), None, None).is_ok());
+        assert_eq!(
+            frequency_limits(None, Some(5_000_000), hw),
+            Err(InvalidValue::FrequencyRange { value: 5_000_000, min: 400_000, max: 4_800_000 })
+        );
+        assert_eq!(
+            frequency_limits(Some(3_000_000), Some(800_000), hw),
+            Err(InvalidValue::FrequencyOrder { min: 3_000_000, max: 800_000 })
+        );
+    }
+}
@@ -0,0 +1,129 @@
+// src/weak_charger.rs
+//
+// Weak charger detection. A 30 W USB-C charger keeps a laptop alive but can't
+// feed the performance governor with turbo, so the battery drains while
+// "charging". With a `[weak_charger]` section, `set_autofreq` uses its profile
+// keys (turbo off and the balance_power EPP unless set otherwise) while the
+// strongest connected adapter delivers at most `max_watts`, or, with
+// `when_draining`, while a battery discharges although the charger is plugged
+// in. The adapter's power comes from the USB-PD contract or the nominal power
+// of its USB type, see `battery::Adapter`; adapters that report neither only
+// count through the draining check.
+
+use anyhow::{Result, bail};
+use tracing::{info, warn};
+
+use crate::battery::PowerSupplies;
+use crate::config::{CONFIG, Snapshot, parse_bool};
+use crate::event_code::EventCode;
+use crate::profiles::{self, Profile, ProfileSwitch};
+
+const SECTION: &str = "weak_charger";
+const DEFAULT_MAX_WATTS: f32 = 45.0;
+
+/// Keyed by why the charger counts as weak
+static SWITCH: ProfileSwitch<String> = ProfileSwitch::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Adapters delivering at most this many W are weak
+    pub max_watts: f32,
+    /// Also weak while a battery discharges on AC
+    pub when_draining: bool,
+    pub profile: Profile,
+}
+
+/// `[weak_charger]`, `None` when the section isn't in the config
+pub fn settings(config: &Snapshot) -> Result<Option<Settings>> {
+    if config.section(SECTION).is_none() {
+        return Ok(None);
+    }
+    let get = |key: &str| config.get(SECTION, key).map(str::trim);
+
+    let max_watts = match get("max_watts") {
+        None => DEFAULT_MAX_WATTS,
+        Some(value) => match value.parse::<f32>() {
+            Ok(watts) if watts > 0.0 => watts,
+            _ => bail!("Invalid max_watts in [{}]: {} (expected watts)", SECTION, value),
+        },
+    };
+    let when_draining = match get("when_draining") {
+        None => true,
+        Some(value) => match parse_bool(value) {
+            Some(enabled) => enabled,
+            None => bail!("Invalid when_draining in [{}]: {} (expected true or false)", SECTION, value),
+        },
+    };
+
+    let mut profile = Profile {
+        name: SECTION.to_string(),
+        turbo: Some("never".to_string()),
+        energy_performance_preference: Some("balance_power".to_string()),
+        ..Profile::default()
+    };
+    profiles::apply_overrides(config, SECTION, &mut profile)?;
+    Ok(Some(Settings { max_watts, when_draining, profile }))
+}
+
+/// Why the charger counts as weak, `None` when it doesn't or isn't plugged in
+pub fn weak_reason(settings: &Settings, supplies: &PowerSupplies, is_charging: bool) -> Option<String> {
+    if !is_charging {
+        return None;
+    }
+    if let Some(watts) = supplies.adapter_watts().filter(|watts| *watts <= settings.max_watts) {
+        return Some(format!("{:.0} W charger", watts));
+    }
+    (settings.when_draining && supplies.draining_on_ac()).then(|| "battery draining on AC".to_string())
+}
+
+/// The `[weak_charger]` profile and why the charger counts as weak
+fn current(is_charging: bool) -> Option<(String, Profile)> {
+    let settings = match settings(&CONFIG.snapshot()) {
+        Ok(settings) => settings?,
+        Err(e) => {
+            warn!(code = %EventCode::ConfigInvalid, "{}", e);
+            return None;
+        }
+    };
+    let reason = weak_reason(&settings, &PowerSupplies::scan(), is_charging)?;
+    Some((reason, settings.profile))
+}
+
+/// Called by `set_autofreq`: the `[weak_charger]` profile while plugged into
+/// a weak charger. Frequency limits set by it are lifted again afterwards.
+pub fn profile(is_charging: bool) -> Option<Profile> {
+    SWITCH.follow(current(is_charging), |reason| match reason {
+        Some((reason, _)) => info!("Weak charger ({}), using the [weak_charger] settings", reason),
+        None => info!("No weak charger any more, back to the regular settings"),
+    })
+}
+
+pub fn preview(is_charging: bool) -> Option<Profile> {
+    SWITCH.preview(current(is_charging))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battery::Adapter;
+
+    #[test]
+    fn test_weak_charger() {
+        let config = Snapshot::from_str_ini("[weak_charger]\nmax_watts = 40\ngovernor = powersave\n").unwrap();
+        let settings = settings(&config).unwrap().unwrap();
+        assert_eq!(settings.max_watts, 40.0);
+        assert!(settings.when_draining);
+        assert_eq!(settings.profile.governor.as_deref(), Some("powersave"));
+        assert_eq!(settings.profile.turbo.as_deref(), Some("never"));
+
+        let adapter = |watts: Option<f32>| Adapter { name: "usb".to_string(), online: true, usb_type: None, watts };
+        let supplies = |watts| PowerSupplies { ac_online: Some(true), adapters: vec![adapter(watts)], batteries: Vec::new() };
+        assert_eq!(weak_reason(&settings, &supplies(Some(30.0)), true).as_deref(), Some("30 W charger"));
+        assert_eq!(weak_reason(&settings, &supplies(Some(30.0)), false), None);
+        assert_eq!(weak_reason(&settings, &supplies(Some(65.0)), true), None);
+        assert_eq!(weak_reason(&settings, &supplies(None), true), None);
+
+        assert_eq!(super::settings(&Snapshot::default()).unwrap(), None);
+        assert!(super::settings(&Snapshot::from_str_ini("[weak_charger]\nmax_watts = lots\n").unwrap()).is_err());
+    }
+}
@@ -73,6 +73,7 @@
             mkdir -p $out/lib/systemd/system
             substitute scripts/auto-cpufreq.service $out/lib/systemd/system/auto-cpufreq.service \
               --replace "/usr/local/bin/auto-cpufreq" "$out/bin/auto-cpufreq"
+            install -Dm644 scripts/auto-cpufreq.socket $out/lib/systemd/system/auto-cpufreq.socket
             
             # Install images
             mkdir -p $out/share/pixmaps
@@ -129,6 +130,7 @@
             mkdir -p $out/lib/systemd/system
             substitute scripts/auto-cpufreq.service $out/lib/systemd/system/auto-cpufreq.service \
               --replace "/usr/local/bin/auto-cpufreq" "$out/bin/auto-cpufreq"
+            install -Dm644 scripts/auto-cpufreq.socket $out/lib/systemd/system/auto-cpufreq.socket
             
             # Install images
             mkdir -p $out/share/pixmaps
@@ -160,10 +160,11 @@ in {
       };
     };
 
-    # Polkit rule to allow auto-cpufreq to run with elevated privileges
+    # Polkit rule to allow auto-cpufreq to run with elevated privileges,
+    # and the control socket commands (org.auto-cpufreq.set-governor, ...)
     security.polkit.extraConfig = ''
       polkit.addRule(function(action, subject) {
-        if (action.id == "org.auto-cpufreq.pkexec" &&
+        if (action.id.indexOf("org.auto-cpufreq.") == 0 &&
             subject.isInGroup("wheel")) {
           return polkit.Result.YES;
         }